@@ -0,0 +1,72 @@
+//! Times the checksum module against the `to_checksum` string round trips it replaces: the
+//! mask alone, where hashing dominates both, and the per-candidate check of a checksum reward,
+//! where most candidates are ruled out before hashing. Run with `cargo bench --bench checksum`.
+
+use std::{hint::black_box, time::Instant};
+
+use alloy_primitives::Address;
+use createxcrunch::{checksum::checksum_mask, checksum_matches};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+const ADDRESSES: usize = 200_000;
+
+const PATTERN: &str = "DeaD...bEEf";
+
+fn string_mask(address: &[u8; 20], chain_id: Option<u64>) -> u64 {
+    Address::from(*address).to_checksum(chain_id)[2..]
+        .bytes()
+        .enumerate()
+        .filter(|(_, c)| c.is_ascii_uppercase())
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+fn string_matches(address: &[u8; 20], pattern: &str) -> bool {
+    let (prefix, suffix) = pattern.split_once("...").unwrap_or((pattern, ""));
+    let lowercase = hex::encode(address);
+    if !lowercase.starts_with(&prefix.to_ascii_lowercase())
+        || !lowercase.ends_with(&suffix.to_ascii_lowercase())
+    {
+        return false;
+    }
+    let checksummed = Address::from(*address).to_checksum(None);
+    checksummed[2..].starts_with(prefix) && checksummed.ends_with(suffix)
+}
+
+fn time<T>(name: &str, addresses: &[[u8; 20]], f: impl Fn(&[u8; 20]) -> T) -> f64 {
+    let started = Instant::now();
+    for address in addresses {
+        black_box(f(black_box(address)));
+    }
+    let nanos = started.elapsed().as_nanos() as f64 / addresses.len() as f64;
+    println!("  {:<24} {:>8.1} ns/address", name, nanos);
+    nanos
+}
+
+fn speedup(direct: f64, string: f64) {
+    println!("  {:<24} {:>8.2}x", "speedup", string / direct);
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(55);
+    let addresses: Vec<[u8; 20]> = (0..ADDRESSES)
+        .map(|_| {
+            let mut address = [0u8; 20];
+            rng.fill_bytes(&mut address);
+            address
+        })
+        .collect();
+
+    for chain_id in [None, Some(30)] {
+        println!("mask, chain ID {:?}:", chain_id);
+        let direct = time("checksum_mask", &addresses, |a| checksum_mask(a, chain_id));
+        let string = time("to_checksum", &addresses, |a| string_mask(a, chain_id));
+        speedup(direct, string);
+    }
+
+    println!("matching {}:", PATTERN);
+    let direct = time("checksum_matches", &addresses, |a| {
+        checksum_matches(a, PATTERN)
+    });
+    let string = time("to_checksum", &addresses, |a| string_matches(a, PATTERN));
+    speedup(direct, string);
+}
@@ -0,0 +1,184 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::plan::StartupTimings;
+
+/// Outcome of one mining run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunSummary {
+    pub found: u64,
+    pub attempts: u64,
+    pub elapsed: Duration,
+    /// Salt and address of every result.
+    pub results: Vec<([u8; 32], [u8; 20])>,
+    /// How long setting the run up took, by phase.
+    pub startup: StartupTimings,
+}
+
+/// A job of a batch file: the command line of a `create3` or `create2` run, plus the number of
+/// results after which it stops and the next job starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Job {
+    pub name: String,
+    pub args: Vec<String>,
+    pub limit: u64,
+}
+
+/// Parse the `[[job]]` tables of a TOML batch file. Every key but `name`, `command` and the
+/// required `limit` is the long option of the same name, so
+///
+/// ```toml
+/// [[job]]
+/// name = "vault"
+/// leading = 4
+/// output = "vault.txt"
+/// limit = 1
+/// ```
+///
+/// becomes `create3 --leading 4 --output vault.txt`, stopping after one result. `command`
+/// selects `create2` instead of the default `create3`, booleans become flags and arrays repeat
+/// their option.
+pub fn parse_jobs(contents: &str) -> Result<Vec<Job>, String> {
+    let document: DocumentMut = contents
+        .parse()
+        .map_err(|e| format!("invalid batch file: {}", e))?;
+    let tables = match document.get("job") {
+        Some(Item::ArrayOfTables(tables)) => tables,
+        _ => return Err("batch file has no [[job]] tables".to_string()),
+    };
+
+    tables
+        .iter()
+        .enumerate()
+        .map(|(index, table)| {
+            let name = match table.get("name") {
+                Some(item) => item
+                    .as_str()
+                    .ok_or_else(|| format!("job {}: name must be a string", index + 1))?
+                    .to_string(),
+                None => format!("job {}", index + 1),
+            };
+            let command = match table.get("command") {
+                Some(item) => item
+                    .as_str()
+                    .filter(|command| ["create2", "create3"].contains(command))
+                    .ok_or_else(|| format!("{}: command must be create2 or create3", name))?,
+                None => "create3",
+            };
+            // without a limit a job would never hand over to the next one
+            let limit = table
+                .get("limit")
+                .and_then(Item::as_integer)
+                .and_then(|limit| u64::try_from(limit).ok())
+                .filter(|limit| *limit > 0)
+                .ok_or_else(|| format!("{}: limit must be a positive integer", name))?;
+
+            let mut args = vec![command.to_string()];
+            for (key, item) in table.iter() {
+                if ["name", "command", "limit"].contains(&key) {
+                    continue;
+                }
+                let value = item
+                    .as_value()
+                    .ok_or_else(|| format!("{}: {} must be a value", name, key))?;
+                push_option(&mut args, key, value).map_err(|e| format!("{}: {}", name, e))?;
+            }
+
+            Ok(Job { name, args, limit })
+        })
+        .collect()
+}
+
+pub(crate) fn push_option(args: &mut Vec<String>, key: &str, value: &Value) -> Result<(), String> {
+    let option = format!("--{}", key);
+    match value {
+        Value::Boolean(flag) => {
+            if *flag.value() {
+                args.push(option);
+            }
+        }
+        Value::String(s) => args.extend([option, s.value().clone()]),
+        Value::Integer(i) => args.extend([option, i.value().to_string()]),
+        Value::Float(f) => args.extend([option, f.value().to_string()]),
+        Value::Array(values) => {
+            for value in values.iter() {
+                push_option(args, key, value)?;
+            }
+        }
+        _ => return Err(format!("unsupported value for {}", key)),
+    }
+    Ok(())
+}
+
+/// Progress of a batch, saved after every job so an interrupted batch resumes at the job it
+/// did not finish.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchState {
+    /// Index of the first job not run to completion.
+    pub next_job: usize,
+}
+
+impl BatchState {
+    /// Sidecar path of the state of the batch file at `batch`.
+    pub fn path(batch: &str) -> String {
+        format!("{}.state", batch)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_json::json!({ "next_job": self.next_job });
+        fs::write(path, serde_json::to_vec_pretty(&contents)?)
+    }
+
+    /// Read the state at `path`, or the state of a batch that did not start yet.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(format!(
+                    "could not read batch state {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|_| format!("invalid batch state {}", path.display()))?;
+        let next_job = value["next_job"]
+            .as_u64()
+            .ok_or_else(|| format!("invalid batch state {}", path.display()))?;
+        Ok(Self {
+            next_job: next_job as usize,
+        })
+    }
+}
+
+/// Final report of a batch, one line per job that ran.
+pub fn report(outcomes: &[(Job, Result<RunSummary, String>)]) -> String {
+    let mut lines: Vec<String> = outcomes
+        .iter()
+        .map(|(job, outcome)| match outcome {
+            Ok(summary) => format!(
+                "ok     {}: {} results, {} attempts in {}s",
+                job.name,
+                summary.found,
+                summary.attempts,
+                summary.elapsed.as_secs()
+            ),
+            Err(e) => format!("failed {}: {}", job.name, e),
+        })
+        .collect();
+
+    let succeeded = outcomes
+        .iter()
+        .filter(|(_, outcome)| outcome.is_ok())
+        .count();
+    lines.push(format!(
+        "{} of {} jobs succeeded",
+        succeeded,
+        outcomes.len()
+    ));
+    lines.join("\n")
+}
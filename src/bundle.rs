@@ -0,0 +1,169 @@
+use alloy_primitives::{hex, keccak256};
+use flate2::{write::GzEncoder, Compression, GzBuilder};
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use crate::{
+    campaign, checkpoint::Checkpoint, forensics, index, params, pool, scan::ScanOrder,
+    schemas::Artifact, writer, Config, KECCAK256_KERNEL,
+};
+
+/// A single file stored in a run bundle.
+pub struct BundleMember {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+impl BundleMember {
+    pub fn new(name: impl Into<String>, contents: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            contents: contents.into(),
+        }
+    }
+}
+
+/// Version and kernel information of the build that produced a run, and the schema version of
+/// the bundle.
+pub fn build_info() -> BundleMember {
+    let info = serde_json::json!({
+        "schema_version": Artifact::Bundle.current(),
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "kernel_keccak256": format!("0x{}", hex::encode(keccak256(KECCAK256_KERNEL))),
+    });
+
+    BundleMember::new("build.json", serde_json::to_vec_pretty(&info).unwrap())
+}
+
+/// The effective configuration of a run.
+pub fn config_info(config: &Config) -> BundleMember {
+    let info = serde_json::json!({
+        "gpu_device": config.gpu_device,
+        "backend": config.backend.map(|backend| backend.to_string()),
+        "factory_address": match config.redact_params {
+            true => params::REDACTED.to_string(),
+            false => format!("0x{}", hex::encode(config.factory_address)),
+        },
+        "salt_variant": params::salt_variant(config),
+        "create_variant": params::create_variant(config),
+        "reward": config.reward.to_string(),
+        "output": config.output,
+        "seed_entropy": config.seed_entropy,
+        "seed": config.seed.map(|seed| seed.to_string()),
+        "campaign": config.campaign,
+    });
+
+    BundleMember::new("config.json", serde_json::to_vec_pretty(&info).unwrap())
+}
+
+/// Parse a session id, the scan seed in hex as output headers record it.
+pub fn parse_session(s: &str) -> Result<u64, String> {
+    u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16)
+        .map_err(|_| format!("invalid session {}, expected the 0x-prefixed hex of a scan seed", s))
+}
+
+/// The session last recorded for the output file at `output`: the scan seed of its checkpoint,
+/// else of the last scan order line of its header. Resumed runs carry on the scan seed of the
+/// run they resume, so the session stays the same across restarts.
+pub fn recorded_session(output: &Path) -> Option<u64> {
+    let checkpoint = Checkpoint::load(Path::new(&Checkpoint::path(&output.to_string_lossy())));
+    if let Ok(Some(checkpoint)) = checkpoint {
+        return Some(checkpoint.scan_seed);
+    }
+    fs::read_to_string(output)
+        .ok()?
+        .lines()
+        .rev()
+        .find_map(ScanOrder::parse_header)
+        .map(|(_, seed)| seed)
+}
+
+/// Collect every artifact of `session` writing to `output`: the output file itself, the bucket
+/// files of `--split-output-by` next to it and the sidecars the miner writes, except for a
+/// checkpoint of another session. Without a session every sidecar is collected. Missing files
+/// are skipped so a crashed run can still be bundled.
+pub fn session_artifacts(output: &Path, session: Option<u64>) -> io::Result<Vec<BundleMember>> {
+    let file_name = match output.file_name().and_then(|name| name.to_str()) {
+        Some(file_name) => file_name,
+        None => return Ok(vec![]),
+    };
+
+    let dir = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut members = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let member = name == file_name || writer::is_bucket_of(output, name);
+        if member && entry.file_type()?.is_file() {
+            members.push(BundleMember::new(name, fs::read(entry.path())?));
+        }
+    }
+
+    let output = dir.join(file_name).to_string_lossy().into_owned();
+    let checkpoint = Checkpoint::path(&output);
+    let sidecars = [
+        checkpoint.clone(),
+        index::index_path(&output),
+        forensics::dump_path(&output),
+        pool::queue_path(&output),
+        campaign::sessions_path(&output),
+    ];
+    for sidecar in sidecars {
+        let path = Path::new(&sidecar);
+        if !path.is_file() {
+            continue;
+        }
+        if let (true, Some(session), Ok(Some(recorded))) =
+            (sidecar == checkpoint, session, Checkpoint::load(path))
+        {
+            if recorded.scan_seed != session {
+                continue;
+            }
+        }
+        let name = path.file_name().unwrap().to_string_lossy();
+        members.push(BundleMember::new(name, fs::read(path)?));
+    }
+
+    Ok(members)
+}
+
+/// Write `members` to a gzipped tarball at `path`. Members are sorted by name and all
+/// timestamps and ownership fields are zeroed, so identical inputs yield identical archives.
+pub fn write_bundle(path: &Path, mut members: Vec<BundleMember>) -> io::Result<()> {
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    members.dedup_by(|a, b| a.name == b.name);
+
+    let encoder: GzEncoder<File> = GzBuilder::new()
+        .mtime(0)
+        .write(File::create(path)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+    archive.mode(tar::HeaderMode::Deterministic);
+
+    for member in &members {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(member.contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_cksum();
+        archive.append_data(&mut header, &member.name, member.contents.as_slice())?;
+    }
+
+    archive.into_inner()?.finish()?;
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    channel::{self, Backpressure, MatchSender},
+    cycle_stats::CycleStats,
+    events::StopReason,
+    warnings::Warning,
+};
+
+/// Events a subscriber holds before [`Subscription::policy`] applies, unless it asks for another
+/// capacity.
+pub const SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// How often subscribers are ticked while no events come in.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An event published by the mining loop to the subscribers of its [`EventBus`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MinerEvent {
+    /// A cycle was dispatched and its matches collected.
+    CycleCompleted(CycleStats),
+    /// A verified result, in the order results reach the output file.
+    Found(FoundResult),
+    /// Progress of the run, once per second.
+    StatusTick(StatusTick),
+    /// A warning raised for the first time in the run.
+    Warning(Warning),
+    /// Mining stopped, the last event of a run.
+    ShuttingDown(StopReason),
+}
+
+impl MinerEvent {
+    pub fn is_found(&self) -> bool {
+        matches!(self, MinerEvent::Found(_))
+    }
+
+    pub fn is_status_tick(&self) -> bool {
+        matches!(self, MinerEvent::StatusTick(_))
+    }
+}
+
+/// A result of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundResult {
+    pub salt: [u8; 32],
+    pub address: [u8; 20],
+    /// The result rendered as a line of the output file, in the `--format` of the run.
+    pub line: String,
+}
+
+/// Progress of a run, what the status display shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusTick {
+    pub elapsed: Duration,
+    pub cycles: u64,
+    pub attempts: u64,
+    /// Attempts per second.
+    pub rate: f64,
+    pub found: u64,
+    /// Scan index mining continues at.
+    pub nonce: u64,
+    /// The reward mined for in its canonical form, which a `--pattern-file` reload can change.
+    pub reward: String,
+    pub expected_attempts: f64,
+    /// Odds of having found a match by now, see [`crate::stats::explored_probability`].
+    pub explored: f64,
+    /// Results the output writer dropped while it was behind.
+    pub dropped: u64,
+    /// Readback bytes saved by the match bitmap, none without one.
+    pub readback_saved: Option<u64>,
+    pub reserved_rejected: u64,
+}
+
+/// A sink of [`MinerEvent`]s, run on a thread of its own so it never slows the mining loop.
+pub trait Subscriber: Send {
+    /// Handle the next event. Events arrive in the order they were published.
+    fn handle(&mut self, event: &MinerEvent);
+
+    /// Called before waiting for each event, and every [`TICK_INTERVAL`] while none come in,
+    /// with the number of events dropped for this subscriber so far.
+    fn tick(&mut self, _dropped: u64) {}
+
+    /// Called once after the last event.
+    fn finish(self) -> Result<(), String>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&MinerEvent) + Send> Subscriber for F {
+    fn handle(&mut self, event: &MinerEvent) {
+        self(event)
+    }
+}
+
+/// How events reach a subscriber.
+#[derive(Clone, Copy, Debug)]
+pub struct Subscription {
+    pub capacity: usize,
+    /// What the bus does once the subscriber holds `capacity` events. Dropping them by default,
+    /// so a slow subscriber never stalls the miner.
+    pub policy: Backpressure,
+    /// Only the events passing the filter are queued for the subscriber.
+    pub filter: fn(&MinerEvent) -> bool,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self {
+            capacity: SUBSCRIBER_CAPACITY,
+            policy: Backpressure::Drop,
+            filter: |_| true,
+        }
+    }
+}
+
+/// Handle of a subscriber of an [`EventBus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubscriberId(usize);
+
+struct Subscribed {
+    name: String,
+    sender: MatchSender<MinerEvent>,
+    filter: fn(&MinerEvent) -> bool,
+    thread: JoinHandle<Result<(), String>>,
+}
+
+/// Fans the events of the mining loop out to subscribers, each with its own thread and bounded
+/// queue.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscribed>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `subscriber` with the default [`Subscription`].
+    pub fn subscribe(&mut self, name: &str, subscriber: impl Subscriber + 'static) -> SubscriberId {
+        self.subscribe_with(name, subscriber, Subscription::default())
+    }
+
+    /// Attach `subscriber`, receiving the events passing the filter of `subscription`.
+    pub fn subscribe_with(
+        &mut self,
+        name: &str,
+        mut subscriber: impl Subscriber + 'static,
+        subscription: Subscription,
+    ) -> SubscriberId {
+        let (sender, receiver) = channel::match_channel(subscription.capacity, subscription.policy);
+        let thread = thread::spawn(move || {
+            loop {
+                subscriber.tick(receiver.dropped());
+                match receiver.recv_timeout(TICK_INTERVAL) {
+                    Ok(event) => subscriber.handle(&event),
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            subscriber.finish()
+        });
+        self.subscribers.push(Subscribed {
+            name: name.to_string(),
+            sender,
+            filter: subscription.filter,
+            thread,
+        });
+        SubscriberId(self.subscribers.len() - 1)
+    }
+
+    /// Queue `event` for every subscriber whose filter it passes.
+    pub fn publish(&self, event: MinerEvent) {
+        for subscriber in &self.subscribers {
+            if (subscriber.filter)(&event) {
+                subscriber.sender.send(event.clone());
+            }
+        }
+    }
+
+    /// Number of events dropped so far for the subscriber `id` under [`Backpressure::Drop`].
+    pub fn dropped(&self, id: SubscriberId) -> u64 {
+        self.subscribers[id.0].sender.dropped()
+    }
+
+    /// Let every subscriber handle its queued events and finish. Returns the first error of a
+    /// subscriber, in the order they were attached.
+    pub fn shutdown(self) -> Result<(), String> {
+        let mut result = Ok(());
+        for subscriber in self.subscribers {
+            drop(subscriber.sender);
+            let finished = subscriber
+                .thread
+                .join()
+                .unwrap_or_else(|_| Err(format!("{} panicked", subscriber.name)));
+            if result.is_ok() {
+                result = finished;
+            }
+        }
+        result
+    }
+}
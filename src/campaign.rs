@@ -0,0 +1,457 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+use alloy_primitives::{hex, Address};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    cycle_stats::StatsFormat,
+    schemas::{self, Artifact},
+    score, Config, PatternBytes, RewardVariant,
+};
+
+/// Best addresses listed by a campaign report.
+pub const BEST_RESULTS: usize = 10;
+
+/// Sidecar path of the session log of the runs writing to `output` for a campaign. Being a
+/// sidecar of the output file, it goes into the bundles of the run too.
+pub fn sessions_path(output: &str) -> String {
+    format!("{}.sessions.jsonl", output)
+}
+
+/// Check a `--campaign` name: letters, digits, `.`, `_` and `-`, at most 64 of them, so it can
+/// be matched across machines and shows the same in every artifact.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || ['.', '_', '-'].contains(&c);
+    if name.is_empty() || name.len() > 64 || !name.chars().all(valid) {
+        return Err(format!(
+            "invalid campaign name {}, expected up to 64 letters, digits, '.', '_' or '-'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// A result of a session, without its salt when the run kept salts out of its artifacts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    /// The checksummed address.
+    pub address: String,
+}
+
+/// One run of a campaign, a line of its [`sessions_path`] log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// See [`Artifact::Sessions`], 0 in logs of builds before it.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub campaign: String,
+    /// File name of the output file the run wrote to.
+    pub output: String,
+    /// The reward mined for, in its canonical form.
+    pub reward: String,
+    pub started_unix: u64,
+    pub elapsed_secs: f64,
+    pub attempts: u64,
+    /// Running cost of the session, when the run had cost options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub results: Vec<SessionResult>,
+}
+
+impl Session {
+    /// The session of the run of `config` started at `started_unix`, which made `attempts` over
+    /// `elapsed` and found `results`.
+    pub fn new(
+        config: &Config,
+        campaign: &str,
+        started_unix: u64,
+        attempts: u64,
+        elapsed: Duration,
+        results: &[([u8; 32], [u8; 20])],
+    ) -> Self {
+        let output = Path::new(config.output)
+            .file_name()
+            .map_or(config.output.into(), |name| name.to_string_lossy());
+        Self {
+            schema_version: Artifact::Sessions.current(),
+            campaign: campaign.to_string(),
+            output: output.to_string(),
+            reward: config.reward.to_string(),
+            started_unix,
+            elapsed_secs: elapsed.as_secs_f64(),
+            attempts,
+            cost: config.cost.map(|cost| cost.cost(elapsed)),
+            results: results
+                .iter()
+                .map(|(salt, address)| SessionResult {
+                    salt: (!config.redact_salts).then(|| format!("0x{}", hex::encode(salt))),
+                    address: Address::from(*address).to_checksum(None),
+                })
+                .collect(),
+        }
+    }
+
+    /// Append the session to the log at `path`.
+    pub fn append(&self, path: &Path) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)
+    }
+
+    /// The reward the result at `address` was found for, in its canonical form: for several
+    /// patterns the first one it matches.
+    fn pattern_of(&self, address: &[u8; 20]) -> String {
+        if let Ok(RewardVariant::MatchingAny { patterns }) = self.reward.parse() {
+            let matched = patterns.into_iter().find(|pattern| {
+                PatternBytes::parse(pattern).is_some_and(|bytes| bytes.matches(address))
+            });
+            if let Some(pattern) = matched {
+                return RewardVariant::Matching { pattern }.to_string();
+            }
+        }
+        self.reward.clone()
+    }
+}
+
+/// A session found in a directory, with the artifact it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundSession {
+    /// Path of the session log, `bundle.tar.gz:name` for logs inside bundles.
+    pub source: String,
+    pub session: Session,
+}
+
+/// The sessions of a campaign found in a directory, and warnings about lines that were skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Discovery {
+    pub sessions: Vec<FoundSession>,
+    pub warnings: Vec<String>,
+}
+
+impl Discovery {
+    /// Read the session logs of `campaign` under `dir` and its subdirectories, loose or inside
+    /// run bundles. A session found both loose and in a bundle counts once.
+    pub fn scan(dir: &Path, campaign: &str) -> Result<Self, String> {
+        let mut discovery = Self::default();
+        discovery.scan_dir(dir, campaign)?;
+        discovery
+            .sessions
+            .sort_by_key(|found| found.session.started_unix);
+        let mut seen: Vec<Session> = vec![];
+        discovery.sessions.retain(|found| {
+            let new = !seen.contains(&found.session);
+            seen.push(found.session.clone());
+            new
+        });
+        Ok(discovery)
+    }
+
+    fn scan_dir(&mut self, dir: &Path, campaign: &str) -> Result<(), String> {
+        let error = |e: io::Error| format!("could not read {}: {}", dir.display(), e);
+        let mut entries = fs::read_dir(dir)
+            .map_err(error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type().map_err(error)?.is_dir() {
+                self.scan_dir(&path, campaign)?;
+            } else if name.ends_with(".sessions.jsonl") {
+                let contents = fs::read_to_string(&path)
+                    .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+                self.read_log(&path.display().to_string(), &contents, campaign);
+            } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+                self.read_bundle(&path, campaign)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_bundle(&mut self, path: &Path, campaign: &str) -> Result<(), String> {
+        let error = |e: io::Error| format!("could not read bundle {}: {}", path.display(), e);
+        let file = fs::File::open(path).map_err(error)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        for entry in archive.entries().map_err(error)? {
+            let mut entry = entry.map_err(error)?;
+            let name = entry.path().map_err(error)?.to_string_lossy().into_owned();
+            if name == "build.json" {
+                // bundles of builds before versioning have no version in their build.json
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).map_err(error)?;
+                let build: Value = serde_json::from_str(&contents)
+                    .map_err(|e| format!("invalid build.json in {}: {}", path.display(), e))?;
+                Artifact::Bundle
+                    .version_of(&build)
+                    .and_then(|version| Artifact::Bundle.check(version))
+                    .map_err(|e| format!("could not read bundle {}: {}", path.display(), e))?;
+            } else if name.ends_with(".sessions.jsonl") {
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).map_err(error)?;
+                self.read_log(&format!("{}:{}", path.display(), name), &contents, campaign);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_log(&mut self, source: &str, contents: &str, campaign: &str) {
+        for (number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let session = serde_json::from_str(line)
+                .map_err(|e| e.to_string())
+                .and_then(|value| schemas::upgrade(Artifact::Sessions, value))
+                .and_then(|value| serde_json::from_value::<Session>(value).map_err(|e| e.to_string()));
+            match session {
+                Ok(session) if session.campaign == campaign => self.sessions.push(FoundSession {
+                    source: source.to_string(),
+                    session,
+                }),
+                Ok(_) => {}
+                Err(e) => self.warnings.push(format!(
+                    "{} line {}: skipped invalid session: {}",
+                    source,
+                    number + 1,
+                    e
+                )),
+            }
+        }
+    }
+}
+
+/// A result of the campaign with what it ranks by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RankedResult {
+    pub address: String,
+    pub salt: Option<String>,
+    pub pattern: String,
+    pub score: u32,
+}
+
+/// Rollup of the sessions of a campaign.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub campaign: String,
+    /// The sessions, oldest first.
+    pub sessions: Vec<FoundSession>,
+    pub attempts: u64,
+    pub elapsed_secs: f64,
+    /// Total cost of the sessions that recorded one, none when none did.
+    pub cost: Option<f64>,
+    /// Sessions without a recorded cost, left out of `cost`.
+    pub uncosted_sessions: usize,
+    /// Results by pattern, then by tier, the leading zero nibbles of the address.
+    pub results: BTreeMap<String, BTreeMap<u32, u64>>,
+    /// The best [`BEST_RESULTS`] distinct addresses, best first.
+    pub best: Vec<RankedResult>,
+}
+
+impl Report {
+    pub fn of(campaign: &str, sessions: Vec<FoundSession>) -> Self {
+        let costs: Vec<f64> = sessions
+            .iter()
+            .filter_map(|found| found.session.cost)
+            .collect();
+
+        let mut results: BTreeMap<String, BTreeMap<u32, u64>> = BTreeMap::new();
+        let mut ranked: Vec<RankedResult> = vec![];
+        for found in &sessions {
+            for result in &found.session.results {
+                let Some(address) = hex::decode(&result.address)
+                    .ok()
+                    .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+                else {
+                    continue;
+                };
+                let pattern = found.session.pattern_of(&address);
+                let score = score(&address);
+                *results
+                    .entry(pattern.clone())
+                    .or_default()
+                    .entry(score / 64)
+                    .or_default() += 1;
+                ranked.push(RankedResult {
+                    address: result.address.clone(),
+                    salt: result.salt.clone(),
+                    pattern,
+                    score,
+                });
+            }
+        }
+        ranked.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.address.cmp(&b.address)));
+        ranked.dedup_by(|a, b| a.address == b.address);
+        ranked.truncate(BEST_RESULTS);
+
+        Self {
+            campaign: campaign.to_string(),
+            attempts: sessions.iter().map(|found| found.session.attempts).sum(),
+            elapsed_secs: sessions
+                .iter()
+                .map(|found| found.session.elapsed_secs)
+                .sum(),
+            cost: (!costs.is_empty()).then(|| costs.iter().sum()),
+            uncosted_sessions: sessions.len() - costs.len(),
+            results,
+            best: ranked,
+            sessions,
+        }
+    }
+
+    pub fn found(&self) -> u64 {
+        self.results.values().flat_map(BTreeMap::values).sum()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let sessions: Vec<Value> = self
+            .sessions
+            .iter()
+            .map(|found| {
+                json!({
+                    "source": found.source,
+                    "output": found.session.output,
+                    "reward": found.session.reward,
+                    "started_unix": found.session.started_unix,
+                    "elapsed_secs": found.session.elapsed_secs,
+                    "attempts": found.session.attempts,
+                    "found": found.session.results.len(),
+                    "cost": found.session.cost,
+                })
+            })
+            .collect();
+        let results: Vec<Value> = self
+            .results
+            .iter()
+            .flat_map(|(pattern, tiers)| {
+                tiers.iter().map(move |(tier, count)| {
+                    json!({ "pattern": pattern, "tier": tier, "results": count })
+                })
+            })
+            .collect();
+        let best: Vec<Value> = self
+            .best
+            .iter()
+            .map(|result| {
+                json!({
+                    "address": result.address,
+                    "salt": result.salt,
+                    "pattern": result.pattern,
+                    "score": result.score,
+                })
+            })
+            .collect();
+        json!({
+            "campaign": self.campaign,
+            "sessions": sessions,
+            "attempts": self.attempts,
+            "elapsed_secs": self.elapsed_secs,
+            "found": self.found(),
+            "cost": self.cost,
+            "uncosted_sessions": self.uncosted_sessions,
+            "results": results,
+            "best": best,
+        })
+    }
+
+    /// Render the report as text, JSON or markdown.
+    pub fn render(&self, format: StatsFormat) -> String {
+        if format == StatsFormat::Json {
+            return format!("{:#}", self.to_json());
+        }
+        let markdown = format == StatsFormat::Markdown;
+
+        let mut rendered = String::new();
+        let heading = |rendered: &mut String, title: &str| match markdown {
+            true => writeln!(rendered, "\n## {}\n", title).unwrap(),
+            false => writeln!(rendered, "\n{}:", title).unwrap(),
+        };
+        let item = match markdown {
+            true => "- ",
+            false => "  ",
+        };
+
+        match markdown {
+            true => writeln!(rendered, "# Campaign {}\n", self.campaign).unwrap(),
+            false => writeln!(rendered, "Campaign {}", self.campaign).unwrap(),
+        }
+        writeln!(rendered, "{}sessions: {}", item, self.sessions.len()).unwrap();
+        writeln!(rendered, "{}attempts: {}", item, self.attempts).unwrap();
+        writeln!(rendered, "{}mining time: {}", item, hours(self.elapsed_secs)).unwrap();
+        writeln!(rendered, "{}results: {}", item, self.found()).unwrap();
+        match (self.cost, self.uncosted_sessions) {
+            (Some(cost), 0) => writeln!(rendered, "{}cost: {:.2}", item, cost).unwrap(),
+            (Some(cost), uncosted) => writeln!(
+                rendered,
+                "{}cost: {:.2}, {} sessions without cost options left out",
+                item, cost, uncosted
+            )
+            .unwrap(),
+            (None, _) => writeln!(rendered, "{}cost: not recorded", item).unwrap(),
+        }
+
+        heading(&mut rendered, "Results by pattern and tier");
+        for (pattern, tiers) in &self.results {
+            let tiers: Vec<String> = tiers
+                .iter()
+                .map(|(tier, count)| format!("tier {}: {}", tier, count))
+                .collect();
+            writeln!(rendered, "{}{}: {}", item, pattern, tiers.join(", ")).unwrap();
+        }
+
+        heading(&mut rendered, "Best addresses");
+        for result in &self.best {
+            writeln!(
+                rendered,
+                "{}{} {} (tier {})",
+                item,
+                result.address,
+                result.pattern,
+                result.score / 64
+            )
+            .unwrap();
+        }
+
+        heading(&mut rendered, "Sessions");
+        for found in &self.sessions {
+            let session = &found.session;
+            writeln!(
+                rendered,
+                "{}{} for {}: {} attempts, {} results, {} in {} ({})",
+                item,
+                timestamp(session.started_unix),
+                hours(session.elapsed_secs),
+                session.attempts,
+                session.results.len(),
+                session.reward,
+                session.output,
+                found.source
+            )
+            .unwrap();
+        }
+        rendered.trim_end().to_string()
+    }
+}
+
+/// `unix` as a UTC date and time.
+fn timestamp(unix: u64) -> String {
+    match chrono::DateTime::from_timestamp(unix as i64, 0) {
+        Some(time) => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+        None => unix.to_string(),
+    }
+}
+
+fn hours(secs: f64) -> String {
+    format!("{:.2}h", secs / 3600.0)
+}
@@ -0,0 +1,208 @@
+use std::{fmt, str::FromStr};
+
+use alloy_primitives::{hex, U256};
+
+use crate::{CreateVariant, RewardVariant, SaltVariant};
+
+/// Split `name(args)` into its name and arguments.
+fn call(s: &str) -> Option<(&str, &str)> {
+    let (name, rest) = s.split_once('(')?;
+    Some((name, rest.strip_suffix(')')?))
+}
+
+fn parse_u8(s: &str, what: &str) -> Result<u8, String> {
+    s.parse().map_err(|_| format!("invalid {} {}", what, s))
+}
+
+/// Two comma separated thresholds.
+fn parse_pair(s: &str) -> Result<(u8, u8), String> {
+    let (leading, total) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected two thresholds, found {}", s))?;
+    Ok((
+        parse_u8(leading, "threshold")?,
+        parse_u8(total, "threshold")?,
+    ))
+}
+
+fn parse_hex<const N: usize>(s: &str, what: &str) -> Result<[u8; N], String> {
+    let bytes = s
+        .strip_prefix("0x")
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or_else(|| format!("invalid {} {}", what, s))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("{} {} is not {} bytes long", what, s, N))
+}
+
+impl fmt::Display for RewardVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewardVariant::LeadingZeros { zeros_threshold } => {
+                write!(f, "leading-zeros({})", zeros_threshold)
+            }
+            RewardVariant::LeadingZeroNibbles { threshold } => {
+                write!(f, "leading-zero-nibbles({})", threshold)
+            }
+            RewardVariant::TotalZeros { zeros_threshold } => {
+                write!(f, "total-zeros({})", zeros_threshold)
+            }
+            RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => write!(
+                f,
+                "leading-and-total-zeros({},{})",
+                leading_zeros_threshold, total_zeros_threshold
+            ),
+            RewardVariant::LeadingOrTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => write!(
+                f,
+                "leading-or-total-zeros({},{})",
+                leading_zeros_threshold, total_zeros_threshold
+            ),
+            RewardVariant::Matching { pattern } => write!(f, "pattern({})", pattern),
+            RewardVariant::MatchingAny { patterns } => {
+                write!(f, "any-pattern({})", patterns.join(","))
+            }
+            RewardVariant::ChecksumMatching { pattern } => {
+                write!(f, "checksum-pattern({})", pattern)
+            }
+            RewardVariant::SymmetricEnds { nibbles } => write!(f, "symmetric-ends({})", nibbles),
+        }
+    }
+}
+
+impl FromStr for RewardVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, args) = call(s).ok_or_else(|| format!("invalid reward {}", s))?;
+        match name {
+            "leading-zeros" => Ok(RewardVariant::LeadingZeros {
+                zeros_threshold: parse_u8(args, "threshold")?,
+            }),
+            "leading-zero-nibbles" => Ok(RewardVariant::LeadingZeroNibbles {
+                threshold: parse_u8(args, "threshold")?,
+            }),
+            "total-zeros" => Ok(RewardVariant::TotalZeros {
+                zeros_threshold: parse_u8(args, "threshold")?,
+            }),
+            "leading-and-total-zeros" => {
+                let (leading, total) = parse_pair(args)?;
+                Ok(RewardVariant::LeadingAndTotalZeros {
+                    leading_zeros_threshold: leading,
+                    total_zeros_threshold: total,
+                })
+            }
+            "leading-or-total-zeros" => {
+                let (leading, total) = parse_pair(args)?;
+                Ok(RewardVariant::LeadingOrTotalZeros {
+                    leading_zeros_threshold: leading,
+                    total_zeros_threshold: total,
+                })
+            }
+            "pattern" if !args.contains(['(', ')']) => Ok(RewardVariant::Matching {
+                pattern: args.into(),
+            }),
+            "any-pattern" if !args.contains(['(', ')']) => {
+                let patterns: Vec<Box<str>> = args.split(',').map(Into::into).collect();
+                match patterns.iter().any(|pattern| pattern.is_empty()) {
+                    true => Err(format!("invalid reward {}", s)),
+                    false => Ok(RewardVariant::MatchingAny { patterns }),
+                }
+            }
+            "checksum-pattern" if !args.contains(['(', ')']) => {
+                Ok(RewardVariant::ChecksumMatching {
+                    pattern: args.into(),
+                })
+            }
+            "symmetric-ends" => Ok(RewardVariant::SymmetricEnds {
+                nibbles: parse_u8(args, "nibble count")?,
+            }),
+            _ => Err(format!("invalid reward {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SaltVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chain = |chain_id: &[u8; 32]| format!("chain({})", U256::from_be_bytes(*chain_id));
+        let sender = |address: &[u8; 20]| format!("sender(0x{})", hex::encode(address));
+        match self {
+            SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            } => write!(f, "{}+{}", sender(calling_address), chain(chain_id)),
+            SaltVariant::Crosschain { chain_id } => write!(f, "{}", chain(chain_id)),
+            SaltVariant::Sender { calling_address } => write!(f, "{}", sender(calling_address)),
+            SaltVariant::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl FromStr for SaltVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "random" {
+            return Ok(SaltVariant::Random);
+        }
+
+        let mut chain_id = None;
+        let mut calling_address = None;
+        for (index, part) in s.split('+').enumerate() {
+            match call(part) {
+                // the sender always comes first
+                Some(("sender", args)) if index == 0 => {
+                    calling_address = Some(parse_hex(args, "sender address")?)
+                }
+                Some(("chain", args)) if chain_id.is_none() => {
+                    let chain = U256::from_str_radix(args, 10)
+                        .map_err(|_| format!("invalid chain id {}", args))?;
+                    chain_id = Some(chain.to_be_bytes::<32>());
+                }
+                _ => return Err(format!("invalid salt variant {}", s)),
+            }
+        }
+
+        match (calling_address, chain_id) {
+            (Some(calling_address), Some(chain_id)) => Ok(SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            }),
+            (Some(calling_address), None) => Ok(SaltVariant::Sender { calling_address }),
+            (None, Some(chain_id)) => Ok(SaltVariant::Crosschain { chain_id }),
+            (None, None) => Err(format!("invalid salt variant {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for CreateVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CreateVariant::Create2 { init_code_hash } => {
+                write!(f, "create2(0x{})", hex::encode(init_code_hash))
+            }
+            CreateVariant::Create3 => write!(f, "create3"),
+        }
+    }
+}
+
+impl FromStr for CreateVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "create3" {
+            return Ok(CreateVariant::Create3);
+        }
+        match call(s) {
+            Some(("create2", args)) => Ok(CreateVariant::Create2 {
+                init_code_hash: parse_hex(args, "init code hash")?,
+            }),
+            _ => Err(format!("invalid create variant {}", s)),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// What the miner does when the consumer of its matches falls behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Wait for the consumer to make room, stalling the miner.
+    #[default]
+    Block,
+    /// Discard the oldest pending match and count it as dropped.
+    Drop,
+}
+
+impl fmt::Display for Backpressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backpressure::Block => write!(f, "block"),
+            Backpressure::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+impl FromStr for Backpressure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(Backpressure::Block),
+            "drop" => Ok(Backpressure::Drop),
+            _ => Err(format!(
+                "unknown backpressure policy {} (expected block or drop)",
+                s
+            )),
+        }
+    }
+}
+
+/// Sending half of a bounded match channel.
+pub struct MatchSender<T> {
+    sender: SyncSender<T>,
+    /// Handle on the queue to discard the oldest match from, under [`Backpressure::Drop`] only:
+    /// a blocking sender must not keep the receiver alive, or it would wait on a queue nobody
+    /// drains once the consumer is gone.
+    oldest: Option<Arc<Mutex<Receiver<T>>>>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Receiving half of a bounded match channel.
+pub struct MatchReceiver<T> {
+    receiver: Arc<Mutex<Receiver<T>>>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Create a channel holding at most `capacity` pending matches, applying `policy` once full.
+pub fn match_channel<T>(
+    capacity: usize,
+    policy: Backpressure,
+) -> (MatchSender<T>, MatchReceiver<T>) {
+    let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+    let receiver = Arc::new(Mutex::new(receiver));
+    let dropped = Arc::new(AtomicU64::new(0));
+
+    (
+        MatchSender {
+            sender,
+            oldest: (policy == Backpressure::Drop).then(|| receiver.clone()),
+            dropped: dropped.clone(),
+        },
+        MatchReceiver { receiver, dropped },
+    )
+}
+
+impl<T> MatchSender<T> {
+    /// Queue a match. Returns `false` once the receiver is gone.
+    pub fn send(&self, mut item: T) -> bool {
+        let Some(oldest) = &self.oldest else {
+            // Backpressure::Block waits for room, or for the receiver to disconnect
+            return self.sender.send(item).is_ok();
+        };
+        loop {
+            match self.sender.try_send(item) {
+                Ok(()) => return true,
+                Err(TrySendError::Disconnected(_)) => return false,
+                Err(TrySendError::Full(rejected)) => {
+                    item = rejected;
+                    // the sender shares the receiver, so it only disconnects once both are gone
+                    if Arc::strong_count(oldest) == 1 {
+                        return false;
+                    }
+                    // the consumer may have drained the queue meanwhile, in which case nothing
+                    // is dropped and the send is retried
+                    if let Ok(Ok(_)) = oldest.lock().map(|r| r.try_recv()) {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of matches discarded so far under [`Backpressure::Drop`].
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> MatchReceiver<T> {
+    /// Wait for the next match. Returns `None` once every sender is gone and the queue is empty.
+    pub fn recv(&self) -> Option<T> {
+        // poll with a timeout so a dropping sender can take the lock between waits
+        loop {
+            let result = self
+                .receiver
+                .lock()
+                .unwrap()
+                .recv_timeout(Duration::from_millis(10));
+            match result {
+                Ok(item) => return Some(item),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    /// Wait at most `timeout` for the next match.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let wait = deadline
+                .saturating_duration_since(std::time::Instant::now())
+                .min(Duration::from_millis(10));
+            match self.receiver.lock().unwrap().recv_timeout(wait) {
+                Err(RecvTimeoutError::Timeout) if std::time::Instant::now() < deadline => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// Take the next match if one is pending.
+    pub fn try_recv(&self) -> Option<T> {
+        match self.receiver.lock().unwrap().try_recv() {
+            Ok(item) => Some(item),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Number of matches discarded so far under [`Backpressure::Drop`].
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Iterator for MatchReceiver<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
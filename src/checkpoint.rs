@@ -0,0 +1,180 @@
+use std::{
+    fs, io,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    scan::ScanOrder,
+    schemas::{self, Artifact},
+};
+
+/// Progress of a run, saved periodically so a restarted run continues where it stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// First scan index not yet mined.
+    pub next_index: u64,
+    pub scan_order: ScanOrder,
+    pub scan_seed: u64,
+    /// Attempts made and results found by the run and the runs it resumed, 0 in checkpoints of
+    /// schema version 0.
+    pub attempts: u64,
+    pub found: u64,
+    /// [`Config::fingerprint`](crate::Config::fingerprint) of the run, absent from some
+    /// checkpoints of schema version 0.
+    pub fingerprint: Option<String>,
+    /// Campaign of the run, see [`crate::campaign`].
+    pub campaign: Option<String>,
+}
+
+impl Checkpoint {
+    /// Sidecar path of the checkpoint of the run writing to `output`.
+    pub fn path(output: &str) -> String {
+        format!("{}.checkpoint", output)
+    }
+
+    /// Write the checkpoint atomically, so a crash mid-write leaves the previous one intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_json::json!({
+            "schema_version": Artifact::Checkpoint.current(),
+            "next_index": self.next_index,
+            "scan_order": self.scan_order.to_string(),
+            "scan_seed": self.scan_seed,
+            "attempts": self.attempts,
+            "found": self.found,
+            "fingerprint": self.fingerprint,
+            "campaign": self.campaign,
+        });
+
+        let tmp = path.with_extension("checkpoint.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&contents)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Read the checkpoint at `path`, if there is one, migrating checkpoints of older schema
+    /// versions.
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(format!(
+                    "could not read checkpoint {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+
+        let invalid = || format!("invalid checkpoint {}", path.display());
+        let value: serde_json::Value = serde_json::from_str(&contents).map_err(|_| invalid())?;
+        let value = schemas::upgrade(Artifact::Checkpoint, value)
+            .map_err(|e| format!("could not read checkpoint {}: {}", path.display(), e))?;
+
+        Ok(Some(Checkpoint {
+            next_index: value["next_index"].as_u64().ok_or_else(invalid)?,
+            scan_order: value["scan_order"]
+                .as_str()
+                .ok_or_else(invalid)?
+                .parse()
+                .map_err(|_| invalid())?,
+            scan_seed: value["scan_seed"].as_u64().ok_or_else(invalid)?,
+            attempts: value["attempts"].as_u64().ok_or_else(invalid)?,
+            found: value["found"].as_u64().ok_or_else(invalid)?,
+            fingerprint: value["fingerprint"].as_str().map(str::to_string),
+            campaign: value["campaign"].as_str().map(str::to_string),
+        }))
+    }
+}
+
+/// When the miner takes a checkpoint: every `interval`, and with `--checkpoint-every-attempts`
+/// and `--checkpoint-every-cycles` also every `every_attempts` attempts and every `every_cycles`
+/// cycles, whichever comes first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointSchedule {
+    interval: Duration,
+    every_attempts: Option<u64>,
+    every_cycles: Option<u64>,
+    last_time: Instant,
+    last_attempts: u64,
+    cycles: u64,
+}
+
+impl CheckpointSchedule {
+    pub fn new(interval: Duration, every_attempts: Option<u64>, now: Instant) -> Self {
+        Self {
+            interval,
+            every_attempts: every_attempts.map(|attempts| attempts.max(1)),
+            every_cycles: None,
+            last_time: now,
+            last_attempts: 0,
+            cycles: 0,
+        }
+    }
+
+    /// Also take a checkpoint every `cycles` cycles, see [`CheckpointSchedule::cycle_done`].
+    pub fn every_cycles(mut self, cycles: Option<u64>) -> Self {
+        self.every_cycles = cycles.map(|cycles| cycles.max(1));
+        self
+    }
+
+    /// Count a cycle mined since the last checkpoint.
+    pub fn cycle_done(&mut self) {
+        self.cycles += 1;
+    }
+
+    /// Size of the next batch, at most `max`, cut so it ends where the next checkpoint is due
+    /// by attempts. A batch never runs past that point, so a restarted run repeats at most
+    /// `every_attempts` attempts.
+    pub fn batch_limit(&self, attempts: u64, max: u64) -> u64 {
+        match self.every_attempts {
+            Some(every) => {
+                let since = attempts - self.last_attempts;
+                max.min(every.saturating_sub(since)).max(1)
+            }
+            None => max,
+        }
+    }
+
+    pub fn is_due(&self, attempts: u64, now: Instant) -> bool {
+        now.duration_since(self.last_time) >= self.interval
+            || self
+                .every_attempts
+                .is_some_and(|every| attempts - self.last_attempts >= every)
+            || self.every_cycles.is_some_and(|every| self.cycles >= every)
+    }
+
+    /// Record a checkpoint taken after `attempts` attempts.
+    pub fn taken(&mut self, attempts: u64, now: Instant) {
+        self.last_time = now;
+        self.last_attempts = attempts;
+        self.cycles = 0;
+    }
+}
+
+/// The latest checkpoint of the miner, waiting for the output writer. A checkpoint is only
+/// saved once every result found before it was handled, so a restarted run never skips
+/// results that had not reached the output file. Newer checkpoints replace older ones.
+#[derive(Clone, Debug, Default)]
+pub struct PendingCheckpoint {
+    slot: Arc<Mutex<Option<(Checkpoint, u64)>>>,
+}
+
+impl PendingCheckpoint {
+    /// Hand over `checkpoint`, taken once `results` results were sent to the writer.
+    pub fn offer(&self, checkpoint: Checkpoint, results: u64) {
+        *self.slot.lock().unwrap() = Some((checkpoint, results));
+    }
+
+    /// The pending checkpoint, if the writer `handled` every result found before it.
+    pub fn take_ready(&self, handled: u64) -> Option<Checkpoint> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.as_ref() {
+            Some((_, results)) if *results <= handled => {
+                slot.take().map(|(checkpoint, _)| checkpoint)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+use alloy_primitives::keccak256;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Bitmask of the nibbles of `address` its checksummed form writes in uppercase, bit `i` for
+/// the `i`th hex digit from the start. EIP-55 without `chain_id`, EIP-1191 with it. Digits have
+/// no case, so only the bits of letters are ever set.
+///
+/// Same as reading the case off [`Address::to_checksum`](alloy_primitives::Address::to_checksum),
+/// without allocating the strings, for the per-candidate checks of the checksum rewards.
+pub fn checksum_mask(address: &[u8; 20], chain_id: Option<u64>) -> u64 {
+    // EIP-1191 hashes the decimal chain ID and 0x in front of the lowercase hex
+    let mut preimage = [0u8; 20 + 2 + 40];
+    let mut len = 0;
+    if let Some(chain_id) = chain_id {
+        let mut digits = [0u8; 20];
+        let mut count = 0;
+        let mut rest = chain_id;
+        loop {
+            digits[count] = b'0' + (rest % 10) as u8;
+            count += 1;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        for digit in digits[..count].iter().rev() {
+            preimage[len] = *digit;
+            len += 1;
+        }
+        preimage[len..len + 2].copy_from_slice(b"0x");
+        len += 2;
+    }
+    for byte in address {
+        preimage[len] = HEX_DIGITS[(byte >> 4) as usize];
+        preimage[len + 1] = HEX_DIGITS[(byte & 0x0f) as usize];
+        len += 2;
+    }
+    let hash = keccak256(&preimage[..len]);
+
+    let mut mask = 0u64;
+    for i in 0..40 {
+        let hash_nibble = nibble(&hash.0, i);
+        if nibble(address, i) >= 10 && hash_nibble >= 8 {
+            mask |= 1 << i;
+        }
+    }
+    mask
+}
+
+/// Number of letters, hex digits `a` to `f`, among the nibbles `span` of `address`.
+pub fn letters(address: &[u8; 20], span: Range<usize>) -> u32 {
+    span.filter(|&i| nibble(address, i) >= 10).count() as u32
+}
+
+/// Number of letters among the nibbles `span` of `address` that its checksum `mask`, see
+/// [`checksum_mask`], writes in uppercase.
+pub fn uppercase_letters(mask: u64, span: Range<usize>) -> u32 {
+    let span = span.start.min(64)..span.end.min(64);
+    let bits = match span.len() {
+        0 => 0,
+        64 => u64::MAX,
+        len => ((1u64 << len) - 1) << span.start,
+    };
+    (mask & bits).count_ones()
+}
+
+/// Whether `address` checksummed with `mask` spells `hex` at nibble `start`, letters with their
+/// case.
+pub fn spells(address: &[u8; 20], mask: u64, start: usize, hex: &str) -> bool {
+    hex.bytes().enumerate().all(|(offset, expected)| {
+        let i = start + offset;
+        if i >= 40 {
+            return false;
+        }
+        let digit = HEX_DIGITS[nibble(address, i) as usize];
+        match mask & (1 << i) != 0 {
+            true => expected == digit.to_ascii_uppercase(),
+            false => expected == digit,
+        }
+    })
+}
+
+/// Whether `address` spells `hex` at nibble `start` whatever the case of its letters, which
+/// rules most addresses out before hashing them for [`checksum_mask`].
+pub fn spells_ignoring_case(address: &[u8; 20], start: usize, hex: &str) -> bool {
+    hex.bytes().enumerate().all(|(offset, expected)| {
+        let i = start + offset;
+        i < 40 && expected.to_ascii_lowercase() == HEX_DIGITS[nibble(address, i) as usize]
+    })
+}
+
+/// The `i`th nibble of `bytes`, high nibble first.
+fn nibble(bytes: &[u8], i: usize) -> u8 {
+    match i % 2 {
+        0 => bytes[i / 2] >> 4,
+        _ => bytes[i / 2] & 0x0f,
+    }
+}
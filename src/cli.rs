@@ -1,4 +1,12 @@
-use clap::{command, ArgAction, ArgGroup, Args, Parser, Subcommand};
+use std::time::Duration;
+
+use alloy_primitives::{Address, U256, B256};
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand};
+use createxcrunch::{
+    bundle::parse_session, channel::Backpressure, cycle_stats::StatsFormat, diagnostics::GpuBackend, scan::ScanOrder, schedule::{parse_duration, Schedule}, selftest::SelfTestMode, warnings::WarningCode,
+    writer::{DuplicatePolicy, SplitBy},
+    parse_salt, OutputFormat, SaltFormat, Seed,
+};
 
 #[derive(Parser)]
 #[command(arg_required_else_help = true)]
@@ -11,30 +19,136 @@ pub struct Cli {
 #[derive(Args)]
 #[clap(group = ArgGroup::new("search-criteria").multiple(true).required(true))]
 #[clap(group = ArgGroup::new("zeros-threshold"))]
+#[clap(group = ArgGroup::new("pattern-source").args(["pattern", "pattern-file"]))]
+#[clap(group = ArgGroup::new("checkpointing").args(["supervised", "resume"]).multiple(true))]
 pub struct CliArgs {
+    #[arg(
+        id = "config",
+        long,
+        value_name = "PATH",
+        long_help = "Read options from a TOML file. Every key is a long option, with underscores allowed for dashes, init_code_hash for --code-hash and chain_id for --crosschain. Options given on the command line override the file, which overrides the environment.\n\nEvery other option can also be set with the CREATEXCRUNCH_ environment variable of its name, such as CREATEXCRUNCH_CALLER for --caller.",
+        help_heading = "Crunching options"
+    )]
+    pub config: Option<String>,
+
+    #[arg(
+        id = "params-stdin",
+        long = "params-stdin",
+        long_help = "Read the deployment parameters from a JSON object on stdin before mining, such as {\"factory\": \"0x…\", \"caller\": \"0x…\", \"init_code_hash\": \"0x…\"}, so they never show on the command line. Keys are the long options they replace, with underscores allowed for dashes and init_code_hash for --code-hash, and override the command line, --config and the environment. Their values are kept out of the logs, --json events, jsonl results, bundles and checkpoints, and the output header records their keccak256 hashes instead, unless --no-redact-params is passed. Salts mined with --caller start with the caller, --redact-salts keeps them out of the logs too.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub params_stdin: bool,
+
+    #[arg(
+        id = "no-redact-params",
+        long = "no-redact-params",
+        long_help = "Show the parameters read by --params-stdin like those of the command line, in the logs and the output header.",
+        action = ArgAction::SetTrue,
+        requires = "params-stdin",
+        help_heading = "Crunching options"
+    )]
+    pub no_redact_params: bool,
+
+    #[arg(
+        id = "print-config",
+        long = "print-config",
+        long_help = "Print the options of the run, from --config, the environment and the command line, as a config file and exit without mining. Options left at their defaults are omitted.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub print_config: bool,
+
+    #[arg(
+        id = "dry-run",
+        long = "dry-run",
+        long_help = "Print what the run would search for and exit without touching the GPU or the output file: the factory, the create and salt variants, how the caller and chain ID are embedded in the salt, the reward and the expected number of attempts per result, with a warning when that exceeds 2^64.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub dry_run: bool,
+
     #[arg(
         id = "factory",
         long,
+        env = "CREATEXCRUNCH_FACTORY",
         short,
-        default_value = "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
-        long_help = "Set the factory address.",
+        value_name = "ADDRESS|PRESET",
+        long_help = "Set the factory address, checked against its checksum, or the name of a preset listed by the presets subcommand. Defaults to the canonical CreateX deployment at 0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed, which startup then says.",
+        help_heading = "Crunching options"
+    )]
+    pub factory: Option<String>,
+
+    #[arg(
+        id = "rpc-url",
+        long = "rpc-url",
+        env = "CREATEXCRUNCH_RPC_URL",
+        value_name = "URL",
+        long_help = "JSON-RPC endpoint of the target chain, http:// or, in builds with the pool feature, https://. Before mining, the code at the factory address is fetched and compared with the CreateX runtime code: a mismatch, such as a wrong chain or a fork with different guard logic, raises warning W006 with the observed code hash and, when the canonical CreateX deployment exists on the chain, suggests its address.",
+        help_heading = "Crunching options"
+    )]
+    pub rpc_url: Option<String>,
+
+    #[arg(
+        id = "require-known-factory",
+        long = "require-known-factory",
+        env = "CREATEXCRUNCH_REQUIRE_KNOWN_FACTORY",
+        long_help = "Abort instead of warning when --rpc-url shows the factory is not CreateX, or cannot be checked.",
+        action = ArgAction::SetTrue,
+        requires = "rpc-url",
+        help_heading = "Crunching options"
+    )]
+    pub require_known_factory: bool,
+
+    #[arg(
+        id = "rpc-max-rps",
+        long = "rpc-max-rps",
+        env = "CREATEXCRUNCH_RPC_MAX_RPS",
+        value_name = "REQUESTS",
+        long_help = "Send at most this many requests per second to the --rpc-url node, to stay within the rate limit of free-tier endpoints. Requests the node still refuses with 429 Too Many Requests are retried, after the wait of its Retry-After header.",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "rpc-url",
+        help_heading = "Crunching options"
+    )]
+    pub rpc_max_rps: Option<u32>,
+
+    #[arg(
+        id = "rpc-burst",
+        long = "rpc-burst",
+        env = "CREATEXCRUNCH_RPC_BURST",
+        value_name = "REQUESTS",
+        long_help = "Requests sent at once before --rpc-max-rps spaces them out. Defaults to --rpc-max-rps.",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "rpc-max-rps",
         help_heading = "Crunching options"
     )]
-    pub factory: String,
+    pub rpc_burst: Option<u32>,
 
     #[arg(
         id = "gpu-device-id",
         long,
+        env = "CREATEXCRUNCH_GPU_DEVICE_ID",
         short,
         default_value = "0",
-        long_help = "Set the GPU device ID.",
+        long_help = "Set the GPU device ID, an index printed by the list-gpus subcommand, across the Vulkan, Metal, DX12 and GL adapters of this system. Discrete GPUs come first, so the default is the fastest card. The selected index and name are printed at startup.",
         help_heading = "Crunching options"
     )]
     pub gpu_device_id: u8,
 
+    #[arg(
+        id = "backend",
+        long,
+        env = "CREATEXCRUNCH_BACKEND",
+        value_name = "vulkan|metal|dx12|gl",
+        long_help = "Only use adapters of this graphics API, for example to keep wgpu from falling back to the much slower GL backend. --gpu-device-id then indexes the adapters of this backend. The backend actually used is printed at startup.",
+        help_heading = "Crunching options"
+    )]
+    pub backend: Option<GpuBackend>,
+
     #[arg(
         id = "caller",
         long,
+        env = "CREATEXCRUNCH_CALLER",
         short,
         long_help = "Set the caller address in hex format for a permissioned deployment.",
         help_heading = "Crunching options"
@@ -44,6 +158,7 @@ pub struct CliArgs {
     #[arg(
         id = "chain-id",
         long = "crosschain",
+        env = "CREATEXCRUNCH_CROSSCHAIN",
         short = 'x',
         long_help = "Set whether or not to enable crosschain deployment protection.",
         help_heading = "Crunching options",
@@ -54,6 +169,7 @@ pub struct CliArgs {
     #[arg(
         id = "zeros",
         long = "leading",
+        env = "CREATEXCRUNCH_LEADING",
         short = 'z',
         group = "search-criteria",
         long_help = "Minimum number of leading zero bytes. Cannot be used in combination with --matching.\n\nExample: --leading 4.",
@@ -64,6 +180,7 @@ pub struct CliArgs {
     #[arg(
         id = "total",
         long = "total",
+        env = "CREATEXCRUNCH_TOTAL",
         short = 't',
         group = "search-criteria",
         long_help = "Total number of zero bytes. If used in conjunction with --leading, search criteria will be both thresholds. Pass --either to search for either threshold.\n\nExample: --total 32.",
@@ -74,6 +191,7 @@ pub struct CliArgs {
     #[arg(
         id = "either",
         long = "either",
+        env = "CREATEXCRUNCH_EITHER",
         long_help = "Search for either threshold. Must be used with --leading and --total.",
         requires_all = &["zeros", "total"],
         action = ArgAction::SetTrue,
@@ -84,45 +202,1151 @@ pub struct CliArgs {
     #[arg(
         id = "pattern",
         long = "matching",
+        env = "CREATEXCRUNCH_MATCHING",
         short = 'm',
+        visible_alias = "pattern",
         group = "search-criteria",
-        long_help = "Matching pattern for the contract address. Cannot be used in combination with --leading.\n\nExample: --matching ba5edXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXba5ed.",
+        action = ArgAction::Append,
+        long_help = "Matching pattern for the contract address. Cannot be used in combination with --leading. Can be repeated, as can its alias --pattern, to mine for all the patterns in the same pass: every result names the pattern it matched, or all of them when it matches several, and --limit applies to each pattern.\n\nExample: --matching ba5edXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXba5ed.\nExample: --pattern dead... --pattern beef... --pattern ...cafe",
         help_heading = "Crunching options",
         conflicts_with_all = &["zeros", "total"]
     )]
-    pub pattern: Option<Box<str>>,
+    pub pattern: Vec<Box<str>>,
+
+    #[arg(
+        id = "pattern-file",
+        long = "pattern-file",
+        env = "CREATEXCRUNCH_PATTERN_FILE",
+        group = "search-criteria",
+        value_name = "PATH",
+        long_help = "Read --matching patterns from a file, one per line, skipping blank lines and # comments. All the patterns are mined in the same pass as repeated --matching patterns are, and results record the line of the pattern they matched. Invalid or repeated patterns are errors naming their line. The file is read again about once per second while mining: a valid edit replaces the patterns from the next cycle on, an invalid one is reported and the previous patterns kept.\n\nExample: --pattern-file wishlist.txt.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern"]
+    )]
+    pub pattern_file: Option<String>,
+
+    #[arg(
+        id = "checksum-case",
+        long = "checksum-case",
+        env = "CREATEXCRUNCH_CHECKSUM_CASE",
+        long_help = "Match the letters of the --matching or --pattern-file pattern with their case against the EIP-55 checksummed address. Every letter halves the odds of a match.\n\nExample: --matching DeaD... --checksum-case.",
+        requires = "pattern-source",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub checksum_case: bool,
+
+    #[arg(
+        id = "symmetric-ends",
+        long = "symmetric-ends",
+        env = "CREATEXCRUNCH_SYMMETRIC_ENDS",
+        group = "search-criteria",
+        value_name = "NIBBLES",
+        long_help = "Number of leading nibbles that must repeat, in the same order, at the end of the address. Cannot be used in combination with --leading, --total or --matching.\n\nExample: --symmetric-ends 4 matches 0xcafe...cafe.",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern"]
+    )]
+    pub symmetric_ends: Option<u8>,
+
+    #[arg(
+        id = "leading-nibbles",
+        long = "leading-nibbles",
+        env = "CREATEXCRUNCH_LEADING_NIBBLES",
+        group = "search-criteria",
+        value_name = "NIBBLES",
+        long_help = "Minimum number of leading zero nibbles, between 1 and 40. Unlike --leading, which counts whole bytes, an odd count also requires the high nibble of the next byte to be zero. Cannot be used in combination with --leading, --total, --matching or --symmetric-ends.\n\nExample: --leading-nibbles 9 matches 0x000000000f....",
+        help_heading = "Crunching options",
+        conflicts_with_all = &["zeros", "total", "pattern", "pattern-file", "symmetric-ends"]
+    )]
+    pub leading_nibbles: Option<u8>,
+
+    #[arg(
+        id = "seed-entropy",
+        long = "seed-entropy",
+        env = "CREATEXCRUNCH_SEED_ENTROPY",
+        long_help = "Derive the starting nonce from a session seed mixing the OS RNG, the high-resolution clock, the process id and the GPU name, so concurrent runs start in distinct regions of the search space even on hosts with a poorly seeded RNG. Only a fingerprint of the session seed is printed, so the search can't be repeated.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub seed_entropy: bool,
+
+    #[arg(
+        id = "seed",
+        long = "seed",
+        env = "CREATEXCRUNCH_SEED",
+        value_name = "SEED",
+        conflicts_with_all = &["seed-entropy", "pool-url"],
+        long_help = "Seed the starting nonce and the --scan-order permutation, a decimal integer or 0x-prefixed hex of up to 32 bytes. Runs with the same seed and the same search mine the same sequence of candidate salts, on any machine and whatever the work size, so a run can be reproduced exactly, while different seeds start far apart in the nonce space. Without --seed a seed is drawn from the OS RNG and printed at startup, pass it back to repeat the search. Seeds of up to 64 bits, such as 1 or 42, raise warning W001 low-entropy, since others may pick them too.",
+        help_heading = "Crunching options"
+    )]
+    pub seed: Option<Seed>,
+
+    #[arg(
+        id = "dual-salt",
+        long = "dual-salt",
+        env = "CREATEXCRUNCH_DUAL_SALT",
+        long_help = "Only accept nonces whose address matches under both the guarded crosschain and the random salt layouts, so the vanity is reachable in both deployment modes. Each nonce yields two salts sharing their last 11 bytes (3 zero bytes and the big-endian nonce) behind a zero sender part: `0x00{20} 01 <tail>` deploys with crosschain protection, `0x00{20} 00 <tail>` without. Both salts are written for every match.\n\nThe kernel only derives the random framing of nonces whose crosschain framing matches, so it costs little throughput, and the CPU confirms both addresses of every result.",
+        action = ArgAction::SetTrue,
+        requires = "chain-id",
+        conflicts_with = "caller",
+        help_heading = "Crunching options"
+    )]
+    pub dual_salt: bool,
+
+    #[arg(
+        id = "avoid-reserved",
+        long = "avoid-reserved",
+        env = "CREATEXCRUNCH_AVOID_RESERVED",
+        long_help = "Reject matches in the precompile range (0x0000…0001 to 0x0000…09ff) and well-known addresses such as the zero address, Permit2 or the ERC-4337 EntryPoints, which some infrastructure refuses to index. Rejections are counted separately from results. On by default for the zero rewards (--leading, --total, --either).",
+        action = ArgAction::SetTrue,
+        conflicts_with = "allow-reserved",
+        help_heading = "Crunching options"
+    )]
+    pub avoid_reserved: bool,
+
+    #[arg(
+        id = "allow-reserved",
+        long = "allow-reserved",
+        env = "CREATEXCRUNCH_ALLOW_RESERVED",
+        long_help = "Keep matches in the precompile range and well-known addresses, see --avoid-reserved.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub allow_reserved: bool,
+
+    #[arg(
+        id = "force",
+        long,
+        env = "CREATEXCRUNCH_FORCE",
+        long_help = "Start even though --avoid-reserved rejects nearly every match of the reward. Without it, a run whose matches are less than 1% outside of the reserved addresses refuses to start.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub force: bool,
+
+    #[arg(
+        id = "limit",
+        long,
+        env = "CREATEXCRUNCH_LIMIT",
+        value_name = "N",
+        long_help = "Stop after finding N matching addresses, instead of mining until interrupted. With several --matching patterns, stop once each of them has N, so a pattern found early doesn't end the search for the others. The first matches in scan order are kept, so runs of the same --seed keep the same ones whatever the work size.",
+        help_heading = "Crunching options"
+    )]
+    pub limit: Option<u64>,
+
+    #[arg(
+        id = "max-results",
+        long = "max-results",
+        env = "CREATEXCRUNCH_MAX_RESULTS",
+        value_name = "N",
+        long_help = "Stop once N matching salts were found by this run, after writing every match of the cycle that reached N. Unlike --limit, a cycle finding several matches at once writes them all, so a run may end with more than N results.",
+        conflicts_with = "limit",
+        help_heading = "Crunching options"
+    )]
+    pub max_results: Option<u64>,
+
+    #[arg(
+        id = "timeout",
+        long,
+        env = "CREATEXCRUNCH_TIMEOUT",
+        value_name = "SECONDS",
+        long_help = "Stop after mining for SECONDS, printing how many results were found, so scripts can retry with a lower difficulty.",
+        help_heading = "Crunching options"
+    )]
+    pub timeout: Option<u64>,
+
+    #[arg(
+        id = "max-runtime",
+        long = "max-runtime",
+        env = "CREATEXCRUNCH_MAX_RUNTIME",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        long_help = "Stop once mining has run for DURATION, given in days, hours, minutes and seconds such as 90m, 6h or 2h30m. The cycle in flight is finished and its matches written, and the run ends with the line `stopped: runtime limit reached`. With --max-results, whichever limit is reached first stops the run.",
+        conflicts_with = "timeout",
+        help_heading = "Crunching options"
+    )]
+    pub max_runtime: Option<Duration>,
+
+    #[arg(
+        id = "work-size",
+        long = "work-size",
+        env = "CREATEXCRUNCH_WORK_SIZE",
+        visible_alias = "batch-size",
+        value_name = "NONCES",
+        long_help = "Nonces hashed per cycle, by default the largest batch the device's buffer and dispatch limits allow. Larger batches keep fast GPUs busy, smaller ones keep integrated GPUs from stuttering the desktop. Batches beyond the device's limits are refused.",
+        help_heading = "Crunching options"
+    )]
+    pub work_size: Option<u32>,
+
+    #[arg(
+        id = "workgroup-size",
+        long = "workgroup-size",
+        env = "CREATEXCRUNCH_WORKGROUP_SIZE",
+        default_value_t = 256,
+        value_name = "THREADS",
+        long_help = "Threads per workgroup of the mining kernel. 64 or 128 often suit AMD GPUs, 256 or 512 NVIDIA ones. Must be within the device's max_compute_workgroup_size_x and max_compute_invocations_per_workgroup, see --debug-env.",
+        help_heading = "Crunching options"
+    )]
+    pub workgroup_size: u32,
+
+    #[arg(
+        id = "kernel-file",
+        long = "kernel-file",
+        env = "CREATEXCRUNCH_KERNEL_FILE",
+        value_name = "PATH",
+        long_help = "Mine with the WGSL kernel at PATH instead of the one built in, such as a tuned copy of src/kernels/keccak256.wgsl. The file is checked before the GPU is set up: it must parse and validate, have a compute entry point `main`, declare the override constants DERIVATION, DUAL_SALT, SALT_GUARD_SIZE and WORKGROUP_SIZE, and bind its buffers as the built-in kernel does. The self-test keeps running the built-in kernel.",
+        help_heading = "Crunching options"
+    )]
+    pub kernel_file: Option<String>,
+
+    #[arg(
+        id = "scan-order",
+        long = "scan-order",
+        env = "CREATEXCRUNCH_SCAN_ORDER",
+        default_value = "linear",
+        value_name = "linear|lcg|splitmix",
+        long_help = "Order in which nonces are explored. `linear` mines nonces in increasing order, `lcg` and `splitmix` permute the nonce space with a full-period LCG or the splitmix64 mixer, so early results don't cluster in low nonces. The permutation is seeded from the session seed of --seed or --seed-entropy and recorded in the output header. Skipped ranges refer to scan positions.",
+        help_heading = "Crunching options"
+    )]
+    pub scan_order: ScanOrder,
+
+    #[arg(
+        id = "skip-ranges",
+        long = "skip-ranges",
+        env = "CREATEXCRUNCH_SKIP_RANGES",
+        value_name = "FILE",
+        long_help = "Skip nonce ranges covered by earlier campaigns. Each line of the file is a `<salt prefix> <start nonce> <end nonce>` triple, with the end nonce excluded. The salt prefix is the hex of the 24 salt bytes before the big-endian nonce: the caller or zero, the crosschain flag and 3 zero bytes. Blank lines and `#` comments are ignored.",
+        help_heading = "Crunching options"
+    )]
+    pub skip_ranges: Option<String>,
+
+    #[arg(
+        id = "pool-url",
+        long = "pool-url",
+        env = "CREATEXCRUNCH_POOL_URL",
+        value_name = "URL",
+        long_help = "Mine for a cooperative pool at an http:// URL, or an https:// one in builds with the pool feature, instead of on your own: request a lease of nonces, mine it, submit the results with the attempt count and covered range, and repeat until the pool has no work left. Leases are renewed while they are mined. Submissions the pool cannot be reached for are kept in <output>.pool-queue and delivered later.",
+        conflicts_with_all = &["seed-entropy", "scan-order", "supervised", "resume", "redact-salts"],
+        help_heading = "Crunching options"
+    )]
+    pub pool_url: Option<String>,
+
+    #[arg(
+        id = "pool-worker",
+        long = "pool-worker",
+        env = "CREATEXCRUNCH_POOL_WORKER",
+        value_name = "NAME",
+        default_value = "anonymous",
+        long_help = "Name this miner reports to the pool.",
+        requires = "pool-url",
+        help_heading = "Crunching options"
+    )]
+    pub pool_worker: String,
+
+    #[arg(
+        id = "supervised",
+        long,
+        env = "CREATEXCRUNCH_SUPERVISED",
+        long_help = "Run under a service manager such as systemd with `Restart=always`: resume from `<output>.checkpoint` if it exists instead of clearing the output file, save a checkpoint every --checkpoint-interval seconds and at shutdown, log plain status lines, and exit with a non-zero code when interrupted or failing, so the unit restarts it. Runs stopping at --limit, --max-results, --timeout, --max-runtime or the end of a pool lease exit with 0. With the `systemd` feature, readiness and watchdog keepalives are sent over sd_notify; keepalives stop once no attempts were made for --watchdog-stall seconds so a hung miner gets restarted.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub supervised: bool,
+
+    #[arg(
+        id = "resume",
+        long,
+        env = "CREATEXCRUNCH_RESUME",
+        long_help = "Continue from `<output>.checkpoint` if it exists instead of starting over, and save a checkpoint every --checkpoint-interval seconds and at shutdown, so an interrupted run can be restarted where it stopped. A checkpoint records the scan position, the scan order seed, the attempts made and results found so far, and the factory, create variant, salt variant and reward of its run, resuming with any of them changed is refused. Resumed runs append to their results, see --overwrite, and count the results of the runs they resume towards --limit and --max-results.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub resume: bool,
+
+    #[arg(
+        id = "checkpoint-interval",
+        long = "checkpoint-interval",
+        env = "CREATEXCRUNCH_CHECKPOINT_INTERVAL",
+        default_value_t = 60,
+        value_name = "SECONDS",
+        requires = "checkpointing",
+        long_help = "Seconds between two checkpoints with --supervised or --resume.",
+        help_heading = "Crunching options"
+    )]
+    pub checkpoint_interval: u64,
+
+    #[arg(
+        id = "checkpoint-every-attempts",
+        long = "checkpoint-every-attempts",
+        env = "CREATEXCRUNCH_CHECKPOINT_EVERY_ATTEMPTS",
+        value_name = "N",
+        requires = "checkpointing",
+        long_help = "Also save a checkpoint every N attempts with --supervised or --resume, whichever of this and --checkpoint-interval comes first. Batches are cut to end where a checkpoint is due, so a restarted run mines at most N attempts again. Checkpoints are saved by the output writer once the results found before them are written, a writer that is behind on results delays them.",
+        help_heading = "Crunching options"
+    )]
+    pub checkpoint_every_attempts: Option<u64>,
+
+    #[arg(
+        id = "checkpoint-every-cycles",
+        long = "checkpoint-every-cycles",
+        env = "CREATEXCRUNCH_CHECKPOINT_EVERY_CYCLES",
+        value_name = "N",
+        requires = "checkpointing",
+        long_help = "Also save a checkpoint every N mining cycles with --supervised or --resume, whichever of this, --checkpoint-interval and --checkpoint-every-attempts comes first.",
+        help_heading = "Crunching options"
+    )]
+    pub checkpoint_every_cycles: Option<u64>,
+
+    #[arg(
+        id = "watchdog-stall",
+        long = "watchdog-stall",
+        env = "CREATEXCRUNCH_WATCHDOG_STALL",
+        default_value_t = 120,
+        value_name = "SECONDS",
+        requires = "supervised",
+        long_help = "Seconds without progress after which watchdog keepalives stop in --supervised mode. Should be below the unit's `WatchdogSec`.",
+        help_heading = "Crunching options"
+    )]
+    pub watchdog_stall: u64,
+
+    #[arg(
+        id = "schedule",
+        long,
+        env = "CREATEXCRUNCH_SCHEDULE",
+        value_name = "HH:MM-HH:MM,...",
+        long_help = "Only mine inside these daily windows of local time, such as 22:00-06:00 for nights, and pause outside of them. Windows are comma-separated and may span midnight. A window opening at a time skipped by a DST change opens once the clock jumps past it. With --supervised or --resume a checkpoint is written whenever mining pauses, and watchdog keepalives continue while paused.",
+        help_heading = "Crunching options"
+    )]
+    pub schedule: Option<Schedule>,
+
+    #[arg(
+        id = "debug-env",
+        long = "debug-env",
+        env = "CREATEXCRUNCH_DEBUG_ENV",
+        long_help = "Print the adapter information, the requested and granted limits and the enabled features in a copy-pasteable block for bug reports.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub debug_env: bool,
+
+    #[arg(
+        id = "self-test",
+        long = "self-test",
+        env = "CREATEXCRUNCH_SELF_TEST",
+        value_name = "basic|full",
+        num_args = 0..=1,
+        default_missing_value = "basic",
+        long_help = "Check the kernel against the CPU before mining and abort on any mismatch. `basic` hashes inputs crafted to carry across the 32-bit halves of the emulated 64-bit lanes, `full` additionally rotates a known pattern in every lane by all 64 amounts. The --debug-env block is printed first, so the output can be pasted into a bug report as is.",
+        help_heading = "Crunching options"
+    )]
+    pub self_test: Option<SelfTestMode>,
+
+    #[arg(
+        id = "strict",
+        long = "strict",
+        env = "CREATEXCRUNCH_STRICT",
+        long_help = "Treat any disagreement between the GPU and the CPU as fatal. Runs the full --self-test at startup, derives 16 of the hashes read back each cycle again on the CPU and confirms the whole hash of every result before it is written. On a disagreement the run halts without the results of that cycle and writes a forensic dump for bug reports to <output>.forensic.json: the cycle, the hashes of both, the words read back around the result, the kernel hash and the adapter.",
+        action = ArgAction::SetTrue,
+        help_heading = "Crunching options"
+    )]
+    pub strict: bool,
+
+    #[arg(
+        id = "record-cycles",
+        long = "record-cycles",
+        env = "CREATEXCRUNCH_RECORD_CYCLES",
+        value_name = "DIR",
+        conflicts_with = "dual-salt",
+        long_help = "Debug aid: write a replay record of every cycle that produced results into this directory, as cycle-<N>.replay. A record holds the base nonce, the nonces dispatched, the salt prefix, the config fingerprint, the kernel hash and the words the kernel wrote for each result the pattern matched, including those the CPU discarded. Check a record with `createxcrunch replay <FILE>` when reporting a bad result.",
+        help_heading = "Crunching options"
+    )]
+    pub record_cycles: Option<String>,
+
+    #[arg(
+        id = "fast-start",
+        long = "fast-start",
+        env = "CREATEXCRUNCH_FAST_START",
+        long_help = "Skip every optional startup phase, the worked example, the disk space check, the --rpc-url factory check and the --self-test, so short scripted jobs start mining as soon as the kernel is compiled. Startup always ends with a line breaking its time down by phase.",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = &["self-test", "strict", "rpc-url"],
+        help_heading = "Crunching options"
+    )]
+    pub fast_start: bool,
 
     #[arg(
         id = "output",
         long,
+        env = "CREATEXCRUNCH_OUTPUT",
         short,
         default_value = "output.txt",
-        long_help = "Output file name.",
+        long_help = "Output file name. Results are appended to an existing file, which is locked while the run writes to it, and the header is only written to new files.",
         help_heading = "Output options"
     )]
     pub output: String,
-}
 
-#[derive(Args)]
-pub struct Create2Args {
-    #[clap(flatten)]
-    pub cli_args: CliArgs,
+    #[arg(
+        id = "no-file",
+        long = "no-file",
+        env = "CREATEXCRUNCH_NO_FILE",
+        long_help = "Print each result to stdout as a line in the --format of the run instead of writing an output file, and the startup information and status to stderr, for one-off searches and scripts such as `createxcrunch create3 --leading 2 --no-file --limit 1 | cut -d' ' -f1`. No file is created, truncated or locked.",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = &["output", "overwrite", "checkpointing", "bundle", "split-output-by", "json-events", "pool-url"],
+        help_heading = "Output options"
+    )]
+    pub no_file: bool,
 
     #[arg(
-        long = "code-hash",
-        visible_alias = "ch",
-        long_help = "Set the init code hash in hex format.",
-        help_heading = "Crunching options",
-        required = true,
-        visible_alias = "ch"
+        id = "overwrite",
+        long,
+        env = "CREATEXCRUNCH_OVERWRITE",
+        long_help = "Clear the results of earlier runs from the output file instead of appending to them. Runs resumed from a checkpoint of --supervised or --resume keep their results regardless.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub overwrite: bool,
+
+    #[arg(
+        id = "format",
+        long,
+        env = "CREATEXCRUNCH_FORMAT",
+        visible_alias = "output-format",
+        default_value = "text",
+        value_name = "text|json|jsonl|safe-tx|csv",
+        long_help = "Output file format. `text` writes `salt -> address` lines below a comment header, `json` writes one JSON object per result and no header. `jsonl` writes JSON Lines for deployment scripts, one object per result with its `salt`, `address`, `factory`, `create_variant`, `pattern`, `found_at_unix` and `attempt`, `attempt` counting as in `csv`. `csv` writes a `salt,address,factory,variant,pattern,found_at_unix,attempt` header row when the file is created and one row per result, `variant` being the create variant as in `jsonl`'s `create_variant` and `attempt` counting the attempts made by the end of the cycle the result was found in. Appending to a file holding another format, or CSV rows of other columns, is refused.\n\n`safe-tx` keeps the output file a Safe Transaction Builder batch with one CreateX deploy call per result, ready to import into the Safe UI. It requires --init-code and --safe-chain-id or --chain-id.",
+        help_heading = "Output options"
+    )]
+    pub format: OutputFormat,
+
+    #[arg(
+        id = "init-code",
+        long = "init-code",
+        env = "CREATEXCRUNCH_INIT_CODE",
+        value_name = "HEX|@PATH",
+        long_help = "Init code of the contract to deploy, as hex or as `@` followed by the path of a file holding the hex. Used by --format safe-tx to encode the deploy calls.",
+        help_heading = "Output options"
+    )]
+    pub init_code: Option<String>,
+
+    #[arg(
+        id = "safe-chain-id",
+        long = "safe-chain-id",
+        env = "CREATEXCRUNCH_SAFE_CHAIN_ID",
+        value_name = "CHAIN_ID",
+        long_help = "Chain of the Safe importing the --format safe-tx batch. Defaults to --chain-id.",
+        help_heading = "Output options"
+    )]
+    pub safe_chain_id: Option<u64>,
+
+    #[arg(
+        id = "emit-salt-format",
+        long = "emit-salt-format",
+        env = "CREATEXCRUNCH_EMIT_SALT_FORMAT",
+        value_name = "bytes32|base64|uint256-dec",
+        long_help = "Add a second rendering of every salt for signing tooling: `bytes32` (0x-prefixed hex), `base64`, or `uint256-dec` for ABIs taking uint256 salts. Text output gets an extra column before the arrow, JSON output an extra `salt_<format>` field and CSV output an extra `salt_<format>` column.",
+        help_heading = "Output options"
+    )]
+    pub emit_salt_format: Option<SaltFormat>,
+
+    #[arg(
+        id = "sort-on-exit",
+        long = "sort-on-exit",
+        env = "CREATEXCRUNCH_SORT_ON_EXIT",
+        long_help = "Hold results in memory and write them at shutdown, ranked by leading then total zero nibbles and then by address, instead of appending them as they are found. Only the best --sort-capacity results are kept.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub sort_on_exit: bool,
+
+    #[arg(
+        id = "sort-capacity",
+        long = "sort-capacity",
+        env = "CREATEXCRUNCH_SORT_CAPACITY",
+        default_value_t = 100_000,
+        value_name = "COUNT",
+        requires = "sort-on-exit",
+        long_help = "Maximum number of results held in memory by --sort-on-exit.",
+        help_heading = "Output options"
+    )]
+    pub sort_capacity: usize,
+
+    #[arg(
+        id = "bundle",
+        long,
+        env = "CREATEXCRUNCH_BUNDLE",
+        value_name = "PATH.tar.gz",
+        long_help = "On shutdown, pack the output file, its sidecar files, the effective configuration, build and adapter information into a tar.gz archive.",
+        help_heading = "Output options"
+    )]
+    pub bundle: Option<String>,
+
+    #[arg(
+        id = "campaign",
+        long,
+        env = "CREATEXCRUNCH_CAMPAIGN",
+        value_name = "NAME",
+        long_help = "Record the run as a session of the campaign NAME, up to 64 letters, digits, '.', '_' or '-'. The name goes into the output header, checkpoints and bundles, and every run appends its attempts, time, cost and results to <OUTPUT>.sessions.jsonl, which `campaign report` rolls up across runs and machines.",
+        help_heading = "Output options"
+    )]
+    pub campaign: Option<String>,
+
+    #[arg(
+        id = "clipboard",
+        long,
+        env = "CREATEXCRUNCH_CLIPBOARD",
+        long_help = "On shutdown, copy the best result line to the system clipboard. Requires the `clipboard` build feature; on headless machines the best result is printed instead.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub clipboard: bool,
+
+    #[arg(
+        id = "split-output-by",
+        long = "split-output-by",
+        env = "CREATEXCRUNCH_SPLIT_OUTPUT_BY",
+        value_name = "leading-zeros|score-tier",
+        long_help = "Also route every result into a bucket file next to the output file, which keeps all results. `leading-zeros` buckets by leading zero bytes (results.lz4.txt), `score-tier` by leading zero nibbles, the first criterion of --sort-on-exit (results.tier9.txt). Bucket files are created on their first result, replacing files of earlier runs.",
+        conflicts_with = "redact-salts",
+        help_heading = "Output options"
+    )]
+    pub split_output_by: Option<SplitBy>,
+
+    #[arg(
+        id = "on-duplicate-address",
+        long = "on-duplicate-address",
+        env = "CREATEXCRUNCH_ON_DUPLICATE_ADDRESS",
+        value_name = "keep-all|first-only|warn",
+        default_value = "warn",
+        long_help = "What to do with a result whose address was already found with another salt, by this run or among the recent results of the output file. `keep-all` writes it, `first-only` skips it and `warn` writes it and reports it. Random salts essentially never share an address, so when more than 1% of the results (and at least 3) do, the derivation is suspect and the kernel self-test is run once.",
+        help_heading = "Output options"
+    )]
+    pub on_duplicate_address: DuplicatePolicy,
+
+    #[arg(
+        id = "stats-file",
+        long = "stats-file",
+        env = "CREATEXCRUNCH_STATS_FILE",
+        value_name = "PATH.csv",
+        long_help = "Append a CSV row per mining cycle to this file: its duration, the nonces hashed, the bytes read back from the GPU and the matches found. Compare the files of several runs with `createxcrunch stats`.",
+        help_heading = "Output options"
+    )]
+    pub stats_file: Option<String>,
+
+    #[arg(
+        id = "redact-salts",
+        long = "redact-salts",
+        env = "CREATEXCRUNCH_REDACT_SALTS",
+        long_help = "Keep full salts only in the output file, which is made readable by its owner only and locked while mining. Everywhere else, including the clipboard and messages about unsaved results, a result shows its address and a salt fingerprint, the first 4 bytes of the salt's hash. Use the export subcommand to reveal the salts once the deployment is safe from front-running.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub redact_salts: bool,
+
+    #[arg(
+        id = "no-worked-example",
+        long = "no-worked-example",
+        env = "CREATEXCRUNCH_NO_WORKED_EXAMPLE",
+        long_help = "Do not print the worked example at startup, the step by step derivation of one salt into its address.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub no_worked_example: bool,
+
+    #[arg(
+        id = "quiet",
+        long,
+        env = "CREATEXCRUNCH_QUIET",
+        long_help = "Do not redraw the status every second nor print how the pattern was parsed, for logs of runs under nohup or a service manager. Startup information, a line per result and the final summary are still printed, and results are written to the output file as usual. On by default when the progress does not go to a terminal, such as when stdout is redirected to a file or a pipe, except with --supervised, whose plain status lines are meant for logs.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        id = "json-events",
+        long = "json",
+        env = "CREATEXCRUNCH_JSON",
+        long_help = "Write newline-delimited JSON events to stdout instead of the human output, for pipelines: a `start` event with the resolved configuration and the time each startup phase took, a `status` event every second with the elapsed seconds, attempts, rate and results found, a `found` event per result with its salt, address and pattern, and a `done` event with the reason the run stopped. Warnings and errors still go to stderr, and results are written to the output file as usual.",
+        action = ArgAction::SetTrue,
+        conflicts_with = "pool-url",
+        help_heading = "Output options"
+    )]
+    pub json_events: bool,
+
+    #[arg(
+        id = "max-pending-matches",
+        long = "max-pending-matches",
+        env = "CREATEXCRUNCH_MAX_PENDING_MATCHES",
+        default_value_t = 1024,
+        value_name = "COUNT",
+        long_help = "Maximum number of matches held between the miner and the output writer before backpressure applies.",
+        help_heading = "Output options"
+    )]
+    pub max_pending_matches: usize,
+
+    #[arg(
+        id = "backpressure",
+        long,
+        env = "CREATEXCRUNCH_BACKPRESSURE",
+        default_value = "block",
+        value_name = "block|drop",
+        long_help = "What to do once --max-pending-matches matches are pending. `block` stalls the miner until the writer catches up, `drop` discards the oldest pending match and reports how many were dropped.",
+        help_heading = "Output options"
+    )]
+    pub backpressure: Backpressure,
+
+    #[arg(
+        id = "allow",
+        long,
+        env = "CREATEXCRUNCH_ALLOW",
+        value_name = "CODE",
+        action = ArgAction::Append,
+        long_help = "Suppress a warning, by code (W001) or name (low-entropy). Can be repeated. Allowed warnings are neither printed nor denied by --deny-warnings.\n\nW001 low-entropy, W002 extreme-difficulty, W003 software-adapter, W004 dropped-matches, W005 low-disk-space, W006 unknown-factory.",
+        help_heading = "Output options"
+    )]
+    pub allow: Vec<WarningCode>,
+
+    #[arg(
+        id = "deny-warnings",
+        long = "deny-warnings",
+        env = "CREATEXCRUNCH_DENY_WARNINGS",
+        long_help = "Abort at startup if any warning that is not suppressed with --allow is raised. Meant for CI-driven mining jobs.",
+        action = ArgAction::SetTrue,
+        help_heading = "Output options"
+    )]
+    pub deny_warnings: bool,
+
+    #[arg(
+        id = "power-watts",
+        long = "power-watts",
+        env = "CREATEXCRUNCH_POWER_WATTS",
+        value_name = "WATTS",
+        requires = "price-per-kwh",
+        conflicts_with = "cloud-cost-per-hour",
+        long_help = "Power draw of the mining host. With --price-per-kwh, the startup banner shows the running cost, the status shows the projected cost per result and the final report the energy and cost consumed.",
+        help_heading = "Output options"
+    )]
+    pub power_watts: Option<f64>,
+
+    #[arg(
+        id = "price-per-kwh",
+        long = "price-per-kwh",
+        env = "CREATEXCRUNCH_PRICE_PER_KWH",
+        value_name = "PRICE",
+        requires = "power-watts",
+        long_help = "Electricity price per kWh, used with --power-watts.",
+        help_heading = "Output options"
+    )]
+    pub price_per_kwh: Option<f64>,
+
+    #[arg(
+        id = "cloud-cost-per-hour",
+        long = "cloud-cost-per-hour",
+        env = "CREATEXCRUNCH_CLOUD_COST_PER_HOUR",
+        value_name = "PRICE",
+        long_help = "Hourly price of a rented GPU instance, projected like --power-watts and --price-per-kwh.",
+        help_heading = "Output options"
+    )]
+    pub cloud_cost_per_hour: Option<f64>,
+
+    #[cfg(feature = "dev")]
+    #[arg(
+        id = "address-offset",
+        long = "address-offset",
+        env = "CREATEXCRUNCH_ADDRESS_OFFSET",
+        value_name = "BYTES",
+        hide = true,
+        long_help = "Take the address from the hash bytes starting at this offset instead of 12. For testing alternative derivations only."
+    )]
+    pub address_offset: Option<usize>,
+
+    #[cfg(feature = "dev")]
+    #[arg(
+        id = "inject-corruption",
+        long = "inject-corruption",
+        env = "CREATEXCRUNCH_INJECT_CORRUPTION",
+        hide = true,
+        action = ArgAction::SetTrue,
+        long_help = "Flip a bit of the first hash read back, as a faulty GPU would. For testing --strict only."
+    )]
+    pub inject_corruption: bool,
+}
+
+#[derive(Args)]
+pub struct Create2Args {
+    #[clap(flatten)]
+    pub cli_args: CliArgs,
+
+    #[arg(
+        long = "code-hash",
+        env = "CREATEXCRUNCH_CODE_HASH",
+        visible_alias = "ch",
+        long_help = "Set the init code hash in hex format.",
+        help_heading = "Crunching options",
+        required_unless_present = "params-stdin",
+        visible_alias = "ch"
+    )]
+    pub init_code_hash: Option<String>,
+}
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[arg(
+        id = "output",
+        long,
+        short,
+        default_value = "output.txt",
+        long_help = "Output file of the session to bundle. Its split bucket files and the sidecar files the miner writes next to it are included as well."
+    )]
+    pub output: String,
+
+    #[arg(
+        id = "session",
+        long,
+        value_name = "SEED",
+        value_parser = parse_session,
+        long_help = "Session to bundle, identified by the scan seed the output header and the checkpoint record. A checkpoint of another session is left out. Defaults to the session the checkpoint records, else the last one of the output header."
+    )]
+    pub session: Option<u64>,
+
+    #[arg(value_name = "PATH.tar.gz", long_help = "Path of the archive to write.")]
+    pub bundle: String,
+}
+
+#[derive(Args)]
+pub struct CampaignArgs {
+    #[command(subcommand)]
+    pub command: CampaignCommand,
+}
+
+#[derive(Subcommand)]
+pub enum CampaignCommand {
+    #[command(about = "Roll up the sessions of a campaign: attempts, results by pattern and tier, best addresses, cost and a timeline.")]
+    Report(CampaignReportArgs),
+}
+
+#[derive(Args)]
+pub struct CampaignReportArgs {
+    #[arg(value_name = "NAME", long_help = "Campaign to report on, as passed to --campaign.")]
+    pub name: String,
+
+    #[arg(
+        id = "dir",
+        long,
+        default_value = ".",
+        value_name = "DIR",
+        long_help = "Directory to search, with its subdirectories, for the session logs of the campaign, loose or inside run bundles."
+    )]
+    pub dir: String,
+
+    #[arg(
+        id = "format",
+        long,
+        default_value = "table",
+        value_name = "table|json|markdown",
+        long_help = "Print the report as text, a JSON object or markdown."
+    )]
+    pub format: StatsFormat,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(
+        id = "output",
+        long,
+        short,
+        default_value = "output.txt",
+        long_help = "Output file of a run with --redact-salts."
+    )]
+    pub output: String,
+
+    #[arg(
+        id = "yes",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Print the salts without asking for confirmation."
+    )]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+pub struct ListGpusArgs {
+    #[arg(
+        id = "backend",
+        long,
+        value_name = "vulkan|metal|dx12|gl",
+        long_help = "Only list the adapters of this graphics API, indexed as --gpu-device-id selects them together with the same --backend."
+    )]
+    pub backend: Option<GpuBackend>,
+}
+
+#[derive(Args)]
+pub struct PresetsArgs {
+    #[arg(
+        id = "json",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Print the presets as a JSON array instead of a table."
+    )]
+    pub json: bool,
+}
+
+#[derive(Args)]
+#[clap(group = ArgGroup::new("result-source").args(["result", "salt"]).required(true))]
+pub struct PrepareTxArgs {
+    #[arg(
+        value_name = "RESULT",
+        long_help = "A result line of the output file, in text, json or jsonl format. A jsonl result also gives the factory and create variant it was mined for."
+    )]
+    pub result: Option<String>,
+
+    #[arg(
+        id = "salt",
+        long,
+        requires = "address",
+        long_help = "The mined salt, as 0x-prefixed hex, decimal or base64, instead of a result line."
+    )]
+    pub salt: Option<String>,
+
+    #[arg(
+        id = "address",
+        long,
+        requires = "salt",
+        long_help = "The address the salt was mined for, checked again before the transaction is built."
+    )]
+    pub address: Option<Address>,
+
+    #[arg(
+        id = "init-code",
+        long = "init-code",
+        value_name = "HEX",
+        required = true,
+        long_help = "Init code of the contract to deploy, the creation bytecode followed by its ABI encoded constructor arguments. CREATE2 results must have been mined for its hash."
+    )]
+    pub init_code: String,
+
+    #[arg(
+        id = "create2",
+        long,
+        action = ArgAction::SetTrue,
+        long_help = "Deploy with deployCreate2 instead of deployCreate3, implied by jsonl results mined with create2."
+    )]
+    pub create2: bool,
+
+    #[arg(
+        id = "factory",
+        long,
+        short,
+        long_help = "The factory address, or the name of a preset listed by the presets subcommand. Defaults to the factory of a jsonl result, or CreateX."
+    )]
+    pub factory: Option<String>,
+
+    #[arg(
+        id = "caller",
+        long,
+        short,
+        long_help = "The address sending the transaction, required for salts mined with --caller. It becomes the from field of the transaction."
+    )]
+    pub caller: Option<Address>,
+
+    #[arg(
+        id = "chain-id",
+        long = "chain-id",
+        required = true,
+        long_help = "Chain ID the transaction is signed for, also the chain salts mined with --crosschain are protected for."
+    )]
+    pub chain_id: u64,
+
+    #[arg(
+        id = "gas-limit",
+        long = "gas-limit",
+        required = true,
+        long_help = "Gas limit of the transaction."
+    )]
+    pub gas_limit: u64,
+
+    #[arg(
+        id = "max-fee-per-gas",
+        long = "max-fee-per-gas",
+        value_name = "WEI",
+        required = true,
+        long_help = "Most the transaction pays per gas, base fee and priority fee together."
+    )]
+    pub max_fee_per_gas: u128,
+
+    #[arg(
+        id = "max-priority-fee-per-gas",
+        long = "max-priority-fee-per-gas",
+        value_name = "WEI",
+        required = true,
+        long_help = "Most the transaction pays the block builder per gas on top of the base fee."
+    )]
+    pub max_priority_fee_per_gas: u128,
+
+    #[arg(
+        id = "nonce",
+        long,
+        long_help = "Nonce of the sender, left for the signer to fill in when omitted."
+    )]
+    pub nonce: Option<u64>,
+
+    #[arg(
+        id = "value",
+        long,
+        value_name = "WEI",
+        default_value = "0",
+        long_help = "Wei sent along, which CreateX forwards to the constructor of the deployed contract."
+    )]
+    pub value: U256,
+
+    #[arg(
+        id = "rpc-url",
+        long = "rpc-url",
+        value_name = "URL",
+        long_help = "JSON-RPC endpoint of the target chain, http:// or, in builds with the pool feature, https://, to check that nothing is deployed at the address yet."
+    )]
+    pub rpc_url: Option<String>,
+}
+
+#[derive(Args)]
+pub struct StatsArgs {
+    #[arg(
+        value_name = "FILE.csv",
+        required = true,
+        long_help = "Files written with --stats-file."
+    )]
+    pub files: Vec<String>,
+
+    #[arg(
+        id = "format",
+        long,
+        default_value = "table",
+        value_name = "table|json|markdown",
+        long_help = "Print the comparison as a table, a JSON array or a markdown table."
+    )]
+    pub format: StatsFormat,
+}
+
+#[derive(Args)]
+pub struct RarityArgs {
+    #[arg(
+        value_name = "ADDRESS",
+        required = true,
+        long_help = "Addresses to estimate the rarity of, 0x-prefixed hex."
+    )]
+    pub addresses: Vec<Address>,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[arg(
+        id = "factory",
+        long,
+        short,
+        value_name = "ADDRESS|PRESET",
+        long_help = "The factory address, or the name of a preset listed by the presets subcommand. Defaults to the canonical CreateX deployment at 0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed."
+    )]
+    pub factory: Option<String>,
+
+    #[arg(
+        id = "salt",
+        long,
+        short,
+        value_name = "SALT",
+        value_parser = parse_salt,
+        long_help = "The salt to verify, as passed to the factory: 0x-prefixed bytes32, a decimal uint256 or base64, in any of the --emit-salt-format renderings."
+    )]
+    pub salt: [u8; 32],
+
+    #[arg(
+        id = "caller",
+        long,
+        short,
+        long_help = "The address deploying with the salt. Required when the salt starts with it, as sender protected salts do."
+    )]
+    pub caller: Option<Address>,
+
+    #[arg(
+        id = "chain-id",
+        long = "chain-id",
+        short = 'x',
+        visible_alias = "crosschain",
+        long_help = "The chain the salt is deployed on. Required when the salt enables cross-chain redeploy protection (byte 20 is 0x01)."
+    )]
+    pub chain_id: Option<u64>,
+
+    #[arg(
+        id = "code-hash",
+        long = "init-code-hash",
+        visible_alias = "code-hash",
+        value_name = "HASH",
+        long_help = "The init code hash of a CREATE2 deployment. Without it the address is the CREATE3 one."
+    )]
+    pub init_code_hash: Option<B256>,
+
+    #[arg(
+        id = "expect",
+        long,
+        value_name = "ADDRESS",
+        long_help = "The address the salt was mined for. Exits with an error naming both addresses when the computed one differs."
+    )]
+    pub expect: Option<Address>,
+}
+
+#[derive(Args)]
+pub struct HashInitCodeArgs {
+    #[arg(
+        value_name = "HEX|PATH|-",
+        long_help = "The init code (creation bytecode) to hash: hex, the path of a .bin or .hex file holding the hex, such as compiler output, or - to read it from stdin. The 0x prefix and whitespace are ignored."
+    )]
+    pub init_code: String,
+
+    #[arg(
+        id = "constructor-args",
+        long = "constructor-args",
+        value_name = "HEX",
+        long_help = "ABI encoded constructor arguments, appended to the init code before hashing as they are in the deployment CreateX hashes."
+    )]
+    pub constructor_args: Option<String>,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[arg(
+        id = "duration",
+        long,
+        value_name = "SECONDS",
+        default_value_t = 10,
+        long_help = "Seconds to run the mining kernel for."
+    )]
+    pub duration: u64,
+
+    #[arg(
+        id = "zeros",
+        long = "leading",
+        short = 'z',
+        value_name = "BYTES",
+        default_value_t = 4,
+        long_help = "Number of leading zero bytes to report the expected time to find an address with, at the measured rate. The kernel matches for them like a `create3 --leading` run does."
+    )]
+    pub zeros: u8,
+
+    #[arg(
+        id = "gpu-device-id",
+        long,
+        short,
+        default_value = "0",
+        long_help = "The GPU device ID to benchmark, an index printed by the list-gpus subcommand."
+    )]
+    pub gpu_device_id: u8,
+
+    #[arg(
+        id = "backend",
+        long,
+        value_name = "vulkan|metal|dx12|gl",
+        long_help = "Only use adapters of this graphics API, --gpu-device-id then indexes the adapters of this backend."
+    )]
+    pub backend: Option<GpuBackend>,
+
+    #[arg(
+        id = "work-size",
+        long = "work-size",
+        visible_alias = "batch-size",
+        value_name = "NONCES",
+        long_help = "Nonces hashed per cycle, by default the largest batch the device's limits allow, as when mining."
+    )]
+    pub work_size: Option<u32>,
+
+    #[arg(
+        id = "workgroup-size",
+        long = "workgroup-size",
+        default_value_t = 256,
+        value_name = "THREADS",
+        long_help = "Threads per workgroup of the mining kernel, see the option of the same name of create3."
+    )]
+    pub workgroup_size: u32,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    #[arg(
+        value_name = "FILE.replay",
+        long_help = "A record written with --record-cycles."
+    )]
+    pub file: String,
+
+    #[arg(
+        id = "gpu-device-id",
+        long,
+        short,
+        default_value = "0",
+        long_help = "The GPU device ID to dispatch the candidates on, an index printed by the list-gpus subcommand."
+    )]
+    pub gpu_device_id: u8,
+
+    #[arg(
+        id = "backend",
+        long,
+        value_name = "vulkan|metal|dx12|gl",
+        long_help = "Only use adapters of this graphics API, --gpu-device-id then indexes the adapters of this backend."
+    )]
+    pub backend: Option<GpuBackend>,
+
+    #[arg(
+        id = "no-gpu",
+        long = "no-gpu",
+        action = ArgAction::SetTrue,
+        long_help = "Only derive the candidates again on the CPU. Without a GPU adapter this is what happens anyway."
+    )]
+    pub no_gpu: bool,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    #[arg(
+        value_name = "FILE.toml",
+        long_help = "TOML file listing the jobs as [[job]] tables. Every key is the long option of the same name, except `name`, `command` (create3 or create2, default create3) and the required `limit`, the number of results after which the job stops. Progress is saved to <FILE>.state after every job, so an interrupted batch resumes at the job it did not finish."
+    )]
+    pub file: String,
+
+    #[arg(
+        id = "stop-on-error",
+        long = "stop-on-error",
+        action = ArgAction::SetTrue,
+        long_help = "Stop the batch at the first failed job instead of continuing with the next one."
     )]
-    pub init_code_hash: String,
+    pub stop_on_error: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    #[command(about = "Mine for a CREATE3 deployment address.")]
+    #[command(about = "Mine for a CREATE3 deployment address.", args_override_self = true)]
     Create3(CliArgs),
-    #[command(about = "Mine for a CREATE2 deployment address.")]
+    #[command(about = "Mine for a CREATE2 deployment address.", args_override_self = true)]
     Create2(Create2Args),
+    #[command(about = "Run the mining jobs of a batch file one after the other.")]
+    Batch(BatchArgs),
+    #[command(about = "Bundle whatever artifacts exist for a session into a tar.gz archive.")]
+    Bundle(BundleArgs),
+    #[command(about = "Report on the runs recorded with --campaign.")]
+    Campaign(CampaignArgs),
+    #[command(about = "Print the full salts of an output file written with --redact-salts.")]
+    Export(ExportArgs),
+    #[command(about = "List the GPU adapters, indexed as --gpu-device-id selects them.")]
+    ListGpus(ListGpusArgs),
+    #[command(about = "List the factory presets --factory accepts and how salts are derived for them.")]
+    Presets(PresetsArgs),
+    #[command(about = "Verify a mined salt and print the unsigned EIP-1559 transaction deploying with it.")]
+    PrepareTx(PrepareTxArgs),
+    #[command(about = "Summarize and compare the --stats-file files of several runs, fastest first.")]
+    Stats(StatsArgs),
+    #[command(about = "Estimate how rare addresses are: how few random addresses have as many leading or total zeros or as long a run of one nibble.")]
+    Rarity(RarityArgs),
+    #[command(about = "Compute the init code hash create2 mines for from the creation bytecode.")]
+    HashInitCode(HashInitCodeArgs),
+    #[command(about = "Compute the address CreateX deploys to for a salt, without a GPU.")]
+    Verify(VerifyArgs),
+    #[command(about = "Measure the hash rate of a GPU for a few seconds and how long a number of leading zero bytes takes at it, writing no results.")]
+    Bench(BenchArgs),
+    #[command(about = "Derive the candidates of a --record-cycles record again on the CPU and the GPU and report whether they agree.")]
+    Replay(ReplayArgs),
 }
@@ -0,0 +1,63 @@
+use crate::score;
+
+/// Destination for the best result of a run. Wraps the platform clipboard so the selection
+/// logic can be exercised without one.
+pub trait Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+#[cfg(feature = "clipboard")]
+struct SystemClipboard(arboard::Clipboard);
+
+#[cfg(feature = "clipboard")]
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.0.set_text(text).map_err(|e| e.to_string())
+    }
+}
+
+/// The platform clipboard (X11/Wayland, macOS or Windows). Fails on headless machines and when
+/// the crate is built without the `clipboard` feature.
+pub fn system_clipboard() -> Result<Box<dyn Clipboard>, String> {
+    #[cfg(feature = "clipboard")]
+    {
+        arboard::Clipboard::new()
+            .map(|clipboard| Box::new(SystemClipboard(clipboard)) as Box<dyn Clipboard>)
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    {
+        Err("built without the `clipboard` feature".to_string())
+    }
+}
+
+/// The highest scoring `(salt, address)` result, keeping the first one on ties.
+pub fn best_result(results: &[([u8; 32], [u8; 20])]) -> Option<&([u8; 32], [u8; 20])> {
+    let mut best = None;
+
+    for result in results {
+        let result_score = score(&result.1);
+        if best.is_none_or(|(_, best_score)| result_score > best_score) {
+            best = Some((result, result_score));
+        }
+    }
+
+    best.map(|(result, _)| result)
+}
+
+/// Copy the best result to `clipboard` as `render` lays it out, returning the copied line.
+pub fn copy_best_result(
+    clipboard: &mut dyn Clipboard,
+    results: &[([u8; 32], [u8; 20])],
+    render: impl Fn(&[u8; 32], &[u8; 20]) -> String,
+) -> Result<Option<String>, String> {
+    match best_result(results) {
+        Some((salt, address)) => {
+            let best = render(salt, address);
+            clipboard.set_text(&best)?;
+            Ok(Some(best))
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,184 @@
+use toml_edit::{value, Array, DocumentMut, Item, Value};
+
+use crate::batch::push_option;
+
+/// Keys accepted in place of the long option of the same meaning.
+const ALIASES: [(&str, &str); 3] = [
+    ("init-code-hash", "code-hash"),
+    ("chain-id", "crosschain"),
+    ("pattern", "matching"),
+];
+
+/// Options of the create2 subcommand only, left out of create3 runs.
+const CREATE2_OPTIONS: [&str; 1] = ["code-hash"];
+
+/// Options selecting the addresses to mine for, of which the command line replaces all or none.
+pub const CRITERIA_OPTIONS: [&str; 7] = [
+    "leading",
+    "total",
+    "either",
+    "matching",
+    "pattern-file",
+    "symmetric-ends",
+    "leading-nibbles",
+];
+
+/// Options the errors of `Config::new` are about, by a phrase of the error.
+const ERROR_OPTIONS: [(&str, &[&str]); 11] = [
+    ("factory address", &["factory"]),
+    ("calling address", &["caller"]),
+    ("caller address", &["caller"]),
+    ("init code hash", &["code-hash"]),
+    ("pattern", &["matching"]),
+    ("leading part", &["matching"]),
+    ("trailing part", &["matching"]),
+    ("threshold", &["leading", "total"]),
+    ("symmetric nibbles", &["symmetric-ends"]),
+    ("leading zero nibbles", &["leading-nibbles"]),
+    ("is reserved", &["leading", "total", "matching", "leading-nibbles"]),
+];
+
+/// Options read from a `--config` file. Every key is the long option of the same name, with
+/// underscores for dashes allowed, so
+///
+/// ```toml
+/// factory = "createx"
+/// caller = "0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A"
+/// chain_id = 1
+/// leading = 4
+/// output = "vault.txt"
+/// ```
+///
+/// is `--factory createx --caller 0x88c6… --crosschain 1 --leading 4 --output vault.txt`.
+/// Booleans become flags and arrays repeat their option, as in batch files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigFile {
+    pub path: String,
+    /// Key as written, long option and arguments of every option, in file order.
+    options: Vec<(String, String, Vec<String>)>,
+}
+
+/// Long option a config file key stands for.
+pub fn option_of_key(key: &str) -> String {
+    let option = key.replace('_', "-");
+    match ALIASES.iter().find(|(alias, _)| *alias == option) {
+        Some((_, option)) => option.to_string(),
+        None => option,
+    }
+}
+
+/// Whether `command_line` gives the long option `option`.
+pub fn gives(command_line: &[String], option: &str) -> bool {
+    command_line.iter().any(|arg| {
+        arg.strip_prefix("--")
+            .and_then(|arg| arg.strip_prefix(option))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+    })
+}
+
+/// Whether `command_line` gives any of the [`CRITERIA_OPTIONS`].
+pub fn gives_criteria(command_line: &[String]) -> bool {
+    CRITERIA_OPTIONS
+        .iter()
+        .any(|option| gives(command_line, option))
+}
+
+/// Whether the first line of `error` names `--option`. Clap appends a usage listing every
+/// option.
+pub fn names_option(error: &str, option: &str) -> bool {
+    let error = error.lines().next().unwrap_or_default();
+    let flag = format!("--{}", option);
+    error.match_indices(&flag).any(|(at, _)| {
+        !error[at + flag.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Whether `error` is a validation error of `Config::new` about `option`.
+pub fn describes_option(error: &str, option: &str) -> bool {
+    ERROR_OPTIONS
+        .iter()
+        .any(|(phrase, options)| error.contains(phrase) && options.contains(&option))
+}
+
+impl ConfigFile {
+    pub fn parse(path: &str, contents: &str) -> Result<Self, String> {
+        let document: DocumentMut = contents
+            .parse()
+            .map_err(|e| format!("invalid config file {}: {}", path, e))?;
+
+        let mut options = Vec::new();
+        for (key, item) in document.iter() {
+            let value = item
+                .as_value()
+                .ok_or_else(|| format!("config file {}: {} must be a value", path, key))?;
+            let option = option_of_key(key);
+            let mut args = Vec::new();
+            push_option(&mut args, &option, value)
+                .map_err(|_| format!("config file {}: unsupported value for {}", path, key))?;
+            options.push((key.to_string(), option, args));
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            options,
+        })
+    }
+
+    /// Command line arguments of the options for `command`, create2 or create3, but those the
+    /// rest of `command_line` gives itself. Search criteria on the command line replace those of
+    /// the file rather than combining with them.
+    pub fn args(&self, command: &str, command_line: &[String]) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(_, option, _)| {
+                command == "create2" || !CREATE2_OPTIONS.contains(&option.as_str())
+            })
+            .filter(|(_, option, _)| !gives(command_line, option))
+            .filter(|(_, option, _)| {
+                !(gives_criteria(command_line) && CRITERIA_OPTIONS.contains(&option.as_str()))
+            })
+            .flat_map(|(_, _, args)| args.iter().cloned())
+            .collect()
+    }
+
+    /// The key of the file `error` is about, unless `command_line` gives that option too, in
+    /// which case the command line is at fault. Errors naming a `--option` are about that
+    /// option, the validation errors of `Config::new` about the option they describe.
+    pub fn blame(&self, error: &str, command_line: &[String]) -> Option<&str> {
+        let candidates = |matches: fn(&str, &str) -> bool| {
+            self.options
+                .iter()
+                .filter(|(_, option, _)| matches(error, option))
+                .find(|(_, option, _)| !gives(command_line, option))
+                .map(|(key, _, _)| key.as_str())
+        };
+        candidates(names_option).or_else(|| candidates(describes_option))
+    }
+
+    /// `error` with the key of the file it is about, if any.
+    pub fn explain(&self, error: String, command_line: &[String]) -> String {
+        match self.blame(&error, command_line) {
+            Some(key) => format!("{} (key {} in config file {})", error, key, self.path),
+            None => error,
+        }
+    }
+}
+
+/// Render options as a config file. Options without arguments are flags.
+pub fn render(options: &[(String, Vec<String>)]) -> String {
+    // keep integers as integers, strings like 00 that would not survive as one stay strings
+    let scalar = |arg: &String| match arg.parse::<i64>() {
+        Ok(integer) if integer.to_string() == *arg => Value::from(integer),
+        _ => Value::from(arg.as_str()),
+    };
+
+    let mut document = DocumentMut::new();
+    for (option, args) in options {
+        document[option.as_str()] = match args.as_slice() {
+            [] => value(true),
+            [arg] => Item::Value(scalar(arg)),
+            args => Item::Value(Value::Array(args.iter().map(scalar).collect::<Array>())),
+        };
+    }
+    document.to_string()
+}
@@ -0,0 +1,289 @@
+use std::{
+    fmt::{self, Write as _},
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    str::FromStr,
+};
+
+use serde_json::{json, Value};
+
+use crate::schemas::Artifact;
+
+/// Version of the `--stats-file` rows written by this build. Every row starts with the version
+/// it was written in, so files appended to by different builds stay readable.
+pub const SCHEMA_VERSION: u32 = Artifact::CycleStats.current();
+
+/// Header row of a stats file.
+pub const HEADER: &str = "schema,cycle,elapsed_ms,cycle_ms,attempts,readback_bytes,matches";
+
+/// Open the stats file at `path` for appending rows, starting new files with the header.
+pub fn open(path: &str) -> io::Result<BufWriter<File>> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    if writer.get_ref().metadata()?.len() == 0 {
+        writeln!(writer, "{}", HEADER)?;
+    }
+    Ok(writer)
+}
+
+/// One mining cycle, a row of a `--stats-file`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CycleStats {
+    pub cycle: u64,
+    /// Time since mining started when the cycle ended.
+    pub elapsed_ms: u64,
+    pub cycle_ms: f64,
+    /// Nonces hashed.
+    pub attempts: u64,
+    /// Bytes copied back from the GPU.
+    pub readback_bytes: u64,
+    pub matches: u64,
+}
+
+impl CycleStats {
+    pub fn to_row(&self) -> String {
+        format!(
+            "{},{},{},{:.3},{},{},{}",
+            SCHEMA_VERSION,
+            self.cycle,
+            self.elapsed_ms,
+            self.cycle_ms,
+            self.attempts,
+            self.readback_bytes,
+            self.matches
+        )
+    }
+
+    /// The cycle of the `fields` of a row written in schema `version`, upgraded to the current
+    /// schema. None for versions newer than this build knows.
+    fn upgrade(version: u32, fields: &[&str]) -> Option<Result<Self, String>> {
+        if version > SCHEMA_VERSION {
+            return None;
+        }
+        // version 1 is the only one so far, older rows never existed
+        Some(Artifact::CycleStats.check(version).and_then(|()| Self::parse_v1(fields)))
+    }
+
+    fn parse_v1(fields: &[&str]) -> Result<Self, String> {
+        let [cycle, elapsed_ms, cycle_ms, attempts, readback_bytes, matches] = fields else {
+            return Err(format!("expected 6 fields, found {}", fields.len()));
+        };
+        let integer = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("invalid number {}", field))
+        };
+        Ok(Self {
+            cycle: integer(cycle)?,
+            elapsed_ms: integer(elapsed_ms)?,
+            cycle_ms: cycle_ms
+                .parse()
+                .map_err(|_| format!("invalid duration {}", cycle_ms))?,
+            attempts: integer(attempts)?,
+            readback_bytes: integer(readback_bytes)?,
+            matches: integer(matches)?,
+        })
+    }
+}
+
+/// The cycles of a stats file, and warnings about rows that were skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatsFile {
+    pub cycles: Vec<CycleStats>,
+    pub warnings: Vec<String>,
+}
+
+impl StatsFile {
+    /// Parse the stats file at `path`. Header rows are skipped wherever they appear, rows of a
+    /// schema newer than [`SCHEMA_VERSION`] are skipped with a warning, malformed rows are
+    /// errors.
+    pub fn parse(path: &str, contents: &str) -> Result<Self, String> {
+        let mut file = Self::default();
+        let mut newer = 0;
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("schema,") {
+                continue;
+            }
+            let error = |e: String| format!("{} line {}: {}", path, number + 1, e);
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let version = fields[0]
+                .parse::<u32>()
+                .map_err(|_| error(format!("invalid schema version {}", fields[0])))?;
+            match CycleStats::upgrade(version, &fields[1..]) {
+                Some(cycle) => file.cycles.push(cycle.map_err(error)?),
+                None => newer += 1,
+            }
+        }
+        if newer > 0 {
+            file.warnings.push(format!(
+                "{}: skipped {} rows of a schema newer than version {}",
+                path, newer, SCHEMA_VERSION
+            ));
+        }
+        Ok(file)
+    }
+}
+
+/// Summary of the cycles of one stats file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub path: String,
+    pub cycles: usize,
+    pub median_cycle_ms: f64,
+    /// Attempts per second of cycle time.
+    pub rate: f64,
+    pub readback_bytes: u64,
+    pub matches: u64,
+}
+
+impl Summary {
+    pub fn of(path: &str, cycles: &[CycleStats]) -> Self {
+        let mut durations: Vec<f64> = cycles.iter().map(|cycle| cycle.cycle_ms).collect();
+        durations.sort_by(f64::total_cmp);
+        let median_cycle_ms = match durations.len() {
+            0 => 0.0,
+            n if n % 2 == 1 => durations[n / 2],
+            n => (durations[n / 2 - 1] + durations[n / 2]) / 2.0,
+        };
+        let seconds = durations.iter().sum::<f64>() / 1000.0;
+        let attempts: u64 = cycles.iter().map(|cycle| cycle.attempts).sum();
+        Self {
+            path: path.to_string(),
+            cycles: cycles.len(),
+            median_cycle_ms,
+            rate: match seconds > 0.0 {
+                true => attempts as f64 / seconds,
+                false => 0.0,
+            },
+            readback_bytes: cycles.iter().map(|cycle| cycle.readback_bytes).sum(),
+            matches: cycles.iter().map(|cycle| cycle.matches).sum(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path,
+            "cycles": self.cycles,
+            "median_cycle_ms": self.median_cycle_ms,
+            "rate": self.rate,
+            "readback_bytes": self.readback_bytes,
+            "matches": self.matches,
+        })
+    }
+}
+
+/// The summaries ordered for comparison, fastest first.
+pub fn compare(mut summaries: Vec<Summary>) -> Vec<Summary> {
+    summaries.sort_by(|a, b| b.rate.total_cmp(&a.rate));
+    summaries
+}
+
+/// How `stats` prints its comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    Table,
+    Json,
+    Markdown,
+}
+
+impl fmt::Display for StatsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsFormat::Table => write!(f, "table"),
+            StatsFormat::Json => write!(f, "json"),
+            StatsFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl FromStr for StatsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(StatsFormat::Table),
+            "json" => Ok(StatsFormat::Json),
+            "markdown" => Ok(StatsFormat::Markdown),
+            _ => Err(format!(
+                "unknown stats format {} (expected table, json or markdown)",
+                s
+            )),
+        }
+    }
+}
+
+const COLUMNS: [&str; 6] = [
+    "file",
+    "cycles",
+    "median cycle ms",
+    "MH/s",
+    "readback MB",
+    "matches",
+];
+
+/// Render `summaries` in `format`, in the order given.
+pub fn render(summaries: &[Summary], format: StatsFormat) -> String {
+    if format == StatsFormat::Json {
+        let summaries: Vec<Value> = summaries.iter().map(Summary::to_json).collect();
+        return format!("{:#}", Value::from(summaries));
+    }
+
+    let rows: Vec<[String; 6]> = summaries
+        .iter()
+        .map(|summary| {
+            [
+                summary.path.clone(),
+                summary.cycles.to_string(),
+                format!("{:.3}", summary.median_cycle_ms),
+                format!("{:.2}", summary.rate / 1_000_000.0),
+                format!("{:.2}", summary.readback_bytes as f64 / 1_000_000.0),
+                summary.matches.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut rendered = String::new();
+    match format {
+        StatsFormat::Markdown => {
+            writeln!(rendered, "| {} |", COLUMNS.join(" | ")).unwrap();
+            writeln!(rendered, "|{}", " --- |".repeat(COLUMNS.len())).unwrap();
+            for row in &rows {
+                writeln!(rendered, "| {} |", row.join(" | ")).unwrap();
+            }
+        }
+        _ => {
+            let widths: Vec<usize> = (0..COLUMNS.len())
+                .map(|i| {
+                    rows.iter()
+                        .map(|row| row[i].len())
+                        .chain([COLUMNS[i].len()])
+                        .max()
+                        .unwrap()
+                })
+                .collect();
+            let line = |cells: Vec<&str>| {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .zip(&widths)
+                    .enumerate()
+                    .map(|(i, (cell, width))| match i {
+                        0 => format!("{:<width$}", cell),
+                        _ => format!("{:>width$}", cell),
+                    })
+                    .collect();
+                cells.join("  ").trim_end().to_string()
+            };
+            writeln!(rendered, "{}", line(COLUMNS.to_vec())).unwrap();
+            for row in &rows {
+                writeln!(
+                    rendered,
+                    "{}",
+                    line(row.iter().map(String::as_str).collect())
+                )
+                .unwrap();
+            }
+        }
+    }
+    rendered.trim_end().to_string()
+}
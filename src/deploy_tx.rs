@@ -0,0 +1,206 @@
+use alloy_primitives::{
+    aliases::{U128, U64, U8},
+    hex, keccak256, Address, Bytes, U256,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{http::Transport, parse_salt, rpc::RpcClient, safe, CreateVariant, SaltVariant};
+
+/// EIP-2718 type of EIP-1559 transactions.
+pub const EIP1559_TX_TYPE: u8 = 2;
+
+/// A mined result read back from a line of output: the salt and address, and in `jsonl`
+/// output the factory and create variant it was mined for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MinedResult {
+    pub salt: [u8; 32],
+    pub address: [u8; 20],
+    pub factory: Option<[u8; 20]>,
+    pub create_variant: Option<CreateVariant>,
+}
+
+impl MinedResult {
+    /// Parse a `salt -> address` text line, with or without an extra salt column, or a `json`
+    /// or `jsonl` result.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            let result: Value = serde_json::from_str(line)
+                .map_err(|e| format!("invalid result {}: {}", line, e))?;
+            let field = |name: &str| result.get(name).and_then(Value::as_str);
+            return Ok(Self {
+                salt: parse_salt(field("salt").ok_or("result has no salt")?)?,
+                address: parse_address(field("address").ok_or("result has no address")?)?,
+                factory: field("factory").map(parse_address).transpose()?,
+                create_variant: field("create_variant")
+                    .map(|variant| variant.parse())
+                    .transpose()?,
+            });
+        }
+
+        let (salt, address) = line
+            .split_once("->")
+            .ok_or_else(|| format!("result {} is not a salt -> address line", line))?;
+        let salt = salt.split_whitespace().next().ok_or("result has no salt")?;
+        let address = address
+            .split_whitespace()
+            .next()
+            .ok_or("result has no address")?;
+        Ok(Self {
+            salt: parse_salt(salt)?,
+            address: parse_address(address)?,
+            factory: None,
+            create_variant: None,
+        })
+    }
+}
+
+fn parse_address(address: &str) -> Result<[u8; 20], String> {
+    address
+        .parse::<Address>()
+        .map(|address| address.0 .0)
+        .map_err(|_| format!("invalid address {}", address))
+}
+
+/// The create variant to deploy `init_code` with: CREATE2 when asked for or when the result was
+/// mined for it, in which case `init_code` must hash to the init code hash it was mined for.
+pub fn deploy_variant(
+    mined: Option<&CreateVariant>,
+    create2: bool,
+    init_code: &[u8],
+) -> Result<CreateVariant, String> {
+    let init_code_hash = keccak256(init_code).0;
+    if let Some(CreateVariant::Create2 {
+        init_code_hash: mined,
+    }) = mined
+    {
+        if *mined != init_code_hash {
+            return Err(format!(
+                "the init code hashes to 0x{}, but the result was mined for init code hash 0x{}",
+                hex::encode(init_code_hash),
+                hex::encode(mined)
+            ));
+        }
+    }
+    match (mined, create2) {
+        (Some(CreateVariant::Create3), true) => {
+            Err("the result was mined for create3, drop --create2".to_string())
+        }
+        (Some(CreateVariant::Create2 { .. }), _) | (None, true) => {
+            Ok(CreateVariant::Create2 { init_code_hash })
+        }
+        (Some(CreateVariant::Create3), false) | (None, false) => Ok(CreateVariant::Create3),
+    }
+}
+
+/// A CreateX deployment of `init_code` with a mined `salt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deployment {
+    pub factory: [u8; 20],
+    pub create_variant: CreateVariant,
+    pub salt_variant: SaltVariant,
+    pub salt: [u8; 32],
+    pub init_code: Vec<u8>,
+}
+
+impl Deployment {
+    /// Address the deployment lands at, derived from the salt as CreateX guards it.
+    pub fn address(&self) -> [u8; 20] {
+        self.create_variant
+            .address(&self.factory, &self.salt_variant.guard(&self.salt))
+    }
+
+    /// Check that the salt still derives to the `expected` address, so a result edited by hand,
+    /// mined for another factory or caller, or deployed on another chain is caught before a
+    /// transaction is signed.
+    pub fn verify(&self, expected: &[u8; 20]) -> Result<(), String> {
+        let address = self.address();
+        match address == *expected {
+            true => Ok(()),
+            false => Err(format!(
+                "salt 0x{} deploys to {} with {} from {}, not to {}",
+                hex::encode(self.salt),
+                Address::from(address).to_checksum(None),
+                self.salt_variant,
+                Address::from(self.factory).to_checksum(None),
+                Address::from(*expected).to_checksum(None)
+            )),
+        }
+    }
+
+    /// Calldata of the CreateX deploy function, see [`safe::deploy_calldata`].
+    pub fn calldata(&self) -> Vec<u8> {
+        safe::deploy_calldata(&self.create_variant, &self.salt, &self.init_code)
+    }
+}
+
+/// Gas limit and fees of a transaction, in gas and wei per gas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fees {
+    pub gas_limit: u64,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+/// An unsigned EIP-1559 transaction, serialized with the field names and hex quantities of an
+/// `eth_sendTransaction` request, which `cast`, wallets and signing tools read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip1559Transaction {
+    #[serde(rename = "type")]
+    pub tx_type: U8,
+    pub chain_id: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    pub to: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    pub gas: U64,
+    pub max_fee_per_gas: U128,
+    pub max_priority_fee_per_gas: U128,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: Vec<Value>,
+}
+
+impl Eip1559Transaction {
+    /// Transaction calling the factory of `deployment` on `chain_id`, without a sender or nonce,
+    /// which the signer fills in unless they are set.
+    pub fn deploy(deployment: &Deployment, chain_id: u64, fees: &Fees) -> Self {
+        Self {
+            tx_type: U8::from(EIP1559_TX_TYPE),
+            chain_id: U64::from(chain_id),
+            from: None,
+            to: Address::from(deployment.factory),
+            nonce: None,
+            gas: U64::from(fees.gas_limit),
+            max_fee_per_gas: U128::from(fees.max_fee_per_gas),
+            max_priority_fee_per_gas: U128::from(fees.max_priority_fee_per_gas),
+            value: U256::ZERO,
+            data: Bytes::from(deployment.calldata()),
+            access_list: vec![],
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).expect("transactions serialize")
+    }
+}
+
+/// Check on the node behind `rpc` that nothing is deployed at `address` yet. CreateX reverts
+/// deployments to taken addresses, and the transaction would only burn its gas.
+pub fn check_vacant<T: Transport>(
+    rpc: &mut RpcClient<T>,
+    address: &[u8; 20],
+) -> Result<(), String> {
+    let code = rpc.get_code(address)?;
+    match code.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "{} already holds {} bytes of code, the salt was deployed already",
+            Address::from(*address).to_checksum(None),
+            code.len()
+        )),
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, fmt::Write, str::FromStr};
+
+/// Render the adapter information, negotiated limits and enabled features as a block that can
+/// be pasted into a bug report.
+pub fn debug_env_report(
+    info: &wgpu::AdapterInfo,
+    requested: &wgpu::Limits,
+    granted: &wgpu::Limits,
+    features: wgpu::Features,
+) -> String {
+    let mut report = String::new();
+
+    writeln!(report, "----- createxcrunch debug environment -----").unwrap();
+    writeln!(report, "version: {}", env!("CARGO_PKG_VERSION")).unwrap();
+    writeln!(report, "adapter: {}", info.name).unwrap();
+    writeln!(report, "backend: {:?}", info.backend).unwrap();
+    writeln!(report, "device type: {:?}", info.device_type).unwrap();
+    writeln!(report, "vendor id: 0x{:04x}", info.vendor).unwrap();
+    writeln!(report, "device id: 0x{:04x}", info.device).unwrap();
+    writeln!(report, "driver: {}", info.driver).unwrap();
+    writeln!(report, "driver info: {}", info.driver_info).unwrap();
+    writeln!(report, "features: {:?}", features).unwrap();
+    writeln!(report, "limits (requested / granted):").unwrap();
+
+    macro_rules! limits {
+        ($($field:ident),* $(,)?) => {
+            $(
+                writeln!(
+                    report,
+                    "  {}: {} / {}",
+                    stringify!($field),
+                    requested.$field,
+                    granted.$field
+                )
+                .unwrap();
+            )*
+        };
+    }
+
+    limits!(
+        max_buffer_size,
+        max_storage_buffer_binding_size,
+        max_storage_buffers_per_shader_stage,
+        max_bind_groups,
+        max_bindings_per_bind_group,
+        max_compute_workgroup_size_x,
+        max_compute_workgroup_size_y,
+        max_compute_workgroup_size_z,
+        max_compute_invocations_per_workgroup,
+        max_compute_workgroups_per_dimension,
+        max_compute_workgroup_storage_size,
+    );
+
+    write!(report, "-------------------------------------------").unwrap();
+
+    report
+}
+
+/// Preference of a device type when picking an adapter, lower first. Discrete GPUs come first
+/// so the default `--gpu-device-id 0` is the fastest card, and software adapters last.
+pub fn device_type_rank(device_type: wgpu::DeviceType) -> u8 {
+    match device_type {
+        wgpu::DeviceType::DiscreteGpu => 0,
+        wgpu::DeviceType::IntegratedGpu => 1,
+        wgpu::DeviceType::VirtualGpu => 2,
+        wgpu::DeviceType::Other => 3,
+        wgpu::DeviceType::Cpu => 4,
+    }
+}
+
+/// Graphics API `--backend` restricts adapters to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuBackend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+
+impl GpuBackend {
+    pub const ALL: [GpuBackend; 4] = [
+        GpuBackend::Vulkan,
+        GpuBackend::Metal,
+        GpuBackend::Dx12,
+        GpuBackend::Gl,
+    ];
+
+    pub fn backends(&self) -> wgpu::Backends {
+        match self {
+            GpuBackend::Vulkan => wgpu::Backends::VULKAN,
+            GpuBackend::Metal => wgpu::Backends::METAL,
+            GpuBackend::Dx12 => wgpu::Backends::DX12,
+            GpuBackend::Gl => wgpu::Backends::GL,
+        }
+    }
+
+    /// The backend of an adapter, none for the empty and browser backends.
+    pub fn of(backend: wgpu::Backend) -> Option<Self> {
+        match backend {
+            wgpu::Backend::Vulkan => Some(GpuBackend::Vulkan),
+            wgpu::Backend::Metal => Some(GpuBackend::Metal),
+            wgpu::Backend::Dx12 => Some(GpuBackend::Dx12),
+            wgpu::Backend::Gl => Some(GpuBackend::Gl),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GpuBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuBackend::Vulkan => write!(f, "vulkan"),
+            GpuBackend::Metal => write!(f, "metal"),
+            GpuBackend::Dx12 => write!(f, "dx12"),
+            GpuBackend::Gl => write!(f, "gl"),
+        }
+    }
+}
+
+impl FromStr for GpuBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GpuBackend::ALL
+            .into_iter()
+            .find(|backend| backend.to_string() == s)
+            .ok_or_else(|| format!("unknown backend {} (expected vulkan, metal, dx12 or gl)", s))
+    }
+}
+
+/// Error for a `--backend` without adapters, naming the backends of the adapters found
+/// without the restriction.
+pub fn missing_backend_error(requested: GpuBackend, available: &[wgpu::Backend]) -> String {
+    let mut others: Vec<GpuBackend> = vec![];
+    for backend in available
+        .iter()
+        .filter_map(|backend| GpuBackend::of(*backend))
+    {
+        if !others.contains(&backend) {
+            others.push(backend);
+        }
+    }
+    match others.as_slice() {
+        [] => format!(
+            "no {} adapter found, and no other backend has one either. Check the graphics \
+             drivers, or the Vulkan, Metal or DX12 runtime of this system.",
+            requested
+        ),
+        others => format!(
+            "no {} adapter found, adapters are available on {} (select one with --backend, or \
+             see list-gpus)",
+            requested,
+            others
+                .iter()
+                .map(|backend| backend.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn device_type_name(device_type: wgpu::DeviceType) -> &'static str {
+    match device_type {
+        wgpu::DeviceType::DiscreteGpu => "discrete",
+        wgpu::DeviceType::IntegratedGpu => "integrated",
+        wgpu::DeviceType::VirtualGpu => "virtual",
+        wgpu::DeviceType::Cpu => "CPU",
+        wgpu::DeviceType::Other => "other",
+    }
+}
+
+/// Render the adapters as an indexed table, in the order `--gpu-device-id` indexes them.
+pub fn adapter_table(adapters: &[(wgpu::AdapterInfo, wgpu::Limits)]) -> String {
+    if adapters.is_empty() {
+        return "No GPU adapter found. Check the graphics drivers, or the Vulkan, Metal or DX12 \
+                runtime of this system."
+            .to_string();
+    }
+
+    let name_width = adapters
+        .iter()
+        .map(|(info, _)| info.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("name".len());
+    let mut table = format!(
+        "{:<3} {:<name_width$} {:<7} {:<10} {:>14} {:>14}",
+        "id", "name", "backend", "type", "max binding", "max buffer"
+    );
+    for (index, (info, limits)) in adapters.iter().enumerate() {
+        write!(
+            table,
+            "\n{:<3} {:<name_width$} {:<7} {:<10} {:>10} MiB {:>10} MiB",
+            index,
+            info.name,
+            format!("{:?}", info.backend),
+            device_type_name(info.device_type),
+            limits.max_storage_buffer_binding_size >> 20,
+            limits.max_buffer_size >> 20
+        )
+        .unwrap();
+    }
+    table
+}
@@ -0,0 +1,132 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+/// An event of the `--json` stream, written to stdout as one JSON object per line tagged with
+/// its kind, e.g. `{"event":"found",...}`. A run writes one `start` event once mining begins,
+/// `status` events every second, a `found` event per result and one `done` event at the end.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Start(Box<StartEvent>),
+    Status(StatusEvent),
+    Found(FoundEvent),
+    Done(DoneEvent),
+}
+
+/// The configuration a run resolved to, once its GPU is set up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartEvent {
+    pub version: String,
+    /// See [`crate::schemas::Artifact::Events`], 0 in streams of builds before it.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Name of the adapter mining.
+    pub gpu: String,
+    pub factory: String,
+    /// Canonical forms, see [`crate::canonical`].
+    pub create_variant: String,
+    pub salt_variant: String,
+    pub reward: String,
+    pub output: String,
+    pub format: String,
+    pub scan_order: String,
+    /// Scan index mining starts at, past the results of a resumed checkpoint.
+    pub start_index: u64,
+    /// Nonces dispatched per cycle, after fitting them to the device.
+    pub work_size: u32,
+    pub workgroup_size: u32,
+    /// Expected number of attempts per result.
+    pub expected_attempts: f64,
+    pub limit: Option<u64>,
+    /// How long each phase of setting the run up took, in the order they ran.
+    pub startup: Vec<StartupPhaseTime>,
+}
+
+/// A phase of the startup and its duration.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartupPhaseTime {
+    /// Name of the phase, see [`crate::plan::StartupPhase::name`].
+    pub phase: String,
+    pub secs: f64,
+}
+
+/// Progress of a run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEvent {
+    pub elapsed_secs: f64,
+    pub attempts: u64,
+    /// Attempts per second.
+    pub rate: f64,
+    pub found: u64,
+    /// Expected seconds to the next match at `rate`, none while no rate was measured or past
+    /// what a duration holds.
+    #[serde(default)]
+    pub eta_secs: Option<f64>,
+    /// Odds of having found a match by now, from 0 to 1.
+    #[serde(default)]
+    pub explored: f64,
+}
+
+/// A result, as written to the output file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoundEvent {
+    /// The salt in hex, or its fingerprint under `--redact-salts`.
+    pub salt: String,
+    /// The checksummed address.
+    pub address: String,
+    /// The reward the address was mined for, in its canonical form.
+    pub pattern: String,
+}
+
+/// The end of a run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DoneEvent {
+    pub reason: StopReason,
+    pub elapsed_secs: f64,
+    pub attempts: u64,
+    pub found: u64,
+}
+
+/// Why a run stopped mining.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// Ctrl+C or SIGTERM.
+    Interrupted,
+    /// `--limit` results were found.
+    Limit,
+    /// A cycle reached `--max-results` results.
+    MaxResults,
+    /// `--timeout` ran out.
+    Timeout,
+    /// `--max-runtime` ran out.
+    MaxRuntime,
+    /// Every nonce of a pool lease was mined.
+    LeaseMined,
+    /// The kernel self-test failed after addresses were found again with other salts.
+    SelfTestFailed,
+    /// A `--strict` run found the GPU and the CPU deriving a result differently.
+    Disagreement,
+}
+
+impl StopReason {
+    /// Whether the run stopped where it was told to, at a limit or the end of its lease, rather
+    /// than interrupted or failing.
+    pub fn is_deliberate(self) -> bool {
+        match self {
+            StopReason::Limit
+            | StopReason::MaxResults
+            | StopReason::Timeout
+            | StopReason::MaxRuntime
+            | StopReason::LeaseMined => true,
+            StopReason::Interrupted | StopReason::SelfTestFailed | StopReason::Disagreement => false,
+        }
+    }
+}
+
+/// Write `event` as a line of the `--json` stream.
+pub fn write_event(mut out: impl io::Write, event: &Event) -> io::Result<()> {
+    writeln!(out, "{}", serde_json::to_string(event)?)?;
+    out.flush()
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use alloy_primitives::{hex, keccak256, Address};
+
+use crate::{http::Transport, rpc::RpcClient};
+
+/// Deployment whose runtime code identifies a factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownCode {
+    pub name: &'static str,
+    /// Keccak256 of the runtime code.
+    pub code_hash: [u8; 32],
+    /// Address of the canonical deployment, the same on every chain.
+    pub address: [u8; 20],
+}
+
+/// CreateX, whose guard logic the miner reproduces.
+pub const CREATEX: KnownCode = KnownCode {
+    name: "CreateX",
+    code_hash: hex!("bd8a7ea8cfca7b4e5f5041d7d4b17bc317c5ce42cfbc42066a00cf26b43eb53f"),
+    address: crate::CREATEX_ADDRESS,
+};
+
+/// Runtime code of the deterministic deployment proxy, a plain CREATE2 deployer.
+pub const DETERMINISTIC_PROXY_CODE: [u8; 69] = hex!(
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3"
+);
+
+/// The deterministic deployment proxy, which deploys with raw CREATE2 and no salt guard.
+pub const DETERMINISTIC_PROXY: KnownCode = KnownCode {
+    name: "the deterministic deployment proxy",
+    code_hash: hex!("2fa86add0aed31f33a762c9d88e807c475bd51d0f52bd0955754b2608f7e4989"),
+    address: hex!("4e59b44847b379578588920cA78FbF26c0B4956C"),
+};
+
+/// Every factory recognized by its code.
+pub const KNOWN_CODE: [KnownCode; 2] = [CREATEX, DETERMINISTIC_PROXY];
+
+/// What runs at a factory address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FactoryCode {
+    Known(KnownCode),
+    /// No contract is deployed there.
+    Empty,
+    Unknown {
+        code_hash: [u8; 32],
+    },
+}
+
+impl FactoryCode {
+    pub fn identify(code: &[u8]) -> Self {
+        if code.is_empty() {
+            return FactoryCode::Empty;
+        }
+        let code_hash = keccak256(code).0;
+        KNOWN_CODE
+            .into_iter()
+            .find(|known| known.code_hash == code_hash)
+            .map_or(FactoryCode::Unknown { code_hash }, FactoryCode::Known)
+    }
+}
+
+impl fmt::Display for FactoryCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FactoryCode::Known(known) => write!(
+                f,
+                "{} (code hash 0x{})",
+                known.name,
+                hex::encode(known.code_hash)
+            ),
+            FactoryCode::Empty => write!(f, "an empty account"),
+            FactoryCode::Unknown { code_hash } => {
+                write!(f, "unknown code (code hash 0x{})", hex::encode(code_hash))
+            }
+        }
+    }
+}
+
+/// Outcome of looking up a factory that does not run the expected code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FactoryMismatch {
+    pub factory: [u8; 20],
+    pub expected: KnownCode,
+    pub found: FactoryCode,
+    /// The canonical deployment of the expected code, if it exists on this chain.
+    pub correction: Option<[u8; 20]>,
+}
+
+impl fmt::Display for FactoryMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "factory {} is {}, not {}, salts mined for it will not deploy where expected",
+            Address::from(self.factory).to_checksum(None),
+            self.found,
+            self.expected.name
+        )?;
+        if let Some(correction) = self.correction {
+            write!(
+                f,
+                " (use --factory {})",
+                Address::from(correction).to_checksum(None)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Check over RPC that `factory` runs the `expected` code. On a mismatch, the canonical
+/// deployment is looked up as well and suggested if it is there.
+pub fn check_factory<T: Transport>(
+    rpc: &mut RpcClient<T>,
+    factory: &[u8; 20],
+    expected: &KnownCode,
+) -> Result<Result<(), FactoryMismatch>, String> {
+    let found = FactoryCode::identify(&rpc.get_code(factory)?);
+    if found == FactoryCode::Known(*expected) {
+        return Ok(Ok(()));
+    }
+
+    let correction = match *factory == expected.address {
+        true => None,
+        false => {
+            let canonical = FactoryCode::identify(&rpc.get_code(&expected.address)?);
+            (canonical == FactoryCode::Known(*expected)).then_some(expected.address)
+        }
+    };
+    Ok(Err(FactoryMismatch {
+        factory: *factory,
+        expected: *expected,
+        found,
+        correction,
+    }))
+}
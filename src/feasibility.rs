@@ -0,0 +1,267 @@
+use std::fmt;
+
+use alloy_primitives::hex;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::SmallRng,
+    Rng, RngCore, SeedableRng,
+};
+
+use crate::{
+    checksum_matches,
+    probability::expected_attempts,
+    reserved::{self, DENYLIST, PRECOMPILE_END},
+    stats, RewardVariant,
+};
+
+/// Random matching addresses drawn for the rewards [`feasibility`] can neither count nor bound.
+pub const SAMPLES: u64 = 2_000_000;
+
+/// Share of the matches `--avoid-reserved` must keep for a run to start without `--force`.
+pub const MIN_KEPT: f64 = 0.01;
+
+/// How many times the reserved addresses the matching ones must outnumber for the bound to
+/// stand in for sampling.
+const BOUND_MARGIN: f64 = 1e6;
+
+/// How a [`Feasibility`] was found.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// Counted from the bytes the reward pins.
+    Exact,
+    /// Bounded by the number of reserved addresses, which the matching ones far outnumber.
+    Bound,
+    /// Estimated from this many random matching addresses.
+    Sampled(u64),
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::Exact => write!(f, "counted exactly"),
+            Method::Bound => write!(f, "bounded by the number of reserved addresses"),
+            Method::Sampled(samples) => write!(f, "estimated from {} samples", samples),
+        }
+    }
+}
+
+/// Share of the addresses matching a reward that `--avoid-reserved` keeps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Feasibility {
+    pub kept: f64,
+    pub method: Method,
+}
+
+/// Every reserved address, sorted, see [`crate::reserved::reserved`].
+pub fn reserved_addresses() -> Vec<[u8; 20]> {
+    let mut addresses: Vec<[u8; 20]> = (1..=PRECOMPILE_END)
+        .map(|low| {
+            let mut address = [0; 20];
+            address[18..].copy_from_slice(&low.to_be_bytes());
+            address
+        })
+        .collect();
+    addresses.extend(DENYLIST.iter().map(|(_, address)| *address));
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+/// The bytes a reward fixes for every address it matches: a prefix, and the last byte of
+/// `...xx` patterns. The zero rewards only fix them when they come down to leading zeros, as
+/// with 20 zero bytes in total or a total threshold the leading one already meets.
+struct Pinned {
+    prefix: Vec<u8>,
+    last: Option<u8>,
+}
+
+impl Pinned {
+    fn of(reward: &RewardVariant) -> Option<Self> {
+        match reward {
+            RewardVariant::LeadingZeros { zeros_threshold } => Some(Self::zeros(*zeros_threshold)),
+            RewardVariant::LeadingZeroNibbles { threshold } if threshold % 2 == 0 => {
+                Some(Self::zeros(threshold / 2))
+            }
+            RewardVariant::TotalZeros { zeros_threshold } if *zeros_threshold >= 20 => {
+                Some(Self::zeros(20))
+            }
+            RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } if leading_zeros_threshold >= total_zeros_threshold
+                || *total_zeros_threshold >= 20 =>
+            {
+                Some(Self::zeros(
+                    *leading_zeros_threshold.max(total_zeros_threshold),
+                ))
+            }
+            RewardVariant::Matching { pattern } | RewardVariant::ChecksumMatching { pattern } => {
+                let (prefix, suffix) = pattern.split_once("...").unwrap_or((pattern, ""));
+                Some(Self {
+                    prefix: hex::decode(prefix).ok()?,
+                    last: hex::decode(suffix).ok()?.last().copied(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn zeros(count: u8) -> Self {
+        Self {
+            prefix: vec![0; (count as usize).min(20)],
+            last: None,
+        }
+    }
+
+    fn contains(&self, address: &[u8; 20]) -> bool {
+        address.starts_with(&self.prefix) && self.last.is_none_or(|last| address[19] == last)
+    }
+
+    /// Number of addresses holding the pinned bytes.
+    fn size(&self) -> f64 {
+        if self.prefix.len() >= 20 {
+            let fits =
+                self.prefix.len() == 20 && self.last.is_none_or(|last| self.prefix[19] == last);
+            return if fits { 1.0 } else { 0.0 };
+        }
+        let free = 20 - self.prefix.len() - self.last.is_some() as usize;
+        256f64.powi(free as i32)
+    }
+
+    fn apply(&self, address: &mut [u8; 20]) {
+        let len = self.prefix.len().min(20);
+        address[..len].copy_from_slice(&self.prefix[..len]);
+        if let Some(last) = self.last {
+            address[19] = last;
+        }
+    }
+}
+
+/// Share of the addresses matching `reward` outside of the reserved ones. Rewards pinning the
+/// bytes of their matches are counted exactly, rewards with far more matches than there are
+/// reserved addresses are bounded, and the others are estimated from `samples` random matching
+/// addresses, drawn from a fixed seed so the estimate is reproducible.
+pub fn feasibility(reward: &RewardVariant, samples: u64) -> Feasibility {
+    let reserved = reserved_addresses();
+
+    if let Some(pinned) = Pinned::of(reward) {
+        let lost = reserved.iter().filter(|address| pinned.contains(address));
+        let (matching, lost) = match reward {
+            // the checksum keeps one case of every letter of the pattern
+            RewardVariant::ChecksumMatching { pattern } => {
+                let letters = pattern.chars().filter(|c| c.is_ascii_alphabetic()).count();
+                (
+                    pinned.size() * 2f64.powi(-(letters as i32)),
+                    lost.filter(|address| checksum_matches(address, pattern))
+                        .count(),
+                )
+            }
+            _ => (pinned.size(), lost.count()),
+        };
+        let kept = match matching > 0.0 {
+            true => (1.0 - lost as f64 / matching).max(0.0),
+            false => 1.0,
+        };
+        return Feasibility {
+            kept,
+            method: Method::Exact,
+        };
+    }
+
+    let matching = 2f64.powi(160) / expected_attempts(reward);
+    if matching >= reserved.len() as f64 * BOUND_MARGIN {
+        return Feasibility {
+            kept: 1.0 - reserved.len() as f64 / matching,
+            method: Method::Bound,
+        };
+    }
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let kept = (0..samples)
+        .filter(|_| reserved::reserved(&sample(reward, &mut rng)).is_none())
+        .count();
+    Feasibility {
+        kept: kept as f64 / samples.max(1) as f64,
+        method: Method::Sampled(samples),
+    }
+}
+
+/// A random address matching `reward`. Addresses with more zero bytes than the zero rewards ask
+/// for are drawn a little more often than uniform sampling would.
+pub fn sample(reward: &RewardVariant, rng: &mut SmallRng) -> [u8; 20] {
+    let mut address = [0; 20];
+    rng.fill_bytes(&mut address);
+    // zero `count` distinct bytes, picked by a partial shuffle of their positions
+    let zero_any = |address: &mut [u8], count: u8, rng: &mut SmallRng| {
+        let mut positions: [usize; 20] = std::array::from_fn(|i| i);
+        let positions = &mut positions[..address.len()];
+        for i in 0..(count as usize).min(positions.len()) {
+            positions.swap(i, rng.gen_range(i..positions.len()));
+            address[positions[i]] = 0;
+        }
+    };
+
+    match reward {
+        RewardVariant::TotalZeros { zeros_threshold } => {
+            zero_any(&mut address, *zeros_threshold, rng)
+        }
+        RewardVariant::LeadingAndTotalZeros {
+            leading_zeros_threshold,
+            total_zeros_threshold,
+        } => {
+            let leading = (*leading_zeros_threshold).min(20);
+            address[..leading as usize].fill(0);
+            let rest = total_zeros_threshold.saturating_sub(leading);
+            zero_any(&mut address[leading as usize..], rest, rng);
+        }
+        RewardVariant::LeadingOrTotalZeros {
+            leading_zeros_threshold,
+            total_zeros_threshold,
+        } => {
+            // each side drawn as often as it matches
+            let leading = 256f64.powi(-(*leading_zeros_threshold as i32));
+            let total = stats::total_zero_bytes_probability(*total_zeros_threshold as u32);
+            match rng.gen_bool(leading / (leading + total)) {
+                true => address[..(*leading_zeros_threshold as usize).min(20)].fill(0),
+                false => zero_any(&mut address, *total_zeros_threshold, rng),
+            }
+        }
+        // a dangling nibble can't be pinned as a byte
+        RewardVariant::LeadingZeroNibbles { threshold } => {
+            let threshold = (*threshold as usize).min(40);
+            address[..threshold / 2].fill(0);
+            if threshold % 2 == 1 {
+                address[threshold / 2] &= 0x0f;
+            }
+        }
+        RewardVariant::SymmetricEnds { nibbles } => {
+            let nibbles = (*nibbles as usize).min(20);
+            let nibble = |address: &[u8; 20], i: usize| (address[i / 2] >> (4 - 4 * (i % 2))) & 0xf;
+            for i in 0..nibbles {
+                let value = nibble(&address, i);
+                let j = 40 - nibbles + i;
+                let shift = 4 - 4 * (j % 2);
+                address[j / 2] = (address[j / 2] & !(0xf << shift)) | (value << shift);
+            }
+        }
+        // each pattern drawn as often as it matches
+        RewardVariant::MatchingAny { patterns } => {
+            let matching = |pattern: &str| RewardVariant::Matching {
+                pattern: pattern.into(),
+            };
+            let odds = patterns
+                .iter()
+                .map(|pattern| 1.0 / expected_attempts(&matching(pattern)));
+            let pick = WeightedIndex::new(odds).expect("patterns have odds");
+            return sample(&matching(&patterns[pick.sample(rng)]), rng);
+        }
+        RewardVariant::LeadingZeros { .. }
+        | RewardVariant::Matching { .. }
+        | RewardVariant::ChecksumMatching { .. } => {
+            if let Some(pinned) = Pinned::of(reward) {
+                pinned.apply(&mut address);
+            }
+        }
+    }
+    address
+}
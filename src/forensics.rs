@@ -0,0 +1,122 @@
+use std::{fs, io, path::Path};
+
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{kernel_source, Config, RESULT_WORDS};
+
+/// Results of every cycle re-derived on the CPU by `--strict` runs besides the reported ones,
+/// spread over the results read back.
+pub const SPOT_CHECKS: usize = 16;
+
+/// Results either side of the offending one kept in a dump.
+pub const CONTEXT_RESULTS: usize = 2;
+
+/// Sidecar path of the forensic dump of a `--strict` run writing to `output`.
+pub fn dump_path(output: &str) -> String {
+    format!("{}.forensic.json", output)
+}
+
+/// Indices of the [`SPOT_CHECKS`] results spot checked among the `entries` read back in a
+/// cycle, evenly spaced and including the first one.
+pub fn spot_checks(entries: usize) -> impl Iterator<Item = usize> {
+    let checks = entries.min(SPOT_CHECKS);
+    (0..checks).map(move |i| i * entries / checks)
+}
+
+/// A result the kernel derived differently from the CPU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disagreement {
+    /// Index of the result among those read back in the cycle.
+    pub index: usize,
+    pub nonce: u64,
+    pub salt: [u8; 32],
+    pub gpu_hash: [u8; 32],
+    pub cpu_hash: [u8; 32],
+}
+
+/// Derive result `index` of `results`, the words the kernel wrote for a cycle, again on the CPU.
+/// None if the CPU derives the same hash from its nonce.
+pub fn check(config: &Config, results: &[u32], index: usize) -> Option<Disagreement> {
+    let words = &results[index * RESULT_WORDS as usize..(index + 1) * RESULT_WORDS as usize];
+    let nonce = words[0] as u64 | (words[1] as u64) << 32;
+    let mut gpu_hash = [0u8; 32];
+    for (bytes, word) in gpu_hash.chunks_mut(4).zip(&words[2..]) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    let salt = config.salt_variant.salt(nonce);
+    let guarded_salt = config.salt_variant.guard(&salt);
+    let cpu_hash = config
+        .create_variant
+        .kernel_hash(&config.factory_address, &guarded_salt);
+    (gpu_hash != cpu_hash).then_some(Disagreement {
+        index,
+        nonce,
+        salt,
+        gpu_hash,
+        cpu_hash,
+    })
+}
+
+/// What a `--strict` run knew when the GPU and the CPU disagreed, written as JSON for bug
+/// reports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForensicDump {
+    /// Cycle of the run the result was read back in, from 1.
+    pub cycle: u64,
+    pub index: usize,
+    pub nonce: u64,
+    pub salt: String,
+    pub gpu_hash: String,
+    pub cpu_hash: String,
+    /// First line of the kernel source, naming the hash of the kernel and its constants, see
+    /// [`kernel_source`].
+    pub kernel: String,
+    pub reward: String,
+    pub create_variant: String,
+    pub salt_variant: String,
+    /// Name, backend and driver of the adapter.
+    pub adapter: String,
+    /// Index of the first result of `buffer` among those read back.
+    pub buffer_start: usize,
+    /// The words read back for the results around the offending one, in hex as mapped.
+    pub buffer: Vec<String>,
+}
+
+impl ForensicDump {
+    pub fn new(
+        config: &Config,
+        disagreement: &Disagreement,
+        cycle: u64,
+        results: &[u32],
+        adapter: &str,
+    ) -> Self {
+        let entries = results.len() / RESULT_WORDS as usize;
+        let start = disagreement.index.saturating_sub(CONTEXT_RESULTS);
+        let end = (disagreement.index + CONTEXT_RESULTS + 1).min(entries);
+        let words = &results[start * RESULT_WORDS as usize..end * RESULT_WORDS as usize];
+        Self {
+            cycle,
+            index: disagreement.index,
+            nonce: disagreement.nonce,
+            salt: format!("0x{}", hex::encode(disagreement.salt)),
+            gpu_hash: format!("0x{}", hex::encode(disagreement.gpu_hash)),
+            cpu_hash: format!("0x{}", hex::encode(disagreement.cpu_hash)),
+            kernel: kernel_source(config)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            reward: config.reward.to_string(),
+            create_variant: config.create_variant.to_string(),
+            salt_variant: config.salt_variant.to_string(),
+            adapter: adapter.to_string(),
+            buffer_start: start,
+            buffer: words.iter().map(|word| format!("{:08x}", word)).collect(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    io::{self, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+/// Way of reaching an HTTP JSON endpoint, such as a pool or an RPC node. Wraps HTTP so the
+/// client logic can be exercised without a server.
+pub trait Transport {
+    /// POST `body` as JSON to `path` below the endpoint URL and return the response body.
+    fn post(&mut self, path: &str, body: &str) -> Result<String, String>;
+
+    /// Whether the endpoint answered the last request with `429 Too Many Requests`, and how
+    /// long its `Retry-After` header asked to wait.
+    fn rate_limited(&self) -> Option<RateLimited> {
+        None
+    }
+}
+
+/// A request refused for exceeding the rate limit of the endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimited {
+    /// The wait of the `Retry-After` header, in seconds, if the response had one.
+    pub retry_after: Option<Duration>,
+}
+
+/// Transport of the command line: [`ReqwestTransport`], which speaks TLS, in builds with the
+/// `pool` feature, [`HttpTransport`] otherwise.
+#[cfg(feature = "pool")]
+pub type DefaultTransport = ReqwestTransport;
+#[cfg(not(feature = "pool"))]
+pub type DefaultTransport = HttpTransport;
+
+/// Plain HTTP/1.1 over TCP, one connection per request, reading the response body until the
+/// server closes the connection. Neither TLS nor chunked responses are supported, build with the
+/// `pool` feature for [`ReqwestTransport`] when the server is not on the local network.
+#[derive(Clone, Debug)]
+pub struct HttpTransport {
+    host: String,
+    base: String,
+    timeout: Duration,
+    rate_limited: Option<RateLimited>,
+}
+
+impl HttpTransport {
+    /// Transport to the endpoint at `url`, of the form `http://host:port/base`.
+    pub fn new(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| match url.starts_with("https://") {
+                true => format!("URL {} needs TLS, which takes a build with the pool feature", url),
+                false => format!("URL {} must start with http://", url),
+            })?;
+        let (host, base) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], rest[slash..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        if host.is_empty() {
+            return Err(format!("URL {} has no host", url));
+        }
+        let host = match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{}:80", host),
+        };
+
+        Ok(Self {
+            host,
+            base: base.to_string(),
+            timeout: Duration::from_secs(30),
+            rate_limited: None,
+        })
+    }
+}
+
+impl Transport for HttpTransport {
+    fn post(&mut self, path: &str, body: &str) -> Result<String, String> {
+        self.rate_limited = None;
+        let target = match format!("{}{}", self.base, path) {
+            target if target.is_empty() => "/".to_string(),
+            target => target,
+        };
+        let request = |stream: &mut TcpStream| -> io::Result<String> {
+            stream.set_read_timeout(Some(self.timeout))?;
+            stream.set_write_timeout(Some(self.timeout))?;
+            write!(
+                stream,
+                "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                target,
+                self.host,
+                body.len(),
+                body
+            )?;
+            stream.flush()?;
+
+            let mut response = String::new();
+            BufReader::new(stream).read_to_string(&mut response)?;
+            Ok(response)
+        };
+
+        let mut stream = TcpStream::connect(&self.host)
+            .map_err(|e| format!("could not reach {}: {}", self.host, e))?;
+        let response =
+            request(&mut stream).map_err(|e| format!("request to {} failed: {}", self.host, e))?;
+
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or("incomplete response")?;
+        let status = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .ok_or("invalid response")?;
+        if status == "429" {
+            let retry_after = head.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                match name.eq_ignore_ascii_case("retry-after") {
+                    true => value.trim().parse().ok().map(Duration::from_secs),
+                    false => None,
+                }
+            });
+            self.rate_limited = Some(RateLimited { retry_after });
+        }
+        if status != "200" {
+            return Err(format!(
+                "{}{} answered with status {}",
+                self.host, target, status
+            ));
+        }
+        Ok(body.to_string())
+    }
+
+    fn rate_limited(&self) -> Option<RateLimited> {
+        self.rate_limited
+    }
+}
+
+/// HTTP/1.1 and HTTPS through reqwest, keeping connections alive between requests.
+#[cfg(feature = "pool")]
+#[derive(Clone, Debug)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+    base: String,
+    rate_limited: Option<RateLimited>,
+}
+
+#[cfg(feature = "pool")]
+impl ReqwestTransport {
+    /// Transport to the endpoint at `url`, of the form `http://host:port/base` or
+    /// `https://host:port/base`.
+    pub fn new(url: &str) -> Result<Self, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("invalid URL {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("URL {} must start with http:// or https://", url));
+        }
+        if parsed.host_str().is_none_or(str::is_empty) {
+            return Err(format!("URL {} has no host", url));
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| format!("could not set up HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            base: url.trim_end_matches('/').to_string(),
+            rate_limited: None,
+        })
+    }
+}
+
+#[cfg(feature = "pool")]
+impl Transport for ReqwestTransport {
+    fn post(&mut self, path: &str, body: &str) -> Result<String, String> {
+        self.rate_limited = None;
+        let url = format!("{}{}", self.base, path);
+        let response = self
+            .client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .map_err(|e| format!("request to {} failed: {}", url, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs);
+            self.rate_limited = Some(RateLimited { retry_after });
+        }
+        if status != reqwest::StatusCode::OK {
+            return Err(format!("{} answered with status {}", url, status.as_u16()));
+        }
+        response
+            .text()
+            .map_err(|e| format!("request to {} failed: {}", url, e))
+    }
+
+    fn rate_limited(&self) -> Option<RateLimited> {
+        self.rate_limited
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::{hex, keccak256};
+
+/// Start of every salt index, the last byte is the format version.
+pub const MAGIC: [u8; 8] = *b"CXIDX\0\0\x01";
+
+/// The magic and the length of the results file the index covers.
+const HEADER_SIZE: usize = 16;
+
+/// Sidecar path of the salt index of the results written to `output`.
+pub fn index_path(output: &str) -> String {
+    format!("{}.idx", output)
+}
+
+/// The salt of a text, JSON or CSV result line, none for headers, comments and redacted
+/// results.
+pub fn salt_of_line(line: &str) -> Option<[u8; 32]> {
+    let salt = match line.trim_start().starts_with('{') {
+        true => serde_json::from_str::<serde_json::Value>(line).ok()?["salt"]
+            .as_str()?
+            .to_string(),
+        false => line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .next()?
+            .to_string(),
+    };
+    hex::decode(salt.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+
+/// The address of a text, JSON or CSV result line, none for headers and comments.
+pub fn address_of_line(line: &str) -> Option<[u8; 20]> {
+    let address = match line.trim_start().starts_with('{') {
+        true => serde_json::from_str::<serde_json::Value>(line).ok()?["address"]
+            .as_str()?
+            .to_string(),
+        false => match line.split_once(" -> ") {
+            Some((_, address)) => address.split_whitespace().next()?.to_string(),
+            None => line.split(',').nth(1)?.to_string(),
+        },
+    };
+    hex::decode(address.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+
+/// Index key of `salt`, the first 8 bytes of its hash. Keys of different salts can collide.
+pub fn salt_key(salt: &[u8; 32]) -> u64 {
+    u64::from_le_bytes(keccak256(salt)[..8].try_into().unwrap())
+}
+
+/// Keys of the salts in a results file, kept in `<output>.idx` so restarted runs skip results
+/// they already wrote without re-reading the results file. The index is the magic, the length
+/// of the results file it covers, and one little-endian key per result.
+pub struct SaltIndex {
+    file: File,
+    results: PathBuf,
+    keys: HashSet<u64>,
+    covered: u64,
+}
+
+impl SaltIndex {
+    /// Open the index of the results file at `results`. It is rebuilt from the results file
+    /// when it is missing, of another version, truncated, or does not cover the results file.
+    /// Returns whether it was rebuilt.
+    pub fn open(results: &Path) -> io::Result<(Self, bool)> {
+        let path = PathBuf::from(index_path(&results.to_string_lossy()));
+        let results_len = match fs::metadata(results) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        if let Some(keys) = Self::load(&file, results_len)? {
+            let index = Self {
+                file,
+                results: results.to_path_buf(),
+                keys,
+                covered: results_len,
+            };
+            return Ok((index, false));
+        }
+
+        let mut index = Self {
+            file,
+            results: results.to_path_buf(),
+            keys: HashSet::new(),
+            covered: 0,
+        };
+        index.rebuild()?;
+        Ok((index, true))
+    }
+
+    /// The keys of a valid index covering `results_len` bytes of results.
+    fn load(file: &File, results_len: u64) -> io::Result<Option<HashSet<u64>>> {
+        if file.metadata()?.len() < HEADER_SIZE as u64 {
+            return Ok(None);
+        }
+        // SAFETY: the index is only written by the writer of its results file, which is locked
+        let map = unsafe { memmap2::Mmap::map(file)? };
+        let (header, entries) = map.split_at(HEADER_SIZE);
+        let covered = u64::from_le_bytes(header[8..].try_into().unwrap());
+        if header[..8] != MAGIC || covered != results_len || entries.len() % 8 != 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            entries
+                .chunks(8)
+                .map(|key| u64::from_le_bytes(key.try_into().unwrap()))
+                .collect(),
+        ))
+    }
+
+    /// Rewrite the index from the salts in the results file.
+    pub fn rebuild(&mut self) -> io::Result<()> {
+        self.keys.clear();
+        self.covered = 0;
+        let mut entries = vec![];
+        match File::open(&self.results) {
+            Ok(results) => {
+                self.covered = results.metadata()?.len();
+                for line in BufReader::new(results).lines() {
+                    if let Some(salt) = salt_of_line(&line?) {
+                        let key = salt_key(&salt);
+                        if self.keys.insert(key) {
+                            entries.extend_from_slice(&key.to_le_bytes());
+                        }
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&MAGIC)?;
+        self.file.write_all(&self.covered.to_le_bytes())?;
+        self.file.write_all(&entries)?;
+        self.file.flush()
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether the results file holds `salt`. A key found in the index is confirmed against the
+    /// results file, as another salt may share it.
+    pub fn contains(&self, salt: &[u8; 32]) -> io::Result<bool> {
+        if !self.keys.contains(&salt_key(salt)) {
+            return Ok(false);
+        }
+        for line in BufReader::new(File::open(&self.results)?).lines() {
+            if salt_of_line(&line?).as_ref() == Some(salt) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Addresses of the last `limit` results in the results file, oldest first.
+    pub fn recent_addresses(&self, limit: usize) -> io::Result<Vec<[u8; 20]>> {
+        let mut addresses = VecDeque::with_capacity(limit);
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        let results = match File::open(&self.results) {
+            Ok(results) => results,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(results).lines() {
+            if let Some(address) = address_of_line(&line?) {
+                if addresses.len() == limit {
+                    addresses.pop_front();
+                }
+                addresses.push_back(address);
+            }
+        }
+        Ok(addresses.into())
+    }
+
+    /// Record `salt`, just appended to the results file in a line of `written` bytes.
+    pub fn insert(&mut self, salt: &[u8; 32], written: u64) -> io::Result<()> {
+        let key = salt_key(salt);
+        if self.keys.insert(key) {
+            self.file.seek(SeekFrom::End(0))?;
+            self.file.write_all(&key.to_le_bytes())?;
+        }
+        self.covered += written;
+        self.file.seek(SeekFrom::Start(8))?;
+        self.file.write_all(&self.covered.to_le_bytes())?;
+        self.file.flush()
+    }
+}
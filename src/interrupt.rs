@@ -0,0 +1,35 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Once,
+};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INSTALL: Once = Once::new();
+
+/// Catch Ctrl+C (SIGINT) and SIGTERM, so the miner stops after the current batch and closes
+/// the output file cleanly instead of dying with results unwritten. A second signal exits at
+/// once. Other platforms keep the default handling.
+pub fn install() {
+    INSTALL.call_once(|| {
+        #[cfg(unix)]
+        // SAFETY: the handler only touches an atomic and calls the async-signal-safe _exit
+        unsafe {
+            let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::signal(libc::SIGINT, handler);
+            libc::signal(libc::SIGTERM, handler);
+        }
+    });
+}
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        // SAFETY: _exit is async-signal-safe
+        unsafe { libc::_exit(128 + signal) };
+    }
+}
+
+/// Whether the run was interrupted since [`install`].
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,138 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use naga::{
+    front::wgsl,
+    valid::{Capabilities, ValidationFlags, Validator},
+    AddressSpace, Module, ShaderStage, StorageAccess,
+};
+
+use crate::KECCAK256_KERNEL;
+
+/// Entry point of the mining kernel.
+pub const ENTRY_POINT: &str = "main";
+
+/// Override constants the miner sets on the mining kernel, see [`crate::kernel_constants`].
+pub const OVERRIDES: [&str; 4] = [
+    "DERIVATION",
+    "DUAL_SALT",
+    "SALT_GUARD_SIZE",
+    "WORKGROUP_SIZE",
+];
+
+/// Read the `--kernel-file` at `path` and [`check`] it, returning its source.
+pub fn load(path: &Path) -> Result<String, String> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(format!("kernel file {} not found", path.display()))
+        }
+        Err(e) => {
+            return Err(format!(
+                "could not read kernel file {}: {}",
+                path.display(),
+                e
+            ))
+        }
+    };
+    check(&source, &path.display().to_string())?;
+    Ok(source)
+}
+
+/// Check that `source`, a kernel named `name` in errors, can stand in for the embedded
+/// [`KECCAK256_KERNEL`]: it parses and validates, has a compute entry point `main`, declares the
+/// override constants the miner sets and binds its resources as the miner does.
+pub fn check(source: &str, name: &str) -> Result<(), String> {
+    let module = wgsl::parse_str(source).map_err(|e| match e.location(source) {
+        Some(location) => format!(
+            "kernel file {}:{}:{}: {}",
+            name,
+            location.line_number,
+            location.line_position,
+            e.message()
+        ),
+        None => format!("kernel file {}: {}", name, e.message()),
+    })?;
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| {
+            let location = e.location(source);
+            // the error only names the function, its sources say what is wrong in it
+            let mut error = e.as_inner().to_string();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(next) = cause {
+                error += &format!(": {}", next);
+                cause = next.source();
+            }
+            match location {
+                Some(location) => format!(
+                    "kernel file {}:{}:{} does not validate: {}",
+                    name, location.line_number, location.line_position, error
+                ),
+                None => format!("kernel file {} does not validate: {}", name, error),
+            }
+        })?;
+
+    if !module
+        .entry_points
+        .iter()
+        .any(|entry| entry.name == ENTRY_POINT && entry.stage == ShaderStage::Compute)
+    {
+        return Err(format!(
+            "kernel file {} has no compute entry point `{}`",
+            name, ENTRY_POINT
+        ));
+    }
+
+    if let Some(missing) = OVERRIDES.iter().find(|constant| {
+        !module
+            .overrides
+            .iter()
+            .any(|(_, declared)| declared.name.as_deref() == Some(**constant))
+    }) {
+        return Err(format!(
+            "kernel file {} does not declare the override constant {}",
+            name, missing
+        ));
+    }
+
+    let expected =
+        bindings(&wgsl::parse_str(KECCAK256_KERNEL).expect("the embedded kernel parses"));
+    for ((group, binding), space) in bindings(&module) {
+        match expected.get(&(group, binding)) {
+            Some(bound) if *bound == space => {}
+            Some(bound) => {
+                return Err(format!(
+                    "kernel file {} declares @group({}) @binding({}) as var<{}>, the miner binds var<{}> there",
+                    name, group, binding, space, bound
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "kernel file {} declares @group({}) @binding({}), which the miner doesn't bind",
+                    name, group, binding
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Address spaces of the resources of `module`, by group and binding, as written in WGSL.
+fn bindings(module: &Module) -> BTreeMap<(u32, u32), String> {
+    module
+        .global_variables
+        .iter()
+        .filter_map(|(_, variable)| {
+            let binding = variable.binding.as_ref()?;
+            let space = match variable.space {
+                AddressSpace::Uniform => "uniform".to_string(),
+                AddressSpace::Storage { access } if access.contains(StorageAccess::STORE) => {
+                    "storage, read_write".to_string()
+                }
+                AddressSpace::Storage { .. } => "storage, read".to_string(),
+                space => format!("{:?}", space),
+            };
+            Some(((binding.group, binding.binding), space))
+        })
+        .collect()
+}
@@ -21,18 +21,26 @@ const ROTATION_INDICES = array<u32, 48>(
 );
 
 // Optimized rotate left function for 64-bit values (represented as 2x32)
+// Takes the low word first and returns the rotated (low, high) pair
 fn rotl64(x_high: u32, x_low: u32, s: u32) -> vec2<u32> {
     var result: vec2<u32>;
+    let shift = s % 32u;
     
+    // For shifts >= 32, we swap high and low
+    var a = x_high;
+    var b = x_low;
     if (s >= 32u) {
-        // For shifts >= 32, we swap high and low
-        let actual_shift = s - 32u;
-        result.x = (x_low << actual_shift) | (x_high >> (32u - actual_shift));
-        result.y = (x_high << actual_shift) | (x_low >> (32u - actual_shift));
+        a = x_low;
+        b = x_high;
+    }
+    
+    // Shifting a u32 by 32 is masked to a shift by 0, so whole-word rotations are special
+    if (shift == 0u) {
+        result.x = a;
+        result.y = b;
     } else {
-        // For shifts < 32, we keep components in same place
-        result.x = (x_high << s) | (x_low >> (32u - s));
-        result.y = (x_low << s) | (x_high >> (32u - s));
+        result.x = (a << shift) | (b >> (32u - shift));
+        result.y = (b << shift) | (a >> (32u - shift));
     }
     
     return result;
@@ -88,13 +96,13 @@ fn keccak_f1600(state: ptr<function, array<u32, 50>>) {
             let temp_high = rotated.x;
             let temp_low = rotated.y;
             
-            // Get next word from permutation
-            t_high = (*state)[permutation*2];
-            t_low = (*state)[permutation*2+1];
+            // Get next word from permutation (permutation values are already word offsets)
+            t_high = (*state)[permutation];
+            t_low = (*state)[permutation+1];
             
             // Update state with rotated word
-            (*state)[permutation*2] = temp_high;
-            (*state)[permutation*2+1] = temp_low;
+            (*state)[permutation] = temp_high;
+            (*state)[permutation+1] = temp_low;
         }
         
         // Chi step
@@ -122,20 +130,22 @@ fn keccak_f1600(state: ptr<function, array<u32, 50>>) {
     }
 }
 
-// The rest of the keccak256 implementation using the optimized keccak_f1600
+// The rest of the keccak256 implementation using the optimized keccak_f1600.
+// Hashes the first input_len bytes of a single block, the input past them must be zero
 fn keccak256(input: ptr<function, array<u32, KECCAK256_INPUT_BUF_SIZE>>, input_len:u32, output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>) {
     var state: array<u32, 50>; // Initialize to zeros
     
     // Pre-process input data
     // XOR input data into state
-    for (var i: u32 = 0u; i < input_len && i < 17u*2u; i = i + 1u) {
+    let input_words = (input_len + 3u) / 4u;
+    for (var i: u32 = 0u; i < input_words && i < 17u*2u; i = i + 1u) {
         state[i] = (*input)[i];
     }
     
-    // Add padding: first byte of padding is 0x01
-    let end_idx = input_len; 
+    // Add padding: first byte of padding is 0x01, right after the input
+    let end_idx = input_len / 4u;
     if (end_idx < 17u*2u) {
-        state[end_idx] = state[end_idx] ^ 0x01u;
+        state[end_idx] = state[end_idx] ^ (0x01u << ((input_len % 4u) * 8u));
         
         // Add end padding byte (0x80) at end of block
         state[17u*2u-1u] = state[17u*2u-1u] ^ 0x80000000u;
@@ -155,18 +165,210 @@ fn keccak256(input: ptr<function, array<u32, KECCAK256_INPUT_BUF_SIZE>>, input_l
 @group(0) @binding(2) var<storage, read> message_sizes: array<u32>;
 @group(0) @binding(3) var<storage, read_write> hashes: array<u32>;
 @group(0) @binding(4) var<storage, read_write> log_buffer: array<u32>;
-@group(0) @binding(5) var<storage, read> pattern_config: array<u32, 4>; // [pattern_value, pattern_type, pattern_length, reserved]
+// [pattern_value, pattern_type, pattern_length, placement, prefix_length, prefix (5 words)],
+// see `MatchPlacement::kernel_config` on the host. With PLACEMENT_GPU_ZERO_NIBBLES the prefix
+// length is the number of leading zero nibbles instead
+@group(0) @binding(5) var<storage, read> pattern_config: array<u32, 10>;
+// Number of matches written to `hashes` when matching on the GPU
+@group(0) @binding(8) var<storage, read_write> match_count: atomic<u32>;
+// With CPU matching and a coarse prefix, one word per workgroup flagging which groups of
+// BITMAP_THREADS_PER_BIT threads have a hash passing the prefix, see `BatchGeometry` on the host
+@group(0) @binding(9) var<storage, read_write> match_bitmap: array<atomic<u32>>;
+// The CREATE2 preimage with a zero salt (words 0 to 21), then the guard preimage of the salt
+// of nonce 0 (from word 24), see `derivation_config` on the host. A uniform, as the storage
+// buffers are at the default limit of 8
+@group(0) @binding(10) var<uniform> derivation: array<vec4<u32>, 12>;
+// With DERIVATION_CREATE2 the guarded salt is hashed into a CREATE2 address, with
+// DERIVATION_CREATE3 into the address the CREATE3 proxy deploys to, otherwise it is kept on its
+// own. Set per pipeline, see `kernel_derivation` on the host, so the other paths compile away
+override DERIVATION: u32 = 0u;
+// Bytes of the guard preimage, ending in the big-endian nonce, see `kernel_guard_size` on the host
+override SALT_GUARD_SIZE: u32 = 32u;
+// Threads per workgroup, see `--workgroup-size` on the host
+override WORKGROUP_SIZE: u32 = 256u;
+// With --dual-salt a nonce only passes when the random framing of its salt passes too, see
+// `DualSalt` on the host. The crosschain framing is the configured salt variant
+override DUAL_SALT: u32 = 0u;
+
+const PLACEMENT_CPU: u32 = 0u;
+const PLACEMENT_GPU: u32 = 1u;
+const PLACEMENT_GPU_ZERO_NIBBLES: u32 = 2u;
+const DERIVATION_CREATE2: u32 = 1u;
+const DERIVATION_CREATE3: u32 = 2u;
+// Bytes of the CREATE2 preimage: 0xff, the deployer, the salt and the init code hash
+const CREATE2_PREIMAGE_SIZE: u32 = 85u;
+// Bytes of the RLP of [proxy, 1]: 0xd6, 0x94, the proxy and the nonce 0x01
+const FIRST_CREATE_PREIMAGE_SIZE: u32 = 23u;
+override BITMAP_THREADS_PER_BIT: u32 = (WORKGROUP_SIZE + 31u) / 32u;
+// Offset of the address in the hash, see `ADDRESS_OFFSET` on the host
+const ADDRESS_OFFSET: u32 = 12u;
 
-// Helper function to extract a byte from the output buffer
+// Helper function to extract a byte from the output buffer (lanes are little-endian)
 fn get_byte_from_hash(output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>, byte_idx: u32) -> u32 {
     let word_idx = byte_idx / 4u;
     let byte_offset = byte_idx % 4u;
-    let shift_amount = 24u - (byte_offset * 8u); // Convert byte offset to bit shift
+    let shift_amount = byte_offset * 8u; // Convert byte offset to bit shift
     return ((*output)[word_idx] >> shift_amount) & 0xFFu;
 }
 
-@compute @workgroup_size(256)
-fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+fn derivation_word(index: u32) -> u32 {
+    return derivation[index / 4u][index % 4u];
+}
+
+fn byte_swap(x: u32) -> u32 {
+    return (x >> 24u) | ((x >> 8u) & 0xFF00u) | ((x << 8u) & 0xFF0000u) | (x << 24u);
+}
+
+// Whether the address part of the hash starts with the configured prefix
+fn matches_prefix(output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>) -> bool {
+    let prefix_length = pattern_config[4];
+    for (var i: u32 = 0u; i < prefix_length; i = i + 1u) {
+        let expected = (pattern_config[5u + i / 4u] >> ((i % 4u) * 8u)) & 0xFFu;
+        if (get_byte_from_hash(output, ADDRESS_OFFSET + i) != expected) {
+            return false;
+        }
+    }
+    return true;
+}
+
+// Whether the address part of the hash starts with the configured number of zero nibbles,
+// the high then the low nibble of each byte
+fn matches_zero_nibbles(output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>) -> bool {
+    let nibbles = pattern_config[4];
+    for (var i: u32 = 0u; i < nibbles / 2u; i = i + 1u) {
+        if (get_byte_from_hash(output, ADDRESS_OFFSET + i) != 0u) {
+            return false;
+        }
+    }
+    if (nibbles % 2u == 1u) {
+        return (get_byte_from_hash(output, ADDRESS_OFFSET + nibbles / 2u) >> 4u) == 0u;
+    }
+    return true;
+}
+
+// Whether the hash passes the match of a GPU placement
+fn matches_on_gpu(output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>) -> bool {
+    if (pattern_config[3] == PLACEMENT_GPU_ZERO_NIBBLES) {
+        return matches_zero_nibbles(output);
+    }
+    return matches_prefix(output);
+}
+
+// Scan orders, see `ScanOrder` on the host. 64-bit values are (low, high) pairs
+const SCAN_ORDER_LINEAR: u32 = 0u;
+const SCAN_ORDER_LCG: u32 = 1u;
+const SCAN_ORDER_SPLITMIX: u32 = 2u;
+
+// Full 64-bit product of two u32, built from 16-bit halves
+fn mul32_wide(a: u32, b: u32) -> vec2<u32> {
+    let a0 = a & 0xFFFFu;
+    let a1 = a >> 16u;
+    let b0 = b & 0xFFFFu;
+    let b1 = b >> 16u;
+    
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    
+    let mid = (p00 >> 16u) + (p01 & 0xFFFFu) + (p10 & 0xFFFFu);
+    let low = (p00 & 0xFFFFu) | (mid << 16u);
+    let high = p11 + (p01 >> 16u) + (p10 >> 16u) + (mid >> 16u);
+    return vec2<u32>(low, high);
+}
+
+// Wrapping 64-bit multiplication
+fn mul64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let low = mul32_wide(a.x, b.x);
+    return vec2<u32>(low.x, low.y + a.x * b.y + a.y * b.x);
+}
+
+// Wrapping 64-bit addition
+fn add64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let low = a.x + b.x;
+    return vec2<u32>(low, a.y + b.y + select(0u, 1u, low < a.x));
+}
+
+// z ^ (z >> shift) for 0 < shift < 32
+fn xor_shr64(z: vec2<u32>, shift: u32) -> vec2<u32> {
+    let low = (z.x >> shift) | (z.y << (32u - shift));
+    return vec2<u32>(z.x ^ low, z.y ^ (z.y >> shift));
+}
+
+// Map a scan index to the nonce mined at that position, a bijection on 64-bit values
+fn scan_nonce(index: vec2<u32>, order: u32, seed: vec2<u32>) -> vec2<u32> {
+    if (order == SCAN_ORDER_LCG) {
+        // Knuth's MMIX multiplier and increment applied to the seeded index
+        let multiplier = vec2<u32>(0x4C957F2Du, 0x5851F42Du);
+        let increment = vec2<u32>(0xF767814Fu, 0x14057B7Eu);
+        return add64(mul64(add64(index, seed), multiplier), increment);
+    }
+    if (order == SCAN_ORDER_SPLITMIX) {
+        // splitmix64 output for state seed + (index + 1) * golden gamma
+        let gamma = vec2<u32>(0x7F4A7C15u, 0x9E3779B9u);
+        var z = add64(seed, mul64(add64(index, vec2<u32>(1u, 0u)), gamma));
+        z = mul64(xor_shr64(z, 30u), vec2<u32>(0x1CE4E5B9u, 0xBF58476Du));
+        z = mul64(xor_shr64(z, 27u), vec2<u32>(0x133111EBu, 0x94D049BBu));
+        return xor_shr64(z, 31u);
+    }
+    return index;
+}
+
+// Hash the guarded salt in `output` into the hash the address is taken from, see DERIVATION
+fn derive_address(input: ptr<function, array<u32, KECCAK256_INPUT_BUF_SIZE>>, output: ptr<function, array<u32, KECCAK256_OUTPUT_SIZE>>) {
+    if (DERIVATION != 0u) {
+        // The CREATE2 preimage, with the guarded salt at bytes 21 to 52 (straddling words 5
+        // to 13)
+        for (var i: u32 = 0u; i < 22u; i = i + 1u) {
+            (*input)[i] = derivation_word(i);
+        }
+        for (var i: u32 = 0u; i < 8u; i = i + 1u) {
+            (*input)[5u + i] = (*input)[5u + i] | ((*output)[i] << 8u);
+            (*input)[6u + i] = (*input)[6u + i] | ((*output)[i] >> 24u);
+        }
+        keccak256(input, CREATE2_PREIMAGE_SIZE, output);
+    }
+
+    if (DERIVATION == DERIVATION_CREATE3) {
+        // The proxy deploys the contract with its first CREATE, so hash rlp([proxy, 1]) with the
+        // proxy address taken from bytes 12 to 31 (words 3 to 7) of the CREATE2 hash
+        (*input)[0] = 0x94d6u | ((*output)[3] << 16u);
+        for (var i: u32 = 1u; i < 5u; i = i + 1u) {
+            (*input)[i] = ((*output)[i + 2u] >> 16u) | ((*output)[i + 3u] << 16u);
+        }
+        (*input)[5] = ((*output)[7] >> 16u) | (0x01u << 16u);
+        for (var i: u32 = 6u; i < 22u; i = i + 1u) {
+            (*input)[i] = 0u;
+        }
+        keccak256(input, FIRST_CREATE_PREIMAGE_SIZE, output);
+    }
+}
+
+// Whether the random framing of the salt of `nonce` passes the GPU match, or the coarse prefix
+// when matching on the CPU: a zero sender part, the flag 0x00, 3 zero bytes and the big-endian
+// nonce, guarded as keccak256(salt). Always passes without DUAL_SALT
+fn random_framing_matches(nonce: vec2<u32>) -> bool {
+    if (DUAL_SALT == 0u) {
+        return true;
+    }
+    var input: array<u32, KECCAK256_INPUT_BUF_SIZE>;
+    var output: array<u32, KECCAK256_OUTPUT_SIZE>;
+    input[6] = byte_swap(nonce.y);
+    input[7] = byte_swap(nonce.x);
+    keccak256(&input, 32u, &output);
+    derive_address(&input, &output);
+    if (pattern_config[3] != PLACEMENT_CPU) {
+        return matches_on_gpu(&output);
+    }
+    return matches_prefix(&output);
+}
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
     let index = global_id.x;
     
     // Early exit for threads beyond our work size
@@ -182,27 +384,49 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let base_nonce_low = messages[0];  // Low 32 bits of nonce
     let base_nonce_high = messages[1]; // High 32 bits of nonce
     
-    // Add the thread index to create a unique nonce for each thread
-    // This effectively processes nonces from base_nonce to base_nonce + num_messages - 1
-    let nonce_low = base_nonce_low + index;
+    // Add the thread index to create a unique scan index for each thread
+    // This effectively processes indices from base_nonce to base_nonce + num_messages - 1
+    let index_low = base_nonce_low + index;
     // Handle carry for overflow using select() instead of if expression
-    let nonce_high = base_nonce_high + select(0u, 1u, nonce_low < base_nonce_low);
+    let index_high = base_nonce_high + select(0u, 1u, index_low < base_nonce_low);
     
-    // Set the input with the combined nonce
-    input[0] = nonce_low;  // Low 32 bits of nonce
-    input[1] = nonce_high; // High 32 bits of nonce
+    // Permute the scan index into the nonce to mine (words 2 to 4: scan order and seed)
+    let nonce = scan_nonce(vec2<u32>(index_low, index_high), messages[2], vec2<u32>(messages[3], messages[4]));
+    let nonce_low = nonce.x;
+    let nonce_high = nonce.y;
     
-    // Zero out the rest of the input
-    for (var i: u32 = 2u; i < KECCAK256_INPUT_BUF_SIZE; i = i + 1u) {
+    // Zero out the input
+    for (var i: u32 = 0u; i < KECCAK256_INPUT_BUF_SIZE; i = i + 1u) {
         input[i] = 0u;
     }
     
-    // Perform keccak256 hash
-    keccak256(&input, 8u, &output); // Using 8 as input_len (32 bytes, 8 u32s)
+    // Guard the salt as CreateX does, the nonce ends both the salt and its guard preimage
+    for (var i: u32 = 0u; i < SALT_GUARD_SIZE / 4u; i = i + 1u) {
+        input[i] = derivation_word(24u + i);
+    }
+    input[SALT_GUARD_SIZE / 4u - 2u] = byte_swap(nonce_high);
+    input[SALT_GUARD_SIZE / 4u - 1u] = byte_swap(nonce_low);
+    keccak256(&input, SALT_GUARD_SIZE, &output);
+
+    derive_address(&input, &output);
+    
+    // With GPU matching only hits are stored, packed at the front of the output buffer.
+    // Otherwise every salt and hash is stored for CPU validation
+    var slot = index;
+    if (pattern_config[3] != PLACEMENT_CPU) {
+        if (!matches_on_gpu(&output) || !random_framing_matches(nonce)) {
+            return;
+        }
+        slot = atomicAdd(&match_count, 1u);
+        if ((slot + 1u) * (2u + KECCAK256_OUTPUT_SIZE) > arrayLength(&hashes)) {
+            return;
+        }
+    } else if (pattern_config[4] > 0u && matches_prefix(&output) && random_framing_matches(nonce)) {
+        atomicOr(&match_bitmap[workgroup_id.x], 1u << (local_index / BITMAP_THREADS_PER_BIT));
+    }
     
-    // Just store salt and hash in the output buffer for CPU validation
     // Save the nonce along with its hash
-    let base_index = index * (2 + KECCAK256_OUTPUT_SIZE); // 2 words for nonce, 8 words for hash
+    let base_index = slot * (2 + KECCAK256_OUTPUT_SIZE); // 2 words for nonce, 8 words for hash
     
     // Store nonce
     hashes[base_index] = nonce_low;
@@ -213,3 +437,64 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         hashes[base_index + 2 + i] = output[i];
     }
 }
+
+// Self-test entry points, used by `--self-test` to compare the u32 pair emulation of 64-bit
+// lanes against native u64 arithmetic on the CPU
+@group(0) @binding(6) var<storage, read> self_test_input: array<u32>;
+@group(0) @binding(7) var<storage, read_write> self_test_output: array<u32>;
+
+// One invocation per (lane, rotation amount) pair: rotates lane index / 64 of the input state
+// by index % 64
+@compute @workgroup_size(64)
+fn rotation_self_test(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index >= 25u * 64u) {
+        return;
+    }
+    
+    let lane = index / 64u;
+    let amount = index % 64u;
+    let rotated = rotl64(self_test_input[lane * 2u], self_test_input[lane * 2u + 1u], amount);
+    
+    self_test_output[index * 2u] = rotated.x;
+    self_test_output[index * 2u + 1u] = rotated.y;
+}
+
+// One invocation per 32-byte input: hashes input words index * 8 to index * 8 + 7
+@compute @workgroup_size(64)
+fn keccak_self_test(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if ((index + 1u) * 8u > arrayLength(&self_test_input)) {
+        return;
+    }
+    
+    var input: array<u32, KECCAK256_INPUT_BUF_SIZE>;
+    var output: array<u32, KECCAK256_OUTPUT_SIZE>;
+    
+    for (var i: u32 = 0u; i < 8u; i = i + 1u) {
+        input[i] = self_test_input[index * 8u + i];
+    }
+    
+    keccak256(&input, 32u, &output);
+    
+    for (var i: u32 = 0u; i < KECCAK256_OUTPUT_SIZE; i = i + 1u) {
+        self_test_output[index * 8u + i] = output[i];
+    }
+}
+
+// One invocation per scan index: input is [order, seed low, seed high, base low, base high],
+// output the (low, high) nonce of scan index base + index
+@compute @workgroup_size(64)
+fn scan_order_self_test(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if ((index + 1u) * 2u > arrayLength(&self_test_output)) {
+        return;
+    }
+    
+    let base = vec2<u32>(self_test_input[3], self_test_input[4]);
+    let seed = vec2<u32>(self_test_input[1], self_test_input[2]);
+    let nonce = scan_nonce(add64(base, vec2<u32>(index, 0u)), self_test_input[0], seed);
+    
+    self_test_output[index * 2u] = nonce.x;
+    self_test_output[index * 2u + 1u] = nonce.y;
+}
@@ -1,18 +1,144 @@
-use alloy_primitives::{hex, Address};
+use alloy_primitives::{hex, keccak256, Address, U256};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, ops::Range, str::FromStr, time::Duration};
+
+#[cfg(feature = "miner")]
+use bus::EventBus;
+#[cfg(feature = "miner")]
+use channel::Backpressure;
+#[cfg(feature = "miner")]
+use diagnostics::GpuBackend;
+#[cfg(feature = "miner")]
 use fs4::FileExt;
+#[cfg(feature = "miner")]
+use rand::RngCore;
+#[cfg(feature = "miner")]
+use ranges::NonceRanges;
+#[cfg(feature = "miner")]
+use scan::ScanOrder;
+#[cfg(feature = "miner")]
+use schedule::Schedule;
+#[cfg(feature = "miner")]
+use selftest::SelfTestMode;
+#[cfg(feature = "miner")]
+use stats::CostModel;
+#[cfg(feature = "miner")]
 use std::{
     fs::{File, OpenOptions},
+    io::{BufRead, BufReader},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
+#[cfg(feature = "miner")]
+use warnings::WarningCode;
+#[cfg(feature = "miner")]
+use writer::{DuplicatePolicy, SplitBy};
+
+#[cfg(feature = "miner")]
+pub mod batch;
+#[cfg(feature = "miner")]
+pub mod bundle;
+#[cfg(feature = "miner")]
+pub mod bus;
+#[cfg(feature = "miner")]
+pub mod campaign;
+pub mod canonical;
+#[cfg(feature = "miner")]
+pub mod channel;
+#[cfg(feature = "miner")]
+pub mod checkpoint;
+pub mod checksum;
+#[cfg(feature = "miner")]
+pub mod clipboard;
+#[cfg(feature = "miner")]
+pub mod config_file;
+#[cfg(feature = "miner")]
+pub mod cycle_stats;
+#[cfg(feature = "miner")]
+pub mod deploy_tx;
+#[cfg(feature = "miner")]
+pub mod diagnostics;
+#[cfg(feature = "miner")]
+pub mod events;
+#[cfg(feature = "miner")]
+pub mod factory;
+#[cfg(feature = "miner")]
+pub mod feasibility;
+#[cfg(feature = "miner")]
+pub mod forensics;
+#[cfg(feature = "miner")]
+pub mod http;
+#[cfg(feature = "miner")]
+pub mod index;
+#[cfg(feature = "miner")]
+pub mod interrupt;
+#[cfg(feature = "miner")]
+pub mod kernel_file;
+#[cfg(feature = "miner")]
+pub mod plan;
+#[cfg(feature = "miner")]
+pub mod params;
+#[cfg(feature = "miner")]
+pub mod pattern_file;
+#[cfg(feature = "miner")]
+pub mod pool;
+#[cfg(feature = "miner")]
+pub mod presets;
+pub mod probability;
+#[cfg(feature = "miner")]
+pub mod ranges;
+#[cfg(feature = "miner")]
+pub mod redact;
+#[cfg(feature = "miner")]
+pub mod replay;
+#[cfg(feature = "miner")]
+pub mod reserved;
+#[cfg(feature = "miner")]
+pub mod rpc;
+#[cfg(feature = "miner")]
+pub mod safe;
+#[cfg(feature = "miner")]
+pub mod scan;
+pub mod schemas;
+#[cfg(feature = "miner")]
+pub mod schedule;
+#[cfg(feature = "miner")]
+pub mod selftest;
+pub mod stats;
+#[cfg(feature = "miner")]
+pub mod supervise;
+pub mod verify;
+#[cfg(feature = "miner")]
+pub mod warnings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "miner")]
+pub mod webgpu;
+#[cfg(feature = "miner")]
+pub mod writer;
 
+/// WGSL source of the keccak256 mining kernel.
+pub const KECCAK256_KERNEL: &str = include_str!("kernels/keccak256.wgsl");
+
+/// Rendered as `create3` or `create2(0x<init code hash>)`, see [`canonical`].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CreateVariant {
     Create2 { init_code_hash: [u8; 32] },
     Create3,
 }
 
+/// Rendered as e.g. `leading-zeros(5)`, `leading-zero-nibbles(9)`, `pattern(dead...beef)`, `any-pattern(ba5e...,cafe...)`
+/// or `checksum-pattern(DeaD...)`, see [`canonical`].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RewardVariant {
     LeadingZeros {
         zeros_threshold: u8,
     },
+    /// Leading zeros counted in nibbles, so an odd threshold ends on the high nibble of a byte.
+    LeadingZeroNibbles {
+        threshold: u8,
+    },
     TotalZeros {
         zeros_threshold: u8,
     },
@@ -27,8 +153,24 @@ pub enum RewardVariant {
     Matching {
         pattern: Box<str>,
     },
+    /// Several `--matching` patterns, of which an address must match any. Results record the
+    /// pattern they matched as a [`RewardVariant::Matching`].
+    MatchingAny {
+        patterns: Vec<Box<str>>,
+    },
+    /// A `--matching` pattern compared against the EIP-55 checksummed address, letters
+    /// included with their case, see [`checksum_matches`].
+    ChecksumMatching {
+        pattern: Box<str>,
+    },
+    SymmetricEnds {
+        nibbles: u8,
+    },
 }
 
+/// Rendered as `random`, `sender(0x…)`, `chain(8453)` or `sender(0x…)+chain(8453)`, see
+/// [`canonical`].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SaltVariant {
     CrosschainSender {
         chain_id: [u8; 32],
@@ -43,62 +185,755 @@ pub enum SaltVariant {
     Random,
 }
 
+impl SaltVariant {
+    /// The salt CreateX actually deploys with for a user supplied `salt` (its `_guard` step).
+    /// The layout of `salt` itself must match the variant: the first 20 bytes are the caller or
+    /// zero, and byte 20 is the cross-chain redeploy protection flag.
+    pub fn guard(&self, salt: &[u8; 32]) -> [u8; 32] {
+        keccak256(self.guard_preimage(salt)).0
+    }
+
+    /// The bytes hashed by [`SaltVariant::guard`].
+    pub fn guard_preimage(&self, salt: &[u8; 32]) -> Vec<u8> {
+        match self {
+            SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            } => {
+                let mut preimage = vec![0u8; 96];
+                preimage[12..32].copy_from_slice(calling_address);
+                preimage[32..64].copy_from_slice(chain_id);
+                preimage[64..].copy_from_slice(salt);
+                preimage
+            }
+            SaltVariant::Crosschain { chain_id } => [chain_id.as_slice(), salt.as_slice()].concat(),
+            SaltVariant::Sender { calling_address } => {
+                let mut preimage = vec![0u8; 64];
+                preimage[12..32].copy_from_slice(calling_address);
+                preimage[32..].copy_from_slice(salt);
+                preimage
+            }
+            SaltVariant::Random => salt.to_vec(),
+        }
+    }
+
+    /// A salt laid out for the variant: the caller or zero, the redeploy protection flag, and
+    /// `entropy` in the remaining 11 bytes.
+    pub fn salt_with_entropy(&self, entropy: &[u8; 11]) -> [u8; 32] {
+        let mut salt = [0u8; 32];
+        if let SaltVariant::CrosschainSender {
+            calling_address, ..
+        }
+        | SaltVariant::Sender { calling_address } = self
+        {
+            salt[..20].copy_from_slice(calling_address);
+        }
+        if let SaltVariant::CrosschainSender { .. } | SaltVariant::Crosschain { .. } = self {
+            salt[20] = 0x01;
+        }
+        salt[21..].copy_from_slice(entropy);
+        salt
+    }
+
+    /// The variant CreateX guards a user supplied `salt` with when `caller` deploys on
+    /// `chain_id`: sender protection when the salt starts with the caller, cross-chain redeploy
+    /// protection when byte 20 is 0x01. Salts CreateX reverts on, and protected salts without
+    /// the caller or chain ID they need, are errors.
+    pub fn of_salt(
+        salt: &[u8; 32],
+        caller: Option<&[u8; 20]>,
+        chain_id: Option<u64>,
+    ) -> Result<Self, String> {
+        let chain_id = || {
+            let chain_id = chain_id
+                .ok_or("the salt enables cross-chain redeploy protection, pass --chain-id")?;
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&chain_id.to_be_bytes());
+            Ok::<_, String>(bytes)
+        };
+        let sender = match caller {
+            Some(caller) if salt[..20] == *caller => Some(*caller),
+            _ => None,
+        };
+        match (sender, salt[..20] == [0; 20], salt[20]) {
+            (Some(calling_address), _, 0x01) => Ok(SaltVariant::CrosschainSender {
+                chain_id: chain_id()?,
+                calling_address,
+            }),
+            (Some(calling_address), _, 0x00) => Ok(SaltVariant::Sender { calling_address }),
+            (None, true, 0x01) => Ok(SaltVariant::Crosschain {
+                chain_id: chain_id()?,
+            }),
+            (Some(_), _, flag) | (None, true, flag) if flag > 0x01 => Err(format!(
+                "CreateX reverts with InvalidSalt: byte 20 of the salt is 0x{:02x}, neither 0x00 nor 0x01",
+                flag
+            )),
+            (None, false, _) if caller.is_none() => Err(
+                "the salt starts with an address, pass it as --caller to deploy from it".to_string(),
+            ),
+            _ => Ok(SaltVariant::Random),
+        }
+    }
+
+    /// The salt mined for `nonce`: entropy of 3 zero bytes and the big-endian nonce, the layout
+    /// [`DualSalt`] shares.
+    pub fn salt(&self, nonce: u64) -> [u8; 32] {
+        let mut entropy = [0u8; 11];
+        entropy[3..].copy_from_slice(&nonce.to_be_bytes());
+        self.salt_with_entropy(&entropy)
+    }
+}
+
+/// Rendering of a salt for signing tooling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaltFormat {
+    /// `0x`-prefixed 32-byte hex string.
+    #[default]
+    Bytes32,
+    /// Standard padded base64 of the 32 bytes.
+    Base64,
+    /// Decimal string of the salt as a big-endian uint256.
+    Uint256Dec,
+}
+
+impl SaltFormat {
+    pub fn render(&self, salt: &[u8; 32]) -> String {
+        match self {
+            SaltFormat::Bytes32 => format!("0x{}", hex::encode(salt)),
+            SaltFormat::Base64 => BASE64_STANDARD.encode(salt),
+            SaltFormat::Uint256Dec => U256::from_be_bytes(*salt).to_string(),
+        }
+    }
+
+    /// Name of the extra JSON field holding this rendering.
+    pub fn field(&self) -> &'static str {
+        match self {
+            SaltFormat::Bytes32 => "salt_bytes32",
+            SaltFormat::Base64 => "salt_base64",
+            SaltFormat::Uint256Dec => "salt_uint256",
+        }
+    }
+}
+
+impl fmt::Display for SaltFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaltFormat::Bytes32 => write!(f, "bytes32"),
+            SaltFormat::Base64 => write!(f, "base64"),
+            SaltFormat::Uint256Dec => write!(f, "uint256-dec"),
+        }
+    }
+}
+
+impl FromStr for SaltFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes32" => Ok(SaltFormat::Bytes32),
+            "base64" => Ok(SaltFormat::Base64),
+            "uint256-dec" => Ok(SaltFormat::Uint256Dec),
+            _ => Err(format!(
+                "unknown salt format {} (expected bytes32, base64 or uint256-dec)",
+                s
+            )),
+        }
+    }
+}
+
+/// Parse a salt in any [`SaltFormat`] rendering: `0x`-prefixed hex, a decimal uint256, or
+/// base64. Hex salts must carry the `0x` prefix, since 64 decimal digits are valid hex as well.
+pub fn parse_salt(s: &str) -> Result<[u8; 32], String> {
+    let s = s.trim();
+
+    if let Some(hex_salt) = s.strip_prefix("0x") {
+        let bytes = hex::decode(hex_salt).map_err(|_| format!("invalid hex salt {}", s))?;
+        return bytes
+            .try_into()
+            .map_err(|_| format!("hex salt {} is not 32 bytes long", s));
+    }
+
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        let value = U256::from_str_radix(s, 10)
+            .map_err(|_| format!("decimal salt {} does not fit in a uint256", s))?;
+        return Ok(value.to_be_bytes());
+    }
+
+    // 64 hex digits with letters among them, a salt copied without its prefix
+    if s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("salt {} looks like hex, hex salts need the 0x prefix", s));
+    }
+
+    let bytes = BASE64_STANDARD
+        .decode(s)
+        .map_err(|_| format!("salt {} is neither 0x-prefixed hex, decimal nor base64", s))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("base64 salt {} is not 32 bytes long", s))
+}
+
+/// Parse bytecode given as hex, such as init code or ABI encoded constructor arguments. An
+/// optional `0x` prefix and any whitespace, like the line breaks of compiler output, are
+/// ignored.
+pub fn parse_hex_code(code: &str) -> Result<Vec<u8>, String> {
+    let digits: String = code.split_whitespace().collect();
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits ({})", digits.len()));
+    }
+    hex::decode(digits).map_err(|e| e.to_string())
+}
+
+/// The init code hash of `init_code` deployed with `constructor_args` appended, as CreateX
+/// hashes it and `--code-hash` takes it.
+pub fn init_code_hash(init_code: &[u8], constructor_args: &[u8]) -> [u8; 32] {
+    keccak256([init_code, constructor_args].concat()).0
+}
+
+/// Layout of the results written to the output file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `salt -> address` lines below a `#` comment header.
+    #[default]
+    Text,
+    /// One JSON object per result, without any header.
+    Json,
+    /// JSON Lines of results with the context deployment scripts need, see [`FoundSalt`].
+    JsonLines,
+    /// A Safe Transaction Builder batch with one CreateX deploy call per result, see [`safe`].
+    SafeTx,
+    /// A header row of [`csv_header`] columns and one row per result, see
+    /// [`FoundSalt::to_csv_row`].
+    Csv,
+}
+
+impl OutputFormat {
+    /// Detect the format of existing output file contents from their first non-empty line.
+    pub fn detect(first_line: &str) -> Self {
+        if first_line.trim_start().starts_with('{') {
+            match first_line.contains("\"found_at_unix\"") {
+                true => OutputFormat::JsonLines,
+                false => OutputFormat::Json,
+            }
+        } else if first_line.starts_with("salt,address,") {
+            OutputFormat::Csv
+        } else {
+            OutputFormat::Text
+        }
+    }
+
+    /// Render a single result. Safe batches hold transactions built with [`safe::transaction`]
+    /// and JSON Lines results with [`FoundSalt`], results are rendered as JSON for them.
+    /// CSV rows built with [`FoundSalt::to_csv_row`] have more columns, results are rendered as
+    /// the first two.
+    pub fn format_result(&self, salt: &str, address: &str) -> String {
+        match self {
+            OutputFormat::Text => format!("{} -> {}", salt, address),
+            OutputFormat::Csv => csv_row(&[salt, address]),
+            OutputFormat::Json | OutputFormat::JsonLines | OutputFormat::SafeTx => {
+                serde_json::json!({ "salt": salt, "address": address }).to_string()
+            }
+        }
+    }
+
+    /// Render a single result with an extra rendering of its salt, as a second column before
+    /// the arrow in text output or as an extra field in JSON output.
+    pub fn format_result_with_salt(
+        &self,
+        salt: &[u8; 32],
+        address: &str,
+        salt_format: SaltFormat,
+    ) -> String {
+        let bytes32 = SaltFormat::Bytes32.render(salt);
+        let rendered = salt_format.render(salt);
+        match self {
+            OutputFormat::Text => format!("{} {} -> {}", bytes32, rendered, address),
+            OutputFormat::Csv => csv_row(&[&bytes32, address, &rendered]),
+            OutputFormat::Json | OutputFormat::JsonLines | OutputFormat::SafeTx => {
+                let mut result = serde_json::json!({ "salt": bytes32, "address": address });
+                result[salt_format.field()] = rendered.into();
+                result.to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::JsonLines => write!(f, "jsonl"),
+            OutputFormat::SafeTx => write!(f, "safe-tx"),
+            OutputFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            "safe-tx" => Ok(OutputFormat::SafeTx),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "unknown output format {} (expected text, json, jsonl, safe-tx or csv)",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "miner")]
+impl Config<'_> {
+    /// Canonical description of what a run searches for, recorded in checkpoints so a run is
+    /// never resumed with a different search. Only its hash is recorded with `redact_params`.
+    pub fn fingerprint(&self) -> String {
+        let fingerprint = format!(
+            "{} factory(0x{}) {} {}",
+            self.create_variant,
+            hex::encode(self.factory_address),
+            self.salt_variant,
+            self.reward
+        );
+        match self.redact_params {
+            true => format!("params({})", params::param_hash(fingerprint.as_bytes())),
+            false => fingerprint,
+        }
+    }
+}
+
+/// A result of `jsonl` output, one JSON object per line with the context deployment scripts
+/// need.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoundSalt {
+    /// See [`schemas::Artifact::Results`], 0 in results of builds before it.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The salt in hex, `0x` and 32 bytes.
+    pub salt: String,
+    /// The checksummed address.
+    pub address: String,
+    /// The checksummed factory deploying the address.
+    pub factory: String,
+    /// The create variant as in checkpoints, `create3` or `create2(0x<init code hash>)`.
+    pub create_variant: String,
+    /// The reward the address was mined for, in its canonical form.
+    pub pattern: String,
+    pub found_at_unix: u64,
+    /// Attempts made by the end of the cycle the result was found in.
+    pub attempt: u64,
+    /// Lines of the `--pattern-file` holding the patterns the address matches, from 1.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pattern_lines: Vec<usize>,
+    /// The extra renderings of the salt of `--emit-salt-format`, by field name.
+    #[serde(flatten)]
+    pub salt_formats: BTreeMap<String, String>,
+}
+
+impl FoundSalt {
+    pub fn new(
+        salt: &[u8; 32],
+        address: &str,
+        factory: &[u8; 20],
+        create_variant: &CreateVariant,
+        pattern: &RewardVariant,
+        found_at_unix: u64,
+        attempt: u64,
+    ) -> Self {
+        Self {
+            schema_version: schemas::Artifact::Results.current(),
+            salt: format!("0x{}", hex::encode(salt)),
+            address: address.to_string(),
+            factory: Address::from(factory).to_checksum(None),
+            create_variant: create_variant.to_string(),
+            pattern: pattern.to_string(),
+            found_at_unix,
+            attempt,
+            pattern_lines: vec![],
+            salt_formats: BTreeMap::new(),
+        }
+    }
+
+    /// Add the rendering of the salt in `salt_format`.
+    pub fn with_salt_format(mut self, salt_format: SaltFormat, salt: &[u8; 32]) -> Self {
+        self.salt_formats
+            .insert(salt_format.field().to_string(), salt_format.render(salt));
+        self
+    }
+
+    /// The result as a line of `jsonl` output.
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("results serialize")
+    }
+
+    /// Read a line of `jsonl` output, migrating results of older schema versions.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let value = serde_json::from_str(line).map_err(|e| format!("invalid result: {}", e))?;
+        let value = schemas::upgrade(schemas::Artifact::Results, value)?;
+        serde_json::from_value(value).map_err(|e| format!("invalid result: {}", e))
+    }
+
+    /// The result as a row of `csv` output, in the columns of [`csv_header`].
+    pub fn to_csv_row(&self) -> String {
+        let found_at_unix = self.found_at_unix.to_string();
+        let attempt = self.attempt.to_string();
+        let mut fields = vec![
+            self.salt.as_str(),
+            &self.address,
+            &self.factory,
+            &self.create_variant,
+            &self.pattern,
+            &found_at_unix,
+            &attempt,
+        ];
+        fields.extend(self.salt_formats.values().map(String::as_str));
+        csv_row(&fields)
+    }
+}
+
+/// Header row of `csv` output, with a column for the extra rendering of salts of
+/// `--emit-salt-format`.
+pub fn csv_header(salt_format: Option<SaltFormat>) -> String {
+    let mut columns = vec![
+        "salt",
+        "address",
+        "factory",
+        "variant",
+        "pattern",
+        "found_at_unix",
+        "attempt",
+    ];
+    columns.extend(salt_format.map(|salt_format| salt_format.field()));
+    columns.join(",")
+}
+
+/// Join `fields` into a CSV row, quoting fields that hold commas, quotes or line breaks.
+fn csv_row(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(feature = "miner")]
+/// Refuse to append `format` results to an output file that already holds results in another
+/// format, which would leave it with mixed, unparseable contents.
+pub fn check_output_format(path: &Path, format: OutputFormat) -> Result<(), String> {
+    // Safe batches are rewritten as a whole rather than appended to
+    if format == OutputFormat::SafeTx {
+        return Ok(());
+    }
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(()),
+    };
+
+    let first_line = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .find(|line| !line.trim().is_empty());
+
+    match first_line.map(|line| OutputFormat::detect(&line)) {
+        Some(existing) if existing != format => Err(format!(
+            "output file {} holds {} results, refusing to append {} results to it",
+            path.display(),
+            existing,
+            format
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "miner")]
+/// Refuse to append rows with the columns of `header` to a `csv` output file that started with
+/// other columns, such as one written by an older version or with another --emit-salt-format.
+pub fn check_csv_header(path: &Path, header: &str) -> Result<(), String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(()),
+    };
+
+    let first_line = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .find(|line| !line.trim().is_empty());
+
+    match first_line {
+        Some(existing) if existing != header => Err(format!(
+            "output file {} has the CSV columns {}, refusing to append rows of columns {} to it",
+            path.display(),
+            existing,
+            header
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "miner")]
 pub struct Config<'a> {
     pub gpu_device: u8,
+    /// Only take adapters of this backend, all of them when unset.
+    pub backend: Option<GpuBackend>,
     pub factory_address: [u8; 20],
+    /// Whether the factory was left at its default, [`CREATEX_ADDRESS`], which startup says.
+    pub default_factory: bool,
     pub salt_variant: SaltVariant,
     pub create_variant: CreateVariant,
     pub reward: RewardVariant,
     pub output: &'a str,
+    /// Print results to stdout instead of writing them to `output`, and the progress to stderr.
+    pub no_file: bool,
+    /// Keep the factory, caller and init code hash out of logs, events and the output header,
+    /// which records their hashes instead, see [`params`].
+    pub redact_params: bool,
+    pub seed_entropy: bool,
+    /// Seed of the starting nonce and the scan order, `seed_entropy` or nonce 0 when unset.
+    pub seed: Option<Seed>,
+    pub bundle: Option<&'a str>,
+    pub debug_env: bool,
+    pub skip_ranges: NonceRanges,
+    pub clipboard: bool,
+    pub format: OutputFormat,
+    pub self_test: Option<SelfTestMode>,
+    /// Halt on any disagreement of the GPU and the CPU, writing a [`forensics::ForensicDump`].
+    pub strict: bool,
+    /// Flip a bit of the first result read back, to exercise `strict`.
+    pub inject_corruption: bool,
+    pub max_pending_matches: usize,
+    pub backpressure: Backpressure,
+    pub allow_warnings: Vec<WarningCode>,
+    pub deny_warnings: bool,
+    pub dual_salt: bool,
+    /// Reject matches in the precompile range or on the denylist, see [`reserved::reserved`].
+    pub avoid_reserved: bool,
+    pub emit_salt_format: Option<SaltFormat>,
+    /// Nonces dispatched per cycle, the largest batch the device can hold when unset, see
+    /// [`BatchGeometry::fit`].
+    pub work_size: Option<u32>,
+    /// Threads per workgroup, the kernel's `WORKGROUP_SIZE` override constant.
+    pub workgroup_size: u32,
+    /// Source of a `--kernel-file` mined with instead of [`KECCAK256_KERNEL`], see
+    /// [`kernel_file::load`].
+    pub kernel: Option<String>,
+    pub scan_order: ScanOrder,
+    pub sort_on_exit: Option<usize>,
+    /// Also route results into bucket files next to the output file.
+    pub split_output: Option<SplitBy>,
+    /// What to do with results for an address already found with another salt.
+    pub on_duplicate_address: DuplicatePolicy,
+    /// Append a row of [`cycle_stats::CycleStats`] per cycle to this CSV file.
+    pub stats_file: Option<&'a str>,
+    /// Write a [`replay::CycleRecord`] of every cycle with results into this directory.
+    pub record_cycles: Option<&'a str>,
+    pub supervised: bool,
+    /// Continue from the checkpoint of the output file and keep saving checkpoints, as
+    /// supervised runs do.
+    pub resume: bool,
+    pub checkpoint_interval: Duration,
+    /// Also checkpoint every this many attempts, bounding the work a restart repeats.
+    pub checkpoint_attempts: Option<u64>,
+    /// Also checkpoint every this many cycles.
+    pub checkpoint_cycles: Option<u64>,
+    pub watchdog_stall: Duration,
+    /// Only mine inside these daily windows of local time, pausing outside of them.
+    pub schedule: Option<Schedule>,
+    pub address_offset: usize,
+    pub cost: Option<CostModel>,
+    /// Campaign the run belongs to, recorded in its artifacts and session log, see [`campaign`].
+    pub campaign: Option<&'a str>,
+    /// Init code passed to the deploy calls of `--format safe-tx`.
+    pub init_code: Option<Vec<u8>>,
+    /// Chain of the Safe importing a `--format safe-tx` batch.
+    pub safe_chain_id: Option<u64>,
+    /// File the matching pattern was read from, reloaded between cycles.
+    pub pattern_file: Option<&'a str>,
+    /// Stop after this many results.
+    pub limit: Option<u64>,
+    /// Stop after the cycle reaching this many results, keeping every result of that cycle.
+    pub max_results: Option<u64>,
+    /// Stop once mining has run this long.
+    pub timeout: Option<Duration>,
+    /// Stop once mining has run this long, ending with a line saying so.
+    pub max_runtime: Option<Duration>,
+    /// Keep full salts out of everything but the output file.
+    pub redact_salts: bool,
+    /// Nonces of a pool lease. The run mines exactly these and then stops.
+    pub lease: Option<Range<u64>>,
+    /// Clear the results of earlier runs from the output file instead of appending to it.
+    pub overwrite: bool,
+    /// Append to the output file even with `overwrite`, for the leases after the first.
+    pub append_output: bool,
+    /// Print the worked derivation example at startup.
+    pub worked_example: bool,
+    /// Skip the optional startup phases, see [`plan::startup_phases`].
+    pub fast_start: bool,
+    /// Print results instead of the status redrawn every second, and skip the pattern notes.
+    pub quiet: bool,
+    /// Write the [`events::Event`] stream to stdout instead of the human output.
+    pub json_events: bool,
+    /// Node used to check that the factory runs the CreateX code.
+    pub rpc_url: Option<&'a str>,
+    /// Refuse to mine for a factory that does not, instead of warning.
+    pub require_known_factory: bool,
+    /// Requests per second and burst the node is sent at most.
+    pub rpc_rate_limit: Option<(u32, u32)>,
 }
 
-impl<'a> Config<'a> {
-    pub fn new(
-        gpu_device: u8,
-        factory_address_str: &str,
-        calling_address_str: Option<&str>,
-        chain_id: Option<u64>,
-        init_code_hash: Option<&str>,
-        reward: RewardVariant,
-        output: &'a str,
-    ) -> Result<Self, &'static str> {
-        // convert main arguments from hex string to vector of bytes
-        let factory_address_vec =
-            hex::decode(factory_address_str).expect("could not decode factory address argument");
-        let calling_address_vec = calling_address_str.map(|calling_address| {
-            hex::decode(calling_address).expect("could not decode calling address argument")
-        });
-        let init_code_hash_vec = init_code_hash.map(|init_code_hash| {
-            hex::decode(init_code_hash).expect("could not decode init code hash argument")
-        });
-
-        // convert from vector to fixed array
-        let factory_address = TryInto::<[u8; 20]>::try_into(factory_address_vec)
-            .expect("invalid length for factory address argument");
-        let calling_address = calling_address_vec.map(|calling_address_vec| {
-            TryInto::<[u8; 20]>::try_into(calling_address_vec)
-                .expect("invalid length for calling address argument")
-        });
-        let init_code_hash = init_code_hash_vec.map(|init_code_hash_vec| {
-            TryInto::<[u8; 32]>::try_into(init_code_hash_vec)
-                .expect("invalid length for init code hash argument")
-        });
-        let chain_id = chain_id.map(|chain_id| {
-            let mut arr = [0u8; 32];
-            arr[24..].copy_from_slice(&chain_id.to_be_bytes());
-            arr
-        });
+/// Why [`Config::new`] rejected its arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidFactoryHex,
+    BadFactoryLength,
+    BadFactoryChecksum,
+    InvalidCallerHex,
+    BadCallerLength,
+    BadCallerChecksum,
+    InvalidInitCodeHashHex,
+    /// The number of bytes the init code hash decoded to instead of 32.
+    InvalidInitCodeHashLength(usize),
+    ZeroThreshold,
+    ThresholdTooLarge,
+    ZeroSymmetricNibbles,
+    TooManySymmetricNibbles,
+    ZeroNibblesThreshold,
+    NibblesThresholdTooLarge,
+    /// The pattern of `--matching`, and what is wrong with it.
+    InvalidPattern(&'static str),
+}
 
-        let create_variant = match init_code_hash {
-            Some(init_code_hash) => CreateVariant::Create2 { init_code_hash },
-            None => CreateVariant::Create3,
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ConfigError::InvalidFactoryHex => "factory address is not valid hex",
+            ConfigError::BadFactoryLength => "factory address must be 20 bytes",
+            ConfigError::BadFactoryChecksum => "factory address uses invalid checksum",
+            ConfigError::InvalidCallerHex => "caller address is not valid hex",
+            ConfigError::BadCallerLength => "caller address must be 20 bytes",
+            ConfigError::BadCallerChecksum => "caller address uses invalid checksum",
+            ConfigError::InvalidInitCodeHashHex => "init code hash is not valid hex",
+            ConfigError::InvalidInitCodeHashLength(length) => {
+                return write!(f, "init code hash must be 32 bytes, got {}", length);
+            }
+            ConfigError::ZeroThreshold => "threshold must be greater than 0",
+            ConfigError::ThresholdTooLarge => "threshold must be less than 20",
+            ConfigError::ZeroSymmetricNibbles => "symmetric nibbles must be greater than 0",
+            ConfigError::TooManySymmetricNibbles => "symmetric nibbles must be at most 20",
+            ConfigError::ZeroNibblesThreshold => "leading zero nibbles must be greater than 0",
+            ConfigError::NibblesThresholdTooLarge => "leading zero nibbles must be at most 40",
+            ConfigError::InvalidPattern(message) => message,
         };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Decode the hex of an init code hash, which must be 32 bytes.
+pub fn parse_init_code_hash(init_code_hash: &str) -> Result<[u8; 32], ConfigError> {
+    let bytes = hex::decode(init_code_hash).map_err(|_| ConfigError::InvalidInitCodeHashHex)?;
+    let length = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| ConfigError::InvalidInitCodeHashLength(length))
+}
+
+/// Check a `--matching` pattern: a byte in hex, or hex bytes followed by `...` and optionally
+/// trailing hex bytes, or `...` followed by trailing hex bytes only.
+pub fn validate_pattern(pattern: &str) -> Result<(), ConfigError> {
+    if pattern.is_empty() {
+        return Err(ConfigError::InvalidPattern("pattern cannot be empty"));
+    }
+
+    // Check if pattern contains "..."
+    if pattern.contains("...") {
+        let parts: Vec<&str> = pattern.split("...").collect();
+        if parts.len() != 2 {
+            return Err(ConfigError::InvalidPattern(
+                "pattern must have exactly one '...' separator",
+            ));
+        }
+
+        let leading_part = parts[0];
+        let trailing_part = parts[1];
+
+        // Validate leading part, which only a pattern of trailing bytes leaves out
+        if leading_part.is_empty() && trailing_part.is_empty() {
+            return Err(ConfigError::InvalidPattern(
+                "leading part of pattern cannot be empty",
+            ));
+        }
+        if !leading_part.len().is_multiple_of(2) {
+            return Err(ConfigError::InvalidPattern(
+                "leading part must have even number of characters",
+            ));
+        }
+        if !leading_part.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ConfigError::InvalidPattern(
+                "leading part must contain only hex characters",
+            ));
+        }
+
+        // We no longer require the leading part to be repeating
+        // The leading part can be any valid hex string now
+
+        // Validate trailing part if present
+        if !trailing_part.is_empty() {
+            if !trailing_part.len().is_multiple_of(2) {
+                return Err(ConfigError::InvalidPattern(
+                    "trailing part must have even number of characters",
+                ));
+            }
+            if !trailing_part.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ConfigError::InvalidPattern(
+                    "trailing part must contain only hex characters",
+                ));
+            }
+        }
+
+        // Longer patterns than an address could never match, and would be mined forever
+        if leading_part.len() + trailing_part.len() > 40 {
+            return Err(ConfigError::InvalidPattern(
+                "pattern must be at most 40 hex characters, the length of an address",
+            ));
+        }
+    } else {
+        // For simple patterns without "..."
+        if pattern.len() != 2 {
+            return Err(ConfigError::InvalidPattern(
+                "simple pattern must be exactly 2 characters",
+            ));
+        }
+        if !pattern.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ConfigError::InvalidPattern(
+                "pattern must contain only hex characters",
+            ));
+        }
+    }
+
+    Ok(())
+}
 
-        match &reward {
+impl RewardVariant {
+    /// Check the thresholds and patterns of the reward, which the miner refuses to start without.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
             RewardVariant::LeadingZeros { zeros_threshold }
             | RewardVariant::TotalZeros { zeros_threshold } => {
                 validate_zeros_threshold(zeros_threshold)?;
             }
+            RewardVariant::LeadingZeroNibbles { threshold } => {
+                validate_nibbles_threshold(threshold)?;
+            }
             RewardVariant::LeadingOrTotalZeros {
                 leading_zeros_threshold,
                 total_zeros_threshold,
@@ -110,72 +945,132 @@ impl<'a> Config<'a> {
                 validate_zeros_threshold(leading_zeros_threshold)?;
                 validate_zeros_threshold(total_zeros_threshold)?;
             }
-            RewardVariant::Matching { pattern } => {
+            RewardVariant::Matching { pattern } | RewardVariant::ChecksumMatching { pattern } => {
                 validate_pattern(pattern)?;
             }
+            RewardVariant::MatchingAny { patterns } => {
+                for pattern in patterns {
+                    validate_pattern(pattern)?;
+                }
+            }
+            RewardVariant::SymmetricEnds { nibbles } => {
+                if nibbles == &0u8 {
+                    return Err(ConfigError::ZeroSymmetricNibbles);
+                }
+                if nibbles > &20u8 {
+                    return Err(ConfigError::TooManySymmetricNibbles);
+                }
+            }
         }
 
-        fn validate_zeros_threshold(threhsold: &u8) -> Result<(), &'static str> {
+        fn validate_zeros_threshold(threhsold: &u8) -> Result<(), ConfigError> {
             if threhsold == &0u8 {
-                return Err("threshold must be greater than 0");
+                return Err(ConfigError::ZeroThreshold);
             }
             if threhsold > &20u8 {
-                return Err("threshold must be less than 20");
+                return Err(ConfigError::ThresholdTooLarge);
             }
 
             Ok(())
         }
 
-        fn validate_pattern(pattern: &str) -> Result<(), &'static str> {
-            if pattern.is_empty() {
-                return Err("pattern cannot be empty");
+        fn validate_nibbles_threshold(threshold: &u8) -> Result<(), ConfigError> {
+            if threshold == &0u8 {
+                return Err(ConfigError::ZeroNibblesThreshold);
+            }
+            if threshold > &40u8 {
+                return Err(ConfigError::NibblesThresholdTooLarge);
             }
 
-            // Check if pattern contains "..."
-            if pattern.contains("...") {
-                let parts: Vec<&str> = pattern.split("...").collect();
-                if parts.len() != 2 {
-                    return Err("pattern must have exactly one '...' separator");
-                }
-
-                let leading_part = parts[0];
-                let trailing_part = parts[1];
-
-                // Validate leading part
-                if leading_part.is_empty() {
-                    return Err("leading part of pattern cannot be empty");
-                }
-                if leading_part.len() % 2 != 0 {
-                    return Err("leading part must have even number of characters");
-                }
-                if !leading_part.chars().all(|c| c.is_ascii_hexdigit()) {
-                    return Err("leading part must contain only hex characters");
-                }
+            Ok(())
+        }
 
-                // We no longer require the leading part to be repeating
-                // The leading part can be any valid hex string now
+        Ok(())
+    }
 
-                // Validate trailing part if present
-                if !trailing_part.is_empty() {
-                    if trailing_part.len() != 2 {
-                        return Err("trailing part must be exactly 2 characters");
-                    }
-                    if !trailing_part.chars().all(|c| c.is_ascii_hexdigit()) {
-                        return Err("trailing part must contain only hex characters");
-                    }
-                }
-            } else {
-                // For simple patterns without "..."
-                if pattern.len() != 2 {
-                    return Err("simple pattern must be exactly 2 characters");
-                }
-                if !pattern.chars().all(|c| c.is_ascii_hexdigit()) {
-                    return Err("pattern must contain only hex characters");
-                }
+    /// Whether `address` satisfies the reward, the check the miner confirms results with on the
+    /// CPU. Patterns of partial bytes are compared on the lowercase hex.
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        let leading_zeros = |count: u8| address[..(count as usize).min(20)].iter().all(|&b| b == 0);
+        let total_zeros = |count: u8| address.iter().filter(|&&b| b == 0).count() >= count as usize;
+        let matches_pattern = |pattern: &str| match PatternBytes::parse(pattern) {
+            Some(bytes) => bytes.matches(address),
+            None => {
+                let (prefix, suffix) = pattern.split_once("...").unwrap_or((pattern, ""));
+                let (prefix, suffix) = (prefix.to_lowercase(), suffix.to_lowercase());
+                let address = hex::encode(address);
+                prefix.len() + suffix.len() <= address.len()
+                    && address.starts_with(&prefix)
+                    && address.ends_with(&suffix)
             }
+        };
 
-            Ok(())
+        match self {
+            RewardVariant::LeadingZeros { zeros_threshold } => leading_zeros(*zeros_threshold),
+            RewardVariant::LeadingZeroNibbles { threshold } => {
+                has_leading_zero_nibbles(address, *threshold)
+            }
+            RewardVariant::TotalZeros { zeros_threshold } => total_zeros(*zeros_threshold),
+            RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => leading_zeros(*leading_zeros_threshold) && total_zeros(*total_zeros_threshold),
+            RewardVariant::LeadingOrTotalZeros {
+                leading_zeros_threshold,
+                total_zeros_threshold,
+            } => leading_zeros(*leading_zeros_threshold) || total_zeros(*total_zeros_threshold),
+            RewardVariant::Matching { pattern } => matches_pattern(pattern),
+            RewardVariant::MatchingAny { patterns } => {
+                patterns.iter().any(|pattern| matches_pattern(pattern))
+            }
+            RewardVariant::ChecksumMatching { pattern } => checksum_matches(address, pattern),
+            RewardVariant::SymmetricEnds { nibbles } => has_symmetric_ends(address, *nibbles),
         }
+    }
+}
+
+#[cfg(feature = "miner")]
+impl<'a> Config<'a> {
+    pub fn new(
+        gpu_device: u8,
+        factory_address_str: &str,
+        calling_address_str: Option<&str>,
+        chain_id: Option<u64>,
+        init_code_hash: Option<&str>,
+        reward: RewardVariant,
+        output: &'a str,
+    ) -> Result<Self, ConfigError> {
+        // convert main arguments from hex string to vector of bytes
+        let factory_address_vec =
+            hex::decode(factory_address_str).map_err(|_| ConfigError::InvalidFactoryHex)?;
+        let calling_address_vec = calling_address_str
+            .map(|calling_address| {
+                hex::decode(calling_address).map_err(|_| ConfigError::InvalidCallerHex)
+            })
+            .transpose()?;
+
+        // convert from vector to fixed array
+        let factory_address = TryInto::<[u8; 20]>::try_into(factory_address_vec)
+            .map_err(|_| ConfigError::BadFactoryLength)?;
+        let calling_address = calling_address_vec
+            .map(|calling_address_vec| {
+                TryInto::<[u8; 20]>::try_into(calling_address_vec)
+                    .map_err(|_| ConfigError::BadCallerLength)
+            })
+            .transpose()?;
+        let init_code_hash = init_code_hash.map(parse_init_code_hash).transpose()?;
+        let chain_id = chain_id.map(|chain_id| {
+            let mut arr = [0u8; 32];
+            arr[24..].copy_from_slice(&chain_id.to_be_bytes());
+            arr
+        });
+
+        let create_variant = match init_code_hash {
+            Some(init_code_hash) => CreateVariant::Create2 { init_code_hash },
+            None => CreateVariant::Create3,
+        };
+
+        reward.validate()?;
 
         let salt_variant = match (chain_id, calling_address) {
             (Some(chain_id), Some(calling_address)) if calling_address != [0u8; 20] => {
@@ -199,7 +1094,7 @@ impl<'a> Config<'a> {
             match Address::parse_checksummed(factory_address_str, None) {
                 Ok(_) => {}
                 Err(_) => {
-                    return Err("factory address uses invalid checksum");
+                    return Err(ConfigError::BadFactoryChecksum);
                 }
             }
         }
@@ -214,7 +1109,7 @@ impl<'a> Config<'a> {
                 match Address::parse_checksummed(calling_address_str, None) {
                     Ok(_) => {}
                     Err(_) => {
-                        return Err("caller address uses invalid checksum");
+                        return Err(ConfigError::BadCallerChecksum);
                     }
                 }
             };
@@ -222,26 +1117,939 @@ impl<'a> Config<'a> {
 
         Ok(Self {
             gpu_device,
+            backend: None,
             factory_address,
+            default_factory: false,
             salt_variant,
             create_variant,
+            avoid_reserved: reserved::avoided_by_default(&reward),
             reward,
             output,
+            no_file: false,
+            redact_params: false,
+            seed_entropy: false,
+            seed: None,
+            bundle: None,
+            debug_env: false,
+            skip_ranges: NonceRanges::default(),
+            clipboard: false,
+            format: OutputFormat::Text,
+            self_test: None,
+            strict: false,
+            inject_corruption: false,
+            max_pending_matches: 1024,
+            backpressure: Backpressure::Block,
+            allow_warnings: vec![],
+            deny_warnings: false,
+            dual_salt: false,
+            emit_salt_format: None,
+            work_size: None,
+            workgroup_size: 256,
+            kernel: None,
+            scan_order: ScanOrder::Linear,
+            sort_on_exit: None,
+            split_output: None,
+            on_duplicate_address: DuplicatePolicy::Warn,
+            stats_file: None,
+            record_cycles: None,
+            supervised: false,
+            resume: false,
+            checkpoint_interval: Duration::from_secs(60),
+            checkpoint_attempts: None,
+            checkpoint_cycles: None,
+            watchdog_stall: Duration::from_secs(120),
+            schedule: None,
+            address_offset: ADDRESS_OFFSET,
+            cost: None,
+            campaign: None,
+            init_code: None,
+            safe_chain_id: None,
+            pattern_file: None,
+            limit: None,
+            max_results: None,
+            timeout: None,
+            max_runtime: None,
+            redact_salts: false,
+            lease: None,
+            overwrite: false,
+            append_output: false,
+            worked_example: true,
+            fast_start: false,
+            quiet: false,
+            json_events: false,
+            rpc_url: None,
+            require_known_factory: false,
+            rpc_rate_limit: None,
         })
     }
+
+    /// The salt bytes preceding the mined nonce, see [`SaltVariant::salt`].
+    pub fn salt_prefix(&self) -> Vec<u8> {
+        self.salt_variant.salt(0)[..24].to_vec()
+    }
+}
+
+#[cfg(feature = "miner")]
+/// A verified result handed to the callback of [`mine`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub salt: [u8; 32],
+    /// Address the salt deploys to through the factory.
+    pub address: [u8; 20],
+}
+
+#[cfg(feature = "miner")]
+/// Outcome of a [`mine`] run.
+pub type MineStats = batch::RunSummary;
+
+#[cfg(feature = "miner")]
+/// Mine for `config` on the GPU, handing every result to `on_match`, for embedding the miner in
+/// other tools. Unlike the command line it writes no output file and prints nothing to stdout,
+/// the caller decides what to do with the results. Stops at the `limit`, `max_results`,
+/// `timeout` or `max_runtime` of `config`, or once a `lease` is mined.
+pub fn mine(
+    config: Config,
+    on_match: impl FnMut(Match),
+) -> Result<MineStats, Box<dyn std::error::Error>> {
+    mine_with(config, EventBus::new(), on_match)
+}
+
+#[cfg(feature = "miner")]
+/// Mine like [`mine`], publishing the events of the run to the subscribers of `bus` as well.
+pub fn mine_with(
+    config: Config,
+    bus: EventBus,
+    mut on_match: impl FnMut(Match),
+) -> Result<MineStats, Box<dyn std::error::Error>> {
+    webgpu::run(config, Some(&mut on_match), bus)
+}
+
+#[cfg(feature = "miner")]
+/// Refuse options of `config` that only work on the output file for a [`mine`] run, which has
+/// none.
+pub fn check_embeddable(config: &Config) -> Result<(), String> {
+    let options = [
+        ("--supervised", config.supervised),
+        ("--resume", config.resume),
+        ("--sort-on-exit", config.sort_on_exit.is_some()),
+        ("--split-output-by", config.split_output.is_some()),
+        ("--bundle", config.bundle.is_some()),
+        ("--clipboard", config.clipboard),
+    ];
+    match options.iter().find(|(_, set)| *set) {
+        Some((option, _)) => Err(format!(
+            "{} works on the output file, which runs with a match callback don't write",
+            option
+        )),
+        None => Ok(()),
+    }
 }
 
-pub fn output_file(config: &Config) -> File {
+#[cfg(feature = "miner")]
+/// Refuse options of `config` that a `--no-file` run, printing its results to stdout instead
+/// of writing an output file, can't honor.
+pub fn check_no_file(config: &Config) -> Result<(), String> {
+    let options = [
+        ("--supervised", config.supervised),
+        ("--resume", config.resume),
+        ("--split-output-by", config.split_output.is_some()),
+        ("--bundle", config.bundle.is_some()),
+        ("--overwrite", config.overwrite),
+    ];
+    if let Some((option, _)) = options.iter().find(|(_, set)| *set) {
+        return Err(format!(
+            "{} works on the output file, which --no-file runs don't write",
+            option
+        ));
+    }
+    if config.json_events {
+        return Err(
+            "--json writes its events to stdout, where --no-file runs print their results"
+                .to_string(),
+        );
+    }
+    if config.format == OutputFormat::SafeTx {
+        return Err(
+            "--format safe-tx batches are written to the output file, which --no-file runs don't write"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "miner")]
+/// How [`output_file`] found the output file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputOpened {
+    /// The file was missing or empty.
+    Created,
+    /// The results of earlier runs were kept.
+    Appended,
+    /// The results of earlier runs were cleared.
+    Overwritten,
+}
+
+#[cfg(feature = "miner")]
+/// Open the output file at `path` for appending, creating it if needed, and lock it
+/// exclusively for the run. With `overwrite`, earlier results are only cleared once the lock is
+/// held, so a run still writing to the file is never truncated.
+pub fn output_file(path: &str, overwrite: bool) -> Result<(File, OutputOpened), String> {
     let file = OpenOptions::new()
         .create(true)
-        .write(true)
         .read(true)
         .append(true)
-        .open(config.output)
-        .expect("Failed to open output file");
+        .open(path)
+        .map_err(|e| format!("could not open output file {}: {}", path, e))?;
+    file.try_lock_exclusive()
+        .map_err(|_| format!("output file {} is locked by another run", path))?;
+
+    let len = file
+        .metadata()
+        .map_err(|e| format!("could not open output file {}: {}", path, e))?
+        .len();
+    let opened = match (len, overwrite) {
+        (0, _) => OutputOpened::Created,
+        (_, false) => OutputOpened::Appended,
+        (_, true) => {
+            file.set_len(0)
+                .map_err(|e| format!("could not clear output file {}: {}", path, e))?;
+            OutputOpened::Overwritten
+        }
+    };
+    Ok((file, opened))
+}
+
+/// Offset of the address in the keccak256 hash it is derived from: addresses are the last 20
+/// bytes of the hash. Only overridden by the hidden `--address-offset` of `dev` builds.
+pub const ADDRESS_OFFSET: usize = 12;
+
+/// Check that an address at `offset` fits in a 32-byte hash.
+pub fn validate_address_offset(offset: usize) -> Result<(), String> {
+    if offset > 32 - 20 {
+        return Err(format!(
+            "address offset {} leaves less than 20 bytes of the hash",
+            offset
+        ));
+    }
+    Ok(())
+}
+
+/// The 20 address bytes of `hash` starting at `offset`, or `None` if they don't fit.
+pub fn address_from_hash(hash: &[u8; 32], offset: usize) -> Option<[u8; 20]> {
+    hash.get(offset..offset + 20)?.try_into().ok()
+}
+
+/// Words per result in the kernel output: the two nonce words followed by the hash.
+pub const RESULT_WORDS: u32 = 2 + 8;
+
+/// Sizing of one mining cycle, valid for any work size and workgroup size (zero is raised to
+/// one), so tiny batches used in tests dispatch and map buffers like large ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchGeometry {
+    pub work_size: u32,
+    pub workgroup_size: u32,
+}
+
+impl BatchGeometry {
+    pub fn new(work_size: u32, workgroup_size: u32) -> Self {
+        Self {
+            work_size: work_size.max(1),
+            workgroup_size: workgroup_size.max(1),
+        }
+    }
+
+    /// Workgroups needed for `entries` nonces, at least one.
+    pub fn workgroups(&self, entries: u32) -> u32 {
+        entries
+            .clamp(1, self.work_size)
+            .div_ceil(self.workgroup_size)
+    }
+
+    /// Size in bytes of the result buffers, holding one full batch.
+    pub fn result_buffer_size(&self) -> u64 {
+        self.work_size as u64 * RESULT_WORDS as u64 * 4
+    }
+
+    /// The largest batch of at most this work size whose result buffers fit in `max_buffer_size`
+    /// bytes and which dispatches at most `max_workgroups` workgroups.
+    pub fn fit(&self, max_buffer_size: u64, max_workgroups: u32) -> Self {
+        let by_buffer = max_buffer_size / (RESULT_WORDS as u64 * 4);
+        let by_workgroups = max_workgroups as u64 * self.workgroup_size as u64;
+        let work_size = (self.work_size as u64).min(by_buffer).min(by_workgroups);
+        Self::new(work_size as u32, self.workgroup_size)
+    }
+
+    /// Check that the batch fits a device whose result buffers hold at most `max_buffer_size`
+    /// bytes and which dispatches at most `max_workgroups` workgroups, see
+    /// [`BatchGeometry::fit`].
+    pub fn check_limits(&self, max_buffer_size: u64, max_workgroups: u32) -> Result<(), String> {
+        let fitted = self.fit(max_buffer_size, max_workgroups);
+        if fitted == *self {
+            return Ok(());
+        }
+        Err(format!(
+            "work size of {} nonces needs {} byte buffers and {} workgroups, more than this device allows ({} byte buffers, {} workgroups), use at most {}",
+            self.work_size,
+            self.result_buffer_size(),
+            self.workgroups(self.work_size),
+            max_buffer_size,
+            max_workgroups,
+            fitted.work_size
+        ))
+    }
+
+    /// Size in bytes of the match bitmap, one word per workgroup of a full batch.
+    pub fn bitmap_size(&self) -> u64 {
+        self.workgroups(self.work_size) as u64 * 4
+    }
+
+    /// Consecutive entries of a workgroup sharing one bit of its bitmap word.
+    pub fn entries_per_bit(&self) -> u32 {
+        self.workgroup_size.div_ceil(BITMAP_BITS)
+    }
+
+    /// Entries flagged in `bitmap`, as ranges merged where adjacent and clipped to the
+    /// `entries` dispatched.
+    pub fn flagged_ranges(&self, bitmap: &[u32], entries: u32) -> Vec<Range<u32>> {
+        let per_bit = self.entries_per_bit();
+        let mut ranges: Vec<Range<u32>> = vec![];
+        for (workgroup, word) in bitmap.iter().enumerate() {
+            let workgroup_start = workgroup as u32 * self.workgroup_size;
+            let workgroup_end = (workgroup_start + self.workgroup_size).min(entries);
+            for bit in (0..BITMAP_BITS).filter(|bit| word & (1 << bit) != 0) {
+                let start = workgroup_start + bit * per_bit;
+                let end = (start + per_bit).min(workgroup_end);
+                if start >= end {
+                    continue;
+                }
+                match ranges.last_mut() {
+                    Some(last) if last.end == start => last.end = end,
+                    _ => ranges.push(start..end),
+                }
+            }
+        }
+        ranges
+    }
+
+    /// Bytes of the result buffers holding `entries`.
+    pub fn byte_range(&self, entries: &Range<u32>) -> Range<u64> {
+        let entry_size = RESULT_WORDS as u64 * 4;
+        entries.start as u64 * entry_size..entries.end as u64 * entry_size
+    }
+}
+
+/// Check that workgroups of `size` threads fit the device, which runs workgroups of at most
+/// `max_size_x` threads along x and `max_invocations` threads in all.
+pub fn validate_workgroup_size(
+    size: u32,
+    max_size_x: u32,
+    max_invocations: u32,
+) -> Result<(), String> {
+    let max = max_size_x.min(max_invocations);
+    if size == 0 || size > max {
+        return Err(format!(
+            "workgroup size {} is out of range for this device, which runs 1 to {} threads per workgroup",
+            size, max
+        ));
+    }
+    Ok(())
+}
+
+/// Bits of a workgroup's match bitmap word.
+pub const BITMAP_BITS: u32 = 32;
+
+/// Attempts per second, or zero while less than a millisecond has elapsed so the first status
+/// lines of tiny batches don't report absurd rates.
+pub fn hash_rate(attempts: u64, elapsed: Duration) -> f64 {
+    if elapsed < Duration::from_millis(1) {
+        return 0.0;
+    }
+
+    attempts as f64 / elapsed.as_secs_f64()
+}
+
+/// Rank an address by its number of leading zero nibbles, then by its total number of zero
+/// nibbles. Higher is better.
+pub fn score(address: &[u8]) -> u32 {
+    let nibbles = address.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]);
+    let leading = nibbles.clone().take_while(|&nibble| nibble == 0).count() as u32;
+    let total = nibbles.filter(|&nibble| nibble == 0).count() as u32;
+
+    leading * 64 + total
+}
+
+/// Results held in memory for `--sort-on-exit`, keeping the best `capacity` of them.
+pub struct RankedResults<T = String> {
+    capacity: usize,
+    entries: Vec<(u32, [u8; 20], T)>,
+}
+
+impl<T> RankedResults<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: vec![],
+        }
+    }
+
+    /// Hold the result line `line` for `address`.
+    pub fn push(&mut self, address: [u8; 20], line: T) {
+        self.entries.push((score(&address), address, line));
+
+        // trim lazily so pushes stay amortized constant time
+        if self.entries.len() >= self.capacity * 2 {
+            self.sort_and_truncate();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len().min(self.capacity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The held lines ranked by score descending, then by address.
+    pub fn into_sorted(self) -> Vec<T> {
+        self.into_sorted_results()
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect()
+    }
+
+    /// The held results ranked like [`RankedResults::into_sorted`], with their addresses.
+    pub fn into_sorted_results(mut self) -> Vec<([u8; 20], T)> {
+        self.sort_and_truncate();
+        self.entries
+            .into_iter()
+            .map(|(_, address, line)| (address, line))
+            .collect()
+    }
+
+    fn sort_and_truncate(&mut self) {
+        self.entries
+            .sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        self.entries.truncate(self.capacity);
+    }
+}
+
+/// Whether the first `nibbles` hex digits of `address` equal its last `nibbles` hex digits, in
+/// the same order (e.g. `0xcafe…cafe`). A random address matches with probability
+/// `16^-nibbles`.
+pub fn has_symmetric_ends(address: &[u8], nibbles: u8) -> bool {
+    let nibble = |idx: usize| {
+        let byte = address[idx / 2];
+        if idx.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        }
+    };
+
+    let total = address.len() * 2;
+    let nibbles = nibbles as usize;
+    nibbles <= total / 2 && (0..nibbles).all(|i| nibble(i) == nibble(total - nibbles + i))
+}
+
+/// Whether `address` starts with `nibbles` zero hex digits, checking the high then the low
+/// nibble of each leading byte. A random address matches with probability `16^-nibbles`.
+pub fn has_leading_zero_nibbles(address: &[u8], nibbles: u8) -> bool {
+    let nibbles = nibbles as usize;
+    nibbles <= address.len() * 2
+        && address[..nibbles / 2].iter().all(|&byte| byte == 0)
+        && (nibbles.is_multiple_of(2) || address[nibbles / 2] >> 4 == 0)
+}
+
+/// Where mined addresses are compared against the reward.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchPlacement {
+    /// The kernel compares each address against a byte prefix and only hands back the hits,
+    /// instead of every hash of the cycle.
+    Gpu { prefix: Vec<u8> },
+    /// The kernel checks the high and the low nibble of each leading byte and only hands back
+    /// the addresses starting with `nibbles` zero nibbles.
+    GpuZeroNibbles { nibbles: u8 },
+    /// Every hash is copied back and checked on the CPU.
+    Cpu,
+}
+
+impl MatchPlacement {
+    /// GPU matching for rewards that reduce to a fixed byte prefix, CPU matching for anything
+    /// the kernel can't express.
+    pub fn for_reward(reward: &RewardVariant) -> Self {
+        match reward {
+            RewardVariant::LeadingZeros { zeros_threshold } => MatchPlacement::Gpu {
+                prefix: vec![0; *zeros_threshold as usize],
+            },
+            RewardVariant::LeadingZeroNibbles { threshold } => {
+                MatchPlacement::GpuZeroNibbles { nibbles: *threshold }
+            }
+            RewardVariant::Matching { pattern } | RewardVariant::ChecksumMatching { pattern } => {
+                match pattern.split_once("...") {
+                    Some((leading, "")) => match hex::decode(leading) {
+                        Ok(prefix) if !prefix.is_empty() && prefix.len() <= 20 => {
+                            MatchPlacement::Gpu { prefix }
+                        }
+                        _ => MatchPlacement::Cpu,
+                    },
+                    _ => MatchPlacement::Cpu,
+                }
+            }
+            RewardVariant::TotalZeros { .. }
+            | RewardVariant::LeadingAndTotalZeros { .. }
+            | RewardVariant::LeadingOrTotalZeros { .. }
+            | RewardVariant::MatchingAny { .. }
+            | RewardVariant::SymmetricEnds { .. } => MatchPlacement::Cpu,
+        }
+    }
+
+    /// Words of the kernel's pattern config from index 3 on: the placement, the prefix length
+    /// and the prefix packed little-endian into five words. Zero nibble placements pass their
+    /// nibble count in place of the prefix length, without a prefix.
+    pub fn kernel_config(&self) -> [u32; 7] {
+        match self {
+            MatchPlacement::Gpu { prefix } => pack_prefix(1, prefix),
+            MatchPlacement::GpuZeroNibbles { nibbles } => [2, *nibbles as u32, 0, 0, 0, 0, 0],
+            MatchPlacement::Cpu => [0; 7],
+        }
+    }
+}
+
+fn pack_prefix(placement: u32, prefix: &[u8]) -> [u32; 7] {
+    let mut config = [0u32; 7];
+    config[0] = placement;
+    config[1] = prefix.len() as u32;
+    for (i, byte) in prefix.iter().enumerate() {
+        config[2 + i / 4] |= (*byte as u32) << (8 * (i % 4));
+    }
+    config
+}
+
+/// Bytes every address matching `reward` starts with, empty when there are none. With CPU
+/// matching the kernel flags the hashes passing this coarse check in the match bitmap, so
+/// only their part of the result buffer is read back.
+pub fn coarse_prefix(reward: &RewardVariant) -> Vec<u8> {
+    let prefix = match reward {
+        RewardVariant::LeadingZeros { zeros_threshold } => vec![0; *zeros_threshold as usize],
+        RewardVariant::LeadingZeroNibbles { threshold } => vec![0; *threshold as usize / 2],
+        RewardVariant::Matching { pattern } | RewardVariant::ChecksumMatching { pattern } => {
+            let digits = pattern.chars().take_while(char::is_ascii_hexdigit).count();
+            hex::decode(&pattern[..digits - digits % 2]).unwrap_or_default()
+        }
+        // the bytes every pattern starts with
+        RewardVariant::MatchingAny { patterns } => {
+            let mut prefixes = patterns.iter().map(|pattern| {
+                coarse_prefix(&RewardVariant::Matching {
+                    pattern: pattern.clone(),
+                })
+            });
+            let first = prefixes.next().unwrap_or_default();
+            let common = prefixes.fold(first.len(), |common, prefix| {
+                first
+                    .iter()
+                    .zip(&prefix)
+                    .take(common)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            first[..common].to_vec()
+        }
+        // the CPU checks of the combined zero rewards don't require leading zero bytes yet
+        RewardVariant::TotalZeros { .. }
+        | RewardVariant::LeadingAndTotalZeros { .. }
+        | RewardVariant::LeadingOrTotalZeros { .. }
+        | RewardVariant::SymmetricEnds { .. } => vec![],
+    };
+    prefix.into_iter().take(20).collect()
+}
+
+/// An address as results render it, EIP-55 checksummed.
+pub fn format_address(address: &[u8; 20]) -> String {
+    Address::from(*address).to_checksum(None)
+}
+
+/// Whether the EIP-55 checksummed form of `address` matches `pattern`, a `--matching` pattern
+/// whose letters must have the same case in the checksummed address.
+pub fn checksum_matches(address: &[u8; 20], pattern: &str) -> bool {
+    let (prefix, suffix) = pattern.split_once("...").unwrap_or((pattern, ""));
+    let Some(suffix_start) = 40usize.checked_sub(suffix.len()) else {
+        return false;
+    };
+    // most addresses already differ in their lowercase hex, which spares hashing them
+    if !checksum::spells_ignoring_case(address, 0, prefix)
+        || !checksum::spells_ignoring_case(address, suffix_start, suffix)
+    {
+        return false;
+    }
+    let mask = checksum::checksum_mask(address, None);
+    checksum::spells(address, mask, 0, prefix) && checksum::spells(address, mask, suffix_start, suffix)
+}
+
+/// The bytes a `--matching` pattern fixes at the start and at the end of an address, such as
+/// `aabb` and `ccdd` for `aabb...ccdd`. A pattern without `...` is a leading byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PatternBytes {
+    pub leading: Vec<u8>,
+    pub trailing: Vec<u8>,
+}
+
+impl PatternBytes {
+    /// The bytes of `pattern`, none if one of its parts is not whole hex bytes.
+    pub fn parse(pattern: &str) -> Option<Self> {
+        let (leading, trailing) = pattern.split_once("...").unwrap_or((pattern, ""));
+        Some(Self {
+            leading: hex::decode(leading).ok()?,
+            trailing: hex::decode(trailing).ok()?,
+        })
+    }
+
+    /// Whether `address` starts with the leading and ends with the trailing bytes, without
+    /// the two overlapping.
+    pub fn matches(&self, address: &[u8]) -> bool {
+        self.leading.len() + self.trailing.len() <= address.len()
+            && address.starts_with(&self.leading)
+            && address.ends_with(&self.trailing)
+    }
+}
+
+/// Pattern config words from index 3 on for CPU matching with a coarse check against `prefix`.
+pub fn coarse_kernel_config(prefix: &[u8]) -> [u32; 7] {
+    pack_prefix(0, prefix)
+}
+
+impl fmt::Display for MatchPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchPlacement::Gpu { prefix } => {
+                write!(f, "GPU (prefix 0x{})", hex::encode(prefix))
+            }
+            MatchPlacement::GpuZeroNibbles { nibbles } => {
+                write!(f, "GPU ({} leading zero nibbles)", nibbles)
+            }
+            MatchPlacement::Cpu => write!(f, "CPU"),
+        }
+    }
+}
+
+/// Address of the canonical CreateX deployment, the same on every chain, and the factory mined
+/// for when none is given.
+pub const CREATEX_ADDRESS: [u8; 20] = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+
+/// Code hash of the proxy CreateX deploys with CREATE2 before a CREATE3 deployment.
+pub const CREATE3_PROXY_INITCODE_HASH: [u8; 32] =
+    hex!("21c35dbe1b344a2488cf3321d6ce542f8e9f305544ff09e4993a62319a497c1f");
+
+/// `0xff || deployer || salt || init code hash`, hashed by CREATE2.
+pub fn create2_preimage(
+    deployer: &[u8; 20],
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 85] {
+    let mut preimage = [0u8; 85];
+    preimage[0] = 0xff;
+    preimage[1..21].copy_from_slice(deployer);
+    preimage[21..53].copy_from_slice(salt);
+    preimage[53..].copy_from_slice(init_code_hash);
+    preimage
+}
+
+/// `rlp([deployer, 1])`, hashed by the first CREATE of a contract.
+pub fn first_create_preimage(deployer: &[u8; 20]) -> [u8; 23] {
+    let mut preimage = [0u8; 23];
+    preimage[0] = 0xd6;
+    preimage[1] = 0x94;
+    preimage[2..22].copy_from_slice(deployer);
+    preimage[22] = 0x01;
+    preimage
+}
+
+/// Address CreateX deploys to with CREATE2 from `factory` for an already guarded salt.
+pub fn create2_address(
+    factory: &[u8; 20],
+    guarded_salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 20] {
+    let hash = keccak256(create2_preimage(factory, guarded_salt, init_code_hash));
+    address_from_hash(&hash.0, ADDRESS_OFFSET).unwrap()
+}
+
+/// Address CreateX deploys to with CREATE3 from `factory` for an already guarded salt.
+pub fn create3_address(factory: &[u8; 20], guarded_salt: &[u8; 32]) -> [u8; 20] {
+    // the proxy deploys the contract with its first CREATE
+    let proxy = create2_address(factory, guarded_salt, &CREATE3_PROXY_INITCODE_HASH);
+    let hash = keccak256(first_create_preimage(&proxy));
+    address_from_hash(&hash.0, ADDRESS_OFFSET).unwrap()
+}
+
+/// The guarded salt CreateX deploys with for the user supplied `salt` of `salt_variant`, and the
+/// address it deploys to with CREATE2 from `factory`. Verifies mined salts without the GPU.
+pub fn compute_create2_address(
+    factory: &[u8; 20],
+    salt_variant: &SaltVariant,
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> ([u8; 32], [u8; 20]) {
+    let guarded_salt = salt_variant.guard(salt);
+    (
+        guarded_salt,
+        create2_address(factory, &guarded_salt, init_code_hash),
+    )
+}
+
+/// The guarded salt CreateX deploys with for the user supplied `salt` of `salt_variant`, and the
+/// address it deploys to with CREATE3 from `factory`.
+pub fn compute_create3_address(
+    factory: &[u8; 20],
+    salt_variant: &SaltVariant,
+    salt: &[u8; 32],
+) -> ([u8; 32], [u8; 20]) {
+    let guarded_salt = salt_variant.guard(salt);
+    (guarded_salt, create3_address(factory, &guarded_salt))
+}
+
+impl CreateVariant {
+    /// Address CreateX deploys to with this variant from `factory` for an already guarded salt.
+    pub fn address(&self, factory: &[u8; 20], guarded_salt: &[u8; 32]) -> [u8; 20] {
+        match self {
+            CreateVariant::Create2 { init_code_hash } => {
+                create2_address(factory, guarded_salt, init_code_hash)
+            }
+            CreateVariant::Create3 => create3_address(factory, guarded_salt),
+        }
+    }
+
+    /// The hash the mining kernel takes the address of an already guarded salt from, see
+    /// [`derivation_config`].
+    pub fn kernel_hash(&self, factory: &[u8; 20], guarded_salt: &[u8; 32]) -> [u8; 32] {
+        match self {
+            CreateVariant::Create2 { init_code_hash } => {
+                keccak256(create2_preimage(factory, guarded_salt, init_code_hash)).0
+            }
+            CreateVariant::Create3 => {
+                let proxy = create2_address(factory, guarded_salt, &CREATE3_PROXY_INITCODE_HASH);
+                keccak256(first_create_preimage(&proxy)).0
+            }
+        }
+    }
+}
+
+/// How the mining kernel derives addresses from salts, its `DERIVATION` override constant: 1
+/// hashes them into CREATE2 addresses, 2 into the address the CREATE3 proxy deploys to.
+pub fn kernel_derivation(variant: &CreateVariant) -> u32 {
+    match variant {
+        CreateVariant::Create2 { .. } => 1,
+        CreateVariant::Create3 => 2,
+    }
+}
+
+/// Derivation uniform of the mining kernel as little-endian words:
+///
+/// - words 0 to 23: the CREATE2 preimage of `factory` with a zero salt, which the kernel fills
+///   the guarded salt into. CREATE3 deploys its proxy with this preimage too.
+/// - words 24 to 47: the guard preimage of the salt of nonce 0, ending in the nonce, which the
+///   kernel fills in. Its length is [`kernel_guard_size`].
+pub fn derivation_config(
+    variant: &CreateVariant,
+    salt_variant: &SaltVariant,
+    factory: &[u8; 20],
+) -> [u32; 48] {
+    let init_code_hash = match variant {
+        CreateVariant::Create2 { init_code_hash } => init_code_hash,
+        CreateVariant::Create3 => &CREATE3_PROXY_INITCODE_HASH,
+    };
+    let mut preimages = [0u8; 192];
+    preimages[..85].copy_from_slice(&create2_preimage(factory, &[0; 32], init_code_hash));
+    let guard = salt_variant.guard_preimage(&salt_variant.salt(0));
+    preimages[96..96 + guard.len()].copy_from_slice(&guard);
+    let mut config = [0u32; 48];
+    for (word, bytes) in config.iter_mut().zip(preimages.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    config
+}
+
+/// Bytes of the guard preimage the mining kernel hashes, its `SALT_GUARD_SIZE` override constant.
+pub fn kernel_guard_size(salt_variant: &SaltVariant) -> u32 {
+    salt_variant.guard_preimage(&[0; 32]).len() as u32
+}
+
+#[cfg(feature = "miner")]
+/// Override constants of the mining kernel for `config`, by name.
+pub fn kernel_constants(config: &Config) -> BTreeMap<&'static str, u32> {
+    BTreeMap::from([
+        ("DERIVATION", kernel_derivation(&config.create_variant)),
+        (
+            "DUAL_SALT",
+            (config.dual_salt && matches!(config.salt_variant, SaltVariant::Crosschain { .. }))
+                as u32,
+        ),
+        ("SALT_GUARD_SIZE", kernel_guard_size(&config.salt_variant)),
+        ("WORKGROUP_SIZE", config.workgroup_size),
+    ])
+}
+
+#[cfg(feature = "miner")]
+/// Source of the mining kernel for `config`. The kernel is specialized through override
+/// constants rather than assembled from sections, so every config runs [`KECCAK256_KERNEL`],
+/// or the `kernel` of the config, behind a header listing the constants and a hash of them and
+/// the kernel. The same config always gives the same source, and pipeline caches keyed by it
+/// change with the constants.
+pub fn kernel_source(config: &Config) -> String {
+    let kernel = config.kernel.as_deref().unwrap_or(KECCAK256_KERNEL);
+    let constants: String = kernel_constants(config)
+        .iter()
+        .map(|(name, value)| format!("// override {} = {}\n", name, value))
+        .collect();
+    let hash = keccak256(format!("{}{}", constants, kernel));
+    format!(
+        "// createXcrunch mining kernel 0x{}\n{}{}",
+        hex::encode(&hash[..8]),
+        constants,
+        kernel
+    )
+}
+
+/// The two salts mined together by `--dual-salt` for one nonce. Both have a zero sender part
+/// and end in the same 11 bytes (3 zero bytes and the big-endian nonce), and differ only in the
+/// redeploy protection flag:
+///
+/// - `crosschain`: `0x00{20} || 0x01 || tail`, guarded as `keccak256(chain_id || salt)`
+/// - `random`: `0x00{20} || 0x00 || tail`, guarded as `keccak256(salt)`
+pub struct DualSalt {
+    pub crosschain: [u8; 32],
+    pub random: [u8; 32],
+}
+
+impl DualSalt {
+    pub fn new(nonce: u64) -> Self {
+        let mut random = [0u8; 32];
+        random[24..].copy_from_slice(&nonce.to_be_bytes());
+        let mut crosschain = random;
+        crosschain[20] = 0x01;
+
+        Self { crosschain, random }
+    }
+
+    /// Addresses of both salts deployed with `variant` on `chain_id`, as `(crosschain, random)`.
+    pub fn addresses(
+        &self,
+        variant: &CreateVariant,
+        factory: &[u8; 20],
+        chain_id: &[u8; 32],
+    ) -> ([u8; 20], [u8; 20]) {
+        let crosschain = SaltVariant::Crosschain {
+            chain_id: *chain_id,
+        }
+        .guard(&self.crosschain);
+        let random = SaltVariant::Random.guard(&self.random);
+
+        (
+            variant.address(factory, &crosschain),
+            variant.address(factory, &random),
+        )
+    }
+}
+
+#[cfg(feature = "miner")]
+/// A `--seed`, a decimal integer or `0x`-prefixed hex of up to 32 bytes. Runs of the same
+/// seed share their session seed, so they start at the same nonce and scan in the same order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Seed(pub [u8; 32]);
 
-    // exclusively lock the file for appending
-    file.lock_exclusive().expect("Couldn't lock the file.");
+#[cfg(feature = "miner")]
+impl Seed {
+    /// A seed drawn from the OS RNG.
+    pub fn random() -> Self {
+        let mut seed = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut seed);
+        Seed(seed)
+    }
+
+    /// The session seed of the seed, hashed so that small seeds start far apart.
+    pub fn session_seed(&self) -> [u8; 32] {
+        keccak256([b"createXcrunch seed".as_slice(), &self.0].concat()).0
+    }
+}
+
+#[cfg(feature = "miner")]
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+#[cfg(feature = "miner")]
+impl FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (digits, radix) = match s.strip_prefix("0x") {
+            Some(digits) => (digits, 16),
+            None => (s, 10),
+        };
+        U256::from_str_radix(digits, radix)
+            .ok()
+            .filter(|_| !digits.is_empty())
+            .map(|seed| Seed(seed.to_be_bytes()))
+            .ok_or_else(|| format!("invalid seed {}, expected a decimal integer or 0x-prefixed hex of up to 32 bytes", s))
+    }
+}
+
+#[cfg(feature = "miner")]
+/// Inputs mixed into the session seed when `--seed-entropy` is enabled.
+pub struct SeedEntropy<'a> {
+    pub os_random: [u8; 32],
+    pub clock_nanos: u128,
+    pub pid: u32,
+    pub gpu_name: &'a str,
+}
+
+#[cfg(feature = "miner")]
+impl<'a> SeedEntropy<'a> {
+    /// Collect entropy from the OS RNG, the high-resolution clock and the process id.
+    pub fn gather(gpu_name: &'a str) -> Self {
+        let mut os_random = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut os_random);
+
+        let clock_nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+
+        Self {
+            os_random,
+            clock_nanos,
+            pid: std::process::id(),
+            gpu_name,
+        }
+    }
+}
+
+#[cfg(feature = "miner")]
+/// Mix all entropy sources into a 32-byte session seed.
+pub fn mix_session_seed(entropy: &SeedEntropy) -> [u8; 32] {
+    let mut preimage = Vec::with_capacity(32 + 16 + 4 + entropy.gpu_name.len());
+    preimage.extend_from_slice(&entropy.os_random);
+    preimage.extend_from_slice(&entropy.clock_nanos.to_be_bytes());
+    preimage.extend_from_slice(&entropy.pid.to_be_bytes());
+    preimage.extend_from_slice(entropy.gpu_name.as_bytes());
+
+    keccak256(preimage).0
+}
+
+/// Short fingerprint of a session seed that is safe to log.
+pub fn seed_fingerprint(seed: &[u8; 32]) -> String {
+    hex::encode(&keccak256(seed)[..4])
+}
+
+/// Starting nonce derived from a session seed.
+pub fn seed_start_nonce(seed: &[u8; 32]) -> u64 {
+    u64::from_be_bytes(seed[..8].try_into().unwrap())
+}
 
-    file
+/// Seed of the non-linear scan orders derived from a session seed, independent of the
+/// starting nonce.
+pub fn seed_scan_seed(seed: &[u8; 32]) -> u64 {
+    u64::from_be_bytes(seed[8..16].try_into().unwrap())
 }
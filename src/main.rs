@@ -1,56 +1,808 @@
-use clap::Parser;
-use crate::cli::{Cli, Commands, CliArgs};
-use createxcrunch::{Config, RewardVariant, CreateVariant};
-use alloy_primitives::hex;
+use clap::{parser::ValueSource, CommandFactory, FromArgMatches};
+use crate::cli::{BatchArgs, BenchArgs, CampaignCommand, CampaignReportArgs, Cli, Commands, CliArgs, Create2Args, ExportArgs, PrepareTxArgs, HashInitCodeArgs, RarityArgs, ReplayArgs, StatsArgs, VerifyArgs};
+use std::{
+    path::Path,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+    time::Duration,
+};
+use createxcrunch::{batch, bundle, campaign::{self, Discovery, Report}, config_file::{self, ConfigFile}, cycle_stats::{self, StatsFile, Summary}, deploy_tx::{self, Deployment, Eip1559Transaction, Fees, MinedResult}, http::DefaultTransport, interrupt, kernel_file, params, plan, webgpu, pattern_file::PatternWatcher, pool, presets, ranges, redact, replay::{self, CycleRecord, Replayed}, reserved, rpc::RpcClient, selftest::SelfTestMode, stats::{CostModel, Rarity}, compute_create2_address, compute_create3_address, parse_hex_code, parse_init_code_hash, Config, CREATEX_ADDRESS, OutputFormat, RewardVariant, CreateVariant, SaltVariant, Seed};
+use alloy_primitives::{aliases::U64, hex, Address};
 
 mod cli;
-mod webgpu;
 
 // Helper function to create config from CLI args
-fn factory_init_config(args: &CliArgs) -> Result<Config, String> {
-    let reward = match (args.zeros, args.total, args.either, args.pattern.clone()) {
-        (Some(zeros), None, false, None) => RewardVariant::LeadingZeros {
+fn factory_init_config(args: &CliArgs) -> Result<Config<'_>, String> {
+    let patterns = match &args.pattern_file {
+        Some(path) => PatternWatcher::open(path)?
+            .patterns()
+            .iter()
+            .map(|pattern| pattern.pattern.as_str().into())
+            .collect(),
+        None => args.pattern.clone(),
+    };
+    let pattern = (!patterns.is_empty()).then_some(patterns);
+    let reward = match (
+        args.zeros,
+        args.total,
+        args.either,
+        pattern,
+        args.symmetric_ends,
+        args.leading_nibbles,
+    ) {
+        (Some(zeros), None, false, None, None, None) => RewardVariant::LeadingZeros {
             zeros_threshold: zeros,
         },
-        (None, Some(total), false, None) => RewardVariant::TotalZeros {
+        (None, Some(total), false, None, None, None) => RewardVariant::TotalZeros {
             zeros_threshold: total,
         },
-        (Some(zeros), Some(total), false, None) => RewardVariant::LeadingAndTotalZeros {
+        (Some(zeros), Some(total), false, None, None, None) => RewardVariant::LeadingAndTotalZeros {
             leading_zeros_threshold: zeros,
             total_zeros_threshold: total,
         },
-        (Some(zeros), Some(total), true, None) => RewardVariant::LeadingOrTotalZeros {
+        (Some(zeros), Some(total), true, None, None, None) => RewardVariant::LeadingOrTotalZeros {
             leading_zeros_threshold: zeros,
             total_zeros_threshold: total,
         },
-        (None, None, false, Some(pattern)) => {
-            let pattern = pattern
-                .strip_prefix("0x")
-                .unwrap_or(&pattern)
-                .to_owned()
-                .into_boxed_str();
-            RewardVariant::Matching { pattern }
+        (None, None, false, Some(patterns), None, None) => {
+            let mut patterns: Vec<Box<str>> = patterns
+                .iter()
+                .map(|pattern| pattern.strip_prefix("0x").unwrap_or(pattern).into())
+                .collect();
+            match (patterns.len(), args.checksum_case) {
+                (1, true) => RewardVariant::ChecksumMatching { pattern: patterns.remove(0) },
+                (1, false) => RewardVariant::Matching { pattern: patterns.remove(0) },
+                (_, true) => return Err("--checksum-case takes a single --matching pattern".to_string()),
+                (_, false) => RewardVariant::MatchingAny { patterns },
+            }
         }
+        (None, None, false, None, Some(nibbles), None) => RewardVariant::SymmetricEnds { nibbles },
+        (None, None, false, None, None, Some(threshold)) => RewardVariant::LeadingZeroNibbles { threshold },
         _ => unreachable!(),
     };
 
-    Ok(Config::new(
+    let factory = match &args.factory {
+        Some(factory) => presets::find(factory).map_or(factory.clone(), |preset| preset.checksummed_address()),
+        None => Address::from(CREATEX_ADDRESS).to_checksum(None),
+    };
+    let mut config = Config::new(
         args.gpu_device_id,
-        &args.factory,
+        &factory,
         args.caller.as_deref(),
         args.chain_id,
         None,
         reward,
         &args.output,
-    )?)
+    )
+    .map_err(|e| e.to_string())?;
+    config.default_factory = args.factory.is_none();
+    config.no_file = args.no_file;
+    config.redact_params = args.params_stdin && !args.no_redact_params;
+    config.seed_entropy = args.seed_entropy;
+    // Without --seed every run draws one, which it prints so the search can be repeated. Pool
+    // leases fix the nonces instead
+    config.seed = match (args.seed, args.seed_entropy || args.pool_url.is_some()) {
+        (Some(seed), _) => Some(seed),
+        (None, false) => Some(Seed::random()),
+        (None, true) => None,
+    };
+    config.bundle = args.bundle.as_deref();
+    config.debug_env = args.debug_env;
+    config.clipboard = args.clipboard;
+    config.redact_salts = args.redact_salts;
+    config.worked_example = !args.no_worked_example;
+    config.fast_start = args.fast_start;
+    // Logs and pipes can't redraw the status, so they get a line per result unless --supervised
+    // asks for plain status lines
+    let progress_term = match args.no_file {
+        true => console::Term::stderr(),
+        false => console::Term::stdout(),
+    };
+    config.quiet = args.quiet || (!args.supervised && !progress_term.is_term());
+    config.json_events = args.json_events;
+    config.rpc_url = args.rpc_url.as_deref();
+    config.require_known_factory = args.require_known_factory;
+    config.rpc_rate_limit = args
+        .rpc_max_rps
+        .map(|max_rps| (max_rps, args.rpc_burst.unwrap_or(max_rps)));
+    config.format = args.format;
+    config.self_test = match args.strict {
+        true => Some(SelfTestMode::Full),
+        false => args.self_test,
+    };
+    config.strict = args.strict;
+    config.max_pending_matches = args.max_pending_matches;
+    config.backpressure = args.backpressure;
+    config.allow_warnings = args.allow.clone();
+    config.deny_warnings = args.deny_warnings;
+    config.dual_salt = args.dual_salt;
+    config.avoid_reserved = match (args.avoid_reserved, args.allow_reserved) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => config.avoid_reserved,
+    };
+    if config.avoid_reserved {
+        reserved::check_avoidable(&config.reward, args.force)?;
+    }
+    config.emit_salt_format = args.emit_salt_format;
+    config.scan_order = args.scan_order;
+    config.work_size = args.work_size;
+    config.workgroup_size = args.workgroup_size;
+    config.kernel = args
+        .kernel_file
+        .as_deref()
+        .map(|path| kernel_file::load(Path::new(path)))
+        .transpose()?;
+    config.pattern_file = args.pattern_file.as_deref();
+    config.limit = args.limit;
+    config.max_results = args.max_results;
+    config.timeout = args.timeout.map(Duration::from_secs);
+    config.max_runtime = args.max_runtime;
+    config.sort_on_exit = args.sort_on_exit.then_some(args.sort_capacity);
+    config.split_output = args.split_output_by;
+    config.on_duplicate_address = args.on_duplicate_address;
+    config.stats_file = args.stats_file.as_deref();
+    config.record_cycles = args.record_cycles.as_deref();
+    config.overwrite = args.overwrite;
+    config.supervised = args.supervised;
+    config.resume = args.resume;
+    config.backend = args.backend;
+    config.checkpoint_interval = Duration::from_secs(args.checkpoint_interval);
+    config.checkpoint_attempts = args.checkpoint_every_attempts;
+    config.checkpoint_cycles = args.checkpoint_every_cycles;
+    config.watchdog_stall = Duration::from_secs(args.watchdog_stall);
+    config.schedule = args.schedule.clone();
+    if let Some(name) = &args.campaign {
+        campaign::validate_name(name)?;
+    }
+    config.campaign = args.campaign.as_deref();
+    config.cost = CostModel::from_options(
+        args.power_watts,
+        args.price_per_kwh,
+        args.cloud_cost_per_hour,
+    );
+
+    #[cfg(feature = "dev")]
+    if let Some(offset) = args.address_offset {
+        createxcrunch::validate_address_offset(offset)?;
+        config.address_offset = offset;
+    }
+    #[cfg(feature = "dev")]
+    {
+        config.inject_corruption = args.inject_corruption;
+    }
+
+    if let Some(init_code) = &args.init_code {
+        let init_code = match init_code.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("could not read init code file {}: {}", path, e))?,
+            None => init_code.clone(),
+        };
+        config.init_code = Some(parse_hex_code(&init_code).map_err(|e| format!("invalid init code: {}", e))?);
+    }
+    config.safe_chain_id = args.safe_chain_id.or(args.chain_id);
+    if config.format == OutputFormat::SafeTx {
+        if config.init_code.is_none() {
+            return Err("--format safe-tx requires --init-code".to_string());
+        }
+        if config.safe_chain_id.is_none() {
+            return Err("--format safe-tx requires --safe-chain-id or --chain-id".to_string());
+        }
+        if config.split_output.is_some() {
+            return Err("--format safe-tx cannot be split into bucket files".to_string());
+        }
+    }
+
+    if let Some(path) = &args.skip_ranges {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("could not read skip ranges file {}: {}", path, e))?;
+        config.skip_ranges = ranges::parse_skip_ranges(&contents, &config.salt_prefix())
+            .map_err(|e| format!("invalid skip ranges file {}: {}", path, e))?;
+    }
+
+    Ok(config)
+}
+
+// Replace the deployment parameters of the command line with those of --params-stdin
+fn merge_params_stdin(command: &mut Commands) -> Result<(), String> {
+    let (args, init_code_hash) = match command {
+        Commands::Create3(args) => (args, None),
+        Commands::Create2(args) => (&mut args.cli_args, Some(&mut args.init_code_hash)),
+        _ => return Ok(()),
+    };
+    if !args.params_stdin {
+        return Ok(());
+    }
+
+    let stdin = params::read(std::io::stdin().lock())?;
+    if let Some(factory) = stdin.factory {
+        args.factory = Some(factory);
+    }
+    if stdin.caller.is_some() {
+        args.caller = stdin.caller;
+    }
+    match (init_code_hash, stdin.init_code_hash) {
+        (Some(init_code_hash), Some(given)) => *init_code_hash = Some(given),
+        (None, Some(_)) => return Err("--params-stdin gives an init code hash, which create3 runs don't use".to_string()),
+        (_, None) => {}
+    }
+    Ok(())
+}
+
+// Splice the options of a --config file in after the subcommand, so the command line overrides them
+fn with_config_file(argv: Vec<String>) -> Result<(Vec<String>, Option<ConfigFile>), String> {
+    let path = argv.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix("--config=") {
+        Some(path) => Some(path.to_string()),
+        None => (arg == "--config").then(|| argv.get(i + 1).cloned()).flatten(),
+    });
+    let (Some(path), Some(command)) = (path, argv.get(1)) else {
+        return Ok((argv, None));
+    };
+    if !["create2", "create3"].contains(&command.as_str()) {
+        return Ok((argv, None));
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("could not read config file {}: {}", path, e))?;
+    let file = ConfigFile::parse(&path, &contents)?;
+    let mut merged = argv[..2].to_vec();
+    merged.extend(file.args(command, &argv[2..]));
+    merged.extend_from_slice(&argv[2..]);
+    Ok((merged, Some(file)))
+}
+
+// The command line parser, reading the options `argv` leaves out from CREATEXCRUNCH_* variables.
+// Search criteria in `argv` replace those of the environment rather than conflicting with them,
+// and flags turned off in the environment are left out rather than requiring their options.
+fn cli_command(argv: &[String]) -> clap::Command {
+    let falsey = |arg: &clap::Arg| {
+        !arg.get_action().takes_values()
+            && arg.get_env().and_then(std::env::var_os).is_some_and(|value| {
+                ["", "0", "n", "no", "f", "false", "off"].contains(&value.to_string_lossy().to_lowercase().as_str())
+            })
+    };
+    let criteria_given = config_file::gives_criteria(argv);
+    let mut command = Cli::command();
+    for name in ["create2", "create3"] {
+        command = command.mut_subcommand(name, |mut subcommand| {
+            let ignored: Vec<_> = subcommand
+                .get_arguments()
+                .filter(|arg| {
+                    falsey(arg)
+                        || criteria_given
+                            && arg.get_long().is_some_and(|long| config_file::CRITERIA_OPTIONS.contains(&long))
+                })
+                .map(|arg| arg.get_id().clone())
+                .collect();
+            for id in ignored {
+                subcommand = subcommand.mut_arg(id, |arg| arg.env(None));
+            }
+            subcommand
+        });
+    }
+    command
+}
+
+// Where the value of the option an error is about came from, the command line or the environment
+fn error_source(error: &str, argv: &[String]) -> Option<String> {
+    let command = cli_command(argv);
+    let subcommand = command.find_subcommand(argv.get(1)?)?;
+    let source = |about: fn(&str, &str) -> bool| {
+        subcommand.get_arguments().find_map(|arg| {
+            let long = arg.get_long().filter(|long| about(error, long))?;
+            if config_file::gives(&argv[2..], long) {
+                return Some(format!("--{}", long));
+            }
+            let env = arg.get_env()?;
+            std::env::var_os(env).map(|_| format!("environment variable {}", env.to_string_lossy()))
+        })
+    };
+    source(config_file::names_option).or_else(|| source(config_file::describes_option))
+}
+
+// Render the options given for the run, from --config, the environment and the command line,
+// as a config file
+fn print_config(argv: &[String]) -> Result<String, String> {
+    let command = cli_command(argv);
+    let matches = command.clone().try_get_matches_from(argv).map_err(|e| e.to_string())?;
+    let (name, matches) = matches.subcommand().unwrap();
+    let options: Vec<(String, Vec<String>)> = command
+        .find_subcommand(name)
+        .unwrap()
+        .get_arguments()
+        .filter(|arg| !["config", "print-config"].contains(&arg.get_id().as_str()))
+        .filter(|arg| {
+            matches!(
+                matches.value_source(arg.get_id().as_str()),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .filter_map(|arg| {
+            let values = match arg.get_action().takes_values() {
+                true => matches
+                    .get_raw(arg.get_id().as_str())?
+                    .map(|value| value.to_string_lossy().into_owned())
+                    .collect(),
+                // flags can be turned off from the environment
+                false => matches.get_flag(arg.get_id().as_str()).then(Vec::new)?,
+            };
+            Some((arg.get_long()?.to_string(), values))
+        })
+        .collect();
+    Ok(config_file::render(&options))
+}
+
+// Helper function to create a CREATE2 config from CLI args
+fn create2_init_config(args: &Create2Args) -> Result<Config<'_>, String> {
+    let mut config = factory_init_config(&args.cli_args)?;
+
+    // Decode the init code hash
+    let init_code_hash = args
+        .init_code_hash
+        .as_deref()
+        .ok_or("create2 requires --code-hash, or init_code_hash in --params-stdin")?;
+    let init_code_hash = parse_init_code_hash(init_code_hash).map_err(|e| e.to_string())?;
+
+    // Set the create variant to Create2
+    config.create_variant = CreateVariant::Create2 { init_code_hash };
+
+    // Safe batches deploy the init code, which must be the one mined for
+    if let Some(init_code) = &config.init_code {
+        if createxcrunch::init_code_hash(init_code, &[]) != init_code_hash {
+            return Err("--init-code does not match the init code hash".to_string());
+        }
+    }
+
+    Ok(config)
+}
+
+// Run the jobs of a batch file in order, resuming after the last completed job
+fn run_batch(args: &BatchArgs) -> Result<(), String> {
+    let contents = std::fs::read_to_string(&args.file)
+        .map_err(|e| format!("could not read batch file {}: {}", args.file, e))?;
+    let jobs = batch::parse_jobs(&contents)?;
+
+    // Validate every job before starting the first one
+    let mut commands = Vec::with_capacity(jobs.len());
+    for job in &jobs {
+        let argv: Vec<String> = std::iter::once("createxcrunch".to_string()).chain(job.args.iter().cloned()).collect();
+        let cli = cli_command(&argv)
+            .try_get_matches_from(&argv)
+            .and_then(|matches| Cli::from_arg_matches(&matches))
+            .map_err(|e| format!("{}: {}", job.name, e.render().to_string().trim_end()))?;
+        commands.push(cli.command);
+    }
+    let mut configs = Vec::with_capacity(jobs.len());
+    for (job, command) in jobs.iter().zip(&commands) {
+        let config = match command {
+            Commands::Create3(CliArgs { pool_url: Some(_), .. })
+            | Commands::Create2(Create2Args { cli_args: CliArgs { pool_url: Some(_), .. }, .. }) => {
+                Err("pool-url cannot be used in a batch".to_string())
+            }
+            Commands::Create3(args) => factory_init_config(args),
+            Commands::Create2(args) => create2_init_config(args),
+            _ => unreachable!(),
+        };
+        let mut config = config.map_err(|e| format!("{}: {}", job.name, e))?;
+        config.limit = Some(job.limit);
+        configs.push(config);
+    }
+
+    let state_path = batch::BatchState::path(&args.file);
+    let mut state = batch::BatchState::load(Path::new(&state_path))?;
+    if state.next_job > 0 {
+        println!("Resuming batch at job {} of {}", state.next_job + 1, jobs.len());
+    }
+
+    let mut outcomes = vec![];
+    for (index, (job, config)) in jobs.iter().zip(configs).enumerate().skip(state.next_job) {
+        println!("Starting {} ({} of {})", job.name, index + 1, jobs.len());
+        let outcome = webgpu::gpu(config).map_err(|e| e.to_string());
+        match &outcome {
+            Ok(summary) => println!("Finished {}: {} results in {}s", job.name, summary.found, summary.elapsed.as_secs()),
+            Err(e) => eprintln!("Job {} failed: {}", job.name, e),
+        }
+        let failed = outcome.is_err();
+        outcomes.push((job.clone(), outcome));
+
+        if failed && args.stop_on_error {
+            break;
+        }
+        // An interrupted job did not finish, the batch resumes at it
+        if interrupt::interrupted() {
+            break;
+        }
+        state.next_job = index + 1;
+        state
+            .save(Path::new(&state_path))
+            .map_err(|e| format!("could not write batch state {}: {}", state_path, e))?;
+    }
+
+    println!("{}", batch::report(&outcomes));
+    if outcomes.iter().any(|(_, outcome)| outcome.is_err()) {
+        return Err("some jobs of the batch failed".to_string());
+    }
+    if interrupt::interrupted() {
+        return Err(format!("interrupted, the batch resumes at job {}", state.next_job + 1));
+    }
+
+    // A completed batch starts over when run again
+    let _ = std::fs::remove_file(&state_path);
+    Ok(())
+}
+
+// Mine leases of a cooperative pool until it runs out of work
+fn run_pool<'a>(
+    args: &'a CliArgs,
+    init_config: impl Fn() -> Result<Config<'a>, String>,
+) -> Result<(), String> {
+    let transport = DefaultTransport::new(args.pool_url.as_deref().unwrap())?;
+    let mut client = pool::PoolClient::new(transport.clone(), &args.pool_worker);
+    let queue_path = pool::queue_path(&args.output);
+    let save_queue = |client: &pool::PoolClient<_>| {
+        client
+            .save_queue(Path::new(&queue_path))
+            .map_err(|e| format!("could not write pool queue {}: {}", queue_path, e))
+    };
+    client.load_queue(Path::new(&queue_path))?;
+
+    let mut leases = 0;
+    loop {
+        let queued = client.flush();
+        save_queue(&client)?;
+        if queued > 0 {
+            println!("{} submissions queued until the pool is reachable", queued);
+        }
+
+        let Some(lease) = client.request_lease()? else {
+            break;
+        };
+        let mut config = init_config()?;
+        if lease.salt_prefix_bytes()? != config.salt_prefix() {
+            return Err(format!(
+                "lease {} uses salt prefix {}, which this miner cannot mine",
+                lease.id, lease.salt_prefix
+            ));
+        }
+        config.lease = Some(lease.range());
+        config.append_output = leases > 0;
+        println!("Mining lease {} (nonces {} to {})", lease.id, lease.start, lease.end);
+
+        // Keep the lease while it is mined
+        let stop = Arc::new(AtomicBool::new(false));
+        let renewal = pool::spawn_renewal(
+            pool::PoolClient::new(transport.clone(), &args.pool_worker),
+            lease,
+            stop.clone(),
+        );
+        let summary = webgpu::gpu(config).map_err(|e| e.to_string());
+        stop.store(true, Ordering::Relaxed);
+        let lease = renewal.join().map_err(|_| "lease renewal thread panicked".to_string())?;
+        let summary = summary?;
+
+        let finds = summary
+            .results
+            .iter()
+            .map(|(salt, address)| pool::Find::new(salt, address))
+            .collect();
+        client.submit(pool::Submission::new(&lease, finds, summary.attempts));
+        save_queue(&client)?;
+        leases += 1;
+
+        // What was found of an interrupted lease is still submitted
+        if interrupt::interrupted() {
+            client.flush();
+            save_queue(&client)?;
+            break;
+        }
+    }
+
+    match interrupt::interrupted() {
+        true => println!("Interrupted, mined {} leases", leases),
+        false => println!("The pool has no work left, mined {} leases", leases),
+    }
+    if !client.pending().is_empty() {
+        return Err(format!(
+            "{} submissions could not be delivered, they are kept in {}",
+            client.pending().len(),
+            queue_path
+        ));
+    }
+    Ok(())
+}
+
+// Print the salts of a redacted run once the user confirmed they want them revealed
+fn run_export(args: &ExportArgs) -> Result<(), String> {
+    let path = Path::new(&args.output);
+    if !args.yes {
+        let confirmed = redact::confirm(std::io::stdin().lock(), std::io::stderr(), path)
+            .map_err(|e| e.to_string())?;
+        if !confirmed {
+            return Err("export not confirmed".to_string());
+        }
+    }
+
+    let count = redact::export(path, std::io::stdout().lock())?;
+    eprintln!("Exported {} results from {}", count, args.output);
+    Ok(())
+}
+
+// Re-derive the address of a mined salt and print the transaction deploying it, for signing
+fn run_prepare_tx(args: &PrepareTxArgs) -> Result<(), String> {
+    let mined = match (&args.result, &args.salt, &args.address) {
+        (Some(line), _, _) => MinedResult::parse(line)?,
+        (None, Some(salt), Some(address)) => MinedResult {
+            salt: createxcrunch::parse_salt(salt)?,
+            address: address.0 .0,
+            factory: None,
+            create_variant: None,
+        },
+        _ => unreachable!(),
+    };
+    let factory = match (&args.factory, mined.factory) {
+        (Some(factory), _) => match presets::find(factory) {
+            Some(preset) => preset.code.address,
+            None => factory
+                .parse::<Address>()
+                .map(|factory| factory.0 .0)
+                .map_err(|_| format!("invalid factory address {}", factory))?,
+        },
+        (None, Some(factory)) => factory,
+        (None, None) => presets::CREATEX.code.address,
+    };
+    let init_code = hex::decode(args.init_code.trim()).map_err(|_| format!("invalid init code {}", args.init_code))?;
+    let create_variant = deploy_tx::deploy_variant(mined.create_variant.as_ref(), args.create2, &init_code)?;
+    let caller = args.caller.map(|caller| caller.0 .0);
+
+    let deployment = Deployment {
+        factory,
+        create_variant,
+        salt_variant: SaltVariant::of_salt(&mined.salt, caller.as_ref(), Some(args.chain_id))?,
+        salt: mined.salt,
+        init_code,
+    };
+    deployment.verify(&mined.address)?;
+    if let Some(url) = &args.rpc_url {
+        deploy_tx::check_vacant(&mut RpcClient::new(DefaultTransport::new(url)?), &mined.address)?;
+    }
+
+    let fees = Fees {
+        gas_limit: args.gas_limit,
+        max_fee_per_gas: args.max_fee_per_gas,
+        max_priority_fee_per_gas: args.max_priority_fee_per_gas,
+    };
+    let mut transaction = Eip1559Transaction::deploy(&deployment, args.chain_id, &fees);
+    transaction.from = args.caller;
+    transaction.nonce = args.nonce.map(U64::from);
+    transaction.value = args.value;
+    println!("{:#}", transaction.to_json());
+    Ok(())
+}
+
+fn run_stats(args: &StatsArgs) -> Result<(), String> {
+    let mut summaries = vec![];
+    for path in &args.files {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let file = StatsFile::parse(path, &contents)?;
+        for warning in &file.warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        summaries.push(Summary::of(path, &file.cycles));
+    }
+
+    println!("{}", cycle_stats::render(&cycle_stats::compare(summaries), args.format));
+    Ok(())
+}
+
+fn run_campaign_report(args: &CampaignReportArgs) -> Result<(), String> {
+    campaign::validate_name(&args.name)?;
+    let discovery = Discovery::scan(Path::new(&args.dir), &args.name)?;
+    for warning in &discovery.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    if discovery.sessions.is_empty() {
+        return Err(format!("no sessions of campaign {} under {}", args.name, args.dir));
+    }
+
+    println!("{}", Report::of(&args.name, discovery.sessions).render(args.format));
+    Ok(())
+}
+
+fn run_bench(args: &BenchArgs) -> Result<(), String> {
+    let factory = Address::from(CREATEX_ADDRESS).to_checksum(None);
+    let reward = RewardVariant::LeadingZeros {
+        zeros_threshold: args.zeros,
+    };
+    let mut config = Config::new(args.gpu_device_id, &factory, None, None, None, reward, "")
+        .map_err(|e| e.to_string())?;
+    config.backend = args.backend;
+    config.work_size = args.work_size;
+    config.workgroup_size = args.workgroup_size;
+
+    println!("Benchmarking for {}s...", args.duration);
+    let benchmark = webgpu::bench(&config, Duration::from_secs(args.duration)).map_err(|e| e.to_string())?;
+    println!("{}", benchmark.report(args.zeros));
+    Ok(())
+}
+
+// Derive the candidates of a --record-cycles record again, on a GPU too unless there is none
+fn run_replay(args: &ReplayArgs) -> Result<(), String> {
+    let record = CycleRecord::read(Path::new(&args.file))?;
+    let mut replayed = replay::replay_cpu(&record)?;
+    if !args.no_gpu {
+        match webgpu::replay_dispatch(&record, args.gpu_device_id, args.backend) {
+            Ok(hashes) => {
+                for (replayed, hash) in replayed.iter_mut().zip(hashes) {
+                    replayed.gpu = Some(hash);
+                }
+            }
+            Err(e) => eprintln!("Only replaying on the CPU: {}", e),
+        }
+    }
+    println!("{}", replay::report(&record, &replayed));
+    match replayed.iter().all(Replayed::agrees) {
+        true => Ok(()),
+        false => Err(format!("the candidates of cycle {} disagree", record.cycle)),
+    }
+}
+
+fn run_rarity(args: &RarityArgs) {
+    for (i, address) in args.addresses.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", address.to_checksum(None));
+        for line in Rarity::of(&address.0 .0).report().lines() {
+            println!("  {}", line);
+        }
+    }
+}
+
+// Hash init code read from the command line, a file or stdin, with its constructor arguments
+fn run_hash_init_code(args: &HashInitCodeArgs) -> Result<(), String> {
+    let code = match args.init_code.as_str() {
+        "-" => std::io::read_to_string(std::io::stdin().lock()).map_err(|e| format!("could not read init code from stdin: {}", e))?,
+        path if Path::new(path).is_file() || path.ends_with(".bin") || path.ends_with(".hex") => {
+            std::fs::read_to_string(path).map_err(|e| format!("could not read init code file {}: {}", path, e))?
+        }
+        code => code.to_string(),
+    };
+    let init_code = parse_hex_code(&code).map_err(|e| format!("invalid init code: {}", e))?;
+    if init_code.is_empty() {
+        return Err("init code is empty".to_string());
+    }
+    let constructor_args = args
+        .constructor_args
+        .as_deref()
+        .map(parse_hex_code)
+        .transpose()
+        .map_err(|e| format!("invalid constructor arguments: {}", e))?
+        .unwrap_or_default();
+
+    println!("0x{}", hex::encode(createxcrunch::init_code_hash(&init_code, &constructor_args)));
+    Ok(())
+}
+
+fn run_verify(args: &VerifyArgs) -> Result<(), String> {
+    let factory = match &args.factory {
+        Some(factory) => presets::find(factory)
+            .map_or(factory.clone(), |preset| preset.checksummed_address())
+            .parse::<Address>()
+            .map_err(|_| format!("factory address {} is not a valid address", factory))?,
+        None => Address::from(CREATEX_ADDRESS),
+    };
+    let salt = args.salt;
+    let salt_variant = SaltVariant::of_salt(&salt, args.caller.as_ref().map(|caller| &caller.0 .0), args.chain_id)?;
+    let (guarded_salt, address) = match &args.init_code_hash {
+        Some(init_code_hash) => compute_create2_address(&factory.0 .0, &salt_variant, &salt, &init_code_hash.0),
+        None => compute_create3_address(&factory.0 .0, &salt_variant, &salt),
+    };
+    let address = Address::from(address);
+
+    println!("Salt variant: {}", salt_variant);
+    println!("Guarded salt: 0x{}", hex::encode(guarded_salt));
+    println!("Address: {}", address.to_checksum(None));
+    match args.expect {
+        Some(expected) if expected != address => Err(format!(
+            "the salt deploys to another address than expected\n- {} (expected)\n+ {} (computed)",
+            expected.to_checksum(None),
+            address.to_checksum(None)
+        )),
+        _ => Ok(()),
+    }
 }
 
 fn main() {
-    let args = Cli::parse();
+    let command_line: Vec<String> = std::env::args().collect();
+    let (argv, config_file) = match with_config_file(command_line.clone()) {
+        Ok(merged) => merged,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // errors about options the command line does not override are the config file's
+    let overrides = command_line.get(2..).unwrap_or_default();
+    let explain = |e: String| {
+        if let Some(file) = config_file.as_ref().filter(|file| file.blame(&e, overrides).is_some()) {
+            return file.explain(e, overrides);
+        }
+        match error_source(&e, &argv) {
+            Some(source) => format!("{} (from {})", e, source),
+            None => e,
+        }
+    };
+
+    let parsed = cli_command(&argv)
+        .try_get_matches_from(&argv)
+        .and_then(|matches| Cli::from_arg_matches(&matches));
+    let mut args = match parsed {
+        Ok(args) => args,
+        Err(e) => {
+            let error = e.to_string();
+            match config_file.as_ref().and_then(|file| Some((file, file.blame(&error, overrides)?))) {
+                Some((file, key)) => eprintln!("Error in key {} of config file {}:", key, file.path),
+                None => {
+                    if let Some(source) = error_source(&error, &argv).filter(|source| source.starts_with("environment")) {
+                        eprintln!("Error in {}:", source);
+                    }
+                }
+            }
+            e.exit()
+        }
+    };
+
+    if let Err(e) = merge_params_stdin(&mut args.command) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
     match args.command {
+        Commands::Create3(args) if args.print_config => {
+            match factory_init_config(&args).map_err(explain).and_then(|_| print_config(&argv)) {
+                Ok(config) => print!("{}", config),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Create2(args) if args.cli_args.print_config => {
+            match create2_init_config(&args).map_err(explain).and_then(|_| print_config(&argv)) {
+                Ok(config) => print!("{}", config),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Create3(args) if args.dry_run => match factory_init_config(&args).map_err(explain) {
+            Ok(config) => println!("{}", plan::dry_run(&config)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Create2(args) if args.cli_args.dry_run => match create2_init_config(&args).map_err(explain) {
+            Ok(config) => println!("{}", plan::dry_run(&config)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Create3(args) if args.pool_url.is_some() => {
+            if let Err(e) = run_pool(&args, || factory_init_config(&args).map_err(explain)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Create2(args) if args.cli_args.pool_url.is_some() => {
+            if let Err(e) = run_pool(&args.cli_args, || create2_init_config(&args).map_err(explain)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
         Commands::Create3(args) => {
-            let config = match factory_init_config(&args) {
+            let config = match factory_init_config(&args).map_err(explain) {
                 Ok(config) => config,
                 Err(e) => {
                     eprintln!("Error: {}", e);
@@ -67,24 +819,13 @@ fn main() {
             }
         }
         Commands::Create2(args) => {
-            let init_code_hash_str = args.init_code_hash.clone();
-            
-            let mut config = match factory_init_config(&args.cli_args) {
+            let config = match create2_init_config(&args).map_err(explain) {
                 Ok(config) => config,
                 Err(e) => {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 }
             };
-            
-            // Decode the init code hash
-            let init_code_hash_vec = hex::decode(&init_code_hash_str)
-                .expect("could not decode init code hash argument");
-            let init_code_hash = TryInto::<[u8; 32]>::try_into(init_code_hash_vec)
-                .expect("invalid length for init code hash argument");
-            
-            // Set the create variant to Create2
-            config.create_variant = CreateVariant::Create2 { init_code_hash };
 
             match webgpu::gpu(config) {
                 Ok(_) => {},
@@ -94,5 +835,86 @@ fn main() {
                 }
             }
         }
+        Commands::Batch(args) => {
+            if let Err(e) = run_batch(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Export(args) => {
+            if let Err(e) = run_export(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::ListGpus(args) => webgpu::list_gpus(args.backend),
+        Commands::Presets(args) => match args.json {
+            true => println!(
+                "{:#}",
+                serde_json::Value::from_iter(presets::all().iter().map(|preset| preset.to_json()))
+            ),
+            false => println!("{}", presets::table(presets::all())),
+        },
+        Commands::PrepareTx(args) => {
+            if let Err(e) = run_prepare_tx(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Stats(args) => {
+            if let Err(e) = run_stats(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Rarity(args) => run_rarity(&args),
+        Commands::HashInitCode(args) => {
+            if let Err(e) = run_hash_init_code(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Verify(args) => {
+            if let Err(e) = run_verify(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Campaign(args) => match args.command {
+            CampaignCommand::Report(args) => {
+                if let Err(e) = run_campaign_report(&args) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        },
+        Commands::Bench(args) => {
+            if let Err(e) = run_bench(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Replay(args) => {
+            if let Err(e) = run_replay(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Bundle(args) => {
+            let session = args.session.or_else(|| bundle::recorded_session(Path::new(&args.output)));
+            let result = bundle::session_artifacts(Path::new(&args.output), session).and_then(|mut members| {
+                members.push(bundle::build_info());
+                let count = members.len();
+                bundle::write_bundle(Path::new(&args.bundle), members).map(|_| count)
+            });
+
+            match result {
+                Ok(count) => println!("Bundled {} files into {}", count, args.bundle),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,245 @@
+use std::io::Read;
+
+use alloy_primitives::{hex, keccak256, Address};
+use serde_json::Value;
+
+use crate::{Config, CreateVariant, OutputFormat, SaltVariant};
+
+/// Stands in for a parameter kept out of logs under `--params-stdin`.
+pub const REDACTED: &str = "<redacted>";
+
+/// Prefix of the output header line recording the hashes of redacted parameters.
+pub const HEADER_PREFIX: &str = "# Params:";
+
+/// Keys of the `--params-stdin` object, by the option they replace. Like config file keys they
+/// are the long option, with underscores for dashes allowed.
+const KEYS: [(&str, &[&str]); 3] = [
+    ("factory", &["factory"]),
+    ("caller", &["caller"]),
+    (
+        "code-hash",
+        &["init-code-hash", "init_code_hash", "code-hash", "code_hash"],
+    ),
+];
+
+/// Deployment parameters read by `--params-stdin`, replacing the options of the same name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    pub factory: Option<String>,
+    pub caller: Option<String>,
+    pub init_code_hash: Option<String>,
+}
+
+/// Read the single JSON object of `--params-stdin` from `input`. Errors name keys and positions
+/// but never the values, which are confidential.
+pub fn read(mut input: impl Read) -> Result<Params, String> {
+    let mut contents = String::new();
+    input
+        .read_to_string(&mut contents)
+        .map_err(|e| format!("could not read --params-stdin: {}", e))?;
+    let object = match serde_json::from_str(&contents) {
+        Ok(Value::Object(object)) => object,
+        Ok(_) => return Err("--params-stdin must be a JSON object".to_string()),
+        Err(e) => {
+            return Err(format!(
+                "--params-stdin is not valid JSON (line {}, column {})",
+                e.line(),
+                e.column()
+            ))
+        }
+    };
+
+    let mut params = Params::default();
+    for (key, value) in object {
+        let Some((option, _)) = KEYS.iter().find(|(_, keys)| keys.contains(&key.as_str())) else {
+            return Err(format!(
+                "unknown key {} in --params-stdin (expected factory, caller or init_code_hash)",
+                key
+            ));
+        };
+        let Value::String(value) = value else {
+            return Err(format!("{} in --params-stdin must be a string", key));
+        };
+        let slot = match *option {
+            "factory" => &mut params.factory,
+            "caller" => &mut params.caller,
+            _ => &mut params.init_code_hash,
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("--params-stdin gives --{} twice", option));
+        }
+    }
+    Ok(params)
+}
+
+/// Hash recorded in place of a redacted parameter: its keccak256.
+pub fn param_hash(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(keccak256(bytes)))
+}
+
+/// The factory of `config` as logged.
+pub fn factory(config: &Config) -> String {
+    match config.redact_params {
+        true => REDACTED.to_string(),
+        false => Address::from(config.factory_address).to_checksum(None),
+    }
+}
+
+/// The create variant of `config` as logged, see [`crate::canonical`].
+pub fn create_variant(config: &Config) -> String {
+    match (&config.create_variant, config.redact_params) {
+        (CreateVariant::Create2 { .. }, true) => format!("create2({})", REDACTED),
+        (create_variant, _) => create_variant.to_string(),
+    }
+}
+
+/// The salt variant of `config` as logged, see [`crate::canonical`].
+pub fn salt_variant(config: &Config) -> String {
+    let salt_variant = config.salt_variant.to_string();
+    match (caller(&config.salt_variant), config.redact_params) {
+        (Some(caller), true) => {
+            salt_variant.replace(&format!("0x{}", hex::encode(caller)), REDACTED)
+        }
+        _ => salt_variant,
+    }
+}
+
+/// Refuse options of `config` that would reveal the parameters it redacts.
+pub fn check_redactable(config: &Config) -> Result<(), String> {
+    if !config.redact_params {
+        return Ok(());
+    }
+    if config.format == OutputFormat::SafeTx {
+        return Err(
+            "--format safe-tx writes the factory and init code into the batch, pass --no-redact-params"
+                .to_string(),
+        );
+    }
+    if config.record_cycles.is_some() {
+        return Err(
+            "--record-cycles writes the factory and caller into its records, pass --no-redact-params"
+                .to_string(),
+        );
+    }
+    if config.rpc_url.is_some() {
+        return Err("--rpc-url sends the factory to the node, pass --no-redact-params".to_string());
+    }
+    Ok(())
+}
+
+fn caller(salt_variant: &SaltVariant) -> Option<&[u8; 20]> {
+    match salt_variant {
+        SaltVariant::CrosschainSender {
+            calling_address, ..
+        }
+        | SaltVariant::Sender { calling_address } => Some(calling_address),
+        SaltVariant::Crosschain { .. } | SaltVariant::Random => None,
+    }
+}
+
+/// Hashes of the parameters of a run, which the output header records in their place under
+/// `--params-stdin` so the results can still be checked against them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamHashes {
+    pub factory: String,
+    pub caller: Option<String>,
+    pub init_code_hash: Option<String>,
+}
+
+impl ParamHashes {
+    /// Hash the parameters given.
+    pub fn new(
+        factory: &[u8; 20],
+        caller: Option<&[u8; 20]>,
+        init_code_hash: Option<&[u8; 32]>,
+    ) -> Self {
+        Self {
+            factory: param_hash(factory),
+            caller: caller.map(|caller| param_hash(caller)),
+            init_code_hash: init_code_hash.map(|hash| param_hash(hash)),
+        }
+    }
+
+    /// The hashes of the parameters of `config`.
+    pub fn of(config: &Config) -> Self {
+        let init_code_hash = match &config.create_variant {
+            CreateVariant::Create2 { init_code_hash } => Some(init_code_hash),
+            CreateVariant::Create3 => None,
+        };
+        Self::new(
+            &config.factory_address,
+            caller(&config.salt_variant),
+            init_code_hash,
+        )
+    }
+
+    /// The output header line, e.g. `# Params: factory=0x… caller=0x…`.
+    pub fn to_header(&self) -> String {
+        let mut header = format!("{} factory={}", HEADER_PREFIX, self.factory);
+        if let Some(caller) = &self.caller {
+            header.push_str(&format!(" caller={}", caller));
+        }
+        if let Some(init_code_hash) = &self.init_code_hash {
+            header.push_str(&format!(" init_code_hash={}", init_code_hash));
+        }
+        header
+    }
+
+    /// Parse the hashes of a [`ParamHashes::to_header`] line, none for other lines.
+    pub fn parse_header(line: &str) -> Option<Self> {
+        let mut factory = None;
+        let mut caller = None;
+        let mut init_code_hash = None;
+        for field in line.strip_prefix(HEADER_PREFIX)?.split_whitespace() {
+            match field.split_once('=')? {
+                ("factory", hash) => factory = Some(hash.to_string()),
+                ("caller", hash) => caller = Some(hash.to_string()),
+                ("init_code_hash", hash) => init_code_hash = Some(hash.to_string()),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            factory: factory?,
+            caller,
+            init_code_hash,
+        })
+    }
+
+    /// Check that `given` are the parameters these hashes were recorded for, naming the first
+    /// that differs.
+    pub fn check(&self, given: &ParamHashes) -> Result<(), String> {
+        let fields = [
+            ("factory", Some(&self.factory), Some(&given.factory)),
+            ("caller", self.caller.as_ref(), given.caller.as_ref()),
+            (
+                "init code hash",
+                self.init_code_hash.as_ref(),
+                given.init_code_hash.as_ref(),
+            ),
+        ];
+        for (name, recorded, given) in fields {
+            match (recorded, given) {
+                (Some(recorded), Some(given)) if recorded != given => {
+                    return Err(format!(
+                        "the {} does not match the hash in the output header",
+                        name
+                    ))
+                }
+                (Some(_), None) => {
+                    return Err(format!(
+                        "the output header records the {}, but it was not given",
+                        name
+                    ))
+                }
+                (None, Some(_)) => {
+                    return Err(format!(
+                        "the {} was given, but the output header doesn't record it",
+                        name
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use std::{fs, path::PathBuf};
+
+use crate::{validate_pattern, RewardVariant};
+
+/// A pattern of a `--pattern-file` and the line it is on, from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternLine {
+    pub line: usize,
+    pub pattern: String,
+}
+
+/// The patterns of a `--pattern-file`: every line that is neither blank nor a `#` comment, in
+/// the syntax of `--matching`. Errors name the line of the first invalid or repeated pattern.
+pub fn parse(contents: &str) -> Result<Vec<PatternLine>, String> {
+    let mut patterns: Vec<PatternLine> = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let pattern = line.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+        let pattern = pattern.strip_prefix("0x").unwrap_or(pattern);
+        validate_pattern(pattern).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        if let Some(first) = patterns.iter().find(|first| first.pattern == pattern) {
+            return Err(format!(
+                "line {}: pattern {} repeats line {}",
+                index + 1,
+                pattern,
+                first.line
+            ));
+        }
+        patterns.push(PatternLine {
+            line: index + 1,
+            pattern: pattern.to_string(),
+        });
+    }
+    if patterns.is_empty() {
+        return Err("pattern file holds no pattern".to_string());
+    }
+    Ok(patterns)
+}
+
+/// The patterns of `patterns` that `others` doesn't hold, on whatever line.
+fn missing_from(patterns: &[PatternLine], others: &[PatternLine]) -> Vec<String> {
+    patterns
+        .iter()
+        .filter(|pattern| !others.iter().any(|other| other.pattern == pattern.pattern))
+        .map(|pattern| pattern.pattern.clone())
+        .collect()
+}
+
+/// What changed in a pattern file since it was last polled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reload {
+    /// Valid new patterns replace the active ones.
+    Changed {
+        removed: Vec<String>,
+        added: Vec<String>,
+    },
+    /// The file can't be read or holds an invalid pattern, the active ones are kept.
+    Invalid(String),
+}
+
+/// A `--pattern-file` mined for while it is edited. Polled between cycles, a valid edit
+/// replaces the active patterns and an invalid one leaves them active.
+pub struct PatternWatcher {
+    path: PathBuf,
+    patterns: Vec<PatternLine>,
+    /// Contents of the last read, none if it failed, to report every edit once.
+    seen: Option<String>,
+}
+
+impl PatternWatcher {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("could not read pattern file {}: {}", path.display(), e))?;
+        let patterns = parse(&contents)
+            .map_err(|e| format!("invalid pattern file {}: {}", path.display(), e))?;
+        Ok(Self {
+            path,
+            patterns,
+            seen: Some(contents),
+        })
+    }
+
+    /// The active patterns, in the order of the file.
+    pub fn patterns(&self) -> &[PatternLine] {
+        &self.patterns
+    }
+
+    /// The line of the active `pattern`, none if it isn't one.
+    pub fn line_of(&self, pattern: &str) -> Option<usize> {
+        self.patterns
+            .iter()
+            .find(|active| active.pattern == pattern)
+            .map(|active| active.line)
+    }
+
+    /// Lines of the active patterns `reward` was mined for, such as the patterns a result
+    /// matched.
+    pub fn lines_of(&self, reward: &RewardVariant) -> Vec<usize> {
+        match reward {
+            RewardVariant::Matching { pattern } | RewardVariant::ChecksumMatching { pattern } => {
+                self.line_of(pattern).into_iter().collect()
+            }
+            RewardVariant::MatchingAny { patterns } => patterns
+                .iter()
+                .filter_map(|pattern| self.line_of(pattern))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Read the file again, none unless it changed in a way that matters since the last poll.
+    pub fn poll(&mut self) -> Option<Reload> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                // editors may replace the file, it is only gone if it stays gone
+                let warned = self.seen.take().is_none();
+                return match warned {
+                    true => None,
+                    false => Some(Reload::Invalid(format!(
+                        "could not read pattern file {}: {}",
+                        self.path.display(),
+                        e
+                    ))),
+                };
+            }
+        };
+        if self.seen.as_ref() == Some(&contents) {
+            return None;
+        }
+        self.seen = Some(contents.clone());
+
+        match parse(&contents) {
+            Ok(patterns) => {
+                let removed = missing_from(&self.patterns, &patterns);
+                let added = missing_from(&patterns, &self.patterns);
+                // patterns that only moved to other lines are still mined for, under their new lines
+                self.patterns = patterns;
+                (!removed.is_empty() || !added.is_empty())
+                    .then_some(Reload::Changed { removed, added })
+            }
+            Err(e) => Some(Reload::Invalid(format!(
+                "invalid pattern file {}: {}",
+                self.path.display(),
+                e
+            ))),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{hex, keccak256, Address, U256};
+use serde_json::{json, Value};
+
+use crate::{
+    address_from_hash, create2_preimage, first_create_preimage,
+    probability::{expected_attempts, HOPELESS_ATTEMPTS},
+    Config, CreateVariant, MatchPlacement, SaltVariant, ADDRESS_OFFSET,
+    CREATE3_PROXY_INITCODE_HASH,
+};
+
+/// Source of the entropy bytes of the worked example salt.
+pub const EXAMPLE_SEED: &str = "createxcrunch-example";
+
+/// How a run turns salts into deployment addresses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MiningPlan {
+    pub factory: [u8; 20],
+    pub create_variant: CreateVariant,
+    pub salt_variant: SaltVariant,
+}
+
+/// One hash of a derivation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationStep {
+    pub label: &'static str,
+    pub preimage: Vec<u8>,
+    pub hash: [u8; 32],
+}
+
+impl DerivationStep {
+    fn new(label: &'static str, preimage: &[u8]) -> Self {
+        Self {
+            label,
+            preimage: preimage.to_vec(),
+            hash: keccak256(preimage).0,
+        }
+    }
+}
+
+/// The derivation of one salt, step by step, so its inputs can be checked with an independent
+/// calculator. The first step guards the salt, the last one hashes into the address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkedExample {
+    pub salt: [u8; 32],
+    pub steps: Vec<DerivationStep>,
+    pub address: [u8; 20],
+}
+
+impl MiningPlan {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            factory: config.factory_address,
+            create_variant: config.create_variant.clone(),
+            salt_variant: config.salt_variant.clone(),
+        }
+    }
+
+    /// Derive the address of the salt whose entropy is `keccak256("createxcrunch-example")`,
+    /// truncated to the 11 bytes the salt layout leaves for it.
+    pub fn worked_example(&self) -> WorkedExample {
+        let entropy: [u8; 11] = keccak256(EXAMPLE_SEED)[..11].try_into().unwrap();
+        let salt = self.salt_variant.salt_with_entropy(&entropy);
+
+        let guard = DerivationStep::new("guarded salt", &self.salt_variant.guard_preimage(&salt));
+        let mut steps = vec![guard.clone()];
+        match &self.create_variant {
+            CreateVariant::Create2 { init_code_hash } => {
+                let preimage = create2_preimage(&self.factory, &guard.hash, init_code_hash);
+                steps.push(DerivationStep::new("create2 hash", &preimage));
+            }
+            CreateVariant::Create3 => {
+                let preimage =
+                    create2_preimage(&self.factory, &guard.hash, &CREATE3_PROXY_INITCODE_HASH);
+                let proxy_step = DerivationStep::new("proxy hash", &preimage);
+                let proxy = address_from_hash(&proxy_step.hash, ADDRESS_OFFSET).unwrap();
+                steps.push(proxy_step);
+                steps.push(DerivationStep::new(
+                    "create hash",
+                    &first_create_preimage(&proxy),
+                ));
+            }
+        }
+
+        let address = address_from_hash(&steps.last().unwrap().hash, ADDRESS_OFFSET).unwrap();
+        WorkedExample {
+            salt,
+            steps,
+            address,
+        }
+    }
+}
+
+impl WorkedExample {
+    /// Startup banner block.
+    pub fn render(&self) -> String {
+        let mut lines = vec![
+            "Worked example, check it with an independent calculator before a long run:"
+                .to_string(),
+            format!("  salt:          0x{}", hex::encode(self.salt)),
+        ];
+        for step in &self.steps {
+            lines.push(format!(
+                "  preimage:      0x{}",
+                hex::encode(&step.preimage)
+            ));
+            lines.push(format!(
+                "  {:<14} 0x{}",
+                format!("{}:", step.label),
+                hex::encode(step.hash)
+            ));
+        }
+        lines.push(format!(
+            "  address:       {}",
+            Address::from(self.address).to_checksum(None)
+        ));
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|step| {
+                json!({
+                    "label": step.label,
+                    "preimage": format!("0x{}", hex::encode(&step.preimage)),
+                    "keccak256": format!("0x{}", hex::encode(step.hash)),
+                })
+            })
+            .collect();
+        json!({
+            "salt": format!("0x{}", hex::encode(self.salt)),
+            "steps": steps,
+            "address": Address::from(self.address).to_checksum(None),
+        })
+    }
+}
+
+/// What a run of `config` would search for, for `--dry-run`: the deployment, how the salt is
+/// laid out, the reward and the expected number of attempts per result, with a warning when
+/// that is beyond any GPU.
+pub fn dry_run(config: &Config) -> String {
+    let create = match &config.create_variant {
+        CreateVariant::Create2 { init_code_hash } => {
+            format!("create2 of init code hash 0x{}", hex::encode(init_code_hash))
+        }
+        CreateVariant::Create3 => {
+            "create3, a CREATE2 proxy deploying the contract with CREATE".to_string()
+        }
+    };
+    let (caller, chain_id) = match &config.salt_variant {
+        SaltVariant::CrosschainSender {
+            chain_id,
+            calling_address,
+        } => (Some(calling_address), Some(chain_id)),
+        SaltVariant::Crosschain { chain_id } => (None, Some(chain_id)),
+        SaltVariant::Sender { calling_address } => (Some(calling_address), None),
+        SaltVariant::Random => (None, None),
+    };
+    let caller = match caller {
+        Some(caller) => format!(
+            "{}, only it can deploy the salt",
+            Address::from(*caller).to_checksum(None)
+        ),
+        None => "zero, anyone can deploy the salt".to_string(),
+    };
+    let protection = match chain_id {
+        Some(chain_id) => format!(
+            "0x01, guarded with chain ID {} against cross-chain redeploys",
+            U256::from_be_bytes(*chain_id)
+        ),
+        None => "0x00, the salt deploys to the same address on every chain".to_string(),
+    };
+    let matched_on = match MatchPlacement::for_reward(&config.reward) {
+        MatchPlacement::Gpu { prefix } => format!("the GPU, prefix 0x{}", hex::encode(prefix)),
+        MatchPlacement::GpuZeroNibbles { nibbles } => {
+            format!("the GPU, {} leading zero nibbles", nibbles)
+        }
+        MatchPlacement::Cpu => "the CPU, every hash is copied back".to_string(),
+    };
+    let attempts = expected_attempts(&config.reward);
+
+    let mut lines = vec![
+        "Dry run, nothing is mined and no file is written:".to_string(),
+        format!(
+            "  factory:            {}{}",
+            Address::from(config.factory_address).to_checksum(None),
+            match config.default_factory {
+                true => ", the canonical CreateX deployment used by default",
+                false => "",
+            }
+        ),
+        format!("  create variant:     {}", create),
+        format!("  salt variant:       {}", config.salt_variant),
+        format!("  salt bytes 0-19:    {}", caller),
+        format!("  salt byte 20:       {}", protection),
+        "  salt bytes 21-31:   mined".to_string(),
+        format!("  reward:             {}", config.reward),
+        format!("  matched on:         {}", matched_on),
+        format!(
+            "  expected attempts:  {:.0} (2^{:.1}) per result",
+            attempts,
+            attempts.log2()
+        ),
+    ];
+    if attempts > HOPELESS_ATTEMPTS {
+        lines.push(format!(
+            "WARNING: the reward needs about 2^{:.1} attempts per result, more than 2^64, no \
+             GPU will ever find one. Relax it before mining.",
+            attempts.log2()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// A step of setting a run up before mining starts. Every step of the setup registers here,
+/// so its time shows in the startup report and `--fast-start` knows whether it may skip it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StartupPhase {
+    /// Enumerating the adapters and picking the GPU.
+    Adapter,
+    /// Deriving the worked example salt.
+    WorkedExample,
+    /// Checking the free space of the output filesystem.
+    DiskCheck,
+    /// Comparing the factory code fetched over `--rpc-url` with CreateX.
+    FactoryCheck,
+    /// Loading the checkpoint of `--supervised` or `--resume`.
+    Checkpoint,
+    /// Opening the output file and loading the index of its salts.
+    OutputFile,
+    /// Opening the device.
+    Device,
+    /// Running the kernel `--self-test`.
+    SelfTest,
+    /// Fitting the batch to the device limits.
+    BatchGeometry,
+    /// Compiling the kernel.
+    Pipeline,
+}
+
+impl StartupPhase {
+    /// Whether the run mines correctly without the phase, so `--fast-start` skips it.
+    pub fn is_optional(&self) -> bool {
+        matches!(
+            self,
+            StartupPhase::WorkedExample
+                | StartupPhase::DiskCheck
+                | StartupPhase::FactoryCheck
+                | StartupPhase::SelfTest
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartupPhase::Adapter => "adapter",
+            StartupPhase::WorkedExample => "worked example",
+            StartupPhase::DiskCheck => "disk check",
+            StartupPhase::FactoryCheck => "factory check",
+            StartupPhase::Checkpoint => "checkpoint",
+            StartupPhase::OutputFile => "output file",
+            StartupPhase::Device => "device",
+            StartupPhase::SelfTest => "self-test",
+            StartupPhase::BatchGeometry => "batch geometry",
+            StartupPhase::Pipeline => "pipeline",
+        }
+    }
+}
+
+/// The phases setting up a run for `config` goes through, in order. `--fast-start` leaves out
+/// every optional phase.
+pub fn startup_phases(config: &Config, embedded: bool) -> Vec<StartupPhase> {
+    let file_less = embedded || config.no_file;
+    let phases = [
+        (StartupPhase::Adapter, true),
+        (
+            StartupPhase::WorkedExample,
+            config.worked_example && !config.redact_params && !embedded && !config.json_events,
+        ),
+        (StartupPhase::DiskCheck, !file_less),
+        (StartupPhase::FactoryCheck, config.rpc_url.is_some()),
+        (StartupPhase::Checkpoint, config.supervised || config.resume),
+        (StartupPhase::OutputFile, !file_less),
+        (StartupPhase::Device, true),
+        (StartupPhase::SelfTest, config.self_test.is_some()),
+        (StartupPhase::BatchGeometry, true),
+        (StartupPhase::Pipeline, true),
+    ];
+    phases
+        .into_iter()
+        .filter(|(phase, needed)| *needed && !(config.fast_start && phase.is_optional()))
+        .map(|(phase, _)| phase)
+        .collect()
+}
+
+/// How long each phase of setting up a run took, in the order they ran.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StartupTimings {
+    pub phases: Vec<(StartupPhase, Duration)>,
+}
+
+impl StartupTimings {
+    /// Run `phase`, recording how long it took. Phases run in several parts add up.
+    pub fn time<T>(&mut self, phase: StartupPhase, run: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = run();
+        match self.phases.iter_mut().find(|(timed, _)| *timed == phase) {
+            Some((_, duration)) => *duration += start.elapsed(),
+            None => self.phases.push((phase, start.elapsed())),
+        }
+        result
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// The phases that ran, in order.
+    pub fn ran(&self) -> Vec<StartupPhase> {
+        self.phases.iter().map(|(phase, _)| *phase).collect()
+    }
+
+    /// One line breaking the startup time down by phase, e.g.
+    /// `Started up in 1.42s: adapter 120ms, device 810ms, pipeline 490ms`.
+    pub fn report(&self) -> String {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(phase, duration)| format!("{} {}ms", phase.name(), duration.as_millis()))
+            .collect();
+        format!(
+            "Started up in {:.2}s: {}",
+            self.total().as_secs_f64(),
+            phases.join(", ")
+        )
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    ops::Range,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::{hex, Address};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub use crate::http::{HttpTransport, Transport};
+
+/// Version of the pool protocol spoken by this miner. Every message carries it and messages of
+/// another version are refused.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Body of `POST <pool>/lease`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseRequest {
+    pub version: u32,
+    pub worker: String,
+}
+
+/// Answer to a lease request. No lease means the pool has no work left.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseResponse {
+    pub version: u32,
+    pub lease: Option<Lease>,
+}
+
+/// A range of nonces handed to one worker until `expires_at`, in seconds since the epoch.
+/// Unless renewed, the pool hands an expired lease to someone else.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    pub id: String,
+    /// Hex salt bytes preceding the nonce, `0x` for none.
+    pub salt_prefix: String,
+    pub start: u64,
+    pub end: u64,
+    pub expires_at: u64,
+}
+
+impl Lease {
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.end
+    }
+
+    pub fn salt_prefix_bytes(&self) -> Result<Vec<u8>, String> {
+        hex::decode(&self.salt_prefix)
+            .map_err(|_| format!("lease {} has an invalid salt prefix", self.id))
+    }
+
+    /// Time left on the lease at `now` seconds since the epoch, none once it expired.
+    pub fn remaining(&self, now: u64) -> Option<Duration> {
+        self.expires_at
+            .checked_sub(now)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+/// Body of `POST <pool>/renew`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenewRequest {
+    pub version: u32,
+    pub lease_id: String,
+}
+
+/// Answer to a renewal, holding the lease with its new expiry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenewResponse {
+    pub version: u32,
+    pub lease: Lease,
+}
+
+/// A result found in a lease.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Find {
+    pub salt: String,
+    pub address: String,
+}
+
+impl Find {
+    pub fn new(salt: &[u8; 32], address: &[u8; 20]) -> Self {
+        Self {
+            salt: format!("0x{}", hex::encode(salt)),
+            address: Address::from(*address).to_checksum(None),
+        }
+    }
+}
+
+/// Body of `POST <pool>/submit`: the finds of a lease and proof of the work done. A lease is
+/// submitted once, so the pool recognizes a retried submission by its lease id.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub version: u32,
+    pub lease_id: String,
+    pub finds: Vec<Find>,
+    pub attempts: u64,
+    pub covered_start: u64,
+    pub covered_end: u64,
+}
+
+impl Submission {
+    pub fn new(lease: &Lease, finds: Vec<Find>, attempts: u64) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            lease_id: lease.id.clone(),
+            finds,
+            attempts,
+            covered_start: lease.start,
+            covered_end: lease.end,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmitStatus {
+    Accepted,
+    /// The lease was submitted before, its response got lost.
+    Duplicate,
+    /// The lease expired and went to another worker.
+    Expired,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmitResponse {
+    pub version: u32,
+    pub status: SubmitStatus,
+}
+
+/// Sidecar path of the submission queue of the run writing to `output`.
+pub fn queue_path(output: &str) -> String {
+    format!("{}.pool-queue", output)
+}
+
+/// Seconds since the epoch, the clock of lease expiries.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Client side of the pool protocol. Requests are retried, and submissions that cannot be
+/// delivered are queued until the pool is reachable again.
+pub struct PoolClient<T: Transport> {
+    transport: T,
+    worker: String,
+    retries: u32,
+    retry_delay: Duration,
+    queue: Vec<Submission>,
+}
+
+impl<T: Transport> PoolClient<T> {
+    pub fn new(transport: T, worker: &str) -> Self {
+        Self {
+            transport,
+            worker: worker.to_string(),
+            retries: 3,
+            retry_delay: Duration::from_secs(5),
+            queue: vec![],
+        }
+    }
+
+    /// Try every request `retries` more times, waiting `delay` in between.
+    pub fn with_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    fn call<Q: Serialize, R: DeserializeOwned>(
+        &mut self,
+        path: &str,
+        request: &Q,
+    ) -> Result<R, String> {
+        let body = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        let mut attempt = 0;
+        let response = loop {
+            match self.transport.post(path, &body) {
+                Ok(response) => break response,
+                Err(e) if attempt == self.retries => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    thread::sleep(self.retry_delay);
+                }
+            }
+        };
+
+        // check the version before the shape, which may differ between versions
+        let value: serde_json::Value = serde_json::from_str(&response)
+            .map_err(|e| format!("invalid response to {}: {}", path, e))?;
+        match value["version"].as_u64() {
+            Some(version) if version == PROTOCOL_VERSION as u64 => {}
+            version => {
+                return Err(format!(
+                    "pool speaks protocol version {}, this miner speaks {}",
+                    version.map_or("unknown".to_string(), |v| v.to_string()),
+                    PROTOCOL_VERSION
+                ))
+            }
+        }
+        serde_json::from_value(value).map_err(|e| format!("invalid response to {}: {}", path, e))
+    }
+
+    /// The next lease of this worker, none once the pool has no work left.
+    pub fn request_lease(&mut self) -> Result<Option<Lease>, String> {
+        let request = LeaseRequest {
+            version: PROTOCOL_VERSION,
+            worker: self.worker.clone(),
+        };
+        let response: LeaseResponse = self.call("/lease", &request)?;
+        Ok(response.lease)
+    }
+
+    /// Extend `lease`, returning it with its new expiry.
+    pub fn renew(&mut self, lease: &Lease) -> Result<Lease, String> {
+        let request = RenewRequest {
+            version: PROTOCOL_VERSION,
+            lease_id: lease.id.clone(),
+        };
+        let response: RenewResponse = self.call("/renew", &request)?;
+        if response.lease.id != lease.id {
+            return Err(format!(
+                "pool renewed lease {} instead of {}",
+                response.lease.id, lease.id
+            ));
+        }
+        Ok(response.lease)
+    }
+
+    /// Queue `submission` and deliver the queue.
+    pub fn submit(&mut self, submission: Submission) -> usize {
+        self.queue.push(submission);
+        self.flush()
+    }
+
+    /// Deliver the queued submissions in order, stopping at the first one the pool cannot be
+    /// reached for. Returns the number still queued.
+    pub fn flush(&mut self) -> usize {
+        while let Some(submission) = self.queue.first().cloned() {
+            match self.call::<_, SubmitResponse>("/submit", &submission) {
+                Ok(response) => {
+                    if response.status == SubmitStatus::Expired {
+                        eprintln!(
+                            "Lease {} expired before it was submitted, its {} results were not counted",
+                            submission.lease_id,
+                            submission.finds.len()
+                        );
+                    }
+                    self.queue.remove(0);
+                }
+                Err(e) => {
+                    eprintln!("Could not submit lease {}: {}", submission.lease_id, e);
+                    break;
+                }
+            }
+        }
+        self.queue.len()
+    }
+
+    pub fn pending(&self) -> &[Submission] {
+        &self.queue
+    }
+
+    /// Persist the queue so undelivered submissions survive a restart. An empty queue removes
+    /// the file.
+    pub fn save_queue(&self, path: &Path) -> io::Result<()> {
+        if self.queue.is_empty() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let mut contents = String::new();
+        for submission in &self.queue {
+            contents.push_str(&serde_json::to_string(submission)?);
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+
+    /// Queue the submissions persisted at `path` by an earlier run.
+    pub fn load_queue(&mut self, path: &Path) -> Result<(), String> {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("could not read {}: {}", path.display(), e)),
+        };
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+            let submission = serde_json::from_str(&line)
+                .map_err(|_| format!("invalid pool queue {}", path.display()))?;
+            self.queue.push(submission);
+        }
+        Ok(())
+    }
+}
+
+/// Renew `lease` whenever less than half of its original duration is left, until `stop` is
+/// set. Returns the lease with its latest expiry.
+pub fn spawn_renewal<T: Transport + Send + 'static>(
+    mut client: PoolClient<T>,
+    mut lease: Lease,
+    stop: Arc<AtomicBool>,
+) -> JoinHandle<Lease> {
+    thread::spawn(move || {
+        let duration = lease.expires_at.saturating_sub(now()).max(2);
+        while !stop.load(Ordering::Relaxed) {
+            if lease
+                .remaining(now())
+                .is_none_or(|left| left.as_secs() <= duration / 2)
+            {
+                match client.renew(&lease) {
+                    Ok(renewed) => lease = renewed,
+                    Err(e) => eprintln!("Could not renew lease {}: {}", lease.id, e),
+                }
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+        lease
+    })
+}
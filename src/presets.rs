@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+use alloy_primitives::{hex, Address};
+use serde_json::{json, Value};
+
+use crate::{
+    factory::{self, KnownCode},
+    CREATE3_PROXY_INITCODE_HASH,
+};
+
+/// A factory the miner derives addresses for, and how it does so.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FactoryPreset {
+    /// Name accepted by `--factory` in place of the address.
+    pub name: &'static str,
+    /// The canonical deployment and its runtime code.
+    pub code: KnownCode,
+    /// Subcommands mining for this factory.
+    pub create_variants: &'static [&'static str],
+    /// Whether the factory guards salts before deploying, see `SaltVariant::guard`.
+    pub guarded_salts: bool,
+    /// Code hash of the proxy deployed before a CREATE3 deployment.
+    pub create3_proxy_init_code_hash: Option<[u8; 32]>,
+    pub description: &'static str,
+    pub documentation: &'static str,
+}
+
+pub const CREATEX: FactoryPreset = FactoryPreset {
+    name: "createx",
+    code: factory::CREATEX,
+    create_variants: &["create2", "create3"],
+    guarded_salts: true,
+    create3_proxy_init_code_hash: Some(CREATE3_PROXY_INITCODE_HASH),
+    description: "CreateX, deployed at the same address on every chain. Salts hold the caller \
+                  and the crosschain flag, and are guarded with them before deploying.",
+    documentation: "https://github.com/pcaversaccio/createx",
+};
+
+const PRESETS: [FactoryPreset; 1] = [CREATEX];
+
+/// Every preset, the default `--factory` first.
+pub fn all() -> &'static [FactoryPreset] {
+    &PRESETS
+}
+
+pub fn find(name: &str) -> Option<&'static FactoryPreset> {
+    all().iter().find(|preset| preset.name == name)
+}
+
+pub fn by_address(address: &[u8; 20]) -> Option<&'static FactoryPreset> {
+    all().iter().find(|preset| preset.code.address == *address)
+}
+
+impl FactoryPreset {
+    pub fn checksummed_address(&self) -> String {
+        Address::from(self.code.address).to_checksum(None)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "address": self.checksummed_address(),
+            "code_hash": format!("0x{}", hex::encode(self.code.code_hash)),
+            "create_variants": self.create_variants,
+            "guarded_salts": self.guarded_salts,
+            "create3_proxy_init_code_hash": self
+                .create3_proxy_init_code_hash
+                .map(|hash| format!("0x{}", hex::encode(hash))),
+            "description": self.description,
+            "documentation": self.documentation,
+        })
+    }
+}
+
+/// Render the presets as a table, one block per preset.
+pub fn table(presets: &[FactoryPreset]) -> String {
+    let mut table = String::new();
+    for preset in presets {
+        if !table.is_empty() {
+            table.push('\n');
+        }
+        writeln!(table, "{}", preset.name).unwrap();
+        writeln!(table, "  address:         {}", preset.checksummed_address()).unwrap();
+        writeln!(
+            table,
+            "  variants:        {}",
+            preset.create_variants.join(", ")
+        )
+        .unwrap();
+        writeln!(
+            table,
+            "  guarded salts:   {}",
+            if preset.guarded_salts { "yes" } else { "no" }
+        )
+        .unwrap();
+        if let Some(hash) = preset.create3_proxy_init_code_hash {
+            writeln!(table, "  CREATE3 proxy:   0x{}", hex::encode(hash)).unwrap();
+        }
+        writeln!(table, "  {}", preset.description).unwrap();
+        writeln!(table, "  docs: {}", preset.documentation).unwrap();
+    }
+    table.trim_end().to_string()
+}
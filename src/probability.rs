@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{stats, RewardVariant};
+
+/// Searches expected to need more attempts than this are out of reach of any GPU, and the
+/// dry run says so loudly.
+pub const HOPELESS_ATTEMPTS: f64 = 18_446_744_073_709_551_616.0; // 2^64
+
+/// Odds of a random address satisfying `reward`.
+pub fn match_probability(reward: &RewardVariant) -> f64 {
+    let total_zeros = |threshold: u8| stats::total_zero_bytes_probability(threshold as u32);
+    let leading_zeros = |threshold: u8| 256f64.powi(-(threshold as i32));
+
+    match reward {
+        RewardVariant::LeadingZeros { zeros_threshold } => leading_zeros(*zeros_threshold),
+        RewardVariant::LeadingZeroNibbles { threshold } => {
+            stats::leading_zero_nibbles_probability(*threshold as u32)
+        }
+        RewardVariant::TotalZeros { zeros_threshold } => total_zeros(*zeros_threshold),
+        RewardVariant::LeadingAndTotalZeros {
+            leading_zeros_threshold,
+            total_zeros_threshold,
+        } => leading_zeros(*leading_zeros_threshold).min(total_zeros(*total_zeros_threshold)),
+        RewardVariant::LeadingOrTotalZeros {
+            leading_zeros_threshold,
+            total_zeros_threshold,
+        } => leading_zeros(*leading_zeros_threshold).max(total_zeros(*total_zeros_threshold)),
+        RewardVariant::Matching { pattern } => pattern_probability(pattern),
+        // the patterns rarely overlap, so their odds add up
+        RewardVariant::MatchingAny { patterns } => {
+            patterns.iter().map(|pattern| pattern_probability(pattern)).sum()
+        }
+        // the checksum picks the case of every letter by one bit of its own hash
+        RewardVariant::ChecksumMatching { pattern } => {
+            let letters = pattern.chars().filter(|c| c.is_ascii_alphabetic()).count();
+            pattern_probability(pattern) * 2f64.powi(-(letters as i32))
+        }
+        RewardVariant::SymmetricEnds { nibbles } => 16f64.powi(-(*nibbles as i32)),
+    }
+}
+
+/// Expected number of attempts before a random address satisfies `reward`.
+pub fn expected_attempts(reward: &RewardVariant) -> f64 {
+    1.0 / match_probability(reward)
+}
+
+/// How hard a reward is to mine, as the browser exports report it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// The reward in its canonical form.
+    pub reward: String,
+    pub probability: f64,
+    pub expected_attempts: f64,
+    /// Whether the search is out of reach, see [`HOPELESS_ATTEMPTS`].
+    pub hopeless: bool,
+}
+
+impl Difficulty {
+    pub fn of(reward: &RewardVariant) -> Self {
+        let expected_attempts = expected_attempts(reward);
+        Self {
+            reward: reward.to_string(),
+            probability: match_probability(reward),
+            expected_attempts,
+            hopeless: expected_attempts > HOPELESS_ATTEMPTS,
+        }
+    }
+}
+
+/// Odds of a random address matching the `--matching` `pattern`, one in 16 per nibble it fixes.
+fn pattern_probability(pattern: &str) -> f64 {
+    let nibbles = pattern.chars().filter(|c| c.is_ascii_hexdigit()).count();
+    16f64.powi(-(nibbles as i32))
+}
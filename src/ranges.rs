@@ -0,0 +1,212 @@
+use alloy_primitives::hex;
+use std::ops::Range;
+
+/// A set of half-open nonce ranges, kept sorted, disjoint and non-adjacent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NonceRanges {
+    ranges: Vec<Range<u64>>,
+}
+
+impl NonceRanges {
+    /// Build a set from arbitrary (unsorted, overlapping, empty) ranges.
+    pub fn new(ranges: impl IntoIterator<Item = Range<u64>>) -> Self {
+        let mut ranges: Vec<Range<u64>> = ranges.into_iter().filter(|r| r.start < r.end).collect();
+        ranges.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Total number of nonces in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn contains(&self, nonce: u64) -> bool {
+        self.find(nonce).is_ok()
+    }
+
+    /// Number of nonces of `range` that are in the set.
+    pub fn covered_in(&self, range: Range<u64>) -> u64 {
+        self.ranges
+            .iter()
+            .map(|r| {
+                let start = r.start.max(range.start);
+                let end = r.end.min(range.end);
+                end.saturating_sub(start)
+            })
+            .sum()
+    }
+
+    /// The parts of `within` that are not in the set, in ascending order.
+    pub fn complement(&self, within: Range<u64>) -> Vec<Range<u64>> {
+        let mut gaps = vec![];
+        let mut cursor = within.start;
+
+        for r in &self.ranges {
+            if r.end <= cursor {
+                continue;
+            }
+            if r.start >= within.end {
+                break;
+            }
+            if r.start > cursor {
+                gaps.push(cursor..r.start);
+            }
+            cursor = r.end;
+        }
+
+        if cursor < within.end {
+            gaps.push(cursor..within.end);
+        }
+
+        gaps
+    }
+
+    /// The first nonce at or after `from` that is not in the set.
+    pub fn next_uncovered(&self, from: u64) -> u64 {
+        match self.find(from) {
+            // ranges are non-adjacent and half-open, so the end of a range is never covered
+            Ok(idx) => self.ranges[idx].end,
+            Err(_) => from,
+        }
+    }
+
+    /// Length of the run of uncovered nonces starting at `from`, capped at `max`.
+    pub fn uncovered_run(&self, from: u64, max: u64) -> u64 {
+        match self.find(from) {
+            Ok(_) => 0,
+            Err(idx) => {
+                let next_start = self.ranges.get(idx).map_or(u64::MAX, |r| r.start);
+                from.saturating_add(max).min(next_start) - from
+            }
+        }
+    }
+
+    fn find(&self, nonce: u64) -> Result<usize, usize> {
+        self.ranges.binary_search_by(|r| {
+            if r.end <= nonce {
+                std::cmp::Ordering::Less
+            } else if r.start > nonce {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+    }
+}
+
+/// Position of a run in the nonce space and the number of candidates it evaluated. Skipped
+/// ranges, leases and resumed runs make the two differ, so rates and estimates must only ever
+/// read `attempts`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchCursor {
+    /// The next nonce to mine.
+    pub nonce: u64,
+    pub attempts: u64,
+}
+
+/// Nonces dispatched in one cycle, `count` of them from `start`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Batch {
+    pub start: u64,
+    pub count: u64,
+}
+
+impl SearchCursor {
+    pub fn new(start: u64) -> Self {
+        Self {
+            nonce: start,
+            attempts: 0,
+        }
+    }
+
+    /// The next batch of at most `max` nonces, jumping over `skip` and stopping at its next
+    /// range and at `end`. None once `end` is reached.
+    pub fn next_batch(&mut self, skip: &NonceRanges, max: u64, end: Option<u64>) -> Option<Batch> {
+        self.nonce = skip.next_uncovered(self.nonce);
+        let max = match end {
+            Some(end) if self.nonce >= end => return None,
+            Some(end) => max.min(end - self.nonce),
+            None => max,
+        };
+        Some(Batch {
+            start: self.nonce,
+            // only the last nonce has no uncovered run, and is mined before wrapping around
+            count: skip.uncovered_run(self.nonce, max).max(1),
+        })
+    }
+
+    /// Move past `batch` once it was mined.
+    pub fn advance(&mut self, batch: Batch) {
+        self.nonce = batch.start.wrapping_add(batch.count);
+        self.attempts += batch.count;
+    }
+}
+
+/// Parse a skip-list of covered `(prefix, start_nonce, end_nonce)` triples, one per line as
+/// `<prefix> <start> <end>`, where `prefix` is the hex salt prefix preceding the nonce and the
+/// range is half-open. Blank lines and `#` comments are ignored. Every triple must apply to
+/// `expected_prefix`, the salt layout of the current run.
+pub fn parse_skip_ranges(contents: &str, expected_prefix: &[u8]) -> Result<NonceRanges, String> {
+    let mut ranges = vec![];
+
+    for (idx, line) in contents.lines().enumerate() {
+        let line_number = idx + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [prefix, start, end] = fields[..] else {
+            return Err(format!(
+                "line {}: expected `<prefix> <start_nonce> <end_nonce>`",
+                line_number
+            ));
+        };
+
+        let prefix = hex::decode(prefix)
+            .map_err(|_| format!("line {}: invalid salt prefix {}", line_number, prefix))?;
+        if prefix != expected_prefix {
+            return Err(format!(
+                "line {}: salt prefix 0x{} does not match the current salt layout (0x{})",
+                line_number,
+                hex::encode(&prefix),
+                hex::encode(expected_prefix)
+            ));
+        }
+
+        let start: u64 = start
+            .parse()
+            .map_err(|_| format!("line {}: invalid start nonce {}", line_number, start))?;
+        let end: u64 = end
+            .parse()
+            .map_err(|_| format!("line {}: invalid end nonce {}", line_number, end))?;
+        if start > end {
+            return Err(format!(
+                "line {}: start nonce {} is after end nonce {}",
+                line_number, start, end
+            ));
+        }
+
+        ranges.push(start..end);
+    }
+
+    Ok(NonceRanges::new(ranges))
+}
@@ -0,0 +1,81 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use alloy_primitives::{hex, keccak256};
+use fs4::FileExt;
+
+/// What has to be typed to confirm an export without `--yes`.
+pub const CONFIRMATION: &str = "reveal";
+
+/// Short identifier of a salt that does not reveal it: the first 4 bytes of its keccak256.
+pub fn salt_fingerprint(salt: &[u8; 32]) -> String {
+    format!("salt:{}", hex::encode(&keccak256(salt)[..4]))
+}
+
+/// A result as shown outside of the output file under `--redact-salts`.
+pub fn redacted_result(salt: &[u8; 32], address: &str) -> String {
+    format!("{} -> {}", salt_fingerprint(salt), address)
+}
+
+/// Make `path` readable and writable by its owner only. Other platforms keep their defaults.
+pub fn restrict_permissions(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Hold an exclusive lock on the output file while a redacted run writes to it, so it cannot be
+/// exported before the run stops.
+pub fn lock_output(file: &File) -> io::Result<()> {
+    file.try_lock_exclusive()
+}
+
+/// Release the lock taken by [`lock_output`] once the run has flushed the output file.
+pub fn unlock_output(file: &File) -> io::Result<()> {
+    FileExt::unlock(file)
+}
+
+/// Ask on `prompt` for the confirmation word and read the answer from `input`.
+pub fn confirm(mut input: impl BufRead, mut prompt: impl Write, path: &Path) -> io::Result<bool> {
+    write!(
+        prompt,
+        "This prints the full salts of {}. Anyone seeing them can front-run the deployments. Type `{}` to continue: ",
+        path.display(),
+        CONFIRMATION
+    )?;
+    prompt.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(answer.trim() == CONFIRMATION)
+}
+
+/// Copy the result lines of the output file at `path` to `out`, skipping comment headers.
+/// Returns the number of results exported.
+pub fn export(path: &Path, mut out: impl Write) -> Result<usize, String> {
+    let file = File::open(path).map_err(|e| format!("could not open {}: {}", path.display(), e))?;
+    file.try_lock_shared()
+        .map_err(|_| format!("{} is still being written by a run", path.display()))?;
+
+    let mut exported = 0;
+    for line in io::BufReader::new(&file).lines() {
+        let line = line.map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+        exported += 1;
+    }
+    Ok(exported)
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    kernel_source, scan::ScanOrder, schemas::Artifact, Config, CreateVariant, SaltVariant,
+    RESULT_WORDS,
+};
+
+/// First bytes of every replay record.
+pub const MAGIC: &[u8; 8] = b"cxreplay";
+
+/// Version of the record layout, written after [`MAGIC`]. Records of other versions are
+/// refused rather than misread.
+pub const VERSION: u32 = Artifact::Replay.current();
+
+/// Path of the record of `cycle` in the `--record-cycles` directory `dir`.
+pub fn record_path(dir: &Path, cycle: u64) -> PathBuf {
+    dir.join(format!("cycle-{:08}.replay", cycle))
+}
+
+/// A result read back in a recorded cycle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// Index of the result among those read back in the cycle.
+    pub index: u32,
+    /// The words the kernel wrote for it: the nonce, then the hash.
+    pub row: [u32; RESULT_WORDS as usize],
+}
+
+impl Candidate {
+    pub fn nonce(&self) -> u64 {
+        self.row[0] as u64 | (self.row[1] as u64) << 32
+    }
+
+    /// The hash the kernel derived, see [`CreateVariant::kernel_hash`].
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        for (bytes, word) in hash.chunks_mut(4).zip(&self.row[2..]) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// What `--record-cycles` keeps of a cycle that produced results: enough to derive its
+/// candidates again, on the CPU and on a GPU, with `createxcrunch replay`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleRecord {
+    /// Cycle of the run, from 1.
+    pub cycle: u64,
+    /// Scan index of the first nonce of the batch.
+    pub base_nonce: u64,
+    pub dispatched: u32,
+    /// Scan order and seed mapping the scan indices of the batch to nonces.
+    pub scan_order: String,
+    pub scan_seed: u64,
+    /// The salt bytes in front of the entropy: the caller or zero, then the redeploy protection
+    /// flag.
+    pub salt_prefix: [u8; 21],
+    pub factory: [u8; 20],
+    pub create_variant: String,
+    pub salt_variant: String,
+    /// See [`Config::fingerprint`].
+    pub fingerprint: String,
+    /// First line of the kernel source, naming the hash of the kernel and its constants, see
+    /// [`kernel_source`].
+    pub kernel: String,
+    pub workgroup_size: u32,
+    pub candidates: Vec<Candidate>,
+}
+
+impl CycleRecord {
+    /// Record the results at `indices` of `results`, the words the kernel wrote for a cycle.
+    pub fn new(
+        config: &Config,
+        cycle: u64,
+        base_nonce: u64,
+        dispatched: u32,
+        scan_seed: u64,
+        results: &[u32],
+        indices: &[usize],
+    ) -> Self {
+        let words = RESULT_WORDS as usize;
+        let candidates = indices
+            .iter()
+            .map(|&index| Candidate {
+                index: index as u32,
+                row: results[index * words..(index + 1) * words]
+                    .try_into()
+                    .unwrap(),
+            })
+            .collect();
+        Self {
+            cycle,
+            base_nonce,
+            dispatched,
+            scan_order: config.scan_order.to_string(),
+            scan_seed,
+            salt_prefix: config.salt_variant.salt(0)[..21].try_into().unwrap(),
+            factory: config.factory_address,
+            create_variant: config.create_variant.to_string(),
+            salt_variant: config.salt_variant.to_string(),
+            fingerprint: config.fingerprint(),
+            kernel: kernel_source(config)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            workgroup_size: config.workgroup_size,
+            candidates,
+        }
+    }
+
+    /// [`MAGIC`], [`VERSION`] as a little-endian word, then the record in bincode.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(self).expect("records serialize"));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let body = bytes
+            .strip_prefix(MAGIC)
+            .ok_or("not a replay record written by --record-cycles")?;
+        let version = body
+            .get(..4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .ok_or("the replay record is truncated")?;
+        Artifact::Replay.check(version)?;
+        bincode::deserialize(&body[4..]).map_err(|e| format!("invalid replay record: {}", e))
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.encode())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let bytes =
+            fs::read(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        Self::decode(&bytes)
+    }
+
+    /// The create and salt variants the candidates were derived with, checked against the salt
+    /// prefix.
+    pub fn derivation(&self) -> Result<(CreateVariant, SaltVariant), String> {
+        let create_variant = self.create_variant.parse()?;
+        let salt_variant: SaltVariant = self.salt_variant.parse()?;
+        self.scan_order.parse::<ScanOrder>()?;
+        if salt_variant.salt(0)[..21] != self.salt_prefix {
+            return Err(format!(
+                "the salt prefix 0x{} of the record does not match its salt variant {}",
+                hex::encode(self.salt_prefix),
+                self.salt_variant
+            ));
+        }
+        Ok((create_variant, salt_variant))
+    }
+}
+
+/// A candidate of a record derived again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replayed {
+    pub index: u32,
+    pub nonce: u64,
+    /// The hash the kernel derived in the recorded cycle.
+    pub recorded: [u8; 32],
+    pub cpu: [u8; 32],
+    /// The hash of a targeted dispatch of the nonce, without a GPU none.
+    pub gpu: Option<[u8; 32]>,
+}
+
+impl Replayed {
+    /// Whether the recorded hash and those derived again are all the same.
+    pub fn agrees(&self) -> bool {
+        self.recorded == self.cpu && self.gpu.is_none_or(|gpu| gpu == self.cpu)
+    }
+}
+
+/// Derive the candidates of `record` again on the CPU.
+pub fn replay_cpu(record: &CycleRecord) -> Result<Vec<Replayed>, String> {
+    let (create_variant, salt_variant) = record.derivation()?;
+    Ok(record
+        .candidates
+        .iter()
+        .map(|candidate| {
+            let guarded_salt = salt_variant.guard(&salt_variant.salt(candidate.nonce()));
+            Replayed {
+                index: candidate.index,
+                nonce: candidate.nonce(),
+                recorded: candidate.hash(),
+                cpu: create_variant.kernel_hash(&record.factory, &guarded_salt),
+                gpu: None,
+            }
+        })
+        .collect())
+}
+
+/// A line per candidate, then whether all of them agree.
+pub fn report(record: &CycleRecord, replayed: &[Replayed]) -> String {
+    let mut lines = vec![format!(
+        "Cycle {} of {}: {} candidates of {} nonces from scan index {}, kernel {}",
+        record.cycle,
+        record.fingerprint,
+        record.candidates.len(),
+        record.dispatched,
+        record.base_nonce,
+        record.kernel.trim_start_matches("// ")
+    )];
+    for replayed in replayed {
+        let gpu = match replayed.gpu {
+            Some(gpu) => format!(", GPU 0x{}", hex::encode(gpu)),
+            None => String::new(),
+        };
+        lines.push(match replayed.agrees() {
+            true => format!(
+                "  #{} nonce {}: agree on 0x{}",
+                replayed.index,
+                replayed.nonce,
+                hex::encode(replayed.cpu)
+            ),
+            false => format!(
+                "  #{} nonce {}: recorded 0x{}, CPU 0x{}{}",
+                replayed.index,
+                replayed.nonce,
+                hex::encode(replayed.recorded),
+                hex::encode(replayed.cpu),
+                gpu
+            ),
+        });
+    }
+    let disagreeing = replayed
+        .iter()
+        .filter(|replayed| !replayed.agrees())
+        .count();
+    lines.push(match disagreeing {
+        0 => "All candidates agree".to_string(),
+        n => format!("{} of {} candidates disagree", n, replayed.len()),
+    });
+    lines.join("\n")
+}
@@ -0,0 +1,89 @@
+use alloy_primitives::hex;
+
+use crate::{
+    factory,
+    feasibility::{feasibility, Method, MIN_KEPT, SAMPLES},
+    RewardVariant,
+};
+
+/// Last address of the precompile range, `0x0000…09ff`. Precompiles start at `0x…01`.
+pub const PRECOMPILE_END: u16 = 0x09ff;
+
+/// Well-known addresses some infrastructure treats specially, by name.
+pub const DENYLIST: [(&str, [u8; 20]); 8] = [
+    ("the zero address", [0; 20]),
+    (
+        "the 0xdead burn address",
+        hex!("000000000000000000000000000000000000dEaD"),
+    ),
+    ("CreateX", factory::CREATEX.address),
+    (
+        "the deterministic deployment proxy",
+        hex!("4e59b44847b379578588920cA78FbF26c0B4956C"),
+    ),
+    (
+        "Multicall3",
+        hex!("cA11bde05977b3631167028862bE2a173976CA11"),
+    ),
+    ("Permit2", hex!("000000000022D473030F116dDEE9F6B43aC78BA3")),
+    (
+        "the ERC-4337 v0.6 EntryPoint",
+        hex!("5FF137D4b0FDCD49DcA30c7CF57E578a026d2789"),
+    ),
+    (
+        "the ERC-4337 v0.7 EntryPoint",
+        hex!("0000000071727De22E5E9d8BAf0edAc6f37da032"),
+    ),
+];
+
+/// Why `address` is reserved, none if it isn't: it is in the precompile range or on the
+/// [`DENYLIST`].
+pub fn reserved(address: &[u8; 20]) -> Option<&'static str> {
+    if let Some((name, _)) = DENYLIST.iter().find(|(_, denied)| denied == address) {
+        return Some(name);
+    }
+    let low = u16::from_be_bytes([address[18], address[19]]);
+    match address[..18].iter().all(|byte| *byte == 0) && (1..=PRECOMPILE_END).contains(&low) {
+        true => Some("the precompile range"),
+        false => None,
+    }
+}
+
+/// Whether `--avoid-reserved` is on unless overridden: for the zero rewards, whose matches
+/// head for the precompile range.
+pub fn avoided_by_default(reward: &RewardVariant) -> bool {
+    match reward {
+        RewardVariant::LeadingZeros { .. }
+        | RewardVariant::LeadingZeroNibbles { .. }
+        | RewardVariant::TotalZeros { .. }
+        | RewardVariant::LeadingAndTotalZeros { .. }
+        | RewardVariant::LeadingOrTotalZeros { .. } => true,
+        RewardVariant::Matching { .. }
+        | RewardVariant::MatchingAny { .. }
+        | RewardVariant::ChecksumMatching { .. }
+        | RewardVariant::SymmetricEnds { .. } => false,
+    }
+}
+
+/// Check that `reward` can be met outside of the reserved addresses often enough, so
+/// `--avoid-reserved` doesn't leave a run mining for nothing. Rewards keeping less than
+/// [`MIN_KEPT`] of their matches are refused unless `force` is set, those keeping none for sure
+/// even then.
+pub fn check_avoidable(reward: &RewardVariant, force: bool) -> Result<(), String> {
+    let feasibility = feasibility(reward, SAMPLES);
+    if feasibility.kept == 0.0 && feasibility.method == Method::Exact {
+        return Err(format!(
+            "every address matching {} is reserved (the precompile range or a well-known address), pass --allow-reserved to mine for it anyway",
+            reward
+        ));
+    }
+    match feasibility.kept >= MIN_KEPT || force {
+        true => Ok(()),
+        false => Err(format!(
+            "only {:.3}% of the addresses matching {} are not reserved ({}), pass --force to mine for them anyway or --allow-reserved to keep reserved matches",
+            feasibility.kept * 100.0,
+            reward,
+            feasibility.method
+        )),
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::hex;
+use serde_json::{json, Value};
+
+use crate::http::{RateLimited, Transport};
+
+/// Spaces requests out to `rate` per second on average, letting bursts of up to `burst` through
+/// at once.
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket, `rate` and `burst` are raised to at least one.
+    pub fn new(rate: u32, burst: u32, now: Instant) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            rate: rate.max(1) as f64,
+            burst,
+            tokens: burst,
+            last: now,
+        }
+    }
+
+    /// Take a token for a request sent at `now`, returning how long to wait before sending it.
+    /// Tokens taken while the bucket is empty are owed, so requests keep being spaced out.
+    pub fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst) - 1.0;
+        self.last = now;
+        match self.tokens >= 0.0 {
+            true => Duration::ZERO,
+            false => Duration::from_secs_f64(-self.tokens / self.rate),
+        }
+    }
+}
+
+/// How often and after how long requests the node rate limited are sent again: after the wait
+/// of its `Retry-After` header, or without one after `base`, doubling with every retry up to
+/// `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 5,
+            base: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry `attempt`, from 0, of a request refused with `limited`.
+    pub fn delay(&self, attempt: u32, limited: RateLimited) -> Duration {
+        limited
+            .retry_after
+            .unwrap_or_else(|| self.base.saturating_mul(1 << attempt.min(16)).min(self.max))
+    }
+}
+
+/// Minimal Ethereum JSON-RPC client, for the few lookups made before mining. Requests can be
+/// rate limited, and those the node refuses with `429 Too Many Requests` are retried.
+pub struct RpcClient<T: Transport> {
+    transport: T,
+    next_id: u64,
+    limiter: Option<TokenBucket>,
+    retry: RetryPolicy,
+    /// Cleared once the node refused a batch, lookups are sent one by one from then on.
+    batching: bool,
+}
+
+impl<T: Transport> RpcClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            next_id: 1,
+            limiter: None,
+            retry: RetryPolicy::default(),
+            batching: true,
+        }
+    }
+
+    /// Send at most `max_rps` requests per second, in bursts of up to `burst`.
+    pub fn with_rate_limit(mut self, max_rps: u32, burst: u32) -> Self {
+        self.limiter = Some(TokenBucket::new(max_rps, burst, Instant::now()));
+        self
+    }
+
+    pub fn with_retries(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// POST `body` once the rate limit allows, retrying while the node rate limits it.
+    fn post(&mut self, body: &str) -> Result<String, String> {
+        let mut attempt = 0;
+        loop {
+            if let Some(limiter) = self.limiter.as_mut() {
+                thread::sleep(limiter.take(Instant::now()));
+            }
+            match self.transport.post("", body) {
+                Err(e) => match self.transport.rate_limited() {
+                    Some(limited) if attempt < self.retry.retries => {
+                        thread::sleep(self.retry.delay(attempt, limited));
+                        attempt += 1;
+                    }
+                    _ => return Err(e),
+                },
+                response => return response,
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id,
+            "method": method,
+            "params": params,
+        });
+        self.next_id += 1;
+        request
+    }
+
+    /// Call `method` and return its result, or the node's error message.
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let request = self.request(method, params);
+        let response = self.post(&request.to_string())?;
+        let response: Value = serde_json::from_str(&response)
+            .map_err(|e| format!("invalid {} response: {}", method, e))?;
+        result(method, response)
+    }
+
+    /// Runtime code deployed at `address` on the latest block, empty if there is none.
+    pub fn get_code(&mut self, address: &[u8; 20]) -> Result<Vec<u8>, String> {
+        let address = format!("0x{}", hex::encode(address));
+        let code = self.call("eth_getCode", json!([address, "latest"]))?;
+        decode_code(&address, &code)
+    }
+
+    /// Runtime code of each of `addresses`, looked up in a single batch request. Nodes that
+    /// don't answer batches are asked one address at a time, for this and every later lookup.
+    pub fn get_codes(&mut self, addresses: &[[u8; 20]]) -> Result<Vec<Vec<u8>>, String> {
+        if addresses.len() > 1 && self.batching {
+            match self.batch_get_code(addresses) {
+                Ok(codes) => return Ok(codes),
+                // a batch refused for the rate limit is no reason to stop batching
+                Err(e) if self.transport.rate_limited().is_some() => return Err(e),
+                Err(_) => self.batching = false,
+            }
+        }
+        addresses
+            .iter()
+            .map(|address| self.get_code(address))
+            .collect()
+    }
+
+    fn batch_get_code(&mut self, addresses: &[[u8; 20]]) -> Result<Vec<Vec<u8>>, String> {
+        let addresses: Vec<String> = addresses
+            .iter()
+            .map(|address| format!("0x{}", hex::encode(address)))
+            .collect();
+        let requests: Vec<Value> = addresses
+            .iter()
+            .map(|address| self.request("eth_getCode", json!([address, "latest"])))
+            .collect();
+        let response = self.post(&Value::from(requests.clone()).to_string())?;
+        let responses: Vec<Value> = serde_json::from_str(&response)
+            .map_err(|e| format!("invalid eth_getCode batch response: {}", e))?;
+
+        // responses may come in any order, and are matched to the requests by id
+        requests
+            .iter()
+            .zip(&addresses)
+            .map(|(request, address)| {
+                let response = responses
+                    .iter()
+                    .find(|response| response["id"] == request["id"])
+                    .ok_or_else(|| format!("eth_getCode batch response lacks {}", address))?;
+                decode_code(address, &result("eth_getCode", response.clone())?)
+            })
+            .collect()
+    }
+}
+
+/// The result of a JSON-RPC `response` to `method`, or the node's error message.
+fn result(method: &str, mut response: Value) -> Result<Value, String> {
+    if let Some(error) = response.get("error") {
+        return Err(format!(
+            "{} failed: {}",
+            method,
+            error["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(format!("{} response has no result", method)),
+    }
+}
+
+fn decode_code(address: &str, code: &Value) -> Result<Vec<u8>, String> {
+    code.as_str()
+        .and_then(|code| hex::decode(code).ok())
+        .ok_or_else(|| format!("invalid code returned for {}", address))
+}
@@ -0,0 +1,100 @@
+use std::{fs, io, path::Path};
+
+use alloy_primitives::{hex, keccak256, Address};
+use serde_json::{json, Value};
+
+use crate::CreateVariant;
+
+/// Version of the Safe Transaction Builder batch format written by `--format safe-tx`.
+pub const BATCH_VERSION: &str = "1.0";
+
+/// Solidity signature of the CreateX function deploying with `variant`.
+pub fn deploy_signature(variant: &CreateVariant) -> &'static str {
+    match variant {
+        CreateVariant::Create2 { .. } => "deployCreate2(bytes32,bytes)",
+        CreateVariant::Create3 => "deployCreate3(bytes32,bytes)",
+    }
+}
+
+/// ABI encoded call of the CreateX deploy function for `variant` with `salt` and `init_code`.
+/// CreateX guards the salt itself, so the mined salt is passed unchanged.
+pub fn deploy_calldata(variant: &CreateVariant, salt: &[u8; 32], init_code: &[u8]) -> Vec<u8> {
+    let padded_len = init_code.len().div_ceil(32) * 32;
+    let mut data = Vec::with_capacity(4 + 32 * 3 + padded_len);
+
+    data.extend_from_slice(&keccak256(deploy_signature(variant))[..4]);
+    data.extend_from_slice(salt);
+    // offset of the dynamic `bytes` argument, right after the two head words
+    data.extend_from_slice(&word(64));
+    data.extend_from_slice(&word(init_code.len() as u64));
+    data.extend_from_slice(init_code);
+    data.resize(4 + 32 * 3 + padded_len, 0);
+    data
+}
+
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// One entry of the `transactions` list of a batch, calling `factory` with `calldata`.
+pub fn transaction(factory: &[u8; 20], calldata: &[u8]) -> Value {
+    json!({
+        "to": Address::from(*factory).to_checksum(None),
+        "value": "0",
+        "data": format!("0x{}", hex::encode(calldata)),
+        "contractMethod": null,
+        "contractInputsValues": null,
+    })
+}
+
+/// Transactions accumulated into one batch file, importable into the Safe Transaction Builder.
+pub struct SafeBatch {
+    chain_id: u64,
+    created_at: u64,
+    transactions: Vec<Value>,
+}
+
+impl SafeBatch {
+    /// An empty batch for `chain_id`, created at `created_at` milliseconds since the epoch.
+    pub fn new(chain_id: u64, created_at: u64) -> Self {
+        Self {
+            chain_id,
+            created_at,
+            transactions: vec![],
+        }
+    }
+
+    pub fn push(&mut self, transaction: Value) {
+        self.transactions.push(transaction);
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": BATCH_VERSION,
+            "chainId": self.chain_id.to_string(),
+            "createdAt": self.created_at,
+            "meta": {
+                "name": "createXcrunch deployments",
+                "description": format!("{} mined CreateX deployments", self.transactions.len()),
+            },
+            "transactions": self.transactions,
+        })
+    }
+
+    /// Rewrite the batch file atomically, so it stays importable if the run is interrupted.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.to_json())?)?;
+        fs::rename(tmp, path)
+    }
+}
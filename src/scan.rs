@@ -0,0 +1,129 @@
+use std::{fmt, str::FromStr};
+
+const LCG_MULTIPLIER: u64 = 6364136223846793005;
+const LCG_INCREMENT: u64 = 1442695040888963407;
+const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+const SPLITMIX_MULTIPLIERS: [u64; 2] = [0xbf58476d1ce4e5b9, 0x94d049bb133111eb];
+
+/// Order in which the nonce space is explored. Every order is a bijection from scan indices
+/// to nonces, so a run covering indices `a..b` mines exactly `b - a` distinct nonces. The
+/// kernel evaluates the same mapping as [`ScanOrder::nonce_at`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScanOrder {
+    /// Nonce equals the scan index.
+    #[default]
+    Linear,
+    /// `(index + seed) * 6364136223846793005 + 1442695040888963407`, wrapping.
+    Lcg,
+    /// The splitmix64 output for the state `seed + (index + 1) * 0x9e3779b97f4a7c15`.
+    Splitmix,
+}
+
+impl ScanOrder {
+    /// Nonce mined at scan position `index` for `seed`.
+    pub fn nonce_at(&self, index: u64, seed: u64) -> u64 {
+        match self {
+            ScanOrder::Linear => index,
+            ScanOrder::Lcg => index
+                .wrapping_add(seed)
+                .wrapping_mul(LCG_MULTIPLIER)
+                .wrapping_add(LCG_INCREMENT),
+            ScanOrder::Splitmix => {
+                let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+                z = (z ^ (z >> 30)).wrapping_mul(SPLITMIX_MULTIPLIERS[0]);
+                z = (z ^ (z >> 27)).wrapping_mul(SPLITMIX_MULTIPLIERS[1]);
+                z ^ (z >> 31)
+            }
+        }
+    }
+
+    /// Scan position at which `nonce` is mined for `seed`, the inverse of
+    /// [`ScanOrder::nonce_at`].
+    pub fn index_of(&self, nonce: u64, seed: u64) -> u64 {
+        match self {
+            ScanOrder::Linear => nonce,
+            ScanOrder::Lcg => nonce
+                .wrapping_sub(LCG_INCREMENT)
+                .wrapping_mul(inverse(LCG_MULTIPLIER))
+                .wrapping_sub(seed),
+            ScanOrder::Splitmix => {
+                let mut z = unshift(nonce, 31);
+                z = unshift(z.wrapping_mul(inverse(SPLITMIX_MULTIPLIERS[1])), 27);
+                z = unshift(z.wrapping_mul(inverse(SPLITMIX_MULTIPLIERS[0])), 30);
+                z.wrapping_sub(seed)
+                    .wrapping_mul(inverse(GOLDEN_GAMMA))
+                    .wrapping_sub(1)
+            }
+        }
+    }
+
+    /// Identifier passed to the kernel in the message buffer.
+    pub fn kernel_id(&self) -> u32 {
+        match self {
+            ScanOrder::Linear => 0,
+            ScanOrder::Lcg => 1,
+            ScanOrder::Splitmix => 2,
+        }
+    }
+
+    /// Output header line recording the order and seed, so salts can be mapped back to scan
+    /// indices.
+    pub fn header(&self, seed: u64) -> String {
+        format!("# Scan order: {} seed 0x{:016x}", self, seed)
+    }
+
+    /// The order and seed of a [`ScanOrder::header`] line.
+    pub fn parse_header(line: &str) -> Option<(Self, u64)> {
+        let (order, seed) = line
+            .strip_prefix("# Scan order: ")?
+            .split_once(" seed 0x")?;
+        Some((order.parse().ok()?, u64::from_str_radix(seed.trim(), 16).ok()?))
+    }
+}
+
+/// Multiplicative inverse of the odd `a` modulo 2^64, by Newton's iteration, each step doubling
+/// the correct low bits.
+const fn inverse(a: u64) -> u64 {
+    let mut inverse = a;
+    let mut step = 0;
+    while step < 5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(a.wrapping_mul(inverse)));
+        step += 1;
+    }
+    inverse
+}
+
+/// Undo `x ^ (x >> shift)`.
+fn unshift(y: u64, shift: u32) -> u64 {
+    let mut x = y;
+    for _ in 0..64 / shift {
+        x = y ^ (x >> shift);
+    }
+    x
+}
+
+impl fmt::Display for ScanOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanOrder::Linear => write!(f, "linear"),
+            ScanOrder::Lcg => write!(f, "lcg"),
+            ScanOrder::Splitmix => write!(f, "splitmix"),
+        }
+    }
+}
+
+impl FromStr for ScanOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(ScanOrder::Linear),
+            "lcg" => Ok(ScanOrder::Lcg),
+            "splitmix" => Ok(ScanOrder::Splitmix),
+            _ => Err(format!(
+                "unknown scan order {} (expected linear, lcg or splitmix)",
+                s
+            )),
+        }
+    }
+}
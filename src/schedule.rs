@@ -0,0 +1,220 @@
+use std::{fmt, str::FromStr, thread, time::Duration};
+
+use chrono::{DateTime, Days, Local, LocalResult, NaiveDateTime, NaiveTime, TimeZone};
+
+/// Daily window of local wall-clock time, `22:00-06:00`. A window ending before it starts spans
+/// midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        match self.start < self.end {
+            true => self.start <= time && time < self.end,
+            false => self.start <= time || time < self.end,
+        }
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl FromStr for TimeWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid time window {} (expected HH:MM-HH:MM)", s);
+        let (start, end) = s.trim().split_once('-').ok_or_else(invalid)?;
+        let time =
+            |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+        let window = TimeWindow {
+            start: time(start)?,
+            end: time(end)?,
+        };
+        if window.start == window.end {
+            return Err(format!("time window {} is empty", s));
+        }
+        Ok(window)
+    }
+}
+
+/// The daily windows `--schedule` mines in, pausing outside of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    windows: Vec<TimeWindow>,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// Comma-separated windows, `22:00-06:00,12:00-14:00`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let windows = s
+            .split(',')
+            .map(TimeWindow::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { windows })
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, window) in self.windows.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", window)?;
+        }
+        Ok(())
+    }
+}
+
+impl Schedule {
+    pub fn windows(&self) -> &[TimeWindow] {
+        &self.windows
+    }
+
+    /// Whether a window is open at the wall-clock `time`.
+    pub fn is_open(&self, time: NaiveTime) -> bool {
+        self.windows.iter().any(|window| window.contains(time))
+    }
+
+    /// When mining resumes if it pauses at `now`, none while a window is open. A window opening
+    /// at a time skipped by a DST change opens once the clock jumps past it, one opening at a
+    /// time repeated by a DST change opens the first time.
+    pub fn pause_until<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let local = now.naive_local();
+        if self.is_open(local.time()) {
+            return None;
+        }
+
+        let timezone = now.timezone();
+        let today = local.date();
+        self.windows
+            .iter()
+            .flat_map(|window| {
+                [
+                    today.pred_opt(),
+                    Some(today),
+                    today.checked_add_days(Days::new(1)),
+                ]
+                .into_iter()
+                .flatten()
+                .map(|date| date.and_time(window.start))
+            })
+            .filter_map(|opening| first_instant(&timezone, opening))
+            .filter(|opening| opening > now)
+            .min()
+    }
+}
+
+/// The first instant at or after the wall-clock time `local` in `timezone`.
+fn first_instant<Tz: TimeZone>(timezone: &Tz, mut local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    // DST gaps are at most a few hours
+    for _ in 0..24 * 60 {
+        match timezone.from_local_datetime(&local) {
+            LocalResult::Single(instant) => return Some(instant),
+            LocalResult::Ambiguous(earliest, _) => return Some(earliest),
+            LocalResult::None => local += chrono::Duration::minutes(1),
+        }
+    }
+    None
+}
+
+/// Units of a [`parse_duration`] duration, largest first.
+const DURATION_UNITS: [(char, u64); 4] = [('d', 86_400), ('h', 3_600), ('m', 60), ('s', 1)];
+
+/// Parse a duration written as amounts of days, hours, minutes and seconds, largest first:
+/// `90m`, `6h`, `2h30m`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {} (expected e.g. 90m, 6h or 2h30m)", s);
+    let mut rest = s.trim();
+    let mut units = DURATION_UNITS.iter();
+    let mut seconds: u64 = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit = rest[digits..].chars().next().unwrap();
+        let (_, unit_seconds) = units.find(|(name, _)| *name == unit).ok_or_else(invalid)?;
+        seconds = amount
+            .checked_mul(*unit_seconds)
+            .and_then(|amount| seconds.checked_add(amount))
+            .ok_or_else(invalid)?;
+        rest = &rest[digits + 1..];
+    }
+    match seconds {
+        0 => Err(format!("duration {} is empty", s)),
+        _ => Ok(Duration::from_secs(seconds)),
+    }
+}
+
+/// Render whole seconds of `duration` the way [`parse_duration`] reads them, `2h30m`.
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+    let mut formatted = String::new();
+    for (name, unit_seconds) in DURATION_UNITS {
+        if seconds >= unit_seconds {
+            formatted += &format!("{}{}", seconds / unit_seconds, name);
+            seconds %= unit_seconds;
+        }
+    }
+    formatted
+}
+
+/// Wall clock of the scheduler. Wraps the system clock so pauses can be exercised without
+/// waiting for them.
+pub trait Clock {
+    type Tz: TimeZone;
+
+    fn now(&self) -> DateTime<Self::Tz>;
+    fn sleep(&self, duration: Duration);
+}
+
+/// Local time of the system.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    type Tz = Local;
+
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Sleep in steps of at most `step` until a window of `schedule` opens, or until `keep_waiting`,
+/// asked before every step, returns false. Returns whether a window opened.
+pub fn wait_for_window<C: Clock>(
+    schedule: &Schedule,
+    clock: &C,
+    step: Duration,
+    mut keep_waiting: impl FnMut() -> bool,
+) -> bool {
+    while let Some(until) = schedule.pause_until(&clock.now()) {
+        if !keep_waiting() {
+            return false;
+        }
+        let left = (until - clock.now()).to_std().unwrap_or_default();
+        clock.sleep(left.min(step).max(Duration::from_millis(1)));
+    }
+    true
+}
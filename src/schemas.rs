@@ -0,0 +1,147 @@
+use serde_json::Value;
+use std::fmt;
+
+/// Field of the schema version in the JSON artifacts. The formats written before artifacts
+/// were versioned lack it, and are read as version 0.
+pub const FIELD: &str = "schema_version";
+
+/// The machine-readable files the miner writes and reads back. Each names its schema version,
+/// so readers migrate older files to the current schema and refuse newer ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    /// A line of `jsonl` output, see [`FoundSalt`](crate::FoundSalt).
+    Results,
+    /// The `<output>.checkpoint` sidecar runs resume from.
+    Checkpoint,
+    /// A row of a `--stats-file`, which carries its version in the first column.
+    CycleStats,
+    /// A line of the sessions log of a campaign.
+    Sessions,
+    /// The `build.json` of a run bundle, which versions the bundle as a whole.
+    Bundle,
+    /// A cycle recorded by `--record-cycles`, which carries its version in its header.
+    Replay,
+    /// The `--json-events` stream, versioned by its start event.
+    Events,
+}
+
+impl Artifact {
+    pub const ALL: [Artifact; 7] = [
+        Artifact::Results,
+        Artifact::Checkpoint,
+        Artifact::CycleStats,
+        Artifact::Sessions,
+        Artifact::Bundle,
+        Artifact::Replay,
+        Artifact::Events,
+    ];
+
+    /// Version written by this build.
+    pub const fn current(self) -> u32 {
+        match self {
+            Artifact::Results
+            | Artifact::Checkpoint
+            | Artifact::CycleStats
+            | Artifact::Sessions
+            | Artifact::Bundle
+            | Artifact::Replay
+            | Artifact::Events => 1,
+        }
+    }
+
+    /// Oldest version this build reads. Stats rows and replay records were versioned from the
+    /// start, and have no version 0.
+    pub const fn oldest(self) -> u32 {
+        match self {
+            Artifact::CycleStats | Artifact::Replay => 1,
+            Artifact::Results
+            | Artifact::Checkpoint
+            | Artifact::Sessions
+            | Artifact::Bundle
+            | Artifact::Events => 0,
+        }
+    }
+
+    /// Check that this build reads `version` of the artifact.
+    pub fn check(self, version: u32) -> Result<(), String> {
+        match (self.oldest()..=self.current()).contains(&version) {
+            true => Ok(()),
+            false => Err(format!(
+                "{} schema version {} is not supported, this build reads versions {} to {}",
+                self,
+                version,
+                self.oldest(),
+                self.current()
+            )),
+        }
+    }
+
+    /// The schema version of a JSON artifact, 0 when it predates versioning.
+    pub fn version_of(self, value: &Value) -> Result<u32, String> {
+        match value.get(FIELD) {
+            None | Some(Value::Null) => Ok(0),
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| format!("invalid {} schema version {}", self, version)),
+        }
+    }
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Artifact::Results => "jsonl result",
+            Artifact::Checkpoint => "checkpoint",
+            Artifact::CycleStats => "stats row",
+            Artifact::Sessions => "campaign session",
+            Artifact::Bundle => "bundle",
+            Artifact::Replay => "replay record",
+            Artifact::Events => "event stream",
+        })
+    }
+}
+
+/// Migrate the JSON `value` of `artifact` from schema version `from` to the later version `to`,
+/// one version at a time.
+pub fn migrate(artifact: Artifact, mut value: Value, from: u32, to: u32) -> Result<Value, String> {
+    artifact.check(from)?;
+    artifact.check(to)?;
+    if from > to {
+        return Err(format!(
+            "{} schema version {} can't be migrated back to version {}",
+            artifact, from, to
+        ));
+    }
+    if !value.is_object() {
+        return Err(format!("{} is not a JSON object", artifact));
+    }
+    for version in from..to {
+        value = step(artifact, version, value);
+    }
+    Ok(value)
+}
+
+/// Read the schema version of the JSON `value` of `artifact`, and migrate it to the current
+/// version.
+pub fn upgrade(artifact: Artifact, value: Value) -> Result<Value, String> {
+    let version = artifact.version_of(&value)?;
+    migrate(artifact, value, version, artifact.current())
+}
+
+/// Migrate `value` of `artifact` from version `from` to the next.
+fn step(artifact: Artifact, from: u32, mut value: Value) -> Value {
+    let object = value.as_object_mut().expect("checked by migrate");
+    // version 0 checkpoints of the oldest builds lack the counts and the fingerprint, the
+    // other version 0 formats only lack the version
+    if (artifact, from) == (Artifact::Checkpoint, 0) {
+        for count in ["attempts", "found"] {
+            object.entry(count).or_insert(Value::from(0));
+        }
+        for optional in ["fingerprint", "campaign"] {
+            object.entry(optional).or_insert(Value::Null);
+        }
+    }
+    object.insert(FIELD.to_string(), Value::from(from + 1));
+    value
+}
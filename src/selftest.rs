@@ -0,0 +1,400 @@
+use alloy_primitives::{hex, keccak256};
+use std::{fmt, str::FromStr};
+use wgpu::util::DeviceExt;
+
+use crate::{scan::ScanOrder, KECCAK256_KERNEL};
+
+/// How much of the kernel to check before mining.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfTestMode {
+    /// Hash known inputs and compare them against the CPU.
+    Basic,
+    /// Additionally rotate every lane pattern by all 64 amounts.
+    Full,
+}
+
+impl FromStr for SelfTestMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "basic" => Ok(SelfTestMode::Basic),
+            "full" => Ok(SelfTestMode::Full),
+            _ => Err(format!(
+                "unknown self-test mode {} (expected basic or full)",
+                s
+            )),
+        }
+    }
+}
+
+/// A rotation the kernel got wrong.
+pub struct RotationMismatch {
+    pub lane: usize,
+    pub amount: u32,
+    pub input: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for RotationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lane {} rotated by {}: 0x{:016x} -> expected 0x{:016x}, got 0x{:016x}",
+            self.lane, self.amount, self.input, self.expected, self.actual
+        )
+    }
+}
+
+/// A hash the kernel got wrong.
+pub struct HashMismatch {
+    pub input: [u8; 32],
+    pub expected: [u8; 32],
+    pub actual: [u8; 32],
+}
+
+impl fmt::Display for HashMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "keccak256(0x{}): expected 0x{}, got 0x{}",
+            hex::encode(self.input),
+            hex::encode(self.expected),
+            hex::encode(self.actual)
+        )
+    }
+}
+
+/// One known pattern per keccak lane, chosen to expose carries and shifts across the 32-bit
+/// boundary of the emulated 64-bit lanes.
+pub fn rotation_patterns() -> [u64; 25] {
+    [
+        0x0000_0000_0000_0001,
+        0x8000_0000_0000_0000,
+        0x0000_0000_8000_0000,
+        0x0000_0001_0000_0000,
+        0x0000_0001_8000_0000,
+        0x8000_0000_0000_0001,
+        0x0000_0000_ffff_ffff,
+        0xffff_ffff_0000_0000,
+        0xffff_ffff_ffff_ffff,
+        0x0123_4567_89ab_cdef,
+        0xfedc_ba98_7654_3210,
+        0xaaaa_aaaa_aaaa_aaaa,
+        0x5555_5555_5555_5555,
+        0xaaaa_aaaa_5555_5555,
+        0x0000_ffff_ffff_0000,
+        0xffff_0000_0000_ffff,
+        0x7fff_ffff_ffff_fffe,
+        0x8000_0001_8000_0001,
+        0x0f0f_0f0f_f0f0_f0f0,
+        0x00ff_00ff_ff00_ff00,
+        0xdead_beef_cafe_babe,
+        0x0000_0000_0000_0000,
+        0x1000_0000_0000_0001,
+        0x0000_0003_c000_0000,
+        0x8181_8181_8181_8181,
+    ]
+}
+
+/// 32-byte inputs whose lanes straddle the 32-bit word boundary.
+pub fn carry_stress_inputs() -> Vec<[u8; 32]> {
+    let mut inputs = vec![[0u8; 32], [0xff; 32]];
+
+    for pattern in rotation_patterns() {
+        let mut input = [0u8; 32];
+        for lane in input.chunks_exact_mut(8) {
+            lane.copy_from_slice(&pattern.to_le_bytes());
+        }
+        inputs.push(input);
+    }
+
+    // a single set bit at either side of every word boundary
+    for bit in [31, 32, 63, 64, 95, 96, 127, 128, 255] {
+        let mut input = [0u8; 32];
+        input[bit / 8] = 1 << (bit % 8);
+        inputs.push(input);
+    }
+
+    inputs
+}
+
+/// Run `entry_point` of the mining kernel over `input`, reading back `output_words` words.
+fn run_kernel(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    entry_point: &str,
+    input: &[u32],
+    invocations: u32,
+    output_words: usize,
+) -> Result<Vec<u32>, String> {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Self-test Shader"),
+        source: wgpu::ShaderSource::Wgsl(KECCAK256_KERNEL.into()),
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Self-test Pipeline"),
+        layout: None,
+        module: &shader,
+        entry_point: Some(entry_point),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let input_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Self-test Input Buffer"),
+        contents: bytemuck::cast_slice(input),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+
+    let output_size = (output_words * 4) as u64;
+    let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Self-test Output Buffer"),
+        size: output_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Self-test Staging Buffer"),
+        size: output_size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Self-test Bind Group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: input_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: output_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Self-test Encoder"),
+    });
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Self-test Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(invocations.div_ceil(64), 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_size);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).unwrap();
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    match pollster::block_on(receiver.receive()) {
+        Some(Ok(())) => {
+            let output = bytemuck::cast_slice::<u8, u32>(&buffer_slice.get_mapped_range()).to_vec();
+            staging_buffer.unmap();
+            Ok(output)
+        }
+        _ => Err("failed to read back the self-test results".to_string()),
+    }
+}
+
+/// Rotate every lane of `patterns` by all 64 amounts on the GPU, in (lane, amount) order.
+pub fn gpu_rotations(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    patterns: &[u64; 25],
+) -> Result<Vec<u64>, String> {
+    let input: Vec<u32> = patterns
+        .iter()
+        .flat_map(|&lane| [lane as u32, (lane >> 32) as u32])
+        .collect();
+
+    let output = run_kernel(
+        device,
+        queue,
+        "rotation_self_test",
+        &input,
+        25 * 64,
+        25 * 64 * 2,
+    )?;
+
+    Ok(output
+        .chunks_exact(2)
+        .map(|words| words[0] as u64 | ((words[1] as u64) << 32))
+        .collect())
+}
+
+/// Hash each 32-byte input with the kernel's keccak256.
+pub fn gpu_keccak256(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    inputs: &[[u8; 32]],
+) -> Result<Vec<[u8; 32]>, String> {
+    let input: Vec<u32> = inputs
+        .iter()
+        .flat_map(|input| {
+            input
+                .chunks_exact(4)
+                .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        })
+        .collect();
+
+    let output = run_kernel(
+        device,
+        queue,
+        "keccak_self_test",
+        &input,
+        inputs.len() as u32,
+        inputs.len() * 8,
+    )?;
+
+    Ok(output
+        .chunks_exact(8)
+        .map(|words| {
+            let mut hash = [0u8; 32];
+            for (bytes, word) in hash.chunks_exact_mut(4).zip(words) {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+            hash
+        })
+        .collect())
+}
+
+/// Nonces the kernel mines at scan positions `base..base + count` in `order`.
+pub fn gpu_scan_nonces(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    order: ScanOrder,
+    seed: u64,
+    base: u64,
+    count: u32,
+) -> Result<Vec<u64>, String> {
+    let input = [
+        order.kernel_id(),
+        seed as u32,
+        (seed >> 32) as u32,
+        base as u32,
+        (base >> 32) as u32,
+    ];
+
+    let output = run_kernel(
+        device,
+        queue,
+        "scan_order_self_test",
+        &input,
+        count,
+        count as usize * 2,
+    )?;
+
+    Ok(output
+        .chunks_exact(2)
+        .map(|words| words[0] as u64 | ((words[1] as u64) << 32))
+        .collect())
+}
+
+/// Compare GPU rotations against native u64 rotations.
+pub fn check_rotations(patterns: &[u64; 25], actual: &[u64]) -> Vec<RotationMismatch> {
+    let mut mismatches = vec![];
+
+    for (lane, &input) in patterns.iter().enumerate() {
+        for amount in 0..64u32 {
+            let expected = input.rotate_left(amount);
+            let actual = actual
+                .get(lane * 64 + amount as usize)
+                .copied()
+                .unwrap_or_default();
+
+            if actual != expected {
+                mismatches.push(RotationMismatch {
+                    lane,
+                    amount,
+                    input,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+/// Compare GPU hashes against the CPU keccak256.
+pub fn check_hashes(inputs: &[[u8; 32]], actual: &[[u8; 32]]) -> Vec<HashMismatch> {
+    inputs
+        .iter()
+        .zip(actual)
+        .filter_map(|(input, actual)| {
+            let expected = keccak256(input).0;
+            (expected != *actual).then_some(HashMismatch {
+                input: *input,
+                expected,
+                actual: *actual,
+            })
+        })
+        .collect()
+}
+
+/// Run the self-test on `device`, describing every mismatch on failure.
+pub fn run(device: &wgpu::Device, queue: &wgpu::Queue, mode: SelfTestMode) -> Result<(), String> {
+    let mut failures: Vec<String> = vec![];
+
+    let inputs = carry_stress_inputs();
+    let hashes = gpu_keccak256(device, queue, &inputs)?;
+    failures.extend(
+        check_hashes(&inputs, &hashes)
+            .iter()
+            .map(ToString::to_string),
+    );
+
+    if mode == SelfTestMode::Full {
+        let patterns = rotation_patterns();
+        let rotations = gpu_rotations(device, queue, &patterns)?;
+        failures.extend(
+            check_rotations(&patterns, &rotations)
+                .iter()
+                .map(ToString::to_string),
+        );
+    }
+
+    if mode == SelfTestMode::Full {
+        // start right below a 32-bit boundary so the index carry is exercised
+        let (seed, base, count) = (0xdead_beef_cafe_babe, 0xffff_ff00, 512);
+        for order in [ScanOrder::Lcg, ScanOrder::Splitmix] {
+            let nonces = gpu_scan_nonces(device, queue, order, seed, base, count)?;
+            for (offset, actual) in nonces.into_iter().enumerate() {
+                let index = base + offset as u64;
+                let expected = order.nonce_at(index, seed);
+                if actual != expected {
+                    failures.push(format!(
+                        "{} scan index {}: expected nonce {}, got {}",
+                        order, index, expected, actual
+                    ));
+                }
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "kernel self-test failed with {} mismatches:\n{}",
+            failures.len(),
+            failures.join("\n")
+        ))
+    }
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+/// Running cost of the mining host, from `--power-watts` and `--price-per-kwh` or from
+/// `--cloud-cost-per-hour`. Costs are in whatever currency the prices are given in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostModel {
+    Electricity { watts: f64, price_per_kwh: f64 },
+    Cloud { per_hour: f64 },
+}
+
+impl CostModel {
+    /// The model described by the cost options, if they describe one.
+    pub fn from_options(
+        power_watts: Option<f64>,
+        price_per_kwh: Option<f64>,
+        cloud_cost_per_hour: Option<f64>,
+    ) -> Option<Self> {
+        match (power_watts, price_per_kwh, cloud_cost_per_hour) {
+            (Some(watts), Some(price_per_kwh), None) => Some(CostModel::Electricity {
+                watts,
+                price_per_kwh,
+            }),
+            (None, None, Some(per_hour)) => Some(CostModel::Cloud { per_hour }),
+            _ => None,
+        }
+    }
+
+    pub fn cost_per_hour(&self) -> f64 {
+        match self {
+            CostModel::Electricity {
+                watts,
+                price_per_kwh,
+            } => watts / 1000.0 * price_per_kwh,
+            CostModel::Cloud { per_hour } => *per_hour,
+        }
+    }
+
+    /// Energy drawn over `elapsed`, only known for the electricity model.
+    pub fn energy_kwh(&self, elapsed: Duration) -> Option<f64> {
+        match self {
+            CostModel::Electricity { watts, .. } => {
+                Some(watts / 1000.0 * elapsed.as_secs_f64() / 3600.0)
+            }
+            CostModel::Cloud { .. } => None,
+        }
+    }
+
+    /// Cost of running for `elapsed`.
+    pub fn cost(&self, elapsed: Duration) -> f64 {
+        self.cost_per_hour() * elapsed.as_secs_f64() / 3600.0
+    }
+
+    /// Expected cost of one result needing `expected_attempts` at `rate` attempts per second.
+    pub fn cost_per_result(&self, expected_attempts: f64, rate: f64) -> Option<f64> {
+        expected_duration(expected_attempts, rate).map(|duration| self.cost(duration))
+    }
+
+    /// Startup banner line.
+    pub fn banner(&self) -> String {
+        match self {
+            CostModel::Electricity {
+                watts,
+                price_per_kwh,
+            } => format!(
+                "Cost: {} per hour ({} W at {} per kWh)",
+                format_cost(self.cost_per_hour()),
+                watts,
+                price_per_kwh
+            ),
+            CostModel::Cloud { .. } => {
+                format!("Cost: {} per hour", format_cost(self.cost_per_hour()))
+            }
+        }
+    }
+
+    /// Final report of what the run consumed and what a result costs at the measured `rate`.
+    pub fn report(&self, elapsed: Duration, expected_attempts: f64, rate: f64) -> String {
+        let mut report = match self.energy_kwh(elapsed) {
+            Some(kwh) => format!(
+                "Energy used: {:.3} kWh, cost {}",
+                kwh,
+                format_cost(self.cost(elapsed))
+            ),
+            None => format!("Cost of this run: {}", format_cost(self.cost(elapsed))),
+        };
+        if let Some(cost) = self.cost_per_result(expected_attempts, rate) {
+            report.push_str(&format!(
+                "\nProjected cost per result at {:.2} MH/s: {}",
+                rate / 1_000_000.0,
+                format_cost(cost)
+            ));
+        }
+        report
+    }
+}
+
+/// Time needed for `expected_attempts` at `rate` attempts per second, unknown while nothing
+/// was measured.
+pub fn expected_duration(expected_attempts: f64, rate: f64) -> Option<Duration> {
+    if rate <= 0.0 || !expected_attempts.is_finite() {
+        return None;
+    }
+    Duration::try_from_secs_f64(expected_attempts / rate).ok()
+}
+
+/// Probability that at least one of `attempts` random addresses satisfies a reward needing
+/// `expected_attempts` on average, the share of the odds a run has explored. A run is as likely
+/// as not to have found a match after about 69% of the expected attempts.
+pub fn explored_probability(attempts: u64, expected_attempts: f64) -> f64 {
+    // 1 - (1 - p)^attempts, without losing the tiny p of hard rewards to rounding
+    -(attempts as f64 * (-1.0 / expected_attempts).ln_1p()).exp_m1()
+}
+
+/// Two decimals, switching to scientific notation for amounts too large to read.
+pub fn format_cost(cost: f64) -> String {
+    if cost < 1e9 {
+        format!("{:.2}", cost)
+    } else {
+        format!("{:.2e}", cost)
+    }
+}
+
+/// Probability that a random address starts with at least `nibbles` zero nibbles.
+pub fn leading_zero_nibbles_probability(nibbles: u32) -> f64 {
+    match nibbles {
+        0..=40 => 16f64.powi(-(nibbles as i32)),
+        _ => 0.0,
+    }
+}
+
+/// Probability that at least `threshold` of the 20 bytes of a random address are zero.
+pub fn total_zero_bytes_probability(threshold: u32) -> f64 {
+    let p: f64 = 1.0 / 256.0;
+    (threshold as i32..=20)
+        .map(|k| {
+            let choose = (0..k).fold(1.0, |acc, i| acc * (20 - i) as f64 / (i + 1) as f64);
+            choose * p.powi(k) * (1.0 - p).powi(20 - k)
+        })
+        .sum()
+}
+
+/// Probability that the 40 nibbles of a random address hold a run of at least `length` equal
+/// nibbles in a row.
+pub fn nibble_run_probability(length: u32) -> f64 {
+    let length = length as usize;
+    match length {
+        0 | 1 => return 1.0,
+        41.. => return 0.0,
+        _ => {}
+    }
+    // probability of the current run being i + 1 nibbles long with no run of `length` yet,
+    // and the probability of a run of `length` having been seen
+    let mut runs = vec![0.0; length - 1];
+    runs[0] = 1.0;
+    let mut seen = 0.0;
+    for _ in 1..40 {
+        let mut next = vec![0.0; length - 1];
+        next[0] = runs.iter().sum::<f64>() * 15.0 / 16.0;
+        for i in 1..length - 1 {
+            next[i] = runs[i - 1] / 16.0;
+        }
+        seen += runs[length - 2] / 16.0;
+        runs = next;
+    }
+    seen
+}
+
+/// How rare an address is by the metrics mining rewards, see [`Rarity::of`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rarity {
+    pub leading_zero_nibbles: u32,
+    pub total_zero_bytes: u32,
+    /// The nibble of the longest run of equal nibbles and its length. The first run wins ties.
+    pub longest_run: (u8, u32),
+}
+
+impl Rarity {
+    pub fn of(address: &[u8; 20]) -> Self {
+        let nibbles: Vec<u8> = address
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0f])
+            .collect();
+        let mut longest_run = (nibbles[0], 1);
+        let mut run = 1;
+        for i in 1..nibbles.len() {
+            run = match nibbles[i] == nibbles[i - 1] {
+                true => run + 1,
+                false => 1,
+            };
+            if run > longest_run.1 {
+                longest_run = (nibbles[i], run);
+            }
+        }
+        Self {
+            leading_zero_nibbles: nibbles.iter().take_while(|&&nibble| nibble == 0).count() as u32,
+            total_zero_bytes: address.iter().filter(|&&byte| byte == 0).count() as u32,
+            longest_run,
+        }
+    }
+
+    /// Probabilities that a random address does at least as well by each metric: leading zero
+    /// nibbles, total zero bytes and the longest nibble run.
+    pub fn probabilities(&self) -> [f64; 3] {
+        [
+            leading_zero_nibbles_probability(self.leading_zero_nibbles),
+            total_zero_bytes_probability(self.total_zero_bytes),
+            nibble_run_probability(self.longest_run.1),
+        ]
+    }
+
+    /// Upper bound of the probability that a random address does at least as well by every
+    /// metric at once. The metrics are correlated, so the bound is the rarest single metric
+    /// rather than their product.
+    pub fn combined_probability(&self) -> f64 {
+        self.probabilities().into_iter().fold(1.0, f64::min)
+    }
+
+    /// Report printed by the `rarity` subcommand.
+    pub fn report(&self) -> String {
+        let [leading, total, run] = self.probabilities();
+        format!(
+            "leading zeros: {} nibbles ({} bytes), {}\n\
+             total zero bytes: {}, {}\n\
+             longest nibble run: {} x {:x}, {}\n\
+             combined: {} (a conservative bound)",
+            self.leading_zero_nibbles,
+            self.leading_zero_nibbles / 2,
+            format_odds(leading),
+            self.total_zero_bytes,
+            format_odds(total),
+            self.longest_run.1,
+            self.longest_run.0,
+            format_odds(run),
+            format_odds(self.combined_probability()),
+        )
+    }
+}
+
+/// `probability` as "rarer than 1 in N random addresses".
+pub fn format_odds(probability: f64) -> String {
+    let odds = 1.0 / probability;
+    if odds < 1.0 + 1e-9 {
+        return "as common as any random address".to_string();
+    }
+    let odds = match odds {
+        odds if odds < 100.0 => format!("{:.2}", odds),
+        odds if odds < 1e9 => format!("{:.0}", odds),
+        odds => format!("{:.2e}", odds),
+    };
+    format!("rarer than 1 in {} random addresses", odds)
+}
+
+#[cfg(feature = "miner")]
+/// Throughput of the mining kernel measured by the `bench` subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    /// Name of the adapter benchmarked.
+    pub adapter: String,
+    /// Nonces dispatched per cycle.
+    pub work_size: u32,
+    pub attempts: u64,
+    pub elapsed: Duration,
+}
+
+#[cfg(feature = "miner")]
+impl Benchmark {
+    /// Attempts per second.
+    pub fn rate(&self) -> f64 {
+        crate::hash_rate(self.attempts, self.elapsed)
+    }
+
+    /// Report printed by the `bench` subcommand, with the expected time to find an address
+    /// starting with `leading` zero bytes at the measured rate.
+    pub fn report(&self, leading: u8) -> String {
+        let expected_attempts = 256f64.powi(leading as i32);
+        let expected = match expected_duration(expected_attempts, self.rate()) {
+            Some(duration) => crate::schedule::format_duration(duration),
+            None => "unknown".to_string(),
+        };
+        format!(
+            "{} attempts in {:.1}s on {}, {} nonces per cycle\n\
+             hash rate: {:.2} MH/s\n\
+             expected time to {} leading zero bytes: {} ({:.3e} attempts)",
+            self.attempts,
+            self.elapsed.as_secs_f64(),
+            self.adapter,
+            self.work_size,
+            self.rate() / 1e6,
+            leading,
+            expected,
+            expected_attempts,
+        )
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Service manager messages sent in `--supervised` mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notification {
+    Ready,
+    Watchdog,
+    Stopping,
+}
+
+/// Destination for service manager notifications. Wraps sd_notify so the keepalive logic can be
+/// exercised without systemd.
+pub trait Notifier: Send {
+    fn notify(&mut self, notification: Notification) -> Result<(), String>;
+}
+
+#[cfg(feature = "systemd")]
+struct SystemdNotifier;
+
+#[cfg(feature = "systemd")]
+impl Notifier for SystemdNotifier {
+    fn notify(&mut self, notification: Notification) -> Result<(), String> {
+        let state = match notification {
+            Notification::Ready => sd_notify::NotifyState::Ready,
+            Notification::Watchdog => sd_notify::NotifyState::Watchdog,
+            Notification::Stopping => sd_notify::NotifyState::Stopping,
+        };
+        sd_notify::notify(false, &[state]).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(feature = "systemd"))]
+struct NoopNotifier;
+
+#[cfg(not(feature = "systemd"))]
+impl Notifier for NoopNotifier {
+    fn notify(&mut self, _notification: Notification) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// sd_notify when built with the `systemd` feature, otherwise a notifier that does nothing.
+/// Outside of a systemd unit sd_notify is a no-op as well.
+pub fn system_notifier() -> Box<dyn Notifier> {
+    #[cfg(feature = "systemd")]
+    {
+        Box::new(SystemdNotifier)
+    }
+
+    #[cfg(not(feature = "systemd"))]
+    {
+        Box::new(NoopNotifier)
+    }
+}
+
+/// Decides whether the miner still makes progress. Keepalives stop once the attempt counter
+/// has not moved for `stall_timeout`, so the service manager restarts a hung miner.
+pub struct Watchdog {
+    stall_timeout: Duration,
+    last_attempts: u64,
+    last_progress: Instant,
+}
+
+impl Watchdog {
+    pub fn new(stall_timeout: Duration, now: Instant) -> Self {
+        Self {
+            stall_timeout,
+            last_attempts: 0,
+            last_progress: now,
+        }
+    }
+
+    /// Record the attempt counter at `now` and return whether a keepalive should be sent.
+    pub fn check(&mut self, attempts: u64, now: Instant) -> bool {
+        if attempts != self.last_attempts {
+            self.last_attempts = attempts;
+            self.last_progress = now;
+        }
+
+        now.saturating_duration_since(self.last_progress) < self.stall_timeout
+    }
+
+    /// Count a deliberate pause at `now` as progress.
+    pub fn rest(&mut self, now: Instant) {
+        self.last_progress = now;
+    }
+}
+
+/// One keepalive round: ping `notifier` unless `watchdog` considers the miner stalled.
+pub fn keepalive_tick(
+    watchdog: &mut Watchdog,
+    notifier: &mut dyn Notifier,
+    attempts: u64,
+    now: Instant,
+) -> Result<bool, String> {
+    if !watchdog.check(attempts, now) {
+        return Ok(false);
+    }
+
+    notifier.notify(Notification::Watchdog)?;
+    Ok(true)
+}
+
+/// Ping `notifier` every `interval` on a background thread while `attempts` keeps moving or
+/// `paused` is set, until `stop` is set.
+pub fn spawn_keepalive(
+    mut notifier: Box<dyn Notifier>,
+    attempts: Arc<AtomicU64>,
+    interval: Duration,
+    stall_timeout: Duration,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut watchdog = Watchdog::new(stall_timeout, Instant::now());
+        while !stop.load(Ordering::Relaxed) {
+            if paused.load(Ordering::Relaxed) {
+                watchdog.rest(Instant::now());
+            }
+            let attempts = attempts.load(Ordering::Relaxed);
+            if let Err(e) =
+                keepalive_tick(&mut watchdog, notifier.as_mut(), attempts, Instant::now())
+            {
+                eprintln!("Watchdog notification failed: {}", e);
+            }
+            thread::sleep(interval);
+        }
+    })
+}
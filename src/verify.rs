@@ -0,0 +1,126 @@
+use std::str::FromStr;
+
+use alloy_primitives::{hex, Address};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute_create2_address, compute_create3_address, parse_init_code_hash, parse_salt,
+    CreateVariant, FoundSalt, RewardVariant, SaltVariant, CREATEX_ADDRESS,
+};
+
+/// Where salts deploy, the options of the `verify` subcommand as JSON:
+/// `{"factory": "0x…", "caller": "0x…", "chain_id": 8453, "init_code_hash": "0x…"}`. Every
+/// field is optional, the factory defaulting to [`CREATEX_ADDRESS`] and deployments without
+/// an init code hash using CREATE3.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Deployment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_code_hash: Option<String>,
+}
+
+/// What a salt deploys to, in the renderings of `jsonl` output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Derivation {
+    pub salt_variant: String,
+    pub create_variant: String,
+    /// The checksummed factory.
+    pub factory: String,
+    /// The salt CreateX deploys with after its `_guard` step.
+    pub guarded_salt: String,
+    /// The checksummed address.
+    pub address: String,
+}
+
+fn parse_address(address: &str, what: &str) -> Result<[u8; 20], String> {
+    Address::from_str(address)
+        .map(|address| address.0 .0)
+        .map_err(|_| format!("{} {} is not a valid address", what, address))
+}
+
+impl Deployment {
+    pub fn create_variant(&self) -> Result<CreateVariant, String> {
+        match &self.init_code_hash {
+            Some(init_code_hash) => {
+                let init_code_hash = init_code_hash.strip_prefix("0x").unwrap_or(init_code_hash);
+                Ok(CreateVariant::Create2 {
+                    init_code_hash: parse_init_code_hash(init_code_hash)
+                        .map_err(|e| e.to_string())?,
+                })
+            }
+            None => Ok(CreateVariant::Create3),
+        }
+    }
+
+    /// Derive the address `salt`, in any rendering [`parse_salt`] reads, deploys to. The salt
+    /// variant is read off the salt as CreateX does, see [`SaltVariant::of_salt`].
+    pub fn derive(&self, salt: &str) -> Result<Derivation, String> {
+        let salt = parse_salt(salt)?;
+        let factory = match &self.factory {
+            Some(factory) => parse_address(factory, "factory address")?,
+            None => CREATEX_ADDRESS,
+        };
+        let caller = self
+            .caller
+            .as_deref()
+            .map(|caller| parse_address(caller, "caller address"))
+            .transpose()?;
+        let salt_variant = SaltVariant::of_salt(&salt, caller.as_ref(), self.chain_id)?;
+        let create_variant = self.create_variant()?;
+        let (guarded_salt, address) = match &create_variant {
+            CreateVariant::Create2 { init_code_hash } => {
+                compute_create2_address(&factory, &salt_variant, &salt, init_code_hash)
+            }
+            CreateVariant::Create3 => compute_create3_address(&factory, &salt_variant, &salt),
+        };
+
+        Ok(Derivation {
+            salt_variant: salt_variant.to_string(),
+            create_variant: create_variant.to_string(),
+            factory: Address::from(factory).to_checksum(None),
+            guarded_salt: format!("0x{}", hex::encode(guarded_salt)),
+            address: Address::from(address).to_checksum(None),
+        })
+    }
+}
+
+/// Check a result of `jsonl` output against `deployment`: its salt must deploy to its address
+/// from its factory with its create variant, and the address must satisfy the reward it was
+/// mined for.
+pub fn verify_record(record: &FoundSalt, deployment: &Deployment) -> Result<Derivation, String> {
+    let derivation = deployment.derive(&record.salt)?;
+    if !record.factory.eq_ignore_ascii_case(&derivation.factory) {
+        return Err(format!(
+            "the result was mined for factory {}, not {}",
+            record.factory, derivation.factory
+        ));
+    }
+    if record.create_variant != derivation.create_variant {
+        return Err(format!(
+            "the result was mined for {}, not {}",
+            record.create_variant, derivation.create_variant
+        ));
+    }
+    if record.address != derivation.address {
+        return Err(format!(
+            "the salt deploys to another address than recorded\n- {} (recorded)\n+ {} (computed)",
+            record.address, derivation.address
+        ));
+    }
+
+    let reward = RewardVariant::from_str(&record.pattern)?;
+    let address = parse_address(&derivation.address, "address")?;
+    if !reward.matches(&address) {
+        return Err(format!(
+            "{} does not satisfy {}",
+            derivation.address, record.pattern
+        ));
+    }
+    Ok(derivation)
+}
@@ -0,0 +1,211 @@
+use std::{fmt, str::FromStr};
+
+use crate::{factory::FactoryMismatch, probability::expected_attempts, RewardVariant, Seed};
+
+/// Searches expected to need more attempts than this (~3 days at 1 GH/s) are flagged.
+pub const EXTREME_DIFFICULTY_ATTEMPTS: f64 = (1u64 << 48) as f64;
+
+/// Stable identifier of a warning. Codes are never renumbered or reused, so they can be relied
+/// on in `--allow` lists of CI jobs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningCode {
+    LowEntropy,
+    ExtremeDifficulty,
+    SoftwareAdapter,
+    DroppedMatches,
+    LowDiskSpace,
+    UnknownFactory,
+}
+
+impl WarningCode {
+    pub const ALL: [WarningCode; 6] = [
+        WarningCode::LowEntropy,
+        WarningCode::ExtremeDifficulty,
+        WarningCode::SoftwareAdapter,
+        WarningCode::DroppedMatches,
+        WarningCode::LowDiskSpace,
+        WarningCode::UnknownFactory,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            WarningCode::LowEntropy => "W001",
+            WarningCode::ExtremeDifficulty => "W002",
+            WarningCode::SoftwareAdapter => "W003",
+            WarningCode::DroppedMatches => "W004",
+            WarningCode::LowDiskSpace => "W005",
+            WarningCode::UnknownFactory => "W006",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningCode::LowEntropy => "low-entropy",
+            WarningCode::ExtremeDifficulty => "extreme-difficulty",
+            WarningCode::SoftwareAdapter => "software-adapter",
+            WarningCode::DroppedMatches => "dropped-matches",
+            WarningCode::LowDiskSpace => "low-disk-space",
+            WarningCode::UnknownFactory => "unknown-factory",
+        }
+    }
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for WarningCode {
+    type Err = String;
+
+    /// Accepts either the code (`W001`) or the name (`low-entropy`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WarningCode::ALL
+            .into_iter()
+            .find(|code| code.code().eq_ignore_ascii_case(s) || code.name() == s)
+            .ok_or_else(|| format!("unknown warning code {}", s))
+    }
+}
+
+/// A condition worth reporting that does not prevent mining.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// The `--seed` is small enough to be picked by others, whose runs search the same salts.
+    LowEntropy,
+    /// The reward is unlikely to be met in a reasonable time.
+    ExtremeDifficulty { expected_attempts: f64 },
+    /// The adapter is a CPU implementation rather than a GPU.
+    SoftwareAdapter { name: String },
+    /// Matches were discarded under `--backpressure drop`.
+    DroppedMatches { count: u64 },
+    /// The output filesystem is close to full.
+    LowDiskSpace { available: u64 },
+    /// `--rpc-url` shows the factory does not run the CreateX code.
+    UnknownFactory { mismatch: FactoryMismatch },
+}
+
+impl Warning {
+    pub fn code(&self) -> WarningCode {
+        match self {
+            Warning::LowEntropy => WarningCode::LowEntropy,
+            Warning::ExtremeDifficulty { .. } => WarningCode::ExtremeDifficulty,
+            Warning::SoftwareAdapter { .. } => WarningCode::SoftwareAdapter,
+            Warning::DroppedMatches { .. } => WarningCode::DroppedMatches,
+            Warning::LowDiskSpace { .. } => WarningCode::LowDiskSpace,
+            Warning::UnknownFactory { .. } => WarningCode::UnknownFactory,
+        }
+    }
+
+    /// The extreme-difficulty warning for `reward`, if it applies.
+    pub fn for_reward(reward: &RewardVariant) -> Option<Warning> {
+        let expected_attempts = expected_attempts(reward);
+        (expected_attempts > EXTREME_DIFFICULTY_ATTEMPTS)
+            .then_some(Warning::ExtremeDifficulty { expected_attempts })
+    }
+
+    /// The low-entropy warning for a user-supplied `seed`, if it fits in 64 bits as hand-picked
+    /// seeds such as 1 or 42 do. Drawn seeds fill all 32 bytes.
+    pub fn for_seed(seed: &Seed) -> Option<Warning> {
+        seed.0[..24].iter().all(|byte| *byte == 0).then_some(Warning::LowEntropy)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning[{}] {}: ", self.code(), self.code().name())?;
+        match self {
+            Warning::LowEntropy => write!(
+                f,
+                "the --seed is small enough to be guessed, runs of others with the same seed will find the same salts (leave out --seed to draw a random one)"
+            ),
+            Warning::ExtremeDifficulty { expected_attempts } => write!(
+                f,
+                "about 2^{:.0} attempts are expected per match",
+                expected_attempts.log2()
+            ),
+            Warning::SoftwareAdapter { name } => write!(
+                f,
+                "{} is a software adapter, expect very low hash rates",
+                name
+            ),
+            Warning::DroppedMatches { count } => write!(
+                f,
+                "dropped {} matches while the output writer was behind",
+                count
+            ),
+            Warning::LowDiskSpace { available } => write!(
+                f,
+                "only {} MiB left on the output filesystem",
+                available >> 20
+            ),
+            Warning::UnknownFactory { mismatch } => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+/// Collects the warnings of a run, applying `--allow` and `--deny-warnings`.
+#[derive(Default)]
+pub struct Warnings {
+    allowed: Vec<WarningCode>,
+    deny: bool,
+    emitted: Vec<Warning>,
+}
+
+impl Warnings {
+    pub fn new(allowed: Vec<WarningCode>, deny: bool) -> Self {
+        Self {
+            allowed,
+            deny,
+            emitted: vec![],
+        }
+    }
+
+    /// Record `warning` unless its code is allowed. Returns whether it was recorded, in which
+    /// case the caller should print it.
+    pub fn emit(&mut self, warning: Warning) -> bool {
+        if self.allowed.contains(&warning.code()) {
+            return false;
+        }
+
+        self.emitted.push(warning);
+        true
+    }
+
+    pub fn emitted(&self) -> &[Warning] {
+        &self.emitted
+    }
+
+    /// Fail if `--deny-warnings` is set and any warning was recorded. Allowed warnings never
+    /// count, so `--allow` takes precedence over `--deny-warnings`.
+    pub fn check_denied(&self) -> Result<(), String> {
+        if !self.deny || self.emitted.is_empty() {
+            return Ok(());
+        }
+
+        let mut codes: Vec<WarningCode> = self.emitted.iter().map(Warning::code).collect();
+        codes.sort();
+        codes.dedup();
+        let codes: Vec<&str> = codes.iter().map(WarningCode::code).collect();
+
+        Err(format!(
+            "{} warnings denied by --deny-warnings ({}), pass --allow <code> to accept them",
+            self.emitted.len(),
+            codes.join(", ")
+        ))
+    }
+
+    /// Summary of every recorded warning for the final report.
+    pub fn report(&self) -> Option<String> {
+        if self.emitted.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<String> = self.emitted.iter().map(ToString::to_string).collect();
+        Some(format!(
+            "{} warnings during this run:\n{}",
+            self.emitted.len(),
+            lines.join("\n")
+        ))
+    }
+}
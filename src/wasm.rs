@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    probability::Difficulty,
+    verify::{self, Deployment},
+    FoundSalt, RewardVariant,
+};
+
+fn from_json<'a, T: serde::Deserialize<'a>>(json: &'a str, what: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("invalid {}: {}", what, e))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("results serialize")
+}
+
+/// The [`Derivation`](verify::Derivation) of `salt` for the [`Deployment`] in `config_json`.
+#[wasm_bindgen(js_name = deriveAddress)]
+pub fn derive_address(config_json: &str, salt: &str) -> Result<String, String> {
+    let deployment: Deployment = from_json(config_json, "deployment")?;
+    deployment.derive(salt).map(|derivation| to_json(&derivation))
+}
+
+/// The [`Difficulty`] of the reward in `reward_json`, a JSON string of its canonical form such
+/// as `"leading-zeros(4)"`.
+#[wasm_bindgen(js_name = estimateDifficulty)]
+pub fn estimate_difficulty(reward_json: &str) -> Result<String, String> {
+    let reward: String = from_json(reward_json, "reward")?;
+    let reward: RewardVariant = reward.parse()?;
+    reward.validate().map_err(|e| e.to_string())?;
+    Ok(to_json(&Difficulty::of(&reward)))
+}
+
+/// Check the `jsonl` result in `record_json` against the [`Deployment`] in `config_json`, see
+/// [`verify::verify_record`]. The derivation of the salt when it checks out.
+#[wasm_bindgen(js_name = verifyRecord)]
+pub fn verify_record(record_json: &str, config_json: &str) -> Result<String, String> {
+    let record = FoundSalt::parse(record_json).map_err(|e| format!("invalid record: {}", e))?;
+    let deployment: Deployment = from_json(config_json, "deployment")?;
+    verify::verify_record(&record, &deployment).map(|derivation| to_json(&derivation))
+}
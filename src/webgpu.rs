@@ -1,339 +1,633 @@
 use std::io::Write;
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
 use std::time::{Duration, Instant};
 use wgpu::util::DeviceExt;
-use hex;
 
-use createxcrunch::{Config, RewardVariant};
+use crate::{
+    batch::RunSummary,
+    bundle, campaign,
+    bus::{EventBus, FoundResult, MinerEvent, StatusTick, Subscriber, Subscription},
+    checkpoint::{Checkpoint, CheckpointSchedule, PendingCheckpoint},
+    factory, forensics,
+    http::DefaultTransport,
+    index::SaltIndex,
+    interrupt,
+    pattern_file::{PatternWatcher, Reload},
+    plan::{self, MiningPlan, StartupPhase, StartupTimings},
+    params::{self, ParamHashes},
+    presets,
+    clipboard, cycle_stats::{self, CycleStats}, diagnostics::{self, GpuBackend}, probability::{self, expected_attempts},
+    events::{self, DoneEvent, Event, FoundEvent, StartEvent, StartupPhaseTime, StatusEvent, StopReason}, has_leading_zero_nibbles, has_symmetric_ends, hash_rate, mix_session_seed,
+    redact, replay::{self, Candidate, CycleRecord}, reserved,
+    ranges::SearchCursor,
+    rpc::RpcClient,
+    schemas::Artifact,
+    scan::ScanOrder,
+    schedule::{self, SystemClock},
+    safe, seed_fingerprint, seed_scan_seed, seed_start_nonce, selftest, stats,
+    supervise::{self, Notification},
+    warnings::{Warning, Warnings},
+    selftest::SelfTestMode,
+    writer::{self, DuplicatePolicy, RecentAddresses, ResultWriter, SplitOutput},
+    check_embeddable, check_no_file, check_csv_header, check_output_format, checksum_matches, csv_header, coarse_kernel_config, format_address, output_file, coarse_prefix, derivation_config, kernel_constants, kernel_source, validate_workgroup_size, BatchGeometry, Config, DualSalt, FoundSalt, MatchPlacement, OutputFormat, PatternBytes, OutputOpened, RankedResults, RewardVariant,
+    Match, SaltVariant, SeedEntropy, ADDRESS_OFFSET, RESULT_WORDS,
+};
 use alloy_primitives::Address;
 
-// Number of bytes in the salt/nonce
-const SALT_SIZE: usize = 32;
-// Number of bytes in an Ethereum address
-const ADDRESS_SIZE: usize = 20;
+// How often the free space on the output filesystem is checked
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 // Output size from Keccak256 hash (same as in the shader)
 const KECCAK256_OUTPUT_SIZE: u32 = 8; // 8 u32 values (32 bytes)
 
-// Store the raw pattern for dynamic matching
-static mut RAW_PATTERN: Option<String> = None;
-
-// Aligned buffer for solutions
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct SolutionBuffer {
-    // Salt that produced the matching address
-    salt: [u32; 8], // 32 bytes as u32 array
-    // The resulting address (only need 20 bytes but use 32 for alignment)
-    address: [u32; 8], // 32 bytes as u32 array
+// println! of the progress of a run, to stderr when stdout holds the results of --no-file
+macro_rules! progress {
+    ($progress:expr, $($arg:tt)*) => {
+        match $progress {
+            Progress::Stdout => println!($($arg)*),
+            Progress::Stderr => eprintln!($($arg)*),
+            Progress::Hidden => {}
+        }
+    };
 }
 
-pub fn gpu(config: Config) -> Result<(), Box<dyn std::error::Error>> {
-    println!(
-        "Setting up WebGPU miner using Metal backend..."
-    );
+/// Where a run prints its progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Progress {
+    Stdout,
+    /// Below the results of a `--no-file` run.
+    Stderr,
+    /// Runs embedded with `mine` and the `--json` stream keep the human output off stdout.
+    Hidden,
+}
 
-    // Store the pattern string for dynamic matching
-    if let RewardVariant::Matching { pattern } = &config.reward {
-        // Store the pattern globally for use in check_eth_address_pattern
-        unsafe {
-            RAW_PATTERN = Some(pattern.to_string());
+impl Progress {
+    /// The terminal the status display redraws, none while progress is hidden.
+    fn term(self) -> Option<console::Term> {
+        match self {
+            Progress::Stdout => Some(console::Term::stdout()),
+            Progress::Stderr => Some(console::Term::stderr()),
+            Progress::Hidden => None,
         }
     }
+}
 
-    // Clear output file first to remove old non-matching results
-    {
-        println!("Clearing previous output file at: {}", config.output);
-        let mut file = std::fs::File::create(&config.output)?;
-        
-        // Display the actual pattern we're looking for
-        if let RewardVariant::Matching { pattern } = &config.reward {
-            writeln!(file, "# Mining for addresses matching pattern {}", pattern)?;
-        } else {
-            writeln!(file, "# Mining for addresses matching pattern")?;
-        }
-        
-        writeln!(file, "# Format: salt -> ethereum_address")?;
-        writeln!(file, "# Started: {}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())?;
-        writeln!(file)?;
+/// Mine for `config`, writing results to the output file and progress to the terminal.
+pub fn gpu(config: Config) -> Result<RunSummary, Box<dyn std::error::Error>> {
+    run(config, None, EventBus::new())
+}
+
+/// Mine for `config`, handing every result to `on_match` instead when given. Runs with a
+/// callback are embedded: they open no output file, print nothing to stdout and leave Ctrl+C
+/// to the caller. The events of the run are published to the subscribers of `bus`, which
+/// the output writer and status display join.
+pub(crate) fn run(
+    config: Config,
+    mut on_match: Option<&mut dyn FnMut(Match)>,
+    mut bus: EventBus,
+) -> Result<RunSummary, Box<dyn std::error::Error>> {
+    let embedded = on_match.is_some();
+    if embedded {
+        check_embeddable(&config)?;
+    }
+    if config.no_file {
+        check_no_file(&config)?;
+    }
+    params::check_redactable(&config)?;
+    let progress = if embedded || config.json_events {
+        Progress::Hidden
+    } else if config.no_file {
+        Progress::Stderr
+    } else {
+        Progress::Stdout
+    };
+    match config.backend {
+        Some(backend) => progress!(progress, "Setting up WebGPU miner on the {} backend...", backend),
+        None => progress!(progress, "Setting up WebGPU miner..."),
     }
 
     // Track found addresses
     let mut found: u64 = 0;
-    let found_list: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let mut ranked = config.sort_on_exit.map(RankedResults::new);
+    let mut run_results = vec![];
     
-    // Set up a controller for terminal output
-    let term = console::Term::stdout();
+    // Set up a controller for terminal output, --quiet leaves the terminal alone
+    let term = progress.term().filter(|_| !config.quiet);
     
     // Start time tracking
     let start_time = Instant::now();
+    let started_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
     let mut last_status_time = Instant::now();
     let mut total_cycles: u64 = 0;
     
-    // Initialize WebGPU
-    let instance = wgpu::Instance::default();
-    
-    // Find an adapter (GPU)
-    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-        power_preference: wgpu::PowerPreference::HighPerformance,
-        compatible_surface: None,
-        force_fallback_adapter: false,
-    })).ok_or("Failed to find a suitable GPU adapter")?;
-    
-    println!("Using GPU: {}", adapter.get_info().name);
-    
-    // Create device and queue
-    let (device, queue) = pollster::block_on(adapter.request_device(
-        &wgpu::DeviceDescriptor {
-            label: Some("CreateXCrunch Device"),
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            memory_hints: wgpu::MemoryHints::default(),
-        },
-        None,
-    ))?;
-    
-    // Prepare shader
-    let shader_src = include_str!("kernels/keccak256.wgsl");
-    
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Keccak Shader"),
-        source: wgpu::ShaderSource::Wgsl(shader_src.into()),
-    });
-    
-    // Determine optimal work size and workgroup size based on GPU capabilities
-    let workgroup_size = 256; // This should match the workgroup_size in the shader
-    
-    // Reduce work size to stay within buffer limits (max 268,435,456 bytes)
-    // Max buffer size is 268,435,456 bytes. Each hash is 32 bytes, so max entries = 8,388,608
-    // Use a much smaller value to be safe
-    let work_size: u32 = 1_000_000; // 1M nonces
-    let workgroups = (work_size + workgroup_size - 1) / workgroup_size;
-    
-    println!("Using work batch size of {} nonces per iteration", work_size);
-    println!("Using {} workgroups of {} threads each", workgroups, workgroup_size);
-    
-    // Create buffers
-    // 1. Input buffer for messages (salt data)
-    let message_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Message Buffer"),
-        contents: bytemuck::cast_slice(&[0u32; 8]), // 32 bytes for salt
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
+    // Every phase of the setup is timed, and --fast-start leaves out the optional ones
+    let phases = plan::startup_phases(&config, embedded);
+    let mut startup = StartupTimings::default();
+
+    // Pick the adapter (GPU) by its index in `list-gpus`
+    let adapter = startup.time(StartupPhase::Adapter, || select_adapter(&config))?;
     
-    // 2. Number of messages
-    let num_messages_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Num Messages Buffer"),
-        contents: bytemuck::cast_slice(&[work_size as u32]),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
+    let adapter_info = adapter.get_info();
+    progress!(progress, "Using GPU {}: {} ({:?})", config.gpu_device, adapter_info.name, adapter_info.backend);
+    if config.default_factory {
+        progress!(progress, "No --factory given, mining for the canonical CreateX deployment at {}", Address::from(config.factory_address).to_checksum(None));
+    }
+    // The worked example spells out the parameters --params-stdin keeps out of logs
+    if phases.contains(&StartupPhase::WorkedExample) {
+        let example = startup.time(StartupPhase::WorkedExample, || MiningPlan::new(&config).worked_example());
+        progress!(progress, "{}", example.render());
+    }
+
+    // Raise the startup warnings before committing the GPU to a long search
+    let mut warnings = Warnings::new(config.allow_warnings.clone(), config.deny_warnings);
+    if let Some(warning) = config.seed.as_ref().and_then(Warning::for_seed) {
+        emit_warning(&mut warnings, &bus, warning);
+    }
+    if let Some(warning) = Warning::for_reward(&config.reward) {
+        emit_warning(&mut warnings, &bus, warning);
+    }
+    if adapter_info.device_type == wgpu::DeviceType::Cpu {
+        emit_warning(
+            &mut warnings,
+            &bus,
+            Warning::SoftwareAdapter {
+                name: adapter_info.name.clone(),
+            },
+        );
+    }
+    let file_less = embedded || config.no_file;
+    let mut low_disk_space = phases.contains(&StartupPhase::DiskCheck)
+        && startup.time(StartupPhase::DiskCheck, || check_disk_space(&mut warnings, &bus, config.output, false));
+    if let Some(url) = config.rpc_url.filter(|_| phases.contains(&StartupPhase::FactoryCheck)) {
+        // Salts only deploy where expected if the factory runs the CreateX guard logic
+        let expected = presets::by_address(&config.factory_address).map_or(factory::CREATEX, |preset| preset.code);
+        let checked = startup.time(StartupPhase::FactoryCheck, || {
+            DefaultTransport::new(url).and_then(|transport| {
+                let mut rpc = RpcClient::new(transport);
+                if let Some((max_rps, burst)) = config.rpc_rate_limit {
+                    rpc = rpc.with_rate_limit(max_rps, burst);
+                }
+                factory::check_factory(&mut rpc, &config.factory_address, &expected)
+            })
+        });
+        match checked {
+            Ok(Ok(())) => progress!(progress, "Factory code verified as {}", expected.name),
+            Ok(Err(mismatch)) if config.require_known_factory => return Err(mismatch.to_string().into()),
+            Ok(Err(mismatch)) => emit_warning(&mut warnings, &bus, Warning::UnknownFactory { mismatch }),
+            Err(e) if config.require_known_factory => {
+                return Err(format!("could not check the factory: {}", e).into())
+            }
+            Err(e) => eprintln!("Could not check the factory: {}", e),
+        }
+    }
+    warnings.check_denied()?;
+
+    // Pick the starting nonce from the session seed of --seed, or a well-mixed one that is
+    // never printed in full
+    let (start_nonce, scan_seed) = match config.seed {
+        Some(seed) => {
+            progress!(progress, "Seed: {}, pass it to --seed to repeat this search", seed);
+            let seed = seed.session_seed();
+            (seed_start_nonce(&seed), seed_scan_seed(&seed))
+        }
+        None if config.seed_entropy => {
+            let seed = mix_session_seed(&SeedEntropy::gather(&adapter_info.name));
+            progress!(progress, "Session seed fingerprint: {}", seed_fingerprint(&seed));
+            (seed_start_nonce(&seed), seed_scan_seed(&seed))
+        }
+        None => (0, 0),
+    };
+
+    // With --supervised or --resume, continue from the checkpoint of a previous run if there is
+    // one
+    let checkpointing = config.supervised || config.resume;
+    let checkpoint_path = Checkpoint::path(config.output);
+    let resumed = match checkpointing {
+        true => startup.time(StartupPhase::Checkpoint, || Checkpoint::load(std::path::Path::new(&checkpoint_path)))?,
+        false => None,
+    };
+    let (start_nonce, scan_seed) = match &resumed {
+        Some(checkpoint) if checkpoint.scan_order != config.scan_order => {
+            return Err(format!(
+                "checkpoint {} was written with --scan-order {}",
+                checkpoint_path, checkpoint.scan_order
+            )
+            .into());
+        }
+        Some(Checkpoint { fingerprint: Some(fingerprint), .. }) if *fingerprint != config.fingerprint() => {
+            return Err(format!(
+                "checkpoint {} was written for another search and can't be resumed: it searched {}, this run {}. Mine into another --output, or delete the checkpoint to start this search over",
+                checkpoint_path, fingerprint, config.fingerprint()
+            )
+            .into());
+        }
+        Some(checkpoint) => {
+            progress!(
+                progress,
+                "Resuming from checkpoint {} at scan index {}, after {} attempts and {} results",
+                checkpoint_path,
+                checkpoint.next_index,
+                checkpoint.attempts,
+                checkpoint.found
+            );
+            (checkpoint.next_index, checkpoint.scan_seed)
+        }
+        None => (start_nonce, scan_seed),
+    };
+    // A pool lease fixes the nonces to mine
+    let start_nonce = config.lease.as_ref().map_or(start_nonce, |lease| lease.start);
+    if config.scan_order != ScanOrder::Linear {
+        progress!(progress, "Scanning in {} order with seed 0x{:016x}", config.scan_order, scan_seed);
+    }
+
+    // Keep the results of earlier runs unless --overwrite clears them. Resumed runs and later
+    // pool leases always append to their own results. Safe batches are rewritten as a whole,
+    // runs embedded with `mine` hand their results to the callback and --no-file runs print them
+    let overwrite = config.overwrite && resumed.is_none() && !config.append_output;
+    let output = match config.format {
+        _ if file_less => None,
+        OutputFormat::SafeTx => None,
+        _ => startup.time(StartupPhase::OutputFile, || {
+            if !overwrite {
+                check_output_format(std::path::Path::new(config.output), config.format)?;
+                if config.format == OutputFormat::Csv {
+                    check_csv_header(std::path::Path::new(config.output), &csv_header(config.emit_salt_format))?;
+                }
+            }
+            output_file(config.output, overwrite).map(Some)
+        })?,
+    };
+    if let Some((file, opened)) = &output {
+        match opened {
+            OutputOpened::Created => progress!(progress, "Writing results to new output file at: {}", config.output),
+            OutputOpened::Appended => progress!(progress, "Appending results to existing output file at: {}", config.output),
+            OutputOpened::Overwritten => progress!(progress, "Overwrote previous output file at: {}", config.output),
+        }
+        if config.redact_salts {
+            redact::restrict_permissions(std::path::Path::new(config.output))?;
+        }
+
+        // Only new files get the header. Comment headers would make JSON output unparseable
+        let mut file = file;
+        if *opened != OutputOpened::Appended && config.format == OutputFormat::Text {
+            // Display what we're looking for
+            writeln!(
+                file,
+                "# Mining {} for {} with {} salts",
+                params::create_variant(&config), config.reward, params::salt_variant(&config)
+            )?;
+            if config.redact_params {
+                writeln!(file, "{}", ParamHashes::of(&config).to_header())?;
+            }
+            if let Some(campaign) = config.campaign {
+                writeln!(file, "# Campaign: {}", campaign)?;
+            }
+
+            match config.emit_salt_format {
+                Some(salt_format) => writeln!(file, "# Format: salt salt_{} -> ethereum_address", salt_format)?,
+                None => writeln!(file, "# Format: salt -> ethereum_address")?,
+            }
+            writeln!(file, "{}", config.scan_order.header(scan_seed))?;
+            writeln!(file, "# Started: {}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())?;
+            writeln!(file)?;
+        }
+        // CSV files get a single header row, so appending across runs keeps them valid
+        if *opened != OutputOpened::Appended && config.format == OutputFormat::Csv {
+            writeln!(file, "{}", csv_header(config.emit_salt_format))?;
+        }
+    }
+
+    // Raised by the writer once addresses found again with other salts point at a derivation bug
+    let duplicate_anomaly = Arc::new(AtomicBool::new(false));
+    // Checkpoints are saved by the writer, after the results found before them
+    let pending_checkpoint = PendingCheckpoint::default();
+    let checkpoint_saver = CheckpointSaver { pending: pending_checkpoint.clone(), path: checkpoint_path.clone() };
+    // The sinks of the run subscribe to the events of the mining loop, each on a thread of its
+    // own, so a slow disk or terminal doesn't stall the GPU. The output writer gets every
+    // result, unless --backpressure drop lets it fall behind
+    let results = Subscription {
+        capacity: config.max_pending_matches,
+        policy: config.backpressure,
+        filter: MinerEvent::is_found,
+    };
+    let writer = match (config.format, config.safe_chain_id) {
+        _ if embedded => None,
+        _ if config.no_file => Some(bus.subscribe_with("stdout writer", StdoutWriter::new(&config), results)),
+        (OutputFormat::SafeTx, Some(chain_id)) => {
+            let writer = SafeBatchWriter::new(chain_id, &config, checkpoint_saver);
+            Some(bus.subscribe_with("output writer", writer, results))
+        }
+        _ => {
+            // Locked by output_file until the writer is done with it
+            let (file, _) = output.ok_or_else(|| format!("could not open {}", config.output))?;
+            let writer = startup.time(StartupPhase::OutputFile, || {
+                OutputWriter::new(file, &config, checkpoint_saver, duplicate_anomaly.clone(), progress)
+            })?;
+            Some(bus.subscribe_with("output writer", writer, results))
+        }
+    };
     
-    // 3. Message sizes
-    let message_sizes_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Message Sizes Buffer"),
-        contents: bytemuck::cast_slice(&[8u32, 8u32]), // Input length and stride
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
+    let (device, queue, required_limits) = startup.time(StartupPhase::Device, || open_device(&adapter, &config))?;
+
+    // Self-test output goes into bug reports, so it carries the environment too
+    let self_test = config.self_test.filter(|_| phases.contains(&StartupPhase::SelfTest));
+    if config.debug_env || self_test.is_some() {
+        progress!(progress,
+            "{}",
+            diagnostics::debug_env_report(
+                &adapter_info,
+                &required_limits,
+                &device.limits(),
+                device.features()
+            )
+        );
+    }
+
+    if let Some(mode) = self_test {
+        startup.time(StartupPhase::SelfTest, || selftest::run(&device, &queue, mode))?;
+        progress!(progress, "Kernel self-test passed");
+    }
     
-    // 4. Output buffer for hashes
-    let hash_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Hash Buffer"),
-        // Each result is (2 u32 for nonce + 8 u32 for hash) * 4 bytes per u32
-        size: (work_size as u64) * ((2 + KECCAK256_OUTPUT_SIZE as u64) * 4),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-        mapped_at_creation: false,
-    });
+    // The kernel's WORKGROUP_SIZE is set from the same config, see `kernel_constants`
+    let workgroup_size = config.workgroup_size;
     
-    // 5. Log buffer
-    let log_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Log Buffer"),
-        size: 64 * 4, // 64 u32 values
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
+    let geometry = startup.time(StartupPhase::BatchGeometry, || batch_geometry(&device, &config, progress))?;
+    let work_size = geometry.work_size;
+    let workgroups = geometry.workgroups(work_size);
     
-    // 6. Solutions buffer (for staging on CPU)
-    let solution_staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Solution Staging Buffer"),
-        size: std::mem::size_of::<SolutionBuffer>() as u64,
-        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
+    progress!(progress, "Using work batch size of {} nonces per iteration", work_size);
+    progress!(progress, "Using {} workgroups of {} threads each", workgroups, workgroup_size);
+    if let Some(cost) = &config.cost {
+        progress!(progress, "{}", cost.banner());
+    }
     
-    // Create a new CPU-side validation buffer
-    let validation_buffer_size = work_size as usize * (2 + KECCAK256_OUTPUT_SIZE as usize) * 4; // Size in bytes
-    let cpu_validation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("CPU Validation Buffer"),
-        size: validation_buffer_size as u64,
-        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
+    // Parse pattern from the user-provided pattern string
+    let mut reward = config.reward.clone();
+    let mut matching = MatchingSetup::new(&config, &reward);
+    matching.announce(progress, config.quiet);
+    let pipeline = startup.time(StartupPhase::Pipeline, || {
+        Pipeline::new(&device, &config, &geometry, scan_seed, &matching.pattern_config())
     });
+    progress!(progress, "{}", startup.report());
+    let validation_buffer_size = geometry.result_buffer_size();
+    let mut readback_saved: u64 = 0;
+    // Matches rejected by --avoid-reserved, not counted as results
+    let mut reserved_rejected: u64 = 0;
+    // Results of each pattern of an any-pattern run, which --limit applies to one by one
+    let mut pattern_found = vec![0u64; matching.alternatives.len()];
     
-    // Parse pattern from the user-provided pattern string
-    let (pattern_value, pattern_flags, pattern_length) = match &config.reward {
-        RewardVariant::Matching { pattern } => {
-            parse_pattern(pattern)
-        },
-        // For backward compatibility, convert old reward types
-        RewardVariant::LeadingZeros { zeros_threshold } => {
-            // Convert leading zeros to a single value
-            (0u32, 4u32, (*zeros_threshold) as u32) // Flag 4 for legacy leading zeros
-        },
-        RewardVariant::TotalZeros { zeros_threshold } => {
-            // Convert total zeros to a single value
-            (0u32, 5u32, (*zeros_threshold) as u32) // Flag 5 for legacy total zeros
-        },
-        RewardVariant::LeadingAndTotalZeros { leading_zeros_threshold, total_zeros_threshold } => {
-            // Store leading zeros in pattern_value, total zeros in flags higher bits
-            ((*leading_zeros_threshold) as u32, 6u32, (*total_zeros_threshold) as u32)
-        },
-        RewardVariant::LeadingOrTotalZeros { leading_zeros_threshold, total_zeros_threshold } => {
-            // Just use leading zeros for simplicity
-            ((*leading_zeros_threshold) as u32, 7u32, (*total_zeros_threshold) as u32)
-        },
+    // With --dual-salt the kernel also matches the random framing of each salt and the CPU
+    // confirms both addresses of its results, see `DualSalt`
+    let dual_salt_chain_id = match &config.salt_variant {
+        SaltVariant::Crosschain { chain_id } if config.dual_salt => Some(chain_id),
+        _ => None,
     };
 
-    // Set up the pattern configuration buffer
-    let pattern_config_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Pattern Config Buffer"),
-        contents: bytemuck::cast_slice(&[
-            pattern_value,    // Main pattern value (prefix byte or zero count)
-            pattern_flags,    // Pattern type flags (1=leading, 2=trailing, 3=both)
-            pattern_length,   // Length of pattern to match
-            0u32,             // Reserved for future use
-        ]),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
-    
-    // Create bind group layout
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("Bind Group Layout"),
-        entries: &[
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: false },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 2,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 3,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: false },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 4,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: false },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 5,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-        ],
-    });
-    
-    // Create bind group
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("Bind Group"),
-        layout: &bind_group_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: message_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: num_messages_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 2,
-                resource: message_sizes_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 3,
-                resource: hash_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 4,
-                resource: log_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 5,
-                resource: pattern_config_buffer.as_entire_binding(),
-            },
-        ],
-    });
-    
-    // Create pipeline layout
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Pipeline Layout"),
-        bind_group_layouts: &[&bind_group_layout],
-        push_constant_ranges: &[],
-    });
-    
-    // Create compute pipeline
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: Some("Compute Pipeline"),
-        layout: Some(&pipeline_layout),
-        module: &shader,
-        entry_point: Some("main"),
-        compilation_options: Default::default(),
-        cache: None,
-    });
-    
+    // One row per cycle for `createxcrunch stats`, appended to the rows of earlier runs
+    let mut stats_file = match config.stats_file {
+        Some(path) => Some(cycle_stats::open(path).map_err(|e| format!("Couldn't open stats file {}: {}", path, e))?),
+        None => None,
+    };
+
+    // --record-cycles keeps a replay record of every cycle with results in this directory
+    if let Some(dir) = config.record_cycles {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Couldn't create replay directory {}: {}", dir, e))?;
+    }
+
+    let match_writer = MatchWriter { config: &config };
+
     // Main loop
-    let mut nonce: u64 = 0;
-    let should_exit = false;
+    let mut cursor = SearchCursor::new(start_nonce);
+    if !embedded {
+        interrupt::install();
+    }
     
-    println!("Mining started. Press Ctrl+C to exit.");
+    progress!(progress, "Mining started. Press Ctrl+C to exit.");
     
-    'outer: loop {
+    // Tell the service manager we're up and keep its watchdog fed while attempts progress
+    let keepalive_stop = Arc::new(AtomicBool::new(false));
+    let paused = Arc::new(AtomicBool::new(false));
+    let shared_attempts = Arc::new(AtomicU64::new(0));
+    let mut checkpoints = CheckpointSchedule::new(config.checkpoint_interval, config.checkpoint_attempts, Instant::now())
+        .every_cycles(config.checkpoint_cycles);
+    // Checkpoints count the attempts and results of the runs they resume too
+    let (resumed_attempts, resumed_found) = resumed.as_ref().map_or((0, 0), |checkpoint| (checkpoint.attempts, checkpoint.found));
+    let checkpoint = |cursor: &SearchCursor, found: u64| Checkpoint {
+        next_index: cursor.nonce,
+        scan_order: config.scan_order,
+        scan_seed,
+        attempts: resumed_attempts + cursor.attempts,
+        found: resumed_found + found,
+        fingerprint: Some(config.fingerprint()),
+        campaign: config.campaign.map(str::to_string),
+    };
+    let mut results_sent = 0;
+    let mut last_disk_check_time = Instant::now();
+    let mut expected_attempts = expected_attempts(&reward);
+    // Attempts made before a --pattern-file reload, which the odds explored start over from
+    let mut reward_attempts_start = 0;
+    let mut pattern_watcher = config.pattern_file.map(PatternWatcher::open).transpose()?;
+    let mut last_pattern_poll = Instant::now();
+    let mut anomaly_self_tested = false;
+    let mut self_test_failure = None;
+    // Set once a --strict run found the GPU and the CPU disagreeing, with the error it ends with
+    let mut strict_failure = None;
+    let mut corruption_injected = false;
+    let keepalive = if config.supervised {
+        let mut notifier = supervise::system_notifier();
+        notifier.notify(Notification::Ready)?;
+        Some(supervise::spawn_keepalive(
+            notifier,
+            shared_attempts.clone(),
+            Duration::from_secs(1),
+            config.watchdog_stall,
+            paused.clone(),
+            keepalive_stop.clone(),
+        ))
+    } else {
+        None
+    };
+
+    if !config.skip_ranges.is_empty() {
+        progress!(progress,
+            "Skipping {} already covered nonces in {} ranges",
+            config.skip_ranges.len(),
+            config.skip_ranges.ranges().len()
+        );
+    }
+
+    if let Some(term) = term {
+        let display = StatusDisplay {
+            term,
+            plain: config.supervised,
+            work_size,
+            create_variant: params::create_variant(&config),
+            cost: config.cost,
+        };
+        let ticks = Subscription { filter: MinerEvent::is_status_tick, ..Subscription::default() };
+        bus.subscribe_with("status display", display, ticks);
+    }
+
+    if config.json_events {
+        emit_event(Event::Start(Box::new(StartEvent {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: Artifact::Events.current(),
+            gpu: adapter_info.name.clone(),
+            factory: params::factory(&config),
+            create_variant: params::create_variant(&config),
+            salt_variant: params::salt_variant(&config),
+            reward: reward.to_string(),
+            output: config.output.to_string(),
+            format: config.format.to_string(),
+            scan_order: config.scan_order.to_string(),
+            start_index: start_nonce,
+            work_size,
+            workgroup_size,
+            expected_attempts,
+            limit: config.limit,
+            startup: startup
+                .phases
+                .iter()
+                .map(|(phase, duration)| StartupPhaseTime { phase: phase.name().to_string(), secs: duration.as_secs_f64() })
+                .collect(),
+        })));
+    }
+
+    // The results of the runs resumed from count towards --limit and --max-results. Each
+    // pattern of an any-pattern run is limited by the results of this run alone
+    let limit_reached = |found: u64, pattern_found: &[u64]| match pattern_found.is_empty() || dual_salt_chain_id.is_some() {
+        true => config.limit.is_some_and(|limit| resumed_found + found >= limit),
+        false => config.limit.is_some_and(|limit| pattern_found.iter().all(|&n| n >= limit)),
+    };
+    let max_results_reached = |found: u64| config.max_results.is_some_and(|max_results| resumed_found + found >= max_results);
+
+    let stop_reason = 'outer: loop {
+        // A resumed run may have reached its limit before it was stopped
+        if total_cycles == 0 && resumed_found > 0 {
+            if limit_reached(found, &pattern_found) {
+                progress!(progress, "\nResumed with {} results, the --limit is reached.", resumed_found);
+                break 'outer StopReason::Limit;
+            }
+            if max_results_reached(found) {
+                progress!(progress, "\nResumed with {} results, the --max-results are reached.", resumed_found);
+                break 'outer StopReason::MaxResults;
+            }
+        }
+
+        // Stop between batches on Ctrl+C, so the results found so far are written
+        if interrupt::interrupted() {
+            progress!(progress, "\nUser interrupted.");
+            break 'outer StopReason::Interrupted;
+        }
+
+        // Outside of the --schedule windows, wait for the next one to open
+        if let Some(schedule) = &config.schedule {
+            if let Some(until) = schedule.pause_until(&chrono::Local::now()) {
+                progress!(progress, "\nscheduled pause until {}", until.format("%H:%M"));
+                if checkpointing {
+                    pending_checkpoint.offer(checkpoint(&cursor, found), results_sent);
+                }
+                paused.store(true, Ordering::Relaxed);
+                let opened = schedule::wait_for_window(schedule, &SystemClock, Duration::from_secs(1), || {
+                    !interrupt::interrupted()
+                });
+                paused.store(false, Ordering::Relaxed);
+                if !opened {
+                    progress!(progress, "\nUser interrupted.");
+                    break 'outer StopReason::Interrupted;
+                }
+                if checkpointing {
+                    pending_checkpoint.offer(checkpoint(&cursor, found), results_sent);
+                }
+                progress!(progress, "Schedule window open, mining resumed");
+            }
+        }
+
+        // A steady rate of addresses found again with other salts points at a derivation bug,
+        // check the kernel once before mining on
+        if !anomaly_self_tested && duplicate_anomaly.load(Ordering::Relaxed) {
+            anomaly_self_tested = true;
+            progress!(progress, "\nMany addresses were found again with other salts, running the kernel self-test");
+            match selftest::run(&device, &queue, SelfTestMode::Full) {
+                Ok(()) => progress!(progress, "Kernel self-test passed, the duplicates do not come from the hashing"),
+                Err(e) => {
+                    self_test_failure = Some(e);
+                    break 'outer StopReason::SelfTestFailed;
+                }
+            }
+        }
+
+        // Pick up edits of the --pattern-file, keeping the active pattern while the file is invalid
+        if let Some(watcher) = pattern_watcher.as_mut().filter(|_| last_pattern_poll.elapsed() >= Duration::from_secs(1)) {
+            match watcher.poll() {
+                Some(Reload::Changed { removed, added }) => {
+                    let mut patterns: Vec<Box<str>> = watcher.patterns().iter().map(|pattern| pattern.pattern.as_str().into()).collect();
+                    let reloaded = match (&reward, patterns.len()) {
+                        (RewardVariant::ChecksumMatching { .. }, 1) => Some(RewardVariant::ChecksumMatching { pattern: patterns.remove(0) }),
+                        (RewardVariant::ChecksumMatching { .. }, _) => None,
+                        (_, 1) => Some(RewardVariant::Matching { pattern: patterns.remove(0) }),
+                        (_, _) => Some(RewardVariant::MatchingAny { patterns }),
+                    };
+                    match reloaded {
+                        Some(reloaded) => {
+                            progress!(progress, "\npatterns changed: removed {}, added {}", list_or_none(&removed), list_or_none(&added));
+                            // Patterns still mined for keep counting towards their --limit
+                            let kept: Vec<(Box<str>, u64)> = matching.alternatives.iter().map(|(alternative, _)| alternative.to_string().into()).zip(pattern_found.iter().copied()).collect();
+                            reward = reloaded;
+                            matching = MatchingSetup::new(&config, &reward);
+                            matching.announce(progress, config.quiet);
+                            pattern_found = matching.alternatives.iter().map(|(alternative, _)| {
+                                let alternative = alternative.to_string();
+                                kept.iter().find(|(pattern, _)| **pattern == *alternative).map_or(0, |(_, found)| *found)
+                            }).collect();
+                            queue.write_buffer(&pipeline.pattern_config_buffer, 0, bytemuck::cast_slice(&matching.pattern_config()));
+                            expected_attempts = probability::expected_attempts(&reward);
+                            reward_attempts_start = cursor.attempts;
+                        }
+                        None => progress!(progress, "\nwarning: --checksum-case takes a single pattern, still mining for {}", reward),
+                    }
+                }
+                Some(Reload::Invalid(warning)) => {
+                    progress!(progress, "\nwarning: {}, still mining for {}", warning, reward);
+                }
+                None => {}
+            }
+            last_pattern_poll = Instant::now();
+        }
+
+        // Jump over nonce ranges covered by earlier campaigns and stop the batch at the next one
+        let lease_end = config.lease.as_ref().map(|lease| lease.end);
+        let max_batch = match checkpointing {
+            true => checkpoints.batch_limit(cursor.attempts, work_size as u64),
+            false => work_size as u64,
+        };
+        let Some(batch) = cursor.next_batch(&config.skip_ranges, max_batch, lease_end) else {
+            progress!(progress, "\nLease mined, stopping.");
+            break 'outer StopReason::LeaseMined;
+        };
+        let dispatched = batch.count as u32;
+        let cycle_start = Instant::now();
+        let found_before = found;
+        queue.write_buffer(&pipeline.num_messages_buffer, 0, bytemuck::cast_slice(&[dispatched]));
+
         // Update input buffer with new nonce
-        queue.write_buffer(&message_buffer, 0, bytemuck::cast_slice(&[nonce as u32]));
+        queue.write_buffer(
+            &pipeline.message_buffer,
+            0,
+            bytemuck::cast_slice(&[batch.start as u32, (batch.start >> 32) as u32]),
+        );
+        queue.write_buffer(&pipeline.match_count_buffer, 0, bytemuck::cast_slice(&[0u32]));
         
         // Create command encoder
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Command Encoder"),
         });
+        if !matching.coarse_prefix.is_empty() {
+            encoder.clear_buffer(&pipeline.bitmap_buffer, 0, None);
+        }
         
         // Compute pass to find matching addresses
         {
@@ -341,47 +635,115 @@ pub fn gpu(config: Config) -> Result<(), Box<dyn std::error::Error>> {
                 label: Some("Compute Pass"),
                 timestamp_writes: None,
             });
-            compute_pass.set_pipeline(&compute_pipeline);
-            compute_pass.set_bind_group(0, &bind_group, &[]);
-            compute_pass.dispatch_workgroups(workgroups, 1, 1);
+            compute_pass.set_pipeline(&pipeline.compute_pipeline);
+            compute_pass.set_bind_group(0, &pipeline.bind_group, &[]);
+            compute_pass.dispatch_workgroups(geometry.workgroups(dispatched), 1, 1);
         }
         
-        // Copy all hash results to CPU validation buffer
-        encoder.copy_buffer_to_buffer(
-            &hash_buffer,
-            0,
-            &cpu_validation_buffer,
-            0,
-            validation_buffer_size as u64,
-        );
-        
-        // Submit command buffer
-        queue.submit(std::iter::once(encoder.finish()));
-        
-        // Map validation buffer for reading
-        let buffer_slice = cpu_validation_buffer.slice(..);
-        let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            sender.send(result).unwrap();
-        });
-        
-        // Wait for GPU mapping
-        device.poll(wgpu::Maintain::Wait);
+        let (entries, readback_bytes) = match matching.placement {
+            MatchPlacement::Gpu { .. } | MatchPlacement::GpuZeroNibbles { .. } => {
+                // Only the hits packed at the front of the output buffer are copied back
+                encoder.copy_buffer_to_buffer(&pipeline.match_count_buffer, 0, &pipeline.match_count_readback, 0, 4);
+                queue.submit(std::iter::once(encoder.finish()));
+                let hits = read_match_count(&device, &pipeline.match_count_readback).min(dispatched);
+                if hits > 0 {
+                    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Match Copy Encoder"),
+                    });
+                    encoder.copy_buffer_to_buffer(
+                        &pipeline.hash_buffer,
+                        0,
+                        &pipeline.cpu_validation_buffer,
+                        0,
+                        hits as u64 * RESULT_WORDS as u64 * 4,
+                    );
+                    queue.submit(std::iter::once(encoder.finish()));
+                }
+                (hits, 4 + geometry.byte_range(&(0..hits)).end)
+            }
+            MatchPlacement::Cpu if !matching.coarse_prefix.is_empty() => {
+                // Read the bitmap, then copy only the flagged entries, packed at the front
+                let bitmap_words = geometry.workgroups(dispatched);
+                encoder.copy_buffer_to_buffer(&pipeline.bitmap_buffer, 0, &pipeline.bitmap_readback, 0, bitmap_words as u64 * 4);
+                queue.submit(std::iter::once(encoder.finish()));
+                let bitmap = read_bitmap(&device, &pipeline.bitmap_readback, bitmap_words);
+
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Flagged Copy Encoder"),
+                });
+                let mut copied = 0;
+                for entries in geometry.flagged_ranges(&bitmap, dispatched) {
+                    let bytes = geometry.byte_range(&entries);
+                    let packed = geometry.byte_range(&(copied..copied + entries.len() as u32));
+                    encoder.copy_buffer_to_buffer(&pipeline.hash_buffer, bytes.start, &pipeline.cpu_validation_buffer, packed.start, bytes.end - bytes.start);
+                    copied += entries.len() as u32;
+                }
+                queue.submit(std::iter::once(encoder.finish()));
+
+                let full = geometry.byte_range(&(0..dispatched)).end;
+                let read = bitmap_words as u64 * 4 + geometry.byte_range(&(0..copied)).end;
+                readback_saved += full.saturating_sub(read);
+                (copied, read)
+            }
+            MatchPlacement::Cpu => {
+                // Copy all hash results to CPU validation buffer
+                encoder.copy_buffer_to_buffer(
+                    &pipeline.hash_buffer,
+                    0,
+                    &pipeline.cpu_validation_buffer,
+                    0,
+                    validation_buffer_size,
+                );
+                queue.submit(std::iter::once(encoder.finish()));
+                (dispatched, geometry.byte_range(&(0..dispatched)).end)
+            }
+        };
         
-        if let Some(Ok(())) = pollster::block_on(receiver.receive()) {
+        // Map the copied results for reading (buffer slices can't be empty)
+        let buffer_slice = pipeline.cpu_validation_buffer.slice(..(entries.max(1) as u64 * RESULT_WORDS as u64 * 4));
+        if entries > 0 && map_read(&device, &buffer_slice) {
             let data = buffer_slice.get_mapped_range();
             let results = bytemuck::cast_slice::<u8, u32>(&data);
+
+            // --inject-corruption flips a bit of the hash of the first result read back
+            let corrupted;
+            let results = match config.inject_corruption && !corruption_injected {
+                true => {
+                    corruption_injected = true;
+                    let mut words = results.to_vec();
+                    words[2] ^= 1;
+                    corrupted = words;
+                    &corrupted[..]
+                }
+                false => results,
+            };
+
+            // --strict derives some of the results read back again on the CPU, whatever they
+            // matched
+            if config.strict {
+                let disagreement = forensics::spot_checks(entries as usize).find_map(|index| forensics::check(&config, results, index));
+                if let Some(disagreement) = disagreement {
+                    strict_failure = Some(halt_strict(&config, &disagreement, total_cycles + 1, results, &adapter_info));
+                }
+            }
             
             // Pre-allocate a vector to store matches for batch processing
             let mut matches = Vec::with_capacity(16); // Usually we won't find more than a few matches
+            // Results the pattern matched, discarded ones included, for --record-cycles
+            let mut recorded = vec![];
             
             // Process each result (nonce + hash)
-            for i in 0..work_size as usize {
+            for i in 0..entries as usize {
+                if strict_failure.is_some() {
+                    break;
+                }
                 let base_idx = i * (2 + KECCAK256_OUTPUT_SIZE as usize);
                 
                 // Extract nonce
                 let nonce_low = results[base_idx];
                 let nonce_high = results[base_idx + 1];
+                let nonce = nonce_low as u64 | (nonce_high as u64) << 32;
+                let index = config.scan_order.index_of(nonce, scan_seed);
                 
                 // Extract hash
                 let mut hash = [0u32; KECCAK256_OUTPUT_SIZE as usize];
@@ -389,389 +751,1655 @@ pub fn gpu(config: Config) -> Result<(), Box<dyn std::error::Error>> {
                     hash[j] = results[base_idx + 2 + j];
                 }
                 
-                // Convert hash to bytes (in big-endian order for Ethereum compatibility)
+                // Convert hash to bytes (keccak lanes are little-endian)
                 let mut hash_bytes = [0u8; 32];
                 for j in 0..8 {
-                    // Convert to big-endian format to match Ethereum convention
-                    let bytes = hash[j].to_be_bytes();
+                    let bytes = hash[j].to_le_bytes();
                     hash_bytes[j*4..(j+1)*4].copy_from_slice(&bytes);
                 }
                 
-                // Get the Ethereum address part (bytes 12-31)
-                let eth_address_start = 12;
-                let eth_address_bytes = &hash_bytes[eth_address_start..32];
+                // Get the Ethereum address part (bytes 12-31 unless overridden)
+                let eth_address_bytes = &hash_bytes[config.address_offset..config.address_offset + 20];
                 
-                // Check if the address matches our pattern
-                let matches_pattern = check_eth_address_pattern(eth_address_bytes, pattern_value as u8, pattern_flags, pattern_length);
+                // Check if the address matches our pattern, GPU hits are confirmed on the CPU
+                let matches_pattern = match &matching.placement {
+                    MatchPlacement::Gpu { prefix } => {
+                        eth_address_bytes.starts_with(prefix) && matching.confirms(eth_address_bytes)
+                    }
+                    MatchPlacement::GpuZeroNibbles { .. } | MatchPlacement::Cpu => matching.matches(eth_address_bytes),
+                };
                 
                 if matches_pattern {
+                    if config.record_cycles.is_some() {
+                        recorded.push(i);
+                    }
+
+                    // --strict confirms the whole hash of every result before it is written
+                    if config.strict {
+                        if let Some(disagreement) = forensics::check(&config, results, i) {
+                            strict_failure = Some(halt_strict(&config, &disagreement, total_cycles + 1, results, &adapter_info));
+                            break;
+                        }
+                    }
+
+                    // Create salt bytes
+                    let salt_bytes = config.salt_variant.salt(nonce);
+                    
+                    // Derive the address again before trusting the kernel with a result
+                    let guarded_salt = config.salt_variant.guard(&salt_bytes);
+                    let expected = config.create_variant.kernel_hash(&config.factory_address, &guarded_salt);
+                    if expected[config.address_offset..config.address_offset + 20] != *eth_address_bytes {
+                        eprintln!(
+                            "\nDiscarding salt 0x{}: the GPU derived 0x{}, the CPU 0x{}",
+                            hex::encode(salt_bytes),
+                            hex::encode(eth_address_bytes),
+                            hex::encode(&expected[config.address_offset..config.address_offset + 20])
+                        );
+                        continue;
+                    }
+                    
+                    let address_bytes: [u8; 20] = eth_address_bytes.try_into().unwrap();
+
+                    // The crosschain framing of a --dual-salt nonce is the salt just confirmed.
+                    // Its random framing must match too, which the kernel checked unless matching
+                    // on the CPU
+                    if let Some(chain_id) = dual_salt_chain_id {
+                        let dual = DualSalt::new(nonce);
+                        let (_, random) = dual.addresses(&config.create_variant, &config.factory_address, chain_id);
+                        if !matching.matches(&random) {
+                            if matching.placement != MatchPlacement::Cpu {
+                                eprintln!(
+                                    "\nDiscarding salt 0x{}: the GPU matched its random framing, the CPU derived 0x{} for it",
+                                    hex::encode(salt_bytes),
+                                    hex::encode(random)
+                                );
+                            }
+                            continue;
+                        }
+                        if config.avoid_reserved && (reserved::reserved(&address_bytes).is_some() || reserved::reserved(&random).is_some()) {
+                            reserved_rejected += 1;
+                            continue;
+                        }
+                        matches.push((index, salt_bytes, address_bytes));
+                        matches.push((index, dual.random, random));
+                        continue;
+                    }
+
+                    if config.avoid_reserved && reserved::reserved(&address_bytes).is_some() {
+                        reserved_rejected += 1;
+                        continue;
+                    }
+
                     // Add to the matches collection for batch processing
-                    matches.push((nonce_low, nonce_high, hash_bytes));
+                    matches.push((index, salt_bytes, address_bytes));
                 }
             }
             
+            if let (Some(dir), false) = (config.record_cycles, recorded.is_empty()) {
+                let record = CycleRecord::new(&config, total_cycles + 1, batch.start, dispatched, scan_seed, results, &recorded);
+                let path = replay::record_path(std::path::Path::new(dir), record.cycle);
+                record.write(&path).map_err(|e| format!("Couldn't write replay record {}: {}", path.display(), e))?;
+            }
+
+            // A --strict run halts without the results of the cycle it disagreed in
+            if strict_failure.is_some() {
+                matches.clear();
+            }
+
+            // Results come back in the order the GPU threads finished. Sorted by scan index, a
+            // --limit keeps the first results in scan order, the same ones on every run of a
+            // --seed whatever the work size splitting the scan into cycles. The sort is stable,
+            // so both salts of a --dual-salt match stay together
+            matches.sort_by_key(|(index, _, _)| *index);
+
+            // Stop at the --limit, keeping both salts of a --dual-salt match. Several patterns
+            // each mine up to the limit, so an easy one doesn't end the search for the others
+            if let (Some(limit), false, None) = (config.limit, pattern_found.is_empty(), dual_salt_chain_id) {
+                matches.retain(|(_, _, address)| matching.counts_towards(address, limit, &mut pattern_found));
+            } else if let Some(limit) = config.limit {
+                let mut keep = limit.saturating_sub(resumed_found + found) as usize;
+                if dual_salt_chain_id.is_some() {
+                    keep += keep % 2;
+                }
+                matches.truncate(keep);
+            }
+
             // Batch process matches
             if !matches.is_empty() {
                 found += matches.len() as u64;
                 
-                // Open the file once outside the loop for better performance
-                let mut file = std::fs::OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .append(true)
-                    .open(&config.output)
-                    .unwrap();
-                    
                 // Process all matches
-                for (nonce_low, nonce_high, hash_bytes) in matches {
-                    // Create salt bytes
-                    let mut salt_bytes = [0u8; 32];
-                    salt_bytes[0..4].copy_from_slice(&nonce_low.to_le_bytes());
-                    salt_bytes[4..8].copy_from_slice(&nonce_high.to_le_bytes());
-                    
+                for (_, salt_bytes, address_bytes) in matches {
+                    run_results.push((salt_bytes, address_bytes));
+                    if let Some(on_match) = on_match.as_mut() {
+                        on_match(Match { salt: salt_bytes, address: address_bytes });
+                    }
+
                     // Format the salt as hex
                     let salt_hex = format!("0x{}", hex::encode(salt_bytes));
                     
-                    // Get the Ethereum address part (bytes 12-31)
-                    let eth_address_start = 12;
-                    let eth_address_bytes = &hash_bytes[eth_address_start..32];
-                    
                     // Create checksummed address
-                    let address = Address::from_slice(eth_address_bytes);
-                    let checksummed_address = format!("{}", address.to_checksum(None));
+                    let checksummed_address = format_address(&address_bytes);
                     
-                    // Write to output file
-                    if let Err(e) = writeln!(file, "{} -> {}", salt_hex, checksummed_address) {
-                        eprintln!("Error writing to output file: {}", e);
+                    // Hand the result to the subscribers, the output writer among them
+                    let attempt = cursor.attempts + batch.count;
+                    let kept_for = matching.kept_for(&address_bytes).unwrap_or_else(|| reward.clone());
+                    let pattern_lines = pattern_watcher.as_ref().map(|watcher| watcher.lines_of(&kept_for)).unwrap_or_default();
+                    let line = match_writer.line(&salt_bytes, &checksummed_address, &kept_for, &pattern_lines, attempt);
+                    let result = FoundResult { salt: salt_bytes, address: address_bytes, line };
+                    match ranked.as_mut() {
+                        Some(ranked) => ranked.push(address_bytes, result),
+                        None => {
+                            bus.publish(MinerEvent::Found(result));
+                            results_sent += 1;
+                        }
+                    }
+                    if embedded {
+                        continue;
+                    }
+
+                    // Log it in place of the status with --quiet
+                    let found_line = found_line(&config, &salt_bytes, &address_bytes);
+                    if config.json_events {
+                        let salt = match config.redact_salts {
+                            true => redact::salt_fingerprint(&salt_bytes),
+                            false => salt_hex,
+                        };
+                        emit_event(Event::Found(FoundEvent {
+                            salt,
+                            address: checksummed_address.clone(),
+                            pattern: kept_for.to_string(),
+                        }));
+                    } else if config.quiet && !config.no_file {
+                        println!("found: {}", found_line);
                     }
-                    
-                    // Add to found list
-                    let mut found_list_guard = found_list.lock().unwrap();
-                    found_list_guard.push(format!("{} -> {}", salt_hex, checksummed_address));
                 }
             }
             
             // Unmap buffer
             drop(data);
-            cpu_validation_buffer.unmap();
+            pipeline.cpu_validation_buffer.unmap();
+        }
+        if strict_failure.is_some() {
+            break 'outer StopReason::Disagreement;
         }
         
-        // Increment nonce for next iteration
-        nonce += work_size as u64;
+        // Only the nonces actually dispatched count as attempts, skipped ones were mined before
+        cursor.advance(batch);
         total_cycles += 1;
+        shared_attempts.store(cursor.attempts, Ordering::Relaxed);
+
+        let cycle = CycleStats {
+            cycle: total_cycles,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+            cycle_ms: cycle_start.elapsed().as_secs_f64() * 1000.0,
+            attempts: batch.count,
+            readback_bytes,
+            matches: found - found_before,
+        };
+        if let Some(stats_file) = stats_file.as_mut() {
+            writeln!(stats_file, "{}", cycle.to_row())?;
+        }
+        bus.publish(MinerEvent::CycleCompleted(cycle));
+
+        checkpoints.cycle_done();
+        if checkpointing && checkpoints.is_due(cursor.attempts, Instant::now()) {
+            pending_checkpoint.offer(checkpoint(&cursor, found), results_sent);
+            checkpoints.taken(cursor.attempts, Instant::now());
+        }
+
+        if !file_less && last_disk_check_time.elapsed() >= DISK_CHECK_INTERVAL {
+            low_disk_space = check_disk_space(&mut warnings, &bus, config.output, low_disk_space);
+            last_disk_check_time = Instant::now();
+        }
         
         // Update status every second
         if last_status_time.elapsed() >= Duration::from_secs(1) {
             let elapsed = start_time.elapsed();
-            let rate = nonce as f64 / elapsed.as_secs_f64() / 1_000_000.0;
-            
-            term.clear_line().unwrap();
-            print!(
-                "total runtime: {:02}:{:02}:{:02.2} ({} cycles)                      work size per cycle: {}",
-                elapsed.as_secs() / 3600,
-                (elapsed.as_secs() % 3600) / 60,
-                (elapsed.as_secs() % 60) as f64 + (elapsed.subsec_nanos() as f64 / 1_000_000_000.0),
-                total_cycles,
-                work_size
-            );
-            print!("\nrate: {:.2} million attempts per second                  total found this run: {}", rate, found);
-            
-            // Print additional pattern information based on config
-            match &config.reward {
-                RewardVariant::Matching { pattern } => {
-                    print!("\ncurrent search space: {}            threshold: mining for Create3 address matching pattern 0x{}", nonce, pattern);
-                }
-                RewardVariant::LeadingZeros { zeros_threshold } => {
-                    print!("\ncurrent search space: {}            threshold: mining for Create3 address with {} leading zero bytes", nonce, zeros_threshold);
-                }
-                RewardVariant::TotalZeros { zeros_threshold } => {
-                    print!("\ncurrent search space: {}            threshold: mining for Create3 address with {} total zero bytes", nonce, zeros_threshold);
-                }
-                _ => {}
+            let rate = hash_rate(cursor.attempts, elapsed);
+            let explored = stats::explored_probability(cursor.attempts - reward_attempts_start, expected_attempts);
+            if config.json_events {
+                emit_event(Event::Status(StatusEvent {
+                    elapsed_secs: elapsed.as_secs_f64(),
+                    attempts: cursor.attempts,
+                    rate,
+                    found,
+                    eta_secs: stats::expected_duration(expected_attempts, rate).map(|eta| eta.as_secs_f64()),
+                    explored,
+                }));
             }
-            
+            bus.publish(MinerEvent::StatusTick(StatusTick {
+                elapsed,
+                cycles: total_cycles,
+                attempts: cursor.attempts,
+                rate,
+                found,
+                nonce: cursor.nonce,
+                reward: reward.to_string(),
+                expected_attempts,
+                explored,
+                dropped: writer.map_or(0, |writer| bus.dropped(writer)),
+                readback_saved: (!matching.coarse_prefix.is_empty()).then_some(readback_saved),
+                reserved_rejected,
+            }));
             last_status_time = Instant::now();
         }
         
-        // Check if the user wants to stop (non-blocking)
-        if should_exit {
-            println!("\nUser interrupted.");
-            break 'outer;
+        if limit_reached(found, &pattern_found) {
+            progress!(progress, "\nFound {} results in {:.1}s, stopping.", found, start_time.elapsed().as_secs_f64());
+            break 'outer StopReason::Limit;
         }
-    }
-    
-    Ok(())
-}
 
-fn parse_pattern(pattern: &str) -> (u32, u32, u32) {
-    let mut pattern_value = 0u32;
-    let mut pattern_flags = 0u32;
-    let mut pattern_length = 0u32;
-    let mut trailing_value = 0u32;
+        if max_results_reached(found) {
+            progress!(progress,
+                "\nFound {} results in {:.1}s after {} attempts, stopping.",
+                found,
+                start_time.elapsed().as_secs_f64(),
+                cursor.attempts
+            );
+            break 'outer StopReason::MaxResults;
+        }
 
-    // Check for empty pattern
-    if pattern.is_empty() {
-        return (0, 0, 0);
+        if config.timeout.is_some_and(|timeout| start_time.elapsed() >= timeout) {
+            progress!(progress, "\nTimed out after {}s with {} results found, stopping.", config.timeout.unwrap().as_secs(), found);
+            break 'outer StopReason::Timeout;
+        }
+
+        if config.max_runtime.is_some_and(|max_runtime| start_time.elapsed() >= max_runtime) {
+            progress!(progress,
+                "\nRan for {} with {} results found after {} attempts, stopping.",
+                schedule::format_duration(start_time.elapsed()),
+                found,
+                cursor.attempts
+            );
+            break 'outer StopReason::MaxRuntime;
+        }
+    };
+
+    if reserved_rejected > 0 {
+        progress!(progress, "Rejected {} matches in the precompile range or on the denylist of well-known addresses", reserved_rejected);
     }
 
-    println!("Raw pattern: '{}'", pattern);
+    if let Some(mut stats_file) = stats_file {
+        if let Err(e) = stats_file.flush() {
+            eprintln!("Error writing stats file: {}", e);
+        }
+    }
 
-    // Check if pattern contains "..."
-    if pattern.contains("...") {
-        let parts: Vec<&str> = pattern.split("...").collect();
-        
-        if parts.len() == 2 {
-            let leading_part = parts[0];
-            let trailing_part = parts[1];
-            
-            // Check for special non-repeating patterns like "ABCD...EF"
-            if !leading_part.is_empty() && leading_part.len() % 2 == 0 && 
-               !trailing_part.is_empty() && trailing_part.len() == 2 {
-                // Store special pattern flag for non-repeating patterns
-                pattern_flags = 99; // Special flag for non-repeating patterns
-                
-                // For non-repeating patterns, we'll use the original pattern in the check function
-                // Just store some placeholder values
-                println!("Detected non-repeating pattern: {}...{}", leading_part, trailing_part);
-                
-                // Return special values that will be checked in the check_eth_address_pattern function
-                return (99, 99, 0);
-            }
-            
-            // Check if leading part is a repeating pattern (e.g., "BBBBBB")
-            if !leading_part.is_empty() && leading_part.len() % 2 == 0 {
-                let first_byte = &leading_part[0..2];
-                let mut is_repeating = true;
-                
-                // Check if all bytes in leading part are the same
-                for i in (0..leading_part.len()).step_by(2) {
-                    if &leading_part[i..i+2] != first_byte {
-                        is_repeating = false;
-                        break;
-                    }
-                }
-                
-                if is_repeating {
-                    // Parse the repeating byte value (e.g., "BB")
-                    if let Ok(value) = u8::from_str_radix(first_byte, 16) {
-                        pattern_value = value as u32;
-                        pattern_length = (leading_part.len() / 2) as u32;
-                        
-                        // Check if we have a trailing pattern
-                        if !trailing_part.is_empty() && trailing_part.len() == 2 {
-                            if let Ok(trailing) = u8::from_str_radix(trailing_part, 16) {
-                                trailing_value = trailing as u32;
-                                pattern_flags = 5; // New flag: multiple leading + specific trailing
-                                println!("Detected complex pattern: {} leading 0x{:02X} bytes + trailing 0x{:02X}", 
-                                       pattern_length, pattern_value, trailing_value);
-                            }
-                        } else {
-                            pattern_flags = 4; // Just multiple leading bytes
-                            println!("Detected multiple leading pattern: {} bytes of 0x{:02X}", 
-                                   pattern_length, pattern_value);
-                        }
-                    }
-                }
-            }
+    // Write the results held by --sort-on-exit, best first
+    if let Some(ranked) = ranked {
+        for result in ranked.into_sorted() {
+            bus.publish(MinerEvent::Found(result));
+            results_sent += 1;
         }
     }
-    
-    // Store trailing value in upper bits of pattern_value if we have one
-    if pattern_flags == 5 {
-        pattern_value = pattern_value | (trailing_value << 8);
+
+    if checkpointing {
+        pending_checkpoint.offer(checkpoint(&cursor, found), results_sent);
+    }
+    if let Some(keepalive) = keepalive {
+        keepalive_stop.store(true, Ordering::Relaxed);
+        let _ = keepalive.join();
+        supervise::system_notifier().notify(Notification::Stopping)?;
     }
-    
-    println!("Final config: value=0x{:04X}, flags={}, length={}", 
-             pattern_value, pattern_flags, pattern_length);
-    return (pattern_value, pattern_flags, pattern_length);
-}
 
-// Add this debug method to print how Ethereum addresses are derived
-fn debug_ethereum_address(salt_bytes: &[u8; 32], hash_output: &[u8; 32]) {
-    let eth_address_start = 12;
-    println!("Salt: {}", hex::encode(salt_bytes));
-    println!("Hash result: {}", hex::encode(hash_output));
-    println!("Derived ETH address: 0x{}", hex::encode(&hash_output[eth_address_start..32]));
-    println!("First byte of ETH address: 0x{:02X}", hash_output[eth_address_start]);
-}
+    let dropped = writer.map_or(0, |writer| bus.dropped(writer));
+    if dropped > 0 {
+        emit_warning(&mut warnings, &bus, Warning::DroppedMatches { count: dropped });
+    }
+    bus.publish(MinerEvent::ShuttingDown(stop_reason));
+    // A full disk fails the run, but only after the final report
+    let written = bus.shutdown();
+    if let Some(report) = warnings.report() {
+        progress!(progress, "{}", report);
+    }
+    if let Some(cost) = &config.cost {
+        let elapsed = start_time.elapsed();
+        progress!(progress, "{}", cost.report(elapsed, expected_attempts, hash_rate(cursor.attempts, elapsed)));
+    }
+
+    if config.clipboard {
+        let render = |salt: &[u8; 32], address: &[u8; 20]| found_line(&config, salt, address);
+        match clipboard::system_clipboard()
+            .and_then(|mut system| clipboard::copy_best_result(system.as_mut(), &run_results, render))
+        {
+            Ok(Some(best)) => progress!(progress, "Copied best result to the clipboard: {}", best),
+            Ok(None) => progress!(progress, "No result to copy to the clipboard"),
+            Err(e) => match clipboard::best_result(&run_results) {
+                Some((salt, address)) => progress!(progress, "Clipboard unavailable ({}), best result: {}", e, render(salt, address)),
+                None => progress!(progress, "Clipboard unavailable ({})", e),
+            },
+        }
+    }
+
+    // The session log of a campaign is a sidecar of the output file, so it is bundled too
+    if let (Some(campaign), false) = (config.campaign, file_less) {
+        let session = campaign::Session::new(&config, campaign, started_unix, cursor.attempts, start_time.elapsed(), &run_results);
+        let path = campaign::sessions_path(config.output);
+        if let Err(e) = session.append(std::path::Path::new(&path)) {
+            eprintln!("Error writing campaign session log {}: {}", path, e);
+        }
+    }
+
+    if let Some(bundle_path) = config.bundle {
+        let mut members = bundle::session_artifacts(std::path::Path::new(config.output), Some(scan_seed))?;
+        members.push(bundle::build_info());
+        members.push(bundle::config_info(&config));
+        members.push(bundle::BundleMember::new("adapter.txt", format!("{:#?}\n", adapter_info)));
+        bundle::write_bundle(std::path::Path::new(bundle_path), members)?;
+        progress!(progress, "Run bundle written to {}", bundle_path);
+    }
+
+    if config.json_events {
+        emit_event(Event::Done(DoneEvent {
+            reason: stop_reason,
+            elapsed_secs: start_time.elapsed().as_secs_f64(),
+            attempts: cursor.attempts,
+            found,
+        }));
+    }
+
+    written?;
+    if let Some(e) = self_test_failure {
+        return Err(format!("kernel self-test failed after duplicate addresses: {}", e).into());
+    }
+    if let Some(e) = strict_failure {
+        return Err(e.into());
+    }
+    if stop_reason == StopReason::MaxRuntime {
+        progress!(progress, "stopped: runtime limit reached");
+    }
 
-// Fix the process_output function to be standalone
-fn process_output(output_buffer: &[u8], log_buffer: &[u8]) -> usize {
-    let mut matches_found = 0;
+    // Supervised runs only succeed when they stopped as told, so the unit restarts them otherwise
+    if config.supervised && !stop_reason.is_deliberate() {
+        return Err("stopped before completion".into());
+    }
 
-    // Extract the salt (first 8 bytes of log_buffer)
-    let mut salt_bytes = [0u8; 32];
-    let mut hash_output = [0u8; 32];
+    Ok(RunSummary {
+        found,
+        attempts: cursor.attempts,
+        elapsed: start_time.elapsed(),
+        results: run_results,
+        startup,
+    })
+}
 
-    // Extract the first 8 bytes into salt_bytes (rest remains zero)
-    for i in 0..8 {
-        salt_bytes[i] = log_buffer[i];
+/// The adapter `config` selects by its index in `list-gpus`.
+fn select_adapter(config: &Config) -> Result<wgpu::Adapter, Box<dyn std::error::Error>> {
+    let instance = gpu_instance(config.backend);
+    let mut adapters = enumerate_adapters(&instance);
+    if adapters.is_empty() {
+        if let Some(backend) = config.backend {
+            let available: Vec<_> = enumerate_adapters(&gpu_instance(None))
+                .iter()
+                .map(|adapter| adapter.get_info().backend)
+                .collect();
+            return Err(diagnostics::missing_backend_error(backend, &available).into());
+        }
+        return Err("Failed to find a suitable GPU adapter".into());
+    }
+    let device_index = config.gpu_device as usize;
+    if device_index >= adapters.len() {
+        let available: Vec<_> = adapters.iter().map(|adapter| (adapter.get_info(), adapter.limits())).collect();
+        return Err(format!(
+            "GPU device {} not found, valid ids are 0 to {}:\n{}",
+            device_index,
+            adapters.len() - 1,
+            diagnostics::adapter_table(&available)
+        )
+        .into());
     }
+    Ok(adapters.swap_remove(device_index))
+}
+
+/// Open the device of `adapter` with the limits the batches and workgroups of `config` need.
+/// Returns the limits requested too, for `--debug-env`.
+fn open_device(
+    adapter: &wgpu::Adapter,
+    config: &Config,
+) -> Result<(wgpu::Device, wgpu::Queue, wgpu::Limits), Box<dyn std::error::Error>> {
+    // Large --work-size batches need the adapter's full buffer limits, large --workgroup-size
+    // workgroups its full workgroup limits
+    let adapter_limits = adapter.limits();
+    validate_workgroup_size(
+        config.workgroup_size,
+        adapter_limits.max_compute_workgroup_size_x,
+        adapter_limits.max_compute_invocations_per_workgroup,
+    )?;
+    let required_limits = wgpu::Limits {
+        max_storage_buffer_binding_size: adapter_limits.max_storage_buffer_binding_size,
+        max_buffer_size: adapter_limits.max_buffer_size,
+        max_compute_workgroup_size_x: adapter_limits.max_compute_workgroup_size_x,
+        max_compute_invocations_per_workgroup: adapter_limits.max_compute_invocations_per_workgroup,
+        ..wgpu::Limits::default()
+    };
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("CreateXCrunch Device"),
+            required_features: wgpu::Features::empty(),
+            required_limits: required_limits.clone(),
+            memory_hints: wgpu::MemoryHints::default(),
+        },
+        None,
+    ))?;
+    Ok((device, queue, required_limits))
+}
 
-    // Extract hash output (next 32 bytes)
-    for i in 0..32 {
-        hash_output[i] = log_buffer[i + 8];
+/// Batches of the `--work-size` of `config` if it fits the device, else of the largest size the
+/// device allows.
+fn batch_geometry(device: &wgpu::Device, config: &Config, progress: Progress) -> Result<BatchGeometry, String> {
+    // Each result takes 40 bytes of the hash and validation buffers, which the device limits
+    let limits = device.limits();
+    let max_buffer_size = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    let max_workgroups = limits.max_compute_workgroups_per_dimension;
+    // Batches that don't fit are refused here, buffer creation would panic on them
+    match config.work_size {
+        Some(work_size) => {
+            let geometry = BatchGeometry::new(work_size, config.workgroup_size);
+            geometry.check_limits(max_buffer_size, max_workgroups)?;
+            Ok(geometry)
+        }
+        None => {
+            let geometry = BatchGeometry::new(u32::MAX, config.workgroup_size).fit(max_buffer_size, max_workgroups);
+            progress!(progress,
+                "Sized batches to the limits of this device ({} byte buffers, {} workgroups), --work-size sets smaller ones",
+                max_buffer_size, max_workgroups
+            );
+            Ok(geometry)
+        }
     }
+}
 
-    // Convert to hex string for display
-    let salt_hex = hex::encode(salt_bytes);
+/// The buffers and compute pipeline of the mining kernel, specialized for `config`. Buffers the
+/// loop never touches are kept alive by the bind group.
+struct Pipeline {
+    /// Words 0 and 1 hold the base scan index of the batch
+    message_buffer: wgpu::Buffer,
+    num_messages_buffer: wgpu::Buffer,
+    hash_buffer: wgpu::Buffer,
+    cpu_validation_buffer: wgpu::Buffer,
+    pattern_config_buffer: wgpu::Buffer,
+    match_count_buffer: wgpu::Buffer,
+    match_count_readback: wgpu::Buffer,
+    bitmap_buffer: wgpu::Buffer,
+    bitmap_readback: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    compute_pipeline: wgpu::ComputePipeline,
+}
+
+impl Pipeline {
+    fn new(
+        device: &wgpu::Device,
+        config: &Config,
+        geometry: &BatchGeometry,
+        scan_seed: u64,
+        pattern_config: &[u32],
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Keccak Shader"),
+            source: wgpu::ShaderSource::Wgsl(kernel_source(config).into()),
+        });
+    
+        // Create buffers
+        // 1. Input buffer for messages (salt data)
+        let message_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Message Buffer"),
+            // Words 0 and 1 hold the base scan index, 2 to 4 the scan order and its seed
+            contents: bytemuck::cast_slice(&[
+                0u32,
+                0,
+                config.scan_order.kernel_id(),
+                scan_seed as u32,
+                (scan_seed >> 32) as u32,
+                0,
+                0,
+                0,
+            ]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+    
+        // 2. Number of messages
+        let num_messages_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Num Messages Buffer"),
+            contents: bytemuck::cast_slice(&[geometry.work_size]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+    
+        // 3. Message sizes
+        let message_sizes_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Message Sizes Buffer"),
+            contents: bytemuck::cast_slice(&[8u32, 8u32]), // Input length and stride
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+    
+        // 4. Output buffer for hashes
+        let hash_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hash Buffer"),
+            // Each result is (2 u32 for nonce + 8 u32 for hash) * 4 bytes per u32
+            size: geometry.result_buffer_size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+    
+        // 5. Log buffer
+        let log_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Log Buffer"),
+            size: 64 * 4, // 64 u32 values
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+    
+        // Create a new CPU-side validation buffer
+        let cpu_validation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("CPU Validation Buffer"),
+            size: geometry.result_buffer_size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+    
+    
+        let pattern_config_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pattern Config Buffer"),
+            contents: bytemuck::cast_slice(pattern_config),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+    
+    
+        // How the kernel derives addresses from salts
+        let derivation_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Derivation Buffer"),
+            contents: bytemuck::cast_slice(&derivation_config(&config.create_variant, &config.salt_variant, &config.factory_address)),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // Hits written by the kernel when matching on the GPU, and its CPU-side copy
+        let match_count_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Match Count Buffer"),
+            size: 4,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let match_count_readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Match Count Readback Buffer"),
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+    
+        // Match bitmap written by the kernel for the coarse prefix check, and its CPU-side copy
+        let bitmap_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Match Bitmap Buffer"),
+            size: geometry.bitmap_size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bitmap_readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Match Bitmap Readback Buffer"),
+            size: geometry.bitmap_size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+    
+        // Create bind group layout
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 9,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 10,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
     
-    // Get the Ethereum address from the hash (last 20 bytes of keccak hash)
-    let eth_address_start = 12;
-    let eth_address = &hash_output[eth_address_start..32];
-    let eth_address_hex = hex::encode(eth_address);
+        // Create bind group
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: message_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: num_messages_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: message_sizes_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: hash_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: log_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: pattern_config_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: match_count_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 9,
+                    resource: bitmap_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 10,
+                    resource: derivation_buffer.as_entire_binding(),
+                },
+            ],
+        });
+    
+        // Create pipeline layout
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
     
-    // Check if the address starts with BB (we're looking for 0xBB...)
-    if eth_address.len() >= 2 && eth_address[0] == 0xBB {
-        println!("MATCH FOUND! Address: 0x{}", eth_address_hex);
-        matches_found += 1;
+        // Create compute pipeline, specialized for the create and salt variants
+        let constants = kernel_constants(config)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value as f64))
+            .collect();
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &constants,
+                ..Default::default()
+            },
+            cache: None,
+        });
         
-        // Write result to output file
-        if let Ok(mut file) = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("output.txt") {
-            
-            if let Err(e) = writeln!(file, "0x{} -> 0x{}", salt_hex, eth_address_hex) {
-                eprintln!("Error writing to output file: {}", e);
+        Self {
+            message_buffer,
+            num_messages_buffer,
+            hash_buffer,
+            cpu_validation_buffer,
+            pattern_config_buffer,
+            match_count_buffer,
+            match_count_readback,
+            bitmap_buffer,
+            bitmap_readback,
+            bind_group,
+            compute_pipeline,
+        }
+    }
+}
+
+/// The `salt -> address` line of a result, as --quiet prints it and --clipboard copies it.
+fn found_line(config: &Config, salt: &[u8; 32], address: &[u8; 20]) -> String {
+    let address = format_address(address);
+    match config.redact_salts {
+        true => redact::redacted_result(salt, &address),
+        false => format!("0x{} -> {}", hex::encode(salt), address),
+    }
+}
+
+/// Print `event` as a line of the `--json` stream.
+fn emit_event(event: Event) {
+    if let Err(e) = events::write_event(std::io::stdout().lock(), &event) {
+        eprintln!("Error writing event: {}", e);
+    }
+}
+
+/// Instance enumerating the adapters of `backend`, or of every backend.
+fn gpu_instance(backend: Option<GpuBackend>) -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: backend.map_or(wgpu::Backends::all(), |backend| backend.backends()),
+        ..Default::default()
+    })
+}
+
+/// Adapters of every backend of `instance`, fastest device types first. `--gpu-device-id` indexes this list.
+fn enumerate_adapters(instance: &wgpu::Instance) -> Vec<wgpu::Adapter> {
+    let mut adapters = instance.enumerate_adapters(wgpu::Backends::all());
+    adapters.sort_by_key(|adapter| diagnostics::device_type_rank(adapter.get_info().device_type));
+    adapters
+}
+
+/// Print the adapters `--gpu-device-id` can select with `--backend`.
+pub fn list_gpus(backend: Option<GpuBackend>) {
+    let adapters: Vec<_> = enumerate_adapters(&gpu_instance(backend))
+        .iter()
+        .map(|adapter| (adapter.get_info(), adapter.limits()))
+        .collect();
+    println!("{}", diagnostics::adapter_table(&adapters));
+}
+
+/// Run the mining kernel of `config` for `duration` without collecting its matches, to measure
+/// the hash rate of the GPU. Writes nothing, only the match count is read back after each cycle
+/// so cycles don't queue up.
+pub fn bench(config: &Config, duration: Duration) -> Result<stats::Benchmark, Box<dyn std::error::Error>> {
+    let adapter = select_adapter(config)?;
+    let (device, queue, _) = open_device(&adapter, config)?;
+    let geometry = batch_geometry(&device, config, Progress::Hidden)?;
+    let matching = MatchingSetup::new(config, &config.reward);
+    let pipeline = Pipeline::new(&device, config, &geometry, 0, &matching.pattern_config());
+
+    let start = Instant::now();
+    let mut attempts: u64 = 0;
+    while start.elapsed() < duration {
+        queue.write_buffer(&pipeline.message_buffer, 0, bytemuck::cast_slice(&[attempts as u32, (attempts >> 32) as u32]));
+        queue.write_buffer(&pipeline.match_count_buffer, 0, bytemuck::cast_slice(&[0u32]));
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Bench Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Bench Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline.compute_pipeline);
+            compute_pass.set_bind_group(0, &pipeline.bind_group, &[]);
+            compute_pass.dispatch_workgroups(geometry.workgroups(geometry.work_size), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&pipeline.match_count_buffer, 0, &pipeline.match_count_readback, 0, 4);
+        queue.submit(std::iter::once(encoder.finish()));
+        read_match_count(&device, &pipeline.match_count_readback);
+        attempts += geometry.work_size as u64;
+    }
+
+    Ok(stats::Benchmark {
+        adapter: adapter.get_info().name,
+        work_size: geometry.work_size,
+        attempts,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Dispatch each candidate of `record` on its own, one nonce at a time, and read back the hash
+/// the kernel derives for it. Runs the built-in kernel with the derivation and workgroup size of
+/// the record.
+pub fn replay_dispatch(
+    record: &CycleRecord,
+    gpu_device: u8,
+    backend: Option<GpuBackend>,
+) -> Result<Vec<[u8; 32]>, Box<dyn std::error::Error>> {
+    let (create_variant, salt_variant) = record.derivation()?;
+    // Every nonce is read back when matching on the CPU without a coarse prefix
+    let reward = RewardVariant::TotalZeros { zeros_threshold: 1 };
+    let mut config = Config::new(gpu_device, &hex::encode(record.factory), None, None, None, reward, "")
+        .map_err(|e| e.to_string())?;
+    config.backend = backend;
+    config.create_variant = create_variant;
+    config.salt_variant = salt_variant;
+    config.workgroup_size = record.workgroup_size;
+    let kernel = kernel_source(&config).lines().next().unwrap_or_default().to_string();
+    if kernel != record.kernel {
+        eprintln!("The record was mined with {}, dispatching {}", record.kernel, kernel);
+    }
+
+    let adapter = select_adapter(&config)?;
+    let (device, queue, _) = open_device(&adapter, &config)?;
+    let geometry = BatchGeometry::new(1, config.workgroup_size);
+    let matching = MatchingSetup::new(&config, &config.reward);
+    let pipeline = Pipeline::new(&device, &config, &geometry, 0, &matching.pattern_config());
+
+    let mut hashes = Vec::with_capacity(record.candidates.len());
+    for candidate in &record.candidates {
+        // The linear scan order mines the nonce at its own scan index
+        let nonce = candidate.nonce();
+        queue.write_buffer(&pipeline.message_buffer, 0, bytemuck::cast_slice(&[nonce as u32, (nonce >> 32) as u32]));
+        queue.write_buffer(&pipeline.num_messages_buffer, 0, bytemuck::cast_slice(&[1u32]));
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Replay Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Replay Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline.compute_pipeline);
+            compute_pass.set_bind_group(0, &pipeline.bind_group, &[]);
+            compute_pass.dispatch_workgroups(geometry.workgroups(1), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&pipeline.hash_buffer, 0, &pipeline.cpu_validation_buffer, 0, geometry.result_buffer_size());
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = pipeline.cpu_validation_buffer.slice(..);
+        if !map_read(&device, &buffer_slice) {
+            return Err(format!("could not read back the dispatch of nonce {}", nonce).into());
+        }
+        let row = bytemuck::cast_slice::<u8, u32>(&buffer_slice.get_mapped_range()).try_into()?;
+        pipeline.cpu_validation_buffer.unmap();
+        hashes.push(Candidate { index: candidate.index, row }.hash());
+    }
+    Ok(hashes)
+}
+
+/// How addresses are matched against a reward: the pattern of the CPU check, where matching
+/// happens, and the coarse prefix the kernel flags hashes with for CPU matching.
+struct MatchingSetup {
+    matcher: PatternMatcher,
+    placement: MatchPlacement,
+    coarse_prefix: Vec<u8>,
+    /// Pattern of a checksum reward, confirmed on the CPU after matching the lowercase bytes.
+    checksum_pattern: Option<Box<str>>,
+    /// The patterns of an any-pattern reward, each as its own reward, tried in order.
+    alternatives: Vec<(RewardVariant, PatternMatcher)>,
+}
+
+impl MatchingSetup {
+    fn new(config: &Config, reward: &RewardVariant) -> Self {
+        let matcher = match reward {
+            RewardVariant::Matching { pattern } => {
+                PatternMatcher::new(pattern)
+            },
+            // The bytes are matched like the lowercase pattern, the case is checked after
+            RewardVariant::ChecksumMatching { pattern } => {
+                PatternMatcher::new(&pattern.to_ascii_lowercase())
+            },
+            // Each pattern has its own matcher in the alternatives
+            RewardVariant::MatchingAny { .. } => PatternMatcher::for_flags(0, 0, 0),
+            // For backward compatibility, convert old reward types
+            RewardVariant::LeadingZeros { zeros_threshold } => {
+                // Convert leading zeros to a single value
+                PatternMatcher::for_flags(0u32, 4u32, (*zeros_threshold) as u32) // Flag 4 for legacy leading zeros
+            },
+            RewardVariant::LeadingZeroNibbles { threshold } => {
+                // Flag 9 counts the leading zeros in nibbles
+                PatternMatcher::for_flags(0u32, 9u32, (*threshold) as u32)
+            },
+            RewardVariant::TotalZeros { zeros_threshold } => {
+                // Convert total zeros to a single value
+                PatternMatcher::for_flags(0u32, 5u32, (*zeros_threshold) as u32) // Flag 5 for legacy total zeros
+            },
+            RewardVariant::LeadingAndTotalZeros { leading_zeros_threshold, total_zeros_threshold } => {
+                // Leading zeros in the value, total zeros in the length
+                PatternMatcher::for_flags((*leading_zeros_threshold) as u32, 6u32, (*total_zeros_threshold) as u32)
+            },
+            RewardVariant::LeadingOrTotalZeros { leading_zeros_threshold, total_zeros_threshold } => {
+                // Either of the leading zeros in the value and the total zeros in the length
+                PatternMatcher::for_flags((*leading_zeros_threshold) as u32, 7u32, (*total_zeros_threshold) as u32)
+            },
+            RewardVariant::SymmetricEnds { nibbles } => {
+                // Flag 8 compares the leading and trailing nibbles on the CPU
+                PatternMatcher::for_flags(0u32, 8u32, (*nibbles) as u32)
+            },
+        };
+
+        // Byte prefix rewards are matched by the kernel, which then only hands back the hits.
+        // The kernel only knows the standard address offset
+        let placement = if config.address_offset != ADDRESS_OFFSET {
+            MatchPlacement::Cpu
+        } else {
+            MatchPlacement::for_reward(reward)
+        };
+
+        // When matching on the CPU, the kernel flags where hashes pass a coarse prefix check so
+        // only those parts of the result buffer are read back
+        let coarse_prefix = match placement {
+            MatchPlacement::Cpu if config.address_offset == ADDRESS_OFFSET => {
+                coarse_prefix(reward)
             }
+            _ => vec![],
+        };
+
+        let checksum_pattern = match reward {
+            RewardVariant::ChecksumMatching { pattern } => Some(pattern.clone()),
+            _ => None,
+        };
+
+        let alternatives = match reward {
+            RewardVariant::MatchingAny { patterns } => patterns
+                .iter()
+                .map(|pattern| (RewardVariant::Matching { pattern: pattern.clone() }, PatternMatcher::new(pattern)))
+                .collect(),
+            _ => vec![],
+        };
+
+        Self { matcher, placement, coarse_prefix, checksum_pattern, alternatives }
+    }
+
+    /// Print where addresses are matched, and how the pattern was parsed unless `quiet`.
+    fn announce(&self, progress: Progress, quiet: bool) {
+        let alternatives = self.alternatives.iter().flat_map(|(_, matcher)| &matcher.notes);
+        for note in self.matcher.notes.iter().chain(alternatives).filter(|_| !quiet) {
+            progress!(progress, "{}", note);
         }
-    } else {
-        // For debugging: print all found addresses
-        println!("Found address: 0x{} (from salt: 0x{})", eth_address_hex, salt_hex);
+        progress!(progress, "Matching addresses on the {}", self.placement);
+        if !self.coarse_prefix.is_empty() {
+            progress!(progress, "Reading back only hashes starting with 0x{}", hex::encode(&self.coarse_prefix));
+        }
+    }
+
+    /// Words of the kernel's pattern config buffer.
+    fn pattern_config(&self) -> Vec<u32> {
+        let mut pattern_config = vec![
+            self.matcher.value,    // Main pattern value (prefix byte or zero count)
+            self.matcher.flags,    // Check of the CPU (4 to 9 for the zero and symmetric rewards)
+            self.matcher.length,   // Length of pattern to match
+        ];
+        pattern_config.extend(match self.coarse_prefix.is_empty() {
+            true => self.placement.kernel_config(),
+            false => coarse_kernel_config(&self.coarse_prefix),
+        });
+        pattern_config
+    }
+
+    /// Whether `address` matches on the CPU.
+    fn matches(&self, address: &[u8]) -> bool {
+        match &self.checksum_pattern {
+            Some(_) => self.confirms(address),
+            None if !self.alternatives.is_empty() => self.matched_patterns(address).next().is_some(),
+            None => check_eth_address_pattern(address, &self.matcher),
+        }
+    }
+
+    /// Indices of the patterns of an any-pattern reward that `address` matches.
+    fn matched_patterns<'s>(&'s self, address: &'s [u8]) -> impl Iterator<Item = usize> + 's {
+        self.alternatives
+            .iter()
+            .enumerate()
+            .filter(move |(_, (_, matcher))| check_eth_address_pattern(address, matcher))
+            .map(|(index, _)| index)
+    }
+
+    /// What an any-pattern result at `address` was found for: the pattern it matches, or the
+    /// patterns when it matches several. None for other rewards.
+    fn kept_for(&self, address: &[u8]) -> Option<RewardVariant> {
+        let mut patterns: Vec<Box<str>> = self
+            .matched_patterns(address)
+            .map(|index| match &self.alternatives[index].0 {
+                RewardVariant::Matching { pattern } => pattern.clone(),
+                reward => reward.to_string().into(),
+            })
+            .collect();
+        match patterns.len() {
+            0 => None,
+            1 => Some(RewardVariant::Matching { pattern: patterns.remove(0) }),
+            _ => Some(RewardVariant::MatchingAny { patterns }),
+        }
+    }
+
+    /// Whether `address` counts towards the `limit` of a pattern it matches still short of it,
+    /// adding it to the results in `found` of every pattern it matches if so.
+    fn counts_towards(&self, address: &[u8], limit: u64, found: &mut [u64]) -> bool {
+        let matched: Vec<usize> = self.matched_patterns(address).collect();
+        let counts = matched.iter().any(|&index| found[index] < limit);
+        if counts {
+            matched.iter().for_each(|&index| found[index] += 1);
+        }
+        counts
     }
 
-    matches_found
+    /// Whether `address`, a hit of the kernel's prefix match, also has the case of a checksum
+    /// reward. Every other reward is settled by the bytes.
+    fn confirms(&self, address: &[u8]) -> bool {
+        match (&self.checksum_pattern, <&[u8; 20]>::try_from(address)) {
+            (Some(pattern), Ok(address)) => checksum_matches(address, pattern),
+            (Some(_), Err(_)) => false,
+            (None, _) => true,
+        }
+    }
 }
 
-// Optimize the check_eth_address_pattern function for better performance
-fn check_eth_address_pattern(address_bytes: &[u8], pattern_value: u8, pattern_flags: u32, pattern_length: u32) -> bool {
-    // Fast path for common case - empty address
-    if address_bytes.is_empty() {
-        return false;
+/// Flags of a pattern matched on the hex rather than the bytes, as its parts aren't whole bytes.
+const PATTERN_HEX: u32 = 99;
+
+/// A reward parsed for check_eth_address_pattern. `--matching` patterns are matched on their
+/// leading and trailing bytes, the other rewards by the byte-level checks of their flags.
+struct PatternMatcher {
+    value: u32,
+    flags: u32,
+    length: u32,
+    /// Leading and trailing bytes of a pattern.
+    bytes: Option<PatternBytes>,
+    /// Lowercase hex parts of a pattern that isn't whole bytes.
+    prefix: String,
+    suffix: String,
+    /// How the pattern was parsed, printed by [`MatchingSetup::announce`].
+    notes: Vec<String>,
+}
+
+impl PatternMatcher {
+    fn new(pattern: &str) -> Self {
+        let mut notes = vec![];
+        let bytes = parse_pattern(pattern, &mut notes);
+        let (prefix, suffix) = pattern.split_once("...").unwrap_or((pattern, ""));
+        Self {
+            value: 0,
+            flags: if bytes.is_some() { 0 } else { PATTERN_HEX },
+            length: 0,
+            bytes,
+            prefix: prefix.to_lowercase(),
+            suffix: suffix.to_lowercase(),
+            notes,
+        }
     }
-    
-    // Special case for non-repeating patterns (pattern_flags = 99)
-    if pattern_flags == 99 {
-        // Use cached pattern parts rather than parsing the pattern every time
-        static mut PREFIX: Option<String> = None;
-        static mut SUFFIX: Option<String> = None;
-        
-        unsafe {
-            // Initialize pattern parts if needed
-            if PREFIX.is_none() || SUFFIX.is_none() {
-                if let Some(pattern_str) = &RAW_PATTERN {
-                    if pattern_str.contains("...") {
-                        let parts: Vec<&str> = pattern_str.split("...").collect();
-                        if parts.len() == 2 {
-                            PREFIX = Some(parts[0].to_lowercase());
-                            SUFFIX = Some(parts[1].to_lowercase());
+
+    /// Matcher of the byte-level checks of `flags`, without a pattern.
+    fn for_flags(value: u32, flags: u32, length: u32) -> Self {
+        Self { value, flags, length, bytes: None, prefix: String::new(), suffix: String::new(), notes: vec![] }
+    }
+}
+
+/// Renders verified matches as lines of the output file, so every format shares the collection
+/// of matches in the mining loop.
+struct MatchWriter<'a> {
+    config: &'a Config<'a>,
+}
+
+impl MatchWriter<'_> {
+    /// The line of a match mined for `reward`, found in the cycle ending at `attempt` attempts.
+    /// Matches of an any-pattern run are mined for the pattern they matched, which text and
+    /// JSON lines name after the address, with the `pattern_lines` of a `--pattern-file`.
+    fn line(&self, salt: &[u8; 32], address: &str, reward: &RewardVariant, pattern_lines: &[usize], attempt: u64) -> String {
+        let config = self.config;
+        match (config.format, &config.init_code, config.emit_salt_format) {
+            (OutputFormat::SafeTx, Some(init_code), _) => {
+                let calldata = safe::deploy_calldata(&config.create_variant, salt, init_code);
+                safe::transaction(&config.factory_address, &calldata).to_string()
+            }
+            // both formats record the same fields, which a third one would only lay out anew
+            (format @ (OutputFormat::JsonLines | OutputFormat::Csv), _, salt_format) => {
+                let found_at_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                let mut result = FoundSalt::new(salt, address, &config.factory_address, &config.create_variant, reward, found_at_unix, attempt);
+                result.pattern_lines = pattern_lines.to_vec();
+                if config.redact_params {
+                    result.factory = params::factory(config);
+                    result.create_variant = params::create_variant(config);
+                }
+                if let Some(salt_format) = salt_format {
+                    result = result.with_salt_format(salt_format, salt);
+                }
+                match format {
+                    OutputFormat::Csv => result.to_csv_row(),
+                    _ => result.to_line(),
+                }
+            }
+            (format, _, salt_format) => {
+                let line = match salt_format {
+                    Some(salt_format) => format.format_result_with_salt(salt, address, salt_format),
+                    None => format.format_result(&format!("0x{}", hex::encode(salt)), address),
+                };
+                let tagged = matches!(config.reward, RewardVariant::MatchingAny { .. }) || !pattern_lines.is_empty();
+                match (tagged, format) {
+                    (true, OutputFormat::Text) if pattern_lines.is_empty() => format!("{} {}", line, reward),
+                    (true, OutputFormat::Text) => {
+                        let lines: Vec<String> = pattern_lines.iter().map(usize::to_string).collect();
+                        format!("{} {} (line {})", line, reward, lines.join(","))
+                    }
+                    (true, _) => {
+                        let mut result: serde_json::Value = serde_json::from_str(&line).expect("results are JSON");
+                        result["pattern"] = reward.to_string().into();
+                        if !pattern_lines.is_empty() {
+                            result["pattern_lines"] = pattern_lines.into();
                         }
+                        result.to_string()
                     }
+                    (false, _) => line,
                 }
             }
-            
-            // Get cached pattern parts
-            if let (Some(prefix), Some(suffix)) = (&PREFIX, &SUFFIX) {
-                // Compute address hex only once
-                let address_hex = hex::encode(address_bytes);
-                
-                // Fast check - if not starting with prefix or ending with suffix, return early
-                if !address_hex.starts_with(prefix) || !address_hex.ends_with(suffix) {
-                    return false;
-                }
-                
-                // Both prefix and suffix match
-                return true;
+        }
+    }
+}
+
+/// Saves the checkpoints offered by the mining loop once the output writer handled the results
+/// found before them.
+struct CheckpointSaver {
+    pending: PendingCheckpoint,
+    path: String,
+}
+
+impl CheckpointSaver {
+    fn save_ready(&self, handled: u64) {
+        if let Some(checkpoint) = self.pending.take_ready(handled) {
+            if let Err(e) = checkpoint.save(std::path::Path::new(&self.path)) {
+                eprintln!("Error writing checkpoint {}: {}", self.path, e);
             }
         }
-        
-        // Fallback - use full pattern
-        let address_hex = hex::encode(address_bytes);
-        
-        // Fix the temporary value issue by creating a longer-lived empty string
-        let empty_str = String::new();
-        let pattern_str = unsafe { 
-            match &RAW_PATTERN {
-                Some(p) => p,
-                None => &empty_str,
+    }
+}
+
+/// Subscriber appending the results of a run to its output file.
+struct OutputWriter {
+    results: ResultWriter<std::fs::File, std::io::Stderr>,
+    /// Kept to flush and unlock the output file once the last result is written
+    output: std::fs::File,
+    output_path: String,
+    duplicate_policy: DuplicatePolicy,
+    /// Raised once addresses found again with other salts point at a derivation bug
+    anomaly: Arc<AtomicBool>,
+    checkpoints: CheckpointSaver,
+    handled: u64,
+    progress: Progress,
+}
+
+impl OutputWriter {
+    fn new(
+        file: std::fs::File,
+        config: &Config,
+        checkpoints: CheckpointSaver,
+        anomaly: Arc<AtomicBool>,
+        progress: Progress,
+    ) -> Result<Self, String> {
+        let output_path = config.output.to_string();
+        let output = file
+            .try_clone()
+            .map_err(|e| format!("could not open {}: {}", output_path, e))?;
+        // Once the disk is full, results are kept in memory and printed to stderr instead
+        let mut results =
+            ResultWriter::new(file, std::io::stderr(), writer::EMERGENCY_BUFFER_RESULTS);
+        if config.redact_salts {
+            results = results.withholding();
+        }
+        // Results already in the output file, from before a restart, are not written again, and
+        // their recent addresses are checked for being found again with other salts
+        let mut recent = RecentAddresses::new(writer::RECENT_ADDRESSES);
+        if !config.redact_salts {
+            match SaltIndex::open(std::path::Path::new(&output_path)) {
+                Ok((index, rebuilt)) => {
+                    if rebuilt && !index.is_empty() {
+                        progress!(progress, "Rebuilt the salt index of {} ({} results)", output_path, index.len());
+                    }
+                    match index.recent_addresses(writer::RECENT_ADDRESSES) {
+                        Ok(addresses) => addresses.iter().for_each(|address| {
+                            recent.insert(address);
+                        }),
+                        Err(e) => eprintln!("Could not read the addresses of {}: {}", output_path, e),
+                    }
+                    results = results.indexed(index);
+                }
+                Err(e) => eprintln!("Could not open the salt index of {}: {}", output_path, e),
             }
-        };
-        
-        if pattern_str.contains("...") {
-            let parts: Vec<&str> = pattern_str.split("...").collect();
-            if parts.len() == 2 {
-                let prefix = parts[0].to_lowercase();
-                let suffix = parts[1].to_lowercase();
-                
-                return address_hex.starts_with(&prefix) && address_hex.ends_with(&suffix);
+        }
+        if let Some(split) = config.split_output {
+            let headers = config.format == OutputFormat::Text;
+            let path = std::path::Path::new(&output_path);
+            let mut split = SplitOutput::new(split, path, writer::MAX_OPEN_BUCKETS, headers);
+            if config.format == OutputFormat::Csv {
+                split = split.with_header_row(csv_header(config.emit_salt_format));
             }
+            results = results.splitting(split);
         }
-        
-        return address_hex.starts_with(&pattern_str.to_lowercase());
+        results = results.detecting_duplicates(recent, config.on_duplicate_address);
+
+        Ok(Self {
+            results,
+            output,
+            output_path,
+            duplicate_policy: config.on_duplicate_address,
+            anomaly,
+            checkpoints,
+            handled: 0,
+            progress,
+        })
     }
-    
-    // For other pattern types, use optimized byte-level checks instead of string conversions
-    match pattern_flags {
-        0 | 1 => {
-            // Simple pattern - just match the first byte
-            // This is extremely fast - just a single byte comparison
-            return address_bytes[0] == pattern_value;
-        },
-        2 => {
-            // Trailing pattern - check last byte
-            return address_bytes[address_bytes.len() - 1] == pattern_value;
-        },
-        3 => {
-            // Both leading and trailing - check first and last bytes are the same
-            return address_bytes[0] == pattern_value && 
-                   address_bytes[address_bytes.len() - 1] == pattern_value;
-        },
-        4 => {
-            // Multiple leading bytes - check the first N bytes
-            let num_bytes = pattern_length as usize;
-            
-            // Fast path - often we check just 1-2 bytes
-            if num_bytes == 1 {
-                return address_bytes[0] == pattern_value;
-            } else if num_bytes == 2 {
-                return address_bytes[0] == pattern_value && address_bytes[1] == pattern_value;
+}
+
+impl Subscriber for OutputWriter {
+    fn handle(&mut self, event: &MinerEvent) {
+        if let MinerEvent::Found(result) = event {
+            self.results.write_result(&result.line, &result.address);
+            if self.results.duplicate_anomaly() {
+                self.anomaly.store(true, Ordering::Relaxed);
             }
-            
-            // Slow path for more bytes
-            for i in 0..num_bytes {
-                if i >= address_bytes.len() || address_bytes[i] != pattern_value {
-                    return false;
-                }
+            self.handled += 1;
+        }
+    }
+
+    fn tick(&mut self, dropped: u64) {
+        // dropped results will never be written, so they don't hold checkpoints back
+        self.checkpoints.save_ready(self.handled + dropped);
+    }
+
+    fn finish(self) -> Result<(), String> {
+        let progress = self.progress;
+        self.checkpoints.save_ready(u64::MAX);
+        if self.results.duplicates() > 0 {
+            progress!(progress, "Skipped {} results already in {}", self.results.duplicates(), self.output_path);
+        }
+        if self.results.duplicate_addresses() > 0 {
+            let handled = match self.duplicate_policy {
+                DuplicatePolicy::FirstOnly => "skipped",
+                DuplicatePolicy::KeepAll | DuplicatePolicy::Warn => "written",
+            };
+            progress!(progress,
+                "Found {} addresses again with other salts ({} by --on-duplicate-address {})",
+                self.results.duplicate_addresses(),
+                handled,
+                self.duplicate_policy
+            );
+        }
+        let finished = self.results.finish();
+        let closed = self.output.sync_data().and_then(|_| redact::unlock_output(&self.output));
+        if let Err(e) = closed {
+            eprintln!("Error closing {}: {}", self.output_path, e);
+        }
+        finished
+    }
+}
+
+/// Subscriber keeping the output file a Safe batch of the deploy transactions of a run's
+/// results. The batch is rewritten with every transaction so the file always imports.
+struct SafeBatchWriter {
+    batch: safe::SafeBatch,
+    output_path: String,
+    redact_salts: bool,
+    checkpoints: CheckpointSaver,
+    handled: u64,
+    /// Why the batch stopped taking transactions.
+    failed: Option<String>,
+}
+
+impl SafeBatchWriter {
+    fn new(chain_id: u64, config: &Config, checkpoints: CheckpointSaver) -> Self {
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Self {
+            batch: safe::SafeBatch::new(chain_id, created_at),
+            output_path: config.output.to_string(),
+            redact_salts: config.redact_salts,
+            checkpoints,
+            handled: 0,
+            failed: None,
+        }
+    }
+}
+
+impl Subscriber for SafeBatchWriter {
+    fn handle(&mut self, event: &MinerEvent) {
+        let MinerEvent::Found(result) = event else {
+            return;
+        };
+        self.handled += 1;
+        if self.failed.is_some() {
+            return;
+        }
+        match serde_json::from_str(&result.line) {
+            Ok(transaction) => self.batch.push(transaction),
+            Err(e) => {
+                self.failed = Some(e.to_string());
+                return;
             }
-            return true;
-        },
-        5 => {
-            // Complex pattern with different leading and trailing bytes
-            let leading_byte = pattern_value;
-            let trailing_byte = (pattern_value as u32 >> 8) as u8;
-            
-            // Fast path - check first and last byte directly
-            if address_bytes[0] != leading_byte || address_bytes[address_bytes.len() - 1] != trailing_byte {
-                return false;
+        }
+        let path = std::path::Path::new(&self.output_path);
+        let saved = self.batch.save(path).and_then(|_| match self.redact_salts {
+            true => redact::restrict_permissions(path),
+            false => Ok(()),
+        });
+        if let Err(e) = saved {
+            // the transaction holds the salt
+            let unsaved = if self.redact_salts { "withheld by --redact-salts" } else { &result.line };
+            eprintln!("Error writing Safe batch: {}, unsaved transaction: {}", e, unsaved);
+        }
+    }
+
+    fn tick(&mut self, dropped: u64) {
+        self.checkpoints.save_ready(self.handled + dropped);
+    }
+
+    fn finish(self) -> Result<(), String> {
+        self.checkpoints.save_ready(u64::MAX);
+        self.failed.map_or(Ok(()), Err)
+    }
+}
+
+/// Subscriber printing the results of a `--no-file` run to stdout, a line each in the
+/// `--format` of the run, after the header row of CSV.
+struct StdoutWriter {
+    header: Option<String>,
+    /// The first error printing a result, failing the run once it is done.
+    failed: Option<String>,
+}
+
+impl StdoutWriter {
+    fn new(config: &Config) -> Self {
+        let header = (config.format == OutputFormat::Csv).then(|| csv_header(config.emit_salt_format));
+        Self { header, failed: None }
+    }
+}
+
+impl Subscriber for StdoutWriter {
+    fn handle(&mut self, event: &MinerEvent) {
+        let MinerEvent::Found(result) = event else {
+            return;
+        };
+        let mut stdout = std::io::stdout().lock();
+        let printed = match self.header.take() {
+            Some(header) => writeln!(stdout, "{}", header),
+            None => Ok(()),
+        };
+        let printed = printed.and_then(|_| writeln!(stdout, "{}", result.line)).and_then(|_| stdout.flush());
+        if let Err(e) = printed {
+            self.failed.get_or_insert_with(|| format!("could not print results: {}", e));
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        self.failed.map_or(Ok(()), Err)
+    }
+}
+
+/// Subscriber showing the progress of a run on the terminal every status tick.
+struct StatusDisplay {
+    term: console::Term,
+    /// Journals and log files get one plain line per update instead of a redrawn block
+    plain: bool,
+    work_size: u32,
+    create_variant: String,
+    cost: Option<stats::CostModel>,
+}
+
+impl Subscriber for StatusDisplay {
+    fn handle(&mut self, event: &MinerEvent) {
+        let MinerEvent::StatusTick(status) = event else {
+            return;
+        };
+        let elapsed = status.elapsed;
+        let rate = status.rate / 1_000_000.0;
+
+        if self.plain {
+            let eta = match stats::expected_duration(status.expected_attempts, status.rate) {
+                Some(eta) => format!("{}s", eta.as_secs()),
+                None => "unknown".to_string(),
+            };
+            let mut line = format!(
+                "runtime={}s cycles={} rate={:.2}MH/s found={} index={} dropped={} eta={} explored={:.1}%",
+                elapsed.as_secs(),
+                status.cycles,
+                rate,
+                status.found,
+                status.nonce,
+                status.dropped,
+                eta,
+                status.explored * 100.0
+            );
+            if let Some(readback_saved) = status.readback_saved {
+                line.push_str(&format!(" readback_saved={}", readback_saved));
             }
-            
-            // Additional checks for multiple leading bytes if needed
-            let num_bytes = pattern_length as usize;
-            if num_bytes > 1 {
-                for i in 1..num_bytes {
-                    if i >= address_bytes.len() || address_bytes[i] != leading_byte {
-                        return false;
-                    }
-                }
+            if status.reserved_rejected > 0 {
+                line.push_str(&format!(" reserved_rejected={}", status.reserved_rejected));
             }
-            
-            return true;
-        },
-        _ => {
-            // For unrecognized pattern flags, default to matching "abcd" prefix and "ef" suffix
-            let address_hex = hex::encode(address_bytes);
-            return address_hex.starts_with("abcd") && address_hex.ends_with("ef");
+            let _ = self.term.write_line(&line);
+            return;
+        }
+
+        // One print per block, so lines printed by the mining loop never land inside it
+        let mut block = format!(
+            "total runtime: {:02}:{:02}:{:02.2} ({} cycles)                      work size per cycle: {}",
+            elapsed.as_secs() / 3600,
+            (elapsed.as_secs() % 3600) / 60,
+            (elapsed.as_secs() % 60) as f64 + (elapsed.subsec_nanos() as f64 / 1_000_000_000.0),
+            status.cycles,
+            self.work_size
+        );
+        block.push_str(&format!("\nrate: {:.2} million attempts per second                  total found this run: {}", rate, status.found));
+        block.push_str(&format!(
+            "\nexpected time to a match: {}            odds explored so far: {:.1}%",
+            format_eta(status.expected_attempts, status.rate),
+            status.explored * 100.0
+        ));
+        if status.dropped > 0 {
+            block.push_str(&format!("\ndropped {} matches while the output writer was behind", status.dropped));
+        }
+        if let Some(readback_saved) = status.readback_saved {
+            block.push_str(&format!("\nreadback saved by the match bitmap: {:.1} MB", readback_saved as f64 / 1_000_000.0));
+        }
+        if status.reserved_rejected > 0 {
+            block.push_str(&format!("\nrejected {} reserved addresses", status.reserved_rejected));
+        }
+        if let Some(cost) = self
+            .cost
+            .and_then(|cost| cost.cost_per_result(status.expected_attempts, status.rate))
+        {
+            block.push_str(&format!("\nprojected cost per result: {}", stats::format_cost(cost)));
+        }
+        block.push_str(&format!(
+            "\ncurrent search space: {}            threshold: mining {} for {}",
+            status.nonce, self.create_variant, status.reward
+        ));
+
+        let _ = self.term.clear_line();
+        let _ = self.term.write_str(&block);
+    }
+}
+
+/// `items` separated by commas, or `none`.
+fn list_or_none(items: &[String]) -> String {
+    match items.is_empty() {
+        true => "none".to_string(),
+        false => items.join(", "),
+    }
+}
+
+/// Expected time to the next match at `rate` attempts per second. Every attempt is a fresh draw,
+/// so the time already spent doesn't shorten it.
+fn format_eta(expected_attempts: f64, rate: f64) -> String {
+    if rate <= 0.0 {
+        return "measuring".to_string();
+    }
+    match stats::expected_duration(expected_attempts, rate) {
+        Some(eta) => schedule::format_duration(eta),
+        None => "beyond reach at this rate".to_string(),
+    }
+}
+
+/// Write the forensic dump of a `--strict` run whose GPU and CPU disagree, next to the output
+/// file or to stderr with `--no-file`. Returns the error the run ends with.
+fn halt_strict(config: &Config, disagreement: &forensics::Disagreement, cycle: u64, results: &[u32], adapter_info: &wgpu::AdapterInfo) -> String {
+    let adapter = format!("{} ({:?}, {} {})", adapter_info.name, adapter_info.backend, adapter_info.driver, adapter_info.driver_info);
+    let dump = forensics::ForensicDump::new(config, disagreement, cycle, results, &adapter);
+    let message = format!(
+        "--strict: the GPU derived 0x{} from nonce {} in cycle {}, the CPU 0x{}",
+        hex::encode(disagreement.gpu_hash),
+        disagreement.nonce,
+        cycle,
+        hex::encode(disagreement.cpu_hash)
+    );
+    if config.no_file {
+        eprintln!("{}", serde_json::to_string_pretty(&dump).expect("dumps are JSON"));
+        return format!("{}, forensic dump printed above", message);
+    }
+    let path = forensics::dump_path(config.output);
+    match dump.write(std::path::Path::new(&path)) {
+        Ok(()) => format!("{}, forensic dump written to {}", message, path),
+        Err(e) => format!("{}, could not write the forensic dump {}: {}", message, path, e),
+    }
+}
+
+/// Map `slice` for reading and wait for the GPU. Returns whether the mapping succeeded.
+fn map_read(device: &wgpu::Device, slice: &wgpu::BufferSlice) -> bool {
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).unwrap();
+    });
+    device.poll(wgpu::Maintain::Wait);
+    matches!(pollster::block_on(receiver.receive()), Some(Ok(())))
+}
+
+/// The first `words` words of the match bitmap copied back from the kernel.
+fn read_bitmap(device: &wgpu::Device, readback: &wgpu::Buffer, words: u32) -> Vec<u32> {
+    let slice = readback.slice(..words as u64 * 4);
+    if !map_read(device, &slice) {
+        return vec![];
+    }
+    let bitmap = bytemuck::cast_slice::<u8, u32>(&slice.get_mapped_range()).to_vec();
+    readback.unmap();
+    bitmap
+}
+
+/// Number of hits the kernel reported in the last cycle when matching on the GPU.
+fn read_match_count(device: &wgpu::Device, readback: &wgpu::Buffer) -> u32 {
+    let slice = readback.slice(..);
+    if !map_read(device, &slice) {
+        return 0;
+    }
+    let count = bytemuck::cast_slice::<u8, u32>(&slice.get_mapped_range())[0];
+    readback.unmap();
+    count
+}
+
+/// Warn once the output filesystem drops below `LOW_DISK_SPACE_BYTES`, unless `warned` says
+/// this already happened. Returns whether the warning has been raised.
+fn check_disk_space(warnings: &mut Warnings, bus: &EventBus, output: &str, warned: bool) -> bool {
+    if warned {
+        return true;
+    }
+    match writer::available_space(std::path::Path::new(output)) {
+        Ok(available) if available < writer::LOW_DISK_SPACE_BYTES => {
+            emit_warning(warnings, bus, Warning::LowDiskSpace { available });
+            true
         }
+        _ => false,
+    }
+}
+
+fn emit_warning(warnings: &mut Warnings, bus: &EventBus, warning: Warning) {
+    if warnings.emit(warning.clone()) {
+        eprintln!("{}", warning);
+        bus.publish(MinerEvent::Warning(warning));
+    }
+}
+
+/// The leading and trailing bytes of `pattern`, none if it isn't whole hex bytes and is matched
+/// on the hex instead.
+fn parse_pattern(pattern: &str, notes: &mut Vec<String>) -> Option<PatternBytes> {
+    notes.push(format!("Raw pattern: '{}'", pattern));
+
+    let bytes = PatternBytes::parse(pattern);
+    match &bytes {
+        Some(bytes) => notes.push(format!(
+            "Detected pattern: {} leading bytes 0x{} + {} trailing bytes 0x{}",
+            bytes.leading.len(),
+            hex::encode(&bytes.leading),
+            bytes.trailing.len(),
+            hex::encode(&bytes.trailing)
+        )),
+        None => notes.push(format!("Detected pattern of partial bytes, matched on the hex: {}", pattern)),
+    }
+    bytes
+}
+
+fn check_eth_address_pattern(address_bytes: &[u8], matcher: &PatternMatcher) -> bool {
+    let pattern_length = matcher.length as usize;
+
+    // Fast path for common case - empty address
+    if address_bytes.is_empty() {
+        return false;
+    }
+
+    // Patterns compare their leading and trailing bytes, whatever their lengths
+    if let Some(bytes) = &matcher.bytes {
+        return bytes.matches(address_bytes);
+    }
+    if matcher.flags == PATTERN_HEX {
+        let address_hex = hex::encode(address_bytes);
+        return address_hex.len() >= matcher.prefix.len() + matcher.suffix.len()
+            && address_hex.starts_with(&matcher.prefix)
+            && address_hex.ends_with(&matcher.suffix);
+    }
+
+    let leading_zeros = |count: usize| count <= address_bytes.len() && address_bytes[..count].iter().all(|&b| b == 0);
+    let total_zeros = |count: usize| address_bytes.iter().filter(|&&b| b == 0).count() >= count;
+    match matcher.flags {
+        4 => leading_zeros(pattern_length),
+        5 => total_zeros(pattern_length),
+        6 => leading_zeros(matcher.value as usize) && total_zeros(pattern_length),
+        7 => leading_zeros(matcher.value as usize) || total_zeros(pattern_length),
+        8 => {
+            // Symmetric ends - the first N nibbles repeat at the end of the address
+            has_symmetric_ends(address_bytes, pattern_length as u8)
+        },
+        9 => has_leading_zero_nibbles(address_bytes, pattern_length as u8),
+        _ => false,
     }
 }
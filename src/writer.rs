@@ -0,0 +1,523 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    index::{self, SaltIndex},
+    score,
+};
+
+/// Free space on the output filesystem below which a warning is emitted (64 MiB).
+pub const LOW_DISK_SPACE_BYTES: u64 = 64 << 20;
+
+/// Results kept in memory once the output file can no longer be written.
+pub const EMERGENCY_BUFFER_RESULTS: usize = 10_000;
+
+/// Bytes available to this user on the filesystem holding `output`.
+pub fn available_space(output: &Path) -> io::Result<u64> {
+    let dir = match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs4::available_space(dir)
+}
+
+/// Whether `error` means the disk is full (ENOSPC) or the user's quota is exhausted.
+pub fn is_disk_full(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded
+    )
+}
+
+/// Bucket files open at once under `--split-output-by`, the least recently used is closed.
+pub const MAX_OPEN_BUCKETS: usize = 16;
+
+/// How `--split-output-by` groups results into bucket files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// By leading zero bytes, the unit of `--leading`: `results.lz4.txt`.
+    LeadingZeros,
+    /// By leading zero nibbles, the first criterion of the result ranking: `results.tier9.txt`.
+    ScoreTier,
+}
+
+impl SplitBy {
+    /// Name of the bucket of `address`.
+    pub fn bucket(&self, address: &[u8; 20]) -> String {
+        match self {
+            SplitBy::LeadingZeros => {
+                let zeros = address.iter().take_while(|byte| **byte == 0).count();
+                format!("lz{}", zeros)
+            }
+            SplitBy::ScoreTier => format!("tier{}", score(address) / 64),
+        }
+    }
+
+    fn describe(&self, bucket: &str) -> String {
+        match self {
+            SplitBy::LeadingZeros => format!(
+                "addresses with {} leading zero bytes",
+                bucket.trim_start_matches("lz")
+            ),
+            SplitBy::ScoreTier => format!(
+                "addresses with {} leading zero nibbles",
+                bucket.trim_start_matches("tier")
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SplitBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitBy::LeadingZeros => write!(f, "leading-zeros"),
+            SplitBy::ScoreTier => write!(f, "score-tier"),
+        }
+    }
+}
+
+impl FromStr for SplitBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leading-zeros" => Ok(SplitBy::LeadingZeros),
+            "score-tier" => Ok(SplitBy::ScoreTier),
+            _ => Err(format!(
+                "unknown split {} (expected leading-zeros or score-tier)",
+                s
+            )),
+        }
+    }
+}
+
+/// Path of the `bucket` file of the output file at `output`: the bucket goes before the
+/// extension, so `results.txt` splits into `results.lz4.txt`.
+pub fn bucket_path(output: &Path, bucket: &str) -> PathBuf {
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => output.with_extension(format!("{}.{}", bucket, extension)),
+        None => output.with_extension(bucket),
+    }
+}
+
+/// Whether the file `name` belongs to the split set of the output file at `output`.
+pub fn is_bucket_of(output: &Path, name: &str) -> bool {
+    let Some(stem) = output.file_stem().and_then(|stem| stem.to_str()) else {
+        return false;
+    };
+    let rest = name
+        .strip_prefix(stem)
+        .and_then(|rest| rest.strip_prefix('.'));
+    let bucket = match output.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => rest
+            .and_then(|rest| rest.strip_suffix(extension))
+            .and_then(|rest| rest.strip_suffix('.')),
+        None => rest,
+    };
+    bucket
+        .and_then(|bucket| {
+            bucket
+                .strip_prefix("lz")
+                .or_else(|| bucket.strip_prefix("tier"))
+        })
+        .is_some_and(|digits| {
+            !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit())
+        })
+}
+
+/// Routes results into per-bucket files next to the output file. Bucket files are created on
+/// their first result, truncating any file left by an earlier run, and only the most recently
+/// used `max_open` stay open.
+pub struct SplitOutput {
+    split: SplitBy,
+    output: PathBuf,
+    max_open: usize,
+    /// Whether bucket files start with a comment header.
+    headers: bool,
+    /// First line of bucket files, the header row of CSV output.
+    header_row: Option<String>,
+    /// Open bucket files, least recently used first.
+    open: VecDeque<(String, File)>,
+    created: HashSet<String>,
+}
+
+impl SplitOutput {
+    pub fn new(split: SplitBy, output: &Path, max_open: usize, headers: bool) -> Self {
+        Self {
+            split,
+            output: output.to_path_buf(),
+            max_open: max_open.max(1),
+            headers,
+            header_row: None,
+            open: VecDeque::new(),
+            created: HashSet::new(),
+        }
+    }
+
+    /// Start bucket files with `header_row`.
+    pub fn with_header_row(mut self, header_row: String) -> Self {
+        self.header_row = Some(header_row);
+        self
+    }
+
+    /// Append `line`, the result for `address`, to its bucket file.
+    pub fn write(&mut self, line: &str, address: &[u8; 20]) -> io::Result<()> {
+        let bucket = self.split.bucket(address);
+        let file = match self.open.iter().position(|(name, _)| *name == bucket) {
+            Some(index) => {
+                let entry = self.open.remove(index).unwrap();
+                self.open.push_back(entry);
+                &mut self.open.back_mut().unwrap().1
+            }
+            None => {
+                if self.open.len() == self.max_open {
+                    self.open.pop_front();
+                }
+                let file = self.open_bucket(&bucket)?;
+                self.open.push_back((bucket, file));
+                &mut self.open.back_mut().unwrap().1
+            }
+        };
+        writeln!(file, "{}", line)?;
+        file.flush()
+    }
+
+    fn open_bucket(&mut self, bucket: &str) -> io::Result<File> {
+        let path = bucket_path(&self.output, bucket);
+        if self.created.contains(bucket) {
+            return OpenOptions::new().append(true).open(path);
+        }
+
+        let mut file = File::create(&path)?;
+        if self.headers {
+            writeln!(
+                file,
+                "# Bucket {} of {}: {}",
+                bucket,
+                self.output.display(),
+                self.split.describe(bucket)
+            )?;
+            writeln!(file, "# Format: salt -> ethereum_address")?;
+            writeln!(file)?;
+        }
+        if let Some(header_row) = &self.header_row {
+            writeln!(file, "{}", header_row)?;
+        }
+        self.created.insert(bucket.to_string());
+        Ok(file)
+    }
+
+    /// Buckets with a file open, least recently used first.
+    pub fn open_buckets(&self) -> Vec<&str> {
+        self.open.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Buckets created by this run.
+    pub fn created_buckets(&self) -> Vec<&str> {
+        let mut created: Vec<&str> = self.created.iter().map(String::as_str).collect();
+        created.sort();
+        created
+    }
+}
+
+/// Addresses remembered by duplicate address detection, the recent results of earlier runs
+/// included.
+pub const RECENT_ADDRESSES: usize = 1 << 16;
+
+/// Addresses found again with another salt before a run is flagged as a derivation bug, at
+/// least [`ANOMALY_MIN_DUPLICATES`] and [`ANOMALY_DUPLICATE_PERCENT`] of the results. Random
+/// salts essentially never collide on an address, so any steady rate points at the derivation.
+pub const ANOMALY_MIN_DUPLICATES: u64 = 3;
+pub const ANOMALY_DUPLICATE_PERCENT: u64 = 1;
+
+/// What `--on-duplicate-address` does with a result whose address was already written with
+/// another salt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Write it like any other result.
+    KeepAll,
+    /// Skip it, keeping the first salt found for the address.
+    FirstOnly,
+    /// Write it and report it on stderr.
+    #[default]
+    Warn,
+}
+
+impl fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuplicatePolicy::KeepAll => write!(f, "keep-all"),
+            DuplicatePolicy::FirstOnly => write!(f, "first-only"),
+            DuplicatePolicy::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-all" => Ok(DuplicatePolicy::KeepAll),
+            "first-only" => Ok(DuplicatePolicy::FirstOnly),
+            "warn" => Ok(DuplicatePolicy::Warn),
+            _ => Err(format!(
+                "unknown duplicate address policy {} (expected keep-all, first-only or warn)",
+                s
+            )),
+        }
+    }
+}
+
+/// The most recently seen `capacity` addresses, the least recently seen forgotten first.
+pub struct RecentAddresses {
+    capacity: usize,
+    order: VecDeque<[u8; 20]>,
+    seen: HashSet<[u8; 20]>,
+}
+
+impl RecentAddresses {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Remember `address`, returning whether it was already remembered.
+    pub fn insert(&mut self, address: &[u8; 20]) -> bool {
+        if self.seen.contains(address) {
+            let index = self.order.iter().position(|seen| seen == address).unwrap();
+            self.order.remove(index);
+            self.order.push_back(*address);
+            return true;
+        }
+        if self.order.len() == self.capacity {
+            let forgotten = self.order.pop_front().unwrap();
+            self.seen.remove(&forgotten);
+        }
+        self.order.push_back(*address);
+        self.seen.insert(*address);
+        false
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Whether `duplicates` addresses found again among `results` point at a derivation bug, see
+/// [`ANOMALY_MIN_DUPLICATES`].
+pub fn is_duplicate_anomaly(results: u64, duplicates: u64) -> bool {
+    duplicates >= ANOMALY_MIN_DUPLICATES && duplicates * 100 >= results * ANOMALY_DUPLICATE_PERCENT
+}
+
+/// Writes result lines to the output file. Once the disk fills up, results are kept in a
+/// bounded in-memory ring buffer and echoed to `stderr` instead of being lost, and
+/// [`ResultWriter::finish`] dumps them and fails the run.
+pub struct ResultWriter<W: Write, E: Write> {
+    out: W,
+    stderr: E,
+    emergency: Option<VecDeque<String>>,
+    capacity: usize,
+    evicted: u64,
+    withhold: bool,
+    split: Option<SplitOutput>,
+    index: Option<SaltIndex>,
+    duplicates: u64,
+    recent: Option<(RecentAddresses, DuplicatePolicy)>,
+    results: u64,
+    duplicate_addresses: u64,
+}
+
+impl<W: Write, E: Write> ResultWriter<W, E> {
+    pub fn new(out: W, stderr: E, capacity: usize) -> Self {
+        Self {
+            out,
+            stderr,
+            emergency: None,
+            capacity: capacity.max(1),
+            evicted: 0,
+            withhold: false,
+            split: None,
+            index: None,
+            duplicates: 0,
+            recent: None,
+            results: 0,
+            duplicate_addresses: 0,
+        }
+    }
+
+    /// Skip results whose salt `index` already holds, and record the salts written. The index
+    /// must belong to the output file.
+    pub fn indexed(mut self, index: SaltIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Number of results skipped as already in the output file.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Detect results for an address already in `recent` or written since, found with another
+    /// salt, and handle them by `policy`.
+    pub fn detecting_duplicates(mut self, recent: RecentAddresses, policy: DuplicatePolicy) -> Self {
+        self.recent = Some((recent, policy));
+        self
+    }
+
+    /// Number of results whose address was found again with another salt, skipped or not.
+    pub fn duplicate_addresses(&self) -> u64 {
+        self.duplicate_addresses
+    }
+
+    /// Whether the rate of addresses found again points at a derivation bug, see
+    /// [`is_duplicate_anomaly`].
+    pub fn duplicate_anomaly(&self) -> bool {
+        is_duplicate_anomaly(self.results, self.duplicate_addresses)
+    }
+
+    /// Also route every result written with [`ResultWriter::write_result`] into its bucket
+    /// file, for `--split-output-by`. The output file keeps all results.
+    pub fn splitting(mut self, split: SplitOutput) -> Self {
+        self.split = Some(split);
+        self
+    }
+
+    /// Never print result lines, for `--redact-salts`. Results that do not fit on the disk are
+    /// then only counted.
+    pub fn withholding(mut self) -> Self {
+        self.withhold = true;
+        self
+    }
+
+    /// Whether the writer switched to the emergency buffer.
+    pub fn is_degraded(&self) -> bool {
+        self.emergency.is_some()
+    }
+
+    /// Write `line` to the output file. Returns whether it got there.
+    pub fn write(&mut self, line: &str) -> bool {
+        if self.emergency.is_none() {
+            let error = match writeln!(self.out, "{}", line).and_then(|_| self.out.flush()) {
+                Ok(()) => return true,
+                Err(e) => e,
+            };
+            if !is_disk_full(&error) {
+                let _ = writeln!(self.stderr, "Error writing to output file: {}", error);
+                return false;
+            }
+
+            let _ = writeln!(
+                self.stderr,
+                "OUTPUT DISK FULL ({}): results are no longer saved, keeping the last {} in memory \
+                 and printing them here",
+                error, self.capacity
+            );
+            self.emergency = Some(VecDeque::with_capacity(self.capacity));
+        }
+
+        if self.withhold {
+            let _ = writeln!(self.stderr, "unsaved result withheld by --redact-salts");
+        } else {
+            let _ = writeln!(self.stderr, "unsaved result: {}", line);
+        }
+        let buffer = self.emergency.as_mut().unwrap();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+            self.evicted += 1;
+        }
+        buffer.push_back(line.to_string());
+        false
+    }
+
+    /// Write `line`, the result for `address`, to the output file and to its bucket file,
+    /// unless the output file already holds its salt or the duplicate address policy skips it.
+    pub fn write_result(&mut self, line: &str, address: &[u8; 20]) {
+        let salt = self.index.as_ref().and_then(|_| index::salt_of_line(line));
+        if let (Some(index), Some(salt)) = (&self.index, salt) {
+            match index.contains(&salt) {
+                Ok(true) => {
+                    self.duplicates += 1;
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    let _ = writeln!(self.stderr, "Error reading the output file index: {}", e);
+                }
+            }
+        }
+
+        self.results += 1;
+        if let Some((recent, policy)) = self.recent.as_mut() {
+            if recent.insert(address) {
+                self.duplicate_addresses += 1;
+                match policy {
+                    DuplicatePolicy::KeepAll => {}
+                    DuplicatePolicy::FirstOnly => return,
+                    DuplicatePolicy::Warn => {
+                        let _ = writeln!(
+                            self.stderr,
+                            "warning: 0x{} was already found with another salt",
+                            alloy_primitives::hex::encode(address)
+                        );
+                    }
+                }
+            }
+        }
+
+        let written = self.write(line);
+        if let (Some(index), Some(salt), true) = (self.index.as_mut(), salt, written) {
+            if let Err(e) = index.insert(&salt, line.len() as u64 + 1) {
+                let _ = writeln!(self.stderr, "Error writing the output file index: {}", e);
+            }
+        }
+        if let Some(split) = self.split.as_mut() {
+            if let Err(e) = split.write(line, address) {
+                let _ = writeln!(self.stderr, "Error writing to bucket file: {}", e);
+            }
+        }
+    }
+
+    /// Dump the emergency buffer to `stderr`. Fails if the disk filled up, since the output
+    /// file is then missing results.
+    pub fn finish(mut self) -> Result<(), String> {
+        let Some(buffer) = self.emergency.take() else {
+            return Ok(());
+        };
+
+        let _ = writeln!(
+            self.stderr,
+            "{} results could not be written to the output file:",
+            buffer.len() as u64 + self.evicted
+        );
+        if self.withhold {
+            let _ = writeln!(self.stderr, "(withheld by --redact-salts)");
+        } else {
+            for line in &buffer {
+                let _ = writeln!(self.stderr, "{}", line);
+            }
+        }
+        if self.evicted > 0 {
+            let _ = writeln!(
+                self.stderr,
+                "{} older results did not fit in the emergency buffer",
+                self.evicted
+            );
+        }
+
+        Err("the output disk is full".to_string())
+    }
+}
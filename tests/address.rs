@@ -0,0 +1,87 @@
+use alloy_primitives::{hex, keccak256, Address};
+use createxcrunch::{
+    address_from_hash, create2_address, create2_preimage, create3_address, derivation_config,
+    kernel_derivation, kernel_guard_size, validate_address_offset, CreateVariant, SaltVariant,
+    ADDRESS_OFFSET, CREATE3_PROXY_INITCODE_HASH,
+};
+
+#[test]
+fn test_default_offset_extracts_address() {
+    // CREATE2 address of the zero deployer, salt and init code (EIP-1014 example 0)
+    let mut preimage = [0u8; 85];
+    preimage[0] = 0xff;
+    preimage[53..].copy_from_slice(keccak256(hex!("00")).as_slice());
+    let hash: [u8; 32] = keccak256(preimage).into();
+
+    let address = address_from_hash(&hash, ADDRESS_OFFSET).unwrap();
+    assert_eq!(
+        Address::from(address),
+        "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+            .parse::<Address>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_address_offset_validation() {
+    assert!(validate_address_offset(ADDRESS_OFFSET).is_ok());
+    assert!(validate_address_offset(0).is_ok());
+    assert!(validate_address_offset(13).is_err());
+    assert!(address_from_hash(&[0; 32], 13).is_none());
+}
+
+#[test]
+fn test_kernel_derivation() {
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let init_code_hash = [0xab; 32];
+    let create2 = CreateVariant::Create2 { init_code_hash };
+
+    // the uniform holds the CREATE2 preimage with a zero salt, the guarded salt is filled in later
+    let config = derivation_config(&create2, &SaltVariant::Random, &factory);
+    let bytes: Vec<u8> = config.iter().flat_map(|word| word.to_le_bytes()).collect();
+    assert_eq!(
+        bytes[..85],
+        create2_preimage(&factory, &[0; 32], &init_code_hash)
+    );
+    assert!(bytes[85..96].iter().all(|byte| *byte == 0));
+    assert_eq!(kernel_derivation(&create2), 1);
+    let config = derivation_config(&CreateVariant::Create3, &SaltVariant::Random, &factory);
+    let bytes: Vec<u8> = config.iter().flat_map(|word| word.to_le_bytes()).collect();
+    assert_eq!(
+        bytes[..85],
+        create2_preimage(&factory, &[0; 32], &CREATE3_PROXY_INITCODE_HASH)
+    );
+    assert_eq!(kernel_derivation(&CreateVariant::Create3), 2);
+
+    // followed by the guard preimage of the salt of nonce 0, ending in the nonce
+    let calling_address = hex!("88c6C46EBf353A52Bdbab708c23D0c81dAA8134A");
+    let salt_variant = SaltVariant::CrosschainSender {
+        chain_id: [0x0a; 32],
+        calling_address,
+    };
+    let config = derivation_config(&CreateVariant::Create3, &salt_variant, &factory);
+    let bytes: Vec<u8> = config.iter().flat_map(|word| word.to_le_bytes()).collect();
+    assert_eq!(kernel_guard_size(&salt_variant), 96);
+    assert_eq!(
+        bytes[96..],
+        salt_variant.guard_preimage(&salt_variant.salt(0))
+    );
+    assert_eq!(kernel_guard_size(&SaltVariant::Random), 32);
+    assert_eq!(
+        kernel_guard_size(&SaltVariant::Sender { calling_address }),
+        64
+    );
+
+    // the CPU confirmation derives the same address as CREATE2 itself
+    let salt = salt_variant.guard(&salt_variant.salt(0x0123_4567_89ab_cdef));
+    let hash = create2.kernel_hash(&factory, &salt);
+    assert_eq!(
+        address_from_hash(&hash, ADDRESS_OFFSET).unwrap(),
+        create2_address(&factory, &salt, &init_code_hash)
+    );
+    let hash = CreateVariant::Create3.kernel_hash(&factory, &salt);
+    assert_eq!(
+        address_from_hash(&hash, ADDRESS_OFFSET).unwrap(),
+        create3_address(&factory, &salt)
+    );
+}
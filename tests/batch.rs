@@ -0,0 +1,76 @@
+use createxcrunch::{hash_rate, validate_workgroup_size, BatchGeometry, RESULT_WORDS};
+use std::time::Duration;
+
+#[test]
+fn test_small_batch_geometry() {
+    let geometry = BatchGeometry::new(32, 256);
+    assert_eq!(geometry.workgroups(32), 1);
+    assert_eq!(geometry.workgroups(1), 1);
+    assert_eq!(geometry.result_buffer_size(), 32 * RESULT_WORDS as u64 * 4);
+
+    let geometry = BatchGeometry::new(1, 256);
+    assert_eq!(geometry.workgroups(1), 1);
+    assert_eq!(geometry.result_buffer_size(), RESULT_WORDS as u64 * 4);
+
+    // degenerate sizes are raised to one entry and one workgroup
+    let geometry = BatchGeometry::new(0, 0);
+    assert_eq!(geometry, BatchGeometry::new(1, 1));
+    assert_eq!(geometry.workgroups(0), 1);
+    assert!(geometry.result_buffer_size() > 0);
+}
+
+#[test]
+fn test_batch_geometry_rounding() {
+    let geometry = BatchGeometry::new(1_000_000, 256);
+    assert_eq!(geometry.workgroups(1_000_000), 3907);
+    assert_eq!(geometry.workgroups(256), 1);
+    assert_eq!(geometry.workgroups(257), 2);
+    // never more workgroups than one full batch needs
+    assert_eq!(geometry.workgroups(u32::MAX), 3907);
+}
+
+#[test]
+fn test_hash_rate_guards_tiny_elapsed() {
+    assert_eq!(hash_rate(32, Duration::ZERO), 0.0);
+    assert_eq!(hash_rate(64, Duration::from_micros(500)), 0.0);
+    assert_eq!(hash_rate(64, Duration::from_millis(2)), 32_000.0);
+    assert_eq!(hash_rate(0, Duration::from_secs(1)), 0.0);
+}
+
+#[test]
+fn test_batch_geometry_fits_device_limits() {
+    let geometry = BatchGeometry::new(10_000_000, 256);
+    // 128 MiB buffers hold 3,355,443 results of 40 bytes
+    assert_eq!(geometry.fit(128 << 20, 65_535).work_size, 3_355_443);
+    assert!(geometry.fit(128 << 20, 65_535).result_buffer_size() <= 128 << 20);
+    // at most 1,000 workgroups of 256
+    assert_eq!(geometry.fit(u64::MAX, 1_000).work_size, 256_000);
+    // a batch within the limits is kept
+    assert_eq!(geometry.fit(1 << 30, 65_535), geometry);
+    assert_eq!(BatchGeometry::new(100, 256).fit(0, 0).work_size, 1);
+}
+
+#[test]
+fn test_batch_geometry_refuses_batches_beyond_device_limits() {
+    let geometry = BatchGeometry::new(10_000_000, 256);
+    assert!(geometry.check_limits(1 << 30, 65_535).is_ok());
+
+    let err = geometry.check_limits(128 << 20, 65_535).unwrap_err();
+    assert!(err.contains("needs 400000000 byte buffers"), "{}", err);
+    assert!(err.ends_with("use at most 3355443"), "{}", err);
+    let err = geometry.check_limits(u64::MAX, 1_000).unwrap_err();
+    assert!(err.contains("39063 workgroups"), "{}", err);
+    assert!(err.ends_with("use at most 256000"), "{}", err);
+}
+
+#[test]
+fn test_workgroup_size_within_device_limits() {
+    for size in [1, 64, 128, 256] {
+        assert!(validate_workgroup_size(size, 256, 256).is_ok());
+    }
+    assert!(validate_workgroup_size(512, 1024, 1024).is_ok());
+    assert!(validate_workgroup_size(0, 256, 256).is_err());
+    assert!(validate_workgroup_size(512, 256, 256).is_err());
+    // the invocation limit applies even where the x limit allows more
+    assert!(validate_workgroup_size(512, 1024, 256).is_err());
+}
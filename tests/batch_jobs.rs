@@ -0,0 +1,89 @@
+use std::{fs, time::Duration};
+
+use createxcrunch::batch::{parse_jobs, report, BatchState, Job, RunSummary};
+
+#[test]
+fn test_parse_jobs_into_command_lines() {
+    let jobs = parse_jobs(
+        r#"
+[[job]]
+name = "vault"
+leading = 4
+output = "vault.txt"
+seed-entropy = true
+allow = ["W001", "W003"]
+limit = 1
+
+[[job]]
+command = "create2"
+code-hash = "0x00"
+matching = "ba5e..."
+limit = 2
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        jobs[0],
+        Job {
+            name: "vault".to_string(),
+            args: [
+                "create3",
+                "--leading",
+                "4",
+                "--output",
+                "vault.txt",
+                "--seed-entropy",
+                "--allow",
+                "W001",
+                "--allow",
+                "W003"
+            ]
+            .map(String::from)
+            .to_vec(),
+            limit: 1,
+        }
+    );
+    assert_eq!(jobs[1].name, "job 2");
+    assert_eq!(jobs[1].args[0], "create2");
+    assert_eq!(jobs[1].limit, 2);
+}
+
+#[test]
+fn test_invalid_jobs_are_rejected() {
+    assert!(parse_jobs("").is_err());
+    let err = parse_jobs("[[job]]\nleading = 4\n").unwrap_err();
+    assert!(err.contains("job 1: limit"), "{}", err);
+    let err = parse_jobs("[[job]]\ncommand = \"create1\"\nlimit = 1\n").unwrap_err();
+    assert!(err.contains("command"), "{}", err);
+}
+
+#[test]
+fn test_batch_state_and_report() {
+    let path =
+        std::env::temp_dir().join(format!("createxcrunch-batch-{}.state", std::process::id()));
+    assert_eq!(BatchState::load(&path).unwrap(), BatchState::default());
+    BatchState { next_job: 1 }.save(&path).unwrap();
+    assert_eq!(BatchState::load(&path).unwrap().next_job, 1);
+    fs::remove_file(&path).unwrap();
+
+    let job = |name: &str| Job {
+        name: name.to_string(),
+        args: vec![],
+        limit: 1,
+    };
+    let summary = RunSummary {
+        found: 1,
+        attempts: 1000,
+        elapsed: Duration::from_secs(3),
+        ..Default::default()
+    };
+    let report = report(&[
+        (job("a"), Ok(summary)),
+        (job("b"), Err("no adapter".to_string())),
+    ]);
+    assert_eq!(
+        report,
+        "ok     a: 1 results, 1000 attempts in 3s\nfailed b: no adapter\n1 of 2 jobs succeeded"
+    );
+}
@@ -0,0 +1,144 @@
+use createxcrunch::{
+    bundle::{build_info, parse_session, recorded_session, session_artifacts, write_bundle},
+    checkpoint::Checkpoint,
+    scan::ScanOrder,
+};
+use flate2::read::GzDecoder;
+use std::{fs, io::Read, path::PathBuf};
+
+fn session_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn read_bundle(path: &PathBuf) -> Vec<(String, Vec<u8>)> {
+    let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(path).unwrap()));
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut contents = vec![];
+            entry.read_to_end(&mut contents).unwrap();
+            (name, contents)
+        })
+        .collect()
+}
+
+fn checkpoint(scan_seed: u64) -> Checkpoint {
+    Checkpoint {
+        next_index: 1 << 20,
+        scan_order: ScanOrder::Linear,
+        scan_seed,
+        attempts: 1 << 20,
+        found: 1,
+        fingerprint: None,
+        campaign: None,
+    }
+}
+
+#[test]
+fn test_bundle_session_artifacts() {
+    let dir = session_dir("bundle");
+    let output = dir.join("output.txt");
+    fs::write(
+        &output,
+        format!(
+            "{}\n\n0x01 -> 0xbb00000000000000000000000000000000000000\n",
+            ScanOrder::Linear.header(42)
+        ),
+    )
+    .unwrap();
+    checkpoint(42)
+        .save(&dir.join("output.txt.checkpoint"))
+        .unwrap();
+    fs::write(dir.join("output.lz1.txt"), "0x01 -> 0x00ff\n").unwrap();
+    fs::write(dir.join("output.txt.bak"), "not written by the miner\n").unwrap();
+    fs::write(dir.join("other.txt"), "not part of the session\n").unwrap();
+
+    assert_eq!(recorded_session(&output), Some(42));
+    let mut members = session_artifacts(&output, Some(42)).unwrap();
+    members.push(build_info());
+    let bundle_path = dir.join("run.tar.gz");
+    write_bundle(&bundle_path, members).unwrap();
+
+    let entries = read_bundle(&bundle_path);
+    let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "build.json",
+            "output.lz1.txt",
+            "output.txt",
+            "output.txt.checkpoint"
+        ]
+    );
+    assert_eq!(entries[2].1, fs::read(&output).unwrap());
+    assert_eq!(
+        entries[3].1,
+        fs::read(dir.join("output.txt.checkpoint")).unwrap()
+    );
+
+    // bundling the same session again produces a byte-identical archive
+    let again_path = dir.join("again.tar.gz");
+    let mut members = session_artifacts(&output, Some(42)).unwrap();
+    members.push(build_info());
+    write_bundle(&again_path, members).unwrap();
+    assert_eq!(
+        fs::read(&bundle_path).unwrap(),
+        fs::read(&again_path).unwrap()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_bundle_leaves_out_other_sessions() {
+    let dir = session_dir("bundle-sessions");
+    let output = dir.join("output.txt");
+    fs::write(
+        &output,
+        format!("{}\n\n", ScanOrder::Splitmix.header(0xbeef)),
+    )
+    .unwrap();
+    assert_eq!(recorded_session(&output), Some(0xbeef));
+
+    // the checkpoint records the session where the header would only record the first one
+    checkpoint(7).save(&dir.join("output.txt.checkpoint")).unwrap();
+    fs::write(dir.join("output.txt.forensic.json"), "{}").unwrap();
+    assert_eq!(recorded_session(&output), Some(7));
+
+    let names = |session| {
+        let mut names: Vec<String> = session_artifacts(&output, session)
+            .unwrap()
+            .into_iter()
+            .map(|member| member.name)
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(Some(0xbeef)), ["output.txt", "output.txt.forensic.json"]);
+    assert_eq!(
+        names(Some(7)),
+        ["output.txt", "output.txt.checkpoint", "output.txt.forensic.json"]
+    );
+    assert_eq!(names(None), names(Some(7)));
+
+    assert_eq!(parse_session("0x000000000000beef"), Ok(0xbeef));
+    assert!(parse_session("session-1").is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_bundle_missing_session() {
+    let dir = session_dir("bundle-missing");
+    let members = session_artifacts(&dir.join("output.txt"), None).unwrap();
+    assert!(members.is_empty());
+    assert_eq!(recorded_session(&dir.join("output.txt")), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,157 @@
+use createxcrunch::{
+    bus::{EventBus, FoundResult, MinerEvent, Subscriber, Subscription},
+    channel::Backpressure,
+    cycle_stats::CycleStats,
+    events::StopReason,
+    warnings::Warning,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+fn found(n: u8) -> MinerEvent {
+    MinerEvent::Found(FoundResult {
+        salt: [n; 32],
+        address: [n; 20],
+        line: format!("result {}", n),
+    })
+}
+
+fn cycle(n: u64) -> MinerEvent {
+    MinerEvent::CycleCompleted(CycleStats {
+        cycle: n,
+        elapsed_ms: n * 100,
+        cycle_ms: 100.0,
+        attempts: 1_000_000,
+        readback_bytes: 4096,
+        matches: 0,
+    })
+}
+
+/// A subscriber keeping every event it gets, and the events it keeps.
+fn recorder() -> (
+    impl FnMut(&MinerEvent) + Send + 'static,
+    Arc<Mutex<Vec<MinerEvent>>>,
+) {
+    let events = Arc::new(Mutex::new(vec![]));
+    let kept = events.clone();
+    (
+        move |event: &MinerEvent| kept.lock().unwrap().push(event.clone()),
+        events,
+    )
+}
+
+#[test]
+fn test_subscribers_get_events_in_publish_order() {
+    let mut bus = EventBus::new();
+    let (all, all_events) = recorder();
+    let (results, found_events) = recorder();
+    let blocking = Subscription {
+        capacity: 2,
+        policy: Backpressure::Block,
+        ..Subscription::default()
+    };
+    bus.subscribe_with("all", all, blocking);
+    bus.subscribe_with(
+        "results",
+        results,
+        Subscription {
+            filter: MinerEvent::is_found,
+            ..blocking
+        },
+    );
+
+    let mut published = vec![MinerEvent::Warning(Warning::LowEntropy)];
+    for n in 0..50 {
+        published.push(cycle(n));
+        published.push(found(n as u8));
+    }
+    published.push(MinerEvent::ShuttingDown(StopReason::Limit));
+    for event in &published {
+        bus.publish(event.clone());
+    }
+    bus.shutdown().unwrap();
+
+    assert_eq!(*all_events.lock().unwrap(), published);
+    let found: Vec<MinerEvent> = published.into_iter().filter(MinerEvent::is_found).collect();
+    assert_eq!(found.len(), 50);
+    assert_eq!(*found_events.lock().unwrap(), found);
+}
+
+#[test]
+fn test_slow_subscribers_do_not_stall_publishing() {
+    let mut bus = EventBus::new();
+    let slow_events = Arc::new(Mutex::new(0u64));
+    let handled = slow_events.clone();
+    let slow = bus.subscribe_with(
+        "slow",
+        move |_: &MinerEvent| {
+            std::thread::sleep(Duration::from_millis(20));
+            *handled.lock().unwrap() += 1;
+        },
+        Subscription {
+            capacity: 4,
+            ..Subscription::default()
+        },
+    );
+    let (fast, fast_events) = recorder();
+    bus.subscribe("fast", fast);
+
+    // 200 events take the slow subscriber 4s to handle
+    let start = Instant::now();
+    for n in 0..200 {
+        bus.publish(cycle(n));
+    }
+    assert!(
+        start.elapsed() < Duration::from_secs(1),
+        "{:?}",
+        start.elapsed()
+    );
+
+    let dropped = bus.dropped(slow);
+    assert!(dropped > 100, "{}", dropped);
+    bus.shutdown().unwrap();
+    assert_eq!(*slow_events.lock().unwrap() + dropped, 200);
+    assert_eq!(fast_events.lock().unwrap().len(), 200);
+}
+
+/// Counts results, failing once finished with none.
+#[derive(Default)]
+struct Counter {
+    results: u64,
+}
+
+impl Subscriber for Counter {
+    fn handle(&mut self, event: &MinerEvent) {
+        if event.is_found() {
+            self.results += 1;
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self.results {
+            0 => Err("no results".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_shutdown_reports_subscriber_failures() {
+    let mut bus = EventBus::new();
+    bus.subscribe("counter", Counter::default());
+    bus.publish(cycle(0));
+    assert_eq!(bus.shutdown(), Err("no results".to_string()));
+
+    let mut bus = EventBus::new();
+    bus.subscribe("counter", Counter::default());
+    bus.publish(found(1));
+    assert_eq!(bus.shutdown(), Ok(()));
+
+    let mut bus = EventBus::new();
+    bus.subscribe("logger", |_: &MinerEvent| panic!("unexpected event"));
+    bus.subscribe("counter", Counter::default());
+    bus.publish(found(1));
+    assert_eq!(bus.shutdown(), Err("logger panicked".to_string()));
+}
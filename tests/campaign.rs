@@ -0,0 +1,192 @@
+use createxcrunch::{
+    campaign::{sessions_path, validate_name, Discovery, Report, Session},
+    cycle_stats::StatsFormat,
+    stats::CostModel,
+    Config, RewardVariant,
+};
+use std::{path::Path, process::Command, time::Duration};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/campaign");
+
+fn report() -> Report {
+    let discovery = Discovery::scan(Path::new(FIXTURES), "launch").unwrap();
+    Report::of("launch", discovery.sessions)
+}
+
+#[test]
+fn test_validate_name() {
+    assert!(validate_name("launch-2026.q4_vaults").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("launch day").is_err());
+    assert!(validate_name(&"a".repeat(65)).is_err());
+}
+
+#[test]
+fn test_discovery_reads_logs_and_bundles() {
+    let discovery = Discovery::scan(Path::new(FIXTURES), "launch").unwrap();
+
+    // the first vault session is both loose and bundled, the other campaign is left out
+    let starts: Vec<u64> = discovery
+        .sessions
+        .iter()
+        .map(|found| found.session.started_unix)
+        .collect();
+    assert_eq!(
+        starts,
+        vec![1_760_000_000, 1_760_050_000, 1_760_090_000, 1_760_100_000]
+    );
+    assert!(discovery.sessions[3]
+        .source
+        .ends_with("vault.tar.gz:vault.txt.sessions.jsonl"));
+    assert!(discovery.sessions[0]
+        .source
+        .ends_with("launch.txt.sessions.jsonl"));
+
+    assert_eq!(discovery.warnings.len(), 1);
+    assert!(
+        discovery.warnings[0].contains("launch.txt.sessions.jsonl line 3"),
+        "{:?}",
+        discovery.warnings
+    );
+
+    let other = Discovery::scan(Path::new(FIXTURES), "other").unwrap();
+    assert_eq!(other.sessions.len(), 1);
+    assert!(Discovery::scan(Path::new(FIXTURES), "none")
+        .unwrap()
+        .sessions
+        .is_empty());
+}
+
+#[test]
+fn test_report_rolls_up_sessions() {
+    let report = report();
+    assert_eq!(report.sessions.len(), 4);
+    assert_eq!(report.attempts, 4_500_000_000_000);
+    assert_eq!(report.elapsed_secs, 16_200.0);
+    assert_eq!(report.cost, Some(2.25));
+    assert_eq!(report.uncosted_sessions, 2);
+    assert_eq!(report.found(), 6);
+
+    // results of several patterns go to the pattern they matched
+    let dead = &report.results["pattern(dead...)"];
+    assert_eq!(dead.iter().collect::<Vec<_>>(), vec![(&0, &4)]);
+    let zeros = &report.results["pattern(00...)"];
+    assert_eq!(zeros.iter().collect::<Vec<_>>(), vec![(&10, &1), (&35, &1)]);
+
+    assert_eq!(
+        report.best[0].address,
+        "0x00000000000000000000000000000000000fACe0"
+    );
+    assert_eq!(report.best[0].pattern, "pattern(00...)");
+    assert_eq!(report.best.len(), 6);
+}
+
+#[test]
+fn test_report_formats() {
+    let report = report();
+
+    let text = report.render(StatsFormat::Table);
+    assert!(text.starts_with("Campaign launch\n  sessions: 4\n  attempts: 4500000000000\n"));
+    assert!(text.contains("  cost: 2.25, 2 sessions without cost options left out"));
+    assert!(text.contains("  pattern(00...): tier 10: 1, tier 35: 1"));
+    assert!(text.contains(
+        "  2025-10-09 08:53 UTC for 1.00h: 1000000000000 attempts, 2 results, pattern(dead...) in launch.txt"
+    ));
+
+    let markdown = report.render(StatsFormat::Markdown);
+    assert!(markdown.starts_with("# Campaign launch\n\n- sessions: 4"));
+    assert!(
+        markdown.contains("\n## Best addresses\n\n- 0x00000000000000000000000000000000000fACe0")
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&report.render(StatsFormat::Json)).unwrap();
+    assert_eq!(json["attempts"], 4_500_000_000_000u64);
+    assert_eq!(json["found"], 6);
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 4);
+    assert_eq!(json["sessions"][0]["cost"], serde_json::Value::Null);
+    assert_eq!(json["results"][0]["pattern"], "pattern(00...)");
+}
+
+#[test]
+fn test_session_log_round_trip() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-campaign-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("vault.txt");
+    let output = output.to_str().unwrap();
+
+    let mut config = Config::new(
+        0,
+        "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        None,
+        RewardVariant::LeadingZeros { zeros_threshold: 2 },
+        output,
+    )
+    .unwrap();
+    config.cost = Some(CostModel::Cloud { per_hour: 2.0 });
+    let results = [([0x11; 32], [0x22; 20])];
+    let session = Session::new(
+        &config,
+        "launch",
+        1_760_000_000,
+        42,
+        Duration::from_secs(1800),
+        &results,
+    );
+    assert_eq!(session.output, "vault.txt");
+    assert_eq!(session.reward, "leading-zeros(2)");
+    assert_eq!(session.cost, Some(1.0));
+    assert_eq!(
+        session.results[0].salt,
+        Some(format!("0x{}", "11".repeat(32)))
+    );
+
+    // salts kept out of the output stay out of the log
+    config.redact_salts = true;
+    let redacted = Session::new(
+        &config,
+        "launch",
+        1_760_000_000,
+        42,
+        Duration::from_secs(1800),
+        &results,
+    );
+    assert_eq!(redacted.results[0].salt, None);
+
+    let path = sessions_path(output);
+    assert!(path.ends_with("vault.txt.sessions.jsonl"));
+    session.append(Path::new(&path)).unwrap();
+    redacted.append(Path::new(&path)).unwrap();
+    let discovery = Discovery::scan(&dir, "launch").unwrap();
+    assert_eq!(discovery.sessions.len(), 2);
+    assert_eq!(discovery.sessions[0].session, session);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_campaign_report_command() {
+    let run = |name: &str| {
+        Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args([
+                "campaign", "report", name, "--dir", FIXTURES, "--format", "json",
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = run("launch");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["campaign"], "launch");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Warning: "));
+
+    let output = run("none");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("no sessions of campaign none"));
+}
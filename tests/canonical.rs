@@ -0,0 +1,164 @@
+use std::{fmt::Debug, str::FromStr};
+
+use createxcrunch::{Config, CreateVariant, RewardVariant, SaltVariant};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn assert_round_trip<T>(variant: T)
+where
+    T: ToString + FromStr + PartialEq + Debug,
+    T::Err: Debug,
+{
+    let rendered = variant.to_string();
+    assert_eq!(rendered.parse::<T>().unwrap(), variant, "{}", rendered);
+}
+
+fn random_reward(rng: &mut StdRng) -> RewardVariant {
+    match rng.gen_range(0..9) {
+        0 => RewardVariant::LeadingZeros {
+            zeros_threshold: rng.gen(),
+        },
+        1 => RewardVariant::TotalZeros {
+            zeros_threshold: rng.gen(),
+        },
+        2 => RewardVariant::LeadingAndTotalZeros {
+            leading_zeros_threshold: rng.gen(),
+            total_zeros_threshold: rng.gen(),
+        },
+        3 => RewardVariant::LeadingOrTotalZeros {
+            leading_zeros_threshold: rng.gen(),
+            total_zeros_threshold: rng.gen(),
+        },
+        4 => {
+            let digits = "0123456789abcdefABCDEF.X";
+            let pattern: String = (0..rng.gen_range(1..40))
+                .map(|_| digits.as_bytes()[rng.gen_range(0..digits.len())] as char)
+                .collect();
+            RewardVariant::Matching {
+                pattern: pattern.into(),
+            }
+        }
+        5 => RewardVariant::ChecksumMatching {
+            pattern: "DeaD...".into(),
+        },
+        6 => RewardVariant::MatchingAny {
+            patterns: vec!["ba5e...".into(), "cafe...01".into()],
+        },
+        7 => RewardVariant::LeadingZeroNibbles {
+            threshold: rng.gen(),
+        },
+        _ => RewardVariant::SymmetricEnds { nibbles: rng.gen() },
+    }
+}
+
+fn random_salt(rng: &mut StdRng) -> SaltVariant {
+    let mut chain_id = [0u8; 32];
+    // chain ids are small in practice, but any uint256 must survive
+    let len = rng.gen_range(0..=32);
+    rng.fill(&mut chain_id[32 - len..]);
+    match rng.gen_range(0..4) {
+        0 => SaltVariant::CrosschainSender {
+            chain_id,
+            calling_address: rng.gen(),
+        },
+        1 => SaltVariant::Crosschain { chain_id },
+        2 => SaltVariant::Sender {
+            calling_address: rng.gen(),
+        },
+        _ => SaltVariant::Random,
+    }
+}
+
+#[test]
+fn test_generated_variants_round_trip() {
+    let mut rng = StdRng::seed_from_u64(493);
+    for _ in 0..1000 {
+        assert_round_trip(random_reward(&mut rng));
+        assert_round_trip(random_salt(&mut rng));
+        assert_round_trip(CreateVariant::Create2 {
+            init_code_hash: rng.gen(),
+        });
+    }
+    assert_round_trip(CreateVariant::Create3);
+}
+
+#[test]
+fn test_canonical_syntax() {
+    assert_eq!(
+        RewardVariant::LeadingZeros { zeros_threshold: 5 }.to_string(),
+        "leading-zeros(5)"
+    );
+    assert_eq!(
+        "leading-zero-nibbles(9)".parse::<RewardVariant>().unwrap(),
+        RewardVariant::LeadingZeroNibbles { threshold: 9 }
+    );
+    assert_eq!(
+        "pattern(dead...beef)".parse::<RewardVariant>().unwrap(),
+        RewardVariant::Matching {
+            pattern: "dead...beef".into()
+        }
+    );
+
+    assert_eq!(
+        RewardVariant::ChecksumMatching {
+            pattern: "DeaD...".into()
+        }
+        .to_string(),
+        "checksum-pattern(DeaD...)"
+    );
+    assert_eq!(
+        "any-pattern(ba5e...,cafe...01)"
+            .parse::<RewardVariant>()
+            .unwrap(),
+        RewardVariant::MatchingAny {
+            patterns: vec!["ba5e...".into(), "cafe...01".into()]
+        }
+    );
+
+    let mut chain_id = [0u8; 32];
+    chain_id[30..].copy_from_slice(&8453u16.to_be_bytes());
+    let salt = SaltVariant::CrosschainSender {
+        chain_id,
+        calling_address: [0xab; 20],
+    };
+    assert_eq!(
+        salt.to_string(),
+        "sender(0xabababababababababababababababababababab)+chain(8453)"
+    );
+
+    for invalid in [
+        "leading-zeros(256)",
+        "pattern(a(b))",
+        "any-pattern(ba5e...,)",
+        "zeros(4)",
+        "leading-zeros",
+    ] {
+        assert!(invalid.parse::<RewardVariant>().is_err(), "{}", invalid);
+    }
+    for invalid in [
+        "chain(1)+sender(0xab)",
+        "chain(1)+chain(2)",
+        "sender(0xab)",
+        "nobody",
+    ] {
+        assert!(invalid.parse::<SaltVariant>().is_err(), "{}", invalid);
+    }
+    assert!("create2(0x00)".parse::<CreateVariant>().is_err());
+}
+
+#[test]
+fn test_config_fingerprint() {
+    let config = Config::new(
+        0,
+        "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        Some(1),
+        None,
+        RewardVariant::LeadingZeros { zeros_threshold: 4 },
+        "output.txt",
+    )
+    .unwrap();
+    assert_eq!(
+        config.fingerprint(),
+        "create3 factory(0xba5ed099633d3b313e4d5f7bdc1305d3c28ba5ed) chain(1) leading-zeros(4)"
+    );
+}
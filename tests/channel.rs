@@ -0,0 +1,68 @@
+use createxcrunch::channel::{match_channel, Backpressure};
+use std::{thread, time::Duration};
+
+#[test]
+fn test_block_backpressure_keeps_every_match() {
+    let (sender, receiver) = match_channel::<u32>(2, Backpressure::Block);
+
+    let consumer = thread::spawn(move || {
+        let mut received = vec![];
+        for item in receiver {
+            thread::sleep(Duration::from_millis(2));
+            received.push(item);
+        }
+        received
+    });
+
+    for i in 0..20 {
+        assert!(sender.send(i));
+    }
+    assert_eq!(sender.dropped(), 0);
+    drop(sender);
+
+    assert_eq!(consumer.join().unwrap(), (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_block_backpressure_stops_when_the_receiver_is_gone() {
+    let (sender, receiver) = match_channel::<u32>(1, Backpressure::Block);
+    assert!(sender.send(0));
+
+    // the queue is full, so the send waits until the receiver drops
+    let producer = thread::spawn(move || sender.send(1));
+    thread::sleep(Duration::from_millis(50));
+    drop(receiver);
+    assert!(!producer.join().unwrap());
+}
+
+#[test]
+fn test_drop_backpressure_discards_oldest() {
+    let (sender, receiver) = match_channel::<u32>(4, Backpressure::Drop);
+
+    // nothing consumes until the producer is done, so only the newest matches survive
+    for i in 0..10 {
+        assert!(sender.send(i));
+    }
+    assert_eq!(sender.dropped(), 6);
+    drop(sender);
+
+    assert_eq!(receiver.dropped(), 6);
+    assert_eq!(receiver.collect::<Vec<_>>(), vec![6, 7, 8, 9]);
+}
+
+#[test]
+fn test_send_fails_without_receiver() {
+    let (sender, receiver) = match_channel::<u32>(1, Backpressure::Block);
+    assert!(sender.send(0));
+    drop(receiver);
+
+    assert!(!sender.send(1));
+}
+
+#[test]
+fn test_backpressure_parsing() {
+    assert_eq!("block".parse::<Backpressure>(), Ok(Backpressure::Block));
+    assert_eq!("drop".parse::<Backpressure>(), Ok(Backpressure::Drop));
+    assert!("drop-newest".parse::<Backpressure>().is_err());
+    assert_eq!(Backpressure::Drop.to_string(), "drop");
+}
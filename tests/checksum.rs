@@ -0,0 +1,66 @@
+use alloy_primitives::{hex, Address};
+use createxcrunch::checksum::{
+    checksum_mask, letters, spells, spells_ignoring_case, uppercase_letters,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+// The mask alloy's checksummed string spells
+fn mask_of(checksummed: &str) -> u64 {
+    checksummed[2..]
+        .bytes()
+        .enumerate()
+        .filter(|(_, c)| c.is_ascii_uppercase())
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+#[test]
+fn test_mask_matches_alloy_checksums() {
+    let mut rng = StdRng::seed_from_u64(1191);
+    for _ in 0..500 {
+        let mut address = [0u8; 20];
+        rng.fill_bytes(&mut address);
+        for chain_id in [None, Some(1), Some(30), Some(31), Some(u64::MAX)] {
+            let checksummed = Address::from(address).to_checksum(chain_id);
+            assert_eq!(
+                checksum_mask(&address, chain_id),
+                mask_of(&checksummed),
+                "{} on chain {:?}",
+                checksummed,
+                chain_id
+            );
+        }
+    }
+}
+
+#[test]
+fn test_eip55_and_eip1191_examples() {
+    // from the test vectors of both EIPs
+    let address = hex!("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    assert_eq!(
+        checksum_mask(&address, None),
+        mask_of("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+    );
+    assert_eq!(
+        checksum_mask(&address, Some(30)),
+        mask_of("0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD")
+    );
+}
+
+#[test]
+fn test_letter_counts() {
+    let address = hex!("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    let mask = checksum_mask(&address, None);
+    // 5aAeb6: a, A, e and b are letters, one of them uppercase
+    assert_eq!(letters(&address, 0..6), 4);
+    assert_eq!(uppercase_letters(mask, 0..6), 1);
+    assert_eq!(letters(&address, 0..40), 18);
+    assert_eq!(uppercase_letters(mask, 0..40), mask.count_ones());
+    assert_eq!(uppercase_letters(mask, 3..3), 0);
+
+    assert!(spells(&address, mask, 0, "5aAe"));
+    assert!(!spells(&address, mask, 0, "5aae"));
+    assert!(spells(&address, mask, 36, "eAed"));
+    assert!(!spells(&address, mask, 38, "eAed"));
+    assert!(spells_ignoring_case(&address, 0, "5AAE"));
+    assert!(!spells_ignoring_case(&address, 0, "5bae"));
+}
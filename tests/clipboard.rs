@@ -0,0 +1,72 @@
+use alloy_primitives::hex;
+use createxcrunch::{
+    clipboard::{best_result, copy_best_result, Clipboard},
+    format_address, score,
+};
+
+#[derive(Default)]
+struct MockClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for MockClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.text = Some(text.to_string());
+        Ok(())
+    }
+}
+
+struct HeadlessClipboard;
+
+impl Clipboard for HeadlessClipboard {
+    fn set_text(&mut self, _text: &str) -> Result<(), String> {
+        Err("no display".to_string())
+    }
+}
+
+fn results() -> Vec<([u8; 32], [u8; 20])> {
+    vec![
+        ([0x01; 32], hex!("00BB000000000000000000000000000000000001")),
+        ([0x02; 32], hex!("0000bbface000000000000000000000000000001")),
+        ([0x03; 32], hex!("0000bbface000000000000000000000000000002")),
+    ]
+}
+
+fn render(salt: &[u8; 32], address: &[u8; 20]) -> String {
+    format!("0x{} -> {}", hex::encode(salt), format_address(address))
+}
+
+#[test]
+fn test_score_prefers_leading_zeros() {
+    let leading = [0u8, 0, 0x10, 0x11, 0x11];
+    let scattered = [0x10, 0, 0, 0, 0];
+    assert!(score(&leading) > score(&scattered));
+    assert!(score(&[0, 0, 1]) > score(&[0, 1, 1]));
+}
+
+#[test]
+fn test_copy_best_result() {
+    let mut clipboard = MockClipboard::default();
+    let copied = copy_best_result(&mut clipboard, &results(), render).unwrap();
+
+    // the address is copied checksummed, as the output file has it
+    let expected = format!(
+        "0x{} -> 0x0000bBface000000000000000000000000000001",
+        "02".repeat(32)
+    );
+    assert_eq!(copied.as_deref(), Some(expected.as_str()));
+    assert_eq!(clipboard.text.as_deref(), Some(expected.as_str()));
+    assert_eq!(best_result(&results()), Some(&results()[1]));
+}
+
+#[test]
+fn test_copy_without_results() {
+    let mut clipboard = MockClipboard::default();
+    assert_eq!(copy_best_result(&mut clipboard, &[], render).unwrap(), None);
+    assert!(clipboard.text.is_none());
+}
+
+#[test]
+fn test_headless_clipboard_degrades() {
+    assert!(copy_best_result(&mut HeadlessClipboard, &results(), render).is_err());
+}
@@ -0,0 +1,131 @@
+use createxcrunch::{
+    config_file::{self, ConfigFile},
+    Config, RewardVariant,
+};
+
+const CONFIG: &str = r#"
+caller = "0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A"
+chain_id = 1
+leading = 4
+output = "vault.txt"
+init_code_hash = "0x0000000000000000000000000000000000000000000000000000000000000000"
+allow = ["W001", "low-entropy"]
+seed_entropy = true
+"#;
+
+fn args(strings: &[&str]) -> Vec<String> {
+    strings.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_config_file_args() {
+    let file = ConfigFile::parse("run.toml", CONFIG).unwrap();
+    let create3 = args(&[
+        "--caller",
+        "0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A",
+        "--crosschain",
+        "1",
+        "--leading",
+        "4",
+        "--output",
+        "vault.txt",
+        "--allow",
+        "W001",
+        "--allow",
+        "low-entropy",
+        "--seed-entropy",
+    ]);
+    assert_eq!(file.args("create3", &[]), create3);
+
+    // the init code hash only goes to create2
+    let create2 = file.args("create2", &[]);
+    assert_eq!(create2.len(), create3.len() + 2);
+    assert!(create2.contains(&"--code-hash".to_string()));
+
+    // options on the command line replace those of the file, search criteria as a whole
+    let overridden = file.args(
+        "create3",
+        &args(&["--output=other.txt", "--matching", "00"]),
+    );
+    assert!(!overridden.contains(&"--output".to_string()));
+    assert!(!overridden.contains(&"--leading".to_string()));
+    assert!(overridden.contains(&"--caller".to_string()));
+
+    assert_eq!(config_file::option_of_key("gpu_device_id"), "gpu-device-id");
+    assert!(ConfigFile::parse("run.toml", "[job]\nleading = 4").is_err());
+    assert!(ConfigFile::parse("run.toml", "leading = 4 4").is_err());
+}
+
+#[test]
+fn test_config_file_errors_name_the_key() {
+    let file = ConfigFile::parse("run.toml", CONFIG).unwrap();
+    let error = Config::new(
+        0,
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        None,
+        RewardVariant::LeadingZeros {
+            zeros_threshold: 30,
+        },
+        "vault.txt",
+    )
+    .err()
+    .unwrap()
+    .to_string();
+    assert_eq!(file.blame(&error, &[]), Some("leading"));
+    assert_eq!(
+        file.explain(error.clone(), &[]),
+        "threshold must be less than 20 (key leading in config file run.toml)"
+    );
+    // a value from the command line is not the file's fault
+    assert_eq!(file.blame(&error, &args(&["--leading", "30"])), None);
+
+    let file = ConfigFile::parse("run.toml", "leading = 4\nfactory = \"0x12\"").unwrap();
+    assert_eq!(
+        file.blame("factory address must be 20 bytes", &[]),
+        Some("factory")
+    );
+    assert_eq!(
+        file.blame(
+            "error: invalid value 'x' for '--leading <zeros>'\n\nUsage: --factory",
+            &[]
+        ),
+        Some("leading")
+    );
+    assert_eq!(file.blame("init code hash is not valid hex", &[]), None);
+}
+
+#[test]
+fn test_render_round_trips() {
+    let options = vec![
+        ("leading".to_string(), vec!["4".to_string()]),
+        ("matching".to_string(), vec!["00".to_string()]),
+        ("seed-entropy".to_string(), vec![]),
+        (
+            "allow".to_string(),
+            vec!["W001".to_string(), "W003".to_string()],
+        ),
+    ];
+    let rendered = config_file::render(&options);
+    assert_eq!(
+        rendered,
+        "leading = 4\nmatching = \"00\"\nseed-entropy = true\nallow = [\"W001\", \"W003\"]\n"
+    );
+
+    let file = ConfigFile::parse("printed.toml", &rendered).unwrap();
+    assert_eq!(
+        file.args("create3", &[]),
+        args(&[
+            "--leading",
+            "4",
+            "--matching",
+            "00",
+            "--seed-entropy",
+            "--allow",
+            "W001",
+            "--allow",
+            "W003"
+        ])
+    );
+}
@@ -0,0 +1,128 @@
+use createxcrunch::cycle_stats::{
+    compare, render, CycleStats, StatsFile, StatsFormat, Summary, HEADER,
+};
+
+const WORKGROUP_64: &str = include_str!("fixtures/stats/workgroup-64.csv");
+const WORKGROUP_256: &str = include_str!("fixtures/stats/workgroup-256.csv");
+const MIXED_VERSIONS: &str = include_str!("fixtures/stats/mixed-versions.csv");
+const MALFORMED: &str = include_str!("fixtures/stats/malformed.csv");
+
+fn summary(path: &str, contents: &str) -> Summary {
+    Summary::of(path, &StatsFile::parse(path, contents).unwrap().cycles)
+}
+
+#[test]
+fn test_parse_stats_files() {
+    let file = StatsFile::parse("workgroup-64.csv", WORKGROUP_64).unwrap();
+    assert!(file.warnings.is_empty());
+    assert_eq!(file.cycles.len(), 4);
+    assert_eq!(
+        file.cycles[2],
+        CycleStats {
+            cycle: 3,
+            elapsed_ms: 38,
+            cycle_ms: 14.0,
+            attempts: 1_000_000,
+            readback_bytes: 80_004,
+            matches: 2,
+        }
+    );
+
+    // rows round trip through the writer's format
+    let row = file.cycles[2].to_row();
+    let reparsed = StatsFile::parse("row", &format!("{}\n{}\n", HEADER, row)).unwrap();
+    assert_eq!(reparsed.cycles, vec![file.cycles[2]]);
+
+    // rows of a newer schema are skipped with a warning, appended headers are skipped
+    let file = StatsFile::parse("mixed-versions.csv", MIXED_VERSIONS).unwrap();
+    assert_eq!(
+        file.cycles
+            .iter()
+            .map(|cycle| cycle.cycle)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(
+        file.warnings,
+        vec!["mixed-versions.csv: skipped 2 rows of a schema newer than version 1"]
+    );
+
+    let error = StatsFile::parse("malformed.csv", MALFORMED).unwrap_err();
+    assert_eq!(error, "malformed.csv line 3: invalid duration fast");
+    assert!(StatsFile::parse("short", "1,1,20,20.0\n").is_err());
+    assert!(StatsFile::parse("version", "v1,1,20,20.0,1,4,0\n").is_err());
+}
+
+#[test]
+fn test_summarize_stats_files() {
+    let workgroup_64 = summary("workgroup-64.csv", WORKGROUP_64);
+    assert_eq!(workgroup_64.cycles, 4);
+    // the middle two of 10, 10, 12 and 14 ms
+    assert_eq!(workgroup_64.median_cycle_ms, 11.0);
+    // 4,000,000 attempts in 46 ms of cycles
+    assert!((workgroup_64.rate - 4_000_000.0 / 0.046).abs() < 1e-3);
+    assert_eq!(workgroup_64.readback_bytes, 120_016);
+    assert_eq!(workgroup_64.matches, 3);
+
+    assert_eq!(
+        summary("workgroup-256.csv", WORKGROUP_256).median_cycle_ms,
+        8.0
+    );
+
+    let empty = Summary::of("empty.csv", &[]);
+    assert_eq!(
+        (empty.cycles, empty.median_cycle_ms, empty.rate),
+        (0, 0.0, 0.0)
+    );
+
+    // the fastest run first
+    let compared = compare(vec![
+        workgroup_64,
+        summary("workgroup-256.csv", WORKGROUP_256),
+        summary("mixed-versions.csv", MIXED_VERSIONS),
+    ]);
+    let order: Vec<&str> = compared
+        .iter()
+        .map(|summary| summary.path.as_str())
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            "workgroup-256.csv",
+            "workgroup-64.csv",
+            "mixed-versions.csv"
+        ]
+    );
+}
+
+#[test]
+fn test_render_comparison() {
+    let summaries = compare(vec![
+        summary("workgroup-64.csv", WORKGROUP_64),
+        summary("workgroup-256.csv", WORKGROUP_256),
+    ]);
+
+    assert_eq!(
+        render(&summaries, StatsFormat::Table),
+        "file               cycles  median cycle ms    MH/s  readback MB  matches\n\
+         workgroup-256.csv       3            8.000  120.00         0.04        1\n\
+         workgroup-64.csv        4           11.000   86.96         0.12        3"
+    );
+    assert_eq!(
+        render(&summaries, StatsFormat::Markdown),
+        "| file | cycles | median cycle ms | MH/s | readback MB | matches |\n\
+         | --- | --- | --- | --- | --- | --- |\n\
+         | workgroup-256.csv | 3 | 8.000 | 120.00 | 0.04 | 1 |\n\
+         | workgroup-64.csv | 4 | 11.000 | 86.96 | 0.12 | 3 |"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render(&summaries, StatsFormat::Json)).unwrap();
+    assert_eq!(json[0]["path"], "workgroup-256.csv");
+    assert_eq!(json[1]["cycles"], 4);
+    assert_eq!(json[1]["median_cycle_ms"], 11.0);
+    assert_eq!(json[1]["readback_bytes"], 120_016);
+
+    assert_eq!("markdown".parse(), Ok(StatsFormat::Markdown));
+    assert!("csv".parse::<StatsFormat>().is_err());
+}
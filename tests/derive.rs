@@ -0,0 +1,135 @@
+use createxcrunch::{
+    verify::{verify_record, Deployment, Derivation},
+    FoundSalt, RewardVariant,
+};
+use serde::Deserialize;
+
+/// Salts and what they deploy to, shared with the tests of the `wasm` exports.
+const VECTORS: &str = include_str!("fixtures/derive/vectors.json");
+
+#[derive(Deserialize)]
+struct Vector {
+    deployment: Deployment,
+    salt: String,
+    derivation: Derivation,
+}
+
+fn vectors() -> Vec<Vector> {
+    serde_json::from_str(VECTORS).unwrap()
+}
+
+fn record(vector: &Vector, pattern: &str) -> FoundSalt {
+    FoundSalt {
+        schema_version: 1,
+        salt: vector.salt.clone(),
+        address: vector.derivation.address.clone(),
+        factory: vector.derivation.factory.clone(),
+        create_variant: vector.derivation.create_variant.clone(),
+        pattern: pattern.to_string(),
+        found_at_unix: 0,
+        attempt: 0,
+        pattern_lines: vec![],
+        salt_formats: Default::default(),
+    }
+}
+
+#[test]
+fn test_golden_vectors() {
+    for vector in vectors() {
+        assert_eq!(
+            vector.deployment.derive(&vector.salt),
+            Ok(vector.derivation.clone()),
+            "{}",
+            vector.salt
+        );
+    }
+}
+
+#[test]
+fn test_verify_record() {
+    let vectors = vectors();
+    // 0xCbc02963Eef555F755E8af71Fd66d3366631Fe74
+    let vector = &vectors[0];
+    let deployment = &vector.deployment;
+    assert_eq!(
+        verify_record(&record(vector, "pattern(cb...74)"), deployment),
+        Ok(vector.derivation.clone())
+    );
+    assert!(verify_record(&record(vector, "checksum-pattern(Cbc0...)"), deployment).is_ok());
+    assert!(verify_record(&record(vector, "pattern(cbc0296...)"), deployment).is_ok());
+    assert_eq!(
+        verify_record(&record(vector, "pattern(00...)"), deployment),
+        Err(
+            "0xCbc02963Eef555F755E8af71Fd66d3366631Fe74 does not satisfy pattern(00...)"
+                .to_string()
+        )
+    );
+
+    let mut moved = record(vector, "pattern(cb...)");
+    moved.address = vectors[1].derivation.address.clone();
+    assert!(verify_record(&moved, deployment)
+        .unwrap_err()
+        .starts_with("the salt deploys to another address than recorded"));
+    let create2 = record(&vectors[1], "pattern(26...)");
+    let create3 = Deployment {
+        init_code_hash: None,
+        ..vectors[1].deployment.clone()
+    };
+    assert_eq!(
+        verify_record(&create2, &create3),
+        Err(format!(
+            "the result was mined for {}, not create3",
+            create2.create_variant
+        ))
+    );
+    let other_factory = Deployment {
+        factory: Some("0x0000000000FFe8B47B3e2130213B802212439497".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        verify_record(&record(vector, "pattern(cb...)"), &other_factory),
+        Err("the result was mined for factory 0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed, not 0x0000000000FFe8B47B3e2130213B802212439497".to_string())
+    );
+}
+
+#[test]
+fn test_reward_matches() {
+    let address: [u8; 20] = [
+        0x00, 0x0c, 0xaf, 0xe0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0c, 0xaf,
+    ];
+    let matches = |reward: &str| reward.parse::<RewardVariant>().unwrap().matches(&address);
+    assert!(matches("leading-zeros(1)"));
+    assert!(!matches("leading-zeros(2)"));
+    assert!(matches("leading-zero-nibbles(3)"));
+    assert!(!matches("leading-zero-nibbles(4)"));
+    assert!(matches("total-zeros(15)"));
+    assert!(matches("leading-and-total-zeros(1,15)"));
+    assert!(!matches("leading-and-total-zeros(2,15)"));
+    assert!(matches("leading-or-total-zeros(2,15)"));
+    assert!(matches("pattern(000caf...0caf)"));
+    assert!(matches("pattern(000CAFE...)"));
+    assert!(!matches("pattern(0c...)"));
+    assert!(matches("any-pattern(0c...,...0caf)"));
+    assert!(!matches("symmetric-ends(1)"));
+
+    let mut address = [0u8; 20];
+    address[..2].copy_from_slice(&[0xca, 0xfe]);
+    address[18..].copy_from_slice(&[0xca, 0xfe]);
+    let symmetric_ends = |nibbles| RewardVariant::SymmetricEnds { nibbles }.matches(&address);
+    assert!(symmetric_ends(4));
+    assert!(!symmetric_ends(5));
+}
+
+#[test]
+fn test_unusable_deployments_error() {
+    let deployment: Deployment = serde_json::from_str(r#"{"caller": "0x1234"}"#).unwrap();
+    assert_eq!(
+        deployment.derive("0x01"),
+        Err("hex salt 0x01 is not 32 bytes long".to_string())
+    );
+    assert_eq!(
+        deployment.derive(&format!("0x{}", "00".repeat(32))),
+        Err("caller address 0x1234 is not a valid address".to_string())
+    );
+    assert!(serde_json::from_str::<Deployment>(r#"{"chainId": 1}"#).is_err());
+}
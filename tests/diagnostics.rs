@@ -0,0 +1,133 @@
+use createxcrunch::diagnostics::{
+    adapter_table, debug_env_report, device_type_rank, missing_backend_error, GpuBackend,
+};
+
+#[test]
+fn test_debug_env_report() {
+    let info = wgpu::AdapterInfo {
+        name: "Test GPU 9000".to_string(),
+        vendor: 0x10de,
+        device: 0x2684,
+        device_type: wgpu::DeviceType::DiscreteGpu,
+        driver: "test-driver".to_string(),
+        driver_info: "1.2.3".to_string(),
+        backend: wgpu::Backend::Vulkan,
+    };
+    let requested = wgpu::Limits::default();
+    let granted = wgpu::Limits {
+        max_storage_buffer_binding_size: 1 << 30,
+        ..wgpu::Limits::default()
+    };
+
+    let report = debug_env_report(&info, &requested, &granted, wgpu::Features::empty());
+
+    assert!(report.contains("adapter: Test GPU 9000"));
+    assert!(report.contains("backend: Vulkan"));
+    assert!(report.contains("driver info: 1.2.3"));
+    assert!(report.contains(&format!(
+        "max_storage_buffer_binding_size: {} / {}",
+        requested.max_storage_buffer_binding_size,
+        1 << 30
+    )));
+}
+
+#[test]
+fn test_adapter_table() {
+    let adapter = |name: &str, device_type, backend| wgpu::AdapterInfo {
+        name: name.to_string(),
+        vendor: 0,
+        device: 0,
+        device_type,
+        driver: String::new(),
+        driver_info: String::new(),
+        backend,
+    };
+    let adapters = vec![
+        (
+            adapter(
+                "Test GPU 9000",
+                wgpu::DeviceType::DiscreteGpu,
+                wgpu::Backend::Vulkan,
+            ),
+            wgpu::Limits {
+                max_storage_buffer_binding_size: 2 << 30,
+                ..wgpu::Limits::default()
+            },
+        ),
+        (
+            adapter("llvmpipe", wgpu::DeviceType::Cpu, wgpu::Backend::Gl),
+            wgpu::Limits::default(),
+        ),
+    ];
+
+    let table = adapter_table(&adapters);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("id  name"));
+    assert!(lines[1].starts_with("0   Test GPU 9000 Vulkan  discrete"));
+    assert!(lines[1].contains("2048 MiB"));
+    assert!(lines[2].starts_with("1   llvmpipe      Gl      CPU"));
+
+    assert!(adapter_table(&[]).starts_with("No GPU adapter found."));
+
+    // the default device id picks discrete cards over integrated and software ones
+    let mut types = [
+        wgpu::DeviceType::Cpu,
+        wgpu::DeviceType::IntegratedGpu,
+        wgpu::DeviceType::DiscreteGpu,
+    ];
+    types.sort_by_key(|device_type| device_type_rank(*device_type));
+    assert_eq!(types[0], wgpu::DeviceType::DiscreteGpu);
+    assert_eq!(types[2], wgpu::DeviceType::Cpu);
+}
+
+#[test]
+fn test_gpu_backend() {
+    for backend in GpuBackend::ALL {
+        assert_eq!(backend.to_string().parse::<GpuBackend>(), Ok(backend));
+    }
+    assert_eq!(GpuBackend::Dx12.backends(), wgpu::Backends::DX12);
+    assert_eq!(
+        GpuBackend::of(wgpu::Backend::Vulkan),
+        Some(GpuBackend::Vulkan)
+    );
+    assert_eq!(GpuBackend::of(wgpu::Backend::BrowserWebGpu), None);
+    assert!("opengl".parse::<GpuBackend>().is_err());
+}
+
+#[test]
+fn test_missing_backend_error() {
+    let error = missing_backend_error(
+        GpuBackend::Vulkan,
+        &[wgpu::Backend::Gl, wgpu::Backend::Dx12, wgpu::Backend::Gl],
+    );
+    assert_eq!(
+        error,
+        "no vulkan adapter found, adapters are available on gl, dx12 (select one with --backend, \
+         or see list-gpus)"
+    );
+    assert!(missing_backend_error(GpuBackend::Metal, &[])
+        .starts_with("no metal adapter found, and no other backend has one either."));
+}
+
+#[cfg(feature = "gpu-tests")]
+#[test]
+fn test_self_test_reports_the_environment() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-self-test-{}.txt", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "1", "--self-test"])
+        .args(["--output", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&output);
+    let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+
+    let printed = format!("{}{}", String::from_utf8_lossy(&run.stdout), String::from_utf8_lossy(&run.stderr));
+    let report = printed
+        .find("----- createxcrunch debug environment -----")
+        .unwrap_or_else(|| panic!("{}", printed));
+    assert!(report < printed.find("Kernel self-test passed").unwrap());
+    assert!(printed.contains("backend: "));
+    assert!(printed.contains("limits (requested / granted):"));
+}
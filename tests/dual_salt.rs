@@ -0,0 +1,119 @@
+use alloy_primitives::keccak256;
+use createxcrunch::{
+    create2_address, create3_address, has_symmetric_ends, CreateVariant, DualSalt, SaltVariant,
+};
+
+const FACTORY: [u8; 20] = [
+    0xba, 0x5e, 0xd0, 0x99, 0x63, 0x3d, 0x3b, 0x31, 0x3e, 0x4d, 0x5f, 0x7b, 0xdc, 0x13, 0x05, 0xd3,
+    0xc2, 0x8b, 0xa5, 0xed,
+];
+
+fn chain_id(id: u64) -> [u8; 32] {
+    let mut chain_id = [0u8; 32];
+    chain_id[24..].copy_from_slice(&id.to_be_bytes());
+    chain_id
+}
+
+#[test]
+fn test_dual_salt_framings() {
+    let dual = DualSalt::new(0x0102_0304_0506_0708);
+
+    assert_eq!(dual.crosschain[..20], [0u8; 20]);
+    assert_eq!(dual.crosschain[20], 0x01);
+    assert_eq!(dual.random[..20], [0u8; 20]);
+    assert_eq!(dual.random[20], 0x00);
+    assert_eq!(dual.crosschain[21..], dual.random[21..]);
+    assert_eq!(dual.random[24..], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    let chain_id = chain_id(10);
+    let guarded = SaltVariant::Crosschain { chain_id }.guard(&dual.crosschain);
+    assert_eq!(guarded, keccak256([chain_id, dual.crosschain].concat()).0);
+    assert_eq!(
+        SaltVariant::Random.guard(&dual.random),
+        keccak256(dual.random).0
+    );
+}
+
+#[test]
+fn test_dual_salt_match_satisfies_both_derivations() {
+    let chain_id = chain_id(8453);
+    let nibbles = 1;
+
+    // mine on the CPU until both framings of a nonce satisfy the reward
+    let (nonce, dual) = (0u64..)
+        .map(|nonce| (nonce, DualSalt::new(nonce)))
+        .find(|(_, dual)| {
+            let (crosschain, random) = dual.addresses(&CreateVariant::Create3, &FACTORY, &chain_id);
+            has_symmetric_ends(&crosschain, nibbles) && has_symmetric_ends(&random, nibbles)
+        })
+        .unwrap();
+
+    // re-derive both addresses from scratch for the found salt
+    let crosschain = create3_address(
+        &FACTORY,
+        &keccak256([chain_id, DualSalt::new(nonce).crosschain].concat()).0,
+    );
+    let random = create3_address(&FACTORY, &keccak256(dual.random).0);
+
+    assert!(has_symmetric_ends(&crosschain, nibbles));
+    assert!(has_symmetric_ends(&random, nibbles));
+    assert_ne!(crosschain, random);
+}
+
+#[test]
+fn test_dual_salt_create2_match_satisfies_both_derivations() {
+    let chain_id = chain_id(8453);
+    let init_code_hash = [0x5a; 32];
+    let variant = CreateVariant::Create2 { init_code_hash };
+    let nibbles = 1;
+
+    let (nonce, dual) = (0u64..)
+        .map(|nonce| (nonce, DualSalt::new(nonce)))
+        .find(|(_, dual)| {
+            let (crosschain, random) = dual.addresses(&variant, &FACTORY, &chain_id);
+            has_symmetric_ends(&crosschain, nibbles) && has_symmetric_ends(&random, nibbles)
+        })
+        .unwrap();
+
+    // both salts deploy with CREATE2 from the init code hash, not through the CREATE3 proxy
+    let crosschain = create2_address(
+        &FACTORY,
+        &keccak256([chain_id, DualSalt::new(nonce).crosschain].concat()).0,
+        &init_code_hash,
+    );
+    let random = create2_address(&FACTORY, &keccak256(dual.random).0, &init_code_hash);
+
+    assert!(has_symmetric_ends(&crosschain, nibbles));
+    assert!(has_symmetric_ends(&random, nibbles));
+    assert_eq!(
+        dual.addresses(&variant, &FACTORY, &chain_id),
+        (crosschain, random)
+    );
+    assert_ne!(
+        dual.addresses(&CreateVariant::Create3, &FACTORY, &chain_id),
+        (crosschain, random)
+    );
+}
+
+#[test]
+fn test_mined_salts_share_the_dual_layout() {
+    let nonce = 0x0123_4567_89ab_cdef;
+    let dual = DualSalt::new(nonce);
+    let crosschain = SaltVariant::Crosschain {
+        chain_id: chain_id(10),
+    };
+    assert_eq!(crosschain.salt(nonce), dual.crosschain);
+    assert_eq!(SaltVariant::Random.salt(nonce), dual.random);
+
+    // the sender part stays fixed and only the nonce changes
+    let calling_address = [0x88; 20];
+    let sender = SaltVariant::CrosschainSender {
+        chain_id: chain_id(10),
+        calling_address,
+    };
+    let salt = sender.salt(nonce);
+    assert_eq!(salt[..20], calling_address);
+    assert_eq!(salt[20], 0x01);
+    assert_eq!(salt[21..24], [0; 3]);
+    assert_eq!(salt[24..], nonce.to_be_bytes());
+}
@@ -0,0 +1,117 @@
+use std::process::{Command, Output};
+
+/// Run the miner with only the environment variables `env`, printing its configuration
+/// instead of mining.
+fn print_config(args: &[&str], env: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(args)
+        .arg("--print-config")
+        .env_clear()
+        .envs(env.iter().copied())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_options_from_the_environment() {
+    let output = print_config(
+        &["create2"],
+        &[
+            ("CREATEXCRUNCH_LEADING", "4"),
+            ("CREATEXCRUNCH_OUTPUT", "vault.txt"),
+            ("CREATEXCRUNCH_SEED_ENTROPY", "true"),
+            ("CREATEXCRUNCH_DUAL_SALT", "false"),
+            (
+                "CREATEXCRUNCH_CODE_HASH",
+                "0x0000000000000000000000000000000000000000000000000000000000000000",
+            ),
+        ],
+    );
+    let config = stdout(&output);
+    assert!(config.contains("leading = 4\n"));
+    assert!(config.contains("output = \"vault.txt\"\n"));
+    assert!(config.contains("seed-entropy = true\n"));
+    assert!(!config.contains("dual-salt"));
+    assert!(config.contains("code-hash = "));
+}
+
+#[test]
+fn test_command_line_takes_precedence_over_environment() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-env-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("run.toml");
+    std::fs::write(&file, "output = \"file.txt\"\ntotal = 6\n").unwrap();
+    let env = [
+        ("CREATEXCRUNCH_LEADING", "4"),
+        ("CREATEXCRUNCH_OUTPUT", "env.txt"),
+        ("CREATEXCRUNCH_GPU_DEVICE_ID", "1"),
+    ];
+
+    // flags over the environment
+    let config = stdout(&print_config(
+        &["create3", "--output", "flag.txt", "--leading", "5"],
+        &env,
+    ));
+    assert!(config.contains("output = \"flag.txt\"\n") && config.contains("leading = 5\n"));
+    assert!(config.contains("gpu-device-id = 1\n"));
+
+    // search criteria replace those of the environment as a whole
+    let config = stdout(&print_config(&["create3", "--matching", "00"], &env));
+    assert!(config.contains("matching = \"00\"\n") && !config.contains("leading"));
+
+    // flags over the config file over the environment
+    let path = file.to_str().unwrap();
+    let config = stdout(&print_config(&["create3", "--config", path], &env));
+    assert!(config.contains("output = \"file.txt\"\n") && config.contains("total = 6\n"));
+    assert!(config.contains("gpu-device-id = 1\n"));
+    let config = stdout(&print_config(
+        &["create3", "--config", path, "--output", "flag.txt"],
+        &env,
+    ));
+    assert!(config.contains("output = \"flag.txt\"\n"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_errors_name_the_source_of_the_value() {
+    let stderr = |output: Output| {
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let from_env = stderr(print_config(
+        &["create3"],
+        &[("CREATEXCRUNCH_LEADING", "40")],
+    ));
+    assert!(
+        from_env.contains("(from environment variable CREATEXCRUNCH_LEADING)"),
+        "{}",
+        from_env
+    );
+
+    let from_flag = stderr(print_config(
+        &["create3", "--leading", "40"],
+        &[("CREATEXCRUNCH_LEADING", "4")],
+    ));
+    assert!(from_flag.contains("(from --leading)"), "{}", from_flag);
+
+    let unparsable = stderr(print_config(
+        &["create3"],
+        &[("CREATEXCRUNCH_LEADING", "x")],
+    ));
+    assert!(
+        unparsable.contains("Error in environment variable CREATEXCRUNCH_LEADING"),
+        "{}",
+        unparsable
+    );
+}
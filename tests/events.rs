@@ -0,0 +1,46 @@
+use createxcrunch::events::{write_event, DoneEvent, Event, FoundEvent, StatusEvent, StopReason};
+
+#[test]
+fn test_events_are_tagged_json_lines() {
+    let found = Event::Found(FoundEvent {
+        salt: format!("0x{}", "00".repeat(32)),
+        address: "0x00000000000000000000000000000000000000Ab".to_string(),
+        pattern: "leading-zeros(1)".to_string(),
+    });
+    let mut out = vec![];
+    write_event(&mut out, &found).unwrap();
+    let line = String::from_utf8(out).unwrap();
+    assert!(line.ends_with("}\n"));
+    assert_eq!(line.lines().count(), 1);
+
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(json["event"], "found");
+    assert_eq!(json["pattern"], "leading-zeros(1)");
+    assert_eq!(serde_json::from_str::<Event>(&line).unwrap(), found);
+
+    let done = Event::Done(DoneEvent {
+        reason: StopReason::MaxRuntime,
+        elapsed_secs: 7200.5,
+        attempts: 1_000_000,
+        found: 2,
+    });
+    assert_eq!(
+        serde_json::to_string(&done).unwrap(),
+        r#"{"event":"done","reason":"max_runtime","elapsed_secs":7200.5,"attempts":1000000,"found":2}"#
+    );
+
+    let status =
+        r#"{"event":"status","elapsed_secs":1.5,"attempts":3000000,"rate":2000000.0,"found":0}"#;
+    assert_eq!(
+        serde_json::from_str::<Event>(status).unwrap(),
+        Event::Status(StatusEvent {
+            elapsed_secs: 1.5,
+            attempts: 3_000_000,
+            rate: 2_000_000.0,
+            found: 0,
+            eta_secs: None,
+            explored: 0.0,
+        })
+    );
+    assert!(serde_json::from_str::<Event>(r#"{"event":"paused"}"#).is_err());
+}
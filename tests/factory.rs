@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use alloy_primitives::{hex, keccak256};
+use createxcrunch::{
+    factory::{self, FactoryCode, FactoryMismatch, CREATEX, DETERMINISTIC_PROXY},
+    http::HttpTransport,
+    rpc::RpcClient,
+};
+use serde_json::{json, Value};
+
+#[derive(Default)]
+struct NodeState {
+    /// Runtime code by lowercase hex address, accounts not in it have none.
+    code: HashMap<String, Vec<u8>>,
+    fail: bool,
+    calls: u32,
+}
+
+/// In-process JSON-RPC node answering `eth_getCode` on a local port.
+struct MockNode {
+    url: String,
+    state: Arc<Mutex<NodeState>>,
+}
+
+impl MockNode {
+    fn start(code: &[([u8; 20], &[u8])]) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(NodeState {
+            code: code
+                .iter()
+                .map(|(address, code)| (format!("0x{}", hex::encode(address)), code.to_vec()))
+                .collect(),
+            ..Default::default()
+        }));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle(stream.unwrap(), &server_state);
+            }
+        });
+        Self { url, state }
+    }
+
+    fn rpc(&self) -> RpcClient<HttpTransport> {
+        RpcClient::new(HttpTransport::new(&self.url).unwrap())
+    }
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<NodeState>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header == "\r\n" {
+            break;
+        }
+        if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let request: Value = serde_json::from_slice(&body).unwrap();
+
+    let mut state = state.lock().unwrap();
+    state.calls += 1;
+    let response = match (state.fail, request["method"].as_str()) {
+        (false, Some("eth_getCode")) => {
+            let address = request["params"][0].as_str().unwrap().to_lowercase();
+            let code = state.code.get(&address).cloned().unwrap_or_default();
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x{}", hex::encode(code)) })
+        }
+        _ => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32000, "message": "header not found" },
+        }),
+    };
+
+    let body = response.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+}
+
+#[test]
+fn test_identify_code() {
+    assert_eq!(
+        keccak256(factory::DETERMINISTIC_PROXY_CODE).0,
+        DETERMINISTIC_PROXY.code_hash
+    );
+    assert_eq!(
+        FactoryCode::identify(&factory::DETERMINISTIC_PROXY_CODE),
+        FactoryCode::Known(DETERMINISTIC_PROXY)
+    );
+    assert_eq!(FactoryCode::identify(&[]), FactoryCode::Empty);
+    assert_eq!(
+        FactoryCode::identify(&[0x60, 0x00]),
+        FactoryCode::Unknown {
+            code_hash: keccak256([0x60, 0x00]).0
+        }
+    );
+}
+
+#[test]
+fn test_known_factory_passes() {
+    let node = MockNode::start(&[(
+        DETERMINISTIC_PROXY.address,
+        &factory::DETERMINISTIC_PROXY_CODE,
+    )]);
+    let checked = factory::check_factory(
+        &mut node.rpc(),
+        &DETERMINISTIC_PROXY.address,
+        &DETERMINISTIC_PROXY,
+    );
+    assert_eq!(checked, Ok(Ok(())));
+    assert_eq!(node.state.lock().unwrap().calls, 1);
+}
+
+#[test]
+fn test_mismatch_suggests_canonical_deployment() {
+    let fork = [0x42; 20];
+    let node = MockNode::start(&[
+        (fork, &[0x60, 0x00]),
+        (
+            DETERMINISTIC_PROXY.address,
+            &factory::DETERMINISTIC_PROXY_CODE,
+        ),
+    ]);
+    let mismatch = factory::check_factory(&mut node.rpc(), &fork, &DETERMINISTIC_PROXY)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(
+        mismatch,
+        FactoryMismatch {
+            factory: fork,
+            expected: DETERMINISTIC_PROXY,
+            found: FactoryCode::Unknown {
+                code_hash: keccak256([0x60, 0x00]).0
+            },
+            correction: Some(DETERMINISTIC_PROXY.address),
+        }
+    );
+    let message = mismatch.to_string();
+    assert!(message.contains(&format!(
+        "code hash 0x{}",
+        hex::encode(keccak256([0x60, 0x00]))
+    )));
+    assert!(message.ends_with("(use --factory 0x4e59b44847b379578588920cA78FbF26c0B4956C)"));
+}
+
+#[test]
+fn test_mismatch_without_correction() {
+    // on this chain CreateX is not deployed at all, and the proxy sits at its address
+    let node = MockNode::start(&[(CREATEX.address, &factory::DETERMINISTIC_PROXY_CODE)]);
+    let mismatch = factory::check_factory(&mut node.rpc(), &CREATEX.address, &CREATEX)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(mismatch.found, FactoryCode::Known(DETERMINISTIC_PROXY));
+    assert_eq!(mismatch.correction, None);
+    assert!(mismatch
+        .to_string()
+        .contains("is the deterministic deployment proxy"));
+
+    let mismatch = factory::check_factory(&mut node.rpc(), &[0x42; 20], &CREATEX)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(
+        (mismatch.found, mismatch.correction),
+        (FactoryCode::Empty, None)
+    );
+}
+
+#[test]
+fn test_rpc_error() {
+    let node = MockNode::start(&[]);
+    node.state.lock().unwrap().fail = true;
+    assert_eq!(
+        factory::check_factory(&mut node.rpc(), &CREATEX.address, &CREATEX),
+        Err("eth_getCode failed: header not found".to_string())
+    );
+    assert!(factory::check_factory(
+        &mut RpcClient::new(HttpTransport::new("http://127.0.0.1:1").unwrap()),
+        &CREATEX.address,
+        &CREATEX
+    )
+    .is_err());
+}
@@ -0,0 +1,158 @@
+use createxcrunch::{
+    feasibility::{feasibility, reserved_addresses, sample, Method},
+    has_symmetric_ends,
+    reserved::{check_avoidable, reserved},
+    RewardVariant,
+};
+use rand::{rngs::SmallRng, SeedableRng};
+
+fn leading(zeros_threshold: u8) -> RewardVariant {
+    RewardVariant::LeadingZeros { zeros_threshold }
+}
+
+fn total(zeros_threshold: u8) -> RewardVariant {
+    RewardVariant::TotalZeros { zeros_threshold }
+}
+
+fn pattern(pattern: &str) -> RewardVariant {
+    RewardVariant::Matching {
+        pattern: pattern.into(),
+    }
+}
+
+#[test]
+fn test_reserved_addresses_are_the_precompiles_and_the_denylist() {
+    let addresses = reserved_addresses();
+    assert_eq!(addresses.len(), 0x09ff + 8);
+    assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(addresses.iter().all(|address| reserved(address).is_some()));
+}
+
+#[test]
+fn test_pinned_rewards_are_counted_exactly() {
+    // the precompiles, the zero address and 0x…dead share the 18 leading zero bytes
+    let counted = feasibility(&leading(18), 0);
+    assert_eq!(counted.method, Method::Exact);
+    assert_eq!(counted.kept, 1.0 - 2561.0 / 65536.0);
+
+    assert_eq!(
+        feasibility(&pattern("00000000000000000000000000000000000009..."), 0).kept,
+        0.0
+    );
+    assert_eq!(
+        feasibility(&pattern("0000000000000000000000000000000000000a..."), 0).kept,
+        1.0
+    );
+    assert_eq!(
+        feasibility(&pattern("ca11bde05977b3631167028862be2a173976ca11..."), 0).kept,
+        0.0
+    );
+    // only the checksummed Multicall3 is reserved
+    assert_eq!(
+        feasibility(
+            &RewardVariant::ChecksumMatching {
+                pattern: "cA11bde05977b3631167028862bE2a173976CA11...".into()
+            },
+            0
+        )
+        .kept,
+        0.0
+    );
+    assert_eq!(
+        feasibility(
+            &RewardVariant::ChecksumMatching {
+                pattern: "ca11bde05977b3631167028862be2a173976ca11...".into()
+            },
+            0
+        )
+        .kept,
+        1.0
+    );
+}
+
+#[test]
+fn test_zero_rewards_reducing_to_leading_zeros_are_counted_exactly() {
+    assert_eq!(feasibility(&total(20), 0).method, Method::Exact);
+    assert_eq!(feasibility(&total(20), 0).kept, 0.0);
+    let either = RewardVariant::LeadingAndTotalZeros {
+        leading_zeros_threshold: 19,
+        total_zeros_threshold: 4,
+    };
+    assert_eq!(feasibility(&either, 0), feasibility(&leading(19), 0));
+}
+
+#[test]
+fn test_common_rewards_are_bounded() {
+    for reward in [
+        total(4),
+        pattern("ab..."),
+        RewardVariant::SymmetricEnds { nibbles: 20 },
+    ] {
+        assert!(feasibility(&reward, 0).kept > 0.999999, "{}", reward);
+    }
+    assert_eq!(feasibility(&total(4), 0).method, Method::Bound);
+}
+
+#[test]
+fn test_rare_rewards_are_sampled() {
+    // a zero byte last is a precompile, a zero byte before it one in 256/10 times
+    let sampled = feasibility(&total(19), 20_000);
+    assert_eq!(sampled.method, Method::Sampled(20_000));
+    assert!((0.85..0.95).contains(&sampled.kept), "{:?}", sampled);
+}
+
+#[test]
+fn test_samples_match_their_reward() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    for _ in 0..1000 {
+        let address = sample(&total(17), &mut rng);
+        assert!(address.iter().filter(|byte| **byte == 0).count() >= 17);
+
+        let address = sample(
+            &RewardVariant::LeadingAndTotalZeros {
+                leading_zeros_threshold: 3,
+                total_zeros_threshold: 10,
+            },
+            &mut rng,
+        );
+        assert_eq!(address[..3], [0; 3]);
+        assert!(address.iter().filter(|byte| **byte == 0).count() >= 10);
+
+        let address = sample(&RewardVariant::SymmetricEnds { nibbles: 7 }, &mut rng);
+        assert!(has_symmetric_ends(&address, 7));
+
+        let address = sample(&pattern("abcd...ef"), &mut rng);
+        assert_eq!([address[0], address[1], address[19]], [0xab, 0xcd, 0xef]);
+
+        let any = RewardVariant::MatchingAny {
+            patterns: vec!["abcd...".into(), "ef...".into()],
+        };
+        let address = sample(&any, &mut rng);
+        assert!(address[..2] == [0xab, 0xcd] || address[0] == 0xef);
+    }
+}
+
+#[test]
+fn test_contradictory_rewards_are_refused() {
+    // both sides of the reward only match the precompiles and the zero address
+    let either = RewardVariant::LeadingOrTotalZeros {
+        leading_zeros_threshold: 19,
+        total_zeros_threshold: 20,
+    };
+    assert_eq!(feasibility(&either, 1000).kept, 0.0);
+    let error = check_avoidable(&either, false).unwrap_err();
+    assert!(
+        error.starts_with("only 0.000% of the addresses matching"),
+        "{}",
+        error
+    );
+    assert!(error.contains("estimated from"), "{}", error);
+    assert!(error.contains("pass --force"), "{}", error);
+    // an estimate doesn't rule the reward out, so it can be forced
+    assert!(check_avoidable(&either, true).is_ok());
+
+    // a count does
+    let error = check_avoidable(&total(20), true).unwrap_err();
+    assert!(error.starts_with("every address matching"), "{}", error);
+    assert!(check_avoidable(&pattern("00000000000000000000000000000000000009..."), true).is_err());
+}
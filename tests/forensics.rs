@@ -0,0 +1,100 @@
+use createxcrunch::{
+    forensics::{self, ForensicDump, SPOT_CHECKS},
+    Config, RewardVariant, RESULT_WORDS,
+};
+
+const FACTORY: &str = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+
+fn config() -> Config<'static> {
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    Config::new(0, FACTORY, None, None, None, reward, "output.txt").unwrap()
+}
+
+/// The words the kernel writes for `nonces`: each nonce, then the hash its address is taken from.
+fn results(config: &Config, nonces: std::ops::Range<u64>) -> Vec<u32> {
+    let mut words = vec![];
+    for nonce in nonces {
+        let guarded_salt = config.salt_variant.guard(&config.salt_variant.salt(nonce));
+        let hash = config
+            .create_variant
+            .kernel_hash(&config.factory_address, &guarded_salt);
+        words.extend([nonce as u32, (nonce >> 32) as u32]);
+        words.extend(
+            hash.chunks(4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())),
+        );
+    }
+    words
+}
+
+#[test]
+fn test_agreeing_results_pass() {
+    let config = config();
+    let results = results(&config, (1 << 32) - 2..(1 << 32) + 3);
+    for index in 0..5 {
+        assert_eq!(forensics::check(&config, &results, index), None);
+    }
+}
+
+#[test]
+fn test_corrupted_candidate_is_caught() {
+    let config = config();
+    let mut results = results(&config, 100..110);
+    // a flipped bit outside of the address still counts
+    results[3 * RESULT_WORDS as usize + 2] ^= 1;
+
+    let spotted: Vec<usize> = forensics::spot_checks(10)
+        .filter_map(|index| forensics::check(&config, &results, index))
+        .map(|disagreement| disagreement.index)
+        .collect();
+    assert_eq!(spotted, vec![3]);
+
+    let disagreement = forensics::check(&config, &results, 3).unwrap();
+    assert_eq!(disagreement.nonce, 103);
+    assert_eq!(disagreement.gpu_hash[0] ^ disagreement.cpu_hash[0], 1);
+    assert_eq!(disagreement.gpu_hash[1..], disagreement.cpu_hash[1..]);
+}
+
+#[test]
+fn test_spot_checks_spread_over_the_results() {
+    assert_eq!(forensics::spot_checks(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+    let checks: Vec<usize> = forensics::spot_checks(1 << 20).collect();
+    assert_eq!(checks.len(), SPOT_CHECKS);
+    assert_eq!(checks[0], 0);
+    assert_eq!(checks[1], (1 << 20) / SPOT_CHECKS);
+    assert_eq!(forensics::spot_checks(0).count(), 0);
+}
+
+#[test]
+fn test_forensic_dump() {
+    let config = config();
+    let mut results = results(&config, 100..110);
+    results[RESULT_WORDS as usize + 5] ^= 0x8000_0000;
+    let disagreement = forensics::check(&config, &results, 1).unwrap();
+
+    let dump = ForensicDump::new(&config, &disagreement, 7, &results, "Test GPU (Vulkan)");
+    assert_eq!(dump.cycle, 7);
+    assert_eq!(dump.nonce, 101);
+    assert_eq!(
+        dump.salt,
+        format!("0x{}", "00".repeat(24) + "0000000000000065")
+    );
+    assert!(dump.kernel.starts_with("// createXcrunch mining kernel 0x"));
+    assert_eq!(dump.adapter, "Test GPU (Vulkan)");
+    // the results around the offending one, cut at the start of the buffer
+    assert_eq!(dump.buffer_start, 0);
+    assert_eq!(dump.buffer.len(), 4 * RESULT_WORDS as usize);
+    assert_eq!(dump.buffer[RESULT_WORDS as usize], "00000065");
+
+    let path = std::env::temp_dir().join(format!(
+        "createxcrunch-forensics-{}.json",
+        std::process::id()
+    ));
+    dump.write(&path).unwrap();
+    let written: ForensicDump =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written, dump);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(forensics::dump_path("vault.txt"), "vault.txt.forensic.json");
+}
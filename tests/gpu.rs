@@ -0,0 +1,1042 @@
+// Runs the kernel on the first available adapter, so it is only built with `--features gpu-tests`.
+#![cfg(feature = "gpu-tests")]
+
+use alloy_primitives::{hex, Address, U256};
+use createxcrunch::{
+    bus::{EventBus, MinerEvent},
+    compute_create2_address, compute_create3_address, create2_address, create3_address,
+    events::{DoneEvent, Event, StopReason},
+    mine, mine_with, params, parse_salt,
+    scan::ScanOrder,
+    selftest::{
+        carry_stress_inputs, check_hashes, check_rotations, gpu_keccak256, gpu_rotations,
+        gpu_scan_nonces, rotation_patterns,
+    },
+    Config, CreateVariant, DualSalt, FoundSalt, RewardVariant, SaltVariant, KECCAK256_KERNEL,
+};
+use std::sync::{Arc, Mutex};
+
+fn device() -> (wgpu::Device, wgpu::Queue) {
+    let instance = wgpu::Instance::default();
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        force_fallback_adapter: false,
+        compatible_surface: None,
+    }))
+    .expect("no GPU adapter available");
+
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+        .expect("could not create device")
+}
+
+#[test]
+fn test_kernel_rotations_match_u64() {
+    let (device, queue) = device();
+    let patterns = rotation_patterns();
+    let rotations = gpu_rotations(&device, &queue, &patterns).unwrap();
+
+    let mismatches: Vec<String> = check_rotations(&patterns, &rotations)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn test_kernel_keccak256_carry_stress() {
+    let (device, queue) = device();
+    let inputs = carry_stress_inputs();
+    let hashes = gpu_keccak256(&device, &queue, &inputs).unwrap();
+
+    assert_eq!(hashes.len(), inputs.len());
+    let mismatches: Vec<String> = check_hashes(&inputs, &hashes)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn test_kernel_scan_orders_match_cpu() {
+    let (device, queue) = device();
+    let seed = 0x0123_4567_89ab_cdef;
+    let base = u32::MAX as u64 - 100;
+
+    for order in [ScanOrder::Linear, ScanOrder::Lcg, ScanOrder::Splitmix] {
+        let nonces = gpu_scan_nonces(&device, &queue, order, seed, base, 256).unwrap();
+        let expected: Vec<u64> = (base..base + 256)
+            .map(|index| order.nonce_at(index, seed))
+            .collect();
+        assert_eq!(nonces, expected, "{}", order);
+    }
+}
+
+#[test]
+fn test_two_job_batch() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-batch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let batch = dir.join("jobs.toml");
+    std::fs::write(
+        &batch,
+        format!(
+            "[[job]]\nname = \"first\"\nleading = 1\noutput = \"{0}/first.txt\"\nlimit = 1\n\n\
+             [[job]]\nname = \"second\"\nmatching = \"ab...\"\noutput = \"{0}/second.txt\"\nlimit = 2\n",
+            dir.display()
+        ),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("batch")
+        .arg(&batch)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("ok     first: ") && stdout.contains("ok     second: "));
+    assert!(stdout.contains("2 of 2 jobs succeeded"));
+
+    let results = |name: &str| {
+        std::fs::read_to_string(dir.join(name))
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("0x"))
+            .map(|line| line.split(" -> ").nth(1).unwrap().to_lowercase())
+            .collect::<Vec<_>>()
+    };
+    assert!(results("first.txt")
+        .iter()
+        .all(|address| address.starts_with("0x00")));
+    assert!(results("second.txt").len() >= 2);
+    assert!(results("second.txt")
+        .iter()
+        .all(|address| address.starts_with("0xab")));
+    // a completed batch leaves no state behind
+    assert!(!dir.join("jobs.toml.state").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Mine the batch job `job` into `<name>.txt` of a fresh directory, returning its `(salt,
+/// address)` results.
+fn mine_job(name: &str, job: &str) -> Vec<(String, String)> {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let batch = dir.join("jobs.toml");
+    std::fs::write(
+        &batch,
+        format!(
+            "[[job]]\n{}output = \"{}/{}.txt\"\n",
+            job,
+            dir.display(),
+            name
+        ),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("batch")
+        .arg(&batch)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let contents = std::fs::read_to_string(dir.join(format!("{}.txt", name))).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .map(|line| {
+            let (salt, address) = line.split_once(" -> ").unwrap();
+            (salt.to_string(), address.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_create2_addresses() {
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let init_code_hash = [0xab; 32];
+    let results = mine_job(
+        "create2",
+        &format!(
+            "command = \"create2\"\ncode-hash = \"0x{}\"\nfactory = \"0x{}\"\nleading = 1\nlimit = 8\n",
+            hex::encode(init_code_hash),
+            hex::encode(factory)
+        ),
+    );
+
+    // every salt deploys to its address with the full CREATE2 preimage, as verified
+    assert!(results.len() >= 8);
+    for (salt, address) in results {
+        let salt = parse_salt(&salt).unwrap();
+        let salt_variant = SaltVariant::of_salt(&salt, None, None).unwrap();
+        let (_, expected) = compute_create2_address(&factory, &salt_variant, &salt, &init_code_hash);
+        assert_eq!(address.parse::<Address>().unwrap(), Address::from(expected));
+        assert!(address.starts_with("0x00"));
+    }
+}
+
+#[test]
+fn test_create3_addresses() {
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let calling_address = hex!("88c6C46EBf353A52Bdbab708c23D0c81dAA8134A");
+    let salt_variant = SaltVariant::CrosschainSender {
+        chain_id: U256::from(10).to_be_bytes(),
+        calling_address,
+    };
+    let results = mine_job(
+        "create3",
+        &format!(
+            "command = \"create3\"\nfactory = \"0x{}\"\ncaller = \"0x{}\"\ncrosschain = 10\n\
+             leading = 1\nlimit = 8\n",
+            hex::encode(factory),
+            hex::encode(calling_address)
+        ),
+    );
+
+    // every guarded salt deploys its proxy, which deploys the contract to the address
+    assert!(results.len() >= 8);
+    for (salt, address) in results {
+        let salt = parse_salt(&salt).unwrap();
+        assert_eq!(salt[..21], salt_variant.salt(0)[..21]);
+        let expected = create3_address(&factory, &salt_variant.guard(&salt));
+        assert_eq!(address.parse::<Address>().unwrap(), Address::from(expected));
+        // the verifier reads the same variant off the salt
+        assert_eq!(SaltVariant::of_salt(&salt, Some(&calling_address), Some(10)), Ok(salt_variant.clone()));
+        assert_eq!(compute_create3_address(&factory, &salt_variant, &salt).1, expected);
+        assert!(address.starts_with("0x00"));
+    }
+}
+
+#[test]
+fn test_limit_stops_at_exactly_n_results() {
+    // one batch holds far more than 3 matches of a single zero byte
+    let results = mine_job("limit", "leading = 1\nlimit = 3\n");
+    assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn test_workgroup_sizes_find_correct_addresses() {
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let init_code_hash = [0xab; 32];
+    // workgroups of 48 threads are not a power of two, with two threads per bitmap bit
+    for workgroup_size in [32, 48, 64, 512] {
+        let results = mine_job(
+            &format!("workgroup-{}", workgroup_size),
+            &format!(
+                "command = \"create2\"\ncode-hash = \"0x{}\"\nfactory = \"0x{}\"\nleading = 1\n\
+                 limit = 8\nworkgroup-size = {}\n",
+                hex::encode(init_code_hash),
+                hex::encode(factory),
+                workgroup_size
+            ),
+        );
+
+        assert_eq!(results.len(), 8, "workgroup size {}", workgroup_size);
+        for (salt, address) in results {
+            let guarded_salt = SaltVariant::Random.guard(&parse_salt(&salt).unwrap());
+            let expected = create2_address(&factory, &guarded_salt, &init_code_hash);
+            assert_eq!(address.parse::<Address>().unwrap(), Address::from(expected));
+            assert!(address.starts_with("0x00"));
+        }
+    }
+}
+
+#[test]
+fn test_timeout_stops_an_unfinished_search() {
+    // eight zero bytes are out of reach of a one second run
+    let started = std::time::Instant::now();
+    let results = mine_job("timeout", "leading = 8\nlimit = 1\ntimeout = 1\n");
+    assert!(results.is_empty());
+    assert!(started.elapsed() < std::time::Duration::from_secs(60));
+}
+
+#[test]
+fn test_max_results_keeps_every_match_of_the_last_cycle() {
+    // a cycle of 10,000 nonces finds about 39 addresses starting with a zero byte
+    let results = mine_job(
+        "max-results",
+        "leading = 1\nlimit = 100000\nmax-results = 3\nwork-size = 10000\n",
+    );
+    assert!(results.len() > 3, "{} results", results.len());
+    assert!(results.len() < 100_000);
+}
+
+#[test]
+fn test_max_runtime_stops_with_its_own_final_line() {
+    let dir =
+        std::env::temp_dir().join(format!("createxcrunch-max-runtime-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let batch = dir.join("jobs.toml");
+    // eight zero bytes are out of reach of a one second run, one is reached in the first cycle
+    std::fs::write(
+        &batch,
+        format!(
+            "[[job]]\nleading = 8\nlimit = 1\nmax-runtime = \"1s\"\noutput = \"{0}/runtime.txt\"\n\n\
+             [[job]]\nleading = 1\nlimit = 100000\nmax-results = 1\nmax-runtime = \"1h\"\noutput = \"{0}/results.txt\"\n",
+            dir.display()
+        ),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("batch")
+        .arg(&batch)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // only the job stopped by its runtime says so
+    assert_eq!(stdout.matches("stopped: runtime limit reached").count(), 1);
+    assert!(stdout.contains(" with 0 results found after "));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_prefix_and_suffix_patterns_match_on_the_cpu() {
+    // each job parses its own pattern, nothing is carried over from the one before
+    for (name, pattern) in [
+        ("prefix-suffix", "a1...b2"),
+        ("other-prefix-suffix", "C3...D4"),
+    ] {
+        let results = mine_job(name, &format!("matching = \"{}\"\nlimit = 2\n", pattern));
+        assert_eq!(results.len(), 2);
+        let (prefix, suffix) = pattern
+            .to_lowercase()
+            .split_once("...")
+            .map(|(p, s)| (p.to_string(), s.to_string()))
+            .unwrap();
+        for (_, address) in results {
+            let address = address.to_lowercase();
+            assert!(
+                address.starts_with(&format!("0x{}", prefix)) && address.ends_with(&suffix),
+                "{}",
+                address
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mine_hands_results_to_the_callback() {
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-mine-{}.txt", std::process::id()));
+    let output = output.to_str().unwrap();
+    let factory = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    let mut config = Config::new(0, factory, None, None, None, reward, output).unwrap();
+    config.limit = Some(3);
+
+    let mut matches = vec![];
+    let stats = mine(config, |found| matches.push(found)).unwrap();
+
+    assert_eq!(stats.found, 3);
+    assert_eq!(matches.len(), 3);
+    let factory = hex::decode(factory).unwrap().try_into().unwrap();
+    for found in &matches {
+        let expected = create3_address(&factory, &SaltVariant::Random.guard(&found.salt));
+        assert_eq!(found.address, expected);
+        assert_eq!(found.address[0], 0);
+    }
+    assert_eq!(
+        stats.results,
+        matches
+            .iter()
+            .map(|found| (found.salt, found.address))
+            .collect::<Vec<_>>()
+    );
+    // the results stay with the caller
+    assert!(!std::path::Path::new(output).exists());
+}
+
+#[test]
+fn test_dual_salt_matches_both_framings() {
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-dual-salt-{}.txt", std::process::id()));
+    let output = output.to_str().unwrap();
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let chain_id = U256::from(8453).to_be_bytes();
+    let init_code_hash = [0xab; 32];
+
+    // the kernel matches both framings of a leading zero byte, the CPU the symmetric ends
+    for (create_variant, reward) in [
+        (
+            CreateVariant::Create3,
+            RewardVariant::LeadingZeros { zeros_threshold: 1 },
+        ),
+        (
+            CreateVariant::Create2 { init_code_hash },
+            RewardVariant::SymmetricEnds { nibbles: 1 },
+        ),
+    ] {
+        let code_hash = match create_variant {
+            CreateVariant::Create2 { init_code_hash } => Some(hex::encode(init_code_hash)),
+            CreateVariant::Create3 => None,
+        };
+        let mut config = Config::new(
+            0,
+            &hex::encode(factory),
+            None,
+            Some(8453),
+            code_hash.as_deref(),
+            reward.clone(),
+            output,
+        )
+        .unwrap();
+        config.dual_salt = true;
+        config.limit = Some(4);
+
+        let stats = mine(config, |_| {}).unwrap();
+
+        // both salts of each match, the crosschain one first
+        assert_eq!(stats.results.len(), 4);
+        for pair in stats.results.chunks(2) {
+            let [(crosschain, crosschain_address), (random, random_address)] = pair else {
+                unreachable!()
+            };
+            let dual = DualSalt::new(u64::from_be_bytes(crosschain[24..].try_into().unwrap()));
+            assert_eq!((*crosschain, *random), (dual.crosschain, dual.random));
+            assert_eq!(
+                dual.addresses(&create_variant, &factory, &chain_id),
+                (*crosschain_address, *random_address)
+            );
+            assert!(reward.matches(crosschain_address), "{:?}", reward);
+            assert!(reward.matches(random_address), "{:?}", reward);
+        }
+    }
+}
+
+/// Run `create3` with `args` and `--quiet` into a fresh output file, returning its stdout and
+/// the output file.
+fn mine_quietly(name: &str, args: &[&str]) -> (String, String) {
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-{}-{}.txt", name, std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("create3")
+        .args(args)
+        .args(["--quiet", "--output", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    let contents = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    (String::from_utf8_lossy(&run.stdout).into_owned(), contents)
+}
+
+#[test]
+fn test_any_of_several_patterns_is_mined_for() {
+    let (_, contents) = mine_quietly(
+        "any-pattern",
+        &["--matching", "00...", "--matching", "ff...", "--limit", "6"],
+    );
+    let results: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    // the limit applies to each pattern
+    assert_eq!(results.len(), 12);
+    for result in &results {
+        // every result names the pattern it matched
+        let (address, pattern) = result
+            .split_once(" -> ")
+            .unwrap()
+            .1
+            .split_once(' ')
+            .unwrap();
+        let expected = format!("pattern({}...)", address[2..4].to_lowercase());
+        assert_eq!(pattern, expected, "{}", result);
+    }
+    let zeros = results
+        .iter()
+        .filter(|result| result.ends_with(" pattern(00...)"))
+        .count();
+    assert_eq!(zeros, 6, "{}", contents);
+}
+
+#[test]
+fn test_leading_zero_nibbles_are_matched_on_the_gpu() {
+    let (_, contents) = mine_quietly("leading-nibbles", &["--leading-nibbles", "3", "--limit", "4"]);
+    let results: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    assert_eq!(results.len(), 4);
+    for result in results {
+        let address = result.split_once(" -> ").unwrap().1;
+        assert!(address.starts_with("0x000"), "{}", result);
+    }
+}
+
+#[test]
+fn test_limit_applies_to_each_pattern() {
+    let (_, contents) = mine_quietly(
+        "pattern-limit",
+        &["--pattern", "00...", "--pattern", "0000...", "--limit", "2"],
+    );
+    let results: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    // the rarer pattern is mined for after the first one has its results
+    let both = results
+        .iter()
+        .filter(|result| result.ends_with(" any-pattern(00...,0000...)"))
+        .count();
+    assert_eq!(both, 2, "{}", contents);
+    // at most the limit of each pattern
+    assert!(results.len() <= 4, "{}", contents);
+}
+
+#[cfg(feature = "dev")]
+#[test]
+fn test_strict_halts_on_an_injected_corruption() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-strict-{}.txt", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--strict", "--inject-corruption", "--quiet", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("--strict: the GPU derived 0x"), "{}", stderr);
+
+    // the dump names the first cycle, and nothing of it was written
+    let dump_path = format!("{}.forensic.json", output.display());
+    let dump: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&dump_path).unwrap()).unwrap();
+    assert_eq!(dump["cycle"], 1);
+    assert_eq!(dump["index"], 0);
+    let contents = std::fs::read_to_string(&output).unwrap_or_default();
+    assert!(!contents.lines().any(|line| line.starts_with("0x")), "{}", contents);
+
+    std::fs::remove_file(&dump_path).unwrap();
+    let _ = std::fs::remove_file(&output);
+}
+
+#[test]
+fn test_quiet_prints_results_instead_of_the_status() {
+    let (stdout, contents) = mine_quietly("quiet-results", &["--leading", "1", "--limit", "3"]);
+    let found: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("found: "))
+        .collect();
+    let written: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    assert_eq!(found.len(), 3);
+    assert_eq!(found, written);
+    assert!(stdout.contains("Found 3 results in "));
+
+    // a run outlasting a status update, for a pattern it doesn't find
+    let (stdout, contents) = mine_quietly(
+        "quiet-status",
+        &["--matching", "deadbeefdead...", "--max-runtime", "2s"],
+    );
+    assert!(stdout.contains("Mining started."));
+    assert!(stdout.contains("stopped: runtime limit reached"));
+    assert!(!stdout.contains("total runtime:"));
+    assert!(!stdout.contains("Raw pattern"));
+    assert!(!contents.lines().any(|line| line.starts_with("0x")));
+}
+
+#[test]
+fn test_redirected_stdout_is_quiet_by_default() {
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-auto-quiet-{}.txt", std::process::id()));
+    // the test captures stdout in a pipe, which is no terminal
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "2", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert_eq!(stdout.lines().filter(|line| line.starts_with("found: ")).count(), 2);
+    assert!(stdout.contains("Found 2 results in "));
+    assert!(!stdout.contains("total runtime:"));
+    assert!(!stdout.contains('\r'));
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn test_checksum_patterns_keep_their_case() {
+    let factory = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+    let reward = RewardVariant::ChecksumMatching {
+        pattern: "DeaD...".into(),
+    };
+    let mut config = Config::new(0, factory, None, None, None, reward, "output.txt").unwrap();
+    config.limit = Some(2);
+
+    let mut addresses = vec![];
+    mine(config, |found| addresses.push(found.address)).unwrap();
+
+    assert_eq!(addresses.len(), 2);
+    for address in addresses {
+        assert!(Address::from(address)
+            .to_checksum(None)
+            .starts_with("0xDeaD"));
+    }
+}
+
+#[test]
+fn test_json_events_replace_the_human_output() {
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-json-{}.txt", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args([
+            "create3",
+            "--leading",
+            "1",
+            "--limit",
+            "3",
+            "--json",
+            "--output",
+        ])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+
+    // every line of stdout is an event
+    let events: Vec<Event> = String::from_utf8(run.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(
+        matches!(&events[0], Event::Start(start) if start.reward == "leading-zeros(1)" && start.limit == Some(3))
+    );
+    assert!(matches!(
+        events.last(),
+        Some(Event::Done(DoneEvent {
+            reason: StopReason::Limit,
+            found: 3,
+            ..
+        }))
+    ));
+
+    let found: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Found(found) => Some(format!("{} -> {}", found.salt, found.address)),
+            _ => None,
+        })
+        .collect();
+    let contents = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    let written: Vec<&str> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    assert_eq!(found, written);
+}
+
+#[test]
+fn test_same_seed_finds_the_same_salts() {
+    let mine = |seed: &str, extra: &[&str], run: usize| {
+        let output = std::env::temp_dir().join(format!("createxcrunch-seed-{}-{}.txt", std::process::id(), run));
+        let mined = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args(["create3", "--leading", "1", "--limit", "3", "--quiet", "--seed", seed])
+            .args(extra)
+            .args(["--output", output.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(mined.status.success(), "{}", String::from_utf8_lossy(&mined.stderr));
+
+        let contents = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+        contents
+            .lines()
+            .filter(|line| line.starts_with("0x"))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    let first = mine("42", &[], 0);
+    assert_eq!(first.len(), 3);
+    assert_eq!(mine("42", &[], 1), first);
+    assert_ne!(mine("43", &[], 2), first);
+
+    // the first results in scan order are kept, however the work size splits the scan
+    let permuted = mine("42", &["--scan-order", "splitmix", "--work-size", "1048576"], 3);
+    assert_eq!(permuted.len(), 3);
+    assert_eq!(mine("42", &["--scan-order", "splitmix", "--work-size", "65536"], 4), permuted);
+}
+
+#[test]
+fn test_csv_output_keeps_one_header_across_runs() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-csv-{}.csv", std::process::id()));
+    // seeded apart, so the second run appends results of its own
+    for _ in 0..2 {
+        let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args([
+                "create3",
+                "--leading",
+                "1",
+                "--limit",
+                "2",
+                "--quiet",
+                "--seed-entropy",
+            ])
+            .args([
+                "--output-format",
+                "csv",
+                "--output",
+                output.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(
+            run.status.success(),
+            "{}",
+            String::from_utf8_lossy(&run.stderr)
+        );
+    }
+
+    let contents = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+    let mut lines = contents.lines();
+    assert_eq!(
+        lines.next(),
+        Some("salt,address,factory,variant,pattern,found_at_unix,attempt")
+    );
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 4);
+    for row in rows {
+        let [salt, address, factory, variant, pattern, found_at_unix, attempt] = row[..] else {
+            panic!("unexpected row {:?}", row);
+        };
+        assert!(parse_salt(salt).is_ok());
+        assert!(address.starts_with("0x0"));
+        assert_eq!(factory, "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+        assert_eq!(variant, "create3");
+        assert_eq!(pattern, "leading-zeros(1)");
+        assert!(found_at_unix.parse::<u64>().unwrap() > 1_700_000_000);
+        assert!(attempt.parse::<u64>().unwrap() > 0);
+    }
+}
+
+#[test]
+fn test_bench_measures_a_hash_rate() {
+    let config = Config::new(
+        0,
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        None,
+        RewardVariant::LeadingZeros { zeros_threshold: 4 },
+        "",
+    )
+    .unwrap();
+    let benchmark = createxcrunch::webgpu::bench(&config, std::time::Duration::from_millis(500)).unwrap();
+    assert!(benchmark.attempts >= benchmark.work_size as u64);
+    assert_eq!(benchmark.attempts % benchmark.work_size as u64, 0);
+    assert!(benchmark.elapsed >= std::time::Duration::from_millis(500));
+    assert!(benchmark.rate() > 0.0);
+}
+
+#[test]
+fn test_jsonl_results_carry_their_metadata() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-jsonl-{}.jsonl", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "2", "--quiet"])
+        .args([
+            "--output-format",
+            "jsonl",
+            "--output",
+            output.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+
+    let contents = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+    let results: Vec<FoundSalt> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert!(parse_salt(&result.salt).is_ok());
+        assert!(result.address.starts_with("0x0"));
+        assert_eq!(result.create_variant, "create3");
+        assert_eq!(result.pattern, "leading-zeros(1)");
+        assert!(result.found_at_unix > 1_700_000_000);
+        assert!(result.attempt > 0);
+    }
+}
+
+#[test]
+fn test_supervised_runs_stopping_at_their_limit_succeed() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-supervised-{}.txt", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "1", "--quiet", "--supervised"])
+        .args(["--output", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+
+    std::fs::remove_file(&output).unwrap();
+    let _ = std::fs::remove_file(createxcrunch::checkpoint::Checkpoint::path(output.to_str().unwrap()));
+    let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+}
+
+#[test]
+fn test_resumed_runs_continue_past_their_checkpoint() {
+    let output = std::env::temp_dir().join(format!("createxcrunch-resume-{}.txt", std::process::id()));
+    let checkpoint = createxcrunch::checkpoint::Checkpoint::path(output.to_str().unwrap());
+    let run_to = |leading: &str, limit: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args(["create3", "--leading", leading, "--limit", limit, "--quiet", "--resume"])
+            .args(["--output", output.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+    let run = |leading: &str| run_to(leading, "2");
+
+    let first = run("1");
+    assert!(first.status.success(), "{}", String::from_utf8_lossy(&first.stderr));
+    let saved = createxcrunch::checkpoint::Checkpoint::load(std::path::Path::new(&checkpoint))
+        .unwrap()
+        .unwrap();
+    assert!(saved.next_index > 0);
+
+    // the results of the first run count towards the limit, resuming at it finds nothing more
+    let finished = run("1");
+    assert!(finished.status.success(), "{}", String::from_utf8_lossy(&finished.stderr));
+    let unchanged = createxcrunch::checkpoint::Checkpoint::load(std::path::Path::new(&checkpoint))
+        .unwrap()
+        .unwrap();
+    assert_eq!(unchanged.found, 2);
+    let lines = |contents: &str| contents.lines().filter(|line| line.starts_with("0x")).count();
+    assert_eq!(lines(&std::fs::read_to_string(&output).unwrap()), 2);
+
+    // the second run starts past the nonces of the first, so it finds results of its own
+    let second = run_to("1", "4");
+    assert!(second.status.success(), "{}", String::from_utf8_lossy(&second.stderr));
+    let resumed = createxcrunch::checkpoint::Checkpoint::load(std::path::Path::new(&checkpoint))
+        .unwrap()
+        .unwrap();
+    assert!(resumed.next_index > saved.next_index);
+    // and counts the attempts and results of both runs
+    assert_eq!((saved.found, resumed.found), (2, 4));
+    assert!(resumed.attempts > saved.attempts);
+    assert!(String::from_utf8_lossy(&second.stdout).contains(" attempts and 2 results"));
+    let contents = std::fs::read_to_string(&output).unwrap();
+    let salts: std::collections::HashSet<_> = contents
+        .lines()
+        .filter(|line| line.starts_with("0x"))
+        .collect();
+    assert_eq!(salts.len(), 4);
+
+    // a checkpoint of another search is refused
+    let mismatched = run("2");
+    assert!(!mismatched.status.success());
+    assert!(
+        String::from_utf8_lossy(&mismatched.stderr).contains("was written for another search and can't be resumed: it searched create3 factory(0xba5ed099633d3b313e4d5f7bdc1305d3c28ba5ed) random leading-zeros(1), this run create3 factory(0xba5ed099633d3b313e4d5f7bdc1305d3c28ba5ed) random leading-zeros(2)"),
+        "{}",
+        String::from_utf8_lossy(&mismatched.stderr)
+    );
+
+    std::fs::remove_file(&output).unwrap();
+    std::fs::remove_file(&checkpoint).unwrap();
+    let _ = std::fs::remove_file(format!("{}.idx", output.display()));
+}
+
+#[test]
+fn test_mine_with_publishes_events_to_subscribers() {
+    let factory = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    let mut config = Config::new(0, factory, None, None, None, reward, "output.txt").unwrap();
+    config.limit = Some(3);
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let published = events.clone();
+    let mut bus = EventBus::new();
+    bus.subscribe("recorder", move |event: &MinerEvent| {
+        published.lock().unwrap().push(event.clone())
+    });
+    let mut matches = vec![];
+    mine_with(config, bus, |found| matches.push(found)).unwrap();
+
+    // every subscriber is finished by the time the run returns
+    let events = events.lock().unwrap();
+    let found: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            MinerEvent::Found(result) => Some((result.salt, result.address)),
+            _ => None,
+        })
+        .collect();
+    let expected: Vec<_> = matches
+        .iter()
+        .map(|found| (found.salt, found.address))
+        .collect();
+    assert_eq!(found, expected);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, MinerEvent::CycleCompleted(_))));
+    assert_eq!(
+        events.last(),
+        Some(&MinerEvent::ShuttingDown(StopReason::Limit))
+    );
+}
+
+#[test]
+fn test_work_sizes_beyond_the_device_limits_are_refused() {
+    let factory = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    let mut config = Config::new(0, factory, None, None, None, reward, "output.txt").unwrap();
+    config.work_size = Some(u32::MAX);
+
+    let err = mine(config, |_| {}).unwrap_err().to_string();
+    assert!(err.contains("more than this device allows"), "{}", err);
+}
+
+#[test]
+fn test_kernel_files_are_mined_with() {
+    let kernel =
+        std::env::temp_dir().join(format!("createxcrunch-kernel-{}.wgsl", std::process::id()));
+    std::fs::write(&kernel, KECCAK256_KERNEL.replace("fn main(", "// tuned\nfn main(")).unwrap();
+    let (_, contents) = mine_quietly(
+        "kernel-file",
+        &["--leading", "1", "--limit", "2", "--kernel-file", kernel.to_str().unwrap()],
+    );
+    std::fs::remove_file(&kernel).unwrap();
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let results: Vec<&str> = contents.lines().filter(|line| line.starts_with("0x")).collect();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        let (salt, address) = result.split_once(" -> ").unwrap();
+        let salt = parse_salt(salt).unwrap();
+        let expected = create3_address(&factory, &SaltVariant::Random.guard(&salt));
+        assert_eq!(address.parse::<Address>().unwrap(), Address::from(expected));
+    }
+}
+
+#[test]
+fn test_no_file_prints_results_to_stdout() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-no-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "3", "--no-file"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let created: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(run.status.success(), "{}", stderr);
+    assert!(created.is_empty());
+
+    // stdout holds nothing but the results, the progress goes to stderr
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    assert_eq!(stdout.lines().count(), 3, "{}", stdout);
+    for line in stdout.lines() {
+        let (salt, address) = line.split_once(" -> ").unwrap();
+        let expected = create3_address(&factory, &SaltVariant::Random.guard(&parse_salt(salt).unwrap()));
+        assert_eq!(address.parse::<Address>().unwrap(), Address::from(expected));
+    }
+    assert!(stderr.contains("Mining started."));
+    assert!(stderr.contains("Found 3 results in "));
+}
+
+#[test]
+fn test_params_stdin_keeps_the_params_out_of_the_header() {
+    use std::io::Write;
+    let output =
+        std::env::temp_dir().join(format!("createxcrunch-params-{}.txt", std::process::id()));
+    let caller = "88c6C46EBf353A52Bdbab708c23D0c81dAA8134A";
+    let init_code_hash = [0x11; 32];
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create2", "--leading", "1", "--limit", "2", "--quiet", "--params-stdin"])
+        .args(["--output", output.to_str().unwrap()])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    write!(
+        child.stdin.take().unwrap(),
+        r#"{{"caller": "0x{}", "init_code_hash": "0x{}"}}"#,
+        caller,
+        hex::encode(init_code_hash)
+    )
+    .unwrap();
+    let run = child.wait_with_output().unwrap();
+    let contents = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+
+    let header: Vec<&str> = contents.lines().filter(|line| line.starts_with('#')).collect();
+    assert!(header[0].starts_with("# Mining create2(<redacted>) for leading-zeros(1) with sender(<redacted>) salts"));
+    assert!(!header.join("\n").contains(&hex::encode(init_code_hash)));
+    assert!(!header.join("\n").to_lowercase().contains(&caller.to_lowercase()));
+    // the recorded hashes check the params the results were mined for
+    let recorded = header.iter().find_map(|line| params::ParamHashes::parse_header(line)).unwrap();
+    let factory = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let caller: [u8; 20] = hex::decode(caller).unwrap().try_into().unwrap();
+    assert_eq!(recorded.check(&params::ParamHashes::new(&factory, Some(&caller), Some(&init_code_hash))), Ok(()));
+    assert!(recorded.check(&params::ParamHashes::new(&factory, Some(&caller), Some(&[0; 32]))).is_err());
+}
+
+#[test]
+fn test_recorded_cycles_replay_in_agreement() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-records-{}", std::process::id()));
+    let output = std::env::temp_dir().join(format!("createxcrunch-records-{}.txt", std::process::id()));
+    let run = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--leading", "1", "--limit", "1", "--quiet", "--record-cycles"])
+        .arg(&dir)
+        .arg("--output")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+
+    let record = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|extension| extension == "replay"))
+        .expect("a cycle with a result is recorded");
+    let replay = std::process::Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("replay")
+        .arg(&record)
+        .output()
+        .unwrap();
+    assert!(replay.status.success());
+    assert!(!String::from_utf8_lossy(&replay.stderr).contains("Only replaying on the CPU"));
+    assert!(String::from_utf8_lossy(&replay.stdout).ends_with("All candidates agree\n"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_file(&output).unwrap();
+}
@@ -0,0 +1,174 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use createxcrunch::{
+    index::{address_of_line, index_path, salt_key, salt_of_line, SaltIndex, MAGIC},
+    writer::ResultWriter,
+};
+
+fn temp_output(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "createxcrunch-index-{}-{}",
+        name,
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join("output.txt")
+}
+
+fn salt(byte: u8) -> [u8; 32] {
+    [byte; 32]
+}
+
+fn line(byte: u8) -> String {
+    format!("0x{} -> 0x{:040x}", hex::encode(salt(byte)), byte)
+}
+
+/// Append the results to `output` through an indexed writer, as one run would.
+fn run(output: &PathBuf, results: &[u8]) -> (u64, bool) {
+    let (index, rebuilt) = SaltIndex::open(output).unwrap();
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output)
+        .unwrap();
+    let mut writer = ResultWriter::new(file, Vec::new(), 4).indexed(index);
+    for byte in results {
+        writer.write_result(&line(*byte), &[0; 20]);
+    }
+    (writer.duplicates(), rebuilt)
+}
+
+#[test]
+fn test_salt_of_line() {
+    assert_eq!(salt_of_line(&line(7)), Some(salt(7)));
+    let json = format!(
+        "{{\"salt\":\"0x{}\",\"address\":\"0x00\"}}",
+        hex::encode(salt(8))
+    );
+    assert_eq!(salt_of_line(&json), Some(salt(8)));
+    let with_format = format!("0x{} BwcH -> 0x00", hex::encode(salt(9)));
+    assert_eq!(salt_of_line(&with_format), Some(salt(9)));
+    assert_eq!(salt_of_line("# Format: salt -> ethereum_address"), None);
+    assert_eq!(salt_of_line("salt:0a1b2c3d -> 0x00"), None);
+    assert_eq!(salt_of_line(""), None);
+}
+
+#[test]
+fn test_restart_skips_written_results() {
+    let output = temp_output("restart");
+    fs::write(&output, "# header\n\n").unwrap();
+
+    assert_eq!(run(&output, &[1, 2, 3]), (0, true));
+    // the index covers the output file, so the next run loads it as is
+    assert_eq!(run(&output, &[3, 4, 2]), (2, false));
+
+    let contents = fs::read_to_string(&output).unwrap();
+    let salts: Vec<_> = contents.lines().filter_map(salt_of_line).collect();
+    assert_eq!(salts, vec![salt(1), salt(2), salt(3), salt(4)]);
+    assert_eq!(SaltIndex::open(&output).unwrap().0.len(), 4);
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_index_rebuilt_when_stale_or_corrupt() {
+    let output = temp_output("rebuild");
+    let index_file = index_path(output.to_str().unwrap());
+    run(&output, &[1, 2]);
+
+    // results appended without the index
+    let mut file = fs::OpenOptions::new().append(true).open(&output).unwrap();
+    writeln!(file, "{}", line(3)).unwrap();
+    let (index, rebuilt) = SaltIndex::open(&output).unwrap();
+    assert!(rebuilt);
+    assert!(index.contains(&salt(3)).unwrap());
+    drop(index);
+
+    // an index of another version
+    let mut contents = fs::read(&index_file).unwrap();
+    assert_eq!(contents[..8], MAGIC);
+    contents[7] = 0xff;
+    fs::write(&index_file, &contents).unwrap();
+    assert!(SaltIndex::open(&output).unwrap().1);
+
+    // a torn key
+    let mut contents = fs::read(&index_file).unwrap();
+    contents.push(0);
+    fs::write(&index_file, &contents).unwrap();
+    assert!(SaltIndex::open(&output).unwrap().1);
+
+    fs::remove_file(&index_file).unwrap();
+    let (index, rebuilt) = SaltIndex::open(&output).unwrap();
+    assert!(rebuilt);
+    assert_eq!(index.len(), 3);
+    assert!(!SaltIndex::open(&output).unwrap().1);
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_key_collision_confirmed_against_results() {
+    let output = temp_output("collision");
+    fs::write(&output, "").unwrap();
+
+    // an index holding the key of salt 5, whose result is not in the output file, stands in
+    // for another salt sharing the key
+    let mut contents = MAGIC.to_vec();
+    contents.extend_from_slice(&0u64.to_le_bytes());
+    contents.extend_from_slice(&salt_key(&salt(5)).to_le_bytes());
+    fs::write(index_path(output.to_str().unwrap()), contents).unwrap();
+
+    let (index, rebuilt) = SaltIndex::open(&output).unwrap();
+    assert!(!rebuilt);
+    assert!(!index.contains(&salt(5)).unwrap());
+    drop(index);
+
+    assert_eq!(run(&output, &[5]), (0, false));
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        format!("{}\n", line(5))
+    );
+    assert_eq!(run(&output, &[5]), (1, false));
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_recent_addresses_of_earlier_runs() {
+    let mut address = [0; 20];
+    address[19] = 7;
+    assert_eq!(address_of_line(&line(7)), Some(address));
+    // results of several patterns name the one they matched
+    let annotated = format!("{} pattern(00...07)", line(7));
+    assert_eq!(address_of_line(&annotated), Some(address));
+    let json = format!(
+        "{{\"salt\":\"0x{}\",\"address\":\"0x{}\"}}",
+        hex::encode(salt(8)),
+        "ab".repeat(20)
+    );
+    assert_eq!(address_of_line(&json), Some([0xab; 20]));
+    assert_eq!(address_of_line("# Format: salt -> ethereum_address"), None);
+    assert_eq!(address_of_line("0x01 -> 0xshort"), None);
+
+    let output = temp_output("recent");
+    let (index, _) = SaltIndex::open(&output).unwrap();
+    // no results file yet
+    assert!(index.recent_addresses(4).unwrap().is_empty());
+
+    fs::write(&output, "# header\n\n").unwrap();
+    run(&output, &[1, 2, 3]);
+    let (index, _) = SaltIndex::open(&output).unwrap();
+    let last = |limit| -> Vec<u8> {
+        index
+            .recent_addresses(limit)
+            .unwrap()
+            .iter()
+            .map(|address| address[19])
+            .collect()
+    };
+    assert_eq!(last(2), vec![2, 3]);
+    assert_eq!(last(8), vec![1, 2, 3]);
+    assert!(last(0).is_empty());
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
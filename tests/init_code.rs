@@ -0,0 +1,104 @@
+use alloy_primitives::{hex, keccak256};
+use createxcrunch::{init_code_hash, parse_hex_code, parse_init_code_hash, ConfigError};
+use std::process::Command;
+
+const INIT_CODE: &str = "6080604052348015600e575f80fd5b50";
+
+#[test]
+fn test_parse_hex_code() {
+    let init_code = hex::decode(INIT_CODE).unwrap();
+    assert_eq!(parse_hex_code(INIT_CODE).unwrap(), init_code);
+    // compiler output wraps lines and may carry the prefix
+    assert_eq!(
+        parse_hex_code(&format!("0x{}\n{}\n", &INIT_CODE[..10], &INIT_CODE[10..])).unwrap(),
+        init_code
+    );
+
+    assert_eq!(
+        parse_hex_code("0x608"),
+        Err("odd number of hex digits (3)".to_string())
+    );
+    assert!(parse_hex_code("0x60zz").is_err());
+}
+
+#[test]
+fn test_init_code_hash_appends_constructor_args() {
+    let init_code = hex::decode(INIT_CODE).unwrap();
+    let args = [0u8; 32];
+    assert_eq!(init_code_hash(&init_code, &[]), keccak256(&init_code).0);
+    assert_eq!(
+        init_code_hash(&init_code, &args),
+        keccak256([init_code.as_slice(), &args].concat()).0
+    );
+}
+
+#[test]
+fn test_parse_init_code_hash() {
+    assert_eq!(parse_init_code_hash(&"ab".repeat(32)), Ok([0xab; 32]));
+    assert_eq!(
+        parse_init_code_hash(&"ab".repeat(31)),
+        Err(ConfigError::InvalidInitCodeHashLength(31))
+    );
+    assert_eq!(
+        parse_init_code_hash("zz"),
+        Err(ConfigError::InvalidInitCodeHashHex)
+    );
+    assert_eq!(
+        ConfigError::InvalidInitCodeHashLength(33).to_string(),
+        "init code hash must be 32 bytes, got 33"
+    );
+}
+
+#[test]
+fn test_create2_rejects_short_code_hash() {
+    let output = Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args([
+            "create2",
+            "--code-hash",
+            &"ab".repeat(31),
+            "--matching",
+            "de",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("Error: init code hash must be 32 bytes, got 31"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"));
+}
+
+fn hash_init_code(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("hash-init-code")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_hash_init_code_command() {
+    let init_code = hex::decode(INIT_CODE).unwrap();
+    let path = std::env::temp_dir().join(format!("createxcrunch-{}.bin", std::process::id()));
+    std::fs::write(&path, format!("0x{}\n", INIT_CODE)).unwrap();
+
+    let args = format!("0x{}", "00".repeat(31) + "2a");
+    let output = hash_init_code(&[path.to_str().unwrap(), "--constructor-args", &args]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let expected = init_code_hash(&init_code, &hex::decode(&args).unwrap());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("0x{}\n", hex::encode(expected))
+    );
+
+    let output = hash_init_code(&["0x6080604"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error: invalid init code: odd number of hex digits (7)\n"
+    );
+}
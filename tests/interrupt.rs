@@ -0,0 +1,15 @@
+#![cfg(unix)]
+
+use createxcrunch::interrupt;
+
+#[test]
+fn test_signal_raises_the_interrupt_flag() {
+    interrupt::install();
+    // installing twice keeps the handler
+    interrupt::install();
+    assert!(!interrupt::interrupted());
+
+    // SAFETY: the handler installed above only raises the flag on the first signal
+    unsafe { libc::raise(libc::SIGTERM) };
+    assert!(interrupt::interrupted());
+}
@@ -0,0 +1,128 @@
+use createxcrunch::{kernel_source, Config, RewardVariant, KECCAK256_KERNEL};
+
+const FACTORY: &str = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+const CALLER: &str = "88c6C46EBf353A52Bdbab708c23D0c81dAA8134A";
+const CODE_HASH: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+fn config(caller: Option<&str>, chain_id: Option<u64>, code_hash: Option<&str>) -> Config<'static> {
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 4 };
+    Config::new(
+        0,
+        FACTORY,
+        caller,
+        chain_id,
+        code_hash,
+        reward,
+        "output.txt",
+    )
+    .unwrap()
+}
+
+/// Kernel headers of representative configs. A change here changes the source every pipeline
+/// is built from, make sure it is intended.
+const GOLDEN: [(&str, &str); 7] = [
+    (
+        "create3 random",
+        "// createXcrunch mining kernel 0x30dcde1d41b13ae5\n// override DERIVATION = 2\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 32\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create3 sender",
+        "// createXcrunch mining kernel 0xb1e0cdc2351fd6e4\n// override DERIVATION = 2\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 64\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create3 crosschain",
+        "// createXcrunch mining kernel 0xb1e0cdc2351fd6e4\n// override DERIVATION = 2\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 64\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create3 crosschain sender",
+        "// createXcrunch mining kernel 0xff02768104deb078\n// override DERIVATION = 2\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 96\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create3 crosschain dual salt",
+        "// createXcrunch mining kernel 0xa86eb7f4eeda8dff\n// override DERIVATION = 2\n// override DUAL_SALT = 1\n// override SALT_GUARD_SIZE = 64\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create2 random",
+        "// createXcrunch mining kernel 0xfd2a82f14015a94f\n// override DERIVATION = 1\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 32\n// override WORKGROUP_SIZE = 256\n",
+    ),
+    (
+        "create2 crosschain sender",
+        "// createXcrunch mining kernel 0xbb669be573089e0c\n// override DERIVATION = 1\n// override DUAL_SALT = 0\n// override SALT_GUARD_SIZE = 96\n// override WORKGROUP_SIZE = 256\n",
+    ),
+];
+
+fn matrix() -> Vec<(&'static str, Config<'static>)> {
+    vec![
+        ("create3 random", config(None, None, None)),
+        ("create3 sender", config(Some(CALLER), None, None)),
+        ("create3 crosschain", config(None, Some(1), None)),
+        (
+            "create3 crosschain sender",
+            config(Some(CALLER), Some(1), None),
+        ),
+        ("create3 crosschain dual salt", {
+            let mut config = config(None, Some(1), None);
+            config.dual_salt = true;
+            config
+        }),
+        ("create2 random", config(None, None, Some(CODE_HASH))),
+        (
+            "create2 crosschain sender",
+            config(Some(CALLER), Some(10), Some(CODE_HASH)),
+        ),
+    ]
+}
+
+#[test]
+fn test_kernel_source_snapshots() {
+    for ((name, config), (golden_name, header)) in matrix().into_iter().zip(GOLDEN) {
+        assert_eq!(name, golden_name);
+        let source = kernel_source(&config);
+        assert_eq!(
+            source.strip_suffix(KECCAK256_KERNEL),
+            Some(header),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_kernel_source_is_reproducible() {
+    for (name, config) in matrix() {
+        assert_eq!(kernel_source(&config), kernel_source(&config), "{}", name);
+    }
+
+    // only what the kernel is specialized for counts
+    let mut other = config(None, None, None);
+    other.output = "other.txt";
+    other.reward = RewardVariant::TotalZeros { zeros_threshold: 6 };
+    assert_eq!(
+        kernel_source(&other),
+        kernel_source(&config(None, None, None))
+    );
+}
+
+#[test]
+fn test_configs_differ_only_in_their_constants() {
+    let baseline = kernel_source(&config(None, None, None));
+    for (name, config) in matrix().into_iter().skip(1) {
+        let source = kernel_source(&config);
+        let changed: Vec<(&str, &str)> = baseline
+            .lines()
+            .zip(source.lines())
+            .filter(|(a, b)| a != b)
+            .collect();
+        assert_eq!(baseline.lines().count(), source.lines().count(), "{}", name);
+        assert!(
+            changed
+                .iter()
+                .all(|(a, _)| a.starts_with("// createXcrunch mining kernel")
+                    || a.starts_with("// override ")),
+            "{}: {:?}",
+            name,
+            changed
+        );
+        assert!(!changed.is_empty(), "{}", name);
+    }
+}
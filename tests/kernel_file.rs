@@ -0,0 +1,156 @@
+use createxcrunch::{
+    kernel_file::{check, load},
+    kernel_source, Config, RewardVariant, KECCAK256_KERNEL,
+};
+use std::{fs, path::PathBuf};
+
+fn kernel_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "createxcrunch-{}-{}.wgsl",
+        name,
+        std::process::id()
+    ))
+}
+
+/// The embedded kernel with `from` replaced by `to`.
+fn edited(from: &str, to: &str) -> String {
+    assert!(KECCAK256_KERNEL.contains(from), "{}", from);
+    KECCAK256_KERNEL.replacen(from, to, 1)
+}
+
+#[test]
+fn test_embedded_kernel_passes() {
+    assert_eq!(check(KECCAK256_KERNEL, "keccak256.wgsl"), Ok(()));
+}
+
+#[test]
+fn test_missing_kernel_file_errors() {
+    let path = kernel_path("missing");
+    assert_eq!(
+        load(&path),
+        Err(format!("kernel file {} not found", path.display()))
+    );
+}
+
+#[test]
+fn test_kernel_file_is_loaded() {
+    let path = kernel_path("loaded");
+    fs::write(&path, KECCAK256_KERNEL).unwrap();
+    let loaded = load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.as_deref(), Ok(KECCAK256_KERNEL));
+}
+
+#[test]
+fn test_broken_kernel_file_is_not_loaded() {
+    let path = kernel_path("broken");
+    fs::write(&path, "fn main( {").unwrap();
+    let loaded = load(&path);
+    fs::remove_file(&path).unwrap();
+    let error = loaded.unwrap_err();
+    assert!(
+        error.starts_with(&format!("kernel file {}:1:", path.display())),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_parse_errors_give_their_line() {
+    let source = edited("fn main(", "fn main((");
+    let line = source[..source.find("fn main((").unwrap()].lines().count() + 1;
+    let error = check(&source, "tuned.wgsl").unwrap_err();
+    assert!(
+        error.starts_with(&format!("kernel file tuned.wgsl:{}:", line)),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_invalid_kernels_are_refused() {
+    let source = format!(
+        "{}\nfn broken() -> u32 {{ return 1.0; }}\n",
+        KECCAK256_KERNEL
+    );
+    let error = check(&source, "tuned.wgsl").unwrap_err();
+    assert!(error.contains("does not validate"), "{}", error);
+    assert!(error.contains("broken"), "{}", error);
+}
+
+#[test]
+fn test_missing_entry_point_errors() {
+    let source = edited("fn main(", "fn mine(");
+    assert_eq!(
+        check(&source, "tuned.wgsl"),
+        Err("kernel file tuned.wgsl has no compute entry point `main`".to_string())
+    );
+}
+
+#[test]
+fn test_missing_override_errors() {
+    let source = edited(
+        "override SALT_GUARD_SIZE: u32",
+        "const SALT_GUARD_SIZE: u32",
+    );
+    assert_eq!(
+        check(&source, "tuned.wgsl"),
+        Err(
+            "kernel file tuned.wgsl does not declare the override constant SALT_GUARD_SIZE"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_binding_mismatches_error() {
+    let source = edited(
+        "@group(0) @binding(3) var<storage, read_write> hashes",
+        "@group(0) @binding(3) var<storage, read> hashes",
+    );
+    let error = check(&source, "tuned.wgsl");
+    // the kernel writes the hashes, so either naga or the binding check refuses it
+    assert!(error.is_err());
+
+    let source = edited(
+        "@group(0) @binding(1) var<storage, read> num_messages",
+        "@group(0) @binding(1) var<storage, read_write> num_messages",
+    );
+    assert_eq!(
+        check(&source, "tuned.wgsl"),
+        Err("kernel file tuned.wgsl declares @group(0) @binding(1) as var<storage, read_write>, the miner binds var<storage, read> there".to_string())
+    );
+
+    let source = edited(
+        "@group(0) @binding(10) var<uniform>",
+        "@group(0) @binding(11) var<uniform>",
+    );
+    assert_eq!(
+        check(&source, "tuned.wgsl"),
+        Err(
+            "kernel file tuned.wgsl declares @group(0) @binding(11), which the miner doesn't bind"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_kernel_source_uses_the_kernel_file() {
+    let mut config = Config::new(
+        0,
+        "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        None,
+        RewardVariant::LeadingZeros { zeros_threshold: 2 },
+        "results.txt",
+    )
+    .unwrap();
+    let embedded = kernel_source(&config);
+    let tuned = edited("fn main(", "// tuned\nfn main(");
+    config.kernel = Some(tuned.clone());
+    let source = kernel_source(&config);
+    assert!(source.ends_with(&tuned));
+    // the header hash changes with the kernel, keeping pipeline caches apart
+    assert_ne!(source.lines().next(), embedded.lines().next());
+}
@@ -0,0 +1,368 @@
+use createxcrunch::{
+    check_csv_header, check_embeddable, check_no_file, check_output_format, csv_header,
+    index::{address_of_line, salt_of_line},
+    output_file,
+    writer::SplitBy,
+    Config, CreateVariant, FoundSalt, OutputFormat, OutputOpened, RewardVariant, SaltFormat,
+};
+use std::{fs, io::Write, path::PathBuf};
+
+fn output_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("createxcrunch-{}-{}.txt", name, std::process::id()))
+}
+
+#[test]
+fn test_append_json_to_text_file_errors() {
+    let path = output_path("text-headed");
+    fs::write(
+        &path,
+        "# Mining for addresses matching pattern\n# Format: salt -> ethereum_address\n\n0x01 -> 0xBB00000000000000000000000000000000000000\n",
+    )
+    .unwrap();
+
+    let err = check_output_format(&path, OutputFormat::Json).unwrap_err();
+    assert!(err.contains("refusing to append json"), "{}", err);
+    assert!(check_output_format(&path, OutputFormat::Text).is_ok());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_append_text_to_json_file_errors() {
+    let path = output_path("json");
+    let line =
+        OutputFormat::Json.format_result("0x01", "0xBB00000000000000000000000000000000000000");
+    fs::write(&path, format!("{}\n", line)).unwrap();
+
+    assert!(check_output_format(&path, OutputFormat::Text).is_err());
+    assert!(check_output_format(&path, OutputFormat::Json).is_ok());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_empty_or_missing_output_accepts_any_format() {
+    let path = output_path("empty");
+    assert!(check_output_format(&path, OutputFormat::Json).is_ok());
+
+    fs::write(&path, "\n").unwrap();
+    assert!(check_output_format(&path, OutputFormat::Json).is_ok());
+    assert!(check_output_format(&path, OutputFormat::Text).is_ok());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_json_result_has_no_comment() {
+    let line =
+        OutputFormat::Json.format_result("0x01", "0xBB00000000000000000000000000000000000000");
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+    assert_eq!(value["salt"], "0x01");
+    assert_eq!(
+        value["address"],
+        "0xBB00000000000000000000000000000000000000"
+    );
+    assert!(!line.contains('#'));
+}
+
+#[test]
+fn test_jsonl_result_fields() {
+    let salt = [0x11; 32];
+    let factory = alloy_primitives::hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let create2 = CreateVariant::Create2 {
+        init_code_hash: [0xab; 32],
+    };
+    let leading = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    let found = FoundSalt::new(
+        &salt,
+        "0x00b77830AAd45015B805AaA8AFa5E31D346126a8",
+        &factory,
+        &create2,
+        &leading,
+        1_700_000_000,
+        42_000_000,
+    );
+    let line = found.to_line();
+    let result: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+    assert_eq!(result["salt"], format!("0x{}", "11".repeat(32)));
+    assert_eq!(
+        result["address"],
+        "0x00b77830AAd45015B805AaA8AFa5E31D346126a8"
+    );
+    assert_eq!(
+        result["factory"],
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed"
+    );
+    assert_eq!(
+        result["create_variant"],
+        format!("create2(0x{})", "ab".repeat(32))
+    );
+    assert_eq!(result["pattern"], "leading-zeros(1)");
+    assert_eq!(result["found_at_unix"], 1_700_000_000);
+    assert_eq!(result["attempt"], 42_000_000);
+    assert_eq!(
+        FoundSalt::new(
+            &salt,
+            "0x00",
+            &factory,
+            &CreateVariant::Create3,
+            &leading,
+            0,
+            0
+        )
+        .create_variant,
+        "create3"
+    );
+    assert_eq!(serde_json::from_str::<FoundSalt>(&line).unwrap(), found);
+
+    let with_base64 = found.with_salt_format(SaltFormat::Base64, &salt).to_line();
+    let value: serde_json::Value = serde_json::from_str(&with_base64).unwrap();
+    assert_eq!(
+        value["salt_base64"],
+        "ERERERERERERERERERERERERERERERERERERERERERE="
+    );
+
+    // one object per line, which the salt index reads like JSON results
+    assert!(!line.contains('\n'));
+    assert_eq!(salt_of_line(&line), Some(salt));
+}
+
+#[test]
+fn test_jsonl_files_are_not_appended_to_as_json() {
+    let path = output_path("jsonl");
+    let leading = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+    let line = FoundSalt::new(
+        &[1; 32],
+        "0x00",
+        &[0; 20],
+        &CreateVariant::Create3,
+        &leading,
+        0,
+        0,
+    )
+    .to_line();
+    fs::write(&path, format!("{}\n", line)).unwrap();
+
+    assert_eq!(OutputFormat::detect(&line), OutputFormat::JsonLines);
+    assert!(check_output_format(&path, OutputFormat::JsonLines).is_ok());
+    let err = check_output_format(&path, OutputFormat::Json).unwrap_err();
+    assert!(err.contains("holds jsonl results"), "{}", err);
+    assert_eq!("jsonl".parse(), Ok(OutputFormat::JsonLines));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_csv_results() {
+    assert_eq!(
+        csv_header(None),
+        "salt,address,factory,variant,pattern,found_at_unix,attempt"
+    );
+    assert_eq!(
+        csv_header(Some(SaltFormat::Base64)),
+        "salt,address,factory,variant,pattern,found_at_unix,attempt,salt_base64"
+    );
+
+    let salt = [0x11; 32];
+    let address = "0x00b77830AAd45015B805AaA8AFa5E31D346126a8";
+    let factory = alloy_primitives::hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+    let leading = RewardVariant::LeadingZeros { zeros_threshold: 2 };
+    let row = FoundSalt::new(
+        &salt,
+        address,
+        &factory,
+        &CreateVariant::Create3,
+        &leading,
+        1_700_000_000,
+        4_194_304,
+    )
+    .to_csv_row();
+    assert_eq!(
+        row,
+        format!(
+            "0x{},{},0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed,create3,leading-zeros(2),1700000000,4194304",
+            "11".repeat(32),
+            address
+        )
+    );
+
+    // rewards with several thresholds hold a comma and are quoted
+    let both = RewardVariant::LeadingAndTotalZeros {
+        leading_zeros_threshold: 2,
+        total_zeros_threshold: 6,
+    };
+    let create2 = CreateVariant::Create2 {
+        init_code_hash: [0xab; 32],
+    };
+    let row = FoundSalt::new(&salt, address, &factory, &create2, &both, 0, 1)
+        .with_salt_format(SaltFormat::Uint256Dec, &salt)
+        .to_csv_row();
+    assert!(
+        row.contains(&format!(
+            ",create2(0x{}),\"leading-and-total-zeros(2,6)\",0,1,",
+            "ab".repeat(32)
+        )),
+        "{}",
+        row
+    );
+    assert_eq!(row.split(',').count(), 9);
+
+    // the salt index reads rows like other results, and skips the header row
+    assert_eq!(salt_of_line(&row), Some(salt));
+    assert_eq!(
+        address_of_line(&row),
+        Some(alloy_primitives::hex!(
+            "00b77830AAd45015B805AaA8AFa5E31D346126a8"
+        ))
+    );
+    assert_eq!(salt_of_line(&csv_header(None)), None);
+
+    let path = output_path("csv");
+    fs::write(&path, format!("{}\n{}\n", csv_header(None), row)).unwrap();
+    assert_eq!(OutputFormat::detect(&csv_header(None)), OutputFormat::Csv);
+    assert!(check_output_format(&path, OutputFormat::Csv).is_ok());
+    let err = check_output_format(&path, OutputFormat::Text).unwrap_err();
+    assert!(err.contains("holds csv results"), "{}", err);
+    assert_eq!("csv".parse(), Ok(OutputFormat::Csv));
+
+    // rows of other columns would not line up with the header
+    assert!(check_csv_header(&path, &csv_header(None)).is_ok());
+    let err = check_csv_header(&path, &csv_header(Some(SaltFormat::Base64))).unwrap_err();
+    assert!(err.contains("refusing to append rows"), "{}", err);
+    fs::write(&path, "salt,address,pattern,found_at_unix,attempt\n").unwrap();
+    assert!(check_csv_header(&path, &csv_header(None)).is_err());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_output_file_keeps_earlier_results() {
+    let path = output_path("append");
+    let _ = fs::remove_file(&path);
+    let name = path.to_str().unwrap();
+
+    let (mut file, opened) = output_file(name, false).unwrap();
+    assert_eq!(opened, OutputOpened::Created);
+    writeln!(file, "0x01 -> 0xBB00000000000000000000000000000000000000").unwrap();
+    drop(file);
+
+    // a restart appends to the results of the run before it
+    let (mut file, opened) = output_file(name, false).unwrap();
+    assert_eq!(opened, OutputOpened::Appended);
+    writeln!(file, "0x02 -> 0xBB00000000000000000000000000000000000001").unwrap();
+    drop(file);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+    let (_, opened) = output_file(name, true).unwrap();
+    assert_eq!(opened, OutputOpened::Overwritten);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_output_file_is_locked_while_written() {
+    let path = output_path("locked");
+    fs::write(
+        &path,
+        "0x01 -> 0xBB00000000000000000000000000000000000000\n",
+    )
+    .unwrap();
+    let name = path.to_str().unwrap();
+
+    let (running, _) = output_file(name, false).unwrap();
+    // a second run neither writes to the file nor clears it
+    let err = output_file(name, true).unwrap_err();
+    assert!(err.contains("locked by another run"), "{}", err);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+    drop(running);
+    assert_eq!(output_file(name, false).unwrap().1, OutputOpened::Appended);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_embedded_runs_refuse_output_file_options() {
+    let config = || {
+        Config::new(
+            0,
+            "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+            None,
+            None,
+            None,
+            RewardVariant::LeadingZeros { zeros_threshold: 1 },
+            "output.txt",
+        )
+        .unwrap()
+    };
+    assert_eq!(check_embeddable(&config()), Ok(()));
+
+    let mut split = config();
+    split.split_output = Some(SplitBy::LeadingZeros);
+    assert_eq!(
+        check_embeddable(&split),
+        Err(
+            "--split-output-by works on the output file, which runs with a match callback don't write"
+                .to_string()
+        )
+    );
+    let mut supervised = config();
+    supervised.supervised = true;
+    assert!(check_embeddable(&supervised)
+        .unwrap_err()
+        .starts_with("--supervised"));
+    let mut resume = config();
+    resume.resume = true;
+    assert!(check_embeddable(&resume)
+        .unwrap_err()
+        .starts_with("--resume"));
+    // the format only shapes lines of the output file, embedded runs ignore it
+    let mut json = config();
+    json.format = OutputFormat::Json;
+    assert_eq!(check_embeddable(&json), Ok(()));
+}
+
+#[test]
+fn test_no_file_runs_refuse_output_file_options() {
+    let config = || {
+        let mut config = Config::new(
+            0,
+            "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+            None,
+            None,
+            None,
+            RewardVariant::LeadingZeros { zeros_threshold: 1 },
+            "output.txt",
+        )
+        .unwrap();
+        config.no_file = true;
+        config
+    };
+    assert_eq!(check_no_file(&config()), Ok(()));
+
+    let mut resume = config();
+    resume.resume = true;
+    assert_eq!(
+        check_no_file(&resume),
+        Err("--resume works on the output file, which --no-file runs don't write".to_string())
+    );
+    let mut safe_tx = config();
+    safe_tx.format = OutputFormat::SafeTx;
+    assert!(check_no_file(&safe_tx)
+        .unwrap_err()
+        .starts_with("--format safe-tx"));
+    let mut json_events = config();
+    json_events.json_events = true;
+    assert!(check_no_file(&json_events)
+        .unwrap_err()
+        .starts_with("--json"));
+    // results are printed in any line format, and sorted or copied like those of the file
+    let mut sorted = config();
+    sorted.format = OutputFormat::Csv;
+    sorted.sort_on_exit = Some(10);
+    sorted.clipboard = true;
+    assert_eq!(check_no_file(&sorted), Ok(()));
+}
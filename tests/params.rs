@@ -0,0 +1,206 @@
+use createxcrunch::{
+    params::{self, check_redactable, read, ParamHashes, Params, REDACTED},
+    Config, OutputFormat, RewardVariant,
+};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const CALLER: &str = "88c6C46EBf353A52Bdbab708c23D0c81dAA8134A";
+const CODE_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+fn config(redact_params: bool) -> Config<'static> {
+    let mut config = Config::new(
+        0,
+        "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        Some(CALLER),
+        Some(1),
+        Some(CODE_HASH),
+        RewardVariant::LeadingZeros { zeros_threshold: 2 },
+        "output.txt",
+    )
+    .unwrap();
+    config.redact_params = redact_params;
+    config
+}
+
+#[test]
+fn test_params_are_read_by_option_name() {
+    let input = format!(
+        r#"{{"factory": "createx", "caller": "0x{}", "init_code_hash": "{}"}}"#,
+        CALLER, CODE_HASH
+    );
+    assert_eq!(
+        read(input.as_bytes()),
+        Ok(Params {
+            factory: Some("createx".to_string()),
+            caller: Some(format!("0x{}", CALLER)),
+            init_code_hash: Some(CODE_HASH.to_string()),
+        })
+    );
+    let params = read(r#"{"code-hash": "0x00"}"#.as_bytes()).unwrap();
+    assert_eq!(params.init_code_hash.as_deref(), Some("0x00"));
+    assert_eq!(params.factory, None);
+}
+
+#[test]
+fn test_params_errors_never_show_values() {
+    assert_eq!(
+        read(r#"{"factory": "0xsecret", "leading": "4"}"#.as_bytes()),
+        Err(
+            "unknown key leading in --params-stdin (expected factory, caller or init_code_hash)"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        read(r#"{"caller": 12345}"#.as_bytes()),
+        Err("caller in --params-stdin must be a string".to_string())
+    );
+    assert_eq!(
+        read(r#"{"code_hash": "0x01", "init_code_hash": "0x02"}"#.as_bytes()),
+        Err("--params-stdin gives --code-hash twice".to_string())
+    );
+    assert_eq!(
+        read(r#"["0xsecret"]"#.as_bytes()),
+        Err("--params-stdin must be a JSON object".to_string())
+    );
+    let error = read(r#"{"factory": "0xsecret"#.as_bytes()).unwrap_err();
+    assert!(error.starts_with("--params-stdin is not valid JSON (line 1"));
+    assert!(!error.contains("secret"));
+}
+
+#[test]
+fn test_redacted_params_are_kept_out_of_logs() {
+    let redacted = config(true);
+    assert_eq!(params::factory(&redacted), REDACTED);
+    assert_eq!(params::create_variant(&redacted), "create2(<redacted>)");
+    assert_eq!(
+        params::salt_variant(&redacted),
+        "sender(<redacted>)+chain(1)"
+    );
+    let fingerprint = redacted.fingerprint();
+    assert!(fingerprint.starts_with("params(0x"), "{}", fingerprint);
+    assert!(!fingerprint.to_lowercase().contains(&CALLER.to_lowercase()));
+
+    let shown = config(false);
+    assert_eq!(
+        params::factory(&shown),
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed"
+    );
+    assert_eq!(
+        params::create_variant(&shown),
+        format!("create2({})", CODE_HASH)
+    );
+    assert!(shown.fingerprint().contains(&CODE_HASH[2..]));
+}
+
+#[test]
+fn test_redacted_params_refuse_revealing_options() {
+    let mut safe_tx = config(true);
+    safe_tx.format = OutputFormat::SafeTx;
+    assert!(check_redactable(&safe_tx)
+        .unwrap_err()
+        .starts_with("--format safe-tx"));
+    let mut rpc = config(true);
+    rpc.rpc_url = Some("http://localhost:8545");
+    assert!(check_redactable(&rpc).unwrap_err().starts_with("--rpc-url"));
+    rpc.redact_params = false;
+    assert_eq!(check_redactable(&rpc), Ok(()));
+}
+
+#[test]
+fn test_header_hashes_check_the_params() {
+    let config = config(true);
+    let header = ParamHashes::of(&config).to_header();
+    assert!(header.starts_with("# Params: factory=0x"));
+    assert!(!header.to_lowercase().contains(&CALLER.to_lowercase()));
+
+    let recorded = ParamHashes::parse_header(&header).unwrap();
+    assert_eq!(recorded, ParamHashes::of(&config));
+    assert_eq!(ParamHashes::parse_header("# Format: salt -> address"), None);
+
+    let init_code_hash = [0x11; 32];
+    let caller: [u8; 20] = alloy_primitives::hex::decode(CALLER)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let given = |caller: Option<&[u8; 20]>, init_code_hash: Option<&[u8; 32]>| {
+        ParamHashes::new(&config.factory_address, caller, init_code_hash)
+    };
+    assert_eq!(
+        recorded.check(&given(Some(&caller), Some(&init_code_hash))),
+        Ok(())
+    );
+    assert_eq!(
+        recorded.check(&given(Some(&caller), Some(&[0x22; 32]))),
+        Err("the init code hash does not match the hash in the output header".to_string())
+    );
+    assert_eq!(
+        recorded.check(&given(None, Some(&init_code_hash))),
+        Err("the output header records the caller, but it was not given".to_string())
+    );
+    let create3 = ParamHashes::new(&config.factory_address, None, None);
+    assert_eq!(
+        create3.check(&given(None, Some(&init_code_hash))),
+        Err("the init code hash was given, but the output header doesn't record it".to_string())
+    );
+}
+
+/// Run the miner with `stdin`, printing its configuration instead of mining.
+fn print_config(args: &[&str], stdin: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(args)
+        .args(["--params-stdin", "--print-config"])
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_params_stdin_overrides_the_command_line() {
+    // the command line factory is valid, the one of stdin is not
+    let output = print_config(
+        &[
+            "create3",
+            "--leading",
+            "2",
+            "--factory",
+            "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        ],
+        r#"{"factory": "0xnothex"}"#,
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("factory address is not valid hex"));
+
+    // create2 takes its init code hash from stdin, which the printed options leave out
+    let output = print_config(
+        &["create2", "--leading", "2"],
+        &format!(r#"{{"init_code_hash": "{}"}}"#, CODE_HASH),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(&CODE_HASH[2..]));
+
+    let output = print_config(
+        &["create3", "--leading", "2"],
+        &format!(r#"{{"init_code_hash": "{}"}}"#, CODE_HASH),
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error: --params-stdin gives an init code hash, which create3 runs don't use\n"
+    );
+}
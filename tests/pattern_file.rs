@@ -0,0 +1,171 @@
+use std::{fs, path::PathBuf};
+
+use createxcrunch::{
+    pattern_file::{parse, PatternLine, PatternWatcher, Reload},
+    RewardVariant,
+};
+
+fn pattern_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "createxcrunch-pattern-{}-{}.txt",
+        name,
+        std::process::id()
+    ));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn patterns(watcher: &PatternWatcher) -> Vec<&str> {
+    watcher
+        .patterns()
+        .iter()
+        .map(|pattern| pattern.pattern.as_str())
+        .collect()
+}
+
+#[test]
+fn test_parse_pattern_file() {
+    assert_eq!(
+        parse("# vanity prefix\n\n  0xdead...  \n"),
+        Ok(vec![PatternLine {
+            line: 3,
+            pattern: "dead...".to_string()
+        }])
+    );
+    assert_eq!(
+        parse("dead...ef\n# wish list\nbeef...\n\n...cafe\n"),
+        Ok(vec![
+            PatternLine {
+                line: 1,
+                pattern: "dead...ef".to_string()
+            },
+            PatternLine {
+                line: 3,
+                pattern: "beef...".to_string()
+            },
+            PatternLine {
+                line: 5,
+                pattern: "...cafe".to_string()
+            },
+        ])
+    );
+
+    assert_eq!(
+        parse("# nothing yet\n"),
+        Err("pattern file holds no pattern".to_string())
+    );
+    assert_eq!(
+        parse("dead...\n# typo below\nzzzz...\n"),
+        Err("line 3: leading part must contain only hex characters".to_string())
+    );
+    assert_eq!(
+        parse("dead...\nbeef...\n0xdead...\n"),
+        Err("line 3: pattern dead... repeats line 1".to_string())
+    );
+}
+
+#[test]
+fn test_reload_edited_pattern_file() {
+    let path = pattern_file("edit", "dead...\n");
+    let mut watcher = PatternWatcher::open(&path).unwrap();
+    assert_eq!(patterns(&watcher), vec!["dead..."]);
+    assert_eq!(watcher.poll(), None);
+
+    // comments and whitespace don't change the patterns, only their lines
+    fs::write(&path, "# still dead\ndead...\n").unwrap();
+    assert_eq!(watcher.poll(), None);
+    assert_eq!(watcher.line_of("dead..."), Some(2));
+
+    fs::write(&path, "beef...\ndead...\ncafe...\n").unwrap();
+    assert_eq!(
+        watcher.poll(),
+        Some(Reload::Changed {
+            removed: vec![],
+            added: vec!["beef...".to_string(), "cafe...".to_string()],
+        })
+    );
+    fs::write(&path, "beef...\n").unwrap();
+    assert_eq!(
+        watcher.poll(),
+        Some(Reload::Changed {
+            removed: vec!["dead...".to_string(), "cafe...".to_string()],
+            added: vec![],
+        })
+    );
+    assert_eq!(patterns(&watcher), vec!["beef..."]);
+    assert_eq!(watcher.poll(), None);
+
+    // an invalid edit is reported once and keeps the active patterns
+    fs::write(&path, "beefy...\n").unwrap();
+    assert!(
+        matches!(watcher.poll(), Some(Reload::Invalid(warning)) if warning.starts_with("invalid pattern file"))
+    );
+    assert_eq!(watcher.poll(), None);
+    assert_eq!(patterns(&watcher), vec!["beef..."]);
+
+    // fixing the edit back to the active patterns changes nothing
+    fs::write(&path, "beef...\n").unwrap();
+    assert_eq!(watcher.poll(), None);
+    assert_eq!(patterns(&watcher), vec!["beef..."]);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_unreadable_pattern_file() {
+    let path = pattern_file("missing", "dead...\n");
+    let mut watcher = PatternWatcher::open(&path).unwrap();
+
+    // a removed file is reported once, and picked up again once it is back
+    fs::remove_file(&path).unwrap();
+    assert!(
+        matches!(watcher.poll(), Some(Reload::Invalid(warning)) if warning.starts_with("could not read pattern file"))
+    );
+    assert_eq!(watcher.poll(), None);
+    assert_eq!(patterns(&watcher), vec!["dead..."]);
+
+    fs::write(&path, "cafe...\n").unwrap();
+    assert_eq!(
+        watcher.poll(),
+        Some(Reload::Changed {
+            removed: vec!["dead...".to_string()],
+            added: vec!["cafe...".to_string()],
+        })
+    );
+    fs::remove_file(&path).unwrap();
+
+    assert!(PatternWatcher::open(&path).is_err());
+    let path = pattern_file("invalid", "# nothing yet\n");
+    assert!(PatternWatcher::open(&path).is_err());
+    let path = pattern_file("invalid", "dead...\nbeef\n");
+    assert_eq!(
+        PatternWatcher::open(&path).err(),
+        Some(format!(
+            "invalid pattern file {}: line 2: simple pattern must be exactly 2 characters",
+            path.display()
+        ))
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_results_map_back_to_lines() {
+    let path = pattern_file("lines", "# wish list\ndead...\n\nbeef...\n...cafe\n");
+    let watcher = PatternWatcher::open(&path).unwrap();
+    let matching = |pattern: &str| RewardVariant::Matching {
+        pattern: pattern.into(),
+    };
+    assert_eq!(watcher.lines_of(&matching("beef...")), vec![4]);
+    assert_eq!(
+        watcher.lines_of(&RewardVariant::MatchingAny {
+            patterns: vec!["dead...".into(), "...cafe".into()],
+        }),
+        vec![2, 5]
+    );
+    assert_eq!(watcher.lines_of(&matching("f00d...")), Vec::<usize>::new());
+    assert_eq!(
+        watcher.lines_of(&RewardVariant::LeadingZeros { zeros_threshold: 2 }),
+        Vec::<usize>::new()
+    );
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,130 @@
+use createxcrunch::{
+    coarse_kernel_config, coarse_prefix, BatchGeometry, MatchPlacement, RewardVariant,
+};
+
+fn matching(pattern: &str) -> RewardVariant {
+    RewardVariant::Matching {
+        pattern: pattern.into(),
+    }
+}
+
+#[test]
+fn test_prefix_rewards_match_on_gpu() {
+    assert_eq!(
+        MatchPlacement::for_reward(&RewardVariant::LeadingZeros { zeros_threshold: 3 }),
+        MatchPlacement::Gpu {
+            prefix: vec![0, 0, 0]
+        }
+    );
+    assert_eq!(
+        MatchPlacement::for_reward(&matching("ba5eD0...")),
+        MatchPlacement::Gpu {
+            prefix: vec![0xba, 0x5e, 0xd0]
+        }
+    );
+    // the kernel matches the bytes of checksum patterns, the CPU their case
+    assert_eq!(
+        MatchPlacement::for_reward(&RewardVariant::ChecksumMatching {
+            pattern: "DeaD...".into()
+        }),
+        MatchPlacement::Gpu {
+            prefix: vec![0xde, 0xad]
+        }
+    );
+}
+
+#[test]
+fn test_leading_zero_nibbles_match_on_gpu() {
+    let placement = MatchPlacement::for_reward(&RewardVariant::LeadingZeroNibbles { threshold: 7 });
+    assert_eq!(placement, MatchPlacement::GpuZeroNibbles { nibbles: 7 });
+    // the nibble count takes the place of the prefix length
+    assert_eq!(placement.kernel_config(), [2, 7, 0, 0, 0, 0, 0]);
+    // only whole zero bytes make a coarse prefix
+    assert_eq!(
+        coarse_prefix(&RewardVariant::LeadingZeroNibbles { threshold: 7 }),
+        vec![0, 0, 0]
+    );
+}
+
+#[test]
+fn test_other_rewards_match_on_cpu() {
+    // patterns with a trailing part can't be expressed as a prefix
+    assert_eq!(
+        MatchPlacement::for_reward(&matching("ba5e...d0")),
+        MatchPlacement::Cpu
+    );
+    assert_eq!(
+        MatchPlacement::for_reward(&RewardVariant::TotalZeros { zeros_threshold: 4 }),
+        MatchPlacement::Cpu
+    );
+    assert_eq!(
+        MatchPlacement::for_reward(&RewardVariant::SymmetricEnds { nibbles: 4 }),
+        MatchPlacement::Cpu
+    );
+}
+
+#[test]
+fn test_kernel_config_packs_prefix() {
+    let placement = MatchPlacement::Gpu {
+        prefix: vec![0x01, 0x02, 0x03, 0x04, 0x05],
+    };
+    assert_eq!(placement.kernel_config(), [1, 5, 0x04030201, 0x05, 0, 0, 0]);
+    assert_eq!(MatchPlacement::Cpu.kernel_config(), [0; 7]);
+}
+
+#[test]
+fn test_coarse_prefix() {
+    assert_eq!(coarse_prefix(&matching("ab...cd")), vec![0xab]);
+    // a dangling nibble is left to the CPU check
+    assert_eq!(coarse_prefix(&matching("abc...d")), vec![0xab]);
+    assert_eq!(coarse_prefix(&matching("...cd")), Vec::<u8>::new());
+    // several patterns share the bytes they all start with
+    let any = |patterns: &[&str]| RewardVariant::MatchingAny {
+        patterns: patterns.iter().map(|pattern| (*pattern).into()).collect(),
+    };
+    assert_eq!(coarse_prefix(&any(&["abcd...", "abce..."])), vec![0xab]);
+    assert_eq!(coarse_prefix(&any(&["abcd...", "cd..."])), Vec::<u8>::new());
+    assert_eq!(
+        MatchPlacement::for_reward(&any(&["abcd...", "abce..."])),
+        MatchPlacement::Cpu
+    );
+    assert_eq!(
+        coarse_prefix(&RewardVariant::TotalZeros { zeros_threshold: 4 }),
+        Vec::<u8>::new()
+    );
+    assert_eq!(
+        coarse_kernel_config(&[0xab, 0xcd]),
+        [0, 2, 0xcdab, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_bitmap_index_math() {
+    // 256 threads per workgroup, 8 per bit
+    let geometry = BatchGeometry::new(1000, 256);
+    assert_eq!(geometry.entries_per_bit(), 8);
+    assert_eq!(geometry.bitmap_size(), 4 * 4);
+
+    // bits 0 and 1 of workgroup 0 merge, bit 31 of workgroup 0 and bit 0 of workgroup 1 too
+    let bitmap = [0b11 | 1 << 31, 0b1, 0, 1 << 31];
+    assert_eq!(
+        geometry.flagged_ranges(&bitmap, 1000),
+        vec![0..16, 248..264]
+    );
+    // the last workgroup is only partly dispatched
+    assert_eq!(
+        geometry.flagged_ranges(&[0, 0, 0, 1 << 29 | 1 << 31], 1000),
+        vec![]
+    );
+    assert_eq!(
+        geometry.flagged_ranges(&[0, 0, 0, 1 << 28], 1000),
+        vec![992..1000]
+    );
+    assert_eq!(
+        geometry.flagged_ranges(&[0, 0, 0, 1 << 28], 995),
+        vec![992..995]
+    );
+
+    // 40 bytes per entry: a 2 word nonce and an 8 word hash
+    assert_eq!(geometry.byte_range(&(248..264)), 9920..10560);
+}
@@ -0,0 +1,314 @@
+use alloy_primitives::{hex, keccak256, Address};
+use createxcrunch::{
+    create2_address,
+    plan::{dry_run, startup_phases, MiningPlan, StartupPhase, StartupTimings, EXAMPLE_SEED},
+    selftest::SelfTestMode,
+    Config, CreateVariant, RewardVariant, SaltVariant,
+};
+use std::{process::Command, time::Duration};
+
+const FACTORY: [u8; 20] = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+
+fn variants() -> (Vec<CreateVariant>, Vec<SaltVariant>) {
+    let mut chain_id = [0u8; 32];
+    chain_id[31] = 1;
+    let calling_address = [0xab; 20];
+    (
+        vec![
+            CreateVariant::Create3,
+            CreateVariant::Create2 {
+                init_code_hash: keccak256("init code").0,
+            },
+        ],
+        vec![
+            SaltVariant::CrosschainSender {
+                chain_id,
+                calling_address,
+            },
+            SaltVariant::Crosschain { chain_id },
+            SaltVariant::Sender { calling_address },
+            SaltVariant::Random,
+        ],
+    )
+}
+
+#[test]
+fn test_worked_example_matches_derivation() {
+    let (create_variants, salt_variants) = variants();
+    for create_variant in &create_variants {
+        for salt_variant in &salt_variants {
+            let plan = MiningPlan {
+                factory: FACTORY,
+                create_variant: create_variant.clone(),
+                salt_variant: salt_variant.clone(),
+            };
+            let example = plan.worked_example();
+            let context = format!("{} {}", create_variant, salt_variant);
+
+            assert_eq!(
+                example.salt[21..],
+                keccak256(EXAMPLE_SEED)[..11],
+                "{}",
+                context
+            );
+            assert_eq!(
+                example.address,
+                create_variant.address(&FACTORY, &salt_variant.guard(&example.salt)),
+                "{}",
+                context
+            );
+            assert_eq!(example.steps[0].hash, salt_variant.guard(&example.salt));
+            let expected_steps = match create_variant {
+                CreateVariant::Create2 { .. } => 2,
+                CreateVariant::Create3 => 3,
+            };
+            assert_eq!(example.steps.len(), expected_steps, "{}", context);
+
+            let checksummed = Address::from(example.address).to_checksum(None);
+            assert!(example.render().ends_with(&checksummed));
+            assert_eq!(example.to_json()["address"], checksummed);
+        }
+    }
+}
+
+#[test]
+fn test_salt_layout() {
+    let (_, salt_variants) = variants();
+    let entropy = [0xee; 11];
+    let salts: Vec<[u8; 32]> = salt_variants
+        .iter()
+        .map(|variant| variant.salt_with_entropy(&entropy))
+        .collect();
+
+    // caller, redeploy protection flag, entropy
+    assert_eq!((&salts[0][..20], salts[0][20]), (&[0xab; 20][..], 0x01));
+    assert_eq!((&salts[1][..20], salts[1][20]), (&[0; 20][..], 0x01));
+    assert_eq!((&salts[2][..20], salts[2][20]), (&[0xab; 20][..], 0x00));
+    assert_eq!((&salts[3][..20], salts[3][20]), (&[0; 20][..], 0x00));
+    assert!(salts.iter().all(|salt| salt[21..] == entropy));
+}
+
+#[test]
+fn test_create2_address() {
+    // EIP-1014 example 0
+    let address = create2_address(&[0; 20], &[0; 32], &keccak256(hex!("00")).0);
+    assert_eq!(
+        Address::from(address),
+        "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+            .parse::<Address>()
+            .unwrap()
+    );
+}
+
+fn config() -> Config<'static> {
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 2 };
+    let mut config = Config::new(
+        0,
+        "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        None,
+        None,
+        None,
+        reward,
+        "output.txt",
+    )
+    .unwrap();
+    config.self_test = Some(SelfTestMode::Basic);
+    config.rpc_url = Some("http://localhost:8545");
+    config.resume = true;
+    config
+}
+
+#[test]
+fn test_startup_phases() {
+    assert_eq!(
+        startup_phases(&config(), false),
+        [
+            StartupPhase::Adapter,
+            StartupPhase::WorkedExample,
+            StartupPhase::DiskCheck,
+            StartupPhase::FactoryCheck,
+            StartupPhase::Checkpoint,
+            StartupPhase::OutputFile,
+            StartupPhase::Device,
+            StartupPhase::SelfTest,
+            StartupPhase::BatchGeometry,
+            StartupPhase::Pipeline,
+        ]
+    );
+
+    // runs with a callback write no file
+    let phases = startup_phases(&config(), true);
+    assert!(!phases.contains(&StartupPhase::OutputFile));
+    assert!(!phases.contains(&StartupPhase::DiskCheck));
+}
+
+#[test]
+fn test_fast_start_skips_optional_phases() {
+    let mut config = config();
+    config.fast_start = true;
+    let phases = startup_phases(&config, false);
+    assert!(
+        phases.iter().all(|phase| !phase.is_optional()),
+        "{:?}",
+        phases
+    );
+    assert_eq!(
+        phases,
+        [
+            StartupPhase::Adapter,
+            StartupPhase::Checkpoint,
+            StartupPhase::OutputFile,
+            StartupPhase::Device,
+            StartupPhase::BatchGeometry,
+            StartupPhase::Pipeline,
+        ]
+    );
+}
+
+#[test]
+fn test_startup_timings() {
+    let mut timings = StartupTimings::default();
+    let device = timings.time(StartupPhase::Device, || {
+        std::thread::sleep(Duration::from_millis(2));
+        "device"
+    });
+    assert_eq!(device, "device");
+    timings.time(StartupPhase::OutputFile, || ());
+    // phases timed in parts add up in their first place
+    timings.time(StartupPhase::Device, || ());
+
+    assert_eq!(
+        timings.ran(),
+        [StartupPhase::Device, StartupPhase::OutputFile]
+    );
+    assert!(timings.total() >= Duration::from_millis(2));
+    assert!(timings.report().starts_with("Started up in "));
+    assert!(timings.report().contains(": device "));
+    assert!(timings.report().contains("ms, output file "));
+}
+
+#[test]
+fn test_dry_run() {
+    let mut config = config();
+    config.salt_variant = variants().1.remove(0);
+    let report = dry_run(&config);
+    assert!(report.contains(&format!(
+        "salt bytes 0-19:    {}",
+        Address::from([0xab; 20]).to_checksum(None)
+    )));
+    assert!(report.contains("salt byte 20:       0x01, guarded with chain ID 1"));
+    assert!(report.contains("reward:             leading-zeros(2)"));
+    assert!(report.contains("expected attempts:  65536 (2^16.0) per result"));
+    assert!(!report.contains("WARNING"));
+
+    config.reward = RewardVariant::LeadingZeros { zeros_threshold: 9 };
+    assert!(dry_run(&config).contains("WARNING: the reward needs about 2^72.0 attempts"));
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-dry-run-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output.txt");
+    let run = Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args(["create3", "--matching", "dead...", "--dry-run", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert!(stdout.starts_with("Dry run"), "{}", stdout);
+    assert!(stdout.contains("salt bytes 0-19:    zero"));
+    assert!(stdout.contains(
+        "factory:            0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed, the canonical CreateX deployment used by default"
+    ));
+    assert!(!output.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_custom_factory_keeps_checksum_validation() {
+    let dry_run = |factory: &str| {
+        Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args([
+                "create3",
+                "--matching",
+                "dead...",
+                "--dry-run",
+                "--factory",
+                factory,
+            ])
+            .env_clear()
+            .output()
+            .unwrap()
+    };
+
+    let run = dry_run("0x4e59b44847b379578588920cA78FbF26c0B4956C");
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert!(stdout.contains("factory:            0x4e59b44847b379578588920cA78FbF26c0B4956C\n"));
+
+    let run = dry_run("0x4e59b44847b379578588920ca78fbf26c0b4956C");
+    assert!(!run.status.success());
+    assert!(String::from_utf8(run.stderr)
+        .unwrap()
+        .contains("factory address uses invalid checksum"));
+}
+
+#[test]
+fn test_repeated_pattern_mines_every_pattern() {
+    let run = Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .args([
+            "create3",
+            "--pattern",
+            "dead...",
+            "--pattern",
+            "beef...",
+            "--pattern",
+            "...cafe",
+            "--dry-run",
+        ])
+        .env_clear()
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert!(
+        stdout.contains("any-pattern(dead...,beef...,...cafe)"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_pattern_file_mines_every_pattern() {
+    let path = std::env::temp_dir().join(format!(
+        "createxcrunch-plan-wishlist-{}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&path, "# wish list\ndead...\n\nbeef...\n").unwrap();
+    let dry_run = |path: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+            .args(["create3", "--pattern-file"])
+            .arg(path)
+            .arg("--dry-run")
+            .env_clear()
+            .output()
+            .unwrap()
+    };
+    let run = dry_run(&path);
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert!(stdout.contains("any-pattern(dead...,beef...)"), "{}", stdout);
+
+    std::fs::write(&path, "dead...\nbeef\n").unwrap();
+    let run = dry_run(&path);
+    assert!(!run.status.success());
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    assert!(
+        stderr.contains("line 2: simple pattern must be exactly 2 characters"),
+        "{}",
+        stderr
+    );
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use createxcrunch::pool::{
+    self, Find, HttpTransport, Lease, PoolClient, Submission, Transport, PROTOCOL_VERSION,
+};
+use serde_json::{json, Value};
+
+#[derive(Default)]
+struct PoolState {
+    ranges: VecDeque<(u64, u64)>,
+    ttl: u64,
+    issued: u64,
+    renewals: u32,
+    submissions: HashMap<String, Value>,
+    drop_next_submit_response: bool,
+}
+
+/// In-process pool server speaking the protocol over HTTP on a local port.
+struct MockPool {
+    url: String,
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl MockPool {
+    fn start(ranges: &[(u64, u64)], ttl: u64) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(PoolState {
+            ranges: ranges.iter().copied().collect(),
+            ttl,
+            ..Default::default()
+        }));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle(stream.unwrap(), &server_state);
+            }
+        });
+        Self { url, state }
+    }
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<PoolState>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let path = request_line.split_whitespace().nth(1).unwrap().to_string();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header == "\r\n" {
+            break;
+        }
+        if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let request: Value = serde_json::from_slice(&body).unwrap();
+
+    let mut state = state.lock().unwrap();
+    let now = pool::now();
+    let response = match path.as_str() {
+        "/v1/lease" => {
+            let lease = state.ranges.pop_front().map(|(start, end)| {
+                state.issued += 1;
+                json!({
+                    "id": format!("lease-{}", state.issued),
+                    "salt_prefix": "0x",
+                    "start": start,
+                    "end": end,
+                    "expires_at": now + state.ttl,
+                })
+            });
+            json!({ "version": PROTOCOL_VERSION, "lease": lease })
+        }
+        "/v1/renew" => {
+            state.renewals += 1;
+            let id = request["lease_id"].as_str().unwrap();
+            json!({
+                "version": PROTOCOL_VERSION,
+                "lease": {
+                    "id": id,
+                    "salt_prefix": "0x",
+                    "start": 0,
+                    "end": 1000,
+                    "expires_at": now + state.ttl,
+                },
+            })
+        }
+        "/v1/submit" => {
+            let id = request["lease_id"].as_str().unwrap().to_string();
+            let status = match state.submissions.contains_key(&id) {
+                true => "duplicate",
+                false => {
+                    state.submissions.insert(id, request);
+                    "accepted"
+                }
+            };
+            if state.drop_next_submit_response {
+                // the submission arrived, but the connection drops before the answer
+                state.drop_next_submit_response = false;
+                return;
+            }
+            json!({ "version": PROTOCOL_VERSION, "status": status })
+        }
+        _ => {
+            let _ = write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n"
+            );
+            return;
+        }
+    };
+
+    let body = response.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+}
+
+fn client(url: &str) -> PoolClient<HttpTransport> {
+    PoolClient::new(HttpTransport::new(url).unwrap(), "tester").with_retries(1, Duration::ZERO)
+}
+
+fn submission(lease: &Lease) -> Submission {
+    let finds = vec![Find::new(&[0x11; 32], &[0x22; 20])];
+    Submission::new(lease, finds, lease.end - lease.start)
+}
+
+#[test]
+fn test_lease_cycle() {
+    let pool = MockPool::start(&[(0, 1000), (1000, 2000)], 60);
+    let mut client = client(&pool.url);
+
+    let first = client.request_lease().unwrap().unwrap();
+    assert_eq!((first.id.as_str(), first.range()), ("lease-1", 0..1000));
+    assert_eq!(first.salt_prefix_bytes().unwrap(), Vec::<u8>::new());
+    let second = client.request_lease().unwrap().unwrap();
+    assert_eq!(second.range(), 1000..2000);
+    assert_eq!(client.request_lease().unwrap(), None);
+
+    assert_eq!(client.submit(submission(&first)), 0);
+    let state = pool.state.lock().unwrap();
+    let submitted = &state.submissions["lease-1"];
+    assert_eq!(submitted["attempts"], 1000);
+    assert_eq!(
+        (&submitted["covered_start"], &submitted["covered_end"]),
+        (&json!(0), &json!(1000))
+    );
+    assert_eq!(
+        submitted["finds"][0]["salt"],
+        format!("0x{}", "11".repeat(32))
+    );
+}
+
+#[test]
+fn test_lease_renewal() {
+    let pool = MockPool::start(&[(0, 1000)], 2);
+    let mut client = client(&pool.url);
+    let lease = client.request_lease().unwrap().unwrap();
+    assert!(lease.remaining(pool::now()).is_some());
+    assert_eq!(lease.remaining(lease.expires_at), None);
+
+    let renewed = client.renew(&lease).unwrap();
+    assert_eq!(renewed.id, lease.id);
+    assert!(renewed.expires_at >= lease.expires_at);
+
+    // the renewal thread keeps a 2 second lease alive past its original expiry
+    let stop = Arc::new(AtomicBool::new(false));
+    let renewal = pool::spawn_renewal(self::client(&pool.url), lease.clone(), stop.clone());
+    thread::sleep(Duration::from_millis(3500));
+    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    let kept = renewal.join().unwrap();
+    assert!(kept.expires_at > lease.expires_at);
+    assert!(pool.state.lock().unwrap().renewals >= 2);
+}
+
+#[test]
+fn test_submission_is_idempotent() {
+    let pool = MockPool::start(&[(0, 1000)], 60);
+    let mut client = client(&pool.url);
+    let lease = client.request_lease().unwrap().unwrap();
+
+    // the first answer gets lost and the retry is recognized as the same submission
+    pool.state.lock().unwrap().drop_next_submit_response = true;
+    assert_eq!(client.submit(submission(&lease)), 0);
+    assert_eq!(pool.state.lock().unwrap().submissions.len(), 1);
+
+    // submitting the lease again is harmless
+    assert_eq!(client.submit(submission(&lease)), 0);
+    assert_eq!(pool.state.lock().unwrap().submissions.len(), 1);
+}
+
+#[test]
+fn test_offline_submissions_are_queued() {
+    // a port nothing listens on
+    let offline = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let lease = Lease {
+        id: "lease-7".to_string(),
+        salt_prefix: "0x".to_string(),
+        start: 0,
+        end: 1000,
+        expires_at: pool::now() + 60,
+    };
+
+    let mut client = client(&offline);
+    assert!(client.request_lease().is_err());
+    assert_eq!(client.submit(submission(&lease)), 1);
+    assert_eq!(client.pending().len(), 1);
+
+    // the queue survives a restart and is delivered once the pool is back
+    let path = std::env::temp_dir().join(format!("createxcrunch-pool-{}", std::process::id()));
+    client.save_queue(&path).unwrap();
+    let pool = MockPool::start(&[], 60);
+    let mut client = self::client(&pool.url);
+    client.load_queue(&path).unwrap();
+    assert_eq!(client.flush(), 0);
+    client.save_queue(&path).unwrap();
+    assert!(!path.exists());
+    assert!(pool
+        .state
+        .lock()
+        .unwrap()
+        .submissions
+        .contains_key("lease-7"));
+}
+
+/// Transport answering every request with the same body.
+struct Canned(&'static str);
+
+impl Transport for Canned {
+    fn post(&mut self, _path: &str, _body: &str) -> Result<String, String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[test]
+fn test_protocol_version_is_checked() {
+    let mut client = PoolClient::new(Canned(r#"{"version":2,"lease":null}"#), "tester");
+    let error = client.request_lease().unwrap_err();
+    assert!(error.contains("protocol version 2"), "{}", error);
+
+    let mut client = PoolClient::new(Canned(r#"{"version":1,"lease":null}"#), "tester");
+    assert_eq!(client.request_lease().unwrap(), None);
+
+    let error = HttpTransport::new("https://pool.example").unwrap_err();
+    assert!(error.contains("pool feature"), "{}", error);
+    assert!(HttpTransport::new("http:///v1").is_err());
+    assert!(HttpTransport::new("http://pool.example/v1/").is_ok());
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn test_lease_cycle_over_reqwest() {
+    use createxcrunch::http::ReqwestTransport;
+
+    let pool = MockPool::start(&[(0, 1000)], 60);
+    let transport = ReqwestTransport::new(&pool.url).unwrap();
+    let mut client = PoolClient::new(transport, "tester").with_retries(1, Duration::ZERO);
+    let lease = client.request_lease().unwrap().unwrap();
+    assert_eq!(lease.range(), 0..1000);
+    assert_eq!(client.request_lease().unwrap(), None);
+    assert_eq!(client.submit(submission(&lease)), 0);
+    assert!(pool.state.lock().unwrap().submissions.contains_key("lease-1"));
+
+    assert!(ReqwestTransport::new("https://pool.example/v1/").is_ok());
+    assert!(ReqwestTransport::new("ftp://pool.example").is_err());
+    assert!(ReqwestTransport::new("pool.example").is_err());
+}
@@ -0,0 +1,268 @@
+use std::process::{Command, Output};
+
+use alloy_primitives::{hex, keccak256};
+use createxcrunch::{
+    deploy_tx::{self, Deployment, Eip1559Transaction, Fees, MinedResult},
+    http::Transport,
+    rpc::RpcClient,
+    CreateVariant, SaltVariant,
+};
+use serde_json::{json, Value};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/prepare_tx");
+
+const FACTORY: [u8; 20] = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+const CALLER: &str = "0x88c6C46EBf353A52Bdbab708c23D0c81dAA8134A";
+const INIT_CODE: &str = "0x6080604052348015600e575f80fd5b50";
+
+/// Sender and cross-chain protected salt of `CALLER` on chain 10, and its CREATE3 address.
+const CREATE3_SALT: &str = "0x88c6c46ebf353a52bdbab708c23d0c81daa8134a01000000000000000badc0de";
+const CREATE3_ADDRESS: &str = "0x4411eC9d0e6aDA54d80F0e4a4ECC3B9C25Ec634e";
+
+/// Unprotected salt, and its CREATE2 address for `INIT_CODE`.
+const CREATE2_SALT: &str = "0x00000000000000000000000000000000000000000000000000000000deadbeef";
+const CREATE2_ADDRESS: &str = "0x5691C5da62F291BE430566ba1c789f78E154d444";
+
+fn prepare_tx(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("prepare-tx")
+        .args(args)
+        .env_clear()
+        .output()
+        .unwrap()
+}
+
+fn transaction(output: &Output) -> Value {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Reference transaction, laid out as `eth_sendTransaction` takes it, with the calldata `cast
+/// calldata "deployCreate3(bytes32,bytes)" <salt> <init code>` encodes, or `deployCreate2`.
+fn reference(name: &str) -> Value {
+    let path = format!("{}/{}", FIXTURES, name);
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_create3_transaction_matches_reference() {
+    let line = format!("{} -> {}", CREATE3_SALT, CREATE3_ADDRESS);
+    let output = prepare_tx(&[
+        &line,
+        "--init-code",
+        INIT_CODE,
+        "--caller",
+        CALLER,
+        "--chain-id",
+        "10",
+        "--gas-limit",
+        "2000000",
+        "--max-fee-per-gas",
+        "1000000000",
+        "--max-priority-fee-per-gas",
+        "100000000",
+        "--nonce",
+        "7",
+    ]);
+    assert_eq!(transaction(&output), reference("create3.json"));
+}
+
+#[test]
+fn test_create2_transaction_matches_reference() {
+    // the create variant of a jsonl result selects deployCreate2
+    let line = json!({
+        "salt": CREATE2_SALT,
+        "address": CREATE2_ADDRESS,
+        "factory": "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+        "create_variant": format!("create2(0x{})", hex::encode(keccak256(hex::decode(INIT_CODE).unwrap()))),
+        "found_at_unix": 1_700_000_000,
+    })
+    .to_string();
+    let output = prepare_tx(&[
+        &line,
+        "--init-code",
+        INIT_CODE,
+        "--chain-id",
+        "1",
+        "--gas-limit",
+        "300000",
+        "--max-fee-per-gas",
+        "2000000000",
+        "--max-priority-fee-per-gas",
+        "1000000000",
+        "--value",
+        "1000000000000000000",
+    ]);
+    assert_eq!(transaction(&output), reference("create2.json"));
+
+    // as does --create2 for a bare salt and address
+    let output = prepare_tx(&[
+        "--salt",
+        CREATE2_SALT,
+        "--address",
+        CREATE2_ADDRESS,
+        "--create2",
+        "--init-code",
+        INIT_CODE,
+        "--chain-id",
+        "1",
+        "--gas-limit",
+        "300000",
+        "--max-fee-per-gas",
+        "2000000000",
+        "--max-priority-fee-per-gas",
+        "1000000000",
+        "--value",
+        "1000000000000000000",
+    ]);
+    assert_eq!(transaction(&output), reference("create2.json"));
+}
+
+#[test]
+fn test_salts_are_verified_before_building() {
+    let fees = [
+        "--gas-limit",
+        "2000000",
+        "--max-fee-per-gas",
+        "1000000000",
+        "--max-priority-fee-per-gas",
+        "100000000",
+    ];
+    let line = format!("{} -> {}", CREATE3_SALT, CREATE3_ADDRESS);
+
+    // protected for chain 10, the salt deploys elsewhere on chain 1
+    let mut args = vec![
+        line.as_str(),
+        "--init-code",
+        INIT_CODE,
+        "--caller",
+        CALLER,
+        "--chain-id",
+        "1",
+    ];
+    args.extend(fees);
+    let output = prepare_tx(&args);
+    assert!(!output.status.success());
+    let error = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        error.contains(&format!("not to {}", CREATE3_ADDRESS)),
+        "{}",
+        error
+    );
+
+    // a salt starting with the caller can't be deployed by anyone else
+    let mut args = vec![line.as_str(), "--init-code", INIT_CODE, "--chain-id", "10"];
+    args.extend(fees);
+    let output = prepare_tx(&args);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass it as --caller"));
+}
+
+#[test]
+fn test_deploy_variant() {
+    let init_code = hex::decode(INIT_CODE).unwrap();
+    let create2 = CreateVariant::Create2 {
+        init_code_hash: keccak256(&init_code).0,
+    };
+    assert_eq!(
+        deploy_tx::deploy_variant(None, false, &init_code),
+        Ok(CreateVariant::Create3)
+    );
+    assert_eq!(
+        deploy_tx::deploy_variant(None, true, &init_code),
+        Ok(create2.clone())
+    );
+    assert_eq!(
+        deploy_tx::deploy_variant(Some(&create2), false, &init_code),
+        Ok(create2)
+    );
+
+    let other = CreateVariant::Create2 {
+        init_code_hash: [0xab; 32],
+    };
+    assert!(deploy_tx::deploy_variant(Some(&other), false, &init_code)
+        .unwrap_err()
+        .contains("mined for init code hash 0xabab"));
+    assert!(deploy_tx::deploy_variant(Some(&CreateVariant::Create3), true, &init_code).is_err());
+}
+
+#[test]
+fn test_result_lines() {
+    let text = MinedResult::parse(&format!("{} -> {}", CREATE2_SALT, CREATE2_ADDRESS)).unwrap();
+    assert_eq!(
+        text.salt,
+        hex!("00000000000000000000000000000000000000000000000000000000deadbeef")
+    );
+    assert_eq!(
+        text.address,
+        hex!("5691C5da62F291BE430566ba1c789f78E154d444")
+    );
+    assert_eq!((text.factory, text.create_variant.clone()), (None, None));
+
+    // the extra salt column of --emit-salt-format is skipped
+    let with_column = format!("{} 3735928559 -> {}", CREATE2_SALT, CREATE2_ADDRESS);
+    assert_eq!(MinedResult::parse(&with_column), Ok(text.clone()));
+    let json = json!({ "salt": CREATE2_SALT, "address": CREATE2_ADDRESS }).to_string();
+    assert_eq!(MinedResult::parse(&json), Ok(text));
+
+    assert!(
+        MinedResult::parse("0x1234…abcd -> 0x5691C5da62F291BE430566ba1c789f78E154d444").is_err()
+    );
+    assert!(MinedResult::parse("# createXcrunch results").is_err());
+}
+
+/// Node answering every `eth_getCode` with `code`.
+struct Node {
+    code: &'static str,
+}
+
+impl Transport for Node {
+    fn post(&mut self, _path: &str, body: &str) -> Result<String, String> {
+        let request: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(request["method"], "eth_getCode");
+        Ok(json!({ "jsonrpc": "2.0", "id": request["id"], "result": self.code }).to_string())
+    }
+}
+
+#[test]
+fn test_check_vacant() {
+    let address = hex!("5691C5da62F291BE430566ba1c789f78E154d444");
+    assert_eq!(
+        deploy_tx::check_vacant(&mut RpcClient::new(Node { code: "0x" }), &address),
+        Ok(())
+    );
+    assert_eq!(
+        deploy_tx::check_vacant(&mut RpcClient::new(Node { code: "0x6080" }), &address),
+        Err("0x5691C5da62F291BE430566ba1c789f78E154d444 already holds 2 bytes of code, the salt was deployed already".to_string())
+    );
+}
+
+#[test]
+fn test_transaction_fields() {
+    let deployment = Deployment {
+        factory: FACTORY,
+        create_variant: CreateVariant::Create3,
+        salt_variant: SaltVariant::Random,
+        salt: [0; 32],
+        init_code: vec![0x60, 0x80],
+    };
+    let fees = Fees {
+        gas_limit: 21_000,
+        max_fee_per_gas: 3,
+        max_priority_fee_per_gas: 1,
+    };
+    let transaction = Eip1559Transaction::deploy(&deployment, 8453, &fees).to_json();
+    assert_eq!(transaction["type"], "0x2");
+    assert_eq!(transaction["chainId"], "0x2105");
+    assert_eq!(transaction["gas"], "0x5208");
+    assert_eq!(
+        transaction["data"],
+        format!("0x{}", hex::encode(deployment.calldata()))
+    );
+    // left to the signer
+    assert!(transaction.get("from").is_none());
+    assert!(transaction.get("nonce").is_none());
+}
@@ -0,0 +1,65 @@
+use alloy_primitives::Address;
+use createxcrunch::{
+    presets::{self, CREATEX},
+    Config, RewardVariant, CREATE3_PROXY_INITCODE_HASH,
+};
+
+#[test]
+fn test_presets_are_consistent() {
+    let presets = presets::all();
+    assert_eq!(presets[0], CREATEX);
+
+    for (index, preset) in presets.iter().enumerate() {
+        // names and addresses resolve to this preset only
+        assert_eq!(presets::find(preset.name), Some(preset));
+        assert_eq!(presets::by_address(&preset.code.address), Some(preset));
+        assert!(presets[..index]
+            .iter()
+            .all(|other| other.name != preset.name && other.code.address != preset.code.address));
+
+        assert!(!preset.create_variants.is_empty());
+        assert!(preset
+            .create_variants
+            .iter()
+            .all(|variant| ["create2", "create3"].contains(variant)));
+        assert_eq!(
+            preset.create_variants.contains(&"create3"),
+            preset.create3_proxy_init_code_hash.is_some()
+        );
+        if let Some(hash) = preset.create3_proxy_init_code_hash {
+            assert_eq!(hash, CREATE3_PROXY_INITCODE_HASH);
+        }
+    }
+    assert_eq!(presets::find("create-x"), None);
+}
+
+#[test]
+fn test_preset_addresses_pass_checksum_validation() {
+    for preset in presets::all() {
+        let address = preset.checksummed_address();
+        assert_eq!(
+            Address::parse_checksummed(&address, None).unwrap(),
+            Address::from(preset.code.address)
+        );
+        let reward = RewardVariant::LeadingZeros { zeros_threshold: 1 };
+        let config = Config::new(0, &address, None, None, None, reward, "output.txt").unwrap();
+        assert_eq!(config.factory_address, preset.code.address);
+    }
+}
+
+#[test]
+fn test_presets_render() {
+    let json = CREATEX.to_json();
+    assert_eq!(
+        json["address"],
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed"
+    );
+    assert_eq!(json["guarded_salts"], true);
+    assert_eq!(json["create_variants"][1], "create3");
+
+    let table = presets::table(presets::all());
+    assert!(
+        table.starts_with("createx\n  address:         0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed")
+    );
+    assert!(table.contains("  CREATE3 proxy:   0x21c35dbe"));
+}
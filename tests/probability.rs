@@ -0,0 +1,114 @@
+use createxcrunch::{
+    probability::{expected_attempts, match_probability, HOPELESS_ATTEMPTS},
+    RewardVariant,
+};
+
+fn matching(pattern: &str) -> RewardVariant {
+    RewardVariant::Matching {
+        pattern: pattern.into(),
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= expected * 1e-9,
+        "{} is not {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_leading_zeros() {
+    let reward = RewardVariant::LeadingZeros { zeros_threshold: 4 };
+    assert_eq!(expected_attempts(&reward), 2f64.powi(32));
+}
+
+#[test]
+fn test_total_zeros() {
+    // one zero byte among 20: 1 - (255/256)^20
+    let reward = RewardVariant::TotalZeros { zeros_threshold: 1 };
+    assert_close(
+        match_probability(&reward),
+        1.0 - (255.0f64 / 256.0).powi(20),
+    );
+
+    // C(20, 20) for every byte zero
+    let reward = RewardVariant::TotalZeros {
+        zeros_threshold: 20,
+    };
+    assert_close(match_probability(&reward), 256f64.powi(-20));
+
+    // the binomial C(20, 2) dominates two zero bytes
+    let reward = RewardVariant::TotalZeros { zeros_threshold: 2 };
+    let at_least_two = match_probability(&reward);
+    assert!(at_least_two > 190.0 * 256f64.powi(-2) * (255.0f64 / 256.0).powi(18));
+    assert!(at_least_two < 191.0 * 256f64.powi(-2));
+}
+
+#[test]
+fn test_leading_and_or_total_zeros() {
+    let leading = RewardVariant::LeadingZeros { zeros_threshold: 3 };
+    let total = RewardVariant::TotalZeros { zeros_threshold: 5 };
+    let both = RewardVariant::LeadingAndTotalZeros {
+        leading_zeros_threshold: 3,
+        total_zeros_threshold: 5,
+    };
+    let either = RewardVariant::LeadingOrTotalZeros {
+        leading_zeros_threshold: 3,
+        total_zeros_threshold: 5,
+    };
+    // the stricter condition bounds both, the looser one either
+    assert_eq!(match_probability(&both), match_probability(&total));
+    assert_eq!(match_probability(&either), match_probability(&leading));
+}
+
+#[test]
+fn test_matching() {
+    assert_eq!(expected_attempts(&matching("de")), 256.0);
+    assert_eq!(expected_attempts(&matching("dead...")), 16f64.powi(4));
+    assert_eq!(expected_attempts(&matching("dead...beef")), 16f64.powi(8));
+}
+
+#[test]
+fn test_matching_any() {
+    let reward = RewardVariant::MatchingAny {
+        patterns: vec!["dead...".into(), "beef...".into()],
+    };
+    assert_eq!(expected_attempts(&reward), 16f64.powi(4) / 2.0);
+}
+
+#[test]
+fn test_checksum_matching() {
+    // each letter adds one bit, digits none
+    let reward = RewardVariant::ChecksumMatching {
+        pattern: "DeaD...".into(),
+    };
+    assert_eq!(expected_attempts(&reward), 16f64.powi(4) * 16.0);
+    let reward = RewardVariant::ChecksumMatching {
+        pattern: "1234...".into(),
+    };
+    assert_eq!(expected_attempts(&reward), 16f64.powi(4));
+}
+
+#[test]
+fn test_symmetric_ends() {
+    let reward = RewardVariant::SymmetricEnds { nibbles: 6 };
+    assert_eq!(expected_attempts(&reward), 16f64.powi(6));
+}
+
+#[test]
+fn test_leading_zero_nibbles() {
+    let reward = RewardVariant::LeadingZeroNibbles { threshold: 9 };
+    assert_eq!(expected_attempts(&reward), 16f64.powi(9));
+}
+
+#[test]
+fn test_hopeless_attempts() {
+    assert!(
+        expected_attempts(&RewardVariant::LeadingZeros { zeros_threshold: 8 }) <= HOPELESS_ATTEMPTS
+    );
+    assert!(
+        expected_attempts(&RewardVariant::LeadingZeros { zeros_threshold: 9 }) > HOPELESS_ATTEMPTS
+    );
+}
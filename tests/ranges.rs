@@ -0,0 +1,139 @@
+use createxcrunch::ranges::{parse_skip_ranges, Batch, NonceRanges, SearchCursor};
+
+#[test]
+fn test_merge_unsorted_overlapping_adjacent() {
+    let ranges = NonceRanges::new([30..40, 0..10, 5..12, 12..15, 50..50, 39..45]);
+
+    assert_eq!(ranges.ranges(), &[0..15, 30..45]);
+    assert_eq!(ranges.len(), 30);
+    assert!(NonceRanges::new(std::iter::once(7..7)).is_empty());
+}
+
+#[test]
+fn test_contains_boundaries() {
+    let ranges = NonceRanges::new(std::iter::once(10..20));
+
+    assert!(!ranges.contains(9));
+    assert!(ranges.contains(10));
+    assert!(ranges.contains(19));
+    assert!(!ranges.contains(20));
+}
+
+#[test]
+fn test_complement() {
+    let ranges = NonceRanges::new([10..20, 30..40]);
+
+    assert_eq!(ranges.complement(0..50), vec![0..10, 20..30, 40..50]);
+    assert_eq!(ranges.complement(15..35), vec![20..30]);
+    assert_eq!(ranges.complement(10..20), vec![]);
+    assert_eq!(NonceRanges::default().complement(3..8), vec![3..8]);
+
+    // the complement and the covered part always add up to the whole window
+    let covered = ranges.covered_in(5..37);
+    let gaps: u64 = ranges
+        .complement(5..37)
+        .iter()
+        .map(|r| r.end - r.start)
+        .sum();
+    assert_eq!(covered + gaps, 32);
+}
+
+#[test]
+fn test_iteration_over_uncovered_nonces() {
+    let ranges = NonceRanges::new([10..20, 20..25, 30..40]);
+
+    assert_eq!(ranges.next_uncovered(0), 0);
+    assert_eq!(ranges.next_uncovered(10), 25);
+    assert_eq!(ranges.next_uncovered(24), 25);
+    assert_eq!(ranges.next_uncovered(25), 25);
+
+    assert_eq!(ranges.uncovered_run(0, 100), 10);
+    assert_eq!(ranges.uncovered_run(0, 4), 4);
+    assert_eq!(ranges.uncovered_run(10, 100), 0);
+    assert_eq!(ranges.uncovered_run(25, 100), 5);
+    assert_eq!(ranges.uncovered_run(40, 100), 100);
+
+    // walking the scheduler over 0..50 visits exactly the uncovered nonces
+    let mut nonce = 0;
+    let mut visited = vec![];
+    while nonce < 50 {
+        nonce = ranges.next_uncovered(nonce);
+        let run = ranges.uncovered_run(nonce, 7).min(50 - nonce);
+        visited.extend(nonce..nonce + run);
+        nonce += run;
+    }
+    let expected: Vec<u64> = ranges.complement(0..50).into_iter().flatten().collect();
+    assert_eq!(visited, expected);
+}
+
+#[test]
+fn test_parse_skip_ranges() {
+    let contents = "# covered by campaign 1\n0x 100 200\n\n0x 0 50 # first batch\n0x 150 300\n";
+    let ranges = parse_skip_ranges(contents, &[]).unwrap();
+    assert_eq!(ranges.ranges(), &[0..50, 100..300]);
+
+    let err = parse_skip_ranges("0xab 0 10\n", &[]).unwrap_err();
+    assert!(err.contains("line 1"), "{}", err);
+    assert!(err.contains("does not match"), "{}", err);
+
+    assert!(parse_skip_ranges("0x 10 5\n", &[]).is_err());
+    assert!(parse_skip_ranges("0x 10\n", &[]).is_err());
+    assert!(parse_skip_ranges("0x ten 20\n", &[]).is_err());
+}
+
+/// Walk a cursor from `start` until `end`, returning the batches it dispatched.
+fn mine(cursor: &mut SearchCursor, skip: &NonceRanges, max: u64, end: u64) -> Vec<Batch> {
+    let mut batches = vec![];
+    while let Some(batch) = cursor.next_batch(skip, max, Some(end)) {
+        batches.push(batch);
+        cursor.advance(batch);
+    }
+    batches
+}
+
+#[test]
+fn test_attempts_exclude_skipped_nonces() {
+    let skip = NonceRanges::new([10..20, 30..40]);
+    let mut cursor = SearchCursor::new(0);
+    let batches = mine(&mut cursor, &skip, 16, 50);
+
+    let starts: Vec<(u64, u64)> = batches.iter().map(|b| (b.start, b.count)).collect();
+    assert_eq!(starts, vec![(0, 10), (20, 10), (40, 10)]);
+    // the cursor moved 50 nonces, but only 30 of them were mined
+    assert_eq!(cursor.nonce, 50);
+    assert_eq!(cursor.attempts, 30);
+    assert_eq!(cursor.attempts, 50 - skip.covered_in(0..50));
+
+    // starting inside a covered range counts nothing before its end
+    let mut cursor = SearchCursor::new(15);
+    mine(&mut cursor, &skip, 16, 50);
+    assert_eq!(cursor.attempts, 20);
+}
+
+#[test]
+fn test_partial_final_batch() {
+    let mut cursor = SearchCursor::new(1000);
+    let batches = mine(&mut cursor, &NonceRanges::default(), 16, 1040);
+
+    let counts: Vec<u64> = batches.iter().map(|b| b.count).collect();
+    assert_eq!(counts, vec![16, 16, 8]);
+    // a resumed or leased run counts from its own start, not from nonce 0
+    assert_eq!(cursor.attempts, 40);
+    assert_eq!(
+        cursor.next_batch(&NonceRanges::default(), 16, Some(1040)),
+        None
+    );
+    assert_eq!(cursor.attempts, 40);
+
+    let mut cursor = SearchCursor::new(u64::MAX - 1);
+    let batch = cursor
+        .next_batch(&NonceRanges::default(), 16, None)
+        .unwrap();
+    assert_eq!(batch.count, 1);
+    cursor.advance(batch);
+    let batch = cursor
+        .next_batch(&NonceRanges::default(), 16, None)
+        .unwrap();
+    cursor.advance(batch);
+    assert_eq!((cursor.nonce, cursor.attempts), (0, 2));
+}
@@ -0,0 +1,48 @@
+use createxcrunch::RankedResults;
+
+fn address(prefix: &[u8]) -> [u8; 20] {
+    let mut address = [0x11; 20];
+    address[..prefix.len()].copy_from_slice(prefix);
+    address
+}
+
+#[test]
+fn test_out_of_order_results_are_written_sorted() {
+    let mut ranked = RankedResults::new(10);
+    ranked.push(address(&[0x10]), "one leading nibble".to_string());
+    ranked.push(address(&[0x00, 0x00]), "four leading nibbles".to_string());
+    ranked.push(address(&[0x01]), "two leading nibbles".to_string());
+    ranked.push(
+        address(&[0x00, 0x00]),
+        "four leading nibbles again".to_string(),
+    );
+    ranked.push(address(&[0x20]), "no leading nibble".to_string());
+    ranked.push(
+        address(&[0x00, 0x00, 0xaa]),
+        "four leading nibbles, higher address".to_string(),
+    );
+
+    assert_eq!(ranked.len(), 6);
+    assert_eq!(
+        ranked.into_sorted(),
+        vec![
+            "four leading nibbles",
+            "four leading nibbles again",
+            "four leading nibbles, higher address",
+            "two leading nibbles",
+            "one leading nibble",
+            "no leading nibble",
+        ]
+    );
+}
+
+#[test]
+fn test_ranked_results_keep_the_best() {
+    let mut ranked = RankedResults::new(2);
+    for byte in (0u8..=0xf0).step_by(0x10).rev() {
+        ranked.push(address(&[byte]), format!("{:02x}", byte));
+    }
+
+    assert_eq!(ranked.len(), 2);
+    assert_eq!(ranked.into_sorted(), vec!["00", "10"]);
+}
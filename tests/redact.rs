@@ -0,0 +1,124 @@
+use std::{fs, path::PathBuf};
+
+use alloy_primitives::hex;
+use createxcrunch::{
+    redact::{self, confirm, export, redacted_result, salt_fingerprint},
+    writer::ResultWriter,
+};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "createxcrunch-redact-{}-{}.txt",
+        name,
+        std::process::id()
+    ))
+}
+
+/// Whether `text` holds a run of 64 hex digits, i.e. something that could be a salt.
+fn has_salt_like(text: &str) -> bool {
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c.is_ascii_hexdigit() { run + 1 } else { 0 };
+        if run >= 64 {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_fingerprint() {
+    let salt = [0x11u8; 32];
+    let fingerprint = salt_fingerprint(&salt);
+    assert_eq!(fingerprint, salt_fingerprint(&salt));
+    assert_ne!(fingerprint, salt_fingerprint(&[0x12u8; 32]));
+    assert_eq!(fingerprint.len(), "salt:".len() + 8);
+
+    let address = "0x0000000000c0fFEE0000000000000000deadBEEF";
+    let line = redacted_result(&salt, address);
+    assert_eq!(line, format!("{} -> {}", fingerprint, address));
+    assert!(!line.contains(&hex::encode(salt)));
+    assert!(!has_salt_like(&line));
+}
+
+#[test]
+fn test_unsaved_results_are_withheld() {
+    let salt = format!("0x{}", hex::encode([0xabu8; 32]));
+    let mut stderr = Vec::new();
+    let mut writer = ResultWriter::new(FailingOutput, &mut stderr, 4).withholding();
+    writer.write(&format!(
+        "{} -> 0x0000000000c0fFEE0000000000000000deadBEEF",
+        salt
+    ));
+    assert!(writer.finish().is_err());
+
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stderr.contains("withheld by --redact-salts"));
+    assert!(!has_salt_like(&stderr));
+}
+
+/// Output on a disk that is already full.
+struct FailingOutput;
+
+impl std::io::Write for FailingOutput {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::from(std::io::ErrorKind::StorageFull))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_is_owner_only() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_path("permissions");
+    fs::write(&path, "").unwrap();
+    redact::restrict_permissions(&path).unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_export() {
+    let path = temp_path("export");
+    fs::write(
+        &path,
+        "# Mining create3 for leading-zeros(4) with random salts\n\n0x01 -> 0xaa\n0x02 -> 0xbb\n",
+    )
+    .unwrap();
+
+    // a running miner holds the lock
+    let running = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    redact::lock_output(&running).unwrap();
+    assert!(export(&path, Vec::new())
+        .unwrap_err()
+        .contains("still being written"));
+    drop(running);
+
+    let mut out = Vec::new();
+    assert_eq!(export(&path, &mut out).unwrap(), 2);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "0x01 -> 0xaa\n0x02 -> 0xbb\n"
+    );
+    fs::remove_file(&path).unwrap();
+
+    assert!(export(&path, Vec::new()).is_err());
+}
+
+#[test]
+fn test_export_confirmation() {
+    let path = PathBuf::from("output.txt");
+    let mut prompt = Vec::new();
+    assert!(confirm(&b"reveal\n"[..], &mut prompt, &path).unwrap());
+    assert!(String::from_utf8(prompt).unwrap().contains("output.txt"));
+
+    for answer in ["yes\n", "\n", ""] {
+        assert!(!confirm(answer.as_bytes(), Vec::new(), &path).unwrap());
+    }
+}
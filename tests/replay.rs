@@ -0,0 +1,84 @@
+use createxcrunch::{
+    replay::{self, CycleRecord, MAGIC, VERSION},
+    RESULT_WORDS,
+};
+
+// Cycle 3 of a create2 run from caller 0x…c0ffee, recording results 1 and 3 (nonces 4097 and
+// 2^32 + 7). The corrupted one has a bit of the hash of result 3 flipped
+const AGREEING: &[u8] = include_bytes!("fixtures/replay/agreeing.replay");
+const CORRUPTED: &[u8] = include_bytes!("fixtures/replay/corrupted.replay");
+
+#[test]
+fn test_fixture_replays_in_agreement() {
+    let record = CycleRecord::decode(AGREEING).unwrap();
+    assert_eq!(record.cycle, 3);
+    assert_eq!(record.base_nonce, 4096);
+    assert_eq!(record.dispatched, 8192);
+    assert!(record.fingerprint.starts_with("create2(0x21c35dbe"));
+    assert!(record.kernel.starts_with("// createXcrunch mining kernel 0x"));
+    assert_eq!(record.salt_prefix[..20], hex_address("0000000000000000000000000000000000c0ffee"));
+    assert_eq!(record.salt_prefix[20], 0);
+
+    let replayed = replay::replay_cpu(&record).unwrap();
+    assert_eq!(replayed.len(), 2);
+    assert_eq!(replayed[1].nonce, (1 << 32) + 7);
+    assert!(replayed.iter().all(|replayed| replayed.agrees()));
+    assert!(replay::report(&record, &replayed).ends_with("All candidates agree"));
+}
+
+#[test]
+fn test_fixture_reports_the_corrupted_candidate() {
+    let record = CycleRecord::decode(CORRUPTED).unwrap();
+    let mut replayed = replay::replay_cpu(&record).unwrap();
+    assert!(replayed[0].agrees());
+    assert!(!replayed[1].agrees());
+    assert_eq!(replayed[1].index, 3);
+    assert_eq!(replayed[1].recorded[8] ^ replayed[1].cpu[8], 0x10);
+
+    let report = replay::report(&record, &replayed);
+    assert!(report.contains("#3 nonce 4294967303: recorded 0x"));
+    assert!(report.ends_with("1 of 2 candidates disagree"));
+
+    // a GPU deriving something else than the CPU disagrees too
+    replayed[0].gpu = Some([0; 32]);
+    assert!(!replayed[0].agrees());
+    replayed[0].gpu = Some(replayed[0].cpu);
+    assert!(replayed[0].agrees());
+}
+
+#[test]
+fn test_records_are_versioned() {
+    let record = CycleRecord::decode(AGREEING).unwrap();
+    let bytes = record.encode();
+    assert_eq!(bytes, AGREEING);
+    assert_eq!(&bytes[..8], MAGIC);
+
+    let mut newer = bytes.clone();
+    newer[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert_eq!(
+        CycleRecord::decode(&newer).unwrap_err(),
+        format!(
+            "replay record schema version {} is not supported, this build reads versions 1 to {}",
+            VERSION + 1,
+            VERSION
+        )
+    );
+    assert!(CycleRecord::decode(b"cycle,elapsed_ms").is_err());
+    assert!(CycleRecord::decode(&bytes[..10]).is_err());
+    assert!(CycleRecord::decode(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut tampered = record.clone();
+    tampered.salt_prefix[20] = 1;
+    assert!(replay::replay_cpu(&tampered).is_err());
+    assert_eq!(record.candidates[0].row.len(), RESULT_WORDS as usize);
+}
+
+#[test]
+fn test_record_path() {
+    let path = replay::record_path(std::path::Path::new("records"), 42);
+    assert_eq!(path, std::path::Path::new("records/cycle-00000042.replay"));
+}
+
+fn hex_address(hex: &str) -> [u8; 20] {
+    alloy_primitives::hex::decode(hex).unwrap().try_into().unwrap()
+}
@@ -0,0 +1,126 @@
+use alloy_primitives::hex;
+use createxcrunch::{
+    reserved::{avoided_by_default, check_avoidable, reserved, DENYLIST},
+    Config, RewardVariant,
+};
+
+fn low(bytes: [u8; 2]) -> [u8; 20] {
+    let mut address = [0; 20];
+    address[18..].copy_from_slice(&bytes);
+    address
+}
+
+#[test]
+fn test_precompile_range_boundaries() {
+    assert_eq!(reserved(&low([0x00, 0x01])), Some("the precompile range"));
+    assert_eq!(reserved(&low([0x00, 0x09])), Some("the precompile range"));
+    assert_eq!(reserved(&low([0x09, 0xff])), Some("the precompile range"));
+    assert_eq!(reserved(&low([0x0a, 0x00])), None);
+    assert_eq!(reserved(&low([0xff, 0xff])), None);
+
+    // the range only covers addresses of 18 leading zero bytes
+    let mut address = low([0x00, 0x01]);
+    address[17] = 0x01;
+    assert_eq!(reserved(&address), None);
+    address = low([0x00, 0x01]);
+    address[0] = 0x01;
+    assert_eq!(reserved(&address), None);
+}
+
+#[test]
+fn test_denylisted_addresses() {
+    assert_eq!(reserved(&[0; 20]), Some("the zero address"));
+    assert_eq!(
+        reserved(&hex!("000000000022D473030F116dDEE9F6B43aC78BA3")),
+        Some("Permit2")
+    );
+    assert_eq!(
+        reserved(&hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed")),
+        Some("CreateX")
+    );
+    for (name, address) in DENYLIST {
+        assert_eq!(reserved(&address), Some(name));
+    }
+
+    // one bit off a denylisted address is fine
+    let mut address = hex!("000000000022D473030F116dDEE9F6B43aC78BA3");
+    address[19] ^= 1;
+    assert_eq!(reserved(&address), None);
+}
+
+#[test]
+fn test_rewards_that_can_only_match_reserved_addresses() {
+    let leading = |zeros_threshold| RewardVariant::LeadingZeros { zeros_threshold };
+    let total = |zeros_threshold| RewardVariant::TotalZeros { zeros_threshold };
+    let pattern = |pattern: &str| RewardVariant::Matching {
+        pattern: pattern.into(),
+    };
+
+    // 18 leading zero bytes leave 0x0a00 to 0xffff
+    assert!(check_avoidable(&leading(18), false).is_ok());
+    assert!(check_avoidable(&leading(19), false).is_err());
+    assert!(check_avoidable(&leading(20), false).is_err());
+    assert!(check_avoidable(&total(19), false).is_ok());
+    assert!(check_avoidable(&total(20), false).is_err());
+    assert!(check_avoidable(
+        &RewardVariant::LeadingAndTotalZeros {
+            leading_zeros_threshold: 19,
+            total_zeros_threshold: 4,
+        },
+        false
+    )
+    .is_err());
+    assert!(check_avoidable(
+        &RewardVariant::LeadingOrTotalZeros {
+            leading_zeros_threshold: 19,
+            total_zeros_threshold: 19,
+        },
+        false
+    )
+    .is_ok());
+    assert!(check_avoidable(&RewardVariant::SymmetricEnds { nibbles: 20 }, false).is_ok());
+
+    // 18 zero bytes leave the last two free, 19 only the last
+    assert!(check_avoidable(&pattern("ab..."), false).is_ok());
+    assert!(check_avoidable(&pattern("000000000000000000000000000000000000..."), false).is_ok());
+    assert!(check_avoidable(&pattern("00000000000000000000000000000000000000..."), false).is_err());
+    assert!(check_avoidable(
+        &pattern("00000000000000000000000000000000000000...0a"),
+        false
+    )
+    .is_err());
+    assert!(check_avoidable(&pattern("0000000000000000000000000000000000000a..."), false).is_ok());
+    assert!(check_avoidable(
+        &pattern("000000000022d473030f116ddee9f6b43ac78ba3..."),
+        false
+    )
+    .is_err());
+    assert_eq!(
+        check_avoidable(&leading(19), false).unwrap_err(),
+        "every address matching leading-zeros(19) is reserved (the precompile range or a \
+         well-known address), pass --allow-reserved to mine for it anyway"
+    );
+}
+
+#[test]
+fn test_avoided_by_default_for_zero_rewards() {
+    let config = |reward| {
+        Config::new(
+            0,
+            "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed",
+            None,
+            None,
+            None,
+            reward,
+            "output.txt",
+        )
+        .unwrap()
+    };
+
+    assert!(config(RewardVariant::LeadingZeros { zeros_threshold: 4 }).avoid_reserved);
+    assert!(config(RewardVariant::TotalZeros { zeros_threshold: 4 }).avoid_reserved);
+    assert!(!config(RewardVariant::SymmetricEnds { nibbles: 4 }).avoid_reserved);
+    assert!(!avoided_by_default(&RewardVariant::Matching {
+        pattern: "ab...".into()
+    }));
+}
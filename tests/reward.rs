@@ -0,0 +1,228 @@
+use alloy_primitives::hex;
+use createxcrunch::{
+    checksum_matches, has_leading_zero_nibbles, has_symmetric_ends, probability::expected_attempts,
+    Config, ConfigError, PatternBytes, RewardVariant,
+};
+
+const FACTORY: &str = "ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed";
+
+fn config_with(reward: RewardVariant) -> Result<(), ConfigError> {
+    Config::new(0, FACTORY, None, None, None, reward, "output.txt").map(|_| ())
+}
+
+#[test]
+fn test_symmetric_ends() {
+    let address = hex::decode("cafe0123456789abcdef0123456789abcdefcafe").unwrap();
+    assert!(has_symmetric_ends(&address, 4));
+    assert!(!has_symmetric_ends(&address, 5));
+
+    // the last nibble differs by one
+    let address = hex::decode("cafe0123456789abcdef0123456789abcdefcaff").unwrap();
+    assert!(!has_symmetric_ends(&address, 4));
+
+    // odd nibble counts compare across byte boundaries
+    let address = hex::decode("abc0000000000000000000000000000000000abc").unwrap();
+    assert!(has_symmetric_ends(&address, 3));
+}
+
+#[test]
+fn test_symmetric_ends_validation() {
+    assert!(config_with(RewardVariant::SymmetricEnds { nibbles: 20 }).is_ok());
+    assert!(config_with(RewardVariant::SymmetricEnds { nibbles: 0 }).is_err());
+    assert!(config_with(RewardVariant::SymmetricEnds { nibbles: 21 }).is_err());
+}
+
+#[test]
+fn test_leading_zero_nibbles() {
+    let address = hex::decode("0000000f0123456789abcdef0123456789abcdef").unwrap();
+    assert!(has_leading_zero_nibbles(&address, 6));
+    // the high nibble of the fourth byte is zero, its low one isn't
+    assert!(has_leading_zero_nibbles(&address, 7));
+    assert!(!has_leading_zero_nibbles(&address, 8));
+
+    let address = [0u8; 20];
+    assert!(has_leading_zero_nibbles(&address, 40));
+    assert!(!has_leading_zero_nibbles(&address, 41));
+}
+
+#[test]
+fn test_leading_zero_nibbles_validation() {
+    assert!(config_with(RewardVariant::LeadingZeroNibbles { threshold: 1 }).is_ok());
+    assert!(config_with(RewardVariant::LeadingZeroNibbles { threshold: 40 }).is_ok());
+    assert_eq!(
+        config_with(RewardVariant::LeadingZeroNibbles { threshold: 0 }),
+        Err(ConfigError::ZeroNibblesThreshold)
+    );
+    assert_eq!(
+        config_with(RewardVariant::LeadingZeroNibbles { threshold: 41 }),
+        Err(ConfigError::NibblesThresholdTooLarge)
+    );
+}
+
+#[test]
+fn test_pattern_bytes() {
+    let pattern = PatternBytes::parse("aabb...ccdd").unwrap();
+    assert_eq!(pattern.leading, vec![0xaa, 0xbb]);
+    assert_eq!(pattern.trailing, vec![0xcc, 0xdd]);
+
+    let address = hex::decode("aabb0123456789abcdef0123456789abcdefccdd").unwrap();
+    assert!(pattern.matches(&address));
+    // only the last trailing byte, or only the first leading byte, matches
+    let address = hex::decode("aabb0123456789abcdef0123456789abcdef00dd").unwrap();
+    assert!(!pattern.matches(&address));
+    let address = hex::decode("aa000123456789abcdef0123456789abcdefccdd").unwrap();
+    assert!(!pattern.matches(&address));
+
+    // distinct leading and trailing parts of any length, or a single leading byte
+    let address = hex::decode("deadbeef456789abcdef0123456789abcafebabe").unwrap();
+    for pattern in ["de", "dead...", "deadbeef...cafebabe", "de...be"] {
+        assert!(
+            PatternBytes::parse(pattern).unwrap().matches(&address),
+            "{}",
+            pattern
+        );
+    }
+    assert!(!PatternBytes::parse("bb...cafebabe")
+        .unwrap()
+        .matches(&address));
+
+    // parts overlapping in the address never match
+    let pattern =
+        PatternBytes::parse(&format!("{}...{}", "00".repeat(12), "00".repeat(9))).unwrap();
+    assert!(!pattern.matches(&[0; 20]));
+
+    // dangling nibbles are not bytes
+    assert_eq!(PatternBytes::parse("abc..."), None);
+
+    assert!(config_with(RewardVariant::Matching {
+        pattern: "aabb...ccdd".into()
+    })
+    .is_ok());
+    assert_eq!(
+        config_with(RewardVariant::Matching {
+            pattern: "aabb...ccd".into()
+        }),
+        Err(ConfigError::InvalidPattern(
+            "trailing part must have even number of characters"
+        ))
+    );
+    // trailing bytes alone, but not an empty pattern
+    assert!(config_with(RewardVariant::Matching {
+        pattern: "...cafe".into()
+    })
+    .is_ok());
+    assert!(PatternBytes::parse("...cafe")
+        .unwrap()
+        .matches(&hex::decode("0123456789abcdef0123456789abcdef0123cafe").unwrap()));
+    assert_eq!(
+        config_with(RewardVariant::Matching {
+            pattern: "...".into()
+        }),
+        Err(ConfigError::InvalidPattern(
+            "leading part of pattern cannot be empty"
+        ))
+    );
+}
+
+#[test]
+fn test_pattern_length_is_bounded_by_the_address() {
+    let matching = |pattern: String| {
+        config_with(RewardVariant::Matching {
+            pattern: pattern.into(),
+        })
+    };
+    let too_long = Err(ConfigError::InvalidPattern(
+        "pattern must be at most 40 hex characters, the length of an address",
+    ));
+
+    assert!(matching(format!("{}...", "ab".repeat(20))).is_ok());
+    assert!(matching(format!("{}...{}", "ab".repeat(19), "cd")).is_ok());
+    assert_eq!(matching(format!("{}...", "ab".repeat(21))), too_long);
+    assert_eq!(
+        matching(format!("{}...{}", "ab".repeat(20), "cd")),
+        too_long
+    );
+    assert_eq!(matching(format!("...{}", "cd".repeat(21))), too_long);
+}
+
+#[test]
+fn test_checksum_matching() {
+    // the mixed-case example address of EIP-55
+    let address = hex::decode("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    let address: [u8; 20] = address.try_into().unwrap();
+    for pattern in ["5aAe...", "5aAeb6...", "5a...ed", "5aAe...ed", "5a"] {
+        assert!(checksum_matches(&address, pattern), "{}", pattern);
+    }
+    // right letters in the wrong case, or the wrong bytes
+    for pattern in ["5aae...", "5AAe...", "5a...Ed", "5bAe...", "5a...ee"] {
+        assert!(!checksum_matches(&address, pattern), "{}", pattern);
+    }
+
+    // every letter halves the odds, digits have no case
+    let checksum = |pattern: &str| RewardVariant::ChecksumMatching {
+        pattern: pattern.into(),
+    };
+    let matching = RewardVariant::Matching {
+        pattern: "12ab...".into(),
+    };
+    assert_eq!(
+        expected_attempts(&checksum("12aB...")),
+        4.0 * expected_attempts(&matching)
+    );
+    assert_eq!(
+        expected_attempts(&checksum("1234...")),
+        expected_attempts(&RewardVariant::Matching {
+            pattern: "1234...".into()
+        })
+    );
+
+    assert!(config_with(checksum("DeaD...")).is_ok());
+    assert_eq!(
+        config_with(checksum("DeA...")),
+        Err(ConfigError::InvalidPattern(
+            "leading part must have even number of characters"
+        ))
+    );
+}
+
+#[test]
+fn test_config_errors() {
+    let reward = || RewardVariant::LeadingZeros { zeros_threshold: 4 };
+    let new = |factory: &str, caller: Option<&str>, code_hash: Option<&str>| {
+        Config::new(0, factory, caller, None, code_hash, reward(), "output.txt").map(|_| ())
+    };
+
+    assert_eq!(new("0xzz", None, None), Err(ConfigError::InvalidFactoryHex));
+    assert_eq!(
+        new("0xba5e", None, None),
+        Err(ConfigError::BadFactoryLength)
+    );
+    assert_eq!(
+        new(&FACTORY.replace('b', "B"), None, None),
+        Err(ConfigError::BadFactoryChecksum)
+    );
+    assert_eq!(
+        new(FACTORY, Some("caller"), None),
+        Err(ConfigError::InvalidCallerHex)
+    );
+    assert_eq!(
+        new(FACTORY, Some("0x0102"), None),
+        Err(ConfigError::BadCallerLength)
+    );
+    assert_eq!(
+        new(FACTORY, None, Some(&"01".repeat(31))),
+        Err(ConfigError::InvalidInitCodeHashLength(31))
+    );
+    assert_eq!(
+        config_with(RewardVariant::Matching {
+            pattern: "0g".into()
+        }),
+        Err(ConfigError::InvalidPattern(
+            "pattern must contain only hex characters"
+        ))
+    );
+    assert_eq!(
+        ConfigError::BadFactoryLength.to_string(),
+        "factory address must be 20 bytes"
+    );
+}
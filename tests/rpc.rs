@@ -0,0 +1,250 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::hex;
+use createxcrunch::{
+    http::{HttpTransport, RateLimited},
+    rpc::{RetryPolicy, RpcClient, TokenBucket},
+};
+use serde_json::{json, Value};
+
+struct NodeState {
+    /// Requests answered in each window of a second, those beyond are refused with a 429.
+    limit: usize,
+    /// `Retry-After` header of the 429s, in seconds.
+    retry_after: Option<u64>,
+    /// Whether JSON-RPC batch arrays are answered, or refused with an error.
+    batches: bool,
+    answered: Vec<Instant>,
+    requests: u32,
+    refused: u32,
+}
+
+/// In-process JSON-RPC node answering `eth_getCode` on a local port, rate limited like a
+/// free-tier endpoint. Every account has the code `0x` followed by its first address byte.
+struct MockNode {
+    url: String,
+    state: Arc<Mutex<NodeState>>,
+}
+
+impl MockNode {
+    fn start(limit: usize, retry_after: Option<u64>, batches: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(NodeState {
+            limit,
+            retry_after,
+            batches,
+            answered: Vec::new(),
+            requests: 0,
+            refused: 0,
+        }));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle(stream.unwrap(), &server_state);
+            }
+        });
+        Self { url, state }
+    }
+
+    fn rpc(&self) -> RpcClient<HttpTransport> {
+        RpcClient::new(HttpTransport::new(&self.url).unwrap())
+    }
+
+    fn requests(&self) -> u32 {
+        self.state.lock().unwrap().requests
+    }
+
+    fn refused(&self) -> u32 {
+        self.state.lock().unwrap().refused
+    }
+}
+
+fn get_code(request: &Value) -> Value {
+    let address = hex::decode(request["params"][0].as_str().unwrap()).unwrap();
+    json!({ "jsonrpc": "2.0", "id": request["id"], "result": format!("0x{:02x}", address[0]) })
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<NodeState>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header == "\r\n" {
+            break;
+        }
+        if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let request: Value = serde_json::from_slice(&body).unwrap();
+
+    let mut state = state.lock().unwrap();
+    state.requests += 1;
+    let now = Instant::now();
+    state
+        .answered
+        .retain(|answered| now.duration_since(*answered) < Duration::from_secs(1));
+    if state.answered.len() >= state.limit {
+        state.refused += 1;
+        let retry_after = state
+            .retry_after
+            .map(|seconds| format!("Retry-After: {}\r\n", seconds))
+            .unwrap_or_default();
+        let _ = write!(
+            stream,
+            "HTTP/1.1 429 Too Many Requests\r\n{}Content-Length: 0\r\n\r\n",
+            retry_after
+        );
+        return;
+    }
+    state.answered.push(now);
+
+    let response = match &request {
+        Value::Array(requests) if state.batches => {
+            // answered in reverse, batch responses may come in any order
+            Value::from(requests.iter().rev().map(get_code).collect::<Vec<_>>())
+        }
+        Value::Array(_) => json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32600, "message": "batch requests are not supported" },
+        }),
+        request => get_code(request),
+    };
+    let body = response.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+}
+
+fn addresses(n: u8) -> Vec<[u8; 20]> {
+    (1..=n).map(|first| [first; 20]).collect()
+}
+
+#[test]
+fn test_token_bucket_spaces_out_requests() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(4, 2, start);
+
+    // the burst goes through at once, then requests are spaced by a quarter second
+    assert_eq!(bucket.take(start), Duration::ZERO);
+    assert_eq!(bucket.take(start), Duration::ZERO);
+    assert_eq!(bucket.take(start), Duration::from_millis(250));
+    assert_eq!(bucket.take(start), Duration::from_millis(500));
+
+    // the owed tokens are paid back before the bucket refills
+    let later = start + Duration::from_millis(500);
+    assert_eq!(bucket.take(later), Duration::from_millis(250));
+    let idle = later + Duration::from_secs(10);
+    assert_eq!(bucket.take(idle), Duration::ZERO);
+    assert_eq!(bucket.take(idle), Duration::ZERO);
+    assert_eq!(bucket.take(idle), Duration::from_millis(250));
+}
+
+#[test]
+fn test_retry_delay_respects_retry_after() {
+    let policy = RetryPolicy {
+        retries: 10,
+        base: Duration::from_millis(100),
+        max: Duration::from_secs(1),
+    };
+    let without = RateLimited { retry_after: None };
+    assert_eq!(policy.delay(0, without), Duration::from_millis(100));
+    assert_eq!(policy.delay(2, without), Duration::from_millis(400));
+    assert_eq!(policy.delay(9, without), Duration::from_secs(1));
+
+    let with = RateLimited {
+        retry_after: Some(Duration::from_secs(3)),
+    };
+    assert_eq!(policy.delay(0, with), Duration::from_secs(3));
+}
+
+#[test]
+fn test_rate_limit_avoids_refusals() {
+    // a second of requests can take the burst and then a second of tokens, 7 of the 10 allowed
+    let node = MockNode::start(10, Some(1), true);
+    let mut rpc = node
+        .rpc()
+        .with_rate_limit(5, 2)
+        .with_retries(RetryPolicy {
+            retries: 0,
+            ..Default::default()
+        });
+
+    for address in addresses(12) {
+        assert_eq!(rpc.get_code(&address), Ok(vec![address[0]]));
+    }
+    assert_eq!(node.requests(), 12);
+    assert_eq!(node.refused(), 0);
+}
+
+#[test]
+fn test_refusals_are_retried() {
+    // without a limiter the node refuses most lookups, and waits on Retry-After get them through
+    let node = MockNode::start(3, Some(1), true);
+    let mut rpc = node.rpc();
+
+    let codes: Vec<_> = addresses(6)
+        .iter()
+        .map(|address| rpc.get_code(address))
+        .collect();
+    let expected: Vec<_> = addresses(6)
+        .iter()
+        .map(|address| Ok(vec![address[0]]))
+        .collect();
+    assert_eq!(codes, expected);
+    assert!(node.refused() > 0);
+}
+
+#[test]
+fn test_exhausted_retries_report_the_refusal() {
+    let node = MockNode::start(1, None, true);
+    let mut rpc = node.rpc().with_retries(RetryPolicy {
+        retries: 2,
+        base: Duration::from_millis(10),
+        max: Duration::from_millis(50),
+    });
+
+    assert_eq!(rpc.get_code(&[1; 20]), Ok(vec![1]));
+    let refused = rpc.get_code(&[2; 20]).unwrap_err();
+    assert!(refused.contains("429"), "{}", refused);
+    assert_eq!(node.refused(), 3);
+}
+
+#[test]
+fn test_lookups_are_batched() {
+    let node = MockNode::start(100, None, true);
+    let mut rpc = node.rpc();
+
+    let codes = rpc.get_codes(&addresses(5)).unwrap();
+    assert_eq!(codes, (1..=5).map(|first| vec![first]).collect::<Vec<_>>());
+    assert_eq!(node.requests(), 1);
+}
+
+#[test]
+fn test_refused_batches_fall_back_to_single_lookups() {
+    let node = MockNode::start(100, None, false);
+    let mut rpc = node.rpc();
+
+    let codes = rpc.get_codes(&addresses(3)).unwrap();
+    assert_eq!(codes, vec![vec![1], vec![2], vec![3]]);
+    assert_eq!(node.requests(), 4);
+
+    // batches are not tried again once refused
+    rpc.get_codes(&addresses(3)).unwrap();
+    assert_eq!(node.requests(), 7);
+}
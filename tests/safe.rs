@@ -0,0 +1,51 @@
+use alloy_primitives::{hex, keccak256, U256};
+use createxcrunch::{
+    safe::{deploy_calldata, transaction, SafeBatch},
+    CreateVariant,
+};
+
+const FACTORY: [u8; 20] = hex!("ba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed");
+
+#[test]
+fn test_deploy_calldata_decodes_to_salt() {
+    let salt = hex!("00000000000000000000000000000000000000000000000000000000deadbeef");
+    let init_code = hex!("6080604052348015600e575f80fd5b50");
+    let data = deploy_calldata(&CreateVariant::Create3, &salt, &init_code);
+
+    assert_eq!(data[..4], keccak256("deployCreate3(bytes32,bytes)")[..4]);
+    assert_eq!(data[4..36], salt);
+
+    // the bytes argument is found through its offset in the head
+    let offset = U256::from_be_slice(&data[36..68]).to::<usize>();
+    let len_at = 4 + offset;
+    let len = U256::from_be_slice(&data[len_at..len_at + 32]).to::<usize>();
+    assert_eq!(data[len_at + 32..len_at + 32 + len], init_code);
+    assert_eq!((data.len() - 4) % 32, 0);
+
+    let create2 = CreateVariant::Create2 {
+        init_code_hash: keccak256(init_code).into(),
+    };
+    assert_eq!(
+        deploy_calldata(&create2, &salt, &init_code)[..4],
+        keccak256("deployCreate2(bytes32,bytes)")[..4]
+    );
+}
+
+#[test]
+fn test_safe_batch_json() {
+    let mut batch = SafeBatch::new(10, 1_700_000_000_000);
+    assert!(batch.is_empty());
+    batch.push(transaction(&FACTORY, &[0x12, 0x34]));
+
+    let json = batch.to_json();
+    assert_eq!(json["version"], "1.0");
+    assert_eq!(json["chainId"], "10");
+    assert_eq!(json["createdAt"], 1_700_000_000_000u64);
+    let transaction = &json["transactions"][0];
+    assert_eq!(
+        transaction["to"],
+        "0xba5Ed099633D3B313e4D5F7bdc1305d3c28ba5Ed"
+    );
+    assert_eq!(transaction["value"], "0");
+    assert_eq!(transaction["data"], "0x1234");
+}
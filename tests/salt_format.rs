@@ -0,0 +1,77 @@
+use createxcrunch::{parse_salt, OutputFormat, SaltFormat};
+
+const SALTS: [[u8; 32]; 4] = [[0u8; 32], [0xff; 32], [0x01; 32], {
+    let mut salt = [0u8; 32];
+    salt[31] = 0x2a;
+    salt
+}];
+
+#[test]
+fn test_salt_renderings_round_trip() {
+    for salt in SALTS {
+        for format in [
+            SaltFormat::Bytes32,
+            SaltFormat::Base64,
+            SaltFormat::Uint256Dec,
+        ] {
+            let rendered = format.render(&salt);
+            assert_eq!(parse_salt(&rendered), Ok(salt), "{} {}", format, rendered);
+        }
+    }
+}
+
+#[test]
+fn test_salt_renderings() {
+    let salt = SALTS[3];
+    assert_eq!(
+        SaltFormat::Bytes32.render(&salt),
+        "0x000000000000000000000000000000000000000000000000000000000000002a"
+    );
+    assert_eq!(
+        SaltFormat::Base64.render(&salt),
+        "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAACo="
+    );
+    assert_eq!(SaltFormat::Uint256Dec.render(&salt), "42");
+    assert_eq!(
+        SaltFormat::Uint256Dec.render(&[0xff; 32]),
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+}
+
+#[test]
+fn test_parse_salt_errors() {
+    // one past uint256 max
+    assert!(parse_salt(
+        "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+    )
+    .is_err());
+    assert!(parse_salt("0x1234").is_err());
+    assert!(parse_salt("AQID").is_err());
+    assert!(parse_salt("not a salt!").is_err());
+
+    let bare = "ff".repeat(32);
+    assert_eq!(
+        parse_salt(&bare),
+        Err(format!("salt {} looks like hex, hex salts need the 0x prefix", bare))
+    );
+    // 64 decimal digits are a decimal salt
+    assert!(parse_salt(&"1".repeat(64)).is_ok());
+}
+
+#[test]
+fn test_format_result_with_salt() {
+    let salt = SALTS[3];
+    let address = "0xBB00000000000000000000000000000000000000";
+
+    assert_eq!(
+        OutputFormat::Text.format_result_with_salt(&salt, address, SaltFormat::Uint256Dec),
+        format!("{} 42 -> {}", SaltFormat::Bytes32.render(&salt), address)
+    );
+
+    let json: serde_json::Value = serde_json::from_str(
+        &OutputFormat::Json.format_result_with_salt(&salt, address, SaltFormat::Base64),
+    )
+    .unwrap();
+    assert_eq!(json["salt_base64"], SaltFormat::Base64.render(&salt));
+    assert_eq!(json["address"], address);
+}
@@ -0,0 +1,70 @@
+use createxcrunch::scan::ScanOrder;
+use std::collections::HashSet;
+
+const ORDERS: [ScanOrder; 3] = [ScanOrder::Linear, ScanOrder::Lcg, ScanOrder::Splitmix];
+
+#[test]
+fn test_scan_orders_are_bijective_on_subspace() {
+    for order in ORDERS {
+        for seed in [0, 0xdead_beef_cafe_babe] {
+            let mut nonces = HashSet::with_capacity(1 << 20);
+            for index in 0..1u64 << 20 {
+                assert!(
+                    nonces.insert(order.nonce_at(index, seed)),
+                    "{} seed {:x} repeats a nonce at index {}",
+                    order,
+                    seed,
+                    index
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scan_orders_invert() {
+    for order in ORDERS {
+        for seed in [0, 7, 0xdead_beef_cafe_babe] {
+            for index in [0, 1, 42, 1 << 40, u64::MAX] {
+                assert_eq!(
+                    order.index_of(order.nonce_at(index, seed), seed),
+                    index,
+                    "{} seed {:x}",
+                    order,
+                    seed
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scan_order_values() {
+    assert_eq!(ScanOrder::Linear.nonce_at(42, 7), 42);
+    assert_eq!(ScanOrder::Lcg.nonce_at(0, 0), 1442695040888963407);
+    // first output of the reference splitmix64 seeded with 0
+    assert_eq!(ScanOrder::Splitmix.nonce_at(0, 0), 0xe220a8397b1dcdaf);
+    assert_ne!(
+        ScanOrder::Splitmix.nonce_at(5, 1),
+        ScanOrder::Splitmix.nonce_at(5, 2)
+    );
+}
+
+#[test]
+fn test_scan_order_parsing_and_header() {
+    for order in ORDERS {
+        assert_eq!(order.to_string().parse(), Ok(order));
+    }
+    assert!("random".parse::<ScanOrder>().is_err());
+    assert_eq!(
+        ScanOrder::Splitmix.header(0xff),
+        "# Scan order: splitmix seed 0x00000000000000ff"
+    );
+    for order in ORDERS {
+        assert_eq!(
+            ScanOrder::parse_header(&order.header(0xdead_beef)),
+            Some((order, 0xdead_beef))
+        );
+    }
+    assert_eq!(ScanOrder::parse_header("# Format: salt -> ethereum_address"), None);
+}
@@ -0,0 +1,249 @@
+use std::{cell::RefCell, time::Duration};
+
+use chrono::{
+    DateTime, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
+use createxcrunch::schedule::{
+    format_duration, parse_duration, wait_for_window, Clock, Schedule, TimeWindow,
+};
+
+/// Central European time in 2026: UTC+1, UTC+2 from March 29 01:00 UTC to October 25 01:00 UTC.
+#[derive(Clone, Copy, Debug)]
+struct Cet;
+
+fn utc(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+}
+
+impl TimeZone for Cet {
+    type Offset = FixedOffset;
+
+    fn from_offset(_offset: &FixedOffset) -> Self {
+        Cet
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+        self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+        let valid: Vec<FixedOffset> = [2, 1]
+            .map(|hours| FixedOffset::east_opt(hours * 3600).unwrap())
+            .into_iter()
+            .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+            .collect();
+        match valid[..] {
+            [] => MappedLocalTime::None,
+            [offset] => MappedLocalTime::Single(offset),
+            [earliest, latest] => MappedLocalTime::Ambiguous(earliest, latest),
+            _ => unreachable!(),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+        self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_utc_datetime(&self, at: &NaiveDateTime) -> FixedOffset {
+        let summer = *at >= utc("2026-03-29 01:00") && *at < utc("2026-10-25 01:00");
+        FixedOffset::east_opt(if summer { 7200 } else { 3600 }).unwrap()
+    }
+}
+
+fn local(s: &str) -> DateTime<Cet> {
+    Cet.from_local_datetime(&utc(s)).earliest().unwrap()
+}
+
+fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+}
+
+#[test]
+fn test_parse_schedule() {
+    let schedule: Schedule = "22:00-06:00, 12:30-13:00".parse().unwrap();
+    assert_eq!(
+        schedule.windows(),
+        &[
+            TimeWindow {
+                start: time("22:00"),
+                end: time("06:00")
+            },
+            TimeWindow {
+                start: time("12:30"),
+                end: time("13:00")
+            },
+        ]
+    );
+    assert_eq!(schedule.to_string(), "22:00-06:00,12:30-13:00");
+
+    for invalid in [
+        "",
+        "22:00",
+        "22:00-",
+        "25:00-06:00",
+        "22:00-06:60",
+        "8-9",
+        "22:00-22:00",
+    ] {
+        assert!(invalid.parse::<Schedule>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_windows_spanning_midnight() {
+    let schedule: Schedule = "22:00-06:00".parse().unwrap();
+    for open in ["22:00", "23:59", "00:00", "05:59"] {
+        assert!(schedule.is_open(time(open)), "{}", open);
+    }
+    for closed in ["06:00", "12:00", "21:59"] {
+        assert!(!schedule.is_open(time(closed)), "{}", closed);
+    }
+
+    assert_eq!(schedule.pause_until(&local("2026-01-10 23:00")), None);
+    assert_eq!(
+        schedule.pause_until(&local("2026-01-10 06:00")),
+        Some(local("2026-01-10 22:00"))
+    );
+
+    // the earliest opening of several windows, on the next day once today's have passed
+    let schedule: Schedule = "22:00-23:00,01:00-02:00".parse().unwrap();
+    assert_eq!(
+        schedule.pause_until(&local("2026-01-10 00:30")),
+        Some(local("2026-01-10 01:00"))
+    );
+    assert_eq!(
+        schedule.pause_until(&local("2026-01-10 23:30")),
+        Some(local("2026-01-11 01:00"))
+    );
+}
+
+#[test]
+fn test_dst_transitions() {
+    // clocks jump from 02:00 to 03:00, a window opening in between opens at 03:00
+    let schedule: Schedule = "02:30-04:00".parse().unwrap();
+    let resume = schedule.pause_until(&local("2026-03-29 01:00")).unwrap();
+    assert_eq!(resume, local("2026-03-29 03:00"));
+    assert_eq!(
+        resume.with_timezone(&Utc).naive_utc(),
+        utc("2026-03-29 01:00")
+    );
+    assert_eq!(schedule.pause_until(&resume), None);
+
+    // one hour of wall-clock time is only half an hour of real time across the jump
+    let schedule: Schedule = "03:00-05:00".parse().unwrap();
+    let now = local("2026-03-29 01:30");
+    let resume = schedule.pause_until(&now).unwrap();
+    assert_eq!(resume - now, chrono::Duration::minutes(30));
+
+    // clocks fall back from 03:00 to 02:00, a window opening at 02:30 opens the first time
+    let schedule: Schedule = "02:30-02:45".parse().unwrap();
+    let resume = schedule.pause_until(&local("2026-10-25 01:00")).unwrap();
+    assert_eq!(
+        resume.with_timezone(&Utc).naive_utc(),
+        utc("2026-10-25 00:30")
+    );
+    // the window repeats in the second 02:00 hour, it is wall-clock time that counts
+    let second = Cet.from_utc_datetime(&utc("2026-10-25 01:35"));
+    assert_eq!(schedule.pause_until(&second), None);
+}
+
+/// Clock advanced by sleeping, recording the sleeps.
+struct MockClock {
+    now: RefCell<DateTime<Cet>>,
+    sleeps: RefCell<Vec<Duration>>,
+}
+
+impl Clock for MockClock {
+    type Tz = Cet;
+
+    fn now(&self) -> DateTime<Cet> {
+        *self.now.borrow()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        *self.now.borrow_mut() += chrono::Duration::from_std(duration).unwrap();
+        self.sleeps.borrow_mut().push(duration);
+    }
+}
+
+#[test]
+fn test_wait_for_window() {
+    let schedule: Schedule = "22:00-06:00".parse().unwrap();
+    let clock = MockClock {
+        now: RefCell::new(local("2026-01-10 21:58")),
+        sleeps: RefCell::new(vec![]),
+    };
+
+    // sleeps in steps, checking in before each, until the window opens
+    let mut checks = 0;
+    assert!(wait_for_window(
+        &schedule,
+        &clock,
+        Duration::from_secs(50),
+        || {
+            checks += 1;
+            true
+        }
+    ));
+    assert_eq!(clock.now(), local("2026-01-10 22:00"));
+    assert_eq!(checks, 3);
+    assert_eq!(
+        *clock.sleeps.borrow(),
+        [50, 50, 20].map(Duration::from_secs)
+    );
+
+    // an open window does not wait
+    assert!(wait_for_window(
+        &schedule,
+        &clock,
+        Duration::from_secs(50),
+        || { unreachable!() }
+    ));
+
+    // stops waiting when told to
+    *clock.now.borrow_mut() = local("2026-01-11 12:00");
+    let mut checks = 0;
+    assert!(!wait_for_window(
+        &schedule,
+        &clock,
+        Duration::from_secs(1),
+        || {
+            checks += 1;
+            checks < 5
+        }
+    ));
+    assert_eq!(
+        clock.now(),
+        local("2026-01-11 12:00") + chrono::Duration::seconds(4)
+    );
+}
+
+#[test]
+fn test_parse_durations() {
+    assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+    assert_eq!(parse_duration("6h"), Ok(Duration::from_secs(6 * 3600)));
+    assert_eq!(
+        parse_duration("2h30m"),
+        Ok(Duration::from_secs(2 * 3600 + 30 * 60))
+    );
+    assert_eq!(
+        parse_duration(" 1d2h3m4s "),
+        Ok(Duration::from_secs(86_400 + 2 * 3600 + 3 * 60 + 4))
+    );
+
+    for invalid in ["", "90", "h", "6 h", "30m2h", "2h2h", "1w", "-1h", "1.5h"] {
+        assert!(parse_duration(invalid).is_err(), "{}", invalid);
+    }
+    assert_eq!(
+        parse_duration("0h"),
+        Err("duration 0h is empty".to_string())
+    );
+    assert!(parse_duration(&format!("{}d", u64::MAX)).is_err());
+
+    // rendered the way they are read
+    for duration in ["2h30m", "1d4s", "45s", "6h"] {
+        assert_eq!(format_duration(parse_duration(duration).unwrap()), duration);
+    }
+    assert_eq!(format_duration(Duration::from_millis(90_500)), "1m30s");
+    assert_eq!(format_duration(Duration::ZERO), "0s");
+}
@@ -0,0 +1,181 @@
+use std::path::Path;
+
+use createxcrunch::{
+    bundle::{self, BundleMember},
+    campaign::Discovery,
+    checkpoint::Checkpoint,
+    cycle_stats::StatsFile,
+    scan::ScanOrder,
+    schemas::{self, Artifact},
+    FoundSalt,
+};
+use serde_json::json;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/schemas");
+
+fn fixture(name: &str) -> String {
+    Path::new(FIXTURES).join(name).display().to_string()
+}
+
+#[test]
+fn test_versions() {
+    for artifact in Artifact::ALL {
+        assert!(artifact.oldest() <= artifact.current(), "{}", artifact);
+        assert!(artifact.check(artifact.current()).is_ok(), "{}", artifact);
+        assert!(artifact.check(artifact.current() + 1).is_err(), "{}", artifact);
+    }
+    assert_eq!(
+        Artifact::Checkpoint.check(7),
+        Err(
+            "checkpoint schema version 7 is not supported, this build reads versions 0 to 1"
+                .to_string()
+        )
+    );
+
+    assert_eq!(Artifact::Results.version_of(&json!({})), Ok(0));
+    assert_eq!(
+        Artifact::Results.version_of(&json!({ "schema_version": 1 })),
+        Ok(1)
+    );
+    assert!(Artifact::Results
+        .version_of(&json!({ "schema_version": "1" }))
+        .is_err());
+}
+
+#[test]
+fn test_migrate() {
+    let legacy = json!({ "next_index": 5, "scan_order": "linear", "scan_seed": 0 });
+    assert_eq!(
+        schemas::migrate(Artifact::Checkpoint, legacy.clone(), 0, 1),
+        Ok(json!({
+            "schema_version": 1,
+            "next_index": 5,
+            "scan_order": "linear",
+            "scan_seed": 0,
+            "attempts": 0,
+            "found": 0,
+            "fingerprint": null,
+            "campaign": null,
+        }))
+    );
+    // migrating to the same version changes nothing
+    assert_eq!(
+        schemas::migrate(Artifact::Checkpoint, legacy.clone(), 0, 0),
+        Ok(legacy.clone())
+    );
+
+    assert_eq!(
+        schemas::migrate(Artifact::Checkpoint, legacy.clone(), 1, 0),
+        Err("checkpoint schema version 1 can't be migrated back to version 0".to_string())
+    );
+    assert!(schemas::migrate(Artifact::Checkpoint, legacy, 0, 2).is_err());
+    assert!(schemas::migrate(Artifact::Results, json!([1]), 0, 1).is_err());
+}
+
+#[test]
+fn test_results_migrate() {
+    let line = std::fs::read_to_string(fixture("results-v0.jsonl")).unwrap();
+    let result = FoundSalt::parse(line.trim()).unwrap();
+    assert_eq!(result.schema_version, Artifact::Results.current());
+    assert_eq!(result.address, "0x00b77830AAd45015B805AaA8AFa5E31D346126a8");
+    assert_eq!(result.attempt, 42_000_000);
+    assert_eq!(
+        result.salt_formats["salt_base64"],
+        "ERERERERERERERERERERERERERERERERERERERERERE="
+    );
+
+    // results written now carry their version, and read back unchanged
+    let line = result.to_line();
+    assert!(line.starts_with("{\"schema_version\":1,"), "{}", line);
+    assert_eq!(FoundSalt::parse(&line), Ok(result));
+
+    let newer = std::fs::read_to_string(fixture("results-v2.jsonl")).unwrap();
+    assert_eq!(
+        FoundSalt::parse(newer.trim()),
+        Err(
+            "jsonl result schema version 2 is not supported, this build reads versions 0 to 1"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_checkpoints_migrate() {
+    let path = fixture("v0.checkpoint");
+    let checkpoint = Checkpoint::load(Path::new(&path)).unwrap().unwrap();
+    assert_eq!(
+        checkpoint,
+        Checkpoint {
+            next_index: 1 << 20,
+            scan_order: ScanOrder::Splitmix,
+            scan_seed: 0xdead_beef,
+            attempts: 0,
+            found: 0,
+            fingerprint: None,
+            campaign: None,
+        }
+    );
+
+    let path = fixture("v2.checkpoint");
+    assert_eq!(
+        Checkpoint::load(Path::new(&path)),
+        Err(format!(
+            "could not read checkpoint {}: checkpoint schema version 2 is not supported, this build reads versions 0 to 1",
+            path
+        ))
+    );
+}
+
+#[test]
+fn test_sessions_migrate() {
+    let discovery = Discovery::scan(&Path::new(FIXTURES).join("campaign"), "launch").unwrap();
+    assert_eq!(discovery.sessions.len(), 1);
+    assert_eq!(discovery.sessions[0].session.schema_version, 1);
+    assert_eq!(discovery.sessions[0].session.started_unix, 1_760_000_000);
+
+    // sessions of newer builds in the same log are skipped with a warning
+    assert_eq!(discovery.warnings.len(), 1);
+    assert!(
+        discovery.warnings[0].ends_with(
+            "line 2: skipped invalid session: campaign session schema version 2 is not supported, this build reads versions 0 to 1"
+        ),
+        "{:?}",
+        discovery.warnings
+    );
+}
+
+#[test]
+fn test_newer_bundles_are_refused() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-schemas-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let build = |version: u32| {
+        let build = json!({ "schema_version": version, "name": "createxcrunch" });
+        BundleMember::new("build.json", serde_json::to_vec(&build).unwrap())
+    };
+
+    bundle::write_bundle(&dir.join("current.tar.gz"), vec![bundle::build_info()]).unwrap();
+    assert!(Discovery::scan(&dir, "launch").is_ok());
+
+    bundle::write_bundle(&dir.join("newer.tar.gz"), vec![build(2)]).unwrap();
+    let error = Discovery::scan(&dir, "launch").unwrap_err();
+    assert!(
+        error.ends_with(
+            "newer.tar.gz: bundle schema version 2 is not supported, this build reads versions 0 to 1"
+        ),
+        "{}",
+        error
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_stats_rows_are_versioned_from_one() {
+    assert_eq!(
+        StatsFile::parse("old.csv", "0,1,20,20.0,1,4,0\n"),
+        Err(
+            "old.csv line 1: stats row schema version 0 is not supported, this build reads versions 1 to 1"
+                .to_string()
+        )
+    );
+}
@@ -0,0 +1,84 @@
+use alloy_primitives::{hex, keccak256};
+use createxcrunch::{
+    create3_address, mix_session_seed, scan::ScanOrder, seed_fingerprint, seed_scan_seed,
+    seed_start_nonce, SaltVariant, Seed, SeedEntropy,
+};
+
+#[test]
+fn test_seed_differs_by_pid() {
+    // Two processes started in the same wall-clock second on a host with a poorly seeded RNG
+    let first = SeedEntropy {
+        os_random: [7u8; 32],
+        clock_nanos: 1_700_000_000_000_000_000,
+        pid: 4242,
+        gpu_name: "Test GPU",
+    };
+    let second = SeedEntropy { pid: 4243, ..first };
+
+    let first_seed = mix_session_seed(&first);
+    let second_seed = mix_session_seed(&second);
+
+    assert_ne!(first_seed, second_seed);
+    assert_ne!(
+        seed_fingerprint(&first_seed),
+        seed_fingerprint(&second_seed)
+    );
+    assert_eq!(first_seed, mix_session_seed(&first));
+}
+
+#[test]
+fn test_seed_fingerprint() {
+    let seed = [7u8; 32];
+    let fingerprint = seed_fingerprint(&seed);
+
+    assert_eq!(fingerprint.len(), 8);
+    assert_eq!(fingerprint, hex::encode(&keccak256(seed)[..4]));
+    assert_eq!(fingerprint, seed_fingerprint(&[7u8; 32]));
+    assert_ne!(fingerprint, seed_fingerprint(&[8u8; 32]));
+}
+
+#[test]
+fn test_seed_parses_decimal_and_hex() {
+    let seed: Seed = "42".parse().unwrap();
+    assert_eq!(seed, "0x2a".parse().unwrap());
+    assert_eq!(seed.0[31], 42);
+    assert_eq!(seed.to_string(), format!("0x{}2a", "00".repeat(31)));
+    assert_eq!(seed.to_string().parse::<Seed>().unwrap(), seed);
+
+    assert!("0x".parse::<Seed>().is_err());
+    assert!("forty-two".parse::<Seed>().is_err());
+    assert!(format!("0x1{}", "00".repeat(32)).parse::<Seed>().is_err());
+    assert_ne!(Seed::random(), Seed::random());
+}
+
+/// The first `count` salts a run mines from the session seed of `seed`, derived on the CPU as
+/// the kernel does.
+fn candidate_salts(seed: Seed, order: ScanOrder, count: u64) -> Vec<[u8; 32]> {
+    let session_seed = seed.session_seed();
+    let start = seed_start_nonce(&session_seed);
+    (start..start + count)
+        .map(|index| order.nonce_at(index, seed_scan_seed(&session_seed)))
+        .map(|nonce| SaltVariant::Random.salt(nonce))
+        .collect()
+}
+
+#[test]
+fn test_seed_reproduces_the_candidate_salts() {
+    let seed: Seed = "42".parse().unwrap();
+    for order in [ScanOrder::Linear, ScanOrder::Splitmix] {
+        let salts = candidate_salts(seed, order, 64);
+        assert_eq!(salts, candidate_salts("0x2a".parse().unwrap(), order, 64));
+        assert_ne!(salts, candidate_salts("43".parse().unwrap(), order, 64));
+
+        let factory = [0xba; 20];
+        let address = |salt: &[u8; 32]| create3_address(&factory, &SaltVariant::Random.guard(salt));
+        let addresses: Vec<_> = salts.iter().map(address).collect();
+        let again: Vec<_> = candidate_salts(seed, order, 64).iter().map(address).collect();
+        assert_eq!(addresses, again);
+    }
+
+    // small seeds start far apart rather than at neighbouring nonces
+    let first = seed_start_nonce(&Seed([0; 32]).session_seed());
+    let second = seed_start_nonce(&"1".parse::<Seed>().unwrap().session_seed());
+    assert!(first.abs_diff(second) > 1 << 32);
+}
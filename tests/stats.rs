@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use alloy_primitives::hex;
+use createxcrunch::stats::{
+    expected_duration, explored_probability, format_cost, format_odds,
+    leading_zero_nibbles_probability, nibble_run_probability, total_zero_bytes_probability,
+    Benchmark, CostModel, Rarity,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+#[test]
+fn test_cost_model_from_options() {
+    assert_eq!(CostModel::from_options(None, None, None), None);
+    assert_eq!(
+        CostModel::from_options(None, None, Some(2.5)),
+        Some(CostModel::Cloud { per_hour: 2.5 })
+    );
+
+    let electricity = CostModel::from_options(Some(300.0), Some(0.2), None).unwrap();
+    assert!((electricity.cost_per_hour() - 0.06).abs() < 1e-9);
+}
+
+#[test]
+fn test_projected_and_consumed_cost() {
+    let electricity = CostModel::Electricity {
+        watts: 500.0,
+        price_per_kwh: 0.3,
+    };
+    let two_hours = Duration::from_secs(7200);
+    assert!((electricity.energy_kwh(two_hours).unwrap() - 1.0).abs() < 1e-9);
+    assert!((electricity.cost(two_hours) - 0.3).abs() < 1e-9);
+
+    // 2^32 attempts at 2^20 per second take 4096 s, 1.1378 hours at 1.20 per hour
+    let cloud = CostModel::Cloud { per_hour: 1.2 };
+    assert_eq!(cloud.energy_kwh(two_hours), None);
+    let cost = cloud.cost_per_result(2f64.powi(32), 2f64.powi(20)).unwrap();
+    assert_eq!(format_cost(cost), "1.37");
+    assert_eq!(cloud.cost_per_result(2f64.powi(32), 0.0), None);
+
+    let report = cloud.report(two_hours, 2f64.powi(32), 2f64.powi(20));
+    assert!(report.starts_with("Cost of this run: 2.40"));
+    assert!(report.ends_with("1.37"));
+}
+
+#[test]
+fn test_expected_duration() {
+    assert_eq!(expected_duration(1e6, 1e3), Some(Duration::from_secs(1000)));
+    assert_eq!(expected_duration(f64::INFINITY, 1e3), None);
+    assert_eq!(format_cost(2e12), "2.00e12");
+}
+
+#[test]
+fn test_explored_probability() {
+    assert_eq!(explored_probability(0, 256.0), 0.0);
+    // one leading zero byte: 1 - (255/256)^256
+    let explored = explored_probability(256, 256.0);
+    assert!((explored - (1.0 - (255.0f64 / 256.0).powi(256))).abs() < 1e-12);
+    // the expected attempts of a hard reward explore 1 - 1/e of its odds
+    let explored = explored_probability(1 << 40, 2f64.powi(40));
+    assert!((explored - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+    assert!(explored_probability(1 << 20, 2f64.powi(160)) > 0.0);
+    assert_eq!(explored_probability(5, 1.0), 1.0);
+    assert_eq!(explored_probability(u64::MAX, f64::INFINITY), 0.0);
+}
+
+#[test]
+fn test_rarity_probabilities() {
+    assert_eq!(leading_zero_nibbles_probability(0), 1.0);
+    assert_eq!(leading_zero_nibbles_probability(10), 16f64.powi(-10));
+    assert_eq!(leading_zero_nibbles_probability(41), 0.0);
+
+    assert!((total_zero_bytes_probability(0) - 1.0).abs() < 1e-12);
+    assert!((total_zero_bytes_probability(1) - (1.0 - (255.0f64 / 256.0).powi(20))).abs() < 1e-12);
+    assert_eq!(total_zero_bytes_probability(20), 256f64.powi(-20));
+
+    assert_eq!(nibble_run_probability(1), 1.0);
+    assert!((nibble_run_probability(2) - (1.0 - (15.0f64 / 16.0).powi(39))).abs() < 1e-12);
+    // one of 16 nibbles, repeated 39 more times
+    assert!((nibble_run_probability(40) - 16f64.powi(-39)).abs() < 1e-60);
+    assert_eq!(nibble_run_probability(41), 0.0);
+    // longer runs are rarer
+    assert!(
+        (2..40).all(|length| nibble_run_probability(length + 1) < nibble_run_probability(length))
+    );
+}
+
+#[test]
+fn test_rarity_of_addresses() {
+    let rarity = Rarity::of(&hex!("0000000000c0ffee254729296a45a3885639ac7e"));
+    assert_eq!(rarity.leading_zero_nibbles, 10);
+    assert_eq!(rarity.total_zero_bytes, 5);
+    assert_eq!(rarity.longest_run, (0, 10));
+    // the leading zeros are the rarest metric
+    assert_eq!(rarity.combined_probability(), 16f64.powi(-10));
+
+    // the first of equally long runs
+    let rarity = Rarity::of(&hex!("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+    assert_eq!(
+        (
+            rarity.leading_zero_nibbles,
+            rarity.total_zero_bytes,
+            rarity.longest_run
+        ),
+        (0, 0, (0xa, 2))
+    );
+    assert_eq!(Rarity::of(&[0x11; 20]).longest_run, (1, 40));
+
+    let report = Rarity::of(&hex!("0000000000c0ffee254729296a45a3885639ac7e")).report();
+    assert_eq!(
+        report,
+        "leading zeros: 10 nibbles (5 bytes), rarer than 1 in 1.10e12 random addresses\n\
+         total zero bytes: 5, rarer than 1 in 74470198 random addresses\n\
+         longest nibble run: 10 x 0, rarer than 1 in 2.36e9 random addresses\n\
+         combined: rarer than 1 in 1.10e12 random addresses (a conservative bound)"
+    );
+    assert_eq!(format_odds(1.0), "as common as any random address");
+    assert_eq!(format_odds(0.5), "rarer than 1 in 2.00 random addresses");
+}
+
+/// Frequency of addresses meeting `metric` among `samples` random ones.
+fn simulate(samples: u32, metric: impl Fn(&Rarity) -> bool) -> f64 {
+    let mut rng = StdRng::seed_from_u64(510);
+    let mut address = [0u8; 20];
+    let hits = (0..samples)
+        .filter(|_| {
+            rng.fill_bytes(&mut address);
+            metric(&Rarity::of(&address))
+        })
+        .count();
+    hits as f64 / samples as f64
+}
+
+#[test]
+#[ignore = "slow, run with --ignored"]
+fn test_rarity_probabilities_match_simulation() {
+    const SAMPLES: u32 = 2_000_000;
+    let close = |simulated: f64, exact: f64| {
+        // five standard deviations of the simulated frequency
+        let tolerance = 5.0 * (exact * (1.0 - exact) / SAMPLES as f64).sqrt();
+        assert!(
+            (simulated - exact).abs() < tolerance,
+            "simulated {} against {}",
+            simulated,
+            exact
+        );
+    };
+
+    for nibbles in 1..=3 {
+        close(
+            simulate(SAMPLES, |rarity| rarity.leading_zero_nibbles >= nibbles),
+            leading_zero_nibbles_probability(nibbles),
+        );
+    }
+    for threshold in 1..=2 {
+        close(
+            simulate(SAMPLES, |rarity| rarity.total_zero_bytes >= threshold),
+            total_zero_bytes_probability(threshold),
+        );
+    }
+    for length in 2..=5 {
+        close(
+            simulate(SAMPLES, |rarity| rarity.longest_run.1 >= length),
+            nibble_run_probability(length),
+        );
+    }
+}
+
+#[test]
+fn test_benchmark_report() {
+    let benchmark = Benchmark {
+        adapter: "Test GPU".to_string(),
+        work_size: 1 << 20,
+        attempts: 50 << 20,
+        elapsed: Duration::from_secs(5),
+    };
+    assert!((benchmark.rate() - 10_485_760.0).abs() < 1e-6);
+    assert_eq!(
+        benchmark.report(4),
+        "52428800 attempts in 5.0s on Test GPU, 1048576 nonces per cycle\n\
+         hash rate: 10.49 MH/s\n\
+         expected time to 4 leading zero bytes: 6m49s (4.295e9 attempts)"
+    );
+
+    let idle = Benchmark {
+        attempts: 0,
+        ..benchmark
+    };
+    assert!(idle.report(4).ends_with("4 leading zero bytes: unknown (4.295e9 attempts)"));
+}
@@ -0,0 +1,209 @@
+use createxcrunch::{
+    checkpoint::{Checkpoint, CheckpointSchedule, PendingCheckpoint},
+    ranges::{NonceRanges, SearchCursor},
+    scan::ScanOrder,
+    supervise::{keepalive_tick, Notification, Notifier, Watchdog},
+};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct MockNotifier {
+    sent: Vec<Notification>,
+}
+
+impl Notifier for MockNotifier {
+    fn notify(&mut self, notification: Notification) -> Result<(), String> {
+        self.sent.push(notification);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_keepalive_stops_without_progress() {
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut watchdog = Watchdog::new(Duration::from_secs(10), start);
+    let mut notifier = MockNotifier::default();
+
+    // progress every second keeps the watchdog fed
+    for (secs, attempts) in [(1, 100), (2, 200), (3, 300)] {
+        assert_eq!(
+            keepalive_tick(&mut watchdog, &mut notifier, attempts, at(secs)),
+            Ok(true)
+        );
+    }
+
+    // no progress, but still within the stall timeout
+    assert_eq!(
+        keepalive_tick(&mut watchdog, &mut notifier, 300, at(12)),
+        Ok(true)
+    );
+
+    // stalled: keepalives stop so the service manager restarts the miner
+    assert_eq!(
+        keepalive_tick(&mut watchdog, &mut notifier, 300, at(13)),
+        Ok(false)
+    );
+    assert_eq!(
+        keepalive_tick(&mut watchdog, &mut notifier, 300, at(30)),
+        Ok(false)
+    );
+
+    // progress resumes keepalives
+    assert_eq!(
+        keepalive_tick(&mut watchdog, &mut notifier, 400, at(31)),
+        Ok(true)
+    );
+
+    assert_eq!(notifier.sent, vec![Notification::Watchdog; 5]);
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-checkpoint-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("output.txt.checkpoint");
+
+    assert_eq!(Checkpoint::load(&path), Ok(None));
+
+    let checkpoint = Checkpoint {
+        next_index: u64::MAX - 1,
+        scan_order: ScanOrder::Splitmix,
+        scan_seed: 0xdead_beef,
+        attempts: 1 << 40,
+        found: 12,
+        fingerprint: Some("create3 factory(0xba5e) random leading-zeros(4)".to_string()),
+        campaign: Some("launch".to_string()),
+    };
+    checkpoint.save(&path).unwrap();
+    assert_eq!(Checkpoint::load(&path), Ok(Some(checkpoint)));
+
+    // checkpoints of older versions counted neither attempts nor results
+    std::fs::write(&path, "{\"next_index\": 5, \"scan_order\": \"linear\", \"scan_seed\": 0}").unwrap();
+    let legacy = Checkpoint::load(&path).unwrap().unwrap();
+    assert_eq!((legacy.next_index, legacy.attempts, legacy.found), (5, 0, 0));
+
+    std::fs::write(&path, "{\"next_index\": \"soon\"}").unwrap();
+    assert!(Checkpoint::load(&path).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_path() {
+    assert_eq!(Checkpoint::path("output.txt"), "output.txt.checkpoint");
+}
+
+fn checkpoint_at(next_index: u64) -> Checkpoint {
+    Checkpoint {
+        next_index,
+        scan_order: ScanOrder::Linear,
+        scan_seed: 7,
+        attempts: next_index,
+        found: 0,
+        fingerprint: None,
+        campaign: None,
+    }
+}
+
+#[test]
+fn test_checkpoint_every_attempts_bounds_repeated_work() {
+    let every = 1_000;
+    let skip = NonceRanges::new([300..2_500, 4_000..4_100]);
+    let start = Instant::now();
+    let mut schedule = CheckpointSchedule::new(Duration::from_secs(3600), Some(every), start);
+    let mut cursor = SearchCursor::new(0);
+    let mut saved = (0, 0);
+
+    // cancel the run after every batch, the restart resumes from the last saved checkpoint
+    while cursor.attempts < 10_000 {
+        let batch = cursor
+            .next_batch(&skip, schedule.batch_limit(cursor.attempts, 768), None)
+            .unwrap();
+        cursor.advance(batch);
+
+        let (saved_attempts, saved_index) = saved;
+        assert!(cursor.attempts - saved_attempts <= every);
+        let mut resumed = SearchCursor::new(saved_index);
+        while resumed.nonce < cursor.nonce {
+            let batch = resumed
+                .next_batch(&skip, u64::MAX, Some(cursor.nonce))
+                .unwrap();
+            resumed.advance(batch);
+        }
+        assert!(resumed.attempts <= every);
+
+        if schedule.is_due(cursor.attempts, start) {
+            assert_eq!(cursor.attempts % every, 0);
+            schedule.taken(cursor.attempts, start);
+            saved = (cursor.attempts, cursor.nonce);
+        }
+    }
+    assert_eq!(saved.0, 10_000);
+}
+
+#[test]
+fn test_checkpoint_interval_still_applies() {
+    let start = Instant::now();
+    let mut schedule = CheckpointSchedule::new(Duration::from_secs(60), Some(1_000), start);
+    assert!(!schedule.is_due(10, start + Duration::from_secs(59)));
+    assert!(schedule.is_due(10, start + Duration::from_secs(60)));
+    schedule.taken(10, start + Duration::from_secs(60));
+    assert_eq!(schedule.batch_limit(10, 4_096), 1_000);
+    assert!(schedule.is_due(1_010, start + Duration::from_secs(61)));
+
+    let schedule = CheckpointSchedule::new(Duration::from_secs(60), None, start);
+    assert_eq!(schedule.batch_limit(10, 4_096), 4_096);
+    assert!(!schedule.is_due(u64::MAX, start));
+}
+
+#[test]
+fn test_checkpoint_every_cycles() {
+    let start = Instant::now();
+    let mut schedule =
+        CheckpointSchedule::new(Duration::from_secs(3600), None, start).every_cycles(Some(3));
+    for _ in 0..2 {
+        schedule.cycle_done();
+        assert!(!schedule.is_due(0, start));
+    }
+    schedule.cycle_done();
+    assert!(schedule.is_due(0, start));
+    schedule.taken(0, start);
+    assert!(!schedule.is_due(0, start));
+
+    // without a cycle count only the interval applies
+    let mut schedule = CheckpointSchedule::new(Duration::from_secs(3600), None, start);
+    schedule.cycle_done();
+    assert!(!schedule.is_due(0, start));
+}
+
+#[test]
+fn test_pending_checkpoint_waits_for_results() {
+    let pending = PendingCheckpoint::default();
+    assert_eq!(pending.take_ready(u64::MAX), None);
+
+    pending.offer(checkpoint_at(100), 3);
+    assert_eq!(pending.take_ready(2), None);
+
+    // a newer checkpoint replaces the one not saved yet
+    pending.offer(checkpoint_at(200), 5);
+    assert_eq!(pending.take_ready(4), None);
+    assert_eq!(pending.take_ready(5), Some(checkpoint_at(200)));
+    assert_eq!(pending.take_ready(5), None);
+}
+
+#[test]
+fn test_watchdog_rests_while_paused() {
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut watchdog = Watchdog::new(Duration::from_secs(10), start);
+    assert!(watchdog.check(100, at(1)));
+
+    // a scheduled pause is not a stall
+    for secs in [5, 10, 15, 20] {
+        watchdog.rest(at(secs));
+        assert!(watchdog.check(100, at(secs)));
+    }
+    assert!(watchdog.check(100, at(29)));
+    assert!(!watchdog.check(100, at(30)));
+}
@@ -0,0 +1,182 @@
+use alloy_primitives::{hex, Address};
+use createxcrunch::{
+    compute_create2_address, compute_create3_address, create2_address, create3_address,
+    SaltFormat, SaltVariant, CREATEX_ADDRESS,
+};
+use std::process::Command;
+
+// verify computes addresses for the canonical CreateX deployment without --factory
+const FACTORY: [u8; 20] = CREATEX_ADDRESS;
+const CALLER: [u8; 20] = hex!("88c6C46EBf353A52Bdbab708c23D0c81dAA8134A");
+
+fn chain(chain_id: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&chain_id.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn test_salt_variants_are_read_off_the_salt() {
+    let salt_variants = [
+        SaltVariant::CrosschainSender {
+            chain_id: chain(10),
+            calling_address: CALLER,
+        },
+        SaltVariant::Sender {
+            calling_address: CALLER,
+        },
+        SaltVariant::Crosschain {
+            chain_id: chain(10),
+        },
+        SaltVariant::Random,
+    ];
+    for salt_variant in salt_variants {
+        let salt = salt_variant.salt(42);
+        assert_eq!(
+            SaltVariant::of_salt(&salt, Some(&CALLER), Some(10)),
+            Ok(salt_variant)
+        );
+    }
+
+    // salts of another caller are pseudo-random to CreateX
+    let salt = SaltVariant::Sender {
+        calling_address: [0xab; 20],
+    }
+    .salt(1);
+    assert_eq!(
+        SaltVariant::of_salt(&salt, Some(&CALLER), None),
+        Ok(SaltVariant::Random)
+    );
+}
+
+#[test]
+fn test_unusable_salts_error() {
+    let crosschain = SaltVariant::Crosschain { chain_id: chain(1) }.salt(1);
+    assert_eq!(
+        SaltVariant::of_salt(&crosschain, None, None),
+        Err("the salt enables cross-chain redeploy protection, pass --chain-id".to_string())
+    );
+    let mut flagged = [0u8; 32];
+    flagged[20] = 0x02;
+    assert_eq!(
+        SaltVariant::of_salt(&flagged, None, None),
+        Err(
+            "CreateX reverts with InvalidSalt: byte 20 of the salt is 0x02, neither 0x00 nor 0x01"
+                .to_string()
+        )
+    );
+    let sender = SaltVariant::Sender {
+        calling_address: CALLER,
+    }
+    .salt(1);
+    assert!(SaltVariant::of_salt(&sender, None, None)
+        .unwrap_err()
+        .contains("--caller"));
+}
+
+#[test]
+fn test_compute_addresses_guard_the_salt() {
+    let salt_variant = SaltVariant::Sender {
+        calling_address: CALLER,
+    };
+    let salt = salt_variant.salt(7);
+    let guarded_salt = salt_variant.guard(&salt);
+    assert_eq!(
+        compute_create2_address(&FACTORY, &salt_variant, &salt, &[0xab; 32]),
+        (
+            guarded_salt,
+            create2_address(&FACTORY, &guarded_salt, &[0xab; 32])
+        )
+    );
+    assert_eq!(
+        compute_create3_address(&FACTORY, &salt_variant, &salt),
+        (guarded_salt, create3_address(&FACTORY, &guarded_salt))
+    );
+}
+
+fn verify(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_createxcrunch"))
+        .arg("verify")
+        .args(args)
+        .env_clear()
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_verify_prints_the_guarded_salt_and_address() {
+    let salt_variant = SaltVariant::CrosschainSender {
+        chain_id: chain(10),
+        calling_address: CALLER,
+    };
+    let salt = salt_variant.salt(3);
+    let (guarded_salt, address) = compute_create3_address(&FACTORY, &salt_variant, &salt);
+    let address = Address::from(address).to_checksum(None);
+    let salt = format!("0x{}", hex::encode(salt));
+    let caller = format!("0x{}", hex::encode(CALLER));
+
+    let output = verify(&[
+        "--salt",
+        &salt,
+        "--caller",
+        &caller,
+        "--chain-id",
+        "10",
+        "--expect",
+        &address,
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains(&format!("Guarded salt: 0x{}", hex::encode(guarded_salt))));
+    assert!(stdout.contains(&format!("Address: {}", address)));
+
+    let other = "0x0000000000000000000000000000000000000001";
+    let output = verify(&[
+        "--salt", &salt, "--caller", &caller, "-x", "1", "--expect", other,
+    ]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "Error: the salt deploys to another address than expected\n- {} (expected)\n+ {} (computed)\n",
+            other,
+            Address::from(
+                compute_create3_address(
+                    &FACTORY,
+                    &SaltVariant::CrosschainSender {
+                        chain_id: chain(1),
+                        calling_address: CALLER
+                    },
+                    &salt_variant.salt(3)
+                )
+                .1
+            )
+            .to_checksum(None)
+        )
+    );
+}
+
+#[test]
+fn test_verify_reads_every_salt_rendering() {
+    let salt = SaltVariant::Random.salt(0x2a2a);
+    let (_, address) = compute_create3_address(&FACTORY, &SaltVariant::Random, &salt);
+    let address = Address::from(address).to_checksum(None);
+
+    for format in [SaltFormat::Bytes32, SaltFormat::Base64, SaltFormat::Uint256Dec] {
+        let rendered = format.render(&salt);
+        let output = verify(&["--salt", &rendered, "--expect", &address]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{} {}: {}",
+            format,
+            rendered,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(stdout.contains(&format!("Address: {}", address)), "{}", stdout);
+    }
+
+    let output = verify(&["--salt", &hex::encode(salt), "--expect", &address]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hex salts need the 0x prefix"));
+}
@@ -0,0 +1,86 @@
+use createxcrunch::{
+    probability::expected_attempts,
+    warnings::{Warning, WarningCode, Warnings},
+    RewardVariant, Seed,
+};
+
+#[test]
+fn test_warning_codes_are_stable() {
+    let codes: Vec<(&str, &str)> = WarningCode::ALL
+        .iter()
+        .map(|code| (code.code(), code.name()))
+        .collect();
+
+    assert_eq!(
+        codes,
+        vec![
+            ("W001", "low-entropy"),
+            ("W002", "extreme-difficulty"),
+            ("W003", "software-adapter"),
+            ("W004", "dropped-matches"),
+            ("W005", "low-disk-space"),
+            ("W006", "unknown-factory"),
+        ]
+    );
+
+    assert_eq!("W003".parse(), Ok(WarningCode::SoftwareAdapter));
+    assert_eq!("w003".parse(), Ok(WarningCode::SoftwareAdapter));
+    assert_eq!("dropped-matches".parse(), Ok(WarningCode::DroppedMatches));
+    assert!("W999".parse::<WarningCode>().is_err());
+
+    assert!(Warning::DroppedMatches { count: 3 }
+        .to_string()
+        .starts_with("warning[W004] dropped-matches: "));
+}
+
+#[test]
+fn test_deny_and_allow_precedence() {
+    // warnings alone never fail a run
+    let mut warnings = Warnings::new(vec![], false);
+    assert!(warnings.emit(Warning::LowEntropy));
+    assert!(warnings.check_denied().is_ok());
+    assert!(warnings.report().unwrap().contains("W001"));
+
+    // --deny-warnings turns any recorded warning into an error
+    let mut warnings = Warnings::new(vec![], true);
+    assert!(warnings.check_denied().is_ok());
+    warnings.emit(Warning::LowEntropy);
+    let err = warnings.check_denied().unwrap_err();
+    assert!(err.contains("W001"));
+
+    // --allow wins over --deny-warnings
+    let mut warnings = Warnings::new(vec![WarningCode::LowEntropy], true);
+    assert!(!warnings.emit(Warning::LowEntropy));
+    assert!(warnings.check_denied().is_ok());
+    assert!(warnings.report().is_none());
+
+    warnings.emit(Warning::SoftwareAdapter {
+        name: "llvmpipe".to_string(),
+    });
+    let err = warnings.check_denied().unwrap_err();
+    assert!(err.contains("W003") && !err.contains("W001"));
+}
+
+#[test]
+fn test_low_entropy() {
+    for small in ["1", "42", "0xffffffffffffffff"] {
+        let seed: Seed = small.parse().unwrap();
+        assert_eq!(Warning::for_seed(&seed), Some(Warning::LowEntropy), "{}", small);
+    }
+    let seed: Seed = "0x10000000000000000".parse().unwrap();
+    assert_eq!(Warning::for_seed(&seed), None);
+    assert_eq!(Warning::for_seed(&Seed::random()), None);
+}
+
+#[test]
+fn test_extreme_difficulty() {
+    let easy = RewardVariant::LeadingZeros { zeros_threshold: 3 };
+    assert_eq!(expected_attempts(&easy), 16_777_216.0);
+    assert!(Warning::for_reward(&easy).is_none());
+
+    let hard = RewardVariant::SymmetricEnds { nibbles: 14 };
+    assert!(matches!(
+        Warning::for_reward(&hard),
+        Some(Warning::ExtremeDifficulty { .. })
+    ));
+}
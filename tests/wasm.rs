@@ -0,0 +1,74 @@
+#![cfg(feature = "wasm")]
+
+use createxcrunch::{
+    probability::Difficulty,
+    verify::Derivation,
+    wasm::{derive_address, estimate_difficulty, verify_record},
+};
+use serde::Deserialize;
+
+// the exports are plain functions of JSON strings, so they are tested natively against the
+// golden vectors of the core
+const VECTORS: &str = include_str!("fixtures/derive/vectors.json");
+
+#[derive(Deserialize)]
+struct Vector {
+    deployment: serde_json::Value,
+    salt: String,
+    derivation: Derivation,
+}
+
+#[test]
+fn test_derive_address() {
+    let vectors: Vec<Vector> = serde_json::from_str(VECTORS).unwrap();
+    for vector in vectors {
+        let derivation = derive_address(&vector.deployment.to_string(), &vector.salt).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Derivation>(&derivation).unwrap(),
+            vector.derivation
+        );
+    }
+    assert!(derive_address("{\"factory\": 1}", "0x01")
+        .unwrap_err()
+        .starts_with("invalid deployment: "));
+}
+
+#[test]
+fn test_estimate_difficulty() {
+    let difficulty: Difficulty =
+        serde_json::from_str(&estimate_difficulty("\"leading-zeros(4)\"").unwrap()).unwrap();
+    assert_eq!(difficulty.reward, "leading-zeros(4)");
+    assert_eq!(difficulty.expected_attempts, 2f64.powi(32));
+    assert!(!difficulty.hopeless);
+    assert_eq!(
+        estimate_difficulty("\"leading-zeros(0)\""),
+        Err("threshold must be greater than 0".to_string())
+    );
+}
+
+#[test]
+fn test_verify_record() {
+    let vectors: Vec<Vector> = serde_json::from_str(VECTORS).unwrap();
+    let vector = &vectors[0];
+    let record = serde_json::json!({
+        "salt": vector.salt,
+        "address": vector.derivation.address,
+        "factory": vector.derivation.factory,
+        "create_variant": vector.derivation.create_variant,
+        "pattern": "pattern(cb...)",
+        "found_at_unix": 1700000000,
+        "attempt": 1,
+    });
+    let deployment = vector.deployment.to_string();
+    let derivation = verify_record(&record.to_string(), &deployment).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Derivation>(&derivation).unwrap(),
+        vector.derivation
+    );
+
+    let mut record = record;
+    record["pattern"] = "leading-zeros(1)".into();
+    assert!(verify_record(&record.to_string(), &deployment)
+        .unwrap_err()
+        .ends_with("does not satisfy leading-zeros(1)"));
+}
@@ -0,0 +1,271 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::Path,
+};
+
+use createxcrunch::writer::{
+    bucket_path, is_bucket_of, is_disk_full, is_duplicate_anomaly, DuplicatePolicy,
+    RecentAddresses, ResultWriter, SplitBy, SplitOutput,
+};
+
+/// Output that accepts `room` lines, then fails like a full disk.
+struct FullDisk {
+    written: Vec<u8>,
+    room: usize,
+}
+
+impl FullDisk {
+    fn lines(&self) -> Vec<&str> {
+        std::str::from_utf8(&self.written)
+            .unwrap()
+            .lines()
+            .collect()
+    }
+}
+
+impl Write for FullDisk {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written.iter().filter(|&&b| b == b'\n').count() == self.room {
+            return Err(io::Error::from(ErrorKind::StorageFull));
+        }
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_disk_full_engages_emergency_buffer() {
+    let mut stderr = Vec::new();
+    let mut out = FullDisk {
+        written: vec![],
+        room: 1,
+    };
+    let mut writer = ResultWriter::new(&mut out, &mut stderr, 2);
+
+    writer.write("0x01 -> 0xaa");
+    assert!(!writer.is_degraded());
+    for line in ["0x02 -> 0xbb", "0x03 -> 0xcc", "0x04 -> 0xdd"] {
+        writer.write(line);
+    }
+    assert!(writer.is_degraded());
+    assert!(writer.finish().is_err());
+
+    assert_eq!(out.lines(), vec!["0x01 -> 0xaa"]);
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stderr.contains("OUTPUT DISK FULL"));
+    assert!(stderr.contains("3 results could not be written"));
+    // the buffer keeps the latest results and every result was echoed when it arrived
+    let dump = stderr.split("could not be written").nth(1).unwrap();
+    assert!(dump.contains("0x03 -> 0xcc") && dump.contains("0x04 -> 0xdd"));
+    assert!(!dump.contains("0x02 -> 0xbb"));
+    assert!(stderr.contains("unsaved result: 0x02 -> 0xbb"));
+}
+
+#[test]
+fn test_other_errors_do_not_degrade() {
+    assert!(is_disk_full(&io::Error::from_raw_os_error(28)));
+    assert!(!is_disk_full(&io::Error::from(ErrorKind::PermissionDenied)));
+
+    let mut stderr = Vec::new();
+    let mut writer = ResultWriter::new(io::sink(), &mut stderr, 2);
+    writer.write("0x01 -> 0xaa");
+    assert!(writer.finish().is_ok());
+    assert!(stderr.is_empty());
+}
+
+/// An address with `zeros` leading zero bytes followed by `0x0f`.
+fn address(zeros: usize) -> [u8; 20] {
+    let mut address = [0xff; 20];
+    address[..zeros].fill(0);
+    address[zeros] = 0x0f;
+    address
+}
+
+#[test]
+fn test_split_routing() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-split-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("results.txt");
+    let bucket = |name: &str| fs::read_to_string(bucket_path(&output, name)).unwrap();
+
+    let mut main = Vec::new();
+    let split = SplitOutput::new(SplitBy::LeadingZeros, &output, 16, true);
+    let mut writer = ResultWriter::new(&mut main, io::sink(), 2).splitting(split);
+    writer.write_result("0x01 -> lz2", &address(2));
+    writer.write_result("0x02 -> lz1", &address(1));
+    writer.write_result("0x03 -> lz2", &address(2));
+    assert!(writer.finish().is_ok());
+
+    // the main output keeps everything, buckets only their own results
+    assert_eq!(String::from_utf8(main).unwrap().lines().count(), 3);
+    let lz2 = bucket("lz2");
+    assert!(lz2.starts_with("# Bucket lz2 of "));
+    assert!(lz2.contains("2 leading zero bytes"));
+    let results: Vec<&str> = lz2.lines().filter(|line| line.starts_with("0x")).collect();
+    assert_eq!(results, vec!["0x01 -> lz2", "0x03 -> lz2"]);
+    assert!(bucket("lz1").ends_with("0x02 -> lz1\n"));
+    // buckets are only created once a result lands in them
+    assert!(!bucket_path(&output, "lz0").exists());
+
+    // score tiers count leading zero nibbles
+    assert_eq!(SplitBy::ScoreTier.bucket(&address(2)), "tier5");
+    assert_eq!(SplitBy::LeadingZeros.bucket(&address(2)), "lz2");
+    assert_eq!("score-tier".parse::<SplitBy>(), Ok(SplitBy::ScoreTier));
+    assert!("nibbles".parse::<SplitBy>().is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_recycles_handles() {
+    let dir = std::env::temp_dir().join(format!("createxcrunch-lru-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("results.txt");
+    // a file of an earlier run is replaced, not appended to
+    fs::write(bucket_path(&output, "lz3"), "0xstale -> lz3\n").unwrap();
+
+    let mut split = SplitOutput::new(SplitBy::LeadingZeros, &output, 2, false);
+    for zeros in [1, 2, 1, 3, 2, 1] {
+        split
+            .write(&format!("0x{} -> lz{}", zeros, zeros), &address(zeros))
+            .unwrap();
+        assert!(split.open_buckets().len() <= 2);
+    }
+    // the least recently used file was closed each time a third bucket was needed
+    assert_eq!(split.open_buckets(), vec!["lz2", "lz1"]);
+    assert_eq!(split.created_buckets(), vec!["lz1", "lz2", "lz3"]);
+
+    // reopened buckets are appended to, without a header when headers are off
+    let lz1 = fs::read_to_string(bucket_path(&output, "lz1")).unwrap();
+    assert_eq!(lz1, "0x1 -> lz1\n".repeat(3));
+    let lz3 = fs::read_to_string(bucket_path(&output, "lz3")).unwrap();
+    assert_eq!(lz3, "0x3 -> lz3\n");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_bucket_paths() {
+    let output = Path::new("runs/results.txt");
+    assert_eq!(
+        bucket_path(output, "lz4"),
+        Path::new("runs/results.lz4.txt")
+    );
+    assert_eq!(
+        bucket_path(Path::new("results"), "tier9"),
+        Path::new("results.tier9")
+    );
+
+    assert!(is_bucket_of(output, "results.lz4.txt"));
+    assert!(is_bucket_of(output, "results.tier12.txt"));
+    assert!(!is_bucket_of(output, "results.txt"));
+    assert!(!is_bucket_of(output, "results.lz.txt"));
+    assert!(!is_bucket_of(output, "results.checkpoint"));
+    assert!(!is_bucket_of(output, "other.lz4.txt"));
+    assert!(is_bucket_of(Path::new("results"), "results.lz4"));
+}
+
+/// A stream of `results` distinct addresses, every `every`th one repeating an earlier one.
+fn stream(results: u8, every: u8) -> Vec<[u8; 20]> {
+    (1..=results)
+        .map(|i| match i % every == 0 {
+            true => [i - 1; 20],
+            false => [i; 20],
+        })
+        .collect()
+}
+
+fn write_stream(policy: DuplicatePolicy, addresses: &[[u8; 20]]) -> (Vec<String>, String, u64) {
+    let (mut output, mut stderr) = (Vec::new(), Vec::new());
+    let mut writer = ResultWriter::new(&mut output, &mut stderr, 4)
+        .detecting_duplicates(RecentAddresses::new(16), policy);
+    for (i, address) in addresses.iter().enumerate() {
+        writer.write_result(&format!("0x{:02x} -> {}", i, address[0]), address);
+    }
+    let duplicates = writer.duplicate_addresses();
+    assert!(writer.finish().is_ok());
+    let lines = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    (lines, String::from_utf8(stderr).unwrap(), duplicates)
+}
+
+#[test]
+fn test_duplicate_address_policies() {
+    // results 4 and 8 repeat the addresses of results 3 and 7
+    let addresses = stream(8, 4);
+
+    let (lines, stderr, duplicates) = write_stream(DuplicatePolicy::KeepAll, &addresses);
+    assert_eq!((lines.len(), duplicates), (8, 2));
+    assert!(stderr.is_empty());
+
+    let (lines, stderr, duplicates) = write_stream(DuplicatePolicy::FirstOnly, &addresses);
+    assert_eq!(duplicates, 2);
+    assert_eq!(
+        lines,
+        vec![
+            "0x00 -> 1",
+            "0x01 -> 2",
+            "0x02 -> 3",
+            "0x04 -> 5",
+            "0x05 -> 6",
+            "0x06 -> 7"
+        ]
+    );
+    assert!(stderr.is_empty());
+
+    let (lines, stderr, duplicates) = write_stream(DuplicatePolicy::Warn, &addresses);
+    assert_eq!((lines.len(), duplicates), (8, 2));
+    assert_eq!(stderr.lines().count(), 2);
+    assert!(stderr.starts_with(&format!(
+        "warning: 0x{} was already found with another salt",
+        "03".repeat(20)
+    )));
+
+    assert_eq!("first-only".parse(), Ok(DuplicatePolicy::FirstOnly));
+    assert_eq!(DuplicatePolicy::default().to_string(), "warn");
+    assert!("skip".parse::<DuplicatePolicy>().is_err());
+}
+
+#[test]
+fn test_recent_addresses_forget_the_least_recent() {
+    let mut recent = RecentAddresses::new(2);
+    assert!(recent.is_empty());
+    assert!(!recent.insert(&[1; 20]));
+    assert!(!recent.insert(&[2; 20]));
+    // seeing 1 again makes 2 the least recent one
+    assert!(recent.insert(&[1; 20]));
+    assert!(!recent.insert(&[3; 20]));
+    assert_eq!(recent.len(), 2);
+    assert!(recent.insert(&[1; 20]));
+    assert!(!recent.insert(&[2; 20]));
+}
+
+#[test]
+fn test_duplicate_anomaly_rate() {
+    // random salts essentially never collide, a few percent of repeats is a bug
+    assert!(!is_duplicate_anomaly(1000, 0));
+    assert!(!is_duplicate_anomaly(1000, 5));
+    assert!(is_duplicate_anomaly(1000, 20));
+    // too few duplicates to tell, however high the rate
+    assert!(!is_duplicate_anomaly(4, 2));
+
+    let (mut output, mut stderr) = (Vec::new(), Vec::new());
+    let mut writer = ResultWriter::new(&mut output, &mut stderr, 4)
+        .detecting_duplicates(RecentAddresses::new(256), DuplicatePolicy::KeepAll);
+    // 200 results, 1 in 40 repeating the previous address
+    for (i, address) in (0..200u8).map(|i| (i, [i - (i % 40 == 39) as u8; 20])) {
+        writer.write_result(&format!("0x{:02x}", i), &address);
+        assert_eq!(writer.duplicate_anomaly(), i >= 119);
+    }
+    assert_eq!(writer.duplicate_addresses(), 5);
+}